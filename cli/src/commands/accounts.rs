@@ -0,0 +1,328 @@
+//! Accounts command - list, inspect and edit accounts
+
+use anyhow::Result;
+use clap::Subcommand;
+use colored::Colorize;
+use comfy_table::{CellAlignment, ContentArrangement, Table};
+use treeline_core::domain::{SignPolicy, StatementCycle};
+use treeline_core::services::AccountInfo;
+
+use super::get_context;
+
+#[derive(Subcommand)]
+pub enum AccountsCommands {
+    /// List accounts with their latest balance
+    List {
+        /// Include archived accounts
+        #[arg(long, short = 'a')]
+        all: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show one account with its transaction and balance history
+    Show {
+        /// Account ID or name
+        account: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Rename a manual or imported account (use set-nickname for synced ones)
+    Rename {
+        /// Account ID or name
+        account: String,
+        /// New name
+        name: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Set the name shown for an account; omit the nickname to clear it
+    SetNickname {
+        /// Account ID or name
+        account: String,
+        /// New nickname
+        nickname: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Set the account type, e.g. checking, savings, credit_card, brokerage
+    SetType {
+        /// Account ID or name
+        account: String,
+        /// One of checking, savings, cash, credit_card, loan, mortgage, brokerage,
+        /// retirement, crypto, property, vehicle, other; credit cards, loans and
+        /// mortgages count as liabilities
+        account_type: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Set the days a credit card's statements close and are due
+    SetCycle {
+        /// Account ID or name
+        account: String,
+        /// Day of the month the statement closes (1-31; later days fall on a short month's last)
+        #[arg(long, required_unless_present = "clear")]
+        closing_day: Option<u32>,
+        /// Day of the month payment is due, the first one after closing
+        #[arg(long, required_unless_present = "clear")]
+        due_day: Option<u32>,
+        /// Remove the statement cycle
+        #[arg(long, conflicts_with_all = ["closing_day", "due_day"])]
+        clear: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Set whether an account's source reports charges as positive amounts
+    SetSign {
+        /// Account ID or name
+        account: String,
+        /// as_reported (outflows arrive negative) or inverted (flip every synced
+        /// and imported amount); imports apply it on top of a profile's flip-signs
+        policy: String,
+        /// Also flip the account's existing synced and imported transactions once
+        #[arg(long)]
+        normalize: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Hide a closed account from lists, keeping its history
+    Archive {
+        /// Account ID or name
+        account: String,
+        /// Restore an archived account
+        #[arg(long)]
+        undo: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn run(command: AccountsCommands) -> Result<()> {
+    match command {
+        AccountsCommands::List { all, json } => run_list(all, json),
+        AccountsCommands::Show { account, json } => run_show(&account, json),
+        AccountsCommands::Rename {
+            account,
+            name,
+            json,
+        } => {
+            let ctx = get_context()?;
+            let id = ctx.import_service.resolve_account(&account)?;
+            let info = ctx.account_service.rename(&id, &name)?;
+            print_updated(&info, "Renamed", json)
+        }
+        AccountsCommands::SetNickname {
+            account,
+            nickname,
+            json,
+        } => {
+            let ctx = get_context()?;
+            let id = ctx.import_service.resolve_account(&account)?;
+            let info = ctx.account_service.set_nickname(&id, nickname.as_deref())?;
+            let action = if info.nickname.is_some() {
+                "Nickname set for"
+            } else {
+                "Nickname cleared for"
+            };
+            print_updated(&info, action, json)
+        }
+        AccountsCommands::SetType {
+            account,
+            account_type,
+            json,
+        } => {
+            let ctx = get_context()?;
+            let id = ctx.import_service.resolve_account(&account)?;
+            let info = ctx.account_service.set_type(&id, &account_type)?;
+            print_updated(&info, "Type set for", json)
+        }
+        AccountsCommands::SetCycle {
+            account,
+            closing_day,
+            due_day,
+            clear: _,
+            json,
+        } => {
+            let cycle = match (closing_day, due_day) {
+                (Some(closing_day), Some(due_day)) => {
+                    Some(StatementCycle::new(closing_day, due_day).map_err(anyhow::Error::msg)?)
+                }
+                _ => None,
+            };
+            let ctx = get_context()?;
+            let id = ctx.import_service.resolve_account(&account)?;
+            let info = ctx.account_service.set_statement_cycle(&id, cycle)?;
+            let action = if info.statement_cycle.is_some() {
+                "Statement cycle set for"
+            } else {
+                "Statement cycle cleared for"
+            };
+            print_updated(&info, action, json)
+        }
+        AccountsCommands::SetSign {
+            account,
+            policy,
+            normalize,
+            json,
+        } => {
+            let policy: SignPolicy = policy.parse().map_err(anyhow::Error::msg)?;
+            let ctx = get_context()?;
+            let id = ctx.import_service.resolve_account(&account)?;
+            let change = ctx
+                .account_service
+                .set_sign_policy(&id, policy, normalize)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&change)?);
+                return Ok(());
+            }
+            println!(
+                "{} {} to {}",
+                "Sign policy set for".green(),
+                display_name(&change.account),
+                policy
+            );
+            if change.transactions_flipped > 0 {
+                println!(
+                    "Flipped {} existing transaction(s)",
+                    change.transactions_flipped
+                );
+            } else if !normalize {
+                println!(
+                    "{}",
+                    "Existing transactions are unchanged; pass --normalize to flip them too."
+                        .dimmed()
+                );
+            }
+            Ok(())
+        }
+        AccountsCommands::Archive {
+            account,
+            undo,
+            json,
+        } => {
+            let ctx = get_context()?;
+            let id = ctx.import_service.resolve_account(&account)?;
+            let info = ctx.account_service.set_archived(&id, !undo)?;
+            print_updated(&info, if undo { "Restored" } else { "Archived" }, json)
+        }
+    }
+}
+
+fn run_list(all: bool, json: bool) -> Result<()> {
+    let ctx = get_context()?;
+    let accounts = ctx.account_service.list(all)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&accounts)?);
+        return Ok(());
+    }
+
+    if accounts.is_empty() {
+        println!("No accounts. Run `tl sync` or `tl import` to add some.");
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec!["Name", "Type", "Institution", "Balance", "ID"]);
+    for account in &accounts {
+        let mut name = display_name(account);
+        if account.archived_at.is_some() {
+            name = format!("{} {}", name, "(archived)".dimmed());
+        }
+        table.add_row(vec![
+            name,
+            account.account_type.clone().unwrap_or_default(),
+            account.institution_name.clone().unwrap_or_default(),
+            account
+                .balance
+                .map(|b| format!("{:.2} {}", b, account.currency))
+                .unwrap_or_default(),
+            account.id.clone(),
+        ]);
+    }
+    if let Some(column) = table.column_mut(3) {
+        column.set_cell_alignment(CellAlignment::Right);
+    }
+    println!("{}", table);
+
+    Ok(())
+}
+
+fn run_show(account: &str, json: bool) -> Result<()> {
+    let ctx = get_context()?;
+    let id = ctx.import_service.resolve_account(account)?;
+    let details = ctx.account_service.details(&id)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&details)?);
+        return Ok(());
+    }
+
+    let info = &details.account;
+    let or_none = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+    println!("{}", display_name(info).bold());
+    println!("  ID:             {}", info.id);
+    println!("  Name:           {}", info.name);
+    println!("  Nickname:       {}", or_none(&info.nickname));
+    println!("  Type:           {}", or_none(&info.account_type));
+    println!("  Classification: {}", or_none(&info.classification));
+    println!("  Institution:    {}", or_none(&info.institution_name));
+    if let Some(cycle) = info.statement_cycle {
+        println!(
+            "  Statement:      closes on day {}, due on day {}",
+            cycle.closing_day, cycle.due_day
+        );
+    }
+    if info.sign_policy != SignPolicy::AsReported {
+        println!("  Signs:          {}", info.sign_policy);
+    }
+    println!(
+        "  Synced from:    {}",
+        info.integration.as_deref().unwrap_or("not synced")
+    );
+    println!(
+        "  Balance:        {}",
+        info.balance
+            .map(|b| format!("{:.2} {}", b, info.currency))
+            .unwrap_or_else(|| "unknown".to_string())
+    );
+    if let Some(at) = details.last_balance_at {
+        println!("  Balance as of:  {}", at.format("%Y-%m-%d %H:%M"));
+    }
+    println!("  Transactions:   {}", details.transaction_count);
+    if let (Some(first), Some(last)) = (
+        details.first_transaction_date,
+        details.last_transaction_date,
+    ) {
+        println!("  Date range:     {} to {}", first, last);
+    }
+    println!("  Snapshots:      {}", details.balance_snapshot_count);
+    if let Some(at) = info.archived_at {
+        println!("  Archived:       {}", at.format("%Y-%m-%d"));
+    }
+
+    Ok(())
+}
+
+fn print_updated(info: &AccountInfo, action: &str, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(info)?);
+    } else {
+        println!("{} {}", action.green(), display_name(info));
+    }
+    Ok(())
+}
+
+/// The nickname if set, otherwise the name
+fn display_name(info: &AccountInfo) -> String {
+    info.nickname.clone().unwrap_or_else(|| info.name.clone())
+}
@@ -0,0 +1,149 @@
+//! Archive command - move old years into their own database files
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::Local;
+use clap::Subcommand;
+use colored::Colorize;
+use comfy_table::{CellAlignment, ContentArrangement, Table};
+use treeline_core::services::ArchiveService;
+
+use super::get_context;
+
+#[derive(Subcommand)]
+pub enum ArchiveCommands {
+    /// Move a past year's transactions and balance snapshots into an archive file
+    Year {
+        /// Year to archive, e.g. 2019
+        year: i32,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List archived years
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Attach archives to SQL queries, read-only, as archive_<year>
+    Mount {
+        /// Years to mount (defaults to every archive)
+        years: Vec<i32>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Stop attaching archives to SQL queries
+    Unmount {
+        /// Years to unmount (defaults to every archive)
+        years: Vec<i32>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn run(command: ArchiveCommands) -> Result<()> {
+    match command {
+        ArchiveCommands::Year { year, json } => {
+            let ctx = get_context()?;
+            let service = ArchiveService::new(Arc::clone(&ctx.repository));
+            let archive = service.archive_year(year, Local::now().date_naive())?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&archive)?);
+                return Ok(());
+            }
+            println!(
+                "{} {} transactions and {} balance snapshots from {} to {}",
+                "Archived".green(),
+                archive.transaction_count,
+                archive.balance_snapshot_count,
+                archive.year,
+                archive.path.display()
+            );
+            println!(
+                "{}",
+                "Run `tl compact` to shrink the main database, and `tl archive mount` to query the archive."
+                    .dimmed()
+            );
+        }
+        ArchiveCommands::List { json } => {
+            let ctx = get_context()?;
+            let archives = ArchiveService::new(Arc::clone(&ctx.repository)).list()?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&archives)?);
+                return Ok(());
+            }
+            if archives.is_empty() {
+                println!("No archived years. Archive one with `tl archive year <year>`.");
+                return Ok(());
+            }
+
+            let mut table = Table::new();
+            table.set_content_arrangement(ContentArrangement::Dynamic);
+            table.set_header(vec![
+                "Year",
+                "Transactions",
+                "Snapshots",
+                "Size",
+                "Mounted",
+                "File",
+            ]);
+            for archive in &archives {
+                table.add_row(vec![
+                    archive.year.to_string(),
+                    archive.transaction_count.to_string(),
+                    archive.balance_snapshot_count.to_string(),
+                    archive
+                        .size_bytes
+                        .map(|size| format!("{} bytes", size))
+                        .unwrap_or_else(|| "missing".red().to_string()),
+                    if archive.mounted { "yes" } else { "no" }.to_string(),
+                    archive.path.display().to_string(),
+                ]);
+            }
+            for column in 1..4 {
+                if let Some(column) = table.column_mut(column) {
+                    column.set_cell_alignment(CellAlignment::Right);
+                }
+            }
+            println!("{}", table);
+        }
+        ArchiveCommands::Mount { years, json } => {
+            let ctx = get_context()?;
+            let archives = ArchiveService::new(Arc::clone(&ctx.repository)).mount(&years)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&archives)?);
+                return Ok(());
+            }
+            for archive in &archives {
+                println!("{} archive_{}", "Mounted".green(), archive.year);
+            }
+            if !archives.is_empty() {
+                println!(
+                    "{}",
+                    "Query all_transactions and all_balance_snapshots for full history.".dimmed()
+                );
+            }
+        }
+        ArchiveCommands::Unmount { years, json } => {
+            let ctx = get_context()?;
+            let archives = ArchiveService::new(Arc::clone(&ctx.repository)).unmount(&years)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&archives)?);
+                return Ok(());
+            }
+            for archive in &archives {
+                println!("{} archive_{}", "Unmounted".green(), archive.year);
+            }
+        }
+    }
+
+    Ok(())
+}
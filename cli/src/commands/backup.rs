@@ -7,6 +7,8 @@ use comfy_table::{ContentArrangement, Table};
 use treeline_core::LogEvent;
 
 use super::{get_context, get_logger, get_treeline_dir, log_event};
+use treeline_core::config::{BackupCompression, CompressionMethod, Config};
+use treeline_core::domain::{format_bytes, BackupMetadata};
 use treeline_core::services::BackupService;
 
 #[derive(Subcommand)]
@@ -16,6 +18,12 @@ pub enum BackupCommands {
         /// Maximum number of backups to keep
         #[arg(long, short = 'm')]
         max_backups: Option<usize>,
+        /// Compression: deflate, zstd or none (default from settings, else deflate)
+        #[arg(long, value_parser = parse_compression)]
+        compression: Option<CompressionMethod>,
+        /// Compression level: 1-9 for deflate, 1-22 for zstd
+        #[arg(long)]
+        level: Option<i64>,
         /// Output as JSON
         #[arg(long)]
         json: bool,
@@ -62,18 +70,77 @@ fn get_backup_service() -> BackupService {
     BackupService::new(treeline_dir, db_filename)
 }
 
+fn parse_compression(value: &str) -> Result<CompressionMethod, String> {
+    match value.to_lowercase().as_str() {
+        "deflate" => Ok(CompressionMethod::Deflate),
+        "zstd" => Ok(CompressionMethod::Zstd),
+        "none" => Ok(CompressionMethod::None),
+        _ => Err(format!(
+            "unknown compression '{}' (expected deflate, zstd or none)",
+            value
+        )),
+    }
+}
+
+/// e.g. "zstd 19, 28% of 40.0 MB", or "-" for legacy backups
+fn compression_display(backup: &BackupMetadata) -> String {
+    let Some(method) = &backup.compression else {
+        return "-".to_string();
+    };
+    let method = match backup.level {
+        Some(level) => format!("{} {}", method, level),
+        None => method.clone(),
+    };
+    match (backup.compression_ratio(), backup.uncompressed_bytes) {
+        (Some(ratio), Some(bytes)) => format!(
+            "{}, {:.0}% of {}",
+            method,
+            ratio * 100.0,
+            format_bytes(bytes)
+        ),
+        _ => method,
+    }
+}
+
+fn duration_display(backup: &BackupMetadata) -> String {
+    match backup.duration_ms {
+        Some(ms) => format!("{:.1}s", ms as f64 / 1000.0),
+        None => "-".to_string(),
+    }
+}
+
 pub fn run(command: BackupCommands) -> Result<()> {
     let logger = get_logger();
 
     match command {
-        BackupCommands::Create { max_backups, json } => {
+        BackupCommands::Create {
+            max_backups,
+            compression,
+            level,
+            json,
+        } => {
             log_event(
                 &logger,
                 LogEvent::new("backup_started").with_command("backup create"),
             );
             // Create needs full context to access the database
             let ctx = get_context()?;
-            match ctx.backup_service.create(max_backups) {
+            let result = if compression.is_some() || level.is_some() {
+                let configured = Config::load(&get_treeline_dir())
+                    .unwrap_or_default()
+                    .backup_compression;
+                // A new method doesn't inherit a level meant for the old one
+                let level = level.or(configured.level.filter(|_| compression.is_none()));
+                let compression = BackupCompression {
+                    method: compression.unwrap_or(configured.method),
+                    level,
+                };
+                ctx.backup_service
+                    .create_with_compression(max_backups, compression)
+            } else {
+                ctx.backup_service.create(max_backups)
+            };
+            match result {
                 Ok(result) => {
                     log_event(
                         &logger,
@@ -85,6 +152,8 @@ pub fn run(command: BackupCommands) -> Result<()> {
                         println!("{}", "Backup created".green());
                         println!("  Name: {}", result.name);
                         println!("  Size: {} bytes", result.size_bytes);
+                        println!("  Compression: {}", compression_display(&result));
+                        println!("  Time: {}", duration_display(&result));
                     }
                 }
                 Err(e) => {
@@ -115,13 +184,15 @@ pub fn run(command: BackupCommands) -> Result<()> {
 
             let mut table = Table::new();
             table.set_content_arrangement(ContentArrangement::Dynamic);
-            table.set_header(vec!["Name", "Created", "Size"]);
+            table.set_header(vec!["Name", "Created", "Size", "Compression", "Time"]);
 
             for backup in backups {
                 table.add_row(vec![
-                    backup.name,
+                    backup.name.clone(),
                     backup.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
                     format!("{} bytes", backup.size_bytes),
+                    compression_display(&backup),
+                    duration_display(&backup),
                 ]);
             }
 
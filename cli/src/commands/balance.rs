@@ -0,0 +1,167 @@
+//! Balance command - repair balance history from a known balance
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use clap::Subcommand;
+use colored::Colorize;
+use comfy_table::{CellAlignment, ContentArrangement, Table};
+use rust_decimal::Decimal;
+
+use super::get_context;
+
+#[derive(Subcommand)]
+pub enum BalanceCommands {
+    /// Recalculate daily balances from one known balance and the transactions
+    Backfill {
+        /// Account ID or name
+        #[arg(long, short = 'a')]
+        account: String,
+        /// Known end-of-day balance on --date
+        #[arg(long, allow_hyphen_values = true)]
+        known: String,
+        /// Date of the known balance (YYYY-MM-DD)
+        #[arg(long)]
+        date: String,
+        /// First day to write (YYYY-MM-DD, defaults to the first transaction)
+        #[arg(long)]
+        from: Option<String>,
+        /// Last day to write (YYYY-MM-DD, defaults to --date)
+        #[arg(long)]
+        to: Option<String>,
+        /// Show the balances without writing them
+        #[arg(long)]
+        dry_run: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn run(command: BalanceCommands) -> Result<()> {
+    match command {
+        BalanceCommands::Backfill {
+            account,
+            known,
+            date,
+            from,
+            to,
+            dry_run,
+            json,
+        } => run_backfill(
+            &account,
+            &known,
+            &date,
+            from.as_deref(),
+            to.as_deref(),
+            dry_run,
+            json,
+        ),
+    }
+}
+
+fn run_backfill(
+    account: &str,
+    known: &str,
+    date: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+    dry_run: bool,
+    json: bool,
+) -> Result<()> {
+    let known_balance: Decimal = known
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid balance '{}'", known))?;
+    let known_date = parse_date(date)?;
+    let start_date = from.map(parse_date).transpose()?;
+    let end_date = to.map(parse_date).transpose()?;
+
+    let ctx = get_context()?;
+    let account_id = ctx.import_service.resolve_account(account)?;
+
+    if !dry_run {
+        let result = ctx.balance_service.backfill_execute(
+            &account_id,
+            known_balance,
+            known_date,
+            start_date,
+            end_date,
+        )?;
+        if json {
+            println!("{}", serde_json::to_string_pretty(&result)?);
+        } else {
+            println!("{}", "Balance history updated".green());
+            println!("  Created: {}", result.snapshots_created);
+            println!("  Updated: {}", result.snapshots_updated);
+            println!("  Skipped: {}", result.snapshots_skipped);
+        }
+        return Ok(());
+    }
+
+    let preview = ctx.balance_service.backfill_preview(
+        &account_id,
+        known_balance,
+        known_date,
+        start_date,
+        end_date,
+    )?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&preview)?);
+        return Ok(());
+    }
+
+    if preview.is_empty() {
+        println!("No days to backfill in this range.");
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        "Date",
+        "Balance",
+        "Change",
+        "Transactions",
+        "Existing",
+        "Action",
+    ]);
+    for day in &preview {
+        let existing = day
+            .existing_balance
+            .map(|b| {
+                let source = day.existing_source.as_deref().unwrap_or("unknown");
+                format!("{:.2} ({})", b, source)
+            })
+            .unwrap_or_default();
+        table.add_row(vec![
+            day.date.clone(),
+            format!("{:.2}", day.balance),
+            format!("{:+.2}", day.daily_change),
+            day.transactions.len().to_string(),
+            existing,
+            if day.is_new { "create" } else { "replace" }.to_string(),
+        ]);
+    }
+    for column in 1..4 {
+        if let Some(column) = table.column_mut(column) {
+            column.set_cell_alignment(CellAlignment::Right);
+        }
+    }
+    println!("{}", table);
+
+    let new = preview.iter().filter(|day| day.is_new).count();
+    println!(
+        "{} days: {} new, {} replaced. Run without --dry-run to apply.",
+        preview.len(),
+        new,
+        preview.len() - new
+    );
+
+    Ok(())
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .with_context(|| format!("Invalid date '{}', expected YYYY-MM-DD", value))
+}
@@ -0,0 +1,223 @@
+//! CDC command - read the change log, or stream it to other tools
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use colored::Colorize;
+use treeline_core::services::{sse_event, CdcService, TokenService, MAX_CHANGES_PER_PAGE};
+
+use super::{get_context, get_treeline_dir};
+
+/// How often new changes are looked for
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// An SSE comment is sent this often when nothing changes, so proxies keep the stream open
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Subcommand)]
+pub enum CdcCommands {
+    /// Print changes after a sequence number, oldest first
+    Tail {
+        /// Last sequence number already processed (0 for everything)
+        #[arg(long, default_value = "0")]
+        since: i64,
+        /// Keep waiting for new changes
+        #[arg(long, short = 'f')]
+        follow: bool,
+        /// Output as JSON, one change per line
+        #[arg(long)]
+        json: bool,
+    },
+    /// Stream changes as server-sent events at http://<bind>:<port>/changes
+    Serve {
+        /// Port to listen on
+        #[arg(long, default_value = "8765")]
+        port: u16,
+        /// Address to listen on (anything but localhost is reachable from the network)
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: String,
+    },
+}
+
+pub fn run(command: CdcCommands) -> Result<()> {
+    match command {
+        CdcCommands::Tail {
+            since,
+            follow,
+            json,
+        } => run_tail(since, follow, json),
+        CdcCommands::Serve { port, bind } => run_serve(port, &bind),
+    }
+}
+
+fn run_tail(since: i64, follow: bool, json: bool) -> Result<()> {
+    let ctx = get_context()?;
+    let service = CdcService::new(Arc::clone(&ctx.repository));
+
+    let mut since = since;
+    loop {
+        let changes = service.changes(since, MAX_CHANGES_PER_PAGE)?;
+        for change in &changes {
+            if json {
+                println!("{}", serde_json::to_string(change)?);
+            } else {
+                println!(
+                    "{:>8}  {}  {:<6}  {:<16}  {}",
+                    change.seq,
+                    change.changed_at.format("%Y-%m-%d %H:%M:%S"),
+                    change.op,
+                    change.entity,
+                    change.entity_id
+                );
+            }
+        }
+        if let Some(last) = changes.last() {
+            since = last.seq;
+        }
+
+        // A full page means more are waiting
+        if changes.len() == MAX_CHANGES_PER_PAGE {
+            continue;
+        }
+        if !follow {
+            break;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    Ok(())
+}
+
+fn run_serve(port: u16, bind: &str) -> Result<()> {
+    let ctx = get_context()?;
+    let listener = TcpListener::bind((bind, port))
+        .with_context(|| format!("Failed to listen on {}:{}", bind, port))?;
+
+    println!(
+        "{} http://{}:{}/changes",
+        "Streaming changes at".green(),
+        bind,
+        port
+    );
+    println!(
+        "{}",
+        "Requests need an access token from `tl token issue` (Ctrl-C to stop).".dimmed()
+    );
+
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let service = CdcService::new(Arc::clone(&ctx.repository));
+        // Ends with an error when the client disconnects, which is how streams normally end
+        thread::spawn(move || {
+            let _ = handle_client(stream, service);
+        });
+    }
+
+    Ok(())
+}
+
+/// Answer one HTTP request, streaming changes until the client goes away
+///
+/// The token comes from `Authorization: Bearer` or, since browsers' EventSource
+/// can't set headers, a `token` query parameter. A reconnecting client's
+/// Last-Event-ID takes precedence over `since`.
+fn handle_client(mut stream: TcpStream, service: CdcService) -> Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    let mut authorization = None;
+    let mut last_event_id = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "authorization" => authorization = Some(value.trim().to_string()),
+                "last-event-id" => last_event_id = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let param = |name: &str| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.to_string())
+    };
+
+    if path != "/changes" {
+        return respond(&mut stream, "404 Not Found", "Changes are at /changes\n");
+    }
+    let token = authorization
+        .and_then(|a| a.strip_prefix("Bearer ").map(String::from))
+        .or_else(|| param("token"));
+    let authorized = token.is_some_and(|t| {
+        TokenService::new(get_treeline_dir())
+            .authenticate(&t)
+            .is_ok()
+    });
+    if !authorized {
+        return respond(
+            &mut stream,
+            "401 Unauthorized",
+            "A valid access token is required\n",
+        );
+    }
+
+    let mut since = last_event_id
+        .or_else(|| param("since"))
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    stream.write_all(
+        b"HTTP/1.1 200 OK\r\n\
+          Content-Type: text/event-stream\r\n\
+          Cache-Control: no-cache\r\n\
+          Connection: keep-alive\r\n\
+          Access-Control-Allow-Origin: *\r\n\r\n",
+    )?;
+
+    let mut idle = Duration::ZERO;
+    loop {
+        let changes = service.changes(since, MAX_CHANGES_PER_PAGE)?;
+        for change in &changes {
+            stream.write_all(sse_event(change)?.as_bytes())?;
+            since = change.seq;
+        }
+        stream.flush()?;
+
+        if changes.is_empty() {
+            idle += POLL_INTERVAL;
+            if idle >= KEEPALIVE_INTERVAL {
+                stream.write_all(b": keepalive\n\n")?;
+                idle = Duration::ZERO;
+            }
+            thread::sleep(POLL_INTERVAL);
+        } else {
+            idle = Duration::ZERO;
+        }
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, body: &str) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
@@ -2,13 +2,14 @@
 
 use anyhow::Result;
 use colored::Colorize;
+use schemars::JsonSchema;
 use serde::Serialize;
 
 use super::get_context;
 
 /// JSON output structure matching Python CLI
-#[derive(Serialize)]
-struct CompactOutput {
+#[derive(Serialize, JsonSchema)]
+pub struct CompactOutput {
     original_size: u64,
     compacted_size: u64,
     backup_name: Option<String>,
@@ -0,0 +1,117 @@
+//! Dev commands - tools for working on Treeline itself
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use clap::Subcommand;
+use colored::Colorize;
+use treeline_core::adapters::mock_provider::{MockFixture, MockProviderServer};
+
+#[derive(Subcommand)]
+pub enum DevCommands {
+    /// Serve fake bank sync APIs (SimpleFIN, Lunchflow, Plaid, GoCardless) to test sync
+    /// without real credentials
+    MockProvider {
+        /// Port to listen on (127.0.0.1 only)
+        #[arg(long, default_value = "8787")]
+        port: u16,
+        /// JSON fixture of accounts, transactions and faults (defaults to a sample)
+        #[arg(long)]
+        fixture: Option<PathBuf>,
+        /// Delay every response by this many milliseconds
+        #[arg(long)]
+        latency_ms: Option<u64>,
+        /// Fail the first N requests
+        #[arg(long)]
+        fail_first: Option<u32>,
+        /// Fail every Nth request
+        #[arg(long)]
+        fail_every: Option<u32>,
+        /// HTTP status of failed requests (default 500)
+        #[arg(long)]
+        error_status: Option<u16>,
+        /// Send Retry-After with failed requests, in seconds
+        #[arg(long)]
+        retry_after: Option<u64>,
+        /// Always fail requests to paths starting with this (repeatable)
+        #[arg(long = "fail-path")]
+        fail_paths: Vec<String>,
+    },
+}
+
+pub fn run(command: DevCommands) -> Result<()> {
+    match command {
+        DevCommands::MockProvider {
+            port,
+            fixture,
+            latency_ms,
+            fail_first,
+            fail_every,
+            error_status,
+            retry_after,
+            fail_paths,
+        } => {
+            let mut fixture = match fixture {
+                Some(path) => {
+                    let content = std::fs::read_to_string(&path)
+                        .with_context(|| format!("Failed to read {}", path.display()))?;
+                    serde_json::from_str(&content)
+                        .with_context(|| format!("Invalid fixture {}", path.display()))?
+                }
+                None => MockFixture::sample(Local::now().date_naive()),
+            };
+            let faults = &mut fixture.faults;
+            faults.latency_ms = latency_ms.unwrap_or(faults.latency_ms);
+            faults.fail_first = fail_first.unwrap_or(faults.fail_first);
+            faults.fail_every = fail_every.unwrap_or(faults.fail_every);
+            faults.error_status = error_status.unwrap_or(faults.error_status);
+            faults.retry_after = retry_after.or(faults.retry_after);
+            faults.fail_paths.extend(fail_paths);
+
+            let server = MockProviderServer::start(port, fixture)?;
+            println!("{} {}", "Mock provider listening on".green(), server.url());
+            println!();
+            println!("Point an integration at it:");
+            println!(
+                "  {}",
+                format!("tl setup simplefin {}", server.simplefin_setup_token()).cyan()
+            );
+            println!(
+                "  {}",
+                format!(
+                    "tl setup lunchflow mock-key --base-url {}",
+                    server.lunchflow_base_url()
+                )
+                .cyan()
+            );
+            println!(
+                "  {}",
+                format!(
+                    "tl setup plaid --client-id mock --secret mock --environment sandbox --base-url {}",
+                    server.plaid_base_url()
+                )
+                .cyan()
+            );
+            println!(
+                "  {}",
+                format!(
+                    "tl setup gocardless --secret-id mock --secret-key mock --institution MOCKBANK_MOCKGB21 --base-url {}",
+                    server.gocardless_base_url()
+                )
+                .cyan()
+            );
+            println!();
+            println!(
+                "{}",
+                "Set TREELINE_DIR to a scratch folder to keep mock accounts out of your data."
+                    .dimmed()
+            );
+            println!("{}", "Press Ctrl+C to stop.".dimmed());
+
+            server.wait();
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,173 @@
+//! Device sync command - exchange changes with other devices through a relay folder
+
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use clap::Subcommand;
+use colored::Colorize;
+use comfy_table::{ContentArrangement, Table};
+use dialoguer::Password;
+use treeline_core::services::{DeviceSyncService, DEVICE_SYNC_PASSPHRASE_ENV};
+use treeline_core::LogEvent;
+
+use super::{get_context, get_logger, get_treeline_dir, log_event};
+
+#[derive(Subcommand)]
+pub enum DeviceSyncCommands {
+    /// Create or join a relay folder shared by your devices
+    Init {
+        /// Folder every device can reach (NAS share, Syncthing folder, USB stick)
+        #[arg(long)]
+        relay: PathBuf,
+        /// Name shown to the other devices
+        #[arg(long)]
+        name: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Send local changes and apply changes from the other devices
+    Run {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show the relay and how far behind each other device this one is
+    Status {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn run(command: DeviceSyncCommands) -> Result<()> {
+    match command {
+        DeviceSyncCommands::Init { relay, name, json } => {
+            let passphrase = get_passphrase(true)?;
+            let ctx = get_context()?;
+            let service = DeviceSyncService::new(Arc::clone(&ctx.repository), get_treeline_dir());
+            let settings = service.init(&relay, &passphrase, name)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&settings)?);
+            } else {
+                println!(
+                    "{} {} as device {}",
+                    "Joined relay".green(),
+                    settings.relay.display(),
+                    settings.device_id
+                );
+                println!(
+                    "{}",
+                    "Run `tl device-sync run` on each device to exchange changes.".dimmed()
+                );
+            }
+        }
+        DeviceSyncCommands::Run { json } => {
+            let passphrase = get_passphrase(false)?;
+            let ctx = get_context()?;
+            let service = DeviceSyncService::new(Arc::clone(&ctx.repository), get_treeline_dir());
+            let logger = get_logger();
+
+            let result = service.sync(&passphrase);
+            let event = LogEvent::new("device_sync_completed").with_command("device-sync run");
+            let event = match &result {
+                Ok(_) => event,
+                Err(e) => event.with_error(e.to_string()),
+            };
+            log_event(&logger, event);
+            let result = result?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&result)?);
+                return Ok(());
+            }
+
+            println!(
+                "{} sent {} change(s), applied {} from {} bundle(s)",
+                "Device sync:".green(),
+                result.pushed,
+                result.applied,
+                result.bundles_read
+            );
+            if result.outdated > 0 {
+                println!(
+                    "{}",
+                    format!(
+                        "{} change(s) were older than the values here and kept local values",
+                        result.outdated
+                    )
+                    .dimmed()
+                );
+            }
+            if result.skipped > 0 {
+                println!(
+                    "{}",
+                    format!(
+                        "{} change(s) were for records this device couldn't create",
+                        result.skipped
+                    )
+                    .yellow()
+                );
+            }
+        }
+        DeviceSyncCommands::Status { json } => {
+            let ctx = get_context()?;
+            let service = DeviceSyncService::new(Arc::clone(&ctx.repository), get_treeline_dir());
+            let status = service.status()?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&status)?);
+                return Ok(());
+            }
+
+            println!("  Relay:   {}", status.relay.display());
+            println!(
+                "  Device:  {} ({})",
+                status.device_name.as_deref().unwrap_or("unnamed"),
+                status.device_id
+            );
+            println!("  Fields:  {}", status.tracked_fields);
+
+            if status.peers.is_empty() {
+                println!("No other devices on this relay yet.");
+                return Ok(());
+            }
+
+            let mut table = Table::new();
+            table.set_content_arrangement(ContentArrangement::Dynamic);
+            table.set_header(vec!["Device", "Bundles", "Applied", "Last synced", "ID"]);
+            for peer in &status.peers {
+                table.add_row(vec![
+                    peer.device_name.clone().unwrap_or_default(),
+                    peer.latest_seq.to_string(),
+                    peer.applied_seq.to_string(),
+                    peer.last_synced_at
+                        .map(|at| at.format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_else(|| "never".to_string()),
+                    peer.device_id.clone(),
+                ]);
+            }
+            println!("{}", table);
+        }
+    }
+
+    Ok(())
+}
+
+/// The relay passphrase from TREELINE_SYNC_PASSPHRASE, or a prompt
+fn get_passphrase(confirm: bool) -> Result<String> {
+    if let Ok(p) = env::var(DEVICE_SYNC_PASSPHRASE_ENV) {
+        return Ok(p);
+    }
+
+    let prompt = Password::new().with_prompt("Relay passphrase");
+    let prompt = if confirm {
+        prompt.with_confirmation("Confirm relay passphrase", "Passphrases do not match")
+    } else {
+        prompt
+    };
+    Ok(prompt.interact()?)
+}
@@ -1,6 +1,7 @@
 //! Encrypt/Decrypt commands - manage database encryption
 
 use std::env;
+use std::time::Duration;
 
 use anyhow::Result;
 use clap::Subcommand;
@@ -10,7 +11,7 @@ use treeline_core::LogEvent;
 
 use super::{get_logger, log_event};
 use treeline_core::config::Config;
-use treeline_core::services::{BackupService, EncryptionService};
+use treeline_core::services::{BackupService, CalibrationResult, EncryptionService};
 
 /// Get password from --password flag, TREELINE_PASSWORD env var, or prompt
 fn get_password_or_prompt(password_flag: Option<String>, prompt: &str) -> Result<String> {
@@ -59,6 +60,15 @@ fn get_password_with_confirm(password_flag: Option<String>) -> Result<String> {
 pub enum EncryptCommands {
     /// Show encryption status
     Status,
+    /// Measure this machine and suggest Argon2 key derivation costs
+    Calibrate {
+        /// Target key derivation time in milliseconds
+        #[arg(long, default_value = "500")]
+        target_ms: u64,
+        /// Save the suggested params without prompting
+        #[arg(long)]
+        save: bool,
+    },
 }
 
 pub fn run(command: Option<EncryptCommands>, password: Option<String>, json: bool) -> Result<()> {
@@ -102,6 +112,9 @@ pub fn run(command: Option<EncryptCommands>, password: Option<String>, json: boo
                 }
             }
         }
+        Some(EncryptCommands::Calibrate { target_ms, save }) => {
+            run_calibrate(&treeline_dir, &encryption_service, target_ms, save, json)?;
+        }
         None => {
             let logger = get_logger();
             log_event(
@@ -163,6 +176,79 @@ pub fn run(command: Option<EncryptCommands>, password: Option<String>, json: boo
     Ok(())
 }
 
+fn run_calibrate(
+    treeline_dir: &std::path::Path,
+    encryption_service: &EncryptionService,
+    target_ms: u64,
+    save: bool,
+    json: bool,
+) -> Result<()> {
+    if !json {
+        println!("Measuring key derivation (this takes a few seconds)...");
+    }
+    let result = EncryptionService::calibrate(Duration::from_millis(target_ms))?;
+
+    if !json {
+        print_calibration(&result);
+    }
+
+    let save = save
+        || (!json
+            && Confirm::new()
+                .with_prompt("Use these parameters for encryption?")
+                .interact()?);
+
+    if save {
+        let mut config = Config::load(treeline_dir)?;
+        config.argon2_params = Some(result.params.clone());
+        config.save(treeline_dir)?;
+    }
+
+    let upgrade_pending = save && encryption_service.needs_param_upgrade()?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "params": result.params,
+                "elapsed_ms": result.elapsed_ms,
+                "target_ms": result.target_ms,
+                "saved": save,
+                "upgrade_pending": upgrade_pending,
+            }))?
+        );
+    } else if save {
+        println!("{}", "Parameters saved".green());
+        if upgrade_pending {
+            println!(
+                "{}",
+                "The database key will be re-derived with these parameters on next password entry"
+                    .dimmed()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn print_calibration(result: &CalibrationResult) {
+    println!();
+    println!("  Memory cost: {} MiB", result.params.memory_cost / 1024);
+    println!("  Time cost:   {} passes", result.params.time_cost);
+    println!("  Parallelism: {}", result.params.parallelism);
+    println!(
+        "  Unlock time: {} ms (target {} ms)",
+        result.elapsed_ms, result.target_ms
+    );
+    if result.elapsed_ms > result.target_ms {
+        println!(
+            "{}",
+            "This machine is slower than the target; defaults are the minimum".dimmed()
+        );
+    }
+    println!();
+}
+
 pub fn run_decrypt(password: Option<String>, json: bool) -> Result<()> {
     let logger = get_logger();
     log_event(
@@ -0,0 +1,83 @@
+//! Holdings command - positions in brokerage accounts
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use colored::Colorize;
+use comfy_table::{CellAlignment, ContentArrangement, Table};
+use treeline_core::services::InvestmentService;
+
+use super::get_context;
+
+pub fn run(account: Option<String>, json: bool) -> Result<()> {
+    let ctx = get_context()?;
+    let account_id = account
+        .map(|a| ctx.import_service.resolve_account(&a))
+        .transpose()?;
+    let summary =
+        InvestmentService::new(Arc::clone(&ctx.repository)).holdings(account_id.as_deref())?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
+
+    if summary.holdings.is_empty() {
+        println!(
+            "No holdings. Brokerage accounts synced with SimpleFIN list their positions here."
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        "Symbol",
+        "Description",
+        "Account",
+        "Quantity",
+        "Price",
+        "Value",
+        "Gain",
+        "As of",
+    ]);
+    for holding in &summary.holdings {
+        let gain = match holding.unrealized_gain {
+            Some(gain) if gain.is_sign_negative() => format!("{:.2}", gain).red(),
+            Some(gain) => format!("{:.2}", gain).green(),
+            None => "-".dimmed(),
+        };
+        table.add_row(vec![
+            holding.symbol.clone().unwrap_or_else(|| "-".to_string()),
+            holding.description.clone().unwrap_or_default(),
+            holding.account_name.clone(),
+            holding.quantity.normalize().to_string(),
+            holding
+                .price
+                .map(|p| format!("{:.2}", p))
+                .unwrap_or_else(|| "-".to_string()),
+            holding
+                .market_value
+                .map(|v| format!("{:.2} {}", v, holding.currency))
+                .unwrap_or_else(|| "-".to_string()),
+            gain.to_string(),
+            holding.as_of.date().to_string(),
+        ]);
+    }
+    for column in 3..7 {
+        if let Some(column) = table.column_mut(column) {
+            column.set_cell_alignment(CellAlignment::Right);
+        }
+    }
+    println!("{}", table);
+
+    for (currency, total) in &summary.market_value_totals {
+        print!("{} {:.2} {}", "Total value:".bold(), total, currency);
+        match summary.unrealized_gain_totals.get(currency) {
+            Some(gain) => println!("  (unrealized gain {:.2})", gain),
+            None => println!(),
+        }
+    }
+
+    Ok(())
+}
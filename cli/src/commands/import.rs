@@ -1,17 +1,26 @@
 //! Import command - import transactions from CSV files
 
+use std::collections::HashMap;
 use std::io::{self, Read as IoRead};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use anyhow::{Context, Result};
+use clap::Subcommand;
 use colored::Colorize;
 use comfy_table::{ContentArrangement, Table};
+use indicatif::{ProgressBar, ProgressStyle};
 use rust_decimal::Decimal;
 use treeline_core::config::ColumnMappings;
-use treeline_core::services::import::{ImportOptions, NumberFormat};
+use treeline_core::services::import::{DetectedColumns, ImportOptions, NumberFormat};
+use treeline_core::services::{
+    date_format_label, find_bank_preset, imports_dir, FolderImportStatus, ImportFolderService,
+    ImportPreset, ImportResult, ImportService, LedgerFormat, LedgerImportResult, RowStatus,
+    BANK_PRESETS,
+};
 use treeline_core::LogEvent;
 
-use super::{get_context, get_logger, log_event};
+use super::{get_context, get_logger, get_treeline_dir, log_event};
 
 pub fn run(
     file: &str,
@@ -22,13 +31,27 @@ pub fn run(
     debit_column: Option<&str>,
     credit_column: Option<&str>,
     balance_column: Option<&str>,
+    account_column: Option<&str>,
+    account_map: &[String],
+    create_accounts: bool,
+    category_column: Option<&str>,
+    tags_column: Option<&str>,
+    check_number_column: Option<&str>,
+    reference_column: Option<&str>,
+    direction_column: Option<&str>,
+    currency_column: Option<&str>,
+    category_map: &[String],
     flip_signs: bool,
     debit_negative: bool,
     skip_rows: u32,
+    no_header: bool,
     number_format: &str,
+    column_formats: &[String],
+    date_format: Option<&str>,
     anchor_balance: Option<f64>,
     anchor_date: Option<&str>,
     profile: Option<&str>,
+    preset: Option<&str>,
     save_profile: Option<&str>,
     dry_run: bool,
     json: bool,
@@ -43,17 +66,31 @@ pub fn run(
 
     // Resolve file path — support stdin via "-"
     let file_path = resolve_file(file)?;
+    // PDF statements are read into fixed Date/Description/Amount/Balance columns
+    let is_pdf = file_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
 
     // Resolve account by UUID or name (via service layer)
     let account_id = ctx.import_service.resolve_account(account)?;
 
-    // Load profile if specified
+    // Load profile if specified (a bank preset acts as a built-in profile)
     let loaded_profile = if let Some(profile_name) = profile {
         let p = ctx
             .import_service
             .get_profile(profile_name)?
             .with_context(|| format!("Import profile '{}' not found", profile_name))?;
         Some(p)
+    } else if let Some(preset_id) = preset {
+        let bank = find_bank_preset(preset_id).with_context(|| {
+            let ids: Vec<&str> = BANK_PRESETS.iter().map(|p| p.id).collect();
+            format!(
+                "Unknown preset '{}' (available: {})",
+                preset_id,
+                ids.join(", ")
+            )
+        })?;
+        Some(bank.profile(&file_path)?)
     } else {
         None
     };
@@ -62,7 +99,14 @@ pub fn run(
     // 1. Explicit flags (highest priority)
     // 2. Profile settings
     // 3. Auto-detection (fallback)
-    let detected = ctx.import_service.detect_columns(&file_path)?;
+    // A file without a header row has nothing to detect from
+    let effective_no_header =
+        no_header || loaded_profile.as_ref().is_some_and(|p| p.options.no_header);
+    let detected = if effective_no_header || is_pdf {
+        DetectedColumns::default()
+    } else {
+        ctx.import_service.detect_columns(&file_path)?
+    };
 
     let profile_mappings = loaded_profile.as_ref().map(|p| &p.column_mappings);
 
@@ -95,6 +139,27 @@ pub fn run(
         balance: balance_column
             .map(String::from)
             .or_else(|| profile_mappings.and_then(|m| m.balance.clone())),
+        account: account_column
+            .map(String::from)
+            .or_else(|| profile_mappings.and_then(|m| m.account.clone())),
+        category: category_column
+            .map(String::from)
+            .or_else(|| profile_mappings.and_then(|m| m.category.clone())),
+        tags: tags_column
+            .map(String::from)
+            .or_else(|| profile_mappings.and_then(|m| m.tags.clone())),
+        check_number: check_number_column
+            .map(String::from)
+            .or_else(|| profile_mappings.and_then(|m| m.check_number.clone())),
+        reference: reference_column
+            .map(String::from)
+            .or_else(|| profile_mappings.and_then(|m| m.reference.clone())),
+        direction: direction_column
+            .map(String::from)
+            .or_else(|| profile_mappings.and_then(|m| m.direction.clone())),
+        currency: currency_column
+            .map(String::from)
+            .or_else(|| profile_mappings.and_then(|m| m.currency.clone())),
     };
 
     // Build import options with same resolution order
@@ -122,26 +187,102 @@ pub fn run(
         })
         .transpose()?;
 
+    // Per-column number formats: profile overrides first, then explicit flags
+    let mut column_number_formats: HashMap<String, NumberFormat> = profile_opts
+        .map(|o| {
+            o.column_number_formats
+                .iter()
+                .map(|(column, format)| (column.clone(), NumberFormat::from_str(format)))
+                .collect()
+        })
+        .unwrap_or_default();
+    for spec in column_formats {
+        let (column, format) = spec.rsplit_once('=').with_context(|| {
+            format!("Invalid --column-format '{}', expected COLUMN=FORMAT", spec)
+        })?;
+        column_number_formats.insert(column.to_string(), NumberFormat::from_str(format));
+    }
+
+    // Account assignments for multi-account files: profile first, then explicit flags
+    let mut effective_account_map: HashMap<String, String> = profile_opts
+        .map(|o| o.account_map.clone())
+        .unwrap_or_default();
+    for spec in account_map {
+        let (value, target) = spec
+            .rsplit_once('=')
+            .with_context(|| format!("Invalid --map-account '{}', expected VALUE=ACCOUNT", spec))?;
+        effective_account_map.insert(value.to_string(), target.to_string());
+    }
+    // Category translations: profile first, then explicit flags
+    let mut effective_category_map: HashMap<String, String> = profile_opts
+        .map(|o| o.category_map.clone())
+        .unwrap_or_default();
+    for spec in category_map {
+        let (category, tag) = spec
+            .rsplit_once('=')
+            .with_context(|| format!("Invalid --map-category '{}', expected CATEGORY=TAG", spec))?;
+        effective_category_map.insert(category.to_string(), tag.to_string());
+    }
+
+    let effective_create_accounts = create_accounts
+        || profile_opts
+            .map(|o| o.create_missing_accounts)
+            .unwrap_or(false);
+
     let options = ImportOptions {
         flip_signs: effective_flip_signs,
         debit_negative: effective_debit_negative,
         skip_rows: effective_skip_rows,
-        number_format: NumberFormat::from_str(number_format),
+        // An explicit non-default format wins over the profile's
+        number_format: NumberFormat::from_str(
+            profile_opts
+                .and_then(|o| o.number_format.as_deref())
+                .filter(|_| number_format == "us")
+                .unwrap_or(number_format),
+        ),
         anchor_balance: parsed_anchor_balance,
         anchor_date: parsed_anchor_date,
+        column_number_formats,
+        account_map: effective_account_map,
+        create_missing_accounts: effective_create_accounts,
+        category_map: effective_category_map,
+        date_format: date_format
+            .map(String::from)
+            .or_else(|| loaded_profile.as_ref().and_then(|p| p.date_format.clone())),
+        no_header: effective_no_header,
     };
 
-    // Run import (preview or execute)
-    let result = ctx
-        .import_service
-        .import(&file_path, &account_id, &mappings, &options, dry_run)
-        .map_err(|e| {
-            log_event(
-                &logger,
-                LogEvent::new("import_failed").with_error(&e.to_string()),
-            );
-            e
-        })?;
+    // Run import (preview or execute), with a progress bar on a terminal
+    let bar = if json {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new(0)
+    };
+    bar.set_style(
+        ProgressStyle::with_template("{msg:24} [{bar:30}] {pos}/{len}")
+            .unwrap()
+            .progress_chars("=> "),
+    );
+    let progress_bar = bar.clone();
+    let import_service = ImportService::new(ctx.repository.clone(), get_treeline_dir())
+        .with_progress(move |progress| {
+            progress_bar.set_message(progress.stage.label());
+            progress_bar.set_length(progress.total as u64);
+            progress_bar.set_position(progress.done as u64);
+        });
+    let result = if is_pdf {
+        import_pdf(&import_service, &file_path, &account_id, &options, dry_run)
+    } else {
+        import_service.import(&file_path, &account_id, &mappings, &options, dry_run)
+    };
+    bar.finish_and_clear();
+    let result = result.map_err(|e| {
+        log_event(
+            &logger,
+            LogEvent::new("import_failed").with_error(&e.to_string()),
+        );
+        e
+    })?;
 
     // Save profile if requested (only on successful non-preview import)
     if let Some(profile_name) = save_profile {
@@ -171,12 +312,513 @@ pub fn run(
     // Resolve account name for display (via service layer)
     let account_display = ctx.import_service.get_account_display_name(&account_id);
 
+    print_import_result(&file_path, &account_display, &result, dry_run);
+
+    Ok(())
+}
+
+/// Import every CSV in the imports folder into the account it matches
+pub fn run_watch_folder(dry_run: bool, json: bool) -> Result<()> {
+    let logger = get_logger();
+    let ctx = get_context()?;
+    let summary = ImportFolderService::new(Arc::clone(&ctx.repository), get_treeline_dir())
+        .import_all(dry_run)?;
+    log_event(
+        &logger,
+        LogEvent::new("import_completed").with_command("import"),
+    );
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
+    if summary.files.is_empty() {
+        println!(
+            "No CSV files waiting in {}",
+            imports_dir(&get_treeline_dir()).display()
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        "File", "Account", "Profile", "Imported", "Skipped", "Status",
+    ]);
+    for file in &summary.files {
+        let status = match file.status {
+            FolderImportStatus::Imported if dry_run => "would import".cyan().to_string(),
+            FolderImportStatus::Imported => "imported".green().to_string(),
+            FolderImportStatus::Unmatched => "no match".yellow().to_string(),
+            FolderImportStatus::Failed => "failed".red().to_string(),
+        };
+        table.add_row(vec![
+            file.file.clone(),
+            file.account_name.clone().unwrap_or_default(),
+            file.profile.clone().unwrap_or_default(),
+            file.imported.to_string(),
+            file.skipped.to_string(),
+            status,
+        ]);
+    }
+    println!("{}", table);
+
+    for file in &summary.files {
+        if let Some(message) = &file.message {
+            println!("  {}: {}", file.file, message.dimmed());
+        }
+    }
+    println!(
+        "{} {} transactions from {} files ({} unmatched, {} failed)",
+        if dry_run { "Would import" } else { "Imported" }.bold(),
+        summary.transactions_imported,
+        summary.imported_files,
+        summary.unmatched_files,
+        summary.failed_files
+    );
+    if summary.unmatched_files > 0 {
+        println!(
+            "{}",
+            "Assign a profile to an account in the desktop import dialog, or name the file after the account."
+                .dimmed()
+        );
+    }
+
+    Ok(())
+}
+
+#[derive(Subcommand)]
+pub enum ImportCommands {
+    /// Migrate accounts, transactions and categories from a GnuCash book (XML or sqlite)
+    Gnucash {
+        /// Path to the GnuCash file
+        file: PathBuf,
+        /// Preview without importing
+        #[arg(long)]
+        dry_run: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Migrate accounts, transactions and categories from a Money Manager EX database
+    Mmex {
+        /// Path to the .mmb database
+        file: PathBuf,
+        /// Preview without importing
+        #[arg(long)]
+        dry_run: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Import a YNAB register export (accounts are created from the Account column)
+    Ynab {
+        /// Path to the register CSV
+        file: String,
+        /// Account ID (UUID) or name for rows without an account
+        #[arg(short, long)]
+        account: Option<String>,
+        /// Number format: us (1,234.56), eu (1.234,56), eu_space (1 234,56)
+        #[arg(long, default_value = "us")]
+        number_format: String,
+        /// Preview without importing
+        #[arg(long)]
+        dry_run: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Import a Mint transactions export (accounts are created from Account Name)
+    Mint {
+        /// Path to the transactions CSV
+        file: String,
+        /// Account ID (UUID) or name for rows without an account
+        #[arg(short, long)]
+        account: Option<String>,
+        /// Number format: us (1,234.56), eu (1.234,56), eu_space (1 234,56)
+        #[arg(long, default_value = "us")]
+        number_format: String,
+        /// Preview without importing
+        #[arg(long)]
+        dry_run: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Import an Apple Card monthly statement CSV (exported from Wallet)
+    AppleCard {
+        /// Path to the statement CSV
+        file: String,
+        /// Account ID (UUID) or name
+        #[arg(short, long)]
+        account: String,
+        /// Preview without importing
+        #[arg(long)]
+        dry_run: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Import a PayPal activity download (fees become separate transactions)
+    Paypal {
+        /// Path to the activity CSV
+        file: String,
+        /// Account ID (UUID) or name; rows in other currencies are skipped
+        #[arg(short, long)]
+        account: String,
+        /// Number format: us (1,234.56), eu (1.234,56), eu_space (1 234,56)
+        #[arg(long, default_value = "us")]
+        number_format: String,
+        /// Preview without importing
+        #[arg(long)]
+        dry_run: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Undo an import, deleting the transactions and balance snapshots it created
+    Undo {
+        /// Batch ID printed by the import (also in its --json output)
+        batch_id: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Add item names to Amazon charges from an Amazon order history export
+    Amazon {
+        /// Path to the order history CSV
+        file: String,
+        /// Only match transactions in this account (ID or name)
+        #[arg(short, long)]
+        account: Option<String>,
+        /// Preview without changing descriptions
+        #[arg(long)]
+        dry_run: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn run_migration(command: ImportCommands) -> Result<()> {
+    let (format, file, dry_run, json) = match command {
+        ImportCommands::Gnucash {
+            file,
+            dry_run,
+            json,
+        } => (LedgerFormat::GnuCash, file, dry_run, json),
+        ImportCommands::Mmex {
+            file,
+            dry_run,
+            json,
+        } => (LedgerFormat::Mmex, file, dry_run, json),
+        ImportCommands::Ynab {
+            file,
+            account,
+            number_format,
+            dry_run,
+            json,
+        } => {
+            return run_preset(
+                ImportPreset::Ynab,
+                &file,
+                account.as_deref(),
+                &number_format,
+                dry_run,
+                json,
+            )
+        }
+        ImportCommands::Mint {
+            file,
+            account,
+            number_format,
+            dry_run,
+            json,
+        } => {
+            return run_preset(
+                ImportPreset::Mint,
+                &file,
+                account.as_deref(),
+                &number_format,
+                dry_run,
+                json,
+            )
+        }
+        ImportCommands::AppleCard {
+            file,
+            account,
+            dry_run,
+            json,
+        } => {
+            return run_preset(
+                ImportPreset::AppleCard,
+                &file,
+                Some(&account),
+                "us",
+                dry_run,
+                json,
+            )
+        }
+        ImportCommands::Paypal {
+            file,
+            account,
+            number_format,
+            dry_run,
+            json,
+        } => {
+            return run_preset(
+                ImportPreset::PayPal,
+                &file,
+                Some(&account),
+                &number_format,
+                dry_run,
+                json,
+            )
+        }
+        ImportCommands::Amazon {
+            file,
+            account,
+            dry_run,
+            json,
+        } => return run_amazon(&file, account.as_deref(), dry_run, json),
+        ImportCommands::Undo { batch_id, json } => return run_undo(&batch_id, json),
+    };
+
+    let logger = get_logger();
+    log_event(
+        &logger,
+        LogEvent::new("import_started").with_command("import"),
+    );
+
+    if !file.exists() {
+        anyhow::bail!("File not found: {}", file.display());
+    }
+
+    let ctx = get_context()?;
+    let result = ctx
+        .ledger_import_service
+        .import(&file, format, dry_run)
+        .inspect_err(|e| {
+            log_event(
+                &logger,
+                LogEvent::new("import_failed").with_error(e.to_string()),
+            );
+        })?;
+
+    log_event(
+        &logger,
+        LogEvent::new("import_completed").with_command("import"),
+    );
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    print_ledger_result(&file, &result);
+    Ok(())
+}
+
+fn run_preset(
+    preset: ImportPreset,
+    file: &str,
+    account: Option<&str>,
+    number_format: &str,
+    dry_run: bool,
+    json: bool,
+) -> Result<()> {
+    let logger = get_logger();
+    log_event(
+        &logger,
+        LogEvent::new("import_started").with_command("import"),
+    );
+
+    let ctx = get_context()?;
+    let file_path = resolve_file(file)?;
+    let account_id = account
+        .map(|a| ctx.import_service.resolve_account(a))
+        .transpose()?;
+
+    let result = ctx
+        .import_service
+        .import_preset(
+            &file_path,
+            preset,
+            account_id.as_deref(),
+            NumberFormat::from_str(number_format),
+            dry_run,
+        )
+        .inspect_err(|e| {
+            log_event(
+                &logger,
+                LogEvent::new("import_failed").with_error(e.to_string()),
+            );
+        })?;
+
+    log_event(
+        &logger,
+        LogEvent::new("import_completed").with_command("import"),
+    );
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    let target = match &account_id {
+        Some(id) => ctx.import_service.get_account_display_name(id),
+        None => format!("{} accounts", preset.label()),
+    };
+    print_import_result(&file_path, &target, &result, dry_run);
+    Ok(())
+}
+
+fn run_amazon(file: &str, account: Option<&str>, dry_run: bool, json: bool) -> Result<()> {
+    let ctx = get_context()?;
+    let file_path = resolve_file(file)?;
+    let account_id = account
+        .map(|a| ctx.import_service.resolve_account(a))
+        .transpose()?;
+
+    let result =
+        ctx.import_service
+            .match_amazon_orders(&file_path, account_id.as_deref(), dry_run)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    if result.preview {
+        println!(
+            "{} {} (Amazon orders)",
+            "Preview".yellow(),
+            file_path.display()
+        );
+    } else {
+        println!(
+            "{} {} (Amazon orders)",
+            "Matched".green(),
+            file_path.display()
+        );
+    }
+    println!();
+
+    if !result.matched.is_empty() {
+        let mut table = Table::new();
+        table.set_content_arrangement(ContentArrangement::Dynamic);
+        table.set_header(vec!["Order", "Date", "Total", "Was", "Now"]);
+        for m in &result.matched {
+            table.add_row(vec![
+                m.order_id.clone(),
+                m.order_date.clone(),
+                format!("{:.2}", m.total),
+                m.previous_description.clone(),
+                m.description.clone(),
+            ]);
+        }
+        println!("{}", table);
+        println!();
+    }
+
+    println!("  Orders:     {}", result.orders);
+    println!("  Matched:    {}", result.matched.len());
+    println!("  Unmatched:  {}", result.unmatched);
+    if result.ambiguous > 0 {
+        println!(
+            "  Ambiguous:  {} (several charges fit; left unchanged)",
+            result.ambiguous
+        );
+    }
+    if result.preview {
+        println!();
+        println!("{}", "  Dry run — no changes applied.".yellow());
+    }
+    Ok(())
+}
+
+fn run_undo(batch_id: &str, json: bool) -> Result<()> {
+    let ctx = get_context()?;
+    let result = ctx.import_service.undo(batch_id)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    println!("{} import {}", "Undid".green(), result.batch_id);
+    println!("  Transactions:  {}", result.transactions_deleted);
+    println!("  Snapshots:     {}", result.balance_snapshots_deleted);
+    Ok(())
+}
+
+fn print_ledger_result(file: &Path, result: &LedgerImportResult) {
+    let source = match result.source.as_str() {
+        "gnucash" => "GnuCash",
+        _ => "Money Manager EX",
+    };
+    if result.preview {
+        println!("{} {} ({})", "Preview".yellow(), file.display(), source);
+    } else {
+        println!("{} {} ({})", "Imported".green(), file.display(), source);
+    }
+    println!();
+
+    if result.accounts.is_empty() {
+        println!("  No accounts found.");
+    } else {
+        let mut table = Table::new();
+        table.set_content_arrangement(ContentArrangement::Dynamic);
+        table.set_header(vec!["Account", "Type", "Status", "New transactions"]);
+        for account in &result.accounts {
+            let status = if account.created { "new" } else { "existing" };
+            table.add_row(vec![
+                account.name.clone(),
+                account.account_type.clone(),
+                status.to_string(),
+                account.transactions.to_string(),
+            ]);
+        }
+        println!("{}", table);
+    }
+
+    if !result.untranslated.is_empty() {
+        println!();
+        println!("  {}", "Not translated:".yellow());
+        for item in &result.untranslated {
+            match &item.name {
+                Some(name) if item.count > 0 => println!(
+                    "    {} '{}' ({} records): {}",
+                    item.kind, name, item.count, item.reason
+                ),
+                Some(name) => println!("    {} '{}': {}", item.kind, name, item.reason),
+                None => println!("    {} x{}: {}", item.kind, item.count, item.reason),
+            }
+        }
+    }
+
+    println!();
+    println!("  Discovered:  {} transactions", result.discovered);
+    println!("  Skipped:     {} (already imported)", result.skipped);
+    if result.preview {
+        println!("  Would import: {} transactions", result.imported);
+        println!();
+        println!("{}", "  Dry run — no changes applied.".yellow());
+    } else {
+        println!("  Imported:    {} transactions", result.imported);
+        println!();
+        println!("  Batch: {}", result.batch_id);
+    }
+}
+
+/// Print a CSV import result (preview table or import summary)
+fn print_import_result(file_path: &Path, target: &str, result: &ImportResult, dry_run: bool) {
     if dry_run {
         println!(
             "{} {} → {}",
             "Preview".yellow(),
             file_path.display(),
-            account_display.bold()
+            target.bold()
         );
         println!();
 
@@ -189,34 +831,141 @@ pub fn run(
                 table.set_content_arrangement(ContentArrangement::Dynamic);
 
                 let has_balance = transactions.iter().any(|t| t.balance.is_some());
+                let has_account = transactions.iter().any(|t| t.account.is_some());
+                let has_tags = transactions.iter().any(|t| !t.tags.is_empty());
+                let has_check = transactions
+                    .iter()
+                    .any(|t| t.check_number.is_some() || t.reference.is_some());
+                let has_duplicates = result.duplicates > 0;
+                let mut header = vec!["Date", "Amount", "Description"];
                 if has_balance {
-                    table.set_header(vec!["Date", "Amount", "Description", "Balance"]);
-                } else {
-                    table.set_header(vec!["Date", "Amount", "Description"]);
+                    header.push("Balance");
+                }
+                if has_account {
+                    header.push("Account");
                 }
+                if has_tags {
+                    header.push("Tags");
+                }
+                if has_check {
+                    header.push("Check/Ref");
+                }
+                if has_duplicates {
+                    header.push("Status");
+                }
+                table.set_header(header);
 
                 for tx in transactions {
                     let desc = tx.description.as_deref().unwrap_or("");
+                    let tags = tx.tags.join(", ");
+                    let amount = match &tx.currency {
+                        Some(currency) => format!("{} {}", tx.amount, currency),
+                        None => tx.amount.clone(),
+                    };
+                    let mut row = vec![tx.date.as_str(), amount.as_str(), desc];
                     if has_balance {
-                        table.add_row(vec![
-                            &tx.date,
-                            &tx.amount,
-                            desc,
-                            tx.balance.as_deref().unwrap_or(""),
-                        ]);
-                    } else {
-                        table.add_row(vec![&tx.date, &tx.amount, desc]);
+                        row.push(tx.balance.as_deref().unwrap_or(""));
+                    }
+                    if has_account {
+                        row.push(tx.account.as_deref().unwrap_or(""));
                     }
+                    if has_tags {
+                        row.push(&tags);
+                    }
+                    if has_check {
+                        row.push(
+                            tx.check_number
+                                .as_deref()
+                                .or(tx.reference.as_deref())
+                                .unwrap_or(""),
+                        );
+                    }
+                    if has_duplicates {
+                        row.push(match tx.status {
+                            RowStatus::Duplicate => "duplicate",
+                            _ => "new",
+                        });
+                    }
+                    table.add_row(row);
                 }
 
                 println!("{}", table);
             }
         }
 
+        if !result.errors.is_empty() {
+            // Group by reason so the summary reads "3 rows will be skipped: invalid date format"
+            let mut reasons: Vec<&str> = Vec::new();
+            for err in &result.errors {
+                if !reasons.contains(&err.reason.as_str()) {
+                    reasons.push(&err.reason);
+                }
+            }
+            for reason in reasons {
+                let rows: Vec<_> = result
+                    .errors
+                    .iter()
+                    .filter(|e| e.reason == reason)
+                    .collect();
+                println!();
+                println!(
+                    "{}",
+                    format!("  {} rows will be skipped: {}", rows.len(), reason).yellow()
+                );
+                for err in rows {
+                    println!("    line {}: {} '{}'", err.line, err.column, err.value);
+                }
+            }
+        }
+
+        if let Some(detected) = &result.date_format {
+            println!();
+            println!("  Dates:       {}", date_format_label(&detected.format));
+        }
+        for warning in &result.warnings {
+            println!("  {} {}", "Warning:".yellow(), warning);
+        }
+        for mismatch in &result.balance_mismatches {
+            println!("    line {}: {}", mismatch.line, mismatch.message());
+        }
+
+        if !result.accounts_created.is_empty() {
+            println!();
+            println!(
+                "  {} {}",
+                "New accounts:".yellow(),
+                result.accounts_created.join(", ")
+            );
+        }
+
+        if let Some(impact) = &result.balance_impact {
+            println!();
+            println!("  Balance impact:");
+            for month in &impact.months {
+                println!(
+                    "    {}  {:>4} txns  in {:>12}  out {:>12}  net {:>12}",
+                    month.month, month.count, month.inflow, month.outflow, month.net
+                );
+            }
+            println!("    Net change: {}", impact.net_change);
+            if let (Some(balance), Some(date)) = (&impact.snapshot_balance, &impact.snapshot_date) {
+                println!("    Latest snapshot: {} on {}", balance, date);
+            }
+            if let Some(projected) = &impact.projected_balance {
+                println!("    Projected balance: {}", projected);
+            }
+            for warning in &impact.warnings {
+                println!("    {} {}", "Warning:".yellow(), warning);
+            }
+        }
+
         println!();
         println!(
-            "  Discovered: {} transactions | Skipped: {} (invalid rows)",
-            result.discovered, result.skipped
+            "  Discovered: {} transactions | New: {} | Duplicates: {} | Invalid: {}",
+            result.discovered,
+            result.discovered - result.duplicates,
+            result.duplicates,
+            result.errors.len()
         );
         println!();
         println!("{}", "  Dry run — no changes applied.".yellow());
@@ -225,26 +974,30 @@ pub fn run(
             "{} {} → {}",
             "Imported".green(),
             file_path.display(),
-            account_display.bold()
+            target.bold()
         );
         println!();
         println!("  Discovered:  {} transactions", result.discovered);
-        println!(
-            "  Skipped:     {} (duplicates/invalid)",
-            result.skipped
-        );
+        println!("  Skipped:     {} (duplicates/invalid)", result.skipped);
         println!("  Imported:    {} transactions", result.imported);
+        if !result.accounts_created.is_empty() {
+            println!(
+                "  Accounts:    created {}",
+                result.accounts_created.join(", ")
+            );
+        }
         if result.balance_snapshots_created > 0 {
             println!(
                 "  Snapshots:   {} balance snapshots",
                 result.balance_snapshots_created
             );
         }
+        for warning in &result.warnings {
+            println!("  {} {}", "Warning:".yellow(), warning);
+        }
         println!();
         println!("  Batch: {}", result.batch_id);
     }
-
-    Ok(())
 }
 
 /// Resolve file path, handling stdin ("-") by writing to a temp file.
@@ -274,6 +1027,29 @@ fn resolve_file(file: &str) -> Result<PathBuf> {
     }
 }
 
+/// Import a PDF statement, or fail when tl was built without PDF support
+#[cfg(feature = "pdf")]
+fn import_pdf(
+    service: &ImportService,
+    file_path: &Path,
+    account_id: &str,
+    options: &ImportOptions,
+    dry_run: bool,
+) -> Result<ImportResult> {
+    service.import_pdf(file_path, account_id, options, dry_run)
+}
+
+#[cfg(not(feature = "pdf"))]
+fn import_pdf(
+    _service: &ImportService,
+    _file_path: &Path,
+    _account_id: &str,
+    _options: &ImportOptions,
+    _dry_run: bool,
+) -> Result<ImportResult> {
+    anyhow::bail!("This build of tl can't read PDF statements (built without the pdf feature)")
+}
+
 /// Resolve optional column with flag > profile > detected priority.
 fn resolve_optional_column(
     flag: Option<&str>,
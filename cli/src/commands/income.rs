@@ -0,0 +1,90 @@
+//! Income command - recurring income streams and upcoming paydays
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::Local;
+use colored::Colorize;
+use comfy_table::{CellAlignment, ContentArrangement, Table};
+use treeline_core::services::{Cadence, IncomeService};
+
+use super::get_context;
+
+pub fn run(months: u32, json: bool) -> Result<()> {
+    let ctx = get_context()?;
+    let service = IncomeService::new(Arc::clone(&ctx.repository))
+        .with_calendar(ctx.config.business_days.business_calendar());
+    let summary = service.summary(Local::now().date_naive(), months.max(1))?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
+
+    if summary.streams.is_empty() {
+        println!(
+            "No recurring income in the last {} months (a stream needs three regular deposits).",
+            months
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        "Payer",
+        "Account",
+        "Cadence",
+        "Average",
+        "Varies",
+        "Per month",
+        "Last",
+        "Next",
+    ]);
+    for stream in &summary.streams {
+        table.add_row(vec![
+            stream.name.clone(),
+            stream.account_name.clone(),
+            cadence_label(stream.cadence).to_string(),
+            format!("{:.2} {}", stream.average_amount, stream.currency),
+            format!("±{:.1}%", stream.amount_variation_percent),
+            format!("{:.2}", stream.monthly_amount),
+            stream.last_date.to_string(),
+            stream
+                .next_date
+                .map(|d| d.to_string())
+                .unwrap_or_else(|| "stopped".dimmed().to_string()),
+        ]);
+    }
+    for column in 3..6 {
+        if let Some(column) = table.column_mut(column) {
+            column.set_cell_alignment(CellAlignment::Right);
+        }
+    }
+    println!("{}", table);
+
+    for (currency, total) in &summary.monthly_totals {
+        println!("{} {:.2} {}", "Monthly income:".bold(), total, currency);
+    }
+    if !summary.upcoming.is_empty() {
+        println!();
+        println!("{}", "Expected in the next 30 days:".bold());
+        for deposit in &summary.upcoming {
+            println!(
+                "  {}  {:>12.2} {}  {}",
+                deposit.date, deposit.amount, deposit.currency, deposit.name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn cadence_label(cadence: Cadence) -> &'static str {
+    match cadence {
+        Cadence::Weekly => "weekly",
+        Cadence::Biweekly => "every 2 weeks",
+        Cadence::SemiMonthly => "twice a month",
+        Cadence::Monthly => "monthly",
+    }
+}
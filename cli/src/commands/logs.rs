@@ -3,9 +3,11 @@
 use anyhow::Result;
 use clap::Subcommand;
 use colored::Colorize;
-use comfy_table::{ContentArrangement, Table};
+use comfy_table::{CellAlignment, ContentArrangement, Table};
 
 use super::get_treeline_dir;
+use treeline_core::config::{Config, LogCategory, LogLevel};
+use treeline_core::services::UsagePingService;
 use treeline_core::{EntryPoint, LoggingService};
 
 #[derive(Subcommand)]
@@ -22,6 +24,21 @@ pub enum LogsCommands {
         #[arg(long)]
         json: bool,
     },
+    /// Show SQL statements run from the desktop app and plugins
+    Queries {
+        /// Show only slow statements, slowest first
+        #[arg(long)]
+        slow: bool,
+        /// What counts as slow, in milliseconds
+        #[arg(long, default_value = "250", requires = "slow")]
+        min_ms: u64,
+        /// Number of statements to show
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Clear old log entries
     Clear {
         /// Delete logs older than N days
@@ -34,12 +51,47 @@ pub enum LogsCommands {
         #[arg(long)]
         json: bool,
     },
+    /// Delete all log history
+    Purge {
+        /// Skip confirmation prompt
+        #[arg(long, short = 'f')]
+        force: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show or set how much is logged: errors, actions or full
+    Level {
+        /// New level (errors, actions, full); "default" clears a category's own level
+        level: Option<String>,
+        /// Only for this category: ui, cli, sync or app
+        #[arg(long, short = 'c')]
+        category: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Show log statistics and database path
     Stats {
         /// Output as JSON
         #[arg(long)]
         json: bool,
     },
+    /// Show or change the opt-in anonymous usage ping
+    Ping {
+        /// Print exactly what the next ping would send, without sending it
+        #[arg(long)]
+        preview: bool,
+        /// Opt in to a weekly ping
+        #[arg(long, conflicts_with = "disable")]
+        enable: bool,
+        /// Opt out
+        #[arg(long)]
+        disable: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 fn get_logging_service() -> Result<LoggingService> {
@@ -47,6 +99,50 @@ fn get_logging_service() -> Result<LoggingService> {
     LoggingService::new(&treeline_dir, EntryPoint::Cli, env!("CARGO_PKG_VERSION"))
 }
 
+fn get_usage_ping_service() -> UsagePingService {
+    UsagePingService::new(
+        get_treeline_dir(),
+        EntryPoint::Cli,
+        env!("CARGO_PKG_VERSION"),
+    )
+}
+
+/// Send the weekly usage ping if the user opted in (called after other commands)
+///
+/// Failures are ignored; the ping never gets in the way of a command.
+pub fn maybe_send_usage_ping() {
+    let service = get_usage_ping_service();
+    if service.is_enabled() {
+        let logger = get_logging_service().ok();
+        let _ = service.send_if_due(logger.as_ref());
+    }
+}
+
+fn parse_category(name: &str) -> Result<LogCategory> {
+    match name {
+        "ui" => Ok(LogCategory::Ui),
+        "cli" => Ok(LogCategory::Cli),
+        "sync" => Ok(LogCategory::Sync),
+        "app" => Ok(LogCategory::App),
+        other => anyhow::bail!(
+            "Unknown category '{}', expected ui, cli, sync or app",
+            other
+        ),
+    }
+}
+
+fn parse_level(name: &str) -> Result<LogLevel> {
+    match name {
+        "errors" => Ok(LogLevel::Errors),
+        "actions" => Ok(LogLevel::Actions),
+        "full" => Ok(LogLevel::Full),
+        other => anyhow::bail!(
+            "Unknown level '{}', expected errors, actions or full",
+            other
+        ),
+    }
+}
+
 fn format_timestamp(timestamp_ms: i64) -> String {
     use chrono::{TimeZone, Utc};
     Utc.timestamp_millis_opt(timestamp_ms)
@@ -127,6 +223,65 @@ pub fn run(command: LogsCommands) -> Result<()> {
                 }
             }
         }
+        LogsCommands::Queries {
+            slow,
+            min_ms,
+            limit,
+            json,
+        } => {
+            let service = get_logging_service()?;
+            let queries = service.get_queries(slow.then_some(min_ms), limit)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&queries)?);
+                return Ok(());
+            }
+
+            if queries.is_empty() {
+                if slow {
+                    println!("No statements took {}ms or more.", min_ms);
+                } else {
+                    println!("No statements logged. They're recorded at the full log level.");
+                }
+                return Ok(());
+            }
+
+            let mut table = Table::new();
+            table.set_content_arrangement(ContentArrangement::Dynamic);
+            table.set_header(vec!["Time", "Caller", "ms", "Rows", "Statement", "Hash"]);
+
+            for query in queries {
+                let rows = match (query.row_count, &query.error_message) {
+                    (_, Some(_)) => "error".red().to_string(),
+                    (Some(rows), None) => rows.to_string(),
+                    (None, None) => String::new(),
+                };
+                let statement = if query.statement.chars().count() > 80 {
+                    format!(
+                        "{}...",
+                        query.statement.chars().take(77).collect::<String>()
+                    )
+                } else {
+                    query.statement
+                };
+
+                table.add_row(vec![
+                    format_timestamp(query.timestamp),
+                    query.caller,
+                    query.duration_ms.to_string(),
+                    rows,
+                    statement,
+                    query.query_hash.dimmed().to_string(),
+                ]);
+            }
+            for column in 2..4 {
+                if let Some(column) = table.column_mut(column) {
+                    column.set_cell_alignment(CellAlignment::Right);
+                }
+            }
+
+            println!("{}", table);
+        }
         LogsCommands::Clear {
             older_than_days,
             force,
@@ -159,6 +314,81 @@ pub fn run(command: LogsCommands) -> Result<()> {
                 println!("Deleted {} log entries", deleted);
             }
         }
+        LogsCommands::Purge { force, json } => {
+            let service = get_logging_service()?;
+
+            if !force && !json {
+                use dialoguer::Confirm;
+                if !Confirm::new()
+                    .with_prompt("Delete all log history?")
+                    .default(false)
+                    .interact()?
+                {
+                    println!("Cancelled.");
+                    return Ok(());
+                }
+            }
+
+            let deleted = service.purge()?;
+
+            if json {
+                println!("{}", serde_json::json!({"deleted": deleted}));
+            } else {
+                println!("Deleted all {} log entries", deleted);
+            }
+        }
+        LogsCommands::Level {
+            level,
+            category,
+            json,
+        } => {
+            let treeline_dir = get_treeline_dir();
+            let mut config = Config::load(&treeline_dir)?;
+            let category = category.as_deref().map(parse_category).transpose()?;
+
+            if let Some(level) = level {
+                match (category, level.as_str()) {
+                    (Some(category), "default") => {
+                        config.logging.categories.remove(&category);
+                    }
+                    (Some(category), level) => {
+                        config
+                            .logging
+                            .categories
+                            .insert(category, parse_level(level)?);
+                    }
+                    (None, level) => config.logging.level = parse_level(level)?,
+                }
+                config.save(&treeline_dir)?;
+            }
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&config.logging)?);
+                return Ok(());
+            }
+
+            let name = |level: LogLevel| match level {
+                LogLevel::Errors => "errors",
+                LogLevel::Actions => "actions",
+                LogLevel::Full => "full",
+            };
+            println!("{}", "Log Levels".bold());
+            println!("  Default: {}", name(config.logging.level));
+            for (label, category) in [
+                ("ui", LogCategory::Ui),
+                ("cli", LogCategory::Cli),
+                ("sync", LogCategory::Sync),
+                ("app", LogCategory::App),
+            ] {
+                let own = config.logging.categories.contains_key(&category);
+                println!(
+                    "  {:<8} {}{}",
+                    format!("{}:", label),
+                    name(config.logging.level_for(category)),
+                    if own { "" } else { " (default)" }
+                );
+            }
+        }
         LogsCommands::Stats { json } => {
             let service = get_logging_service()?;
             let total = service.count()?;
@@ -184,6 +414,46 @@ pub fn run(command: LogsCommands) -> Result<()> {
                 println!("  Size: {} bytes", size_bytes);
             }
         }
+        LogsCommands::Ping {
+            preview,
+            enable,
+            disable,
+            json,
+        } => {
+            let service = get_usage_ping_service();
+            if enable || disable {
+                service.set_enabled(enable)?;
+            }
+
+            if preview {
+                // Always JSON: this is the exact payload
+                let logger = get_logging_service().ok();
+                let ping = service.build(logger.as_ref())?;
+                println!("{}", serde_json::to_string_pretty(&ping)?);
+                return Ok(());
+            }
+
+            let status = service.status()?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&status)?);
+                return Ok(());
+            }
+
+            if status.enabled {
+                println!("Usage ping: {}", "on".green());
+                println!("  Sent weekly to {}", status.url);
+                match status.last_sent_at {
+                    Some(at) => {
+                        println!("  Last sent: {}", format_timestamp(at.timestamp_millis()))
+                    }
+                    None => println!("  Last sent: never"),
+                }
+            } else {
+                println!("Usage ping: {}", "off".dimmed());
+                println!("  Opt in with `tl logs ping --enable`");
+            }
+            println!("  See exactly what is sent with `tl logs ping --preview`");
+        }
     }
 
     Ok(())
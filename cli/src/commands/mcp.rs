@@ -0,0 +1,209 @@
+//! MCP command - read-only tools for AI assistants over the Model Context Protocol
+//!
+//! Speaks JSON-RPC 2.0 over stdio, one message per line, as MCP clients
+//! expect when they launch `tl mcp` themselves. Only the curated tools from
+//! `ToolService` are exposed; there is no raw SQL tool. Stdout carries
+//! protocol messages only, so nothing else may print to it.
+
+use std::io::{self, BufRead, Write};
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use treeline_core::services::ToolService;
+use treeline_core::{LogEvent, QueryResult};
+
+use super::{get_context, get_logger, log_event};
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+#[derive(Deserialize)]
+struct Request {
+    /// Absent for notifications, which get no response
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Deserialize)]
+struct ToolCallParams {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+pub fn run() -> Result<()> {
+    let ctx = get_context()?;
+    let logger = get_logger();
+    log_event(&logger, LogEvent::new("mcp_started").with_command("mcp"));
+
+    let call_tool = |name: &str, arguments: Value| {
+        let result = ctx.tool_service.call(name, arguments);
+        // Tool names only: arguments can contain search text
+        let event = LogEvent::new("mcp_tool_called").with_command(format!("mcp {}", name));
+        let event = match &result {
+            Ok(_) => event,
+            Err(e) => event.with_error(e.to_string()),
+        };
+        log_event(&logger, event);
+        result
+    };
+
+    let stdin = io::stdin();
+    let mut stdout = io::stdout().lock();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_message(&line, &call_tool) {
+            writeln!(stdout, "{}", response)?;
+            stdout.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle one incoming line; returns the response to send, if any
+fn handle_message(
+    line: &str,
+    call_tool: &dyn Fn(&str, Value) -> Result<QueryResult>,
+) -> Option<Value> {
+    let request: Request = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, &e.to_string())),
+    };
+    let id = request.id?;
+
+    let response = match request.method.as_str() {
+        "initialize" => success_response(
+            id,
+            json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {"tools": {}},
+                "serverInfo": {"name": "treeline", "version": env!("CARGO_PKG_VERSION")},
+            }),
+        ),
+        "ping" => success_response(id, json!({})),
+        "tools/list" => {
+            let tools: Vec<Value> = ToolService::definitions()
+                .into_iter()
+                .map(|tool| {
+                    json!({
+                        "name": tool.name,
+                        "description": tool.description,
+                        "inputSchema": tool.input_schema,
+                    })
+                })
+                .collect();
+            success_response(id, json!({"tools": tools}))
+        }
+        "tools/call" => match serde_json::from_value::<ToolCallParams>(request.params) {
+            Ok(params) => {
+                success_response(id, tool_result(call_tool(&params.name, params.arguments)))
+            }
+            Err(e) => error_response(id, INVALID_PARAMS, &e.to_string()),
+        },
+        method => error_response(
+            id,
+            METHOD_NOT_FOUND,
+            &format!("Method not found: {}", method),
+        ),
+    };
+    Some(response)
+}
+
+/// Tool output as MCP content; failures are reported to the model, not as protocol errors
+fn tool_result(result: Result<QueryResult>) -> Value {
+    match result {
+        Ok(result) => {
+            let records: Vec<serde_json::Map<String, Value>> = result
+                .rows
+                .into_iter()
+                .map(|row| result.columns.iter().cloned().zip(row).collect())
+                .collect();
+            let text = serde_json::to_string_pretty(&records).unwrap_or_default();
+            json!({"content": [{"type": "text", "text": text}], "isError": false})
+        }
+        Err(e) => {
+            json!({"content": [{"type": "text", "text": format!("{:#}", e)}], "isError": true})
+        }
+    }
+}
+
+fn success_response(id: Value, result: Value) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "result": result})
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_tools(_: &str, _: Value) -> Result<QueryResult> {
+        anyhow::bail!("Unknown tool")
+    }
+
+    fn one_account(_: &str, _: Value) -> Result<QueryResult> {
+        Ok(QueryResult {
+            columns: vec!["name".to_string(), "balance".to_string()],
+            rows: vec![vec![json!("Checking"), json!(120.5)]],
+            row_count: 1,
+        })
+    }
+
+    #[test]
+    fn test_initialize_and_notifications() {
+        let response = handle_message(
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#,
+            &no_tools,
+        )
+        .unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["protocolVersion"], PROTOCOL_VERSION);
+
+        // Notifications have no id and get no response
+        assert!(handle_message(
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+            &no_tools,
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_protocol_errors() {
+        let response = handle_message("not json", &no_tools).unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+
+        let response = handle_message(
+            r#"{"jsonrpc":"2.0","id":2,"method":"resources/list"}"#,
+            &no_tools,
+        )
+        .unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_tool_call_results() {
+        let call =
+            r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"list_accounts"}}"#;
+
+        let response = handle_message(call, &one_account).unwrap();
+        assert_eq!(response["result"]["isError"], false);
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        let records: Value = serde_json::from_str(text).unwrap();
+        assert_eq!(records[0]["name"], "Checking");
+
+        let response = handle_message(call, &no_tools).unwrap();
+        assert_eq!(response["result"]["isError"], true);
+    }
+}
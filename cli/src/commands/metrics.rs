@@ -0,0 +1,33 @@
+//! Metrics command - Prometheus metrics for sync and storage health
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use treeline_core::json_file::write_atomic;
+use treeline_core::services::MetricsService;
+
+use super::{get_context, get_treeline_dir};
+
+pub fn run(output: Option<PathBuf>) -> Result<()> {
+    let ctx = get_context()?;
+    let db_filename = if ctx.config.demo_mode {
+        "demo.duckdb"
+    } else {
+        "treeline.duckdb"
+    };
+    let service = MetricsService::new(
+        Arc::clone(&ctx.repository),
+        get_treeline_dir(),
+        db_filename.to_string(),
+    );
+    let metrics = service.render()?;
+
+    match output {
+        // Atomic so a collector never reads a partial file
+        Some(path) => write_atomic(&path, metrics)?,
+        None => print!("{}", metrics),
+    }
+
+    Ok(())
+}
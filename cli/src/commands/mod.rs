@@ -1,24 +1,46 @@
 //! CLI command implementations
 
+pub mod accounts;
+pub mod archive;
 pub mod backup;
+pub mod balance;
+pub mod cdc;
 pub mod compact;
 pub mod demo;
+pub mod dev;
+pub mod device_sync;
 pub mod doctor;
 pub mod encrypt;
+pub mod holdings;
 pub mod import;
+pub mod income;
 pub mod logs;
+pub mod mcp;
+pub mod metrics;
+pub mod move_data;
 pub mod plugin;
+pub mod publish;
 pub mod query;
+pub mod report;
+pub mod schema;
 pub mod setup;
 pub mod status;
+pub mod storage;
 pub mod sync;
 pub mod tag;
+pub mod token;
+pub mod tx;
 pub mod update;
 
 use anyhow::{Context, Result};
+use colored::Colorize;
 use std::path::PathBuf;
-use treeline_core::services::EncryptionService;
-use treeline_core::{EntryPoint, LogEvent, LoggingService, TreelineContext};
+use std::sync::OnceLock;
+use treeline_core::services::{
+    detect_cloud_sync, portable_data_dir, resolve_data_dir, BackupService, EncryptionService,
+    TokenGrant, TokenService,
+};
+use treeline_core::{EncryptionKey, EntryPoint, LogEvent, LoggingService, TreelineContext};
 
 /// Get the logging service for CLI operations
 ///
@@ -38,29 +60,57 @@ pub fn log_event(logger: &Option<LoggingService>, event: LogEvent) {
 }
 
 /// Get the treeline directory from environment or default
+///
+/// `TREELINE_DIR` wins, then portable mode's `data` folder beside the
+/// executable, then `~/.treeline`. Follows the pointer left by
+/// `tl move-data`, if any.
 pub fn get_treeline_dir() -> PathBuf {
-    if let Ok(dir) = std::env::var("TREELINE_DIR") {
+    let dir = if let Ok(dir) = std::env::var("TREELINE_DIR") {
         PathBuf::from(dir)
+    } else if let Some(dir) = portable_data_dir() {
+        dir
     } else {
         dirs::home_dir()
             .expect("Could not find home directory")
             .join(".treeline")
+    };
+    resolve_data_dir(dir)
+}
+
+/// Warn (once per process) when the data directory is in a cloud-synced folder
+fn warn_if_cloud_synced(treeline_dir: &std::path::Path) {
+    static WARNED: OnceLock<()> = OnceLock::new();
+    if let Some(provider) = detect_cloud_sync(treeline_dir) {
+        WARNED.get_or_init(|| {
+            eprintln!(
+                "{}",
+                format!(
+                    "Warning: {} is inside {}, which can corrupt the database.\n\
+                     Move it with `tl move-data <folder>`.",
+                    treeline_dir.display(),
+                    provider.name()
+                )
+                .red()
+                .bold()
+            );
+        });
     }
 }
 
 /// Get or create treeline context
 pub fn get_context() -> Result<TreelineContext> {
     let treeline_dir = get_treeline_dir();
+    warn_if_cloud_synced(&treeline_dir);
 
     // Create directory if it doesn't exist
     std::fs::create_dir_all(&treeline_dir)
         .with_context(|| format!("Failed to create treeline directory: {:?}", treeline_dir))?;
 
     // Determine encryption key
-    // Priority: TL_DB_KEY (pre-derived) > TL_DB_PASSWORD (needs derivation)
+    // Priority: TL_DB_KEY (pre-derived) > TL_DB_PASSWORD (needs derivation) > TL_TOKEN
     let encryption_key = if let Ok(key) = std::env::var("TL_DB_KEY") {
         // Already derived key (used by Tauri app)
-        Some(key)
+        Some(EncryptionKey::new(key))
     } else if let Ok(password) = std::env::var("TL_DB_PASSWORD") {
         // Password that needs derivation
         let config = treeline_core::config::Config::load(&treeline_dir).unwrap_or_default();
@@ -76,20 +126,136 @@ pub fn get_context() -> Result<TreelineContext> {
 
         if is_encrypted {
             // Derive key from password
-            match encryption_service.derive_key_for_connection(&password) {
-                Ok(key) => Some(key),
+            let key = match encryption_service.derive_key_for_connection(&password) {
+                Ok(key) => key,
                 Err(e) => {
                     return Err(e).context("Failed to derive encryption key from password");
                 }
+            };
+
+            // Re-derive with the calibrated params if the database uses weaker ones.
+            // If the upgrade fails, keep going with the old key.
+            if encryption_service.needs_param_upgrade().unwrap_or(false) {
+                let backup_service =
+                    BackupService::new(treeline_dir.clone(), db_filename.to_string());
+                match encryption_service.upgrade_params(&password, &backup_service) {
+                    Ok(new_key) => Some(new_key),
+                    Err(e) => {
+                        log_event(
+                            &get_logger(),
+                            LogEvent::new("encryption_upgrade_failed").with_error(&e.to_string()),
+                        );
+                        Some(key)
+                    }
+                }
+            } else {
+                Some(key)
             }
         } else {
             // Database not encrypted, don't need a key
             None
         }
+    } else if let Some(grant) = token_grant()? {
+        grant.encryption_key.clone()
     } else {
         None
     };
 
-    TreelineContext::new(&treeline_dir, encryption_key.as_deref())
-        .context("Failed to initialize treeline context")
+    let password = encryption_key.as_ref().map(EncryptionKey::expose);
+    if read_only_token()? {
+        TreelineContext::open_read_only(&treeline_dir, password)
+    } else {
+        TreelineContext::new(&treeline_dir, password)
+    }
+    .context("Failed to initialize treeline context")
+}
+
+/// Grant for the TL_TOKEN access token, checked once per process
+///
+/// Each check records a use of the token, so it is cached rather than
+/// re-authenticated by every caller.
+fn token_grant() -> Result<Option<&'static TokenGrant>> {
+    static GRANT: OnceLock<Option<TokenGrant>> = OnceLock::new();
+    if let Some(grant) = GRANT.get() {
+        return Ok(grant.as_ref());
+    }
+
+    let grant = match std::env::var("TL_TOKEN") {
+        Ok(token) => {
+            let logger = get_logger();
+            match TokenService::new(get_treeline_dir()).authenticate(&token) {
+                Ok(grant) => {
+                    log_event(&logger, LogEvent::new("token_used"));
+                    Some(grant)
+                }
+                Err(e) => {
+                    log_event(
+                        &logger,
+                        LogEvent::new("token_rejected").with_error(&e.to_string()),
+                    );
+                    return Err(e).context("TL_TOKEN was rejected");
+                }
+            }
+        }
+        Err(_) => None,
+    };
+    Ok(GRANT.get_or_init(|| grant).as_ref())
+}
+
+/// Commands a read-only TL_TOKEN may run, as `tl` subcommand paths
+///
+/// Everything else is refused before it starts. These still only get a
+/// read-only database, so a write one of them attempts fails there.
+const READ_ONLY_COMMANDS: &[&str] = &[
+    "accounts list",
+    "accounts show",
+    "archive list",
+    "backup list",
+    "cdc serve",
+    "cdc tail",
+    "demo",
+    "demo status",
+    "device-sync status",
+    "doctor",
+    "encrypt status",
+    "holdings",
+    "income",
+    "logs list",
+    "logs queries",
+    "logs stats",
+    "mcp",
+    "metrics",
+    "plugin list",
+    "publish",
+    "query",
+    "report cards",
+    "report compare",
+    "report flows",
+    "report statement",
+    "schema dump",
+    "schema list",
+    "setup",
+    "setup accounts",
+    "setup status",
+    "status",
+    "storage show",
+    "sync history",
+    "tx duplicates",
+    "tx list",
+    "tx show",
+];
+
+/// Whether TL_TOKEN is set and read-only
+fn read_only_token() -> Result<bool> {
+    Ok(token_grant()?.is_some_and(|grant| !grant.scope.allows_writes()))
+}
+
+/// Fail when a read-only TL_TOKEN doesn't allow `command`
+///
+/// `command` is the subcommand path, e.g. `backup list`.
+pub fn check_token_scope(command: &str) -> Result<()> {
+    if read_only_token()? && !READ_ONLY_COMMANDS.contains(&command) {
+        anyhow::bail!("`tl {}` isn't allowed with a read-only TL_TOKEN", command);
+    }
+    Ok(())
 }
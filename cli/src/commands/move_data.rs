@@ -0,0 +1,78 @@
+//! Move-data command - relocate the data directory out of a cloud-synced folder
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use colored::Colorize;
+use dialoguer::Confirm;
+use treeline_core::services::{detect_cloud_sync, DataDirService};
+use treeline_core::LogEvent;
+
+use super::{get_logger, get_treeline_dir, log_event};
+
+pub fn run(destination: PathBuf, allow_cloud_sync: bool, yes: bool, json: bool) -> Result<()> {
+    let treeline_dir = get_treeline_dir();
+    let service = DataDirService::new(treeline_dir.clone());
+
+    if !yes && !json {
+        println!("Current location: {}", treeline_dir.display());
+        match service.cloud_sync_provider() {
+            Some(provider) => println!(
+                "  {}",
+                format!("Inside {} - this can corrupt the database", provider.name()).red()
+            ),
+            None => println!("  {}", "Not in a cloud-synced folder".dimmed()),
+        }
+        println!("New location:     {}", destination.display());
+        if let Some(provider) = detect_cloud_sync(&destination) {
+            println!("  {}", format!("Inside {}", provider.name()).red());
+        }
+        println!();
+        println!(
+            "Close the Treeline app before continuing; the move stops if a database is in use."
+        );
+
+        if !Confirm::new()
+            .with_prompt("Move all Treeline data?")
+            .default(false)
+            .interact()?
+        {
+            println!("Cancelled.");
+            return Ok(());
+        }
+    }
+
+    let result = service.move_to(&destination, allow_cloud_sync)?;
+
+    // Logged after the move so the entry lands in the new location
+    log_event(
+        &get_logger(),
+        LogEvent::new("data_directory_moved").with_command("move-data"),
+    );
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        format!(
+            "Moved {} files ({} bytes) to {}",
+            result.files_moved,
+            result.bytes_moved,
+            result.to.display()
+        )
+        .green()
+    );
+    println!(
+        "{}",
+        format!(
+            "{} now only points to the new location; the CLI and app follow it automatically.",
+            result.from.display()
+        )
+        .dimmed()
+    );
+
+    Ok(())
+}
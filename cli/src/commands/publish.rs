@@ -0,0 +1,60 @@
+//! Publish command - push balances and sync freshness to Home Assistant
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use colored::Colorize;
+use comfy_table::{ContentArrangement, Table};
+use treeline_core::services::PublishService;
+use treeline_core::LogEvent;
+
+use super::{get_context, get_logger, get_treeline_dir, log_event};
+
+pub fn run(dry_run: bool, json: bool) -> Result<()> {
+    let ctx = get_context()?;
+    let service = PublishService::new(Arc::clone(&ctx.repository), get_treeline_dir());
+    let logger = get_logger();
+
+    let result = service.publish(dry_run);
+    if !dry_run {
+        let event = LogEvent::new("sensors_published").with_command("publish");
+        let event = match &result {
+            Ok(_) => event,
+            Err(e) => event.with_error(e.to_string()),
+        };
+        log_event(&logger, event);
+    }
+    let result = result?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec!["Entity", "State", "Unit"]);
+    for sensor in &result.sensors {
+        table.add_row(vec![
+            sensor.entity_id.clone(),
+            sensor.state.clone(),
+            sensor.unit.clone().unwrap_or_default(),
+        ]);
+    }
+    println!("{}", table);
+
+    if result.published {
+        println!(
+            "{}",
+            format!(
+                "Published {} sensors to Home Assistant",
+                result.sensors.len()
+            )
+            .green()
+        );
+    } else {
+        println!("{}", "Dry run: nothing was sent".dimmed());
+    }
+
+    Ok(())
+}
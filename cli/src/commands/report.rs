@@ -0,0 +1,400 @@
+//! Report command - printable account statements, period comparisons, card cycles
+//! and cash flows
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chrono::{Datelike, Local};
+use clap::Subcommand;
+use colored::Colorize;
+use comfy_table::{CellAlignment, ContentArrangement, Table};
+use rust_decimal::Decimal;
+use treeline_core::json_file::write_atomic;
+use treeline_core::services::{CompareBy, FlowNodeKind, Period};
+
+use super::get_context;
+
+#[derive(Subcommand)]
+pub enum ReportCommands {
+    /// Monthly statement for one account: balances, transactions and categories
+    Statement {
+        /// Account ID or name
+        account: String,
+        /// Month as YYYY-MM (defaults to last month)
+        #[arg(long, short = 'm')]
+        month: Option<String>,
+        /// Write a printable HTML statement to this file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Compare two periods per category or account, e.g. this quarter vs last
+    Compare {
+        /// Period: YYYY, YYYY-Qn, YYYY-MM, YYYY-MM-DD or START..END
+        current: String,
+        /// Period to compare against (defaults to the one just before)
+        previous: Option<String>,
+        /// Compare against the same period a year earlier
+        #[arg(long, short = 'y', conflicts_with = "previous")]
+        year_over_year: bool,
+        /// Group by: category or account
+        #[arg(long, default_value = "category")]
+        by: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Credit card statements: spending on the open one, payment due on the last
+    Cards {
+        /// Only cards with a payment due within this many days (or overdue)
+        #[arg(long)]
+        due_within: Option<i64>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Where money came from and went: income, categories and merchants
+    Flows {
+        /// Period: YYYY, YYYY-Qn, YYYY-MM, YYYY-MM-DD or START..END (defaults to last month)
+        period: Option<String>,
+        /// Output as JSON nodes and links, ready for a Sankey diagram
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// `no_cache` recomputes reports instead of reusing results cached since the last write
+pub fn run(command: ReportCommands, no_cache: bool) -> Result<()> {
+    match command {
+        ReportCommands::Statement {
+            account,
+            month,
+            output,
+            json,
+        } => run_statement(&account, month, output, json, no_cache),
+        ReportCommands::Compare {
+            current,
+            previous,
+            year_over_year,
+            by,
+            json,
+        } => run_compare(
+            &current,
+            previous.as_deref(),
+            year_over_year,
+            &by,
+            json,
+            no_cache,
+        ),
+        ReportCommands::Cards { due_within, json } => run_cards(due_within, json, no_cache),
+        ReportCommands::Flows { period, json } => run_flows(period, json, no_cache),
+    }
+}
+
+fn run_statement(
+    account: &str,
+    month: Option<String>,
+    output: Option<PathBuf>,
+    json: bool,
+    no_cache: bool,
+) -> Result<()> {
+    let ctx = get_context()?;
+    let account_id = ctx.import_service.resolve_account(account)?;
+    let month = month.unwrap_or_else(last_month);
+    let statement = ctx
+        .report_service
+        .with_cache(!no_cache)
+        .generate_statement(&account_id, &month)?;
+
+    if let Some(path) = output {
+        write_atomic(&path, statement.to_html())?;
+        if json {
+            println!(
+                "{}",
+                serde_json::json!({"path": path, "transactions": statement.transactions.len()})
+            );
+        } else {
+            println!("{} {}", "Statement written to".green(), path.display());
+            println!("  Open it in a browser and print to save as PDF.");
+        }
+        return Ok(());
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&statement)?);
+        return Ok(());
+    }
+
+    let balance = |b: Option<Decimal>| {
+        b.map(|b| format!("{:.2}", b))
+            .unwrap_or_else(|| "unknown".dimmed().to_string())
+    };
+    println!(
+        "{} - {}",
+        statement.account_name.bold(),
+        statement.period_start.format("%B %Y")
+    );
+    println!("  Opening balance: {}", balance(statement.opening_balance));
+    println!("  Credits:         {:.2}", statement.total_credits);
+    println!("  Debits:          {:.2}", statement.total_debits);
+    println!("  Closing balance: {}", balance(statement.closing_balance));
+    println!();
+
+    if statement.transactions.is_empty() {
+        println!("No transactions in this period.");
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec!["Date", "Description", "Category", "Amount", "Balance"]);
+    for line in &statement.transactions {
+        table.add_row(vec![
+            line.date.to_string(),
+            line.description.clone(),
+            line.category.clone(),
+            format!("{:.2}", line.amount),
+            line.balance
+                .map(|b| format!("{:.2}", b))
+                .unwrap_or_default(),
+        ]);
+    }
+    for column in 3..5 {
+        if let Some(column) = table.column_mut(column) {
+            column.set_cell_alignment(CellAlignment::Right);
+        }
+    }
+    println!("{}", table);
+
+    let mut categories = Table::new();
+    categories.set_content_arrangement(ContentArrangement::Dynamic);
+    categories.set_header(vec!["Category", "Transactions", "Total"]);
+    for category in &statement.categories {
+        categories.add_row(vec![
+            category.category.clone(),
+            category.count.to_string(),
+            format!("{:.2}", category.total),
+        ]);
+    }
+    println!("{}", categories);
+
+    Ok(())
+}
+
+fn run_compare(
+    current: &str,
+    previous: Option<&str>,
+    year_over_year: bool,
+    by: &str,
+    json: bool,
+    no_cache: bool,
+) -> Result<()> {
+    let group_by = match by {
+        "category" => CompareBy::Category,
+        "account" => CompareBy::Account,
+        other => anyhow::bail!("Unknown grouping '{}', expected category or account", other),
+    };
+    let current = Period::parse(current)?;
+    let previous = match previous {
+        Some(previous) => Period::parse(previous)?,
+        None if year_over_year => current.year_earlier(),
+        None => current.preceding(),
+    };
+
+    let ctx = get_context()?;
+    let comparison = ctx
+        .report_service
+        .with_cache(!no_cache)
+        .compare_periods(current, previous, group_by)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&comparison)?);
+        return Ok(());
+    }
+
+    let period = |p: &Period| format!("{} to {}", p.start, p.end);
+    println!(
+        "{} vs {}",
+        period(&comparison.current).bold(),
+        period(&comparison.previous)
+    );
+
+    if comparison.rows.is_empty() {
+        println!("No transactions in either period.");
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        if group_by == CompareBy::Account {
+            "Account"
+        } else {
+            "Category"
+        },
+        "Current",
+        "Previous",
+        "Change",
+        "Change %",
+    ]);
+    for row in comparison.rows.iter().chain([&comparison.total]) {
+        let percent = row
+            .change_percent
+            .map(|p| format!("{:+.1}%", p))
+            .unwrap_or_else(|| "-".to_string());
+        table.add_row(vec![
+            row.label.clone(),
+            format!("{:.2}", row.current),
+            format!("{:.2}", row.previous),
+            format!("{:+.2}", row.change),
+            percent,
+        ]);
+    }
+    for column in 1..5 {
+        if let Some(column) = table.column_mut(column) {
+            column.set_cell_alignment(CellAlignment::Right);
+        }
+    }
+    println!("{}", table);
+
+    Ok(())
+}
+
+/// The last complete month, as YYYY-MM
+fn last_month() -> String {
+    let today = Local::now().date_naive();
+    if today.month() == 1 {
+        format!("{}-12", today.year() - 1)
+    } else {
+        format!("{}-{:02}", today.year(), today.month() - 1)
+    }
+}
+
+fn run_cards(due_within: Option<i64>, json: bool, no_cache: bool) -> Result<()> {
+    let ctx = get_context()?;
+    let mut cards = ctx
+        .report_service
+        .with_cache(!no_cache)
+        .card_cycles(Local::now().date_naive())?;
+    if let Some(days) = due_within {
+        cards.retain(|card| {
+            card.days_until_due <= days && card.payment_due.is_some_and(|due| due > Decimal::ZERO)
+        });
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&cards)?);
+        return Ok(());
+    }
+
+    if cards.is_empty() {
+        match due_within {
+            Some(days) => println!("No card payments due within {} days.", days),
+            None => println!(
+                "No credit cards with a statement cycle. Set one with `tl accounts set-cycle`."
+            ),
+        }
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        "Card",
+        "Open statement",
+        "Spent",
+        "Last statement",
+        "Paid",
+        "Due",
+        "Due date",
+    ]);
+    let money = |amount: Option<Decimal>| {
+        amount
+            .map(|a| format!("{:.2}", a))
+            .unwrap_or_else(|| "unknown".to_string())
+    };
+    for card in &cards {
+        let due_date = match card.days_until_due {
+            days if days < 0 => format!("{} ({} days ago)", card.due_date, -days),
+            0 => format!("{} (today)", card.due_date),
+            days => format!("{} (in {} days)", card.due_date, days),
+        };
+        table.add_row(vec![
+            card.account_name.clone(),
+            format!("{} to {}", card.current.start, card.current.end),
+            format!("{:.2}", card.current_spend),
+            money(card.statement_balance),
+            format!("{:.2}", card.paid_since_closing),
+            money(card.payment_due),
+            due_date,
+        ]);
+    }
+    for column in 2..6 {
+        if let Some(column) = table.column_mut(column) {
+            column.set_cell_alignment(CellAlignment::Right);
+        }
+    }
+    println!("{}", table);
+
+    for card in &cards {
+        if let Some(due) = card.payment_due.filter(|due| *due > Decimal::ZERO) {
+            if card.days_until_due < 0 {
+                println!(
+                    "{}",
+                    format!(
+                        "{}: {:.2} {} was due {}",
+                        card.account_name, due, card.currency, card.due_date
+                    )
+                    .red()
+                );
+            } else if card.days_until_due <= 7 {
+                println!(
+                    "{}",
+                    format!(
+                        "{}: {:.2} {} due {}",
+                        card.account_name, due, card.currency, card.due_date
+                    )
+                    .yellow()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run_flows(period: Option<String>, json: bool, no_cache: bool) -> Result<()> {
+    let period = Period::parse(&period.unwrap_or_else(last_month))?;
+    let ctx = get_context()?;
+    let flows = ctx
+        .report_service
+        .with_cache(!no_cache)
+        .cash_flows(period)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&flows)?);
+        return Ok(());
+    }
+
+    println!("{}", format!("{} to {}", period.start, period.end).bold());
+    if flows.links.is_empty() {
+        println!("No income or spending in this period.");
+        return Ok(());
+    }
+
+    for node in &flows.nodes {
+        let line = format!("{:>12.2}  {}", node.value, node.label);
+        match node.kind {
+            FlowNodeKind::Source => println!("  {}", line.green()),
+            FlowNodeKind::Shortfall => println!("  {}", line.red()),
+            FlowNodeKind::Income => println!("{}", line.bold()),
+            FlowNodeKind::Category => println!("  {}", line),
+            FlowNodeKind::Merchant => println!("    {}", line.dimmed()),
+            FlowNodeKind::Saved => println!("{}", line.green().bold()),
+        }
+    }
+
+    Ok(())
+}
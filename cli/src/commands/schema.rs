@@ -0,0 +1,102 @@
+//! Schema command - JSON Schemas for `--json` output
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use colored::Colorize;
+use comfy_table::{ContentArrangement, Table};
+use treeline_core::schema::{output_schemas, OutputSchema};
+
+use super::compact::CompactOutput;
+
+#[derive(Subcommand)]
+pub enum SchemaCommands {
+    /// List the available schemas and the commands they describe
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print JSON Schemas, or write one file per schema with --out
+    Dump {
+        /// Only this schema (e.g. SyncResult); all schemas if omitted
+        name: Option<String>,
+        /// Directory to write <Name>.schema.json files to
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+/// Core schemas plus the CLI's own output types
+fn all_schemas() -> Vec<OutputSchema> {
+    let mut schemas = output_schemas();
+    schemas.push(OutputSchema::of::<CompactOutput>("tl compact"));
+    schemas
+}
+
+pub fn run(command: SchemaCommands) -> Result<()> {
+    match command {
+        SchemaCommands::List { json } => {
+            let schemas = all_schemas();
+
+            if json {
+                let list: Vec<_> = schemas
+                    .iter()
+                    .map(|s| serde_json::json!({"name": s.name, "command": s.command}))
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&list)?);
+                return Ok(());
+            }
+
+            let mut table = Table::new();
+            table.set_content_arrangement(ContentArrangement::Dynamic);
+            table.set_header(vec!["Schema", "Output of (--json)"]);
+            for schema in &schemas {
+                table.add_row(vec![schema.name.clone(), schema.command.to_string()]);
+            }
+            println!("{}", table);
+        }
+        SchemaCommands::Dump { name, out } => {
+            let mut schemas = all_schemas();
+            if let Some(name) = &name {
+                schemas.retain(|s| s.name.eq_ignore_ascii_case(name));
+                if schemas.is_empty() {
+                    anyhow::bail!(
+                        "Unknown schema '{}'. Run `tl schema list` to see available schemas.",
+                        name
+                    );
+                }
+            }
+
+            match out {
+                Some(dir) => {
+                    fs::create_dir_all(&dir)
+                        .with_context(|| format!("Failed to create {}", dir.display()))?;
+                    for schema in &schemas {
+                        let path = dir.join(format!("{}.schema.json", schema.name));
+                        fs::write(&path, serde_json::to_string_pretty(&schema.schema)?)
+                            .with_context(|| format!("Failed to write {}", path.display()))?;
+                    }
+                    println!(
+                        "{}",
+                        format!("Wrote {} schemas to {}", schemas.len(), dir.display()).green()
+                    );
+                }
+                None if name.is_some() => {
+                    println!("{}", serde_json::to_string_pretty(&schemas[0].schema)?);
+                }
+                None => {
+                    let all: serde_json::Map<String, serde_json::Value> = schemas
+                        .into_iter()
+                        .map(|s| Ok((s.name, serde_json::to_value(s.schema)?)))
+                        .collect::<Result<_>>()?;
+                    println!("{}", serde_json::to_string_pretty(&all)?);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -5,11 +5,26 @@ use clap::Subcommand;
 use colored::Colorize;
 
 use super::{get_context, get_logger, get_treeline_dir, log_event};
+use treeline_core::adapters::plaid::PlaidToken;
 use treeline_core::LogEvent;
 
 /// Environment variable for Lunchflow API key
 const LUNCHFLOW_API_KEY_ENV: &str = "LUNCHFLOW_API_KEY";
 
+/// Environment variables for Plaid API keys
+const PLAID_CLIENT_ID_ENV: &str = "PLAID_CLIENT_ID";
+const PLAID_SECRET_ENV: &str = "PLAID_SECRET";
+
+/// Environment variables for GoCardless user secrets
+const GOCARDLESS_SECRET_ID_ENV: &str = "GOCARDLESS_SECRET_ID";
+const GOCARDLESS_SECRET_KEY_ENV: &str = "GOCARDLESS_SECRET_KEY";
+
+/// Environment variables for crypto exchange API keys
+const COINBASE_API_KEY_ENV: &str = "COINBASE_API_KEY";
+const COINBASE_API_SECRET_ENV: &str = "COINBASE_API_SECRET";
+const KRAKEN_API_KEY_ENV: &str = "KRAKEN_API_KEY";
+const KRAKEN_API_SECRET_ENV: &str = "KRAKEN_API_SECRET";
+
 #[derive(Subcommand)]
 pub enum SetupCommands {
     /// Set up SimpleFIN integration
@@ -27,12 +42,127 @@ pub enum SetupCommands {
         #[arg(long)]
         base_url: Option<String>,
     },
+    /// Set up Plaid integration, linking one bank (run again for each bank)
+    #[command(name = "plaid")]
+    Plaid {
+        /// Public token from Plaid Link
+        #[arg(long, conflicts_with = "access_token")]
+        public_token: Option<String>,
+        /// Access token already exchanged for a bank (e.g. with Plaid's quickstart app)
+        #[arg(long)]
+        access_token: Option<String>,
+        /// Client ID from the Plaid dashboard (or set PLAID_CLIENT_ID env var)
+        #[arg(long)]
+        client_id: Option<String>,
+        /// Secret from the Plaid dashboard (or set PLAID_SECRET env var)
+        #[arg(long)]
+        secret: Option<String>,
+        /// Plaid environment: sandbox or production
+        #[arg(long, default_value = "production")]
+        environment: String,
+        /// Custom API base URL (for testing)
+        #[arg(long)]
+        base_url: Option<String>,
+    },
+    /// Set up GoCardless integration, connecting one European bank (run again
+    /// for each bank, and to renew access when it expires)
+    #[command(name = "gocardless")]
+    GoCardless {
+        /// Institution ID of the bank to connect, as listed with --country
+        #[arg(long, conflicts_with = "country")]
+        institution: Option<String>,
+        /// List the banks available in a country (ISO code, e.g. DE)
+        #[arg(long)]
+        country: Option<String>,
+        /// Only list banks whose name contains this
+        #[arg(long, requires = "country")]
+        search: Option<String>,
+        /// Secret ID from the GoCardless portal (or set GOCARDLESS_SECRET_ID env var)
+        #[arg(long)]
+        secret_id: Option<String>,
+        /// Secret key from the GoCardless portal (or set GOCARDLESS_SECRET_KEY env var)
+        #[arg(long)]
+        secret_key: Option<String>,
+        /// Where the bank sends you after giving consent
+        #[arg(long)]
+        redirect: Option<String>,
+        /// Custom API base URL (for testing)
+        #[arg(long)]
+        base_url: Option<String>,
+    },
+    /// Set up Coinbase integration, syncing crypto balances and transactions
+    #[command(name = "coinbase")]
+    Coinbase {
+        /// API key from the Coinbase settings (or set COINBASE_API_KEY env var)
+        #[arg(long)]
+        api_key: Option<String>,
+        /// API secret from the Coinbase settings (or set COINBASE_API_SECRET env var)
+        #[arg(long)]
+        api_secret: Option<String>,
+        /// Currency to value balances in (defaults to your Coinbase currency)
+        #[arg(long)]
+        currency: Option<String>,
+        /// Custom API base URL (for testing)
+        #[arg(long)]
+        base_url: Option<String>,
+    },
+    /// Set up Kraken integration, syncing crypto balances
+    #[command(name = "kraken")]
+    Kraken {
+        /// API key from the Kraken settings (or set KRAKEN_API_KEY env var)
+        #[arg(long)]
+        api_key: Option<String>,
+        /// Private key of the API key (or set KRAKEN_API_SECRET env var)
+        #[arg(long)]
+        api_secret: Option<String>,
+        /// Currency to value balances in (defaults to USD)
+        #[arg(long)]
+        currency: Option<String>,
+        /// Custom API base URL (for testing)
+        #[arg(long)]
+        base_url: Option<String>,
+    },
     /// Show configured integrations
     Status,
     /// Remove an integration
     Remove {
         /// Integration name to remove (e.g., simplefin, lunchflow)
         name: String,
+        /// Also unlink its accounts, keeping them as standalone accounts
+        #[arg(long)]
+        unlink_accounts: bool,
+    },
+    /// Replace an integration's credentials, keeping its account settings
+    Rotate {
+        /// Integration name (simplefin, lunchflow, plaid or gocardless;
+        /// set up coinbase and kraken again instead)
+        name: String,
+        /// New SimpleFIN setup token, Lunchflow API key, Plaid secret or
+        /// GoCardless secret key (or set LUNCHFLOW_API_KEY, PLAID_SECRET or
+        /// GOCARDLESS_SECRET_KEY env var)
+        credential: Option<String>,
+    },
+    /// List the accounts an integration's provider offers
+    Accounts {
+        /// Integration name (e.g., simplefin, lunchflow)
+        name: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Include a provider account in sync
+    Enable {
+        /// Integration name (e.g., simplefin, lunchflow)
+        name: String,
+        /// Provider account ID, as shown by 'tl setup accounts'
+        external_id: String,
+    },
+    /// Leave a provider account out of sync
+    Disable {
+        /// Integration name (e.g., simplefin, lunchflow)
+        name: String,
+        /// Provider account ID, as shown by 'tl setup accounts'
+        external_id: String,
     },
 }
 
@@ -126,6 +256,267 @@ pub fn run(command: Option<SetupCommands>) -> Result<()> {
                 }
             }
         }
+        Some(SetupCommands::Plaid {
+            public_token,
+            access_token,
+            client_id,
+            secret,
+            environment,
+            base_url,
+        }) => {
+            log_event(
+                &logger,
+                LogEvent::new("setup_started").with_integration("plaid"),
+            );
+
+            // Try to get API keys from arguments, then environment variables
+            let client_id = client_id
+                .or_else(|| std::env::var(PLAID_CLIENT_ID_ENV).ok())
+                .ok_or_else(|| plaid_keys_required)?;
+            let secret = secret
+                .or_else(|| std::env::var(PLAID_SECRET_ENV).ok())
+                .ok_or_else(|| plaid_keys_required)?;
+
+            // The sandbox can link Plaid's test bank without Plaid Link
+            let token = match (&public_token, &access_token) {
+                (Some(token), _) => PlaidToken::Public(token),
+                (None, Some(token)) => PlaidToken::Access(token),
+                (None, None) if environment == "sandbox" => PlaidToken::Sandbox,
+                (None, None) => anyhow::bail!(
+                    "A bank login is required. Provide --public-token from Plaid Link, \
+                    or --access-token for a bank you've already linked with Plaid.\n\n\
+                    The desktop app opens Plaid Link for you; use it to link a bank \
+                    without handling tokens."
+                ),
+            };
+
+            println!("Setting up Plaid integration...");
+
+            let ctx = get_context()?;
+            match ctx.sync_service.setup_plaid(
+                &client_id,
+                &secret,
+                &environment,
+                token,
+                base_url.as_deref(),
+            ) {
+                Ok(()) => {
+                    log_event(
+                        &logger,
+                        LogEvent::new("setup_completed").with_integration("plaid"),
+                    );
+                    println!("{}", "Plaid configured successfully!".green());
+                    println!();
+                    println!("Run '{}' to sync your accounts.", "tl sync".cyan());
+                    println!(
+                        "Run '{}' again to link another bank.",
+                        "tl setup plaid".cyan()
+                    );
+                    Ok(())
+                }
+                Err(e) => {
+                    log_event(
+                        &logger,
+                        LogEvent::new("setup_failed")
+                            .with_integration("plaid")
+                            .with_error(&e.to_string()),
+                    );
+                    Err(e)
+                }
+            }
+        }
+        Some(SetupCommands::GoCardless {
+            institution,
+            country,
+            search,
+            secret_id,
+            secret_key,
+            redirect,
+            base_url,
+        }) => {
+            // Try to get user secrets from arguments, then environment variables
+            let secret_id = secret_id
+                .or_else(|| std::env::var(GOCARDLESS_SECRET_ID_ENV).ok())
+                .ok_or_else(gocardless_secrets_required)?;
+            let secret_key = secret_key
+                .or_else(|| std::env::var(GOCARDLESS_SECRET_KEY_ENV).ok())
+                .ok_or_else(gocardless_secrets_required)?;
+
+            if let Some(country) = country {
+                let ctx = get_context()?;
+                let institutions = ctx.sync_service.list_gocardless_institutions(
+                    &secret_id,
+                    &secret_key,
+                    &country,
+                    base_url.as_deref(),
+                )?;
+                let search = search.map(|s| s.to_lowercase());
+                let matching: Vec<_> = institutions
+                    .iter()
+                    .filter(|i| {
+                        search
+                            .as_ref()
+                            .map_or(true, |s| i.name.to_lowercase().contains(s))
+                    })
+                    .collect();
+                if matching.is_empty() {
+                    println!("{}", "No banks found.".yellow());
+                    return Ok(());
+                }
+                for institution in matching {
+                    println!("  {}  {}", institution.id.dimmed(), institution.name);
+                }
+                println!();
+                println!(
+                    "Connect one with '{}'.",
+                    "tl setup gocardless --institution <id>".cyan()
+                );
+                return Ok(());
+            }
+
+            let Some(institution) = institution else {
+                anyhow::bail!(
+                    "Choose a bank to connect with --institution.                     List the banks in your country with --country (e.g. --country DE)."
+                );
+            };
+
+            log_event(
+                &logger,
+                LogEvent::new("setup_started").with_integration("gocardless"),
+            );
+
+            println!("Setting up GoCardless integration...");
+
+            let ctx = get_context()?;
+            match ctx.sync_service.setup_gocardless(
+                &secret_id,
+                &secret_key,
+                &institution,
+                redirect.as_deref(),
+                base_url.as_deref(),
+            ) {
+                Ok(link) => {
+                    log_event(
+                        &logger,
+                        LogEvent::new("setup_completed").with_integration("gocardless"),
+                    );
+                    println!("{}", "GoCardless configured successfully!".green());
+                    println!();
+                    println!("Open this link to give consent at your bank:");
+                    println!("  {}", link.cyan());
+                    println!();
+                    println!("Then run '{}' to sync your accounts.", "tl sync".cyan());
+                    Ok(())
+                }
+                Err(e) => {
+                    log_event(
+                        &logger,
+                        LogEvent::new("setup_failed")
+                            .with_integration("gocardless")
+                            .with_error(&e.to_string()),
+                    );
+                    Err(e)
+                }
+            }
+        }
+        Some(SetupCommands::Coinbase {
+            api_key,
+            api_secret,
+            currency,
+            base_url,
+        }) => {
+            log_event(
+                &logger,
+                LogEvent::new("setup_started").with_integration("coinbase"),
+            );
+
+            // Try to get API keys from arguments, then environment variables
+            let api_key = api_key
+                .or_else(|| std::env::var(COINBASE_API_KEY_ENV).ok())
+                .ok_or_else(coinbase_keys_required)?;
+            let api_secret = api_secret
+                .or_else(|| std::env::var(COINBASE_API_SECRET_ENV).ok())
+                .ok_or_else(coinbase_keys_required)?;
+
+            println!("Setting up Coinbase integration...");
+
+            let ctx = get_context()?;
+            match ctx.sync_service.setup_coinbase(
+                &api_key,
+                &api_secret,
+                currency.as_deref(),
+                base_url.as_deref(),
+            ) {
+                Ok(()) => {
+                    log_event(
+                        &logger,
+                        LogEvent::new("setup_completed").with_integration("coinbase"),
+                    );
+                    println!("{}", "Coinbase configured successfully!".green());
+                    println!();
+                    println!("Run '{}' to sync your balances.", "tl sync".cyan());
+                    Ok(())
+                }
+                Err(e) => {
+                    log_event(
+                        &logger,
+                        LogEvent::new("setup_failed")
+                            .with_integration("coinbase")
+                            .with_error(&e.to_string()),
+                    );
+                    Err(e)
+                }
+            }
+        }
+        Some(SetupCommands::Kraken {
+            api_key,
+            api_secret,
+            currency,
+            base_url,
+        }) => {
+            log_event(
+                &logger,
+                LogEvent::new("setup_started").with_integration("kraken"),
+            );
+
+            // Try to get API keys from arguments, then environment variables
+            let api_key = api_key
+                .or_else(|| std::env::var(KRAKEN_API_KEY_ENV).ok())
+                .ok_or_else(kraken_keys_required)?;
+            let api_secret = api_secret
+                .or_else(|| std::env::var(KRAKEN_API_SECRET_ENV).ok())
+                .ok_or_else(kraken_keys_required)?;
+
+            println!("Setting up Kraken integration...");
+
+            let ctx = get_context()?;
+            match ctx.sync_service.setup_kraken(
+                &api_key,
+                &api_secret,
+                currency.as_deref(),
+                base_url.as_deref(),
+            ) {
+                Ok(()) => {
+                    log_event(
+                        &logger,
+                        LogEvent::new("setup_completed").with_integration("kraken"),
+                    );
+                    println!("{}", "Kraken configured successfully!".green());
+                    println!();
+                    println!("Run '{}' to sync your balances.", "tl sync".cyan());
+                    Ok(())
+                }
+                Err(e) => {
+                    log_event(
+                        &logger,
+                        LogEvent::new("setup_failed")
+                            .with_integration("kraken")
+                            .with_error(&e.to_string()),
+                    );
+                    Err(e)
+                }
+            }
+        }
         Some(SetupCommands::Status) => {
             let ctx = get_context()?;
             let integrations = ctx.sync_service.list_integrations()?;
@@ -142,20 +533,92 @@ pub fn run(command: Option<SetupCommands>) -> Result<()> {
             }
             Ok(())
         }
-        Some(SetupCommands::Remove { name }) => {
+        Some(SetupCommands::Remove {
+            name,
+            unlink_accounts,
+        }) => {
             log_event(
                 &logger,
                 LogEvent::new("setup_remove").with_integration(&name),
             );
 
             let ctx = get_context()?;
-            match ctx.sync_service.remove_integration(&name) {
-                Ok(()) => {
-                    println!("{} integration removed.", name.green());
-                    Ok(())
-                }
-                Err(e) => Err(e),
+            let unlinked = ctx
+                .sync_service
+                .remove_integration(&name, unlink_accounts)?;
+            println!("{} integration removed.", name.green());
+            if unlink_accounts {
+                println!("Unlinked {} account(s).", unlinked);
             }
+            Ok(())
+        }
+        Some(SetupCommands::Rotate { name, credential }) => {
+            log_event(
+                &logger,
+                LogEvent::new("setup_rotate").with_integration(&name),
+            );
+
+            let credential = credential
+                .or_else(|| match name.as_str() {
+                    "lunchflow" => std::env::var(LUNCHFLOW_API_KEY_ENV).ok(),
+                    "plaid" => std::env::var(PLAID_SECRET_ENV).ok(),
+                    "gocardless" => std::env::var(GOCARDLESS_SECRET_KEY_ENV).ok(),
+                    _ => None,
+                })
+                .ok_or_else(|| anyhow::anyhow!("A new setup token or API key is required"))?;
+
+            let ctx = get_context()?;
+            ctx.sync_service.rotate_credentials(&name, &credential)?;
+            println!("{} credentials replaced.", name.green());
+            Ok(())
+        }
+        Some(SetupCommands::Accounts { name, json }) => {
+            let ctx = get_context()?;
+            let accounts = ctx.sync_service.list_remote_accounts(&name)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&accounts)?);
+                return Ok(());
+            }
+            if accounts.is_empty() {
+                println!("{}", "No accounts found.".yellow());
+                return Ok(());
+            }
+            for account in accounts {
+                let state = if !account.enabled {
+                    "off".red()
+                } else if account.balances_only {
+                    "balances only".yellow()
+                } else {
+                    "on".green()
+                };
+                let institution = account
+                    .institution_name
+                    .map(|i| format!(" ({})", i))
+                    .unwrap_or_default();
+                println!(
+                    "  {}  {}{}  [{}]",
+                    account.external_id.dimmed(),
+                    account.name,
+                    institution,
+                    state
+                );
+            }
+            Ok(())
+        }
+        Some(SetupCommands::Enable { name, external_id }) => {
+            let ctx = get_context()?;
+            ctx.sync_service
+                .set_account_enabled(&name, &external_id, true)?;
+            println!("{} will be included in sync.", external_id.green());
+            Ok(())
+        }
+        Some(SetupCommands::Disable { name, external_id }) => {
+            let ctx = get_context()?;
+            ctx.sync_service
+                .set_account_enabled(&name, &external_id, false)?;
+            println!("{} will be left out of sync.", external_id.yellow());
+            Ok(())
         }
         None => {
             // Show help when no subcommand provided
@@ -165,6 +628,42 @@ pub fn run(command: Option<SetupCommands>) -> Result<()> {
     }
 }
 
+fn plaid_keys_required() -> anyhow::Error {
+    anyhow::anyhow!(
+        "Plaid client ID and secret required. Provide them as options or set {} and {} environment variables.\n\n\
+        Find your keys at https://dashboard.plaid.com/developers/keys",
+        PLAID_CLIENT_ID_ENV,
+        PLAID_SECRET_ENV
+    )
+}
+
+fn gocardless_secrets_required() -> anyhow::Error {
+    anyhow::anyhow!(
+        "GoCardless secret ID and key required. Provide them as options or set {} and {} environment variables.\n\n\
+        Create them at https://bankaccountdata.gocardless.com/user-secrets/",
+        GOCARDLESS_SECRET_ID_ENV,
+        GOCARDLESS_SECRET_KEY_ENV
+    )
+}
+
+fn coinbase_keys_required() -> anyhow::Error {
+    anyhow::anyhow!(
+        "Coinbase API key and secret required. Provide them as options or set {} and {} environment variables.\n\n\
+        Create a key with view permissions at https://www.coinbase.com/settings/api",
+        COINBASE_API_KEY_ENV,
+        COINBASE_API_SECRET_ENV
+    )
+}
+
+fn kraken_keys_required() -> anyhow::Error {
+    anyhow::anyhow!(
+        "Kraken API key and private key required. Provide them as options or set {} and {} environment variables.\n\n\
+        Create a key with the Query Funds permission at https://pro.kraken.com/app/settings/api",
+        KRAKEN_API_KEY_ENV,
+        KRAKEN_API_SECRET_ENV
+    )
+}
+
 fn show_available_integrations() {
     println!("Available integrations:");
     println!();
@@ -188,6 +687,43 @@ fn show_available_integrations() {
     println!("    tl setup simplefin <token>");
     println!("    Get a setup token: https://beta-bridge.simplefin.org/");
     println!();
+    println!("  {} - US/Canada bank connections", "plaid".cyan());
+    println!("    tl setup plaid --public-token <token>");
+    println!(
+        "    Set {} and {} from https://dashboard.plaid.com/developers/keys",
+        PLAID_CLIENT_ID_ENV.yellow(),
+        PLAID_SECRET_ENV.yellow()
+    );
+    println!("    Try it with Plaid's test bank: tl setup plaid --environment sandbox");
+    println!();
+    println!(
+        "  {} - European bank connections (EU/UK)",
+        "gocardless".cyan()
+    );
+    println!("    tl setup gocardless --country <code>");
+    println!("    tl setup gocardless --institution <id>");
+    println!(
+        "    Set {} and {} from https://bankaccountdata.gocardless.com/user-secrets/",
+        GOCARDLESS_SECRET_ID_ENV.yellow(),
+        GOCARDLESS_SECRET_KEY_ENV.yellow()
+    );
+    println!();
+    println!("  {} - Crypto balances and transactions", "coinbase".cyan());
+    println!("    tl setup coinbase");
+    println!(
+        "    Set {} and {} from https://www.coinbase.com/settings/api",
+        COINBASE_API_KEY_ENV.yellow(),
+        COINBASE_API_SECRET_ENV.yellow()
+    );
+    println!();
+    println!("  {} - Crypto balances", "kraken".cyan());
+    println!("    tl setup kraken");
+    println!(
+        "    Set {} and {} from https://pro.kraken.com/app/settings/api",
+        KRAKEN_API_KEY_ENV.yellow(),
+        KRAKEN_API_SECRET_ENV.yellow()
+    );
+    println!();
     println!(
         "Use '{}' to see configured integrations.",
         "tl setup status".cyan()
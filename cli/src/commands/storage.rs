@@ -0,0 +1,131 @@
+//! Storage command - where the space goes, and pruning history tables
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::Local;
+use clap::Subcommand;
+use colored::Colorize;
+use comfy_table::{CellAlignment, ContentArrangement, Table};
+use treeline_core::services::{format_bytes, StorageGroup, StorageService};
+
+use super::{get_context, get_treeline_dir};
+
+#[derive(Subcommand)]
+pub enum StorageCommands {
+    /// Show space used by tables, logs, backups and archives, with suggestions
+    Show {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Empty history tables (query history, sync history)
+    Prune {
+        /// Tables to prune (defaults to every prunable table)
+        tables: Vec<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn run(command: StorageCommands) -> Result<()> {
+    match command {
+        StorageCommands::Show { json } => {
+            let ctx = get_context()?;
+            let service = StorageService::new(Arc::clone(&ctx.repository), get_treeline_dir());
+            let breakdown = service.analyze(Local::now().date_naive())?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&breakdown)?);
+                return Ok(());
+            }
+
+            let mut table = Table::new();
+            table.set_content_arrangement(ContentArrangement::Dynamic);
+            table.set_header(vec!["Table", "Kind", "Rows", "Size"]);
+            for entry in breakdown.tables.iter().filter(|t| t.rows > 0) {
+                let name = if entry.prunable {
+                    format!("{} {}", entry.table, "(prunable)".dimmed())
+                } else {
+                    entry.table.clone()
+                };
+                table.add_row(vec![
+                    name,
+                    group_label(entry.group).to_string(),
+                    entry.rows.to_string(),
+                    format_bytes(entry.estimated_bytes),
+                ]);
+            }
+            for column in 2..4 {
+                if let Some(column) = table.column_mut(column) {
+                    column.set_cell_alignment(CellAlignment::Right);
+                }
+            }
+            println!("{}", table);
+
+            println!(
+                "{} {} ({} free, {} write-ahead log)",
+                "Database:".bold(),
+                format_bytes(breakdown.database_bytes),
+                format_bytes(breakdown.free_bytes),
+                format_bytes(breakdown.wal_bytes)
+            );
+            println!("{} {}", "Logs:".bold(), format_bytes(breakdown.logs_bytes));
+            println!(
+                "{} {}",
+                "Backups:".bold(),
+                format_bytes(breakdown.backups_bytes)
+            );
+            println!(
+                "{} {}",
+                "Archives:".bold(),
+                format_bytes(breakdown.archives_bytes)
+            );
+
+            if !breakdown.suggestions.is_empty() {
+                println!();
+                println!("{}", "Suggestions:".bold());
+                for suggestion in &breakdown.suggestions {
+                    println!("  {}", suggestion.message);
+                    println!("    {}", suggestion.command.cyan());
+                }
+            }
+        }
+        StorageCommands::Prune { tables, json } => {
+            let ctx = get_context()?;
+            let service = StorageService::new(Arc::clone(&ctx.repository), get_treeline_dir());
+            let results = service.prune(&tables)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&results)?);
+                return Ok(());
+            }
+            for result in &results {
+                println!(
+                    "{} {} rows from {}",
+                    "Pruned".green(),
+                    result.rows_deleted,
+                    result.table
+                );
+            }
+            if results.iter().any(|r| r.rows_deleted > 0) {
+                println!(
+                    "{}",
+                    "Run `tl compact` to return the space to the filesystem.".dimmed()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn group_label(group: StorageGroup) -> &'static str {
+    match group {
+        StorageGroup::Data => "data",
+        StorageGroup::Plugin => "plugin",
+        StorageGroup::History => "history",
+        StorageGroup::System => "system",
+    }
+}
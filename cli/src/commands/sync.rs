@@ -1,12 +1,14 @@
 //! Sync command - sync accounts and transactions from integrations
 
 use anyhow::Result;
+use clap::Subcommand;
 use colored::Colorize;
+use comfy_table::{ContentArrangement, Table};
 use treeline_core::LogEvent;
 
 use super::{get_context, get_logger, log_event};
 
-pub fn run(integration: Option<String>, dry_run: bool, json: bool) -> Result<()> {
+pub fn run(integration: Option<String>, dry_run: bool, full: bool, json: bool) -> Result<()> {
     let logger = get_logger();
     log_event(&logger, LogEvent::new("sync_started").with_command("sync"));
 
@@ -14,7 +16,7 @@ pub fn run(integration: Option<String>, dry_run: bool, json: bool) -> Result<()>
     // CLI always syncs with transactions (balances_only = false)
     let result = ctx
         .sync_service
-        .sync(integration.as_deref(), dry_run, false);
+        .sync(integration.as_deref(), dry_run, false, full);
 
     match &result {
         Ok(sync_result) => {
@@ -68,11 +70,19 @@ pub fn run(integration: Option<String>, dry_run: bool, json: bool) -> Result<()>
         } else {
             println!("{} {}", "Synced:".green(), sync_result.integration);
             println!("  Accounts synced: {}", sync_result.accounts_synced);
+            if sync_result.holdings_synced > 0 {
+                println!("  Holdings updated: {}", sync_result.holdings_synced);
+            }
             if sync_result.sync_type == "incremental" {
                 println!(
                     "  Syncing transactions since {} (with 7-day overlap)",
                     sync_result.start_date
                 );
+            } else if sync_result.sync_type == "full" {
+                println!(
+                    "  Full resync: {} to {}",
+                    sync_result.start_date, sync_result.end_date
+                );
             } else {
                 println!(
                     "  Date range: {} to {}",
@@ -85,10 +95,25 @@ pub fn run(integration: Option<String>, dry_run: bool, json: bool) -> Result<()>
                 sync_result.transaction_stats.discovered
             );
             println!("    New: {}", sync_result.transaction_stats.new);
+            if sync_result.transaction_stats.updated > 0 {
+                println!(
+                    "    Updated: {} (pending transactions that changed or posted)",
+                    sync_result.transaction_stats.updated
+                );
+            }
             println!(
                 "    Skipped: {} (already exists)",
                 sync_result.transaction_stats.skipped
             );
+            if !sync_result.failed_accounts.is_empty() {
+                println!(
+                    "  {} Transactions not synced for some accounts:",
+                    "Warning:".yellow()
+                );
+                for failure in &sync_result.failed_accounts {
+                    println!("    - {}: {}", failure.account_name, failure.error);
+                }
+            }
             // Show auto-tag failures if any
             if !sync_result.auto_tag_failures.is_empty() {
                 println!("  {} Auto-tag rule failures:", "Warning:".yellow());
@@ -109,3 +134,143 @@ pub fn run(integration: Option<String>, dry_run: bool, json: bool) -> Result<()>
 
     Ok(())
 }
+
+#[derive(Subcommand)]
+pub enum SyncCommands {
+    /// Show recent sync runs per integration
+    History {
+        /// Only show runs of this integration
+        integration: Option<String>,
+        /// Number of runs to show
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: usize,
+        /// Show when each account last synced and last produced new transactions
+        #[arg(long)]
+        accounts: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn run_command(command: SyncCommands) -> Result<()> {
+    match command {
+        SyncCommands::History {
+            integration,
+            limit,
+            accounts,
+            json,
+        } => {
+            if accounts {
+                run_account_activity(json)
+            } else {
+                run_history(integration.as_deref(), limit, json)
+            }
+        }
+    }
+}
+
+fn run_history(integration: Option<&str>, limit: usize, json: bool) -> Result<()> {
+    let ctx = get_context()?;
+    let history = ctx.sync_service.history(integration, limit)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&history)?);
+        return Ok(());
+    }
+
+    if history.is_empty() {
+        println!("{}", "No syncs recorded yet.".yellow());
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        "Started",
+        "Integration",
+        "Duration",
+        "Accounts",
+        "New",
+        "Skipped",
+        "Status",
+    ]);
+    for entry in &history {
+        let status = match &entry.error {
+            Some(error) => format!("failed: {}", error),
+            None if !entry.warnings.is_empty() => format!("{} warning(s)", entry.warnings.len()),
+            None => "ok".to_string(),
+        };
+        table.add_row(vec![
+            entry
+                .started_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            entry.integration.clone(),
+            format!("{:.1}s", entry.duration_ms as f64 / 1000.0),
+            entry.accounts_synced.to_string(),
+            entry.transactions_new.to_string(),
+            entry.transactions_skipped.to_string(),
+            status,
+        ]);
+    }
+    println!("{}", table);
+    Ok(())
+}
+
+fn run_account_activity(json: bool) -> Result<()> {
+    let ctx = get_context()?;
+    let activity = ctx.sync_service.account_activity()?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&activity)?);
+        return Ok(());
+    }
+
+    if activity.is_empty() {
+        println!("{}", "No synced accounts.".yellow());
+        return Ok(());
+    }
+
+    let local = |ts: &Option<String>| match ts
+        .as_deref()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+    {
+        Some(dt) => dt
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+        None => "never".to_string(),
+    };
+
+    let mut table = Table::new();
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        "Account",
+        "Integration",
+        "Last synced",
+        "Last new data",
+        "Last error",
+    ]);
+    for account in &activity {
+        let last_data = if account.last_new_data_at.is_some() {
+            format!(
+                "{} ({} new)",
+                local(&account.last_new_data_at),
+                account.last_new_transactions
+            )
+        } else {
+            "never".yellow().to_string()
+        };
+        table.add_row(vec![
+            account.account_name.clone(),
+            account.integration.clone(),
+            local(&account.last_synced_at),
+            last_data,
+            account.last_error.clone().unwrap_or_default(),
+        ]);
+    }
+    println!("{}", table);
+    Ok(())
+}
@@ -0,0 +1,173 @@
+//! Token command - issue and revoke access tokens for external scripts
+
+use anyhow::Result;
+use chrono::Utc;
+use clap::Subcommand;
+use colored::Colorize;
+use comfy_table::{ContentArrangement, Table};
+use dialoguer::Password;
+use treeline_core::config::Config;
+use treeline_core::domain::TokenScope;
+use treeline_core::services::token::parse_ttl;
+use treeline_core::services::{EncryptionService, TokenService};
+use treeline_core::LogEvent;
+
+use super::{get_logger, get_treeline_dir, log_event};
+
+#[derive(Subcommand)]
+pub enum TokenCommands {
+    /// Issue a new token (shown once)
+    Issue {
+        /// Only allow reading (queries, status); writes are rejected
+        #[arg(long)]
+        readonly: bool,
+        /// Lifetime such as 30m, 1h, 7d (default: never expires)
+        #[arg(long)]
+        ttl: Option<String>,
+        /// Label to recognise the token by, e.g. "notebook"
+        #[arg(long)]
+        name: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List issued tokens and when they were last used
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Revoke a token so it can no longer be used
+    Revoke {
+        /// Token ID (from `tl token list`)
+        id: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn run(command: TokenCommands) -> Result<()> {
+    let treeline_dir = get_treeline_dir();
+    let token_service = TokenService::new(treeline_dir.clone());
+    let logger = get_logger();
+
+    match command {
+        TokenCommands::Issue {
+            readonly,
+            ttl,
+            name,
+            json,
+        } => {
+            let ttl = ttl.as_deref().map(parse_ttl).transpose()?;
+            let scope = if readonly {
+                TokenScope::ReadOnly
+            } else {
+                TokenScope::ReadWrite
+            };
+
+            // Encrypted databases: the token carries a wrapped copy of the key
+            let config = Config::load(&treeline_dir)?;
+            let encryption_service =
+                EncryptionService::new(treeline_dir.clone(), treeline_dir.join("treeline.duckdb"));
+            let encryption_key = if !config.demo_mode && encryption_service.is_encrypted()? {
+                let password = match std::env::var("TL_DB_PASSWORD") {
+                    Ok(p) => p,
+                    Err(_) => Password::new()
+                        .with_prompt("Enter database password")
+                        .interact()?,
+                };
+                Some(encryption_service.derive_key_for_connection(&password)?)
+            } else {
+                None
+            };
+
+            let issued =
+                token_service.issue(name.as_deref(), scope, ttl, encryption_key.as_ref())?;
+            log_event(
+                &logger,
+                LogEvent::new("token_issued").with_command("token issue"),
+            );
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&issued)?);
+            } else {
+                println!("{}", "Token issued".green());
+                println!("  ID:      {}", issued.info.id);
+                println!("  Scope:   {}", issued.info.scope);
+                println!("  Expires: {}", format_expiry(issued.info.expires_at));
+                println!();
+                println!("  {}", issued.token.bold());
+                println!();
+                println!(
+                    "{}",
+                    "Copy it now, it won't be shown again. Use it with TL_TOKEN=<token>.".dimmed()
+                );
+            }
+        }
+        TokenCommands::List { json } => {
+            let tokens = token_service.list()?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&tokens)?);
+                return Ok(());
+            }
+
+            if tokens.is_empty() {
+                println!("No tokens issued.");
+                return Ok(());
+            }
+
+            let now = Utc::now();
+            let mut table = Table::new();
+            table.set_content_arrangement(ContentArrangement::Dynamic);
+            table.set_header(vec![
+                "ID",
+                "Name",
+                "Scope",
+                "Status",
+                "Expires",
+                "Last used",
+                "Uses",
+            ]);
+
+            for token in tokens {
+                table.add_row(vec![
+                    token.id.clone(),
+                    token.name.clone().unwrap_or_default(),
+                    token.scope.to_string(),
+                    token.status(now).to_string(),
+                    format_expiry(token.expires_at),
+                    token
+                        .last_used_at
+                        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_else(|| "never".to_string()),
+                    token.use_count.to_string(),
+                ]);
+            }
+
+            println!("{}", table);
+        }
+        TokenCommands::Revoke { id, json } => {
+            let token = token_service.revoke(&id)?;
+            log_event(
+                &logger,
+                LogEvent::new("token_revoked").with_command("token revoke"),
+            );
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&token)?);
+            } else {
+                println!("Token {} revoked", token.id.green());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn format_expiry(expires_at: Option<chrono::DateTime<Utc>>) -> String {
+    expires_at
+        .map(|e| e.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "never".to_string())
+}
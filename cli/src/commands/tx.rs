@@ -0,0 +1,425 @@
+//! Transactions command - list, inspect, edit and delete transactions, and
+//! resolve likely duplicates
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use clap::Subcommand;
+use colored::Colorize;
+use comfy_table::{CellAlignment, ContentArrangement, Table};
+use dialoguer::Confirm;
+use rust_decimal::Decimal;
+use treeline_core::services::{
+    DuplicateOptions, DuplicateService, TransactionEdit, TransactionFilter,
+};
+
+use super::get_context;
+
+#[derive(Subcommand)]
+pub enum TxCommands {
+    /// List transactions, newest first
+    List {
+        /// Account ID or name
+        #[arg(long, short = 'a')]
+        account: Option<String>,
+        /// First date (YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<String>,
+        /// Last date (YYYY-MM-DD)
+        #[arg(long)]
+        to: Option<String>,
+        /// Only transactions with this tag
+        #[arg(long, short = 't', conflicts_with = "untagged")]
+        tag: Option<String>,
+        /// Only transactions without tags
+        #[arg(long)]
+        untagged: bool,
+        /// Smallest amount (spending is negative)
+        #[arg(long, allow_hyphen_values = true)]
+        min: Option<String>,
+        /// Largest amount (spending is negative)
+        #[arg(long, allow_hyphen_values = true)]
+        max: Option<String>,
+        /// Description contains this text
+        #[arg(long, short = 's')]
+        search: Option<String>,
+        /// Only transactions excluded from reports
+        #[arg(long)]
+        excluded: bool,
+        /// Maximum number of transactions (0 for all)
+        #[arg(long, short = 'n', default_value = "50")]
+        limit: usize,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show one transaction
+    Show {
+        /// Transaction ID
+        id: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Change a transaction's description, tags, amount or date
+    Edit {
+        /// Transaction ID
+        id: String,
+        /// New description
+        #[arg(long, short = 'd')]
+        description: Option<String>,
+        /// Comma-separated tags, replacing the current ones ("" to clear)
+        #[arg(long)]
+        tags: Option<String>,
+        /// New amount
+        #[arg(long, allow_hyphen_values = true)]
+        amount: Option<String>,
+        /// New transaction date (YYYY-MM-DD)
+        #[arg(long)]
+        date: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Leave transactions out of reports, budgets and income (they still count
+    /// toward the account balance)
+    Exclude {
+        /// Transaction IDs
+        #[arg(required = true)]
+        ids: Vec<String>,
+        /// Count them in reports again
+        #[arg(long)]
+        include: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Delete transactions (sync and re-imports won't bring them back)
+    Delete {
+        /// Transaction IDs
+        #[arg(required = true)]
+        ids: Vec<String>,
+        /// Skip the confirmation prompt
+        #[arg(long, short = 'f')]
+        force: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List likely duplicates that exact dedup missed (e.g. a CSV row and its synced copy)
+    Duplicates {
+        /// Most days between the two dates
+        #[arg(long, default_value = "3")]
+        days: i64,
+        /// Least description similarity, 0 to 1
+        #[arg(long, default_value = "0.6")]
+        similarity: f64,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Resolve a likely duplicate by deleting one side (its tags move to the kept one)
+    Merge {
+        /// Transaction ID to keep
+        keep: String,
+        /// Transaction ID to delete
+        remove: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Mark two transactions as not duplicates, so they aren't listed again
+    Dismiss {
+        /// Transaction ID
+        first: String,
+        /// Transaction ID
+        second: String,
+    },
+}
+
+pub fn run(command: TxCommands) -> Result<()> {
+    match command {
+        TxCommands::List {
+            account,
+            from,
+            to,
+            tag,
+            untagged,
+            min,
+            max,
+            search,
+            excluded,
+            limit,
+            json,
+        } => {
+            let ctx = get_context()?;
+            let filter = TransactionFilter {
+                account_id: account
+                    .map(|a| ctx.import_service.resolve_account(&a))
+                    .transpose()?,
+                start: from.as_deref().map(parse_date).transpose()?,
+                end: to.as_deref().map(parse_date).transpose()?,
+                tag,
+                untagged,
+                min_amount: min.as_deref().map(parse_amount).transpose()?,
+                max_amount: max.as_deref().map(parse_amount).transpose()?,
+                text: search,
+                excluded: excluded.then_some(true),
+                limit: (limit > 0).then_some(limit),
+            };
+            let transactions = ctx.transaction_service.list(&filter)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&transactions)?);
+                return Ok(());
+            }
+
+            if transactions.is_empty() {
+                println!("No matching transactions.");
+                return Ok(());
+            }
+
+            let mut table = Table::new();
+            table.set_content_arrangement(ContentArrangement::Dynamic);
+            table.set_header(vec![
+                "Date",
+                "Account",
+                "Description",
+                "Amount",
+                "Tags",
+                "ID",
+            ]);
+            for tx in &transactions {
+                table.add_row(vec![
+                    tx.date.to_string(),
+                    tx.account_name.clone(),
+                    tx.description.clone().unwrap_or_default(),
+                    format!("{:.2}", tx.amount),
+                    tx.tags.join(", "),
+                    tx.id.clone(),
+                ]);
+            }
+            if let Some(column) = table.column_mut(3) {
+                column.set_cell_alignment(CellAlignment::Right);
+            }
+            println!("{}", table);
+            if filter.limit == Some(transactions.len()) {
+                println!(
+                    "{}",
+                    format!("Showing the newest {}; use --limit 0 for all.", limit).dimmed()
+                );
+            }
+        }
+        TxCommands::Show { id, json } => {
+            let ctx = get_context()?;
+            let tx = ctx.transaction_service.get(&id)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&tx)?);
+                return Ok(());
+            }
+
+            let or_none = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+            println!("{}", or_none(&tx.description).bold());
+            println!("  ID:           {}", tx.id);
+            println!("  Account:      {}", tx.account_name);
+            println!("  Amount:       {:.2}", tx.amount);
+            println!("  Date:         {}", tx.date);
+            println!("  Posted:       {}", tx.posted_date);
+            println!("  Tags:         {}", tx.tags.join(", "));
+            println!("  Source:       {}", tx.source);
+            if let Some(parent) = &tx.parent_id {
+                println!("  Split from:   {}", parent);
+            }
+            if tx.check_number.is_some() {
+                println!("  Check number: {}", or_none(&tx.check_number));
+            }
+            if tx.reference.is_some() {
+                println!("  Reference:    {}", or_none(&tx.reference));
+            }
+            if tx.excluded_from_reports {
+                println!("  Reports:      excluded");
+            }
+        }
+        TxCommands::Edit {
+            id,
+            description,
+            tags,
+            amount,
+            date,
+            json,
+        } => {
+            let edit = TransactionEdit {
+                description,
+                tags: tags.map(|t| t.split(',').map(String::from).collect()),
+                amount: amount.as_deref().map(parse_amount).transpose()?,
+                date: date.as_deref().map(parse_date).transpose()?,
+                ..Default::default()
+            };
+            if edit.is_empty() {
+                anyhow::bail!("Nothing to change. Pass --description, --tags, --amount or --date.");
+            }
+
+            let ctx = get_context()?;
+            let tx = ctx.transaction_service.edit(&id, &edit)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&tx)?);
+            } else {
+                println!(
+                    "{} {} {} {:.2}",
+                    "Updated".green(),
+                    tx.date,
+                    tx.description.as_deref().unwrap_or_default(),
+                    tx.amount
+                );
+            }
+        }
+        TxCommands::Exclude { ids, include, json } => {
+            let ctx = get_context()?;
+            let changed = ctx.transaction_service.set_excluded(&ids, !include)?;
+
+            if json {
+                println!("{}", serde_json::json!({ "changed": changed }));
+            } else {
+                let (done, preposition) = if include {
+                    ("Included", "in")
+                } else {
+                    ("Excluded", "from")
+                };
+                println!(
+                    "{} {} transaction(s) {} reports",
+                    done.green(),
+                    changed,
+                    preposition
+                );
+            }
+        }
+        TxCommands::Delete { ids, force, json } => {
+            let ctx = get_context()?;
+
+            if !force {
+                if json {
+                    anyhow::bail!("Pass --force to delete without a confirmation prompt");
+                }
+                for id in &ids {
+                    let tx = ctx.transaction_service.get(id)?;
+                    println!(
+                        "  {}  {}  {:.2}",
+                        tx.date,
+                        tx.description.as_deref().unwrap_or_default(),
+                        tx.amount
+                    );
+                }
+                if !Confirm::new()
+                    .with_prompt(format!("Delete {} transaction(s)?", ids.len()))
+                    .default(false)
+                    .interact()?
+                {
+                    println!("Cancelled.");
+                    return Ok(());
+                }
+            }
+
+            let deleted = ctx.transaction_service.delete(&ids)?;
+            if json {
+                println!("{}", serde_json::json!({ "deleted": deleted }));
+            } else {
+                println!("{} {} transaction(s)", "Deleted".green(), deleted);
+            }
+        }
+        TxCommands::Duplicates {
+            days,
+            similarity,
+            json,
+        } => {
+            if !(0.0..=1.0).contains(&similarity) {
+                anyhow::bail!("--similarity must be between 0 and 1");
+            }
+            let ctx = get_context()?;
+            let options = DuplicateOptions {
+                max_days: days,
+                min_similarity: similarity,
+            };
+            let candidates = DuplicateService::new(ctx.repository.clone()).find(&options)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&candidates)?);
+                return Ok(());
+            }
+
+            if candidates.is_empty() {
+                println!("No likely duplicates.");
+                return Ok(());
+            }
+
+            for candidate in &candidates {
+                println!(
+                    "{}  {:.2}  {}",
+                    candidate.first.account_name.bold(),
+                    candidate.first.amount,
+                    format!(
+                        "{} day(s) apart, {:.0}% similar",
+                        candidate.days_apart,
+                        candidate.similarity * 100.0
+                    )
+                    .dimmed()
+                );
+                for tx in [&candidate.first, &candidate.second] {
+                    let keep = if tx.id == candidate.suggested_keep {
+                        "keep".green()
+                    } else {
+                        "".normal()
+                    };
+                    println!(
+                        "  {}  {:<10}  {}  {}  {}",
+                        tx.date,
+                        tx.source,
+                        tx.description.as_deref().unwrap_or_default(),
+                        tx.id.dimmed(),
+                        keep
+                    );
+                }
+                println!();
+            }
+            println!(
+                "{}",
+                "Resolve with 'tl tx merge <keep> <remove>' or 'tl tx dismiss <id> <id>'.".dimmed()
+            );
+        }
+        TxCommands::Merge { keep, remove, json } => {
+            let ctx = get_context()?;
+            let tx = DuplicateService::new(ctx.repository.clone()).merge(&keep, &remove)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&tx)?);
+            } else {
+                println!(
+                    "{} kept {} {} {:.2}",
+                    "Merged".green(),
+                    tx.date,
+                    tx.description.as_deref().unwrap_or_default(),
+                    tx.amount
+                );
+            }
+        }
+        TxCommands::Dismiss { first, second } => {
+            let ctx = get_context()?;
+            DuplicateService::new(ctx.repository.clone()).dismiss(&first, &second)?;
+            println!("{} the pair", "Dismissed".green());
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .with_context(|| format!("Invalid date '{}', expected YYYY-MM-DD", value))
+}
+
+fn parse_amount(value: &str) -> Result<Decimal> {
+    value
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid amount '{}'", value))
+}
@@ -12,6 +12,7 @@ use anyhow::{bail, Context, Result};
 use chrono::{DateTime, Utc};
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
+use treeline_core::json_file::{read_json, write_json};
 
 use super::get_treeline_dir;
 
@@ -36,22 +37,11 @@ impl UpdateState {
     }
 
     pub fn load() -> Self {
-        let path = Self::path();
-        if path.exists() {
-            fs::read_to_string(&path)
-                .ok()
-                .and_then(|content| serde_json::from_str(&content).ok())
-                .unwrap_or_default()
-        } else {
-            Self::default()
-        }
+        read_json(&Self::path()).ok().flatten().unwrap_or_default()
     }
 
     pub fn save(&self) -> Result<()> {
-        let path = Self::path();
-        let content = serde_json::to_string_pretty(self)?;
-        fs::write(&path, content)?;
-        Ok(())
+        write_json(&Self::path(), self)
     }
 }
 
@@ -4,14 +4,15 @@ use std::path::PathBuf;
 use std::process::ExitCode;
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
 
 mod commands;
 mod output;
 
 use commands::{
-    backup, compact, demo, doctor, encrypt, import, logs, plugin, query, setup, status, sync, tag,
-    update,
+    accounts, archive, backup, balance, cdc, compact, demo, dev, device_sync, doctor, encrypt,
+    holdings, import, income, logs, mcp, metrics, move_data, plugin, publish, query, report,
+    schema, setup, status, storage, sync, tag, token, tx, update,
 };
 
 /// Treeline - personal finance in your terminal
@@ -32,24 +33,40 @@ enum Commands {
     },
 
     /// Sync accounts and transactions from integrations
+    #[command(args_conflicts_with_subcommands = true)]
     Sync {
+        /// Show past sync runs
+        #[command(subcommand)]
+        command: Option<sync::SyncCommands>,
         /// Integration name (optional, syncs all if not specified)
         integration: Option<String>,
         /// Preview changes without applying
         #[arg(long)]
         dry_run: bool,
+        /// Fetch the full history window again instead of picking up from the last sync
+        #[arg(long)]
+        full: bool,
         /// Output as JSON
         #[arg(long)]
         json: bool,
     },
 
-    /// Import transactions from a CSV file
+    /// Import transactions from a CSV file, or migrate from another app
+    #[command(subcommand_negates_reqs = true, args_conflicts_with_subcommands = true)]
     Import {
-        /// Path to CSV file (use "-" for stdin)
-        file: String,
-        /// Account ID (UUID) or name to import into
-        #[arg(short, long)]
-        account: String,
+        /// Migrate from another app (GnuCash, Money Manager EX, YNAB, Mint), or undo an import
+        #[command(subcommand)]
+        command: Option<import::ImportCommands>,
+        /// Path to CSV file (use "-" for stdin), or a PDF statement
+        #[arg(required_unless_present = "watch_folder")]
+        file: Option<String>,
+        /// Account ID (UUID) or name to import into (default for rows without an account)
+        #[arg(short, long, required_unless_present = "watch_folder")]
+        account: Option<String>,
+        /// Import every CSV in the imports folder into the account with a profile
+        /// whose name or columns it matches, moving imported files to imported/
+        #[arg(long, conflicts_with_all = ["file", "account", "profile", "preset", "save_profile"])]
+        watch_folder: bool,
         /// CSV column name for dates
         #[arg(long)]
         date_column: Option<String>,
@@ -68,6 +85,36 @@ enum Commands {
         /// CSV column name for running balance (creates balance snapshots)
         #[arg(long)]
         balance_column: Option<String>,
+        /// CSV column naming the account of each row (multi-account files)
+        #[arg(long)]
+        account_column: Option<String>,
+        /// Assign an account-column value to an account as VALUE=ACCOUNT (repeatable)
+        #[arg(long = "map-account", value_name = "VALUE=ACCOUNT")]
+        account_map: Vec<String>,
+        /// Create accounts for account-column values that match no existing account
+        #[arg(long)]
+        create_accounts: bool,
+        /// CSV column name for the bank's category (imported as a tag)
+        #[arg(long)]
+        category_column: Option<String>,
+        /// CSV column name for tags (comma, semicolon or pipe separated)
+        #[arg(long)]
+        tags_column: Option<String>,
+        /// CSV column name for check numbers
+        #[arg(long)]
+        check_number_column: Option<String>,
+        /// CSV column name for reference/confirmation numbers
+        #[arg(long)]
+        reference_column: Option<String>,
+        /// CSV column marking each row as debit or credit (for unsigned amounts)
+        #[arg(long)]
+        direction_column: Option<String>,
+        /// CSV column with each row's currency code (for multi-currency files such as Wise)
+        #[arg(long)]
+        currency_column: Option<String>,
+        /// Translate a bank category to a tag as CATEGORY=TAG (repeatable, empty TAG drops it)
+        #[arg(long = "map-category", value_name = "CATEGORY=TAG")]
+        category_map: Vec<String>,
         /// Negate all amounts (for credit card statements)
         #[arg(long)]
         flip_signs: bool,
@@ -77,9 +124,18 @@ enum Commands {
         /// Skip N rows before the header row
         #[arg(long, default_value = "0")]
         skip_rows: u32,
+        /// The file has no header row: give columns by 0-based index (e.g. --date-column 0)
+        #[arg(long)]
+        no_header: bool,
         /// Number format: us (1,234.56), eu (1.234,56), eu_space (1 234,56)
         #[arg(long, default_value = "us")]
         number_format: String,
+        /// Per-column number format override as COLUMN=FORMAT (repeatable)
+        #[arg(long = "column-format", value_name = "COLUMN=FORMAT")]
+        column_formats: Vec<String>,
+        /// Date format of the date column, e.g. %d.%m.%Y (detected from the whole column by default)
+        #[arg(long)]
+        date_format: Option<String>,
         /// Known balance for historical balance calculation (preview only)
         #[arg(long)]
         anchor_balance: Option<f64>,
@@ -89,6 +145,10 @@ enum Commands {
         /// Use a saved import profile
         #[arg(long)]
         profile: Option<String>,
+        /// Use a bank's export layout: chase, chase-card, amex, capital-one,
+        /// bank-of-america, wise, revolut, n26, dkb, monzo, starling
+        #[arg(long, conflicts_with = "profile")]
+        preset: Option<String>,
         /// Save settings as a named profile after import
         #[arg(long)]
         save_profile: Option<String>,
@@ -183,13 +243,19 @@ enum Commands {
         json: bool,
     },
 
+    /// Issue and revoke access tokens for scripts (used via TL_TOKEN)
+    Token {
+        #[command(subcommand)]
+        command: token::TokenCommands,
+    },
+
     /// Manage demo mode
     Demo {
         #[command(subcommand)]
         command: Option<demo::DemoCommands>,
     },
 
-    /// Set up integrations (SimpleFIN, Lunchflow)
+    /// Set up integrations (SimpleFIN, Lunchflow, Plaid, GoCardless)
     Setup {
         #[command(subcommand)]
         command: Option<setup::SetupCommands>,
@@ -207,6 +273,124 @@ enum Commands {
         command: logs::LogsCommands,
     },
 
+    /// Publish balances, net worth and sync freshness to Home Assistant
+    Publish {
+        /// Show the sensor values without sending them
+        #[arg(long)]
+        dry_run: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Sync your data with your other devices through an encrypted relay folder
+    DeviceSync {
+        #[command(subcommand)]
+        command: device_sync::DeviceSyncCommands,
+    },
+
+    /// Read the change log, or stream it to tools that mirror your data
+    Cdc {
+        #[command(subcommand)]
+        command: cdc::CdcCommands,
+    },
+
+    /// List, edit and delete transactions, and resolve likely duplicates
+    Tx {
+        #[command(subcommand)]
+        command: tx::TxCommands,
+    },
+
+    /// List and edit accounts
+    Accounts {
+        #[command(subcommand)]
+        command: accounts::AccountsCommands,
+    },
+
+    /// Repair balance history from a known balance
+    Balance {
+        #[command(subcommand)]
+        command: balance::BalanceCommands,
+    },
+
+    /// Account statements, period comparisons, credit card cycles and cash flows
+    Report {
+        #[command(subcommand)]
+        command: report::ReportCommands,
+        /// Recompute instead of reusing results cached since the last write
+        #[arg(long, global = true)]
+        no_cache: bool,
+    },
+
+    /// Recurring income (paychecks and the like) and upcoming paydays
+    Income {
+        /// Months of deposits to look through
+        #[arg(long, default_value = "12")]
+        months: u32,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Positions in brokerage accounts, from providers that report them (SimpleFIN)
+    Holdings {
+        /// Only this account (ID or name)
+        #[arg(long)]
+        account: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Move past years into archive files, and attach them to queries when needed
+    Archive {
+        #[command(subcommand)]
+        command: archive::ArchiveCommands,
+    },
+
+    /// See what takes up space and free some of it
+    Storage {
+        #[command(subcommand)]
+        command: storage::StorageCommands,
+    },
+
+    /// Move the data directory, e.g. out of iCloud Drive or Dropbox
+    MoveData {
+        /// New location (must be new or empty)
+        destination: PathBuf,
+        /// Allow a destination inside a cloud-synced folder
+        #[arg(long)]
+        allow_cloud_sync: bool,
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Serve read-only finance tools to AI assistants over MCP (stdio)
+    Mcp,
+
+    /// Print Prometheus metrics for sync health, database size and backups
+    Metrics {
+        /// Write to this file instead of stdout (for node_exporter's textfile collector)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// JSON Schemas for the output of --json, for validation and autocomplete
+    Schema {
+        #[command(subcommand)]
+        command: schema::SchemaCommands,
+    },
+
+    /// Tools for developing and testing Treeline (mock sync providers)
+    Dev {
+        #[command(subcommand)]
+        command: dev::DevCommands,
+    },
+
     /// Update to the latest version
     Update {
         /// Skip confirmation prompt
@@ -219,18 +403,25 @@ enum Commands {
 }
 
 fn main() -> ExitCode {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    // Skip the update notification and usage ping for the update command itself, for the
+    // Skip the update notification for the update command itself, for the
+    // MCP server, whose client has already closed the session, and for
+    // metrics, which runs unattended from a scheduler
+    let skip_update_check = matches!(
+        cli.command,
+        Commands::Update { .. } | Commands::Mcp | Commands::Metrics { .. }
+    );
 
-    // Check if this is the update command (skip update notification for it)
-    let is_update_command = matches!(cli.command, Commands::Update { .. });
-
-    let result = run(cli);
+    let result = commands::check_token_scope(&command_path(&matches)).and_then(|()| run(cli));
 
     match result {
         Ok(()) => {
-            // Check for updates after successful commands (except update itself)
-            if !is_update_command {
+            // Check for updates and send the opt-in usage ping after successful commands
+            if !skip_update_check {
                 update::maybe_notify_update();
+                logs::maybe_send_usage_ping();
             }
             ExitCode::SUCCESS
         }
@@ -241,15 +432,45 @@ fn main() -> ExitCode {
     }
 }
 
+/// The subcommand path, e.g. "backup list"
+fn command_path(matches: &ArgMatches) -> String {
+    let mut names = Vec::new();
+    let mut current = matches;
+    while let Some((name, sub)) = current.subcommand() {
+        names.push(name);
+        current = sub;
+    }
+    names.join(" ")
+}
+
 fn run(cli: Cli) -> Result<()> {
     match cli.command {
         Commands::Status { json } => status::run(json),
         Commands::Sync {
+            command: Some(command),
+            ..
+        } => sync::run_command(command),
+        Commands::Sync {
+            command: None,
             integration,
             dry_run,
+            full,
             json,
-        } => sync::run(integration, dry_run, json),
+        } => sync::run(integration, dry_run, full, json),
         Commands::Import {
+            command: Some(command),
+            ..
+        } => import::run_migration(command),
+        Commands::Import {
+            command: None,
+            watch_folder: true,
+            dry_run,
+            json,
+            ..
+        } => import::run_watch_folder(dry_run, json),
+        Commands::Import {
+            command: None,
+            watch_folder: false,
             file,
             account,
             date_column,
@@ -258,32 +479,60 @@ fn run(cli: Cli) -> Result<()> {
             debit_column,
             credit_column,
             balance_column,
+            account_column,
+            account_map,
+            create_accounts,
+            category_column,
+            tags_column,
+            check_number_column,
+            reference_column,
+            direction_column,
+            currency_column,
+            category_map,
             flip_signs,
             debit_negative,
             skip_rows,
+            no_header,
             number_format,
+            column_formats,
+            date_format,
             anchor_balance,
             anchor_date,
             profile,
+            preset,
             save_profile,
             dry_run,
             json,
         } => import::run(
-            &file,
-            &account,
+            &file.unwrap_or_default(),
+            &account.unwrap_or_default(),
             date_column.as_deref(),
             amount_column.as_deref(),
             description_column.as_deref(),
             debit_column.as_deref(),
             credit_column.as_deref(),
             balance_column.as_deref(),
+            account_column.as_deref(),
+            &account_map,
+            create_accounts,
+            category_column.as_deref(),
+            tags_column.as_deref(),
+            check_number_column.as_deref(),
+            reference_column.as_deref(),
+            direction_column.as_deref(),
+            currency_column.as_deref(),
+            &category_map,
             flip_signs,
             debit_negative,
             skip_rows,
+            no_header,
             &number_format,
+            &column_formats,
+            date_format.as_deref(),
             anchor_balance,
             anchor_date.as_deref(),
             profile.as_deref(),
+            preset.as_deref(),
             save_profile.as_deref(),
             dry_run,
             json,
@@ -313,10 +562,32 @@ fn run(cli: Cli) -> Result<()> {
             json,
         } => encrypt::run(command, password, json),
         Commands::Decrypt { password, json } => encrypt::run_decrypt(password, json),
+        Commands::Token { command } => token::run(command),
         Commands::Demo { command } => demo::run(command),
         Commands::Setup { command } => setup::run(command),
         Commands::Plugin { command } => plugin::run(command),
         Commands::Logs { command } => logs::run(command),
+        Commands::Publish { dry_run, json } => publish::run(dry_run, json),
+        Commands::DeviceSync { command } => device_sync::run(command),
+        Commands::Cdc { command } => cdc::run(command),
+        Commands::Tx { command } => tx::run(command),
+        Commands::Accounts { command } => accounts::run(command),
+        Commands::Balance { command } => balance::run(command),
+        Commands::Report { command, no_cache } => report::run(command, no_cache),
+        Commands::Income { months, json } => income::run(months, json),
+        Commands::Holdings { account, json } => holdings::run(account, json),
+        Commands::Archive { command } => archive::run(command),
+        Commands::Storage { command } => storage::run(command),
+        Commands::MoveData {
+            destination,
+            allow_cloud_sync,
+            yes,
+            json,
+        } => move_data::run(destination, allow_cloud_sync, yes, json),
+        Commands::Mcp => mcp::run(),
+        Commands::Metrics { output } => metrics::run(output),
+        Commands::Schema { command } => schema::run(command),
+        Commands::Dev { command } => dev::run(command),
         Commands::Update { yes, check } => update::run(yes, check),
     }
 }
@@ -0,0 +1 @@
+SELECT CAST((SELECT max(amount) FROM sys_transactions) AS INT) FROM accounts
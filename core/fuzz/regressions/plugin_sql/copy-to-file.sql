@@ -0,0 +1 @@
+COPY sys_transactions TO 'out.csv'
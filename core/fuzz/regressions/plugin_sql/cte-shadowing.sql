@@ -0,0 +1 @@
+WITH accounts AS (SELECT * FROM sys_transactions) SELECT * FROM accounts
\ No newline at end of file
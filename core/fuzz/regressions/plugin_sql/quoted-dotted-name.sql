@@ -0,0 +1 @@
+SELECT * FROM "plugin_fuzz.x".sys_transactions
\ No newline at end of file
@@ -0,0 +1 @@
+SELECT * FROM plugin_fuzz.goals; DROP TABLE sys_transactions
\ No newline at end of file
@@ -0,0 +1 @@
+SELECT * FROM read_csv('/etc/passwd')
\ No newline at end of file
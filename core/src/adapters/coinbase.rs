@@ -0,0 +1,817 @@
+//! Coinbase API client
+//!
+//! Handles communication with the Coinbase v2 API for crypto balance and
+//! transaction sync. Coinbase keeps a wallet per asset (BTC, ETH, ...) and
+//! one per fiat currency; each wallet becomes an account valued in the user's
+//! currency at Coinbase's exchange rate, with the asset quantity and price
+//! in its provider metadata. Access is read-only, with an API key and secret
+//! from the Coinbase settings that sign every request.
+//!
+//! API Documentation: https://docs.cdp.coinbase.com/coinbase-app/docs/api-accounts
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::blocking::Client;
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::domain::result::{Error as DomainError, Result as DomainResult};
+use crate::domain::{Account, AccountType, BalanceSnapshot, ProviderData, Transaction};
+use crate::ports::{
+    DataAggregationProvider, FetchAccountsResult, FetchTransactionsResult, IntegrationProvider,
+};
+
+// =============================================================================
+// API Response Models (matching Coinbase API spec)
+// =============================================================================
+
+/// Every Coinbase v2 response wraps its payload in `data`
+#[derive(Debug, Clone, Deserialize)]
+struct DataResponse<T> {
+    data: T,
+}
+
+/// A list endpoint's page, with the path of the next one
+#[derive(Debug, Clone, Deserialize)]
+struct PageResponse<T> {
+    #[serde(default)]
+    pagination: Option<Pagination>,
+    data: Vec<T>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Pagination {
+    #[serde(default)]
+    next_uri: Option<String>,
+}
+
+/// Error body Coinbase sends with any non-200 response
+#[derive(Debug, Clone, Deserialize)]
+struct ErrorResponse {
+    errors: Vec<ApiError>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ApiError {
+    id: String,
+    message: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct User {
+    #[serde(default)]
+    native_currency: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ExchangeRates {
+    /// Units of each currency one unit of the base currency buys
+    rates: HashMap<String, String>,
+}
+
+/// Coinbase wallet from API
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CoinbaseAccount {
+    pub id: String,
+    pub name: String,
+    /// wallet, fiat or vault
+    #[serde(rename = "type")]
+    pub account_type: String,
+    pub currency: CoinbaseCurrency,
+    pub balance: CoinbaseMoney,
+}
+
+/// The asset of a wallet, a bare code in older API versions
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum CoinbaseCurrency {
+    Code(String),
+    Details { code: String },
+}
+
+impl CoinbaseCurrency {
+    pub fn code(&self) -> &str {
+        match self {
+            CoinbaseCurrency::Code(code) | CoinbaseCurrency::Details { code } => code,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CoinbaseMoney {
+    /// Decimal string, e.g. "0.00150000"
+    pub amount: String,
+    pub currency: String,
+}
+
+/// Coinbase transaction from API
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CoinbaseTransaction {
+    pub id: String,
+    /// send, buy, sell, trade, staking_reward, ...
+    #[serde(rename = "type")]
+    pub tx_type: String,
+    #[serde(default)]
+    pub status: Option<String>,
+    /// In the wallet's asset, negative when it leaves the wallet
+    pub amount: CoinbaseMoney,
+    /// What the amount was worth in the user's native currency at the time
+    pub native_amount: CoinbaseMoney,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// RFC 3339 timestamp
+    pub created_at: String,
+    #[serde(default)]
+    pub details: Option<TransactionDetails>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TransactionDetails {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub subtitle: Option<String>,
+}
+
+/// Result of syncing wallets from Coinbase
+#[derive(Debug, Default)]
+pub struct SyncedAccounts {
+    pub accounts: Vec<Account>,
+    pub balance_snapshots: Vec<BalanceSnapshot>,
+    /// IDs of wallets with a zero balance
+    pub empty: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+// =============================================================================
+// Coinbase HTTP Client
+// =============================================================================
+
+pub const COINBASE_API_URL: &str = "https://api.coinbase.com";
+
+/// API version every request is pinned to
+const API_VERSION: &str = "2024-01-01";
+
+/// Wallets or transactions per page (Coinbase's maximum)
+const PAGE_SIZE: u32 = 100;
+
+/// Coinbase API client
+#[derive(Debug)]
+pub struct CoinbaseClient {
+    client: Client,
+    api_key: String,
+    api_secret: String,
+    base_url: String,
+}
+
+impl CoinbaseClient {
+    /// Create a new Coinbase client with an API key and secret
+    pub fn new(api_key: &str, api_secret: &str, base_url: &str) -> Result<Self> {
+        if api_key.is_empty() || api_secret.is_empty() {
+            anyhow::bail!("Coinbase API key and secret cannot be empty");
+        }
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(60))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self {
+            client,
+            api_key: api_key.to_string(),
+            api_secret: api_secret.to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// The user's native currency, which also checks the API key works
+    pub fn get_native_currency(&self) -> Result<Option<String>> {
+        let response: DataResponse<User> = self.get("/v2/user")?;
+        Ok(response.data.native_currency)
+    }
+
+    /// Every wallet, including the empty ones
+    pub fn list_accounts(&self) -> Result<Vec<CoinbaseAccount>> {
+        let mut accounts = Vec::new();
+        let mut path = Some(format!("/v2/accounts?limit={}", PAGE_SIZE));
+        while let Some(current) = path {
+            let page: PageResponse<CoinbaseAccount> = self.get(&current)?;
+            accounts.extend(page.data);
+            path = page.pagination.and_then(|p| p.next_uri);
+        }
+        Ok(accounts)
+    }
+
+    /// Fetch wallets valued in `currency`
+    ///
+    /// A wallet whose asset has no exchange rate keeps an unknown balance
+    /// and is reported in the warnings.
+    pub fn get_accounts(&self, currency: &str) -> Result<SyncedAccounts> {
+        let wallets = self.list_accounts()?;
+        let prices = self.get_prices(currency)?;
+
+        let mut synced = SyncedAccounts::default();
+        for wallet in &wallets {
+            let asset = wallet.currency.code();
+            let quantity = parse_amount(&wallet.balance.amount);
+            if quantity.is_zero() {
+                synced.empty.push(wallet.id.clone());
+            } else if !prices.contains_key(asset) {
+                synced
+                    .warnings
+                    .push(format!("No {} price for {}", currency, asset));
+            }
+
+            let account = map_account(wallet, currency, prices.get(asset).copied());
+            if let Some(balance) = account.balance {
+                synced.balance_snapshots.push(BalanceSnapshot {
+                    id: Uuid::new_v4(),
+                    account_id: account.id,
+                    balance,
+                    snapshot_time: Utc::now().naive_utc(),
+                    source: Some("sync".to_string()),
+                    csv_batch_id: None,
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                });
+            }
+            synced.accounts.push(account);
+        }
+        Ok(synced)
+    }
+
+    /// Price of one unit of each asset in `currency`
+    pub fn get_prices(&self, currency: &str) -> Result<HashMap<String, Decimal>> {
+        let response: DataResponse<ExchangeRates> =
+            self.get(&format!("/v2/exchange-rates?currency={}", currency))?;
+        Ok(prices_from_rates(&response.data.rates, currency))
+    }
+
+    /// Fetch a wallet's transactions created on or after `since`
+    ///
+    /// Coinbase lists newest first, so paging stops at the first page that
+    /// reaches back past `since`.
+    pub fn get_transactions(
+        &self,
+        account_id: &str,
+        since: NaiveDate,
+    ) -> Result<Vec<CoinbaseTransaction>> {
+        let mut transactions = Vec::new();
+        let mut path = Some(format!(
+            "/v2/accounts/{}/transactions?limit={}",
+            account_id, PAGE_SIZE
+        ));
+        while let Some(current) = path {
+            let page: PageResponse<CoinbaseTransaction> = self.get(&current)?;
+            let reached_since = page
+                .data
+                .iter()
+                .any(|tx| parse_date(&tx.created_at).is_some_and(|date| date < since));
+            transactions.extend(
+                page.data
+                    .into_iter()
+                    .filter(|tx| parse_date(&tx.created_at).map_or(true, |date| date >= since)),
+            );
+            path = if reached_since {
+                None
+            } else {
+                page.pagination.and_then(|p| p.next_uri)
+            };
+        }
+        Ok(transactions)
+    }
+
+    /// GET a Coinbase endpoint, signing the request with the API secret
+    fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let timestamp = Utc::now().timestamp().to_string();
+        let url = format!("{}{}", self.base_url, path);
+        let response = self
+            .client
+            .get(&url)
+            .header("CB-ACCESS-KEY", &self.api_key)
+            .header(
+                "CB-ACCESS-SIGN",
+                sign(&self.api_secret, &timestamp, "GET", path, ""),
+            )
+            .header("CB-ACCESS-TIMESTAMP", &timestamp)
+            .header("CB-VERSION", API_VERSION)
+            .send()
+            .map_err(|e| self.map_request_error(e))?;
+
+        let status = response.status();
+        if status.as_u16() == 401 {
+            anyhow::bail!("Coinbase authentication failed. Check your API key and secret.");
+        }
+        if !status.is_success() {
+            // Coinbase explains what went wrong in the body
+            return match response.json::<ErrorResponse>() {
+                Ok(body) if !body.errors.is_empty() => Err(anyhow::anyhow!(
+                    "Coinbase error {}: {}",
+                    body.errors[0].id,
+                    body.errors[0].message
+                )),
+                _ => Err(anyhow::anyhow!(
+                    "Coinbase API error: HTTP {}",
+                    status.as_u16()
+                )),
+            };
+        }
+
+        response
+            .json()
+            .with_context(|| format!("Failed to parse Coinbase {} response", path))
+    }
+
+    /// Map request errors to user-friendly messages
+    fn map_request_error(&self, error: reqwest::Error) -> anyhow::Error {
+        if error.is_timeout() {
+            anyhow::anyhow!("Connection timed out after 60 seconds")
+        } else if error.is_connect() {
+            anyhow::anyhow!("Unable to connect to Coinbase servers")
+        } else {
+            anyhow::anyhow!("Coinbase request failed: {}", error)
+        }
+    }
+}
+
+/// CB-ACCESS-SIGN for a request: hex HMAC-SHA256 of the timestamp, method,
+/// path (with query) and body
+fn sign(secret: &str, timestamp: &str, method: &str, path: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(timestamp.as_bytes());
+    mac.update(method.as_bytes());
+    mac.update(path.as_bytes());
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Asset prices from Coinbase's rates, which run the other way (how much of
+/// each asset one unit of `currency` buys)
+fn prices_from_rates(rates: &HashMap<String, String>, currency: &str) -> HashMap<String, Decimal> {
+    let mut prices: HashMap<String, Decimal> = rates
+        .iter()
+        .filter_map(|(asset, rate)| {
+            let rate: Decimal = rate.parse().ok()?;
+            let price = Decimal::ONE.checked_div(rate)?;
+            Some((asset.clone(), price))
+        })
+        .collect();
+    prices.insert(currency.to_string(), Decimal::ONE);
+    prices
+}
+
+/// Parse a Coinbase amount string, zero when it isn't a number
+fn parse_amount(amount: &str) -> Decimal {
+    amount.parse().unwrap_or_default()
+}
+
+/// The date of an RFC 3339 timestamp
+fn parse_date(timestamp: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|t| t.date_naive())
+}
+
+/// Map Coinbase wallet to domain Account, valued at `price` per unit
+fn map_account(wallet: &CoinbaseAccount, currency: &str, price: Option<Decimal>) -> Account {
+    let account_type = match wallet.account_type.as_str() {
+        "fiat" => AccountType::Cash,
+        _ => AccountType::Crypto,
+    };
+    let quantity = parse_amount(&wallet.balance.amount);
+    // Nothing held is worth nothing, priced or not
+    let balance = if quantity.is_zero() {
+        Some(Decimal::ZERO)
+    } else {
+        price.map(|p| (quantity * p).round_dp(2))
+    };
+
+    let now = Utc::now();
+    Account {
+        id: Uuid::new_v4(),
+        name: wallet.name.clone(),
+        nickname: None,
+        currency: currency.to_string(),
+        account_type: Some(account_type.to_string()),
+        classification: Some(Account::compute_classification(Some(account_type.as_str()))),
+        balance,
+        institution_name: Some("Coinbase".to_string()),
+        institution_url: Some("https://www.coinbase.com".to_string()),
+        institution_domain: Some("coinbase.com".to_string()),
+        created_at: now,
+        updated_at: now,
+        // Manual flag
+        is_manual: false,
+        // Coinbase: the holding behind the balance
+        provider: ProviderData::new(
+            "coinbase",
+            &wallet.id,
+            json!({
+                "asset": wallet.currency.code(),
+                "quantity": quantity.normalize().to_string(),
+                "price": price.map(|p| p.round_dp(8).normalize().to_string()),
+                "type": wallet.account_type,
+            }),
+        ),
+    }
+}
+
+/// Map Coinbase transaction to domain Transaction in `currency`
+fn map_transaction(cb_tx: &CoinbaseTransaction, currency: &str) -> Transaction {
+    let date = parse_date(&cb_tx.created_at).unwrap_or_else(|| Utc::now().naive_utc().date());
+
+    // The title reads like the app ("Bought Bitcoin"); older transactions
+    // only have a description
+    let description = cb_tx
+        .details
+        .as_ref()
+        .and_then(|d| d.title.clone())
+        .or_else(|| cb_tx.description.clone())
+        .filter(|d| !d.trim().is_empty())
+        .unwrap_or_else(|| cb_tx.tx_type.replace('_', " "));
+
+    let now = Utc::now();
+    Transaction {
+        id: Uuid::new_v4(),
+        account_id: Uuid::nil(), // Will be set by sync service after mapping
+        amount: parse_amount(&cb_tx.native_amount.amount),
+        description: Some(description),
+        transaction_date: date,
+        posted_date: date,
+        tags: vec![],
+        created_at: now,
+        updated_at: now,
+        deleted_at: None,
+        parent_transaction_id: None,
+        // CSV Import tracking (not applicable)
+        csv_fingerprint: None,
+        csv_batch_id: None,
+        // Reconciliation identifiers (CSV import only)
+        check_number: None,
+        reference: None,
+        // Currency, when Coinbase values it in other than the account's
+        currency: (!cb_tx.native_amount.currency.eq_ignore_ascii_case(currency))
+            .then(|| cb_tx.native_amount.currency.to_uppercase()),
+        // Manual flag
+        is_manual: false,
+        // Auto-tag tracking (starts false, set true when rules apply)
+        tags_auto_applied: false,
+        // Report exclusion (set by the user or a rule)
+        excluded_from_reports: false,
+        // Pending until Coinbase completes it
+        pending: cb_tx.status.as_deref() == Some("pending"),
+        // Coinbase: the asset movement behind the amount
+        provider: ProviderData::new(
+            "coinbase",
+            &cb_tx.id,
+            json!({
+                "type": cb_tx.tx_type,
+                "status": cb_tx.status,
+                "amount": cb_tx.amount.amount,
+                "asset": cb_tx.amount.currency,
+                "subtitle": cb_tx.details.as_ref().and_then(|d| d.subtitle.clone()),
+            }),
+        ),
+    }
+}
+
+// =============================================================================
+// CoinbaseProvider - implements DataAggregationProvider trait
+// =============================================================================
+
+/// Client for the keys in settings or setup options
+fn client_from(settings: &JsonValue) -> DomainResult<CoinbaseClient> {
+    let field = |key: &str| settings.get(key).and_then(|v| v.as_str());
+    let (Some(api_key), Some(api_secret)) = (field("apiKey"), field("apiSecret")) else {
+        return Err(DomainError::Config(
+            "Coinbase apiKey and apiSecret not found in settings".to_string(),
+        ));
+    };
+    // Custom base URL for testing with mock server
+    let base_url = field("baseUrl").unwrap_or(COINBASE_API_URL);
+
+    CoinbaseClient::new(api_key, api_secret, base_url).map_err(|e| DomainError::Sync(e.to_string()))
+}
+
+/// The currency balances are valued in
+fn currency(settings: &JsonValue) -> String {
+    settings
+        .get("currency")
+        .and_then(|v| v.as_str())
+        .unwrap_or("USD")
+        .to_uppercase()
+}
+
+/// Coinbase data provider
+///
+/// Implements DataAggregationProvider and IntegrationProvider traits
+/// for syncing crypto balances and transactions from Coinbase.
+pub struct CoinbaseProvider;
+
+impl CoinbaseProvider {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for CoinbaseProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DataAggregationProvider for CoinbaseProvider {
+    fn name(&self) -> &str {
+        "coinbase"
+    }
+
+    fn can_get_accounts(&self) -> bool {
+        true
+    }
+
+    fn can_get_transactions(&self) -> bool {
+        true
+    }
+
+    fn can_get_balances(&self) -> bool {
+        true
+    }
+
+    /// Every wallet, valued in the settings' currency
+    ///
+    /// Coinbase lists a wallet for each asset it supports, so the empty ones
+    /// are reported as such and only synced once they hold something.
+    fn get_accounts(&self, settings: &JsonValue) -> DomainResult<FetchAccountsResult> {
+        let client = client_from(settings)?;
+        let synced = client
+            .get_accounts(&currency(settings))
+            .map_err(|e| DomainError::Sync(e.to_string()))?;
+
+        Ok(FetchAccountsResult {
+            accounts: synced.accounts,
+            balance_snapshots: synced.balance_snapshots,
+            warnings: synced.warnings,
+            empty_accounts: synced.empty,
+            holdings: None,
+        })
+    }
+
+    /// Transactions of each wallet since `start_date`
+    ///
+    /// Without account IDs, that's every wallet holding something. A wallet
+    /// that fails becomes a warning so the others still sync.
+    fn get_transactions(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        account_ids: &[String],
+        settings: &JsonValue,
+    ) -> DomainResult<FetchTransactionsResult> {
+        let client = client_from(settings)?;
+        let currency = currency(settings);
+
+        let wallet_ids: Vec<String> = if account_ids.is_empty() {
+            client
+                .list_accounts()
+                .map_err(|e| DomainError::Sync(e.to_string()))?
+                .into_iter()
+                .filter(|w| !parse_amount(&w.balance.amount).is_zero())
+                .map(|w| w.id)
+                .collect()
+        } else {
+            account_ids.to_vec()
+        };
+
+        let mut result = FetchTransactionsResult::default();
+        let mut seen = HashSet::new();
+        for wallet_id in &wallet_ids {
+            match client.get_transactions(wallet_id, start_date) {
+                Ok(transactions) => {
+                    for cb_tx in transactions {
+                        let tx = map_transaction(&cb_tx, &currency);
+                        if tx.transaction_date <= end_date && seen.insert(cb_tx.id.clone()) {
+                            result.transactions.push((wallet_id.clone(), tx));
+                        }
+                    }
+                }
+                Err(e) => result.warnings.push(format!("{}: {}", wallet_id, e)),
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl IntegrationProvider for CoinbaseProvider {
+    /// Validate an API key and store it
+    ///
+    /// Takes `apiKey`, `apiSecret`, an optional `currency` to value balances
+    /// in (the Coinbase account's native currency by default) and `baseUrl`
+    /// for testing.
+    fn setup(&self, options: &JsonValue) -> DomainResult<JsonValue> {
+        let client = client_from(options)?;
+        let option = |key: &str| options.get(key).and_then(|v| v.as_str());
+
+        // Validate the key by fetching the user
+        let native_currency = client.get_native_currency().map_err(|e| {
+            DomainError::Sync(format!("Failed to validate Coinbase API key: {}", e))
+        })?;
+        let currency = option("currency")
+            .map(|c| c.to_string())
+            .or(native_currency)
+            .unwrap_or_else(|| "USD".to_string());
+
+        let mut settings = json!({
+            "apiKey": option("apiKey"),
+            "apiSecret": option("apiSecret"),
+            "currency": currency.to_uppercase(),
+        });
+
+        // Include base URL if custom (for testing)
+        if let Some(url) = option("baseUrl") {
+            settings["baseUrl"] = json!(url);
+        }
+
+        Ok(settings)
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wallet(account_type: &str, asset: &str, amount: &str) -> CoinbaseAccount {
+        CoinbaseAccount {
+            id: format!("{}-wallet", asset.to_lowercase()),
+            name: format!("{} Wallet", asset),
+            account_type: account_type.to_string(),
+            currency: CoinbaseCurrency::Code(asset.to_string()),
+            balance: CoinbaseMoney {
+                amount: amount.to_string(),
+                currency: asset.to_string(),
+            },
+        }
+    }
+
+    fn cb_tx(native_amount: &str, native_currency: &str) -> CoinbaseTransaction {
+        CoinbaseTransaction {
+            id: "tx-1".to_string(),
+            tx_type: "buy".to_string(),
+            status: Some("completed".to_string()),
+            amount: CoinbaseMoney {
+                amount: "0.01".to_string(),
+                currency: "BTC".to_string(),
+            },
+            native_amount: CoinbaseMoney {
+                amount: native_amount.to_string(),
+                currency: native_currency.to_string(),
+            },
+            description: None,
+            created_at: "2025-01-15T13:13:35-07:00".to_string(),
+            details: Some(TransactionDetails {
+                title: Some("Bought Bitcoin".to_string()),
+                subtitle: Some("Using USD Wallet".to_string()),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_provider_name() {
+        let provider = CoinbaseProvider::new();
+        assert_eq!(provider.name(), "coinbase");
+    }
+
+    #[test]
+    fn test_reject_empty_keys() {
+        let result = CoinbaseClient::new("key", "", "http://localhost");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("cannot be empty"));
+    }
+
+    #[test]
+    fn test_sign() {
+        assert_eq!(
+            sign("secret", "1700000000", "GET", "/v2/accounts?limit=100", ""),
+            "10d3a7608f525eb44484257305662b6485f17e3924eb2dec9c53d142686e5750"
+        );
+    }
+
+    #[test]
+    fn test_prices_from_rates() {
+        let rates = HashMap::from([
+            ("BTC".to_string(), "0.00002".to_string()),
+            ("ETH".to_string(), "0.0005".to_string()),
+            ("BAD".to_string(), "0".to_string()),
+        ]);
+        let prices = prices_from_rates(&rates, "USD");
+
+        assert_eq!(prices["BTC"], Decimal::new(50000, 0));
+        assert_eq!(prices["ETH"], Decimal::new(2000, 0));
+        assert_eq!(prices["USD"], Decimal::ONE);
+        assert!(!prices.contains_key("BAD"));
+    }
+
+    #[test]
+    fn test_account_mapping() {
+        let account = map_account(
+            &wallet("wallet", "BTC", "0.015"),
+            "USD",
+            Some(Decimal::new(50000, 0)),
+        );
+
+        assert_eq!(account.account_type, Some("crypto".to_string()));
+        assert_eq!(account.classification, Some("asset".to_string()));
+        assert_eq!(account.currency, "USD");
+        assert_eq!(account.balance, Some(Decimal::new(750, 0)));
+        assert_eq!(account.institution_name, Some("Coinbase".to_string()));
+        assert_eq!(account.provider.external_id("coinbase"), Some("btc-wallet"));
+        assert_eq!(
+            account.provider.metadata_str("coinbase", "asset"),
+            Some("BTC")
+        );
+        assert_eq!(
+            account.provider.metadata_str("coinbase", "quantity"),
+            Some("0.015")
+        );
+        assert_eq!(
+            account.provider.metadata_str("coinbase", "price"),
+            Some("50000")
+        );
+
+        let cash = map_account(&wallet("fiat", "USD", "12.50"), "USD", Some(Decimal::ONE));
+        assert_eq!(cash.account_type, Some("cash".to_string()));
+        assert_eq!(cash.balance, Some(Decimal::new(1250, 2)));
+
+        // Without a price the value is unknown, not zero
+        let unpriced = map_account(&wallet("wallet", "NEW", "3"), "USD", None);
+        assert_eq!(unpriced.balance, None);
+        assert_eq!(unpriced.provider.metadata("coinbase", "price"), None);
+    }
+
+    #[test]
+    fn test_parse_wallet() {
+        let wallet: CoinbaseAccount = serde_json::from_value(json!({
+            "id": "58542935-67b5-56e1-a3f9-42686e07fa40",
+            "name": "ETH Wallet",
+            "primary": false,
+            "type": "wallet",
+            "currency": { "code": "ETH", "name": "Ethereum" },
+            "balance": { "amount": "1.20000000", "currency": "ETH" }
+        }))
+        .unwrap();
+        assert_eq!(wallet.currency.code(), "ETH");
+        assert_eq!(parse_amount(&wallet.balance.amount), Decimal::new(12, 1));
+    }
+
+    #[test]
+    fn test_transaction_mapping() {
+        let tx = map_transaction(&cb_tx("-500.00", "USD"), "USD");
+
+        assert_eq!(tx.amount, Decimal::new(-50000, 2));
+        assert_eq!(tx.description, Some("Bought Bitcoin".to_string()));
+        // The date where the transaction happened, not in UTC
+        assert_eq!(tx.transaction_date.to_string(), "2025-01-15");
+        assert_eq!(tx.currency, None);
+        assert_eq!(tx.provider.external_id("coinbase"), Some("tx-1"));
+        assert_eq!(tx.provider.metadata_str("coinbase", "asset"), Some("BTC"));
+        assert_eq!(tx.provider.metadata_str("coinbase", "amount"), Some("0.01"));
+
+        // Valued in another currency than the account's
+        let tx = map_transaction(&cb_tx("-450.00", "eur"), "USD");
+        assert_eq!(tx.currency, Some("EUR".to_string()));
+    }
+
+    #[test]
+    fn test_transaction_mapping_no_title() {
+        let mut cb_tx = cb_tx("1.20", "USD");
+        cb_tx.tx_type = "staking_reward".to_string();
+        cb_tx.details = None;
+        let tx = map_transaction(&cb_tx, "USD");
+        assert_eq!(tx.description, Some("staking reward".to_string()));
+
+        cb_tx.description = Some("Rewards for staking".to_string());
+        let tx = map_transaction(&cb_tx, "USD");
+        assert_eq!(tx.description, Some("Rewards for staking".to_string()));
+    }
+
+    #[test]
+    fn test_provider_setup_requires_keys() {
+        let provider = CoinbaseProvider::new();
+        let result = provider.setup(&json!({ "apiKey": "key" }));
+        assert!(result.unwrap_err().to_string().contains("apiSecret"));
+        assert_eq!(currency(&json!({})), "USD");
+        assert_eq!(currency(&json!({ "currency": "eur" })), "EUR");
+    }
+}
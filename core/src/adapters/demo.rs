@@ -11,7 +11,7 @@ use chrono::{Datelike, Duration, NaiveDate, Utc};
 use rust_decimal::Decimal;
 use uuid::Uuid;
 
-use crate::domain::{Account, BalanceSnapshot, Transaction};
+use crate::domain::{Account, BalanceSnapshot, ProviderData, Transaction};
 
 /// Generate demo accounts
 pub fn generate_demo_accounts() -> Vec<Account> {
@@ -22,7 +22,7 @@ pub fn generate_demo_accounts() -> Vec<Account> {
             id: Uuid::parse_str("11111111-1111-1111-1111-111111111111").unwrap(),
             name: "Primary Checking".to_string(),
             nickname: Some("Everyday Spending".to_string()),
-            account_type: Some("depository".to_string()),
+            account_type: Some("checking".to_string()),
             classification: Some("asset".to_string()),
             currency: "USD".to_string(),
             balance: Some(Decimal::new(482347, 2)), // $4,823.47
@@ -33,29 +33,13 @@ pub fn generate_demo_accounts() -> Vec<Account> {
             updated_at: now,
             // Demo accounts are identified by name for deduplication
             is_manual: false,
-            sf_id: None,
-            sf_name: None,
-            sf_currency: None,
-            sf_balance: None,
-            sf_available_balance: None,
-            sf_balance_date: None,
-            sf_org_name: None,
-            sf_org_url: None,
-            sf_org_domain: None,
-            sf_extra: None,
-            lf_id: None,
-            lf_name: None,
-            lf_institution_name: None,
-            lf_institution_logo: None,
-            lf_provider: None,
-            lf_currency: None,
-            lf_status: None,
+            provider: ProviderData::default(),
         },
         Account {
             id: Uuid::parse_str("22222222-2222-2222-2222-222222222222").unwrap(),
             name: "High-Yield Savings".to_string(),
             nickname: Some("Emergency Fund".to_string()),
-            account_type: Some("depository".to_string()),
+            account_type: Some("savings".to_string()),
             classification: Some("asset".to_string()),
             currency: "USD".to_string(),
             balance: Some(Decimal::new(1875000, 2)), // $18,750.00
@@ -65,29 +49,13 @@ pub fn generate_demo_accounts() -> Vec<Account> {
             created_at: now,
             updated_at: now,
             is_manual: false,
-            sf_id: None,
-            sf_name: None,
-            sf_currency: None,
-            sf_balance: None,
-            sf_available_balance: None,
-            sf_balance_date: None,
-            sf_org_name: None,
-            sf_org_url: None,
-            sf_org_domain: None,
-            sf_extra: None,
-            lf_id: None,
-            lf_name: None,
-            lf_institution_name: None,
-            lf_institution_logo: None,
-            lf_provider: None,
-            lf_currency: None,
-            lf_status: None,
+            provider: ProviderData::default(),
         },
         Account {
             id: Uuid::parse_str("33333333-3333-3333-3333-333333333333").unwrap(),
             name: "Sapphire Reserve".to_string(),
             nickname: Some("Travel Card".to_string()),
-            account_type: Some("credit".to_string()),
+            account_type: Some("credit_card".to_string()),
             classification: Some("liability".to_string()),
             currency: "USD".to_string(),
             balance: Some(Decimal::new(-284763, 2)), // -$2,847.63
@@ -97,29 +65,13 @@ pub fn generate_demo_accounts() -> Vec<Account> {
             created_at: now,
             updated_at: now,
             is_manual: false,
-            sf_id: None,
-            sf_name: None,
-            sf_currency: None,
-            sf_balance: None,
-            sf_available_balance: None,
-            sf_balance_date: None,
-            sf_org_name: None,
-            sf_org_url: None,
-            sf_org_domain: None,
-            sf_extra: None,
-            lf_id: None,
-            lf_name: None,
-            lf_institution_name: None,
-            lf_institution_logo: None,
-            lf_provider: None,
-            lf_currency: None,
-            lf_status: None,
+            provider: ProviderData::default(),
         },
         Account {
             id: Uuid::parse_str("44444444-4444-4444-4444-444444444444").unwrap(),
             name: "Citi Double Cash".to_string(),
             nickname: Some("Cashback Card".to_string()),
-            account_type: Some("credit".to_string()),
+            account_type: Some("credit_card".to_string()),
             classification: Some("liability".to_string()),
             currency: "USD".to_string(),
             balance: Some(Decimal::new(-124589, 2)), // -$1,245.89
@@ -129,29 +81,13 @@ pub fn generate_demo_accounts() -> Vec<Account> {
             created_at: now,
             updated_at: now,
             is_manual: false,
-            sf_id: None,
-            sf_name: None,
-            sf_currency: None,
-            sf_balance: None,
-            sf_available_balance: None,
-            sf_balance_date: None,
-            sf_org_name: None,
-            sf_org_url: None,
-            sf_org_domain: None,
-            sf_extra: None,
-            lf_id: None,
-            lf_name: None,
-            lf_institution_name: None,
-            lf_institution_logo: None,
-            lf_provider: None,
-            lf_currency: None,
-            lf_status: None,
+            provider: ProviderData::default(),
         },
         Account {
             id: Uuid::parse_str("55555555-5555-5555-5555-555555555555").unwrap(),
             name: "Individual Brokerage".to_string(),
             nickname: Some("Investments".to_string()),
-            account_type: Some("investment".to_string()),
+            account_type: Some("brokerage".to_string()),
             classification: Some("asset".to_string()),
             currency: "USD".to_string(),
             balance: Some(Decimal::new(4782315, 2)), // $47,823.15
@@ -161,29 +97,13 @@ pub fn generate_demo_accounts() -> Vec<Account> {
             created_at: now,
             updated_at: now,
             is_manual: false,
-            sf_id: None,
-            sf_name: None,
-            sf_currency: None,
-            sf_balance: None,
-            sf_available_balance: None,
-            sf_balance_date: None,
-            sf_org_name: None,
-            sf_org_url: None,
-            sf_org_domain: None,
-            sf_extra: None,
-            lf_id: None,
-            lf_name: None,
-            lf_institution_name: None,
-            lf_institution_logo: None,
-            lf_provider: None,
-            lf_currency: None,
-            lf_status: None,
+            provider: ProviderData::default(),
         },
         Account {
             id: Uuid::parse_str("66666666-6666-6666-6666-666666666666").unwrap(),
             name: "401(k)".to_string(),
             nickname: Some("Retirement".to_string()),
-            account_type: Some("investment".to_string()),
+            account_type: Some("retirement".to_string()),
             classification: Some("asset".to_string()),
             currency: "USD".to_string(),
             balance: Some(Decimal::new(8943267, 2)), // $89,432.67
@@ -193,23 +113,7 @@ pub fn generate_demo_accounts() -> Vec<Account> {
             created_at: now,
             updated_at: now,
             is_manual: false,
-            sf_id: None,
-            sf_name: None,
-            sf_currency: None,
-            sf_balance: None,
-            sf_available_balance: None,
-            sf_balance_date: None,
-            sf_org_name: None,
-            sf_org_url: None,
-            sf_org_domain: None,
-            sf_extra: None,
-            lf_id: None,
-            lf_name: None,
-            lf_institution_name: None,
-            lf_institution_logo: None,
-            lf_provider: None,
-            lf_currency: None,
-            lf_status: None,
+            provider: ProviderData::default(),
         },
     ]
 }
@@ -616,6 +520,7 @@ pub fn generate_demo_balance_snapshots() -> Vec<BalanceSnapshot> {
                 balance: Decimal::new((balance * 100.0).round() as i64, 2),
                 snapshot_time,
                 source: Some("sync".to_string()),
+                csv_batch_id: None,
                 created_at: now,
                 updated_at: now,
             });
@@ -675,6 +580,8 @@ impl DataAggregationProvider for DemoDataProvider {
             accounts: generate_demo_accounts(),
             balance_snapshots: generate_demo_balance_snapshots(),
             warnings: Vec::new(),
+            empty_accounts: Vec::new(),
+            holdings: None,
         })
     }
 
@@ -708,6 +615,7 @@ impl DataAggregationProvider for DemoDataProvider {
         Ok(FetchTransactionsResult {
             transactions: txs_with_ids,
             warnings: Vec::new(),
+            ..Default::default()
         })
     }
 }
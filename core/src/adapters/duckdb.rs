@@ -2,18 +2,31 @@
 
 use std::fs::{File, OpenOptions};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
 
 use fs2::FileExt;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use duckdb::{params, Connection};
 use rust_decimal::Decimal;
 use sqlparser::dialect::DuckDbDialect;
 use sqlparser::parser::Parser;
 use uuid::Uuid;
-
-use crate::domain::{Account, AutoTagRule, BalanceSnapshot, Transaction};
+use zeroize::Zeroizing;
+
+use crate::adapters::credentials::{
+    has_plaintext_credentials, has_sealed_credentials, CredentialCipher, KeySource, NoKeychain,
+    OsKeychain,
+};
+use crate::adapters::extensions::{self, ExtensionStatus};
+use crate::adapters::write_queue::{QueueSlot, WriteQueue, WriteQueueStats};
+use crate::domain::{
+    Account, AutoTagRule, BalanceSnapshot, EncryptionKey, Holding, HoldingLot, ProviderData,
+    SecurityPrice, SignPolicy, StatementCycle, Transaction,
+};
+use crate::ports::Repository;
 use crate::services::MigrationService;
 
 /// Validate SQL syntax before execution to catch malformed queries early.
@@ -45,7 +58,44 @@ fn validate_sql_syntax(sql: &str) -> Result<()> {
 /// This allows the CLI to work while the desktop app is idle.
 pub struct DuckDbRepository {
     db_path: PathBuf,
-    encryption_key: Option<String>,
+    /// Wiped from memory once the last handle is gone (e.g. on lock)
+    encryption_key: Option<EncryptionKey>,
+    /// Loaded on first credential access so plain queries never touch the keychain
+    credentials: OnceLock<CredentialCipher>,
+    /// Held while loading `credentials`, so only one caller can make a new key
+    credentials_init: Mutex<()>,
+    /// Where the credentials key is kept
+    key_source: Arc<dyn KeySource>,
+    /// Orders this process's access to the database by priority
+    queue: Arc<WriteQueue>,
+    /// Every connection is opened in DuckDB's READ_ONLY mode
+    read_only: bool,
+}
+
+/// The database contents as of a moment, for caches to compare
+///
+/// Two versions are equal only if no write happened in between: writes by
+/// this process are counted, and writes by another process (the CLI while
+/// the app runs) change the file's modification time when they checkpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DataVersion {
+    writes: u64,
+    modified: Option<SystemTime>,
+}
+
+/// The OS keychain, except in unit tests, which mustn't touch the user's
+fn default_key_source() -> Arc<dyn KeySource> {
+    if cfg!(test) {
+        Arc::new(NoKeychain)
+    } else {
+        Arc::new(OsKeychain)
+    }
+}
+
+/// Held database access: the file lock, then the queue slot (dropped in that order)
+struct DbLock {
+    _file: File,
+    _slot: QueueSlot,
 }
 
 impl DuckDbRepository {
@@ -64,7 +114,12 @@ impl DuckDbRepository {
 
         let repo = Self {
             db_path: db_path.to_path_buf(),
-            encryption_key: encryption_key.map(|k| k.to_string()),
+            encryption_key: encryption_key.map(|k| EncryptionKey::new(k.to_string())),
+            credentials: OnceLock::new(),
+            credentials_init: Mutex::new(()),
+            key_source: default_key_source(),
+            queue: WriteQueue::for_path(db_path),
+            read_only: false,
         };
 
         // Verify we can open the database (acquires and releases lock)
@@ -73,12 +128,51 @@ impl DuckDbRepository {
         Ok(repo)
     }
 
+    /// Open an existing database that this handle will never write
+    ///
+    /// Every connection is opened in DuckDB's READ_ONLY mode, so writes fail
+    /// in the engine as well as here. Nothing is created: the database must
+    /// exist, and migrations aren't run.
+    pub fn open_read_only(db_path: &Path, encryption_key: Option<&str>) -> Result<Self> {
+        if !db_path.is_file() {
+            anyhow::bail!("No database at {}", db_path.display());
+        }
+
+        let repo = Self {
+            db_path: db_path.to_path_buf(),
+            encryption_key: encryption_key.map(|k| EncryptionKey::new(k.to_string())),
+            credentials: OnceLock::new(),
+            credentials_init: Mutex::new(()),
+            key_source: default_key_source(),
+            queue: WriteQueue::for_path(db_path),
+            read_only: true,
+        };
+        repo.with_connection(|_| Ok(()))?;
+
+        Ok(repo)
+    }
+
+    /// Whether this handle was opened with [`Self::open_read_only`]
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Fail for a read-only handle, before anything is written
+    fn ensure_writable(&self) -> Result<()> {
+        if self.read_only {
+            anyhow::bail!("The database is open read-only");
+        }
+        Ok(())
+    }
+
     /// Acquire the filesystem lock for database access.
     ///
     /// This prevents concurrent access from multiple processes (app, CLI, etc.).
     /// Uses a blocking lock - if another process holds the lock, this will wait
-    /// until it's released.
-    fn acquire_lock(&self) -> Result<File> {
+    /// until it's released. Threads in this process first queue in the
+    /// database's `WriteQueue`, so interactive work isn't stuck behind a sync.
+    fn acquire_lock(&self) -> Result<DbLock> {
+        let slot = self.queue.acquire();
         let lock_path = self.db_path.with_extension("duckdb.lock");
 
         let lock_file = OpenOptions::new()
@@ -93,7 +187,32 @@ impl DuckDbRepository {
             .lock_exclusive()
             .map_err(|e| anyhow!("Failed to acquire database lock: {}", e))?;
 
-        Ok(lock_file)
+        Ok(DbLock {
+            _file: lock_file,
+            _slot: slot,
+        })
+    }
+
+    /// Whether another operation holds the database lock right now
+    ///
+    /// Never waits: takes and immediately releases the lock if it is free.
+    pub fn is_locked(&self) -> bool {
+        let lock_path = self.db_path.with_extension("duckdb.lock");
+        match OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_path)
+        {
+            Ok(lock_file) => lock_file.try_lock_exclusive().is_err(),
+            Err(_) => false,
+        }
+    }
+
+    /// Hex encryption key, if the database is encrypted
+    fn key(&self) -> Option<&str> {
+        self.encryption_key.as_ref().map(EncryptionKey::expose)
     }
 
     /// Execute a read-only operation with the database connection.
@@ -108,7 +227,7 @@ impl DuckDbRepository {
         let _lock = self.acquire_lock()?;
 
         // Open connection (closed when conn drops)
-        let conn = Self::try_open_connection(&self.db_path, self.encryption_key.as_deref())?;
+        let conn = Self::try_open_connection_with_mode(&self.db_path, self.key(), self.read_only)?;
 
         // Execute the operation
         f(&conn)
@@ -124,14 +243,18 @@ impl DuckDbRepository {
     where
         F: FnOnce(&Connection) -> Result<T>,
     {
+        self.ensure_writable()?;
+
         // Acquire filesystem lock (released when _lock drops)
         let _lock = self.acquire_lock()?;
 
         // Open connection (closed when conn drops)
-        let conn = Self::try_open_connection(&self.db_path, self.encryption_key.as_deref())?;
+        let conn = Self::try_open_connection(&self.db_path, self.key())?;
 
-        // Execute the operation
-        let result = f(&conn)?;
+        // Execute the operation; a failed one may still have written something
+        let result = f(&conn);
+        self.queue.record_write();
+        let result = result?;
 
         // Checkpoint to flush WAL before releasing lock
         let _ = conn.execute("CHECKPOINT", []);
@@ -150,11 +273,7 @@ impl DuckDbRepository {
         F: FnOnce(&Connection) -> Result<T>,
     {
         let _lock = self.acquire_lock()?;
-        let conn = Self::try_open_connection_with_mode(
-            &self.db_path,
-            self.encryption_key.as_deref(),
-            true,
-        )?;
+        let conn = Self::try_open_connection_with_mode(&self.db_path, self.key(), true)?;
         f(&conn)
     }
 
@@ -179,14 +298,12 @@ impl DuckDbRepository {
             let config = duckdb::Config::default().enable_autoload_extension(false)?;
             let conn = Connection::open_in_memory_with_flags(config)?;
             let access_mode = if read_only { ", READ_ONLY" } else { "" };
-            conn.execute(
-                &format!(
-                    "ATTACH '{}' AS main_db (ENCRYPTION_KEY '{}'{access_mode})",
-                    db_path.display(),
-                    key
-                ),
-                [],
-            )?;
+            let attach = Zeroizing::new(format!(
+                "ATTACH '{}' AS main_db (ENCRYPTION_KEY '{}'{access_mode})",
+                db_path.display(),
+                key
+            ));
+            conn.execute(&attach, [])?;
             conn.execute("USE main_db", [])?;
             conn
         } else {
@@ -217,6 +334,22 @@ impl DuckDbRepository {
     /// Ensure database schema exists (runs pending migrations)
     pub fn ensure_schema(&self) -> Result<()> {
         self.run_migrations()?;
+        self.seal_plaintext_credentials()?;
+        Ok(())
+    }
+
+    /// Fail unless every migration has been applied
+    ///
+    /// For read-only handles, which can't run them.
+    pub fn require_current_schema(&self) -> Result<()> {
+        let pending = self.with_connection(|conn| MigrationService::new(conn).get_pending())?;
+        if let Some(first) = pending.first() {
+            anyhow::bail!(
+                "The database needs upgrading ({} pending, starting with {}); open it once with write access",
+                pending.len(),
+                first
+            );
+        }
         Ok(())
     }
 
@@ -237,6 +370,26 @@ impl DuckDbRepository {
         &self.db_path
     }
 
+    /// Current version of the data, which changes with every write
+    pub fn data_version(&self) -> DataVersion {
+        DataVersion {
+            writes: self.queue.writes(),
+            modified: std::fs::metadata(&self.db_path)
+                .and_then(|m| m.modified())
+                .ok(),
+        }
+    }
+
+    /// Wait counts and times of this process's database access queue
+    pub fn write_queue_stats(&self) -> WriteQueueStats {
+        self.queue.stats()
+    }
+
+    /// Which optional extensions load, tried on a read-only connection
+    pub fn extension_status(&self) -> Result<Vec<ExtensionStatus>> {
+        self.with_readonly_connection(|conn| Ok(extensions::probe(conn)))
+    }
+
     // === Account operations ===
 
     pub fn get_accounts(&self) -> Result<Vec<Account>> {
@@ -249,11 +402,7 @@ impl DuckDbRepository {
                         (SELECT balance FROM sys_balance_snapshots bs
                          WHERE bs.account_id = a.account_id
                          ORDER BY bs.snapshot_time DESC LIMIT 1) as latest_balance,
-                        a.classification, a.is_manual,
-                        a.sf_id, a.sf_name, a.sf_currency, a.sf_balance, a.sf_available_balance,
-                        a.sf_balance_date, a.sf_org_name, a.sf_org_url, a.sf_org_domain, a.sf_extra,
-                        a.lf_id, a.lf_name, a.lf_institution_name, a.lf_institution_logo,
-                        a.lf_provider, a.lf_currency, a.lf_status
+                        a.classification, a.is_manual, a.provider_metadata
                  FROM sys_accounts a",
             )?;
 
@@ -275,11 +424,7 @@ impl DuckDbRepository {
                         (SELECT balance FROM sys_balance_snapshots bs
                          WHERE bs.account_id = a.account_id
                          ORDER BY bs.snapshot_time DESC LIMIT 1) as latest_balance,
-                        a.classification, a.is_manual,
-                        a.sf_id, a.sf_name, a.sf_currency, a.sf_balance, a.sf_available_balance,
-                        a.sf_balance_date, a.sf_org_name, a.sf_org_url, a.sf_org_domain, a.sf_extra,
-                        a.lf_id, a.lf_name, a.lf_institution_name, a.lf_institution_logo,
-                        a.lf_provider, a.lf_currency, a.lf_status
+                        a.classification, a.is_manual, a.provider_metadata
                  FROM sys_accounts a WHERE a.account_id = ?",
             )?;
 
@@ -294,15 +439,12 @@ impl DuckDbRepository {
         // 0: account_id, 1: name, 2: nickname, 3: account_type, 4: currency,
         // 5: external_ids, 6: institution_name, 7: institution_url, 8: institution_domain,
         // 9: created_at, 10: updated_at, 11: latest_balance, 12: classification, 13: is_manual,
-        // 14: sf_id, 15: sf_name, 16: sf_currency, 17: sf_balance, 18: sf_available_balance,
-        // 19: sf_balance_date, 20: sf_org_name, 21: sf_org_url, 22: sf_org_domain, 23: sf_extra,
-        // 24: lf_id, 25: lf_name, 26: lf_institution_name, 27: lf_institution_logo,
-        // 28: lf_provider, 29: lf_currency, 30: lf_status
+        // 14: provider_metadata
         let id_str: String = row.get(0)?;
-        // Note: column 5 (external_ids) is read but not used - kept for backwards compat
         let created_str: String = row.get(9).unwrap_or_default();
         let updated_str: String = row.get(10).unwrap_or_default();
-        let sf_extra_json: Option<String> = row.get(23).ok();
+        let external_ids: Option<String> = row.get(5).ok();
+        let provider_metadata: Option<String> = row.get(14).ok();
 
         // Parse UUID - if this fails, skip the row rather than creating a new UUID
         let id = Uuid::parse_str(&id_str).map_err(|e| {
@@ -321,87 +463,61 @@ impl DuckDbRepository {
             institution_domain: row.get(8).ok(),
             created_at: parse_timestamp(&created_str),
             updated_at: parse_timestamp(&updated_str),
-            // Balance from latest balance snapshot (column 11)
-            balance: row
-                .get::<_, Option<f64>>(11)
-                .ok()
-                .flatten()
-                .map(|f| Decimal::try_from(f).unwrap_or_default()),
+            // Balance from latest balance snapshot (column 11), read exactly like
+            // transaction amounts; the f64 fallback covers non-DECIMAL columns
+            balance: match row.get_ref(11) {
+                Ok(duckdb::types::ValueRef::Decimal(d)) => Some(d),
+                _ => row
+                    .get::<_, Option<f64>>(11)
+                    .ok()
+                    .flatten()
+                    .and_then(|f| Decimal::try_from(f).ok()),
+            },
             // Manual flag (column 13)
             is_manual: row
                 .get::<_, Option<bool>>(13)
                 .ok()
                 .flatten()
                 .unwrap_or(false),
-            // SimpleFIN fields (columns 14-23)
-            sf_id: row.get(14).ok(),
-            sf_name: row.get(15).ok(),
-            sf_currency: row.get(16).ok(),
-            sf_balance: row.get(17).ok(),
-            sf_available_balance: row.get(18).ok(),
-            sf_balance_date: row.get(19).ok(),
-            sf_org_name: row.get(20).ok(),
-            sf_org_url: row.get(21).ok(),
-            sf_org_domain: row.get(22).ok(),
-            sf_extra: sf_extra_json.and_then(|s| serde_json::from_str(&s).ok()),
-            // Lunchflow fields (columns 24-30)
-            lf_id: row.get(24).ok(),
-            lf_name: row.get(25).ok(),
-            lf_institution_name: row.get(26).ok(),
-            lf_institution_logo: row.get(27).ok(),
-            lf_provider: row.get(28).ok(),
-            lf_currency: row.get(29).ok(),
-            lf_status: row.get(30).ok(),
+            // Provider IDs and fields (columns 5 and 14)
+            provider: ProviderData::from_json(
+                external_ids.as_deref(),
+                provider_metadata.as_deref(),
+            ),
         })
     }
 
     pub fn upsert_account(&self, account: &Account) -> Result<()> {
         self.with_connection_write(|conn| {
-            // Write empty JSON for external_ids - kept for backwards compat with DB schema
-            let external_ids = "{}";
-            let sf_extra = account.sf_extra.as_ref().map(|v| v.to_string());
+            let external_ids = account.provider.external_ids_json();
+            let provider_metadata = account.provider.provider_metadata_json();
 
             // Use COALESCE to preserve user-edited values like Python CLI does
             // Note: balance is stored in balance_snapshots, not in accounts table (matching Python schema)
-            // Classification is preserved on sync - we only set it if the user hasn't already set one
+            // Classification is preserved on sync - we only set it if the user hasn't already set one,
+            // or when an untyped account is given a type, since the old value was only a default
             conn.execute(
                 "INSERT INTO sys_accounts (account_id, name, nickname, account_type, classification, currency,
                                            external_ids, institution_name, institution_url, institution_domain,
-                                           created_at, updated_at, is_manual,
-                                           sf_id, sf_name, sf_currency, sf_balance, sf_available_balance,
-                                           sf_balance_date, sf_org_name, sf_org_url, sf_org_domain, sf_extra,
-                                           lf_id, lf_name, lf_institution_name, lf_institution_logo,
-                                           lf_provider, lf_currency, lf_status)
-                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                                           created_at, updated_at, is_manual, provider_metadata)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                  ON CONFLICT (account_id) DO UPDATE SET
                     name = EXCLUDED.name,
                     nickname = COALESCE(sys_accounts.nickname, EXCLUDED.nickname),
                     account_type = COALESCE(sys_accounts.account_type, EXCLUDED.account_type),
-                    classification = COALESCE(sys_accounts.classification, EXCLUDED.classification),
+                    classification = CASE
+                        WHEN sys_accounts.account_type IS NULL AND EXCLUDED.account_type IS NOT NULL
+                        THEN EXCLUDED.classification
+                        ELSE COALESCE(sys_accounts.classification, EXCLUDED.classification)
+                    END,
                     currency = EXCLUDED.currency,
-                    external_ids = EXCLUDED.external_ids,
+                    external_ids = json_merge_patch(COALESCE(sys_accounts.external_ids, '{}'), EXCLUDED.external_ids),
                     institution_name = COALESCE(EXCLUDED.institution_name, sys_accounts.institution_name),
                     institution_url = COALESCE(EXCLUDED.institution_url, sys_accounts.institution_url),
                     institution_domain = COALESCE(EXCLUDED.institution_domain, sys_accounts.institution_domain),
                     updated_at = EXCLUDED.updated_at,
                     is_manual = COALESCE(sys_accounts.is_manual, EXCLUDED.is_manual),
-                    sf_id = COALESCE(EXCLUDED.sf_id, sys_accounts.sf_id),
-                    sf_name = COALESCE(EXCLUDED.sf_name, sys_accounts.sf_name),
-                    sf_currency = COALESCE(EXCLUDED.sf_currency, sys_accounts.sf_currency),
-                    sf_balance = COALESCE(EXCLUDED.sf_balance, sys_accounts.sf_balance),
-                    sf_available_balance = COALESCE(EXCLUDED.sf_available_balance, sys_accounts.sf_available_balance),
-                    sf_balance_date = COALESCE(EXCLUDED.sf_balance_date, sys_accounts.sf_balance_date),
-                    sf_org_name = COALESCE(EXCLUDED.sf_org_name, sys_accounts.sf_org_name),
-                    sf_org_url = COALESCE(EXCLUDED.sf_org_url, sys_accounts.sf_org_url),
-                    sf_org_domain = COALESCE(EXCLUDED.sf_org_domain, sys_accounts.sf_org_domain),
-                    sf_extra = COALESCE(EXCLUDED.sf_extra, sys_accounts.sf_extra),
-                    lf_id = COALESCE(EXCLUDED.lf_id, sys_accounts.lf_id),
-                    lf_name = COALESCE(EXCLUDED.lf_name, sys_accounts.lf_name),
-                    lf_institution_name = COALESCE(EXCLUDED.lf_institution_name, sys_accounts.lf_institution_name),
-                    lf_institution_logo = COALESCE(EXCLUDED.lf_institution_logo, sys_accounts.lf_institution_logo),
-                    lf_provider = COALESCE(EXCLUDED.lf_provider, sys_accounts.lf_provider),
-                    lf_currency = COALESCE(EXCLUDED.lf_currency, sys_accounts.lf_currency),
-                    lf_status = COALESCE(EXCLUDED.lf_status, sys_accounts.lf_status)",
+                    provider_metadata = json_merge_patch(COALESCE(sys_accounts.provider_metadata, '{}'), EXCLUDED.provider_metadata)",
                 params![
                     account.id.to_string(),
                     account.name,
@@ -416,25 +532,11 @@ impl DuckDbRepository {
                     account.created_at.to_rfc3339(),
                     account.updated_at.to_rfc3339(),
                     account.is_manual,
-                    account.sf_id,
-                    account.sf_name,
-                    account.sf_currency,
-                    account.sf_balance,
-                    account.sf_available_balance,
-                    account.sf_balance_date,
-                    account.sf_org_name,
-                    account.sf_org_url,
-                    account.sf_org_domain,
-                    sf_extra,
-                    account.lf_id,
-                    account.lf_name,
-                    account.lf_institution_name,
-                    account.lf_institution_logo,
-                    account.lf_provider,
-                    account.lf_currency,
-                    account.lf_status,
+                    provider_metadata,
                 ],
             )?;
+            let account_id = account.id.to_string();
+            Self::log_change(conn, Captured::Account, UPSERT, "account_id = ?", &[&account_id])?;
 
             Ok(())
         })
@@ -459,7 +561,16 @@ impl DuckDbRepository {
     pub fn delete_account(&self, account_id: &str) -> Result<()> {
         self.with_connection_write(|conn| {
             // Delete in order to respect foreign key constraints:
-            // transactions and snapshots reference accounts, so delete them first
+            // transactions, snapshots and holdings reference accounts, so delete them first
+
+            // Log the rows before they go
+            for table in [
+                Captured::Transaction,
+                Captured::BalanceSnapshot,
+                Captured::Account,
+            ] {
+                Self::log_change(conn, table, DELETE, "account_id = ?", &[&account_id])?;
+            }
 
             // 1. Delete all transactions (including soft-deleted ones)
             conn.execute(
@@ -473,7 +584,19 @@ impl DuckDbRepository {
                 params![account_id],
             )?;
 
-            // 3. Delete the account
+            // 3. Delete holdings and their lots
+            conn.execute(
+                "DELETE FROM sys_holding_lots WHERE holding_id IN (
+                    SELECT holding_id FROM sys_holdings WHERE account_id = ?
+                 )",
+                params![account_id],
+            )?;
+            conn.execute(
+                "DELETE FROM sys_holdings WHERE account_id = ?",
+                params![account_id],
+            )?;
+
+            // 4. Delete the account
             conn.execute(
                 "DELETE FROM sys_accounts WHERE account_id = ?",
                 params![account_id],
@@ -483,6 +606,189 @@ impl DuckDbRepository {
         })
     }
 
+    /// Replace an account's name
+    pub fn update_account_name(&self, account_id: &str, name: &str) -> Result<()> {
+        self.with_connection_write(|conn| {
+            conn.execute(
+                "UPDATE sys_accounts SET name = ?, updated_at = CURRENT_TIMESTAMP WHERE account_id = ?",
+                params![name, account_id],
+            )?;
+            Self::log_change(conn, Captured::Account, UPSERT, "account_id = ?", &[&account_id])?;
+            Ok(())
+        })
+    }
+
+    /// Set or clear an account's nickname
+    pub fn update_account_nickname(&self, account_id: &str, nickname: Option<&str>) -> Result<()> {
+        self.with_connection_write(|conn| {
+            conn.execute(
+                "UPDATE sys_accounts SET nickname = ?, updated_at = CURRENT_TIMESTAMP WHERE account_id = ?",
+                params![nickname, account_id],
+            )?;
+            Self::log_change(conn, Captured::Account, UPSERT, "account_id = ?", &[&account_id])?;
+            Ok(())
+        })
+    }
+
+    /// Set an account's type along with its classification
+    pub fn update_account_type(
+        &self,
+        account_id: &str,
+        account_type: &str,
+        classification: &str,
+    ) -> Result<()> {
+        self.with_connection_write(|conn| {
+            conn.execute(
+                "UPDATE sys_accounts SET account_type = ?, classification = ?, updated_at = CURRENT_TIMESTAMP
+                 WHERE account_id = ?",
+                params![account_type, classification, account_id],
+            )?;
+            Self::log_change(conn, Captured::Account, UPSERT, "account_id = ?", &[&account_id])?;
+            Ok(())
+        })
+    }
+
+    /// Archive an account now, or bring it back
+    pub fn set_account_archived(&self, account_id: &str, archived: bool) -> Result<()> {
+        self.with_connection_write(|conn| {
+            conn.execute(
+                "UPDATE sys_accounts
+                 SET archived_at = CASE WHEN ? THEN COALESCE(archived_at, CURRENT_TIMESTAMP) ELSE NULL END,
+                     updated_at = CURRENT_TIMESTAMP
+                 WHERE account_id = ?",
+                params![archived, account_id],
+            )?;
+            Self::log_change(conn, Captured::Account, UPSERT, "account_id = ?", &[&account_id])?;
+            Ok(())
+        })
+    }
+
+    /// When each archived account was archived, by account ID
+    pub fn get_archived_accounts(
+        &self,
+    ) -> Result<std::collections::HashMap<String, DateTime<Utc>>> {
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT account_id, archived_at::VARCHAR FROM sys_accounts WHERE archived_at IS NOT NULL",
+            )?;
+            let archived = stmt
+                .query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })?
+                .filter_map(|r| r.ok())
+                .map(|(id, at)| (id, parse_naive_datetime(&at).and_utc()))
+                .collect();
+            Ok(archived)
+        })
+    }
+
+    /// Set a credit card's statement cycle, or clear it with None
+    pub fn set_account_statement_cycle(
+        &self,
+        account_id: &str,
+        cycle: Option<StatementCycle>,
+    ) -> Result<()> {
+        self.with_connection_write(|conn| {
+            conn.execute(
+                "UPDATE sys_accounts
+                 SET statement_closing_day = ?, statement_due_day = ?, updated_at = CURRENT_TIMESTAMP
+                 WHERE account_id = ?",
+                params![
+                    cycle.map(|c| c.closing_day),
+                    cycle.map(|c| c.due_day),
+                    account_id
+                ],
+            )?;
+            Self::log_change(conn, Captured::Account, UPSERT, "account_id = ?", &[&account_id])?;
+            Ok(())
+        })
+    }
+
+    /// Statement cycles of the accounts that have one, by account ID
+    pub fn get_statement_cycles(
+        &self,
+    ) -> Result<std::collections::HashMap<String, StatementCycle>> {
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT account_id, statement_closing_day, statement_due_day FROM sys_accounts
+                 WHERE statement_closing_day IS NOT NULL AND statement_due_day IS NOT NULL",
+            )?;
+            let cycles = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        StatementCycle {
+                            closing_day: row.get(1)?,
+                            due_day: row.get(2)?,
+                        },
+                    ))
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+            Ok(cycles)
+        })
+    }
+
+    /// Set how an account's source signs amounts
+    pub fn set_account_sign_policy(&self, account_id: &str, policy: SignPolicy) -> Result<()> {
+        let stored = match policy {
+            SignPolicy::AsReported => None,
+            SignPolicy::Inverted => Some(policy.as_str()),
+        };
+        self.with_connection_write(|conn| {
+            conn.execute(
+                "UPDATE sys_accounts SET sign_policy = ?, updated_at = CURRENT_TIMESTAMP
+                 WHERE account_id = ?",
+                params![stored, account_id],
+            )?;
+            Self::log_change(
+                conn,
+                Captured::Account,
+                UPSERT,
+                "account_id = ?",
+                &[&account_id],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Sign policies of the accounts that don't store amounts as reported, by account ID
+    pub fn get_sign_policies(&self) -> Result<std::collections::HashMap<String, SignPolicy>> {
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT account_id, sign_policy FROM sys_accounts WHERE sign_policy IS NOT NULL",
+            )?;
+            let policies = stmt
+                .query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })?
+                .filter_map(|r| r.ok())
+                .filter_map(|(id, policy)| Some((id, policy.parse().ok()?)))
+                .collect();
+            Ok(policies)
+        })
+    }
+
+    /// Negate the amounts of an account's synced and imported transactions,
+    /// returning how many changed. Manual transactions are left alone.
+    pub fn invert_transaction_amounts(&self, account_id: &str) -> Result<usize> {
+        self.with_connection_write(|conn| {
+            let filter = "account_id = ? AND deleted_at IS NULL AND NOT COALESCE(is_manual, FALSE)";
+            let changed = conn.execute(
+                &format!(
+                    "UPDATE sys_transactions SET amount = -amount, updated_at = CURRENT_TIMESTAMP
+                     WHERE {}",
+                    filter
+                ),
+                params![account_id],
+            )?;
+            if changed > 0 {
+                Self::log_change(conn, Captured::Transaction, UPSERT, filter, &[&account_id])?;
+            }
+            Ok(changed)
+        })
+    }
+
     // === Transaction operations ===
 
     pub fn get_transactions(&self) -> Result<Vec<Transaction>> {
@@ -492,10 +798,9 @@ impl DuckDbRepository {
             // See parse_duckdb_array() for the parsing logic.
             let mut stmt = conn.prepare(
                 "SELECT transaction_id, account_id, amount, description, transaction_date::VARCHAR,
-                        posted_date::VARCHAR, CAST(tags AS VARCHAR) as tags, external_ids, deleted_at, parent_transaction_id,
+                        posted_date::VARCHAR, CAST(tags AS VARCHAR) as tags, external_ids, deleted_at::VARCHAR, parent_transaction_id,
                         created_at, updated_at, csv_fingerprint, csv_batch_id, is_manual, tags_auto_applied,
-                        sf_id, sf_posted, sf_amount, sf_description, sf_transacted_at, sf_pending, sf_extra,
-                        lf_id, lf_account_id, lf_amount, lf_currency, lf_date::VARCHAR, lf_merchant, lf_description, lf_is_pending
+                        check_number, reference, currency, excluded_from_reports, provider_metadata, pending
                  FROM sys_transactions
                  WHERE deleted_at IS NULL"
             )?;
@@ -515,10 +820,9 @@ impl DuckDbRepository {
             // CAST(tags AS VARCHAR) required - see get_transactions() for explanation
             let mut stmt = conn.prepare(
                 "SELECT transaction_id, account_id, amount, description, transaction_date::VARCHAR,
-                        posted_date::VARCHAR, CAST(tags AS VARCHAR) as tags, external_ids, deleted_at, parent_transaction_id,
+                        posted_date::VARCHAR, CAST(tags AS VARCHAR) as tags, external_ids, deleted_at::VARCHAR, parent_transaction_id,
                         created_at, updated_at, csv_fingerprint, csv_batch_id, is_manual, tags_auto_applied,
-                        sf_id, sf_posted, sf_amount, sf_description, sf_transacted_at, sf_pending, sf_extra,
-                        lf_id, lf_account_id, lf_amount, lf_currency, lf_date::VARCHAR, lf_merchant, lf_description, lf_is_pending
+                        check_number, reference, currency, excluded_from_reports, provider_metadata, pending
                  FROM sys_transactions
                  WHERE account_id = ? AND deleted_at IS NULL
                  ORDER BY transaction_date DESC"
@@ -544,15 +848,25 @@ impl DuckDbRepository {
         })
     }
 
-    /// Get the maximum transaction date from the database
-    pub fn get_max_transaction_date(&self) -> Result<Option<NaiveDate>> {
+    /// Date of each account's latest transaction, for accounts that have any
+    pub fn get_latest_transaction_dates(
+        &self,
+    ) -> Result<std::collections::HashMap<Uuid, NaiveDate>> {
         self.with_connection(|conn| {
-            let result: Option<String> = conn.query_row(
-                "SELECT MAX(transaction_date)::VARCHAR FROM sys_transactions WHERE deleted_at IS NULL",
-                [],
-                |row| row.get(0),
+            let mut stmt = conn.prepare(
+                "SELECT account_id::VARCHAR, MAX(transaction_date)::VARCHAR
+                 FROM sys_transactions
+                 WHERE deleted_at IS NULL
+                 GROUP BY account_id",
             )?;
-            Ok(result.map(|s| parse_date(&s)))
+            let dates = stmt
+                .query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })?
+                .filter_map(|r| r.ok())
+                .filter_map(|(id, date)| Some((Uuid::parse_str(&id).ok()?, parse_date(&date))))
+                .collect();
+            Ok(dates)
         })
     }
 
@@ -589,11 +903,16 @@ impl DuckDbRepository {
         // 0: transaction_id, 1: account_id, 2: amount, 3: description, 4: transaction_date,
         // 5: posted_date, 6: tags, 7: external_ids, 8: deleted_at, 9: parent_transaction_id,
         // 10: created_at, 11: updated_at, 12: csv_fingerprint, 13: csv_batch_id, 14: is_manual, 15: tags_auto_applied,
-        // 16: sf_id, 17: sf_posted, 18: sf_amount, 19: sf_description, 20: sf_transacted_at, 21: sf_pending, 22: sf_extra,
-        // 23: lf_id, 24: lf_account_id, 25: lf_amount, 26: lf_currency, 27: lf_date, 28: lf_merchant, 29: lf_description, 30: lf_is_pending
+        // 16: check_number, 17: reference, 18: currency, 19: excluded_from_reports, 20: provider_metadata,
+        // 21: pending
         let id_str: String = row.get(0)?;
         let account_id_str: String = row.get(1)?;
-        let amount: f64 = row.get(2).unwrap_or(0.0);
+        // Read DECIMAL amounts exactly; an f64 read would round them to whole units
+        let amount = match row.get_ref(2) {
+            Ok(duckdb::types::ValueRef::Decimal(d)) => Some(d),
+            _ => None,
+        };
+        let amount_f64: f64 = row.get(2).unwrap_or(0.0);
         let tx_date_str: String = row.get(4).unwrap_or_default();
         let posted_date_str: String = row.get(5).unwrap_or_default();
 
@@ -602,12 +921,12 @@ impl DuckDbRepository {
         let tags_str: String = row.get(6).unwrap_or_else(|_| "[]".to_string());
         let tags = parse_duckdb_array(&tags_str);
 
-        // Note: column 7 (external_ids) is in the query but not used - kept for backwards compat
+        let external_ids: Option<String> = row.get(7).ok();
+        let deleted_str: Option<String> = row.get(8).ok();
         let parent_id_str: Option<String> = row.get(9).ok();
         let created_str: String = row.get(10).unwrap_or_default();
         let updated_str: String = row.get(11).unwrap_or_default();
-        let sf_extra_json: Option<String> = row.get(22).ok();
-        let lf_date_str: Option<String> = row.get(27).ok();
+        let provider_metadata: Option<String> = row.get(20).ok();
 
         // Parse UUIDs - if these fail, skip the row rather than creating new UUIDs
         let id = Uuid::parse_str(&id_str).map_err(|e| {
@@ -618,9 +937,12 @@ impl DuckDbRepository {
         })?;
 
         // Parse amount - if conversion fails, this is a data integrity issue
-        let amount = Decimal::try_from(amount).map_err(|e| {
-            duckdb::Error::FromSqlConversionFailure(2, duckdb::types::Type::Text, Box::new(e))
-        })?;
+        let amount = match amount {
+            Some(d) => d,
+            None => Decimal::try_from(amount_f64).map_err(|e| {
+                duckdb::Error::FromSqlConversionFailure(2, duckdb::types::Type::Text, Box::new(e))
+            })?,
+        };
 
         Ok(Transaction {
             id,
@@ -630,13 +952,18 @@ impl DuckDbRepository {
             transaction_date: parse_date(&tx_date_str),
             posted_date: parse_date(&posted_date_str),
             tags,
-            deleted_at: None,
+            deleted_at: deleted_str.map(|s| parse_naive_datetime(&s).and_utc()),
             parent_transaction_id: parent_id_str.and_then(|s| Uuid::parse_str(&s).ok()),
             created_at: parse_timestamp(&created_str),
             updated_at: parse_timestamp(&updated_str),
             // CSV Import tracking (columns 12-13)
             csv_fingerprint: row.get(12).ok(),
             csv_batch_id: row.get(13).ok(),
+            // Reconciliation identifiers (columns 16-17)
+            check_number: row.get(16).ok(),
+            reference: row.get(17).ok(),
+            // Currency from a multi-currency CSV (column 18)
+            currency: row.get(18).ok(),
             // Manual flag (column 14)
             is_manual: row
                 .get::<_, Option<bool>>(14)
@@ -649,35 +976,30 @@ impl DuckDbRepository {
                 .ok()
                 .flatten()
                 .unwrap_or(false),
-            // SimpleFIN fields (columns 16-22)
-            sf_id: row.get(16).ok(),
-            sf_posted: row.get(17).ok(),
-            sf_amount: row.get(18).ok(),
-            sf_description: row.get(19).ok(),
-            sf_transacted_at: row.get(20).ok(),
-            sf_pending: row.get(21).ok(),
-            sf_extra: sf_extra_json.and_then(|s| serde_json::from_str(&s).ok()),
-            // Lunchflow fields (columns 23-30)
-            lf_id: row.get(23).ok(),
-            lf_account_id: row.get(24).ok(),
-            lf_amount: row
-                .get::<_, Option<f64>>(25)
+            // Report exclusion (column 19)
+            excluded_from_reports: row
+                .get::<_, Option<bool>>(19)
+                .ok()
+                .flatten()
+                .unwrap_or(false),
+            // Pending flag (column 21)
+            pending: row
+                .get::<_, Option<bool>>(21)
                 .ok()
                 .flatten()
-                .map(|f| Decimal::try_from(f).unwrap_or_default()),
-            lf_currency: row.get(26).ok(),
-            lf_date: lf_date_str.map(|s| parse_date(&s)),
-            lf_merchant: row.get(28).ok(),
-            lf_description: row.get(29).ok(),
-            lf_is_pending: row.get(30).ok(),
+                .unwrap_or(false),
+            // Provider IDs and fields (columns 7 and 20)
+            provider: ProviderData::from_json(
+                external_ids.as_deref(),
+                provider_metadata.as_deref(),
+            ),
         })
     }
 
     pub fn upsert_transaction(&self, tx: &Transaction) -> Result<()> {
         self.with_connection_write(|conn| {
-            // Write empty JSON for external_ids - kept for backwards compat with DB schema
-            let external_ids = "{}";
-            let sf_extra = tx.sf_extra.as_ref().map(|v| v.to_string());
+            let external_ids = tx.provider.external_ids_json();
+            let provider_metadata = tx.provider.provider_metadata_json();
 
             // Build tags array literal for DuckDB: ['tag1', 'tag2']
             let tags_literal = format_tags_array(&tx.tags);
@@ -688,9 +1010,8 @@ impl DuckDbRepository {
                                                transaction_date, posted_date, tags, external_ids,
                                                parent_transaction_id, created_at, updated_at,
                                                csv_fingerprint, csv_batch_id, is_manual, tags_auto_applied,
-                                               sf_id, sf_posted, sf_amount, sf_description, sf_transacted_at, sf_pending, sf_extra,
-                                               lf_id, lf_account_id, lf_amount, lf_currency, lf_date, lf_merchant, lf_description, lf_is_pending)
-                 VALUES (?, ?, ?, ?, ?, ?, {}, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                                               check_number, reference, currency, excluded_from_reports, provider_metadata, pending)
+                 VALUES (?, ?, ?, ?, ?, ?, {}, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                  ON CONFLICT (transaction_id) DO UPDATE SET
                     account_id = EXCLUDED.account_id,
                     amount = EXCLUDED.amount,
@@ -698,28 +1019,19 @@ impl DuckDbRepository {
                     transaction_date = EXCLUDED.transaction_date,
                     posted_date = EXCLUDED.posted_date,
                     tags = EXCLUDED.tags,
-                    external_ids = EXCLUDED.external_ids,
+                    external_ids = json_merge_patch(COALESCE(sys_transactions.external_ids, '{}'), EXCLUDED.external_ids),
                     parent_transaction_id = EXCLUDED.parent_transaction_id,
                     updated_at = EXCLUDED.updated_at,
                     csv_fingerprint = COALESCE(EXCLUDED.csv_fingerprint, sys_transactions.csv_fingerprint),
                     csv_batch_id = COALESCE(EXCLUDED.csv_batch_id, sys_transactions.csv_batch_id),
                     is_manual = COALESCE(sys_transactions.is_manual, EXCLUDED.is_manual),
                     tags_auto_applied = COALESCE(sys_transactions.tags_auto_applied, EXCLUDED.tags_auto_applied),
-                    sf_id = COALESCE(EXCLUDED.sf_id, sys_transactions.sf_id),
-                    sf_posted = COALESCE(EXCLUDED.sf_posted, sys_transactions.sf_posted),
-                    sf_amount = COALESCE(EXCLUDED.sf_amount, sys_transactions.sf_amount),
-                    sf_description = COALESCE(EXCLUDED.sf_description, sys_transactions.sf_description),
-                    sf_transacted_at = COALESCE(EXCLUDED.sf_transacted_at, sys_transactions.sf_transacted_at),
-                    sf_pending = COALESCE(EXCLUDED.sf_pending, sys_transactions.sf_pending),
-                    sf_extra = COALESCE(EXCLUDED.sf_extra, sys_transactions.sf_extra),
-                    lf_id = COALESCE(EXCLUDED.lf_id, sys_transactions.lf_id),
-                    lf_account_id = COALESCE(EXCLUDED.lf_account_id, sys_transactions.lf_account_id),
-                    lf_amount = COALESCE(EXCLUDED.lf_amount, sys_transactions.lf_amount),
-                    lf_currency = COALESCE(EXCLUDED.lf_currency, sys_transactions.lf_currency),
-                    lf_date = COALESCE(EXCLUDED.lf_date, sys_transactions.lf_date),
-                    lf_merchant = COALESCE(EXCLUDED.lf_merchant, sys_transactions.lf_merchant),
-                    lf_description = COALESCE(EXCLUDED.lf_description, sys_transactions.lf_description),
-                    lf_is_pending = COALESCE(EXCLUDED.lf_is_pending, sys_transactions.lf_is_pending)",
+                    excluded_from_reports = EXCLUDED.excluded_from_reports,
+                    pending = EXCLUDED.pending,
+                    provider_metadata = json_merge_patch(COALESCE(sys_transactions.provider_metadata, '{}'), EXCLUDED.provider_metadata),
+                    check_number = COALESCE(EXCLUDED.check_number, sys_transactions.check_number),
+                    reference = COALESCE(EXCLUDED.reference, sys_transactions.reference),
+                    currency = COALESCE(EXCLUDED.currency, sys_transactions.currency)",
                 tags_literal
             );
 
@@ -740,24 +1052,16 @@ impl DuckDbRepository {
                     tx.csv_batch_id,
                     tx.is_manual,
                     tx.tags_auto_applied,
-                    tx.sf_id,
-                    tx.sf_posted,
-                    tx.sf_amount,
-                    tx.sf_description,
-                    tx.sf_transacted_at,
-                    tx.sf_pending,
-                    sf_extra,
-                    tx.lf_id,
-                    tx.lf_account_id,
-                    tx.lf_amount
-                        .map(|d| d.to_string().parse::<f64>().unwrap_or(0.0)),
-                    tx.lf_currency,
-                    tx.lf_date.map(|d| d.to_string()),
-                    tx.lf_merchant,
-                    tx.lf_description,
-                    tx.lf_is_pending,
+                    tx.check_number,
+                    tx.reference,
+                    tx.currency,
+                    tx.excluded_from_reports,
+                    provider_metadata,
+                    tx.pending,
                 ],
             )?;
+            let tx_id = tx.id.to_string();
+            Self::log_change(conn, Captured::Transaction, UPSERT, "transaction_id = ?", &[&tx_id])?;
 
             Ok(())
         })
@@ -771,10 +1075,108 @@ impl DuckDbRepository {
                 tags_literal
             );
             conn.execute(&sql, params![tx_id])?;
+            Self::log_change(conn, Captured::Transaction, UPSERT, "transaction_id = ?", &[&tx_id])?;
+            Ok(())
+        })
+    }
+
+    /// Replace a transaction's description
+    pub fn update_transaction_description(&self, tx_id: &str, description: &str) -> Result<()> {
+        self.with_connection_write(|conn| {
+            conn.execute(
+                "UPDATE sys_transactions SET description = ?, updated_at = CURRENT_TIMESTAMP WHERE transaction_id = ?",
+                params![description, tx_id],
+            )?;
+            Self::log_change(conn, Captured::Transaction, UPSERT, "transaction_id = ?", &[&tx_id])?;
+            Ok(())
+        })
+    }
+
+    /// Include or exclude transactions from reports; returns how many changed
+    pub fn set_transactions_excluded(&self, tx_ids: &[String], excluded: bool) -> Result<usize> {
+        self.with_connection_write(|conn| {
+            let mut changed = 0;
+            for tx_id in tx_ids {
+                let updated = conn.execute(
+                    "UPDATE sys_transactions SET excluded_from_reports = ?, updated_at = CURRENT_TIMESTAMP
+                     WHERE transaction_id = ? AND deleted_at IS NULL
+                     AND COALESCE(excluded_from_reports, FALSE) != ?",
+                    params![excluded, tx_id, excluded],
+                )?;
+                if updated > 0 {
+                    Self::log_change(conn, Captured::Transaction, UPSERT, "transaction_id = ?", &[tx_id])?;
+                    changed += updated;
+                }
+            }
+            Ok(changed)
+        })
+    }
+
+    /// Soft delete a transaction; it stays in sys_transactions with deleted_at set
+    pub fn delete_transaction(&self, tx_id: &str) -> Result<()> {
+        self.with_connection_write(|conn| {
+            let deleted = conn.execute(
+                "UPDATE sys_transactions SET deleted_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+                 WHERE transaction_id = ? AND deleted_at IS NULL",
+                params![tx_id],
+            )?;
+            if deleted > 0 {
+                Self::log_change(
+                    conn,
+                    Captured::Transaction,
+                    DELETE,
+                    "transaction_id = ?",
+                    &[&tx_id],
+                )?;
+            }
             Ok(())
         })
     }
 
+    /// Soft-delete the transactions with these provider IDs, returning how many
+    ///
+    /// Plaid withdraws a pending transaction once it posts under a new ID.
+    pub fn delete_transactions_by_external_ids(
+        &self,
+        provider: &str,
+        ids: &[String],
+    ) -> Result<usize> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let path = external_id_path(provider);
+        self.with_connection_write(|conn| {
+            let mut deleted = 0;
+            for id in ids {
+                let tx_id: Option<String> = conn
+                    .query_row(
+                        "SELECT transaction_id FROM sys_transactions
+                         WHERE json_extract_string(external_ids, ?) = ? AND deleted_at IS NULL",
+                        params![path, id],
+                        |row| row.get(0),
+                    )
+                    .ok();
+                let Some(tx_id) = tx_id else {
+                    continue;
+                };
+                conn.execute(
+                    "UPDATE sys_transactions SET deleted_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+                     WHERE transaction_id = ?",
+                    params![tx_id],
+                )?;
+                Self::log_change(
+                    conn,
+                    Captured::Transaction,
+                    DELETE,
+                    "transaction_id = ?",
+                    &[&tx_id],
+                )?;
+                deleted += 1;
+            }
+            Ok(deleted)
+        })
+    }
+
     /// Update transaction tags and mark them as auto-applied (by rules)
     pub fn update_transaction_tags_auto(&self, tx_id: &str, tags: &[String]) -> Result<()> {
         self.with_connection_write(|conn| {
@@ -784,6 +1186,7 @@ impl DuckDbRepository {
                 tags_literal
             );
             conn.execute(&sql, params![tx_id])?;
+            Self::log_change(conn, Captured::Transaction, UPSERT, "transaction_id = ?", &[&tx_id])?;
             Ok(())
         })
     }
@@ -792,9 +1195,8 @@ impl DuckDbRepository {
     /// Returns true if inserted, false if skipped
     pub fn insert_transaction_if_not_exists(&self, tx: &Transaction) -> Result<bool> {
         self.with_connection_write(|conn| {
-            // Write empty JSON for external_ids - kept for backwards compat with DB schema
-            let external_ids = "{}";
-            let sf_extra = tx.sf_extra.as_ref().map(|v| v.to_string());
+            let external_ids = tx.provider.external_ids_json();
+            let provider_metadata = tx.provider.provider_metadata_json();
             let tags_literal = format_tags_array(&tx.tags);
 
             // Use INSERT ... ON CONFLICT DO NOTHING to skip existing transactions
@@ -803,9 +1205,8 @@ impl DuckDbRepository {
                                                transaction_date, posted_date, tags, external_ids,
                                                parent_transaction_id, created_at, updated_at,
                                                csv_fingerprint, csv_batch_id, is_manual, tags_auto_applied,
-                                               sf_id, sf_posted, sf_amount, sf_description, sf_transacted_at, sf_pending, sf_extra,
-                                               lf_id, lf_account_id, lf_amount, lf_currency, lf_date, lf_merchant, lf_description, lf_is_pending)
-                 VALUES (?, ?, ?, ?, ?, ?, {}, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                                               check_number, reference, currency, excluded_from_reports, provider_metadata, pending)
+                 VALUES (?, ?, ?, ?, ?, ?, {}, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                  ON CONFLICT (transaction_id) DO NOTHING",
                 tags_literal
             );
@@ -827,25 +1228,26 @@ impl DuckDbRepository {
                     tx.csv_batch_id,
                     tx.is_manual,
                     tx.tags_auto_applied,
-                    tx.sf_id,
-                    tx.sf_posted,
-                    tx.sf_amount,
-                    tx.sf_description,
-                    tx.sf_transacted_at,
-                    tx.sf_pending,
-                    sf_extra,
-                    tx.lf_id,
-                    tx.lf_account_id,
-                    tx.lf_amount
-                        .map(|d| d.to_string().parse::<f64>().unwrap_or(0.0)),
-                    tx.lf_currency,
-                    tx.lf_date.map(|d| d.to_string()),
-                    tx.lf_merchant,
-                    tx.lf_description,
-                    tx.lf_is_pending,
+                    tx.check_number,
+                    tx.reference,
+                    tx.currency,
+                    tx.excluded_from_reports,
+                    provider_metadata,
+                    tx.pending,
                 ],
             )?;
 
+            if rows_changed > 0 {
+                let tx_id = tx.id.to_string();
+                Self::log_change(
+                    conn,
+                    Captured::Transaction,
+                    UPSERT,
+                    "transaction_id = ?",
+                    &[&tx_id],
+                )?;
+            }
+
             Ok(rows_changed > 0)
         })
     }
@@ -862,24 +1264,12 @@ impl DuckDbRepository {
         })
     }
 
-    /// Check if a transaction exists by SimpleFIN ID (indexed, fast)
-    pub fn transaction_exists_by_sf_id(&self, sf_id: &str) -> Result<bool> {
+    /// Check if a transaction exists under a provider's ID for it
+    pub fn transaction_exists_by_external_id(&self, provider: &str, id: &str) -> Result<bool> {
         self.with_connection(|conn| {
             let count: i64 = conn.query_row(
-                "SELECT COUNT(*) FROM sys_transactions WHERE sf_id = ?",
-                params![sf_id],
-                |row| row.get(0),
-            )?;
-            Ok(count > 0)
-        })
-    }
-
-    /// Check if a transaction exists by Lunchflow ID (indexed, fast)
-    pub fn transaction_exists_by_lf_id(&self, lf_id: &str) -> Result<bool> {
-        self.with_connection(|conn| {
-            let count: i64 = conn.query_row(
-                "SELECT COUNT(*) FROM sys_transactions WHERE lf_id = ?",
-                params![lf_id],
+                "SELECT COUNT(*) FROM sys_transactions WHERE json_extract_string(external_ids, ?) = ?",
+                params![external_id_path(provider), id],
                 |row| row.get(0),
             )?;
             Ok(count > 0)
@@ -1004,91 +1394,67 @@ impl DuckDbRepository {
     // These methods use a single connection for multiple operations to avoid
     // the visibility gap between check and insert that causes duplicate transactions.
 
-    /// Get existing SimpleFIN IDs from a list (single connection)
+    /// Get a provider's transaction IDs that already exist, from a list (single connection)
     ///
-    /// Returns a HashSet of sf_ids that already exist in the database.
+    /// Returns a HashSet of the `provider` IDs already in the database.
     /// Used for bulk deduplication during sync.
-    pub fn get_existing_sf_ids(&self, sf_ids: &[String]) -> Result<std::collections::HashSet<String>> {
+    pub fn get_existing_external_ids(
+        &self,
+        provider: &str,
+        ids: &[String],
+    ) -> Result<std::collections::HashSet<String>> {
         use std::collections::HashSet;
 
-        if sf_ids.is_empty() {
+        if ids.is_empty() {
             return Ok(HashSet::new());
         }
 
+        let path = external_id_path(provider);
         self.with_connection(|conn| {
             let mut existing = HashSet::new();
 
-            for chunk in sf_ids.chunks(500) {
+            for chunk in ids.chunks(500) {
                 let placeholders: Vec<&str> = chunk.iter().map(|_| "?").collect();
                 let sql = format!(
-                    "SELECT sf_id FROM sys_transactions WHERE sf_id IN ({})",
+                    "SELECT id FROM (
+                         SELECT json_extract_string(external_ids, ?) AS id FROM sys_transactions
+                     ) WHERE id IN ({})",
                     placeholders.join(", ")
                 );
 
                 let mut stmt = conn.prepare(&sql)?;
-
-                let params: Vec<&dyn duckdb::ToSql> = chunk
-                    .iter()
-                    .map(|s| s as &dyn duckdb::ToSql)
-                    .collect();
-
-                let rows = stmt.query_map(params.as_slice(), |row| {
-                    let sf_id: String = row.get(0)?;
-                    Ok(sf_id)
-                })?;
-
-                for row in rows {
-                    if let Ok(sf_id) = row {
-                        existing.insert(sf_id);
-                    }
-                }
+                let mut params: Vec<&dyn duckdb::ToSql> = vec![&path];
+                params.extend(chunk.iter().map(|s| s as &dyn duckdb::ToSql));
+                let rows = stmt.query_map(params.as_slice(), |row| row.get::<_, String>(0))?;
+                existing.extend(rows.flatten());
             }
 
             Ok(existing)
         })
     }
 
-    /// Get existing Lunchflow IDs from a list (single connection)
-    ///
-    /// Returns a HashSet of lf_ids that already exist in the database.
-    /// Used for bulk deduplication during sync.
-    pub fn get_existing_lf_ids(&self, lf_ids: &[String]) -> Result<std::collections::HashSet<String>> {
-        use std::collections::HashSet;
-
-        if lf_ids.is_empty() {
-            return Ok(HashSet::new());
-        }
-
+    /// Pending transactions a provider synced, for matching them to the
+    /// versions that post
+    pub fn get_pending_transactions(&self, provider: &str) -> Result<Vec<Transaction>> {
+        let path = external_id_path(provider);
         self.with_connection(|conn| {
-            let mut existing = HashSet::new();
-
-            for chunk in lf_ids.chunks(500) {
-                let placeholders: Vec<&str> = chunk.iter().map(|_| "?").collect();
-                let sql = format!(
-                    "SELECT lf_id FROM sys_transactions WHERE lf_id IN ({})",
-                    placeholders.join(", ")
-                );
-
-                let mut stmt = conn.prepare(&sql)?;
-
-                let params: Vec<&dyn duckdb::ToSql> = chunk
-                    .iter()
-                    .map(|s| s as &dyn duckdb::ToSql)
-                    .collect();
-
-                let rows = stmt.query_map(params.as_slice(), |row| {
-                    let lf_id: String = row.get(0)?;
-                    Ok(lf_id)
-                })?;
+            // CAST(tags AS VARCHAR) required - see get_transactions() for explanation
+            let mut stmt = conn.prepare(
+                "SELECT transaction_id, account_id, amount, description, transaction_date::VARCHAR,
+                        posted_date::VARCHAR, CAST(tags AS VARCHAR) as tags, external_ids, deleted_at::VARCHAR, parent_transaction_id,
+                        created_at, updated_at, csv_fingerprint, csv_batch_id, is_manual, tags_auto_applied,
+                        check_number, reference, currency, excluded_from_reports, provider_metadata, pending
+                 FROM sys_transactions
+                 WHERE pending AND deleted_at IS NULL
+                   AND json_extract_string(external_ids, ?) IS NOT NULL"
+            )?;
 
-                for row in rows {
-                    if let Ok(lf_id) = row {
-                        existing.insert(lf_id);
-                    }
-                }
-            }
+            let transactions = stmt
+                .query_map([&path], |row| Self::row_to_transaction(row))?
+                .filter_map(|r| r.ok())
+                .collect();
 
-            Ok(existing)
+            Ok(transactions)
         })
     }
 
@@ -1108,9 +1474,8 @@ impl DuckDbRepository {
             let mut count = 0;
 
             for tx in transactions {
-                // Write empty JSON for external_ids - kept for backwards compat with DB schema
-                let external_ids = "{}";
-                let sf_extra = tx.sf_extra.as_ref().map(|v| v.to_string());
+                let external_ids = tx.provider.external_ids_json();
+                let provider_metadata = tx.provider.provider_metadata_json();
 
                 // Build tags array literal for DuckDB: ['tag1', 'tag2']
                 let tags_literal = format_tags_array(&tx.tags);
@@ -1121,9 +1486,8 @@ impl DuckDbRepository {
                                                    transaction_date, posted_date, tags, external_ids,
                                                    parent_transaction_id, created_at, updated_at,
                                                    csv_fingerprint, csv_batch_id, is_manual, tags_auto_applied,
-                                                   sf_id, sf_posted, sf_amount, sf_description, sf_transacted_at, sf_pending, sf_extra,
-                                                   lf_id, lf_account_id, lf_amount, lf_currency, lf_date, lf_merchant, lf_description, lf_is_pending)
-                     VALUES (?, ?, ?, ?, ?, ?, {}, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                                                   check_number, reference, currency, excluded_from_reports, provider_metadata, pending)
+                     VALUES (?, ?, ?, ?, ?, ?, {}, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                      ON CONFLICT (transaction_id) DO NOTHING",
                     tags_literal
                 );
@@ -1145,26 +1509,24 @@ impl DuckDbRepository {
                         tx.csv_batch_id,
                         tx.is_manual,
                         tx.tags_auto_applied,
-                        tx.sf_id,
-                        tx.sf_posted,
-                        tx.sf_amount,
-                        tx.sf_description,
-                        tx.sf_transacted_at,
-                        tx.sf_pending,
-                        sf_extra,
-                        tx.lf_id,
-                        tx.lf_account_id,
-                        tx.lf_amount
-                            .map(|d| d.to_string().parse::<f64>().unwrap_or(0.0)),
-                        tx.lf_currency,
-                        tx.lf_date.map(|d| d.to_string()),
-                        tx.lf_merchant,
-                        tx.lf_description,
-                        tx.lf_is_pending,
+                        tx.check_number,
+                        tx.reference,
+                        tx.currency,
+                        tx.excluded_from_reports,
+                        provider_metadata,
+                        tx.pending,
                     ],
                 )?;
 
                 if rows_changed > 0 {
+                    let tx_id = tx.id.to_string();
+                    Self::log_change(
+                        conn,
+                        Captured::Transaction,
+                        UPSERT,
+                        "transaction_id = ?",
+                        &[&tx_id],
+                    )?;
                     count += 1;
                 }
             }
@@ -1174,39 +1536,26 @@ impl DuckDbRepository {
         // Single checkpoint happens here when with_connection_write completes
     }
 
-    /// Check for duplicate SimpleFIN IDs in the database
-    ///
-    /// Returns sf_ids that appear more than once. Used by doctor check.
-    pub fn check_duplicate_sf_ids(&self) -> Result<Vec<String>> {
-        self.with_connection(|conn| {
-            let mut stmt = conn.prepare(
-                "SELECT sf_id FROM sys_transactions
-                 WHERE sf_id IS NOT NULL
-                 GROUP BY sf_id HAVING COUNT(*) > 1"
-            )?;
-
-            let duplicates: Vec<String> = stmt
-                .query_map([], |row| row.get(0))?
-                .filter_map(|r| r.ok())
-                .collect();
-
-            Ok(duplicates)
-        })
-    }
-
-    /// Check for duplicate Lunchflow IDs in the database
+    /// Check for provider IDs held by more than one transaction
     ///
-    /// Returns lf_ids that appear more than once. Used by doctor check.
-    pub fn check_duplicate_lf_ids(&self) -> Result<Vec<String>> {
+    /// Returns (provider, id) pairs that appear more than once. Used by doctor check.
+    pub fn check_duplicate_external_ids(&self) -> Result<Vec<(String, String)>> {
         self.with_connection(|conn| {
             let mut stmt = conn.prepare(
-                "SELECT lf_id FROM sys_transactions
-                 WHERE lf_id IS NOT NULL
-                 GROUP BY lf_id HAVING COUNT(*) > 1"
+                "SELECT provider, id FROM (
+                     SELECT provider, json_extract_string(external_ids, '$.\"' || provider || '\"') AS id
+                     FROM (
+                         SELECT unnest(json_keys(external_ids)) AS provider, external_ids
+                         FROM sys_transactions
+                     )
+                 )
+                 WHERE id IS NOT NULL
+                 GROUP BY provider, id HAVING COUNT(*) > 1
+                 ORDER BY provider, id",
             )?;
 
-            let duplicates: Vec<String> = stmt
-                .query_map([], |row| row.get(0))?
+            let duplicates: Vec<(String, String)> = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
                 .filter_map(|r| r.ok())
                 .collect();
 
@@ -1219,10 +1568,9 @@ impl DuckDbRepository {
             // CAST(tags AS VARCHAR) required - see get_transactions() for explanation
             let mut stmt = conn.prepare(
                 "SELECT transaction_id, account_id, amount, description, transaction_date::VARCHAR,
-                        posted_date::VARCHAR, CAST(tags AS VARCHAR) as tags, external_ids, deleted_at, parent_transaction_id,
+                        posted_date::VARCHAR, CAST(tags AS VARCHAR) as tags, external_ids, deleted_at::VARCHAR, parent_transaction_id,
                         created_at, updated_at, csv_fingerprint, csv_batch_id, is_manual, tags_auto_applied,
-                        sf_id, sf_posted, sf_amount, sf_description, sf_transacted_at, sf_pending, sf_extra,
-                        lf_id, lf_account_id, lf_amount, lf_currency, lf_date::VARCHAR, lf_merchant, lf_description, lf_is_pending
+                        check_number, reference, currency, excluded_from_reports, provider_metadata, pending
                  FROM sys_transactions WHERE transaction_id = ?"
             )?;
 
@@ -1239,8 +1587,8 @@ impl DuckDbRepository {
     pub fn add_balance_snapshot(&self, snapshot: &BalanceSnapshot) -> Result<()> {
         self.with_connection_write(|conn| {
             conn.execute(
-                "INSERT INTO sys_balance_snapshots (snapshot_id, account_id, balance, snapshot_time, source, created_at, updated_at)
-                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+                "INSERT INTO sys_balance_snapshots (snapshot_id, account_id, balance, snapshot_time, source, created_at, updated_at, csv_batch_id)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
                 params![
                     snapshot.id.to_string(),
                     snapshot.account_id.to_string(),
@@ -1249,8 +1597,17 @@ impl DuckDbRepository {
                     snapshot.source.as_ref().map(|s| s.to_string()),
                     snapshot.created_at.to_rfc3339(),
                     snapshot.updated_at.to_rfc3339(),
+                    snapshot.csv_batch_id,
                 ],
             )?;
+            let snapshot_id = snapshot.id.to_string();
+            Self::log_change(
+                conn,
+                Captured::BalanceSnapshot,
+                UPSERT,
+                "snapshot_id = ?",
+                &[&snapshot_id],
+            )?;
             Ok(())
         })
     }
@@ -1267,8 +1624,8 @@ impl DuckDbRepository {
             let mut count = 0;
             for snapshot in snapshots {
                 let rows_changed = conn.execute(
-                    "INSERT INTO sys_balance_snapshots (snapshot_id, account_id, balance, snapshot_time, source, created_at, updated_at)
-                     VALUES (?, ?, ?, ?, ?, ?, ?)",
+                    "INSERT INTO sys_balance_snapshots (snapshot_id, account_id, balance, snapshot_time, source, created_at, updated_at, csv_batch_id)
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
                     params![
                         snapshot.id.to_string(),
                         snapshot.account_id.to_string(),
@@ -1277,9 +1634,18 @@ impl DuckDbRepository {
                         snapshot.source.as_ref().map(|s| s.to_string()),
                         snapshot.created_at.to_rfc3339(),
                         snapshot.updated_at.to_rfc3339(),
+                        snapshot.csv_batch_id,
                     ],
                 )?;
                 if rows_changed > 0 {
+                    let snapshot_id = snapshot.id.to_string();
+                    Self::log_change(
+                        conn,
+                        Captured::BalanceSnapshot,
+                        UPSERT,
+                        "snapshot_id = ?",
+                        &[&snapshot_id],
+                    )?;
                     count += 1;
                 }
             }
@@ -1291,10 +1657,10 @@ impl DuckDbRepository {
         self.with_connection(|conn| {
             // Cast TIMESTAMP and balance columns to VARCHAR so they can be read as strings with full precision
             let sql = if account_id.is_some() {
-                "SELECT snapshot_id, account_id, balance::VARCHAR, snapshot_time::VARCHAR, source, created_at::VARCHAR, updated_at::VARCHAR
+                "SELECT snapshot_id, account_id, balance::VARCHAR, snapshot_time::VARCHAR, source, created_at::VARCHAR, updated_at::VARCHAR, csv_batch_id
                  FROM sys_balance_snapshots WHERE account_id = ? ORDER BY snapshot_time DESC"
             } else {
-                "SELECT snapshot_id, account_id, balance::VARCHAR, snapshot_time::VARCHAR, source, created_at::VARCHAR, updated_at::VARCHAR
+                "SELECT snapshot_id, account_id, balance::VARCHAR, snapshot_time::VARCHAR, source, created_at::VARCHAR, updated_at::VARCHAR, csv_batch_id
                  FROM sys_balance_snapshots ORDER BY snapshot_time DESC"
             };
 
@@ -1330,6 +1696,13 @@ impl DuckDbRepository {
                     snapshot_id,
                 ],
             )?;
+            Self::log_change(
+                conn,
+                Captured::BalanceSnapshot,
+                UPSERT,
+                "snapshot_id = ?",
+                &[&snapshot_id],
+            )?;
             Ok(())
         })
     }
@@ -1342,18 +1715,48 @@ impl DuckDbRepository {
         end_date: NaiveDate,
     ) -> Result<usize> {
         self.with_connection_write(|conn| {
-            // Delete snapshots where the date part of snapshot_time falls within the range
-            let deleted = conn.execute(
-                "DELETE FROM sys_balance_snapshots
-                 WHERE account_id = ?
+            // Snapshots where the date part of snapshot_time falls within the range
+            let filter = "account_id = ?
                  AND CAST(snapshot_time AS DATE) >= ?
-                 AND CAST(snapshot_time AS DATE) <= ?",
-                params![account_id, start_date.to_string(), end_date.to_string(),],
+                 AND CAST(snapshot_time AS DATE) <= ?";
+            let (start, end) = (start_date.to_string(), end_date.to_string());
+            Self::log_change(
+                conn,
+                Captured::BalanceSnapshot,
+                DELETE,
+                filter,
+                &[&account_id, &start, &end],
+            )?;
+            let deleted = conn.execute(
+                &format!("DELETE FROM sys_balance_snapshots WHERE {}", filter),
+                params![account_id, start, end],
             )?;
             Ok(deleted)
         })
     }
 
+    /// Delete the transactions and balance snapshots an import created
+    ///
+    /// Transactions are removed outright rather than soft-deleted so their
+    /// fingerprints no longer count as duplicates when the file is imported again.
+    /// Returns the number of (transactions, balance snapshots) deleted.
+    pub fn delete_import_batch(&self, batch_id: &str) -> Result<(usize, usize)> {
+        self.with_connection_write(|conn| {
+            for table in [Captured::Transaction, Captured::BalanceSnapshot] {
+                Self::log_change(conn, table, DELETE, "csv_batch_id = ?", &[&batch_id])?;
+            }
+            let transactions = conn.execute(
+                "DELETE FROM sys_transactions WHERE csv_batch_id = ?",
+                params![batch_id],
+            )?;
+            let snapshots = conn.execute(
+                "DELETE FROM sys_balance_snapshots WHERE csv_batch_id = ?",
+                params![batch_id],
+            )?;
+            Ok((transactions, snapshots))
+        })
+    }
+
     fn row_to_balance_snapshot(row: &duckdb::Row) -> BalanceSnapshot {
         let id_str: String = row.get(0).unwrap_or_default();
         let account_id_str: String = row.get(1).unwrap_or_default();
@@ -1362,6 +1765,7 @@ impl DuckDbRepository {
         let source: Option<String> = row.get(4).ok();
         let created_str: String = row.get(5).unwrap_or_default();
         let updated_str: String = row.get(6).unwrap_or_default();
+        let csv_batch_id: Option<String> = row.get(7).ok();
 
         BalanceSnapshot {
             id: Uuid::parse_str(&id_str).unwrap_or_else(|_| Uuid::new_v4()),
@@ -1371,6 +1775,7 @@ impl DuckDbRepository {
             source,
             created_at: parse_timestamp(&created_str),
             updated_at: parse_timestamp(&updated_str),
+            csv_batch_id,
         }
     }
 
@@ -1419,6 +1824,7 @@ impl DuckDbRepository {
         }
 
         self.with_connection(|conn| {
+            self.attach_mounted_archives(conn)?;
             let mut stmt = conn.prepare(sql)?;
 
             // Execute query and iterate
@@ -1484,6 +1890,7 @@ impl DuckDbRepository {
         validate_sql_syntax(sql)?;
 
         self.with_readonly_connection(|conn| {
+            self.attach_mounted_archives(conn)?;
             let mut stmt = conn.prepare(sql)?;
             let mut result_rows = stmt.query([])?;
 
@@ -1534,6 +1941,69 @@ impl DuckDbRepository {
         })
     }
 
+    /// Execute a parameterized read-only SQL query using a DuckDB read-only connection.
+    ///
+    /// Parameters are passed as JSON values and bound to ? placeholders.
+    pub fn execute_query_readonly_with_params(
+        &self,
+        sql: &str,
+        params: &[serde_json::Value],
+    ) -> Result<QueryResult> {
+        validate_sql_syntax(sql)?;
+
+        let duckdb_params: Vec<Box<dyn duckdb::ToSql>> = params
+            .iter()
+            .map(|v| Self::json_to_duckdb_param(v))
+            .collect();
+        let param_refs: Vec<&dyn duckdb::ToSql> =
+            duckdb_params.iter().map(|b| b.as_ref()).collect();
+
+        self.with_readonly_connection(|conn| {
+            self.attach_mounted_archives(conn)?;
+            let mut stmt = conn.prepare(sql)?;
+            let mut result_rows = stmt.query(param_refs.as_slice())?;
+
+            let mut rows: Vec<Vec<serde_json::Value>> = Vec::new();
+            let mut column_count = 0;
+
+            while let Some(row) = result_rows.next()? {
+                if rows.is_empty() {
+                    column_count = row.as_ref().column_count();
+                }
+
+                let mut row_values: Vec<serde_json::Value> = Vec::new();
+                for i in 0..column_count {
+                    let value = Self::get_column_value(row, i);
+                    row_values.push(value);
+                }
+                rows.push(row_values);
+            }
+
+            drop(result_rows);
+
+            let count = if column_count > 0 {
+                column_count
+            } else {
+                stmt.column_count()
+            };
+            let columns: Vec<String> = (0..count)
+                .map(|i| {
+                    stmt.column_name(i)
+                        .map(|s| s.to_string())
+                        .unwrap_or_else(|_| format!("col{}", i))
+                })
+                .collect();
+
+            let row_count = rows.len();
+
+            Ok(QueryResult {
+                columns,
+                rows,
+                row_count,
+            })
+        })
+    }
+
     /// Execute arbitrary SQL (read or write)
     ///
     /// Unlike `execute_query`, this method allows both SELECT and write operations.
@@ -1558,6 +2028,7 @@ impl DuckDbRepository {
         if is_select {
             self.with_connection(|conn| {
                 // Read query - return columns and rows
+                self.attach_mounted_archives(conn)?;
                 let mut stmt = conn.prepare(sql)?;
                 let mut result_rows = stmt.query([])?;
 
@@ -1654,6 +2125,7 @@ impl DuckDbRepository {
         if is_select {
             self.with_connection(|conn| {
                 // Read query - return columns and rows
+                self.attach_mounted_archives(conn)?;
                 let mut stmt = conn.prepare(sql)?;
                 let mut result_rows = stmt.query(param_refs.as_slice())?;
 
@@ -1898,7 +2370,50 @@ impl DuckDbRepository {
 
     // === Integration operations ===
 
-    pub fn get_integrations(&self) -> Result<Vec<Integration>> {
+    /// Keep the credentials key in `source` instead of the OS keychain
+    pub fn with_key_source(mut self, source: Arc<dyn KeySource>) -> Self {
+        self.key_source = source;
+        self
+    }
+
+    /// Cipher for credential fields in integration settings
+    ///
+    /// Only a loaded key is kept, so a locked keychain can be retried once
+    /// it's unlocked.
+    fn credential_cipher(&self) -> Result<&CredentialCipher> {
+        if let Some(cipher) = self.credentials.get() {
+            return Ok(cipher);
+        }
+        let _init = self
+            .credentials_init
+            .lock()
+            .map_err(|_| anyhow!("Credentials key lock poisoned"))?;
+        if let Some(cipher) = self.credentials.get() {
+            return Ok(cipher);
+        }
+
+        let treeline_dir = self.db_path.parent().unwrap_or(Path::new("."));
+        let has_sealed = self
+            .get_integrations_raw()?
+            .iter()
+            .any(|i| has_sealed_credentials(&i.settings));
+        let cipher =
+            CredentialCipher::load_or_create(treeline_dir, self.key_source.as_ref(), has_sealed)
+                .context("Failed to load credentials key")?;
+        Ok(self.credentials.get_or_init(|| cipher))
+    }
+
+    /// Names of the configured integrations, without opening their credentials
+    pub fn get_integration_names(&self) -> Result<Vec<String>> {
+        Ok(self
+            .get_integrations_raw()?
+            .into_iter()
+            .map(|i| i.name)
+            .collect())
+    }
+
+    /// Integration settings as stored, with credential fields still sealed
+    fn get_integrations_raw(&self) -> Result<Vec<Integration>> {
         self.with_connection(|conn| {
             let mut stmt = conn
                 .prepare("SELECT integration_name, integration_settings FROM sys_integrations")?;
@@ -1918,8 +2433,46 @@ impl DuckDbRepository {
         })
     }
 
+    /// Configured integrations, with credential fields decrypted
+    pub fn get_integrations(&self) -> Result<Vec<Integration>> {
+        let integrations = self.get_integrations_raw()?;
+        if integrations.is_empty() {
+            return Ok(integrations);
+        }
+
+        let cipher = self.credential_cipher()?;
+        integrations
+            .into_iter()
+            .map(|i| {
+                let settings = cipher.open_settings(&i.settings).with_context(|| {
+                    format!(
+                        "Can't decrypt the {} credentials; restore credentials.key or run \
+                         `tl setup remove {}` and set it up again",
+                        i.name, i.name
+                    )
+                })?;
+                Ok(Integration {
+                    name: i.name,
+                    settings,
+                })
+            })
+            .collect()
+    }
+
+    /// Encrypt credentials saved before field-level encryption existed
+    fn seal_plaintext_credentials(&self) -> Result<()> {
+        for integration in self.get_integrations_raw()? {
+            if has_plaintext_credentials(&integration.settings) {
+                self.upsert_integration(&integration.name, &integration.settings)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Save an integration, encrypting its credential fields
     pub fn upsert_integration(&self, name: &str, settings: &serde_json::Value) -> Result<()> {
-        let settings_json = serde_json::to_string(settings)?;
+        let settings = self.credential_cipher()?.seal_settings(settings)?;
+        let settings_json = serde_json::to_string(&settings)?;
         let now = chrono::Utc::now().to_rfc3339();
 
         self.with_connection_write(|conn| {
@@ -1946,134 +2499,1009 @@ impl DuckDbRepository {
         })
     }
 
-    // === Maintenance operations ===
-
-    pub fn compact(&self) -> Result<()> {
-        use std::fs;
-
-        // Acquire lock for the entire compaction operation
-        let _lock = self.acquire_lock()?;
-
-        // Proper DuckDB compaction: COPY FROM DATABASE to a new file
-        // Note: VACUUM does not reclaim space in DuckDB - only COPY FROM DATABASE does
-        // Reference: https://duckdb.org/docs/stable/operations_manual/footprint_of_duckdb/reclaiming_space
-
-        // Create temp path for the compacted database
-        let temp_db = self.db_path.with_extension("duckdb.tmp");
-
-        // Remove temp file if it exists from a previous failed run
-        let _ = fs::remove_file(&temp_db);
-
-        // Create a new in-memory connection for the compact operation
-        // This allows us to attach both source and target databases
-        let config = duckdb::Config::default().enable_autoload_extension(false)?;
-        let compact_conn = Connection::open_in_memory_with_flags(config)?;
+    /// Set one key of an integration's per-account settings (`accountSettings`)
+    ///
+    /// Works on the stored settings, so credential fields stay sealed as they
+    /// are. Returns false when the integration isn't configured.
+    pub fn set_integration_account_setting(
+        &self,
+        name: &str,
+        external_id: &str,
+        key: &str,
+        value: serde_json::Value,
+    ) -> Result<bool> {
+        let Some(mut integration) = self
+            .get_integrations_raw()?
+            .into_iter()
+            .find(|i| i.name == name)
+        else {
+            return Ok(false);
+        };
 
-        // Attach the source database (current db_path)
-        if let Some(key) = &self.encryption_key {
-            compact_conn.execute(
-                &format!(
-                    "ATTACH '{}' AS source_db (ENCRYPTION_KEY '{}')",
-                    self.db_path.display(),
-                    key
-                ),
-                [],
-            )?;
-        } else {
-            compact_conn.execute(
-                &format!("ATTACH '{}' AS source_db", self.db_path.display()),
-                [],
-            )?;
+        let settings = &mut integration.settings;
+        if !settings.is_object() {
+            *settings = serde_json::json!({});
         }
-
-        // Attach the target database (temp file)
-        if let Some(key) = &self.encryption_key {
-            compact_conn.execute(
-                &format!(
-                    "ATTACH '{}' AS target_db (ENCRYPTION_KEY '{}')",
-                    temp_db.display(),
-                    key
-                ),
-                [],
-            )?;
-        } else {
-            compact_conn.execute(&format!("ATTACH '{}' AS target_db", temp_db.display()), [])?;
+        if !settings["accountSettings"].is_object() {
+            settings["accountSettings"] = serde_json::json!({});
         }
+        let entry = &mut settings["accountSettings"][external_id];
+        if !entry.is_object() {
+            *entry = serde_json::json!({});
+        }
+        entry[key] = value;
 
-        // Workaround for DuckDB issue #16785: COPY FROM DATABASE with foreign keys
-        // Setting threads to 1 may help with foreign key constraint ordering
-        compact_conn.execute("SET threads TO 1", [])?;
-
-        // Copy all data from source to target
-        // This copies schema (tables, constraints, indexes, sequences, macros) and data
-        compact_conn.execute("COPY FROM DATABASE source_db TO target_db", [])?;
-
-        // Detach both databases to ensure they're flushed
-        compact_conn.execute("DETACH source_db", [])?;
-        compact_conn.execute("DETACH target_db", [])?;
-
-        // Close the compact connection
-        drop(compact_conn);
-
-        // Replace the old database with the compacted one
-        // Backup the original first, then move temp in place
-        let backup_db = self.db_path.with_extension("duckdb.old");
-        let _ = fs::remove_file(&backup_db); // Remove old backup if exists
-        fs::rename(&self.db_path, &backup_db)?;
-        fs::rename(&temp_db, &self.db_path)?;
-
-        // Clean up the backup file
-        let _ = fs::remove_file(&backup_db);
-
-        // Lock is released when _lock drops
-        Ok(())
-    }
-
-    pub fn get_db_size(&self) -> Result<u64> {
-        // Get actual file size from filesystem
-        let metadata = std::fs::metadata(&self.db_path)?;
-        Ok(metadata.len())
+        let settings_json = serde_json::to_string(&integration.settings)?;
+        self.with_connection_write(|conn| {
+            conn.execute(
+                "UPDATE sys_integrations SET integration_settings = ?, updated_at = ?
+                 WHERE integration_name = ?",
+                params![settings_json, Utc::now().to_rfc3339(), name],
+            )?;
+            Ok(true)
+        })
     }
 
-    // === Doctor checks ===
-
-    pub fn check_orphaned_transactions(&self) -> Result<Vec<String>> {
-        self.with_connection(|conn| {
+    /// Clear the provider account IDs linking accounts to an integration
+    ///
+    /// The accounts keep their transactions and balances but are no longer
+    /// matched by sync, so reconnecting creates new accounts. Returns the
+    /// number of accounts unlinked.
+    pub fn unlink_integration_accounts(&self, name: &str) -> Result<usize> {
+        let path = external_id_path(name);
+        // Merging a null member removes the provider's ID and leaves the rest
+        let patch = serde_json::json!({ name: null }).to_string();
+        self.with_connection_write(|conn| {
             let mut stmt = conn.prepare(
-                "SELECT t.transaction_id FROM sys_transactions t
-                 LEFT JOIN sys_accounts a ON t.account_id = a.account_id
-                 WHERE a.account_id IS NULL AND t.deleted_at IS NULL",
+                "SELECT account_id FROM sys_accounts
+                 WHERE json_extract_string(external_ids, ?) IS NOT NULL",
             )?;
-
-            let orphans: Vec<String> = stmt
-                .query_map([], |row| row.get(0))?
+            let ids: Vec<String> = stmt
+                .query_map(params![path], |row| row.get(0))?
                 .filter_map(|r| r.ok())
                 .collect();
-
-            Ok(orphans)
+            for id in &ids {
+                conn.execute(
+                    "UPDATE sys_accounts
+                     SET external_ids = json_merge_patch(external_ids, ?), updated_at = CURRENT_TIMESTAMP
+                     WHERE account_id = ?",
+                    params![patch, id],
+                )?;
+                Self::log_change(conn, Captured::Account, UPSERT, "account_id = ?", &[id])?;
+            }
+            Ok(ids.len())
         })
     }
 
-    pub fn check_orphaned_snapshots(&self) -> Result<Vec<String>> {
-        self.with_connection(|conn| {
-            let mut stmt = conn.prepare(
-                "SELECT s.snapshot_id FROM sys_balance_snapshots s
-                 LEFT JOIN sys_accounts a ON s.account_id = a.account_id
-                 WHERE a.account_id IS NULL",
-            )?;
+    // === Duplicate review operations ===
 
-            let orphans: Vec<String> = stmt
-                .query_map([], |row| row.get(0))?
+    /// Transaction ID pairs already resolved in the duplicate review queue,
+    /// smaller ID first
+    pub fn get_duplicate_reviews(&self) -> Result<std::collections::HashSet<(String, String)>> {
+        self.with_connection(|conn| {
+            let mut stmt = conn
+                .prepare("SELECT transaction_id_a, transaction_id_b FROM sys_duplicate_reviews")?;
+            let pairs = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
                 .filter_map(|r| r.ok())
                 .collect();
+            Ok(pairs)
+        })
+    }
 
-            Ok(orphans)
+    /// Record how a pair of likely duplicates was resolved ("merged" or "dismissed")
+    pub fn record_duplicate_review(
+        &self,
+        first: &str,
+        second: &str,
+        resolution: &str,
+    ) -> Result<()> {
+        let (a, b) = if first <= second {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        self.with_connection_write(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO sys_duplicate_reviews
+                    (transaction_id_a, transaction_id_b, resolution, resolved_at)
+                 VALUES (?, ?, ?, ?)",
+                params![a, b, resolution, Utc::now().naive_utc().to_string()],
+            )?;
+            Ok(())
         })
     }
 
-    pub fn check_future_transactions(&self) -> Result<i64> {
+    // === Holding operations ===
+
+    /// Holdings with their lots, for one account or all, largest first
+    pub fn get_holdings(&self, account_id: Option<&str>) -> Result<Vec<Holding>> {
         self.with_connection(|conn| {
-            // Use Rust-computed date to avoid ICU extension dependency
+            let sql = format!(
+                "SELECT holding_id, account_id, symbol, description, quantity::VARCHAR,
+                        cost_basis::VARCHAR, market_value::VARCHAR, currency, as_of::VARCHAR,
+                        external_ids::VARCHAR, provider_metadata::VARCHAR,
+                        created_at::VARCHAR, updated_at::VARCHAR
+                 FROM sys_holdings {}
+                 ORDER BY market_value DESC NULLS LAST, symbol",
+                if account_id.is_some() {
+                    "WHERE account_id = ?"
+                } else {
+                    ""
+                }
+            );
+            let mut stmt = conn.prepare(&sql)?;
+            let mut holdings: Vec<Holding> = match account_id {
+                Some(aid) => stmt
+                    .query_map([aid], |row| Ok(Self::row_to_holding(row)))?
+                    .filter_map(|r| r.ok())
+                    .collect(),
+                None => stmt
+                    .query_map([], |row| Ok(Self::row_to_holding(row)))?
+                    .filter_map(|r| r.ok())
+                    .collect(),
+            };
+
+            let mut stmt = conn.prepare(
+                "SELECT lot_id, holding_id, acquired_date::VARCHAR, quantity::VARCHAR, cost_basis::VARCHAR
+                 FROM sys_holding_lots ORDER BY acquired_date NULLS LAST",
+            )?;
+            let lots = stmt.query_map([], |row| {
+                let lot_id: String = row.get(0)?;
+                let holding_id: String = row.get(1)?;
+                let acquired: Option<String> = row.get(2)?;
+                let quantity: String = row.get(3)?;
+                let cost_basis: Option<String> = row.get(4)?;
+                Ok((
+                    holding_id,
+                    HoldingLot {
+                        id: Uuid::parse_str(&lot_id).unwrap_or_else(|_| Uuid::new_v4()),
+                        acquired_date: acquired.as_deref().map(parse_date),
+                        quantity: Decimal::from_str_exact(&quantity).unwrap_or_default(),
+                        cost_basis: cost_basis.and_then(|c| Decimal::from_str_exact(&c).ok()),
+                    },
+                ))
+            })?;
+            for (holding_id, lot) in lots.filter_map(|r| r.ok()) {
+                if let Some(holding) = holdings
+                    .iter_mut()
+                    .find(|h| h.id.to_string() == holding_id)
+                {
+                    holding.lots.push(lot);
+                }
+            }
+
+            Ok(holdings)
+        })
+    }
+
+    /// Replace an account's holdings (and their lots) with `holdings`
+    pub fn replace_holdings(&self, account_id: &str, holdings: &[Holding]) -> Result<()> {
+        self.with_connection_write(|conn| {
+            conn.execute(
+                "DELETE FROM sys_holding_lots WHERE holding_id IN (
+                    SELECT holding_id FROM sys_holdings WHERE account_id = ?
+                 )",
+                params![account_id],
+            )?;
+            conn.execute(
+                "DELETE FROM sys_holdings WHERE account_id = ?",
+                params![account_id],
+            )?;
+
+            for holding in holdings {
+                conn.execute(
+                    "INSERT INTO sys_holdings (holding_id, account_id, symbol, description, quantity,
+                                               cost_basis, market_value, currency, as_of,
+                                               external_ids, provider_metadata, created_at, updated_at)
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    params![
+                        holding.id.to_string(),
+                        account_id,
+                        holding.symbol,
+                        holding.description,
+                        holding.quantity.to_string().parse::<f64>().unwrap_or(0.0),
+                        holding
+                            .cost_basis
+                            .map(|c| c.to_string().parse::<f64>().unwrap_or(0.0)),
+                        holding
+                            .market_value
+                            .map(|v| v.to_string().parse::<f64>().unwrap_or(0.0)),
+                        holding.currency,
+                        holding.as_of.to_string(),
+                        holding.provider.external_ids_json(),
+                        holding.provider.provider_metadata_json(),
+                        holding.created_at.to_rfc3339(),
+                        holding.updated_at.to_rfc3339(),
+                    ],
+                )?;
+                for lot in &holding.lots {
+                    conn.execute(
+                        "INSERT INTO sys_holding_lots (lot_id, holding_id, acquired_date, quantity, cost_basis)
+                         VALUES (?, ?, ?, ?, ?)",
+                        params![
+                            lot.id.to_string(),
+                            holding.id.to_string(),
+                            lot.acquired_date.map(|d| d.to_string()),
+                            lot.quantity.to_string().parse::<f64>().unwrap_or(0.0),
+                            lot.cost_basis
+                                .map(|c| c.to_string().parse::<f64>().unwrap_or(0.0)),
+                        ],
+                    )?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn row_to_holding(row: &duckdb::Row) -> Holding {
+        let id_str: String = row.get(0).unwrap_or_default();
+        let account_id_str: String = row.get(1).unwrap_or_default();
+        let quantity_str: String = row.get(4).unwrap_or_default();
+        let cost_basis: Option<String> = row.get(5).ok().flatten();
+        let market_value: Option<String> = row.get(6).ok().flatten();
+        let as_of_str: String = row.get(8).unwrap_or_default();
+        let external_ids: Option<String> = row.get(9).ok().flatten();
+        let provider_metadata: Option<String> = row.get(10).ok().flatten();
+        let created_str: String = row.get(11).unwrap_or_default();
+        let updated_str: String = row.get(12).unwrap_or_default();
+
+        Holding {
+            id: Uuid::parse_str(&id_str).unwrap_or_else(|_| Uuid::new_v4()),
+            account_id: Uuid::parse_str(&account_id_str).unwrap_or_else(|_| Uuid::new_v4()),
+            symbol: row.get(2).ok().flatten(),
+            description: row.get(3).ok().flatten(),
+            quantity: Decimal::from_str_exact(&quantity_str).unwrap_or_default(),
+            cost_basis: cost_basis.and_then(|c| Decimal::from_str_exact(&c).ok()),
+            market_value: market_value.and_then(|v| Decimal::from_str_exact(&v).ok()),
+            currency: row.get(7).unwrap_or_else(|_| "USD".to_string()),
+            as_of: parse_naive_datetime(&as_of_str),
+            lots: Vec::new(),
+            created_at: parse_timestamp(&created_str),
+            updated_at: parse_timestamp(&updated_str),
+            provider: ProviderData::from_json(
+                external_ids.as_deref(),
+                provider_metadata.as_deref(),
+            ),
+        }
+    }
+
+    /// Record security prices, replacing any for the same symbol and day
+    pub fn upsert_security_prices(&self, prices: &[SecurityPrice]) -> Result<usize> {
+        if prices.is_empty() {
+            return Ok(0);
+        }
+        self.with_connection_write(|conn| {
+            for price in prices {
+                conn.execute(
+                    "INSERT OR REPLACE INTO sys_security_prices (symbol, price_date, price, currency, source)
+                     VALUES (?, ?, ?, ?, ?)",
+                    params![
+                        price.symbol,
+                        price.price_date.to_string(),
+                        price.price.to_string().parse::<f64>().unwrap_or(0.0),
+                        price.currency,
+                        price.source,
+                    ],
+                )?;
+            }
+            Ok(prices.len())
+        })
+    }
+
+    /// A symbol's price history, oldest first
+    pub fn get_security_prices(&self, symbol: &str) -> Result<Vec<SecurityPrice>> {
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT symbol, price_date::VARCHAR, price::VARCHAR, currency, source
+                 FROM sys_security_prices WHERE symbol = ? ORDER BY price_date",
+            )?;
+            let prices = stmt
+                .query_map([symbol], |row| {
+                    let date: String = row.get(1)?;
+                    let price: String = row.get(2)?;
+                    Ok(SecurityPrice {
+                        symbol: row.get(0)?,
+                        price_date: parse_date(&date),
+                        price: Decimal::from_str_exact(&price).unwrap_or_default(),
+                        currency: row.get(3)?,
+                        source: row.get(4)?,
+                    })
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+            Ok(prices)
+        })
+    }
+
+    // === Sync history operations ===
+
+    /// Record one integration's sync run and prune history beyond the retention limit
+    pub fn insert_sync_history(&self, entry: &SyncHistoryEntry) -> Result<()> {
+        let account_stats = serde_json::to_string(&entry.account_stats)?;
+        let warnings = serde_json::to_string(&entry.warnings)?;
+
+        self.with_connection_write(|conn| {
+            conn.execute(
+                "INSERT INTO sys_sync_history (sync_id, integration_name, started_at, duration_ms, sync_type,
+                                               accounts_synced, transactions_discovered, transactions_new,
+                                               transactions_skipped, account_stats, warnings, error)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                params![
+                    entry.sync_id,
+                    entry.integration,
+                    entry.started_at.naive_utc().to_string(),
+                    entry.duration_ms,
+                    entry.sync_type,
+                    entry.accounts_synced,
+                    entry.transactions_discovered,
+                    entry.transactions_new,
+                    entry.transactions_skipped,
+                    account_stats,
+                    warnings,
+                    entry.error,
+                ],
+            )?;
+            conn.execute(
+                "DELETE FROM sys_sync_history WHERE sync_id NOT IN (
+                    SELECT sync_id FROM sys_sync_history ORDER BY started_at DESC LIMIT ?
+                 )",
+                params![SYNC_HISTORY_RETENTION as i64],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Get recorded sync runs, newest first
+    pub fn get_sync_history(
+        &self,
+        integration: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<SyncHistoryEntry>> {
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT sync_id, integration_name, started_at::VARCHAR, duration_ms, sync_type,
+                        accounts_synced, transactions_discovered, transactions_new,
+                        transactions_skipped, account_stats::VARCHAR, warnings::VARCHAR, error
+                 FROM sys_sync_history
+                 WHERE ? IS NULL OR integration_name = ?
+                 ORDER BY started_at DESC
+                 LIMIT ?",
+            )?;
+
+            let entries = stmt
+                .query_map(params![integration, integration, limit as i64], |row| {
+                    let started_at: String = row.get(2)?;
+                    let account_stats: Option<String> = row.get(9)?;
+                    let warnings: Option<String> = row.get(10)?;
+                    Ok(SyncHistoryEntry {
+                        sync_id: row.get(0)?,
+                        integration: row.get(1)?,
+                        started_at: parse_timestamp(&started_at),
+                        duration_ms: row.get(3)?,
+                        sync_type: row.get(4)?,
+                        accounts_synced: row.get(5)?,
+                        transactions_discovered: row.get(6)?,
+                        transactions_new: row.get(7)?,
+                        transactions_skipped: row.get(8)?,
+                        account_stats: account_stats
+                            .and_then(|s| serde_json::from_str(&s).ok())
+                            .unwrap_or_default(),
+                        warnings: warnings
+                            .and_then(|s| serde_json::from_str(&s).ok())
+                            .unwrap_or_default(),
+                        error: row.get(11)?,
+                    })
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            Ok(entries)
+        })
+    }
+
+    // === Device sync operations ===
+
+    /// The last synced value of every replicated field
+    pub fn get_synced_fields(&self) -> Result<Vec<SyncedField>> {
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT entity, entity_id, field, value::VARCHAR, updated_at::VARCHAR, device_id
+                 FROM sys_device_sync_fields",
+            )?;
+
+            let fields = stmt
+                .query_map([], |row| {
+                    let value: Option<String> = row.get(3)?;
+                    let updated_at: String = row.get(4)?;
+                    Ok(SyncedField {
+                        entity: row.get(0)?,
+                        entity_id: row.get(1)?,
+                        field: row.get(2)?,
+                        value: value
+                            .and_then(|v| serde_json::from_str(&v).ok())
+                            .unwrap_or_default(),
+                        updated_at: parse_naive_datetime(&updated_at).and_utc(),
+                        device_id: row.get(5)?,
+                    })
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            Ok(fields)
+        })
+    }
+
+    /// Record the values fields were last synced with
+    pub fn upsert_synced_fields(&self, fields: &[SyncedField]) -> Result<()> {
+        self.with_connection_write(|conn| {
+            let mut stmt = conn.prepare(
+                "INSERT INTO sys_device_sync_fields (entity, entity_id, field, value, updated_at, device_id)
+                 VALUES (?, ?, ?, ?, ?, ?)
+                 ON CONFLICT (entity, entity_id, field) DO UPDATE SET
+                    value = EXCLUDED.value,
+                    updated_at = EXCLUDED.updated_at,
+                    device_id = EXCLUDED.device_id",
+            )?;
+            for field in fields {
+                stmt.execute(params![
+                    field.entity,
+                    field.entity_id,
+                    field.field,
+                    field.value.to_string(),
+                    field.updated_at.naive_utc().to_string(),
+                    field.device_id,
+                ])?;
+            }
+            Ok(())
+        })
+    }
+
+    /// The last bundle sequence number applied from each other device
+    pub fn get_device_sync_peers(&self) -> Result<Vec<DeviceSyncPeer>> {
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT device_id, device_name, last_seq, last_synced_at::VARCHAR
+                 FROM sys_device_sync_peers
+                 ORDER BY device_id",
+            )?;
+
+            let peers = stmt
+                .query_map([], |row| {
+                    let last_synced_at: Option<String> = row.get(3)?;
+                    Ok(DeviceSyncPeer {
+                        device_id: row.get(0)?,
+                        device_name: row.get(1)?,
+                        last_seq: row.get(2)?,
+                        last_synced_at: last_synced_at
+                            .map(|at| parse_naive_datetime(&at).and_utc()),
+                    })
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            Ok(peers)
+        })
+    }
+
+    /// Remember the last bundle applied from another device
+    pub fn set_device_sync_peer(
+        &self,
+        device_id: &str,
+        device_name: Option<&str>,
+        last_seq: i64,
+    ) -> Result<()> {
+        self.with_connection_write(|conn| {
+            conn.execute(
+                "INSERT INTO sys_device_sync_peers (device_id, device_name, last_seq, last_synced_at)
+                 VALUES (?, ?, ?, ?)
+                 ON CONFLICT (device_id) DO UPDATE SET
+                    device_name = COALESCE(EXCLUDED.device_name, sys_device_sync_peers.device_name),
+                    last_seq = EXCLUDED.last_seq,
+                    last_synced_at = EXCLUDED.last_synced_at",
+                params![
+                    device_id,
+                    device_name,
+                    last_seq,
+                    Utc::now().naive_utc().to_string()
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Forget all device sync state, e.g. when switching to another relay
+    pub fn clear_device_sync(&self) -> Result<()> {
+        self.with_connection_write(|conn| {
+            conn.execute("DELETE FROM sys_device_sync_fields", [])?;
+            conn.execute("DELETE FROM sys_device_sync_peers", [])?;
+            Ok(())
+        })
+    }
+
+    /// IDs of soft-deleted transactions
+    pub fn get_deleted_transaction_ids(&self) -> Result<Vec<String>> {
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT transaction_id FROM sys_transactions WHERE deleted_at IS NOT NULL",
+            )?;
+            let ids = stmt
+                .query_map([], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+            Ok(ids)
+        })
+    }
+
+    // === Change log operations ===
+
+    /// Append the matching rows of a captured table to sys_change_log
+    ///
+    /// Called inside the write that changes them: after an upsert, so the
+    /// payload is the row as stored, and before a delete, so it's the row
+    /// being removed. Writes hold the database lock, so MAX(seq) + 1 is safe.
+    fn log_change(
+        conn: &Connection,
+        table: Captured,
+        op: &str,
+        filter: &str,
+        filter_params: &[&dyn duckdb::ToSql],
+    ) -> Result<()> {
+        let sql = format!(
+            "INSERT INTO sys_change_log (seq, entity, entity_id, op, payload, changed_at)
+             SELECT (SELECT COALESCE(MAX(seq), 0) FROM sys_change_log) + ROW_NUMBER() OVER (ORDER BY {key}),
+                    ?, {key}, ?, to_json(t), ?
+             FROM {table} t
+             WHERE {filter}",
+            key = table.key_column(),
+            table = table.table(),
+            filter = filter,
+        );
+        let entity = table.entity();
+        let changed_at = Utc::now().naive_utc().to_string();
+        let mut all_params: Vec<&dyn duckdb::ToSql> = vec![&entity, &op, &changed_at];
+        all_params.extend_from_slice(filter_params);
+        conn.execute(&sql, all_params.as_slice())?;
+        Ok(())
+    }
+
+    /// Changes after `since`, oldest first
+    pub fn get_changes(&self, since: i64, limit: usize) -> Result<Vec<ChangeEvent>> {
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT seq, entity, entity_id, op, payload::VARCHAR, changed_at::VARCHAR
+                 FROM sys_change_log
+                 WHERE seq > ?
+                 ORDER BY seq
+                 LIMIT ?",
+            )?;
+
+            let changes = stmt
+                .query_map(params![since, limit as i64], |row| {
+                    let payload: Option<String> = row.get(4)?;
+                    let changed_at: String = row.get(5)?;
+                    Ok(ChangeEvent {
+                        seq: row.get(0)?,
+                        entity: row.get(1)?,
+                        entity_id: row.get(2)?,
+                        op: row.get(3)?,
+                        payload: payload
+                            .and_then(|p| serde_json::from_str(&p).ok())
+                            .unwrap_or_default(),
+                        changed_at: parse_naive_datetime(&changed_at).and_utc(),
+                    })
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            Ok(changes)
+        })
+    }
+
+    /// Sequence number of the newest change (0 when the log is empty)
+    pub fn get_latest_change_seq(&self) -> Result<i64> {
+        self.with_connection(|conn| {
+            let seq = conn.query_row(
+                "SELECT COALESCE(MAX(seq), 0) FROM sys_change_log",
+                [],
+                |row| row.get(0),
+            )?;
+            Ok(seq)
+        })
+    }
+
+    // === Archive operations ===
+
+    /// Folder holding this database's year archives, next to the database
+    pub fn archive_dir(&self) -> PathBuf {
+        self.db_path.with_file_name("archives")
+    }
+
+    /// Move one calendar year's transactions and balance snapshots to an archive file
+    ///
+    /// The archive gets the year's `sys_transactions` and
+    /// `sys_balance_snapshots` rows as they are (soft-deleted transactions
+    /// included), so they can be restored, plus the matching rows of the
+    /// `transactions`, `balance_snapshots` and `accounts` views for querying
+    /// once mounted. It's encrypted with this database's key if it has one.
+    /// Exactly the copied rows are removed from the main database, and only
+    /// once the archive is written. The change log is left alone, so mirrors
+    /// keep the archived rows.
+    pub fn archive_year(&self, year: i32) -> Result<ArchiveInfo> {
+        let stem = self
+            .db_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "treeline".to_string());
+        let file_name = format!("{}-{}.duckdb", stem, year);
+        let path = self.archive_dir().join(&file_name);
+        if path.exists() {
+            anyhow::bail!("{} already exists", path.display());
+        }
+        std::fs::create_dir_all(self.archive_dir())?;
+
+        let in_year = |column: &str| {
+            format!(
+                "{column} >= '{}-01-01' AND {column} < '{}-01-01'",
+                year,
+                year + 1
+            )
+        };
+        // The copies, the change log and the delete share these, so nothing is dropped unarchived
+        let transactions_in_year = in_year("transaction_date");
+        let snapshots_in_year = in_year("snapshot_time");
+        let result = self.with_connection_write(|conn| {
+            Self::attach_archive(conn, &path, "new_archive", self.key(), false)?;
+            conn.execute(
+                &format!(
+                    "CREATE TABLE new_archive.sys_transactions AS
+                     SELECT * FROM sys_transactions WHERE {} ORDER BY transaction_date",
+                    transactions_in_year
+                ),
+                [],
+            )?;
+            conn.execute(
+                &format!(
+                    "CREATE TABLE new_archive.sys_balance_snapshots AS
+                     SELECT * FROM sys_balance_snapshots WHERE {} ORDER BY snapshot_time",
+                    snapshots_in_year
+                ),
+                [],
+            )?;
+            conn.execute(
+                "CREATE TABLE new_archive.transactions AS
+                 SELECT * FROM transactions
+                 WHERE transaction_id IN (SELECT transaction_id FROM new_archive.sys_transactions)
+                 ORDER BY transaction_date",
+                [],
+            )?;
+            conn.execute(
+                "CREATE TABLE new_archive.balance_snapshots AS
+                 SELECT * FROM balance_snapshots
+                 WHERE snapshot_id IN (SELECT snapshot_id FROM new_archive.sys_balance_snapshots)
+                 ORDER BY snapshot_time",
+                [],
+            )?;
+            conn.execute(
+                "CREATE TABLE new_archive.accounts AS SELECT * FROM accounts",
+                [],
+            )?;
+            let transaction_count: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM new_archive.transactions",
+                [],
+                |row| row.get(0),
+            )?;
+            let balance_snapshot_count: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM new_archive.balance_snapshots",
+                [],
+                |row| row.get(0),
+            )?;
+            conn.execute("DETACH new_archive", [])?;
+            if transaction_count == 0 && balance_snapshot_count == 0 {
+                anyhow::bail!("No transactions or balance snapshots in {}", year);
+            }
+
+            // One database per transaction, so the archive is finished before this starts
+            conn.execute_batch("BEGIN TRANSACTION")?;
+            let removed = (|| -> Result<()> {
+                Self::log_change(conn, Captured::Transaction, DELETE, &transactions_in_year, &[])?;
+                Self::log_change(
+                    conn,
+                    Captured::BalanceSnapshot,
+                    DELETE,
+                    &snapshots_in_year,
+                    &[],
+                )?;
+                conn.execute_batch(&format!(
+                    "DELETE FROM sys_transactions WHERE {};
+                     DELETE FROM sys_balance_snapshots WHERE {};
+                     INSERT INTO sys_archives
+                        (year, file_name, transaction_count, balance_snapshot_count, archived_at, mounted)
+                     VALUES ({}, '{}', {}, {}, '{}', FALSE);",
+                    transactions_in_year,
+                    snapshots_in_year,
+                    year,
+                    file_name.replace('\'', "''"),
+                    transaction_count,
+                    balance_snapshot_count,
+                    Utc::now().naive_utc()
+                ))?;
+                Ok(())
+            })();
+            match removed {
+                Ok(()) => conn.execute_batch("COMMIT")?,
+                Err(e) => {
+                    let _ = conn.execute_batch("ROLLBACK");
+                    return Err(e);
+                }
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            let _ = std::fs::remove_file(&path);
+            let _ = std::fs::remove_file(path.with_extension("duckdb.wal"));
+            return Err(e);
+        }
+
+        self.get_archives()?
+            .into_iter()
+            .find(|a| a.year == year)
+            .ok_or_else(|| anyhow!("Archive for {} was not recorded", year))
+    }
+
+    /// Every archived year, oldest first
+    pub fn get_archives(&self) -> Result<Vec<ArchiveInfo>> {
+        let dir = self.archive_dir();
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT year, file_name, transaction_count, balance_snapshot_count,
+                        archived_at::VARCHAR, mounted
+                 FROM sys_archives
+                 ORDER BY year",
+            )?;
+
+            let archives = stmt
+                .query_map([], |row| {
+                    let file_name: String = row.get(1)?;
+                    let archived_at: String = row.get(4)?;
+                    let path = dir.join(&file_name);
+                    Ok(ArchiveInfo {
+                        year: row.get(0)?,
+                        size_bytes: std::fs::metadata(&path).ok().map(|m| m.len()),
+                        path,
+                        file_name,
+                        transaction_count: row.get(2)?,
+                        balance_snapshot_count: row.get(3)?,
+                        archived_at: parse_naive_datetime(&archived_at).and_utc(),
+                        mounted: row.get(5)?,
+                    })
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            Ok(archives)
+        })
+    }
+
+    /// Mount or unmount an archived year; false if the year isn't archived
+    pub fn set_archive_mounted(&self, year: i32, mounted: bool) -> Result<bool> {
+        self.with_connection_write(|conn| {
+            let updated = conn.execute(
+                "UPDATE sys_archives SET mounted = ? WHERE year = ?",
+                params![mounted, year],
+            )?;
+            Ok(updated > 0)
+        })
+    }
+
+    /// ATTACH an archive file as `alias`
+    fn attach_archive(
+        conn: &Connection,
+        path: &Path,
+        alias: &str,
+        encryption_key: Option<&str>,
+        read_only: bool,
+    ) -> Result<()> {
+        let mut options = Vec::new();
+        if let Some(key) = encryption_key {
+            options.push(format!("ENCRYPTION_KEY '{}'", key));
+        }
+        if read_only {
+            options.push("READ_ONLY".to_string());
+        }
+        let options = if options.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", options.join(", "))
+        };
+        let attach = Zeroizing::new(format!(
+            "ATTACH IF NOT EXISTS '{}' AS {}{}",
+            path.display(),
+            alias,
+            options
+        ));
+        conn.execute(&attach, [])?;
+        Ok(())
+    }
+
+    /// Attach mounted archives read-only as `archive_<year>`
+    ///
+    /// With at least one mounted, also defines the temporary views
+    /// `all_transactions` and `all_balance_snapshots` over the main database
+    /// and every mounted archive. Archives whose file is missing or won't
+    /// open (e.g. written under an earlier encryption key) are skipped.
+    fn attach_mounted_archives(&self, conn: &Connection) -> Result<()> {
+        // Nothing to attach before the archives migration has run
+        let Ok(mut stmt) =
+            conn.prepare("SELECT year, file_name FROM sys_archives WHERE mounted ORDER BY year")
+        else {
+            return Ok(());
+        };
+        let mounted: Vec<(i32, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        if mounted.is_empty() {
+            return Ok(());
+        }
+
+        let dir = self.archive_dir();
+        let mut transactions = vec!["SELECT * FROM transactions".to_string()];
+        let mut snapshots = vec!["SELECT * FROM balance_snapshots".to_string()];
+        for (year, file_name) in mounted {
+            let path = dir.join(file_name);
+            let alias = format!("archive_{}", year);
+            if !path.is_file()
+                || Self::attach_archive(conn, &path, &alias, self.key(), true).is_err()
+            {
+                continue;
+            }
+            transactions.push(format!("SELECT * FROM {}.transactions", alias));
+            snapshots.push(format!("SELECT * FROM {}.balance_snapshots", alias));
+        }
+        conn.execute(
+            &format!(
+                "CREATE OR REPLACE TEMP VIEW all_transactions AS {}",
+                transactions.join(" UNION ALL BY NAME ")
+            ),
+            [],
+        )?;
+        conn.execute(
+            &format!(
+                "CREATE OR REPLACE TEMP VIEW all_balance_snapshots AS {}",
+                snapshots.join(" UNION ALL BY NAME ")
+            ),
+            [],
+        )?;
+        Ok(())
+    }
+
+    // === Maintenance operations ===
+
+    pub fn compact(&self) -> Result<()> {
+        use std::fs;
+
+        self.ensure_writable()?;
+
+        // Acquire lock for the entire compaction operation
+        let _lock = self.acquire_lock()?;
+
+        // Proper DuckDB compaction: COPY FROM DATABASE to a new file
+        // Note: VACUUM does not reclaim space in DuckDB - only COPY FROM DATABASE does
+        // Reference: https://duckdb.org/docs/stable/operations_manual/footprint_of_duckdb/reclaiming_space
+
+        // Create temp path for the compacted database
+        let temp_db = self.db_path.with_extension("duckdb.tmp");
+
+        // Remove temp file if it exists from a previous failed run
+        let _ = fs::remove_file(&temp_db);
+
+        // Create a new in-memory connection for the compact operation
+        // This allows us to attach both source and target databases
+        let config = duckdb::Config::default().enable_autoload_extension(false)?;
+        let compact_conn = Connection::open_in_memory_with_flags(config)?;
+
+        // Attach the source database (current db_path)
+        if let Some(key) = self.key() {
+            let attach = Zeroizing::new(format!(
+                "ATTACH '{}' AS source_db (ENCRYPTION_KEY '{}')",
+                self.db_path.display(),
+                key
+            ));
+            compact_conn.execute(&attach, [])?;
+        } else {
+            compact_conn.execute(
+                &format!("ATTACH '{}' AS source_db", self.db_path.display()),
+                [],
+            )?;
+        }
+
+        // Attach the target database (temp file)
+        if let Some(key) = self.key() {
+            let attach = Zeroizing::new(format!(
+                "ATTACH '{}' AS target_db (ENCRYPTION_KEY '{}')",
+                temp_db.display(),
+                key
+            ));
+            compact_conn.execute(&attach, [])?;
+        } else {
+            compact_conn.execute(&format!("ATTACH '{}' AS target_db", temp_db.display()), [])?;
+        }
+
+        // Workaround for DuckDB issue #16785: COPY FROM DATABASE with foreign keys
+        // Setting threads to 1 may help with foreign key constraint ordering
+        compact_conn.execute("SET threads TO 1", [])?;
+
+        // Copy all data from source to target
+        // This copies schema (tables, constraints, indexes, sequences, macros) and data
+        compact_conn.execute("COPY FROM DATABASE source_db TO target_db", [])?;
+
+        // Detach both databases to ensure they're flushed
+        compact_conn.execute("DETACH source_db", [])?;
+        compact_conn.execute("DETACH target_db", [])?;
+
+        // Close the compact connection
+        drop(compact_conn);
+
+        // Replace the old database with the compacted one
+        // Backup the original first, then move temp in place
+        let backup_db = self.db_path.with_extension("duckdb.old");
+        let _ = fs::remove_file(&backup_db); // Remove old backup if exists
+        fs::rename(&self.db_path, &backup_db)?;
+        fs::rename(&temp_db, &self.db_path)?;
+
+        // Clean up the backup file
+        let _ = fs::remove_file(&backup_db);
+
+        // Lock is released when _lock drops
+        Ok(())
+    }
+
+    pub fn get_db_size(&self) -> Result<u64> {
+        // Get actual file size from filesystem
+        let metadata = std::fs::metadata(&self.db_path)?;
+        Ok(metadata.len())
+    }
+
+    // === Doctor checks ===
+
+    pub fn check_orphaned_transactions(&self) -> Result<Vec<String>> {
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT t.transaction_id FROM sys_transactions t
+                 LEFT JOIN sys_accounts a ON t.account_id = a.account_id
+                 WHERE a.account_id IS NULL AND t.deleted_at IS NULL",
+            )?;
+
+            let orphans: Vec<String> = stmt
+                .query_map([], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            Ok(orphans)
+        })
+    }
+
+    pub fn check_orphaned_snapshots(&self) -> Result<Vec<String>> {
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT s.snapshot_id FROM sys_balance_snapshots s
+                 LEFT JOIN sys_accounts a ON s.account_id = a.account_id
+                 WHERE a.account_id IS NULL",
+            )?;
+
+            let orphans: Vec<String> = stmt
+                .query_map([], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            Ok(orphans)
+        })
+    }
+
+    pub fn check_future_transactions(&self) -> Result<i64> {
+        self.with_connection(|conn| {
+            // Use Rust-computed date to avoid ICU extension dependency
             let tomorrow = (chrono::Utc::now() + chrono::Duration::days(1))
                 .format("%Y-%m-%d")
                 .to_string();
@@ -2172,6 +3600,156 @@ impl DuckDbRepository {
         })
     }
 
+    /// Row count of every table in the database, by name
+    pub fn get_table_row_counts(&self) -> Result<Vec<(String, i64)>> {
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT table_schema, table_name FROM information_schema.tables
+                 WHERE table_type = 'BASE TABLE' AND table_catalog = current_database()
+                 ORDER BY table_schema, table_name",
+            )?;
+            let tables: Vec<(String, String)> = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            let mut counts = Vec::with_capacity(tables.len());
+            for (schema, table) in tables {
+                let count: i64 = conn.query_row(
+                    &format!(
+                        "SELECT COUNT(*) FROM \"{}\".\"{}\"",
+                        schema.replace('"', "\"\""),
+                        table.replace('"', "\"\"")
+                    ),
+                    [],
+                    |row| row.get(0),
+                )?;
+                let name = if schema == "main" {
+                    table
+                } else {
+                    format!("{}.{}", schema, table)
+                };
+                counts.push((name, count));
+            }
+            Ok(counts)
+        })
+    }
+
+    /// Every table with its row count and estimated size on disk, by name
+    ///
+    /// The size counts the storage blocks holding the table's persisted
+    /// segments. Small tables can share a block, so it's an estimate.
+    pub fn get_table_storage(&self) -> Result<Vec<(String, i64, u64)>> {
+        self.with_connection(|conn| {
+            let block_size: i64 = conn.query_row(
+                "SELECT block_size FROM pragma_database_size()
+                 WHERE database_name = current_database()",
+                [],
+                |row| row.get(0),
+            )?;
+            let mut stmt = conn.prepare(
+                "SELECT table_schema, table_name FROM information_schema.tables
+                 WHERE table_type = 'BASE TABLE' AND table_catalog = current_database()
+                 ORDER BY table_schema, table_name",
+            )?;
+            let tables: Vec<(String, String)> = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            let mut storage = Vec::with_capacity(tables.len());
+            for (schema, table) in tables {
+                let quoted = format!(
+                    "\"{}\".\"{}\"",
+                    schema.replace('"', "\"\""),
+                    table.replace('"', "\"\"")
+                );
+                let rows: i64 =
+                    conn.query_row(&format!("SELECT COUNT(*) FROM {}", quoted), [], |row| {
+                        row.get(0)
+                    })?;
+                let blocks: i64 = conn.query_row(
+                    &format!(
+                        "SELECT COUNT(DISTINCT block_id) FROM pragma_storage_info('{}')
+                         WHERE persistent AND block_id >= 0",
+                        quoted.replace('\'', "''")
+                    ),
+                    [],
+                    |row| row.get(0),
+                )?;
+                let name = if schema == "main" {
+                    table
+                } else {
+                    format!("{}.{}", schema, table)
+                };
+                storage.push((name, rows, (blocks * block_size) as u64));
+            }
+            Ok(storage)
+        })
+    }
+
+    /// Bytes in free blocks, which only compaction gives back to the filesystem
+    pub fn get_free_bytes(&self) -> Result<u64> {
+        self.with_connection(|conn| {
+            let free: i64 = conn.query_row(
+                "SELECT free_blocks * block_size FROM pragma_database_size()
+                 WHERE database_name = current_database()",
+                [],
+                |row| row.get(0),
+            )?;
+            Ok(free.max(0) as u64)
+        })
+    }
+
+    /// Number of transactions dated in each year, oldest first
+    pub fn get_transaction_counts_by_year(&self) -> Result<Vec<(i32, i64)>> {
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT year(transaction_date)::INTEGER AS year, COUNT(*)
+                 FROM sys_transactions
+                 WHERE deleted_at IS NULL
+                 GROUP BY 1
+                 ORDER BY 1",
+            )?;
+            let counts = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .filter_map(|r| r.ok())
+                .collect();
+            Ok(counts)
+        })
+    }
+
+    /// Delete every row of a table, returning how many there were
+    ///
+    /// Only for tables nothing else depends on; callers keep their own list.
+    pub fn clear_table(&self, table_name: &str) -> Result<usize> {
+        let quoted = table_name
+            .split('.')
+            .map(|part| format!("\"{}\"", part.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(".");
+        self.with_connection_write(|conn| Ok(conn.execute(&format!("DELETE FROM {}", quoted), [])?))
+    }
+
+    /// Most recently applied migration and when it ran
+    pub fn get_last_migration(&self) -> Result<Option<(String, DateTime<Utc>)>> {
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT migration_name, applied_at::VARCHAR FROM sys_migrations
+                 ORDER BY migration_name DESC LIMIT 1",
+            )?;
+            let mut rows = stmt.query([])?;
+            match rows.next()? {
+                Some(row) => {
+                    let name: String = row.get(0)?;
+                    let applied_at: String = row.get(1)?;
+                    Ok(Some((name, parse_naive_datetime(&applied_at).and_utc())))
+                }
+                None => Ok(None),
+            }
+        })
+    }
+
     // ========================================================================
     // Auto-Tag Rules
     // ========================================================================
@@ -2183,7 +3761,8 @@ impl DuckDbRepository {
             // to read VARCHAR[] as String, returning "[]" and causing rules to have no tags.
             // This was the root cause of auto-tag rules not applying. See parse_duckdb_array().
             let mut stmt = conn.prepare(
-                "SELECT rule_id, name, sql_condition, CAST(tags AS VARCHAR) as tags_str, enabled, sort_order
+                "SELECT rule_id, name, sql_condition, CAST(tags AS VARCHAR) as tags_str, enabled, sort_order,
+                        COALESCE(exclude_from_reports, FALSE)
                  FROM sys_transactions_rules
                  WHERE enabled = true
                  ORDER BY sort_order, created_at"
@@ -2196,6 +3775,7 @@ impl DuckDbRepository {
                     name: row.get(1)?,
                     sql_condition: row.get(2)?,
                     tags: parse_duckdb_array(&tags_str),
+                    exclude_from_reports: row.get(6)?,
                     enabled: row.get(4)?,
                     sort_order: row.get(5)?,
                 })
@@ -2253,6 +3833,51 @@ impl DuckDbRepository {
             Ok(result)
         })
     }
+    /// Exclude matching transactions from reports in bulk (single connection).
+    ///
+    /// Returns the IDs that weren't excluded before.
+    pub fn exclude_matching_from_reports(
+        &self,
+        tx_ids: &[Uuid],
+        sql_condition: &str,
+    ) -> Result<Vec<Uuid>> {
+        if tx_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.with_connection_write(|conn| {
+            let id_list: Vec<String> = tx_ids.iter().map(|id| format!("'{}'", id)).collect();
+            let in_clause = id_list.join(", ");
+
+            let sql = format!(
+                "SELECT transaction_id FROM transactions
+                 WHERE transaction_id IN ({})
+                 AND NOT excluded_from_reports
+                 AND ({})",
+                in_clause, sql_condition
+            );
+            let mut stmt = conn.prepare(&sql)?;
+            let matching: Vec<String> = stmt
+                .query_map([], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            let mut modified = Vec::new();
+            for tx_id in &matching {
+                conn.execute(
+                    "UPDATE sys_transactions SET excluded_from_reports = TRUE, updated_at = CURRENT_TIMESTAMP
+                     WHERE transaction_id = ?",
+                    params![tx_id],
+                )?;
+                Self::log_change(conn, Captured::Transaction, UPSERT, "transaction_id = ?", &[tx_id])?;
+                if let Ok(uuid) = Uuid::parse_str(tx_id) {
+                    modified.push(uuid);
+                }
+            }
+            Ok(modified)
+        })
+    }
+
     /// Apply tags to matching transactions in bulk (single connection).
     ///
     /// For each matching transaction, merges the new tags with existing tags (additive).
@@ -2318,6 +3943,13 @@ impl DuckDbRepository {
                         tags_literal
                     );
                     conn.execute(&update_sql, params![tx_id_str])?;
+                    Self::log_change(
+                        conn,
+                        Captured::Transaction,
+                        UPSERT,
+                        "transaction_id = ?",
+                        &[tx_id_str],
+                    )?;
 
                     if let Ok(uuid) = Uuid::parse_str(tx_id_str) {
                         modified.push(uuid);
@@ -2330,8 +3962,55 @@ impl DuckDbRepository {
     }
 }
 
+impl Repository for DuckDbRepository {
+    fn get_accounts(&self) -> Result<Vec<Account>> {
+        DuckDbRepository::get_accounts(self)
+    }
+
+    fn get_account_by_id(&self, id: &str) -> Result<Option<Account>> {
+        DuckDbRepository::get_account_by_id(self, id)
+    }
+
+    fn upsert_account(&self, account: &Account) -> Result<()> {
+        DuckDbRepository::upsert_account(self, account)
+    }
+
+    fn get_transactions_by_account(&self, account_id: &str) -> Result<Vec<Transaction>> {
+        DuckDbRepository::get_transactions_by_account(self, account_id)
+    }
+
+    fn get_transaction_count(&self) -> Result<i64> {
+        DuckDbRepository::get_transaction_count(self)
+    }
+
+    fn upsert_transaction(&self, tx: &Transaction) -> Result<()> {
+        DuckDbRepository::upsert_transaction(self, tx)
+    }
+
+    fn get_balance_snapshots(&self, account_id: Option<&str>) -> Result<Vec<BalanceSnapshot>> {
+        DuckDbRepository::get_balance_snapshots(self, account_id)
+    }
+
+    fn add_balance_snapshot(&self, snapshot: &BalanceSnapshot) -> Result<()> {
+        DuckDbRepository::add_balance_snapshot(self, snapshot)
+    }
+
+    fn bulk_insert_balance_snapshots(&self, snapshots: &[BalanceSnapshot]) -> Result<usize> {
+        DuckDbRepository::bulk_insert_balance_snapshots(self, snapshots)
+    }
+
+    fn delete_balance_snapshots_in_range(
+        &self,
+        account_id: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<usize> {
+        DuckDbRepository::delete_balance_snapshots_in_range(self, account_id, start_date, end_date)
+    }
+}
+
 /// Query result structure
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
 pub struct QueryResult {
     pub columns: Vec<String>,
     pub rows: Vec<Vec<serde_json::Value>>,
@@ -2345,6 +4024,118 @@ pub struct Integration {
     pub settings: serde_json::Value,
 }
 
+/// Number of sync runs kept in sys_sync_history
+pub const SYNC_HISTORY_RETENTION: usize = 1000;
+
+/// One integration's sync run, as recorded in sys_sync_history
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
+pub struct SyncHistoryEntry {
+    pub sync_id: String,
+    pub integration: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: i64,
+    pub sync_type: Option<String>,
+    pub accounts_synced: i64,
+    pub transactions_discovered: i64,
+    pub transactions_new: i64,
+    pub transactions_skipped: i64,
+    /// New transactions per account ID
+    pub account_stats: std::collections::BTreeMap<String, i64>,
+    pub warnings: Vec<String>,
+    /// Why the sync failed, if it did
+    pub error: Option<String>,
+}
+
+/// `op` values in sys_change_log
+const UPSERT: &str = "upsert";
+const DELETE: &str = "delete";
+
+/// Tables whose changes are recorded in sys_change_log
+#[derive(Clone, Copy)]
+enum Captured {
+    Account,
+    Transaction,
+    BalanceSnapshot,
+}
+
+impl Captured {
+    fn entity(self) -> &'static str {
+        match self {
+            Self::Account => "account",
+            Self::Transaction => "transaction",
+            Self::BalanceSnapshot => "balance_snapshot",
+        }
+    }
+
+    fn table(self) -> &'static str {
+        match self {
+            Self::Account => "sys_accounts",
+            Self::Transaction => "sys_transactions",
+            Self::BalanceSnapshot => "sys_balance_snapshots",
+        }
+    }
+
+    fn key_column(self) -> &'static str {
+        match self {
+            Self::Account => "account_id",
+            Self::Transaction => "transaction_id",
+            Self::BalanceSnapshot => "snapshot_id",
+        }
+    }
+}
+
+/// One entry in the change log
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
+pub struct ChangeEvent {
+    /// Increases by one per change; resume after the last one seen
+    pub seq: i64,
+    /// "account", "transaction" or "balance_snapshot"
+    pub entity: String,
+    pub entity_id: String,
+    /// "upsert" or "delete" (transaction deletes are soft: the payload has deleted_at set)
+    pub op: String,
+    /// The row after an upsert, or before a delete
+    pub payload: serde_json::Value,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// One replicated field as last exchanged through device sync
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SyncedField {
+    /// "account" or "transaction"
+    pub entity: String,
+    pub entity_id: String,
+    pub field: String,
+    pub value: serde_json::Value,
+    /// When the change was first synced, on the device that made it
+    pub updated_at: DateTime<Utc>,
+    pub device_id: String,
+}
+
+/// Another device whose bundles have been applied here
+#[derive(Debug, Clone)]
+pub struct DeviceSyncPeer {
+    pub device_id: String,
+    pub device_name: Option<String>,
+    pub last_seq: i64,
+    pub last_synced_at: Option<DateTime<Utc>>,
+}
+
+/// A year moved out of the main database, as recorded in sys_archives
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
+pub struct ArchiveInfo {
+    pub year: i32,
+    pub file_name: String,
+    pub path: PathBuf,
+    /// None when the archive file is missing
+    pub size_bytes: Option<u64>,
+    pub transaction_count: i64,
+    pub balance_snapshot_count: i64,
+    pub archived_at: DateTime<Utc>,
+    /// Attached read-only to SQL queries as `archive_<year>`
+    pub mounted: bool,
+}
+
 // Helper functions
 
 fn parse_timestamp(s: &str) -> DateTime<Utc> {
@@ -2384,6 +4175,11 @@ fn parse_naive_datetime(s: &str) -> NaiveDateTime {
         .unwrap_or_else(|_| Utc::now().naive_utc())
 }
 
+/// JSON path to a provider's entry in external_ids
+fn external_id_path(provider: &str) -> String {
+    format!("$.\"{}\"", provider)
+}
+
 /// Format tags as a DuckDB array literal: ['tag1', 'tag2']
 fn format_tags_array(tags: &[String]) -> String {
     if tags.is_empty() {
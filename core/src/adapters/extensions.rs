@@ -0,0 +1,170 @@
+//! Optional DuckDB extensions, loaded explicitly
+//!
+//! Treeline opens DuckDB with extension autoloading off: extensions cached in
+//! ~/.duckdb/extensions may be signed by a different team than the app,
+//! which macOS refuses to load. So an extension only works when it's already
+//! installed and an explicit `LOAD` succeeds. Anything that would use one
+//! checks [`load`] first and falls back, and a query that needs a missing one
+//! gets an error saying which, rather than DuckDB's advice to turn
+//! autoloading back on.
+
+use anyhow::Result;
+use duckdb::Connection;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// An optional extension and what Treeline does without it
+pub struct OptionalExtension {
+    pub name: &'static str,
+    /// What it's for
+    pub purpose: &'static str,
+    /// What happens when it can't load
+    pub fallback: &'static str,
+}
+
+/// The extensions Treeline can use when they load
+pub const OPTIONAL_EXTENSIONS: &[OptionalExtension] = &[
+    OptionalExtension {
+        name: "fts",
+        purpose: "full-text search",
+        fallback: "search matches words anywhere in the description",
+    },
+    OptionalExtension {
+        name: "httpfs",
+        purpose: "reading s3:// and https:// paths",
+        fallback: "backups and exports stay on local disk",
+    },
+];
+
+/// Whether an optional extension loads here
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ExtensionStatus {
+    pub name: String,
+    pub purpose: String,
+    /// Installed in DuckDB's extension directory
+    pub installed: bool,
+    /// Loaded, or loadable with an explicit `LOAD`
+    pub available: bool,
+    /// What's used instead when it isn't available
+    pub fallback: String,
+    /// Why an installed extension failed to load (e.g. a signature mismatch)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Load an extension on `conn` if it's installed; Err says why it isn't usable
+pub fn load(conn: &Connection, name: &str) -> std::result::Result<(), String> {
+    let (installed, loaded) = state(conn, name).map_err(|e| e.to_string())?;
+    if loaded {
+        return Ok(());
+    }
+    if !installed {
+        return Err(format!("the {} extension isn't installed", name));
+    }
+    conn.execute_batch(&format!("LOAD {}", name))
+        .map_err(|e| format!("the {} extension didn't load: {}", name, e))
+}
+
+/// Try each optional extension on `conn`
+pub fn probe(conn: &Connection) -> Vec<ExtensionStatus> {
+    OPTIONAL_EXTENSIONS
+        .iter()
+        .map(|extension| {
+            let installed = state(conn, extension.name).is_ok_and(|(installed, _)| installed);
+            let result = load(conn, extension.name);
+            ExtensionStatus {
+                name: extension.name.to_string(),
+                purpose: extension.purpose.to_string(),
+                installed,
+                available: result.is_ok(),
+                fallback: extension.fallback.to_string(),
+                error: result.err().filter(|_| installed),
+            }
+        })
+        .collect()
+}
+
+/// (installed, loaded) as DuckDB reports it; unknown names are neither
+fn state(conn: &Connection, name: &str) -> Result<(bool, bool)> {
+    let mut stmt =
+        conn.prepare("SELECT installed, loaded FROM duckdb_extensions() WHERE extension_name = ?")?;
+    let mut rows = stmt.query([name])?;
+    match rows.next()? {
+        Some(row) => Ok((row.get(0)?, row.get(1)?)),
+        None => Ok((false, false)),
+    }
+}
+
+/// DuckDB's error for something that needs an extension that isn't loaded,
+/// reworded; None for any other error
+///
+/// DuckDB suggests `INSTALL`/`LOAD` or turning autoloading on, neither of
+/// which a Treeline query can do.
+pub fn explain_error(message: &str) -> Option<String> {
+    let name = extension_named_in(message)?;
+    let purpose = OPTIONAL_EXTENSIONS
+        .iter()
+        .find(|e| e.name == name)
+        .map(|e| format!(" ({})", e.purpose))
+        .unwrap_or_default();
+    Some(format!(
+        "This needs DuckDB's {}{} extension, which isn't loaded: Treeline doesn't \
+         load extensions automatically. Run `tl doctor` to see which extensions are available.",
+        name, purpose
+    ))
+}
+
+fn extension_named_in(message: &str) -> Option<String> {
+    // "...but it exists in the fts extension." (missing function or pragma)
+    // "...requires the extension httpfs to be loaded" (remote file paths)
+    let after = |marker: &str| {
+        message.find(marker).map(|i| {
+            message[i + marker.len()..]
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+                .collect::<String>()
+        })
+    };
+    let name = after("exists in the ")
+        .filter(|_| message.contains(" extension"))
+        .or_else(|| after("requires the extension "))?;
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_extension_errors() {
+        let missing_pragma = "Catalog Error: Pragma Function with name create_fts_index is not \
+            in the catalog, but it exists in the fts extension.\n\nPlease try installing and \
+            loading the fts extension by running:\nINSTALL fts;\nLOAD fts;";
+        let explained = explain_error(missing_pragma).unwrap();
+        assert!(explained.starts_with("This needs DuckDB's fts (full-text search) extension"));
+
+        let remote_file = "Missing Extension Error: File s3://bucket/tx.csv requires the \
+            extension httpfs to be loaded";
+        assert!(explain_error(remote_file)
+            .unwrap()
+            .contains("httpfs (reading s3://"));
+
+        assert!(explain_error("Catalog Error: Table with name budgets does not exist!").is_none());
+    }
+
+    #[test]
+    fn test_probe_without_autoload() {
+        let config = duckdb::Config::default()
+            .enable_autoload_extension(false)
+            .unwrap();
+        let conn = Connection::open_in_memory_with_flags(config).unwrap();
+        let statuses = probe(&conn);
+        assert_eq!(statuses.len(), OPTIONAL_EXTENSIONS.len());
+        for status in &statuses {
+            // Only what's installed can load, and only installed ones carry an error
+            assert!(status.installed || !status.available);
+            assert!(status.error.is_none() || status.installed);
+        }
+        assert!(load(&conn, "no_such_extension").is_err());
+    }
+}
@@ -0,0 +1,1169 @@
+//! GoCardless Bank Account Data API client
+//!
+//! Handles communication with GoCardless Bank Account Data (formerly
+//! Nordigen), which reaches banks across the EU and UK through PSD2.
+//! Connecting a bank is a *requisition*: setup creates one for the chosen
+//! institution and hands back a link where the user gives consent at their
+//! bank. Once linked, the requisition lists the bank's accounts, and access
+//! lasts for the agreement's term (usually 90 days) before it has to be
+//! renewed with a new requisition.
+//!
+//! API Documentation: https://developer.gocardless.com/bank-account-data/overview
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use reqwest::blocking::{Client, RequestBuilder};
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{json, Value as JsonValue};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::domain::result::{Error as DomainError, Result as DomainResult};
+use crate::domain::{Account, AccountType, BalanceSnapshot, ProviderData, Transaction};
+use crate::ports::{
+    DataAggregationProvider, FetchAccountsResult, FetchTransactionsResult, IntegrationProvider,
+};
+
+// =============================================================================
+// API Response Models (matching GoCardless API spec)
+// =============================================================================
+
+/// Error body GoCardless sends with most non-2xx responses
+#[derive(Debug, Clone, Deserialize)]
+struct ErrorResponse {
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct TokenResponse {
+    access: String,
+}
+
+/// A bank GoCardless can connect to
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GoCardlessInstitution {
+    /// e.g. "REVOLUT_REVOGB21"
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub bic: Option<String>,
+    /// How far back the bank offers transactions
+    #[serde(default, deserialize_with = "days")]
+    pub transaction_total_days: Option<u32>,
+    /// Longest access the bank allows before consent must be renewed
+    #[serde(default, deserialize_with = "days")]
+    pub max_access_valid_for_days: Option<u32>,
+    #[serde(default)]
+    pub countries: Vec<String>,
+    #[serde(default)]
+    pub logo: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AgreementResponse {
+    id: String,
+    /// When the user gave consent (absent until they do)
+    #[serde(default)]
+    accepted: Option<String>,
+    #[serde(default, deserialize_with = "days")]
+    access_valid_for_days: Option<u32>,
+}
+
+/// A bank connection and the accounts consent was given for
+#[derive(Debug, Clone, Deserialize)]
+pub struct Requisition {
+    pub id: String,
+    /// CR created, GC giving consent, UA authenticating, RJ rejected,
+    /// SA selecting accounts, GA granting access, LN linked, EX expired
+    pub status: String,
+    pub link: String,
+    #[serde(default)]
+    pub accounts: Vec<String>,
+    #[serde(default)]
+    pub agreement: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct DetailsResponse {
+    account: AccountDetails,
+}
+
+/// GoCardless account details from API
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountDetails {
+    #[serde(default)]
+    pub iban: Option<String>,
+    #[serde(default)]
+    pub currency: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub product: Option<String>,
+    #[serde(default)]
+    pub owner_name: Option<String>,
+    /// ISO 20022 code: CACC current, SVGS savings, CARD, LOAN, ...
+    #[serde(default)]
+    pub cash_account_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct BalancesResponse {
+    #[serde(default)]
+    balances: Vec<Balance>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Balance {
+    pub balance_amount: Amount,
+    /// interimBooked, closingBooked, expected, interimAvailable, ...
+    pub balance_type: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Amount {
+    /// Signed decimal string, e.g. "-12.50"
+    pub amount: String,
+    #[serde(default)]
+    pub currency: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct TransactionsResponse {
+    transactions: BookedAndPending,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct BookedAndPending {
+    #[serde(default)]
+    booked: Vec<GoCardlessTransaction>,
+}
+
+/// GoCardless transaction from API
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoCardlessTransaction {
+    /// The bank's ID; not every bank sends one
+    #[serde(default)]
+    pub transaction_id: Option<String>,
+    #[serde(default)]
+    pub internal_transaction_id: Option<String>,
+    #[serde(default)]
+    pub booking_date: Option<String>,
+    #[serde(default)]
+    pub value_date: Option<String>,
+    pub transaction_amount: Amount,
+    #[serde(default)]
+    pub creditor_name: Option<String>,
+    #[serde(default)]
+    pub debtor_name: Option<String>,
+    #[serde(default)]
+    pub remittance_information_unstructured: Option<String>,
+    #[serde(default)]
+    pub remittance_information_unstructured_array: Vec<String>,
+    #[serde(default)]
+    pub additional_information: Option<String>,
+}
+
+/// GoCardless sends day counts as numbers or as strings, depending on the endpoint
+fn days<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<u32>, D::Error> {
+    Ok(match Option::<JsonValue>::deserialize(deserializer)? {
+        Some(JsonValue::Number(n)) => n.as_u64().map(|n| n as u32),
+        Some(JsonValue::String(s)) => s.parse().ok(),
+        _ => None,
+    })
+}
+
+/// An error GoCardless reported
+#[derive(Debug, Clone)]
+pub struct GoCardlessApiError {
+    pub status: u16,
+    pub summary: String,
+    pub detail: String,
+    /// Seconds until the account's rate limit resets (429 only)
+    pub retry_after: Option<u64>,
+}
+
+impl fmt::Display for GoCardlessApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.status {
+            401 => write!(
+                f,
+                "GoCardless authentication failed. Check your secret ID and key."
+            ),
+            429 => match self.retry_after {
+                Some(seconds) => write!(
+                    f,
+                    "GoCardless rate limit reached (banks allow a few requests a day); try again in {} hours",
+                    seconds.div_ceil(3600)
+                ),
+                None => write!(
+                    f,
+                    "GoCardless rate limit reached (banks allow a few requests a day); try again later"
+                ),
+            },
+            _ if self.detail.is_empty() => {
+                write!(f, "GoCardless error {}: {}", self.status, self.summary)
+            }
+            _ => write!(
+                f,
+                "GoCardless error {}: {}: {}",
+                self.status, self.summary, self.detail
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GoCardlessApiError {}
+
+/// One account's details and balance
+#[derive(Debug)]
+pub struct SyncedAccount {
+    pub account: Account,
+    pub balance_snapshot: Option<BalanceSnapshot>,
+}
+
+// =============================================================================
+// GoCardless HTTP Client
+// =============================================================================
+
+pub const GOCARDLESS_API_URL: &str = "https://bankaccountdata.gocardless.com/api/v2";
+
+/// Where the bank sends the user after consent when setup isn't given one
+pub const DEFAULT_REDIRECT_URL: &str = "https://treeline.money";
+
+/// Agreement terms when the institution doesn't state its own
+const DEFAULT_HISTORY_DAYS: u32 = 90;
+const DEFAULT_ACCESS_DAYS: u32 = 90;
+
+/// How close to expiry sync starts warning that access needs renewing
+const EXPIRY_WARNING_DAYS: i64 = 7;
+
+/// Requisition statuses that can't become linked any more
+const DEAD_STATUSES: &[&str] = &["EX", "RJ", "SU"];
+
+/// GoCardless API client, authenticated with a short-lived access token
+#[derive(Debug)]
+pub struct GoCardlessClient {
+    client: Client,
+    base_url: String,
+    access_token: String,
+}
+
+impl GoCardlessClient {
+    /// Get an access token for the secret ID and key from the GoCardless portal
+    pub fn connect(secret_id: &str, secret_key: &str, base_url: &str) -> Result<Self> {
+        if secret_id.is_empty() || secret_key.is_empty() {
+            anyhow::bail!("GoCardless secret ID and key cannot be empty");
+        }
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(120))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        let mut gc = Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            access_token: String::new(),
+        };
+        let token: TokenResponse = gc.post(
+            "/token/new/",
+            json!({ "secret_id": secret_id, "secret_key": secret_key }),
+        )?;
+        gc.access_token = token.access;
+        Ok(gc)
+    }
+
+    /// Banks GoCardless can connect to in a country (ISO 3166 code, e.g. "DE")
+    pub fn list_institutions(&self, country: &str) -> Result<Vec<GoCardlessInstitution>> {
+        self.get(&format!(
+            "/institutions/?country={}",
+            country.to_ascii_lowercase()
+        ))
+    }
+
+    pub fn get_institution(&self, institution_id: &str) -> Result<GoCardlessInstitution> {
+        self.get(&format!("/institutions/{}/", institution_id))
+    }
+
+    /// Create a requisition for a bank and the agreement it asks consent for
+    ///
+    /// The agreement asks for as much history and as long access as the bank
+    /// allows. The user gives consent at the returned requisition's link.
+    pub fn create_requisition(
+        &self,
+        institution: &GoCardlessInstitution,
+        redirect_url: &str,
+    ) -> Result<Requisition> {
+        let agreement: AgreementResponse = self.post(
+            "/agreements/enduser/",
+            json!({
+                "institution_id": institution.id,
+                "max_historical_days": institution
+                    .transaction_total_days
+                    .unwrap_or(DEFAULT_HISTORY_DAYS),
+                "access_valid_for_days": institution
+                    .max_access_valid_for_days
+                    .unwrap_or(DEFAULT_ACCESS_DAYS),
+                "access_scope": ["balances", "details", "transactions"],
+            }),
+        )?;
+        self.post(
+            "/requisitions/",
+            json!({
+                "redirect": redirect_url,
+                "institution_id": institution.id,
+                "agreement": agreement.id,
+                "reference": Uuid::new_v4().to_string(),
+                "user_language": "EN",
+            }),
+        )
+    }
+
+    pub fn get_requisition(&self, requisition_id: &str) -> Result<Requisition> {
+        self.get(&format!("/requisitions/{}/", requisition_id))
+    }
+
+    /// When access under an agreement ends, once the user has given consent
+    pub fn get_access_expiry(&self, agreement_id: &str) -> Result<Option<DateTime<Utc>>> {
+        let agreement: AgreementResponse =
+            self.get(&format!("/agreements/enduser/{}/", agreement_id))?;
+        let accepted = agreement
+            .accepted
+            .as_deref()
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok());
+        Ok(accepted.map(|at| {
+            at.with_timezone(&Utc)
+                + chrono::Duration::days(
+                    agreement
+                        .access_valid_for_days
+                        .unwrap_or(DEFAULT_ACCESS_DAYS) as i64,
+                )
+        }))
+    }
+
+    /// Fetch an account's details and current balance
+    pub fn get_account(
+        &self,
+        account_id: &str,
+        institution_id: &str,
+        institution_name: Option<&str>,
+    ) -> Result<SyncedAccount> {
+        let details: DetailsResponse = self.get(&format!("/accounts/{}/details/", account_id))?;
+        let balances: BalancesResponse =
+            self.get(&format!("/accounts/{}/balances/", account_id))?;
+
+        let account = map_account(
+            account_id,
+            &details.account,
+            &balances.balances,
+            institution_id,
+            institution_name,
+        );
+        let balance_snapshot = account.balance.map(|balance| BalanceSnapshot {
+            id: Uuid::new_v4(),
+            account_id: account.id,
+            balance,
+            snapshot_time: Utc::now().naive_utc(),
+            source: Some("sync".to_string()),
+            csv_batch_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        });
+
+        Ok(SyncedAccount {
+            account,
+            balance_snapshot,
+        })
+    }
+
+    /// Fetch an account's booked transactions between two dates
+    ///
+    /// Pending transactions are skipped: banks rarely give them an ID, so
+    /// they couldn't be matched to the booked transaction that replaces them.
+    pub fn get_transactions(
+        &self,
+        account_id: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<Transaction>> {
+        let response: TransactionsResponse = self.get(&format!(
+            "/accounts/{}/transactions/?date_from={}&date_to={}",
+            account_id,
+            start_date.format("%Y-%m-%d"),
+            end_date.format("%Y-%m-%d")
+        ))?;
+        Ok(map_transactions(account_id, &response.transactions.booked))
+    }
+
+    fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let url = format!("{}{}", self.base_url, path);
+        self.send(self.client.get(&url), path)
+    }
+
+    fn post<T: DeserializeOwned>(&self, path: &str, body: JsonValue) -> Result<T> {
+        let url = format!("{}{}", self.base_url, path);
+        self.send(self.client.post(&url).json(&body), path)
+    }
+
+    fn send<T: DeserializeOwned>(&self, request: RequestBuilder, path: &str) -> Result<T> {
+        let request = if self.access_token.is_empty() {
+            request
+        } else {
+            request.bearer_auth(&self.access_token)
+        };
+        let response = request.send().map_err(|e| self.map_request_error(e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = response
+                .headers()
+                .get("x-ratelimit-account-success-reset")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse().ok());
+            // GoCardless explains what went wrong in the body
+            let error = response.json::<ErrorResponse>().ok();
+            return Err(GoCardlessApiError {
+                status: status.as_u16(),
+                summary: error
+                    .as_ref()
+                    .and_then(|e| e.summary.clone())
+                    .unwrap_or_else(|| format!("HTTP {}", status.as_u16())),
+                detail: error.and_then(|e| e.detail).unwrap_or_default(),
+                retry_after,
+            }
+            .into());
+        }
+
+        response
+            .json()
+            .with_context(|| format!("Failed to parse GoCardless {} response", path))
+    }
+
+    /// Map request errors to user-friendly messages
+    fn map_request_error(&self, error: reqwest::Error) -> anyhow::Error {
+        if error.is_timeout() {
+            anyhow::anyhow!("Connection timed out after 120 seconds")
+        } else if error.is_connect() {
+            anyhow::anyhow!("Unable to connect to GoCardless servers")
+        } else {
+            anyhow::anyhow!("GoCardless request failed: {}", error)
+        }
+    }
+}
+
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+/// Treeline account type for an ISO 20022 cash account type
+fn gocardless_account_type(
+    details: &AccountDetails,
+    name: &str,
+    institution_name: Option<&str>,
+) -> Option<AccountType> {
+    match details.cash_account_type.as_deref() {
+        Some("CACC") | Some("TRAN") | Some("CASH") => Some(AccountType::Checking),
+        Some("SVGS") | Some("MOMA") => Some(AccountType::Savings),
+        Some("CARD") => Some(AccountType::CreditCard),
+        Some("LOAN") => Some(AccountType::Loan),
+        _ => AccountType::infer(name, institution_name),
+    }
+}
+
+/// The balance closest to what the bank shows: booked, then expected, then available
+fn pick_balance(balances: &[Balance]) -> Option<&Balance> {
+    [
+        "interimBooked",
+        "closingBooked",
+        "expected",
+        "interimAvailable",
+    ]
+    .iter()
+    .find_map(|kind| balances.iter().find(|b| b.balance_type == *kind))
+    .or(balances.first())
+}
+
+/// Map GoCardless account to domain Account
+fn map_account(
+    account_id: &str,
+    details: &AccountDetails,
+    balances: &[Balance],
+    institution_id: &str,
+    institution_name: Option<&str>,
+) -> Account {
+    let balance = pick_balance(balances);
+    let name = details
+        .name
+        .clone()
+        .or_else(|| details.product.clone())
+        .or_else(|| {
+            // Banks that name nothing still have an IBAN to tell accounts apart
+            details.iban.as_ref().map(|iban| {
+                let last4 = &iban[iban.len().saturating_sub(4)..];
+                format!("{} {}", institution_name.unwrap_or("Account"), last4)
+            })
+        })
+        .unwrap_or_else(|| institution_name.unwrap_or("GoCardless Account").to_string());
+
+    let account_type = gocardless_account_type(details, &name, institution_name);
+    let classification = Some(Account::compute_classification(
+        account_type.map(|t| t.as_str()),
+    ));
+
+    let now = Utc::now();
+    Account {
+        id: Uuid::new_v4(),
+        name,
+        nickname: None,
+        currency: details
+            .currency
+            .clone()
+            .or_else(|| balance.and_then(|b| b.balance_amount.currency.clone()))
+            .unwrap_or_else(|| "EUR".to_string()),
+        account_type: account_type.map(|t| t.to_string()),
+        classification,
+        balance: balance.and_then(|b| b.balance_amount.amount.parse().ok()),
+        institution_name: institution_name.map(|n| n.to_string()),
+        institution_url: None,
+        institution_domain: None,
+        created_at: now,
+        updated_at: now,
+        // Manual flag
+        is_manual: false,
+        // GoCardless: identity fields from /accounts/{id}/details
+        provider: ProviderData::new(
+            "gocardless",
+            account_id,
+            json!({
+                "institution_id": institution_id,
+                "iban": details.iban,
+            }),
+        ),
+    }
+}
+
+/// Map an account's GoCardless transactions to domain Transactions
+///
+/// Transactions without an ID from the bank or GoCardless get one hashed
+/// from what they say; identical ones on the same day are numbered apart.
+fn map_transactions(account_id: &str, gc_txs: &[GoCardlessTransaction]) -> Vec<Transaction> {
+    let mut seen: HashMap<String, u32> = HashMap::new();
+    gc_txs
+        .iter()
+        .map(|gc_tx| {
+            let mut tx = map_transaction(account_id, gc_tx);
+            if tx.provider.external_id("gocardless").is_none() {
+                let key = format!(
+                    "{}|{}|{}|{}",
+                    account_id,
+                    tx.posted_date,
+                    gc_tx.transaction_amount.amount,
+                    tx.description.as_deref().unwrap_or_default()
+                );
+                let n = seen.entry(key.clone()).or_insert(0);
+                *n += 1;
+                let digest = Sha256::digest(format!("{}|{}", key, n).as_bytes());
+                let id = format!("gc-{}", hex::encode(&digest[..16]));
+                tx.provider.set_external_id("gocardless", Some(id));
+            }
+            tx
+        })
+        .collect()
+}
+
+/// Map GoCardless transaction to domain Transaction
+fn map_transaction(account_id: &str, gc_tx: &GoCardlessTransaction) -> Transaction {
+    let booking_date = gc_tx.booking_date.as_deref().and_then(parse_date);
+    let value_date = gc_tx.value_date.as_deref().and_then(parse_date);
+    let posted_date = booking_date
+        .or(value_date)
+        .unwrap_or_else(|| Utc::now().naive_utc().date());
+    // Card payments are valued the day they're made, but some banks value
+    // transfers after booking them
+    let transaction_date = value_date
+        .filter(|d| *d < posted_date)
+        .unwrap_or(posted_date);
+
+    let counterparty = gc_tx
+        .creditor_name
+        .as_ref()
+        .or(gc_tx.debtor_name.as_ref())
+        .filter(|n| !n.trim().is_empty())
+        .cloned();
+    let description = gc_tx
+        .remittance_information_unstructured
+        .clone()
+        .or_else(|| {
+            Some(gc_tx.remittance_information_unstructured_array.join(" "))
+                .filter(|d| !d.is_empty())
+        })
+        .or_else(|| gc_tx.additional_information.clone())
+        .filter(|d| !d.trim().is_empty())
+        .or_else(|| counterparty.clone());
+
+    // Transactions without an ID get one in map_transactions
+    let mut provider = ProviderData::default();
+    provider.set_external_id(
+        "gocardless",
+        gc_tx
+            .transaction_id
+            .clone()
+            .or_else(|| gc_tx.internal_transaction_id.clone()),
+    );
+    provider.set_metadata("gocardless", "account_id", account_id);
+    provider.set_metadata("gocardless", "counterparty", counterparty);
+
+    let now = Utc::now();
+    Transaction {
+        id: Uuid::new_v4(),
+        account_id: Uuid::nil(), // Will be set by sync service after mapping
+        // GoCardless amounts are already negative for money out
+        amount: gc_tx
+            .transaction_amount
+            .amount
+            .parse()
+            .unwrap_or(Decimal::ZERO),
+        description,
+        transaction_date,
+        posted_date,
+        tags: vec![],
+        created_at: now,
+        updated_at: now,
+        deleted_at: None,
+        parent_transaction_id: None,
+        // CSV Import tracking (not applicable)
+        csv_fingerprint: None,
+        csv_batch_id: None,
+        // Reconciliation identifiers (CSV import only)
+        check_number: None,
+        reference: None,
+        // Currency (CSV import only; the account's currency applies)
+        currency: None,
+        // Manual flag
+        is_manual: false,
+        // Auto-tag tracking (starts false, set true when rules apply)
+        tags_auto_applied: false,
+        // Report exclusion (set by the user or a rule)
+        excluded_from_reports: false,
+        // Booked transactions only
+        pending: false,
+        // GoCardless: raw fields from /accounts/{id}/transactions
+        provider,
+    }
+}
+
+// =============================================================================
+// GoCardlessProvider - implements DataAggregationProvider trait
+// =============================================================================
+
+/// A connected bank in the integration's `requisitions`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RequisitionSettings {
+    requisition_id: String,
+    institution_id: String,
+    #[serde(default)]
+    institution_name: Option<String>,
+    /// Where the user gives consent, until they have
+    #[serde(default)]
+    link: Option<String>,
+}
+
+impl RequisitionSettings {
+    /// How warnings name the bank
+    fn label(&self) -> &str {
+        self.institution_name
+            .as_deref()
+            .unwrap_or(&self.institution_id)
+    }
+}
+
+/// The API URL in settings (for testing with mock server), or GoCardless's
+pub fn base_url(settings: &JsonValue) -> &str {
+    settings
+        .get("baseUrl")
+        .and_then(|v| v.as_str())
+        .unwrap_or(GOCARDLESS_API_URL)
+}
+
+/// Client for the secrets in settings or setup options
+fn client_from(settings: &JsonValue) -> DomainResult<GoCardlessClient> {
+    let field = |key: &str| settings.get(key).and_then(|v| v.as_str());
+    let (Some(secret_id), Some(secret_key)) = (field("secretId"), field("secretKey")) else {
+        return Err(DomainError::Config(
+            "GoCardless secretId and secretKey not found in settings".to_string(),
+        ));
+    };
+
+    GoCardlessClient::connect(secret_id, secret_key, base_url(settings))
+        .map_err(|e| DomainError::Sync(e.to_string()))
+}
+
+/// The connected banks in settings
+fn requisitions(settings: &JsonValue) -> DomainResult<Vec<RequisitionSettings>> {
+    let requisitions: Vec<RequisitionSettings> = match settings.get("requisitions") {
+        Some(requisitions) => serde_json::from_value(requisitions.clone()).map_err(|e| {
+            DomainError::Config(format!(
+                "Invalid GoCardless requisitions in settings: {}",
+                e
+            ))
+        })?,
+        None => Vec::new(),
+    };
+    if requisitions.is_empty() {
+        return Err(DomainError::Config(
+            "No banks are connected to GoCardless yet".to_string(),
+        ));
+    }
+    Ok(requisitions)
+}
+
+/// What a requisition that isn't linked needs from the user
+fn requisition_warning(requisition: &Requisition, bank: &RequisitionSettings) -> String {
+    if DEAD_STATUSES.contains(&requisition.status.as_str()) {
+        format!(
+            "{}: access has expired. Run `tl setup gocardless --institution {}` to renew it.",
+            bank.label(),
+            bank.institution_id
+        )
+    } else {
+        format!(
+            "{}: finish connecting at {}",
+            bank.label(),
+            bank.link.as_deref().unwrap_or(&requisition.link)
+        )
+    }
+}
+
+/// GoCardless data provider
+///
+/// Implements DataAggregationProvider and IntegrationProvider traits
+/// for syncing financial data via GoCardless Bank Account Data.
+pub struct GoCardlessProvider;
+
+impl GoCardlessProvider {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for GoCardlessProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DataAggregationProvider for GoCardlessProvider {
+    fn name(&self) -> &str {
+        "gocardless"
+    }
+
+    fn can_get_accounts(&self) -> bool {
+        true
+    }
+
+    fn can_get_transactions(&self) -> bool {
+        true
+    }
+
+    fn can_get_balances(&self) -> bool {
+        true
+    }
+
+    /// Accounts of every linked requisition
+    ///
+    /// A bank the user hasn't finished connecting, or whose access expired
+    /// or expires within a week, becomes a warning saying what to do, as does
+    /// an account that fails (e.g. the bank's daily rate limit). Only when
+    /// nothing comes back is it an error.
+    fn get_accounts(&self, settings: &JsonValue) -> DomainResult<FetchAccountsResult> {
+        let client = client_from(settings)?;
+
+        let mut result = FetchAccountsResult::default();
+        for bank in requisitions(settings)? {
+            let requisition = match client.get_requisition(&bank.requisition_id) {
+                Ok(requisition) => requisition,
+                Err(e) => {
+                    result.warnings.push(format!("{}: {}", bank.label(), e));
+                    continue;
+                }
+            };
+            if requisition.status != "LN" {
+                result
+                    .warnings
+                    .push(requisition_warning(&requisition, &bank));
+                continue;
+            }
+
+            // Expiry is only advice, so a failed lookup is skipped
+            let expires = requisition
+                .agreement
+                .as_deref()
+                .and_then(|id| client.get_access_expiry(id).ok().flatten());
+            if let Some(expires) = expires {
+                let days_left = (expires - Utc::now()).num_days();
+                if days_left <= EXPIRY_WARNING_DAYS {
+                    result.warnings.push(format!(
+                        "{}: access expires in {} day(s). Run `tl setup gocardless --institution {}` to renew it.",
+                        bank.label(),
+                        days_left.max(0),
+                        bank.institution_id
+                    ));
+                }
+            }
+
+            for account_id in &requisition.accounts {
+                match client.get_account(
+                    account_id,
+                    &bank.institution_id,
+                    bank.institution_name.as_deref(),
+                ) {
+                    Ok(synced) => {
+                        result.balance_snapshots.extend(synced.balance_snapshot);
+                        result.accounts.push(synced.account);
+                    }
+                    Err(e) => result.warnings.push(format!(
+                        "{} account {}: {}",
+                        bank.label(),
+                        account_id,
+                        e
+                    )),
+                }
+            }
+        }
+
+        if result.accounts.is_empty() && !result.warnings.is_empty() {
+            return Err(DomainError::Sync(result.warnings.remove(0)));
+        }
+        Ok(result)
+    }
+
+    /// Booked transactions of the linked requisitions' accounts
+    ///
+    /// Accounts from requisitions that have since expired are skipped;
+    /// `get_accounts` already warned about those.
+    fn get_transactions(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        account_ids: &[String],
+        settings: &JsonValue,
+    ) -> DomainResult<FetchTransactionsResult> {
+        let client = client_from(settings)?;
+        let wanted: HashSet<&str> = account_ids.iter().map(|id| id.as_str()).collect();
+
+        let mut result = FetchTransactionsResult::default();
+        for bank in requisitions(settings)? {
+            let linked = match client.get_requisition(&bank.requisition_id) {
+                Ok(requisition) if requisition.status == "LN" => requisition.accounts,
+                _ => continue,
+            };
+            for account_id in linked
+                .iter()
+                .filter(|id| wanted.is_empty() || wanted.contains(id.as_str()))
+            {
+                match client.get_transactions(account_id, start_date, end_date) {
+                    Ok(transactions) => result
+                        .transactions
+                        .extend(transactions.into_iter().map(|tx| (account_id.clone(), tx))),
+                    Err(e) => result.warnings.push(format!(
+                        "{} account {}: {}",
+                        bank.label(),
+                        account_id,
+                        e
+                    )),
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl IntegrationProvider for GoCardlessProvider {
+    /// Start connecting one bank
+    ///
+    /// Takes `secretId`, `secretKey` and `institutionId` (plus `redirect`, and
+    /// `baseUrl` for testing). Creates a requisition for the bank; the
+    /// settings hold it as the only requisition, with the `link` where the
+    /// user gives consent. Accounts sync once they have.
+    fn setup(&self, options: &JsonValue) -> DomainResult<JsonValue> {
+        // Getting an access token validates the secrets
+        let client = client_from(options)?;
+        let option = |key: &str| options.get(key).and_then(|v| v.as_str());
+        let sync_err = |e: anyhow::Error| DomainError::Sync(e.to_string());
+
+        let Some(institution_id) = option("institutionId") else {
+            return Err(DomainError::Config(
+                "GoCardless institutionId required for setup".to_string(),
+            ));
+        };
+        let institution = client.get_institution(institution_id).map_err(sync_err)?;
+        let requisition = client
+            .create_requisition(
+                &institution,
+                option("redirect").unwrap_or(DEFAULT_REDIRECT_URL),
+            )
+            .map_err(sync_err)?;
+
+        let mut settings = json!({
+            "secretId": option("secretId"),
+            "secretKey": option("secretKey"),
+            "requisitions": [{
+                "requisitionId": requisition.id,
+                "institutionId": institution.id,
+                "institutionName": institution.name,
+                "link": requisition.link,
+            }],
+        });
+
+        // Include base URL if custom (for testing)
+        if let Some(url) = option("baseUrl") {
+            settings["baseUrl"] = json!(url);
+        }
+
+        Ok(settings)
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance(kind: &str, amount: &str) -> Balance {
+        Balance {
+            balance_amount: Amount {
+                amount: amount.to_string(),
+                currency: Some("EUR".to_string()),
+            },
+            balance_type: kind.to_string(),
+        }
+    }
+
+    fn gc_tx(amount: &str) -> GoCardlessTransaction {
+        GoCardlessTransaction {
+            transaction_id: Some("tx_1".to_string()),
+            booking_date: Some("2025-01-16".to_string()),
+            value_date: Some("2025-01-15".to_string()),
+            transaction_amount: Amount {
+                amount: amount.to_string(),
+                currency: Some("EUR".to_string()),
+            },
+            creditor_name: Some("REWE Markt GmbH".to_string()),
+            remittance_information_unstructured: Some("REWE SAGT DANKE 1234".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_provider_name() {
+        let provider = GoCardlessProvider::new();
+        assert_eq!(provider.name(), "gocardless");
+    }
+
+    #[test]
+    fn test_reject_empty_secrets() {
+        let result = GoCardlessClient::connect("id", "", "http://localhost");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("cannot be empty"));
+    }
+
+    #[test]
+    fn test_institution_days_as_strings() {
+        let institution: GoCardlessInstitution = serde_json::from_value(json!({
+            "id": "SANDBOXFINANCE_SFIN0000",
+            "name": "Sandbox Finance",
+            "transaction_total_days": "540",
+            "max_access_valid_for_days": 180,
+            "countries": ["XX"],
+        }))
+        .unwrap();
+        assert_eq!(institution.transaction_total_days, Some(540));
+        assert_eq!(institution.max_access_valid_for_days, Some(180));
+    }
+
+    #[test]
+    fn test_account_mapping() {
+        let details = AccountDetails {
+            iban: Some("DE89370400440532013000".to_string()),
+            currency: Some("EUR".to_string()),
+            product: Some("Girokonto".to_string()),
+            cash_account_type: Some("CACC".to_string()),
+            ..Default::default()
+        };
+        let balances = [
+            balance("interimAvailable", "1500.00"),
+            balance("closingBooked", "1250.50"),
+        ];
+        let account = map_account(
+            "acc_1",
+            &details,
+            &balances,
+            "COMMERZBANK_COBADEFF",
+            Some("Commerzbank"),
+        );
+
+        assert_eq!(account.name, "Girokonto");
+        assert_eq!(account.account_type, Some("checking".to_string()));
+        // Booked balances win over available ones
+        assert_eq!(account.balance, Some(Decimal::new(125050, 2)));
+        assert_eq!(account.currency, "EUR");
+        assert_eq!(account.provider.external_id("gocardless"), Some("acc_1"));
+        assert_eq!(
+            account
+                .provider
+                .metadata_str("gocardless", "institution_id"),
+            Some("COMMERZBANK_COBADEFF")
+        );
+        assert_eq!(
+            account.provider.metadata_str("gocardless", "iban"),
+            details.iban.as_deref()
+        );
+    }
+
+    #[test]
+    fn test_account_mapping_unnamed() {
+        let details = AccountDetails {
+            iban: Some("GB33BUKB20201555555555".to_string()),
+            cash_account_type: Some("CARD".to_string()),
+            ..Default::default()
+        };
+        let account = map_account("acc_2", &details, &[], "MONZO_MONZGB2L", Some("Monzo"));
+
+        assert_eq!(account.name, "Monzo 5555");
+        assert_eq!(account.account_type, Some("credit_card".to_string()));
+        assert_eq!(account.balance, None);
+        assert_eq!(account.currency, "EUR");
+    }
+
+    #[test]
+    fn test_transaction_mapping() {
+        let tx = map_transaction("acc_1", &gc_tx("-23.45"));
+
+        // GoCardless amounts are already signed
+        assert_eq!(tx.amount, Decimal::new(-2345, 2));
+        assert_eq!(tx.description, Some("REWE SAGT DANKE 1234".to_string()));
+        assert_eq!(tx.transaction_date.to_string(), "2025-01-15");
+        assert_eq!(tx.posted_date.to_string(), "2025-01-16");
+        assert_eq!(tx.provider.external_id("gocardless"), Some("tx_1"));
+        assert_eq!(
+            tx.provider.metadata_str("gocardless", "account_id"),
+            Some("acc_1")
+        );
+        assert_eq!(
+            tx.provider.metadata_str("gocardless", "counterparty"),
+            Some("REWE Markt GmbH")
+        );
+    }
+
+    #[test]
+    fn test_transaction_mapping_fallbacks() {
+        let mut gc_tx = gc_tx("100.00");
+        gc_tx.remittance_information_unstructured = None;
+        gc_tx.remittance_information_unstructured_array =
+            vec!["Invoice".to_string(), "4711".to_string()];
+        assert_eq!(
+            map_transaction("acc_1", &gc_tx).description,
+            Some("Invoice 4711".to_string())
+        );
+
+        gc_tx.remittance_information_unstructured_array.clear();
+        gc_tx.creditor_name = None;
+        gc_tx.debtor_name = Some("Erika Mustermann".to_string());
+        gc_tx.value_date = Some("2025-01-20".to_string());
+        let tx = map_transaction("acc_1", &gc_tx);
+        // Falls back to the counterparty, and a later value date is ignored
+        assert_eq!(tx.description, Some("Erika Mustermann".to_string()));
+        assert_eq!(tx.transaction_date, tx.posted_date);
+    }
+
+    #[test]
+    fn test_transactions_without_ids_get_stable_ones() {
+        let mut gc_tx = gc_tx("-5.00");
+        gc_tx.transaction_id = None;
+        let txs = map_transactions("acc_1", &[gc_tx.clone(), gc_tx.clone()]);
+        let again = map_transactions("acc_1", &[gc_tx]);
+
+        let ids: Vec<_> = txs
+            .iter()
+            .map(|tx| tx.provider.external_id("gocardless").unwrap().to_string())
+            .collect();
+        assert!(ids[0].starts_with("gc-"));
+        // Identical transactions stay apart, and the first keeps its ID
+        assert_ne!(ids[0], ids[1]);
+        assert_eq!(
+            again[0].provider.external_id("gocardless"),
+            Some(ids[0].as_str())
+        );
+    }
+
+    #[test]
+    fn test_error_messages() {
+        let error = GoCardlessApiError {
+            status: 429,
+            summary: "Rate limit exceeded".to_string(),
+            detail: String::new(),
+            retry_after: Some(5000),
+        };
+        assert!(error.to_string().contains("try again in 2 hours"));
+
+        let error = GoCardlessApiError {
+            status: 400,
+            summary: "Invalid ID".to_string(),
+            detail: "Requisition not found".to_string(),
+            retry_after: None,
+        };
+        assert_eq!(
+            error.to_string(),
+            "GoCardless error 400: Invalid ID: Requisition not found"
+        );
+    }
+
+    #[test]
+    fn test_requisition_warnings() {
+        let bank = RequisitionSettings {
+            requisition_id: "req_1".to_string(),
+            institution_id: "N26_NTSBDEB1".to_string(),
+            institution_name: Some("N26".to_string()),
+            link: Some("https://ob.gocardless.com/psd2/start/req_1".to_string()),
+        };
+        let mut requisition = Requisition {
+            id: "req_1".to_string(),
+            status: "CR".to_string(),
+            link: String::new(),
+            accounts: Vec::new(),
+            agreement: None,
+        };
+        assert!(requisition_warning(&requisition, &bank).contains("finish connecting"));
+
+        requisition.status = "EX".to_string();
+        assert!(requisition_warning(&requisition, &bank)
+            .contains("tl setup gocardless --institution N26_NTSBDEB1"));
+    }
+
+    #[test]
+    fn test_settings_require_requisitions() {
+        assert!(requisitions(&json!({ "requisitions": [] })).is_err());
+        let settings = json!({
+            "requisitions": [{ "requisitionId": "req_1", "institutionId": "N26_NTSBDEB1" }]
+        });
+        let requisitions = requisitions(&settings).unwrap();
+        assert_eq!(requisitions[0].label(), "N26_NTSBDEB1");
+    }
+}
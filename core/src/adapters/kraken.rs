@@ -0,0 +1,612 @@
+//! Kraken API client
+//!
+//! Handles communication with the Kraken REST API for crypto balance sync.
+//! Each asset the user holds becomes an account valued in the user's currency
+//! at Kraken's last trade price, with the asset quantity and price in its
+//! provider metadata. Kraken's ledger has no fiat values, so only balances
+//! are synced. Access is read-only, with an API key and private key from the
+//! Kraken settings that sign every private request.
+//!
+//! API Documentation: https://docs.kraken.com/api/docs/rest-api/get-account-balance
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use chrono::{NaiveDate, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::blocking::Client;
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value as JsonValue};
+use sha2::{Digest, Sha256, Sha512};
+use uuid::Uuid;
+
+use crate::domain::result::{Error as DomainError, Result as DomainResult};
+use crate::domain::{Account, AccountType, BalanceSnapshot, ProviderData};
+use crate::ports::{
+    DataAggregationProvider, FetchAccountsResult, FetchTransactionsResult, IntegrationProvider,
+};
+
+// =============================================================================
+// API Response Models (matching Kraken API spec)
+// =============================================================================
+
+/// Every Kraken response: errors, or the result
+#[derive(Debug, Clone, Deserialize)]
+struct KrakenResponse<T> {
+    #[serde(default)]
+    error: Vec<String>,
+    #[serde(default)]
+    result: Option<T>,
+}
+
+/// A pair's ticker; `c` is the last trade as [price, volume]
+#[derive(Debug, Clone, Deserialize)]
+struct Ticker {
+    c: Vec<String>,
+}
+
+/// A balance Kraken reports, under its own asset code
+#[derive(Debug, Clone, PartialEq)]
+pub struct KrakenAsset {
+    /// The code as Kraken reports it, e.g. "XXBT" or "DOT.S"
+    pub code: String,
+    /// The code Kraken prices it under, e.g. "XBT"
+    pub base: String,
+    /// The symbol people know it by, e.g. "BTC"
+    pub symbol: String,
+    /// Held in Kraken's staking or rewards programs
+    pub earn: bool,
+}
+
+/// Result of syncing balances from Kraken
+#[derive(Debug, Default)]
+pub struct SyncedAccounts {
+    pub accounts: Vec<Account>,
+    pub balance_snapshots: Vec<BalanceSnapshot>,
+    /// Codes of assets with a zero balance
+    pub empty: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+// =============================================================================
+// Kraken HTTP Client
+// =============================================================================
+
+pub const KRAKEN_API_URL: &str = "https://api.kraken.com";
+
+/// Suffixes of balances held in Kraken's earn programs (staked, opt-in
+/// rewards, flexible, bonded, parachain)
+const EARN_SUFFIXES: &[&str] = &["S", "M", "F", "B", "P"];
+
+/// Assets listed before Kraken dropped the X (crypto) and Z (fiat) prefixes
+const LEGACY_ASSETS: &[&str] = &[
+    "XXBT", "XETH", "XLTC", "XXRP", "XXLM", "XXMR", "XETC", "XZEC", "XREP", "XMLN", "XXDG", "ZUSD",
+    "ZEUR", "ZGBP", "ZCAD", "ZJPY",
+];
+
+/// Fiat currencies Kraken holds
+const FIAT_ASSETS: &[&str] = &["USD", "EUR", "GBP", "CAD", "JPY", "CHF", "AUD"];
+
+/// Kraken API client
+#[derive(Debug)]
+pub struct KrakenClient {
+    client: Client,
+    api_key: String,
+    api_secret: String,
+    base_url: String,
+}
+
+impl KrakenClient {
+    /// Create a new Kraken client with an API key and its private key
+    pub fn new(api_key: &str, api_secret: &str, base_url: &str) -> Result<Self> {
+        if api_key.is_empty() || api_secret.is_empty() {
+            anyhow::bail!("Kraken API key and secret cannot be empty");
+        }
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(60))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self {
+            client,
+            api_key: api_key.to_string(),
+            api_secret: api_secret.to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Quantity of every asset in the account, keyed by Kraken's code
+    pub fn get_balances(&self) -> Result<BTreeMap<String, Decimal>> {
+        let balances: BTreeMap<String, String> = self.post_private("/0/private/Balance")?;
+        Ok(balances
+            .into_iter()
+            .map(|(code, quantity)| (code, quantity.parse().unwrap_or_default()))
+            .collect())
+    }
+
+    /// Last trade price of one unit of `base` in `currency`
+    pub fn get_price(&self, base: &str, currency: &str) -> Result<Decimal> {
+        let tickers: BTreeMap<String, Ticker> =
+            self.get_public(&format!("/0/public/Ticker?pair={}{}", base, currency))?;
+        // The result is keyed by Kraken's own pair name (XXBTZUSD for XBTUSD)
+        let last = tickers
+            .values()
+            .next()
+            .and_then(|ticker| ticker.c.first())
+            .ok_or_else(|| anyhow::anyhow!("Kraken has no {}{} price", base, currency))?;
+        last.parse()
+            .with_context(|| format!("Invalid Kraken {}{} price", base, currency))
+    }
+
+    /// Fetch balances valued in `currency`
+    ///
+    /// An asset without a price keeps an unknown balance and is reported in
+    /// the warnings.
+    pub fn get_accounts(&self, currency: &str) -> Result<SyncedAccounts> {
+        let balances = self.get_balances()?;
+
+        let mut synced = SyncedAccounts::default();
+        let mut prices: BTreeMap<String, Option<Decimal>> = BTreeMap::new();
+        for (code, quantity) in &balances {
+            let asset = normalize_asset(code);
+            let price = if quantity.is_zero() {
+                synced.empty.push(code.clone());
+                None
+            } else if asset.base == currency {
+                Some(Decimal::ONE)
+            } else {
+                // Staked and spot balances of an asset share its price
+                *prices.entry(asset.base.clone()).or_insert_with(|| {
+                    match self.get_price(&asset.base, currency) {
+                        Ok(price) => Some(price),
+                        Err(e) => {
+                            synced.warnings.push(format!("{}: {}", asset.symbol, e));
+                            None
+                        }
+                    }
+                })
+            };
+
+            let account = map_account(&asset, *quantity, currency, price);
+            if let Some(balance) = account.balance {
+                synced.balance_snapshots.push(BalanceSnapshot {
+                    id: Uuid::new_v4(),
+                    account_id: account.id,
+                    balance,
+                    snapshot_time: Utc::now().naive_utc(),
+                    source: Some("sync".to_string()),
+                    csv_batch_id: None,
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                });
+            }
+            synced.accounts.push(account);
+        }
+        Ok(synced)
+    }
+
+    /// POST to a private endpoint, signed with the API secret
+    fn post_private<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        // Kraken only needs the nonce to increase between requests
+        let nonce = Utc::now().timestamp_millis().to_string();
+        let body = format!("nonce={}", nonce);
+        let signature = sign(&self.api_secret, path, &nonce, &body)?;
+
+        let url = format!("{}{}", self.base_url, path);
+        let response = self
+            .client
+            .post(&url)
+            .header("API-Key", &self.api_key)
+            .header("API-Sign", signature)
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(body)
+            .send()
+            .map_err(|e| self.map_request_error(e))?;
+        self.parse(path, response)
+    }
+
+    /// GET a public endpoint
+    fn get_public<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let url = format!("{}{}", self.base_url, path);
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .map_err(|e| self.map_request_error(e))?;
+        self.parse(path, response)
+    }
+
+    /// The result of a response, or the errors Kraken reported instead
+    fn parse<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        response: reqwest::blocking::Response,
+    ) -> Result<T> {
+        let status = response.status();
+        if !status.is_success() {
+            anyhow::bail!("Kraken API error: HTTP {}", status.as_u16());
+        }
+
+        let body: KrakenResponse<T> = response
+            .json()
+            .with_context(|| format!("Failed to parse Kraken {} response", path))?;
+        if let Some(error) = body.error.first() {
+            anyhow::bail!("{}", error_message(error));
+        }
+        body.result
+            .ok_or_else(|| anyhow::anyhow!("Kraken {} response has no result", path))
+    }
+
+    /// Map request errors to user-friendly messages
+    fn map_request_error(&self, error: reqwest::Error) -> anyhow::Error {
+        if error.is_timeout() {
+            anyhow::anyhow!("Connection timed out after 60 seconds")
+        } else if error.is_connect() {
+            anyhow::anyhow!("Unable to connect to Kraken servers")
+        } else {
+            anyhow::anyhow!("Kraken request failed: {}", error)
+        }
+    }
+}
+
+/// API-Sign for a private request: base64 HMAC-SHA512, keyed with the decoded
+/// secret, of the path followed by the SHA-256 of nonce and body
+fn sign(secret: &str, path: &str, nonce: &str, body: &str) -> Result<String> {
+    let engine = base64::engine::general_purpose::STANDARD;
+    let key = engine
+        .decode(secret.trim())
+        .context("Kraken API secret is not valid base64")?;
+
+    let digest = Sha256::new()
+        .chain_update(nonce.as_bytes())
+        .chain_update(body.as_bytes())
+        .finalize();
+    let mut mac = Hmac::<Sha512>::new_from_slice(&key).expect("HMAC takes keys of any size");
+    mac.update(path.as_bytes());
+    mac.update(&digest);
+    Ok(engine.encode(mac.finalize().into_bytes()))
+}
+
+/// A readable message for a Kraken error like "EAPI:Invalid key"
+fn error_message(error: &str) -> String {
+    match error {
+        "EAPI:Invalid key" | "EAPI:Invalid signature" => {
+            "Kraken authentication failed. Check your API key and secret.".to_string()
+        }
+        "EGeneral:Permission denied" => {
+            "Kraken API key lacks the Query Funds permission".to_string()
+        }
+        _ => format!("Kraken error: {}", error),
+    }
+}
+
+/// Work out what asset a Kraken balance code stands for
+///
+/// Older assets carry an X or Z prefix (XXBT, ZUSD), balances in earn
+/// programs a suffix (DOT.S), and Kraken calls BTC XBT and DOGE XDG.
+pub fn normalize_asset(code: &str) -> KrakenAsset {
+    let (base, earn) = match code.split_once('.') {
+        Some((base, suffix)) if EARN_SUFFIXES.contains(&suffix) => (base, true),
+        _ => (code, false),
+    };
+    let base = match base {
+        legacy if LEGACY_ASSETS.contains(&legacy) => &legacy[1..],
+        // Staked ETH from before the merge, priced as ETH
+        "ETH2" => "ETH",
+        other => other,
+    };
+    let symbol = match base {
+        "XBT" => "BTC",
+        "XDG" => "DOGE",
+        other => other,
+    };
+    KrakenAsset {
+        code: code.to_string(),
+        base: base.to_string(),
+        symbol: symbol.to_string(),
+        earn,
+    }
+}
+
+/// Map a Kraken balance to domain Account, valued at `price` per unit
+fn map_account(
+    asset: &KrakenAsset,
+    quantity: Decimal,
+    currency: &str,
+    price: Option<Decimal>,
+) -> Account {
+    let account_type = if FIAT_ASSETS.contains(&asset.base.as_str()) {
+        AccountType::Cash
+    } else {
+        AccountType::Crypto
+    };
+    let name = if asset.earn {
+        format!("{} (Earn)", asset.symbol)
+    } else {
+        asset.symbol.clone()
+    };
+
+    let now = Utc::now();
+    Account {
+        id: Uuid::new_v4(),
+        name,
+        nickname: None,
+        currency: currency.to_string(),
+        account_type: Some(account_type.to_string()),
+        classification: Some(Account::compute_classification(Some(account_type.as_str()))),
+        // Nothing held is worth nothing, priced or not
+        balance: if quantity.is_zero() {
+            Some(Decimal::ZERO)
+        } else {
+            price.map(|p| (quantity * p).round_dp(2))
+        },
+        institution_name: Some("Kraken".to_string()),
+        institution_url: Some("https://www.kraken.com".to_string()),
+        institution_domain: Some("kraken.com".to_string()),
+        created_at: now,
+        updated_at: now,
+        // Manual flag
+        is_manual: false,
+        // Kraken: the holding behind the balance
+        provider: ProviderData::new(
+            "kraken",
+            &asset.code,
+            json!({
+                "asset": asset.symbol,
+                "quantity": quantity.normalize().to_string(),
+                "price": price.map(|p| p.normalize().to_string()),
+                "earn": asset.earn,
+            }),
+        ),
+    }
+}
+
+// =============================================================================
+// KrakenProvider - implements DataAggregationProvider trait
+// =============================================================================
+
+/// Client for the keys in settings or setup options
+fn client_from(settings: &JsonValue) -> DomainResult<KrakenClient> {
+    let field = |key: &str| settings.get(key).and_then(|v| v.as_str());
+    let (Some(api_key), Some(api_secret)) = (field("apiKey"), field("apiSecret")) else {
+        return Err(DomainError::Config(
+            "Kraken apiKey and apiSecret not found in settings".to_string(),
+        ));
+    };
+    // Custom base URL for testing with mock server
+    let base_url = field("baseUrl").unwrap_or(KRAKEN_API_URL);
+
+    KrakenClient::new(api_key, api_secret, base_url).map_err(|e| DomainError::Sync(e.to_string()))
+}
+
+/// The currency balances are valued in
+fn currency(settings: &JsonValue) -> String {
+    settings
+        .get("currency")
+        .and_then(|v| v.as_str())
+        .unwrap_or("USD")
+        .to_uppercase()
+}
+
+/// Kraken data provider
+///
+/// Implements DataAggregationProvider and IntegrationProvider traits
+/// for syncing crypto balances from Kraken.
+pub struct KrakenProvider;
+
+impl KrakenProvider {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for KrakenProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DataAggregationProvider for KrakenProvider {
+    fn name(&self) -> &str {
+        "kraken"
+    }
+
+    fn can_get_accounts(&self) -> bool {
+        true
+    }
+
+    fn can_get_transactions(&self) -> bool {
+        false
+    }
+
+    fn can_get_balances(&self) -> bool {
+        true
+    }
+
+    /// Every asset balance, valued in the settings' currency
+    fn get_accounts(&self, settings: &JsonValue) -> DomainResult<FetchAccountsResult> {
+        let client = client_from(settings)?;
+        let synced = client
+            .get_accounts(&currency(settings))
+            .map_err(|e| DomainError::Sync(e.to_string()))?;
+
+        Ok(FetchAccountsResult {
+            accounts: synced.accounts,
+            balance_snapshots: synced.balance_snapshots,
+            warnings: synced.warnings,
+            empty_accounts: synced.empty,
+            holdings: None,
+        })
+    }
+
+    /// Kraken syncs balances only
+    fn get_transactions(
+        &self,
+        _start_date: NaiveDate,
+        _end_date: NaiveDate,
+        _account_ids: &[String],
+        _settings: &JsonValue,
+    ) -> DomainResult<FetchTransactionsResult> {
+        Ok(FetchTransactionsResult::default())
+    }
+}
+
+impl IntegrationProvider for KrakenProvider {
+    /// Validate an API key and store it
+    ///
+    /// Takes `apiKey`, `apiSecret` (the private key), an optional `currency`
+    /// to value balances in (USD by default) and `baseUrl` for testing.
+    fn setup(&self, options: &JsonValue) -> DomainResult<JsonValue> {
+        let client = client_from(options)?;
+        let option = |key: &str| options.get(key).and_then(|v| v.as_str());
+
+        // Validate the key by fetching the balances
+        client
+            .get_balances()
+            .map_err(|e| DomainError::Sync(format!("Failed to validate Kraken API key: {}", e)))?;
+
+        let mut settings = json!({
+            "apiKey": option("apiKey"),
+            "apiSecret": option("apiSecret"),
+            "currency": currency(options),
+        });
+
+        // Include base URL if custom (for testing)
+        if let Some(url) = option("baseUrl") {
+            settings["baseUrl"] = json!(url);
+        }
+
+        Ok(settings)
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_name() {
+        let provider = KrakenProvider::new();
+        assert_eq!(provider.name(), "kraken");
+        assert!(!provider.can_get_transactions());
+    }
+
+    #[test]
+    fn test_reject_empty_keys() {
+        let result = KrakenClient::new("", "secret", "http://localhost");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("cannot be empty"));
+    }
+
+    #[test]
+    fn test_sign() {
+        // The example from Kraken's authentication docs
+        let signature = sign(
+            "kQH5HW/8p1uGOVjbgWA7FunAmGO8lsSUXNsu3eow76sz84Q18fWxnyRzBHCd3pd5nE9qa99HAZtuZuj6F1huXg==",
+            "/0/private/AddOrder",
+            "1616492376594",
+            "nonce=1616492376594&ordertype=limit&pair=XBTUSD&price=37500&type=buy&volume=1.25",
+        )
+        .unwrap();
+        assert_eq!(
+            signature,
+            "4/dpxb3iT4tp/ZCVEwSnEsLxx0bqyhLpdfOpc6fn7OR8+UClSV5n9E6aSS8MPtnRfp32bAb0nmbRn6H8ndwLUQ=="
+        );
+
+        assert!(sign("not base64!", "/0/private/Balance", "1", "nonce=1").is_err());
+    }
+
+    #[test]
+    fn test_normalize_asset() {
+        let btc = normalize_asset("XXBT");
+        assert_eq!(btc.base, "XBT");
+        assert_eq!(btc.symbol, "BTC");
+        assert!(!btc.earn);
+
+        let usd = normalize_asset("ZUSD");
+        assert_eq!(usd.base, "USD");
+
+        let staked = normalize_asset("DOT.S");
+        assert_eq!(staked.code, "DOT.S");
+        assert_eq!(staked.base, "DOT");
+        assert!(staked.earn);
+
+        let eth2 = normalize_asset("ETH2.S");
+        assert_eq!(eth2.symbol, "ETH");
+        assert!(eth2.earn);
+
+        // Newer assets have no prefix to strip
+        assert_eq!(normalize_asset("XTZ").base, "XTZ");
+        assert_eq!(normalize_asset("XDG").symbol, "DOGE");
+    }
+
+    #[test]
+    fn test_account_mapping() {
+        let account = map_account(
+            &normalize_asset("XXBT"),
+            Decimal::new(5, 1),
+            "USD",
+            Some(Decimal::new(60000, 0)),
+        );
+
+        assert_eq!(account.name, "BTC");
+        assert_eq!(account.account_type, Some("crypto".to_string()));
+        assert_eq!(account.currency, "USD");
+        assert_eq!(account.balance, Some(Decimal::new(30000, 0)));
+        assert_eq!(account.institution_name, Some("Kraken".to_string()));
+        assert_eq!(account.provider.external_id("kraken"), Some("XXBT"));
+        assert_eq!(
+            account.provider.metadata_str("kraken", "asset"),
+            Some("BTC")
+        );
+        assert_eq!(
+            account.provider.metadata_str("kraken", "quantity"),
+            Some("0.5")
+        );
+
+        let staked = map_account(&normalize_asset("DOT.S"), Decimal::TEN, "USD", None);
+        assert_eq!(staked.name, "DOT (Earn)");
+        assert_eq!(staked.balance, None);
+
+        let cash = map_account(
+            &normalize_asset("ZEUR"),
+            Decimal::TEN,
+            "EUR",
+            Some(Decimal::ONE),
+        );
+        assert_eq!(cash.account_type, Some("cash".to_string()));
+        assert_eq!(cash.balance, Some(Decimal::TEN));
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let body: KrakenResponse<BTreeMap<String, Ticker>> = serde_json::from_value(json!({
+            "error": [],
+            "result": { "XXBTZUSD": { "a": ["60001.0", "1", "1.000"], "c": ["60000.5", "0.01"] } }
+        }))
+        .unwrap();
+        let tickers = body.result.unwrap();
+        assert_eq!(tickers["XXBTZUSD"].c[0], "60000.5");
+
+        let body: KrakenResponse<BTreeMap<String, String>> =
+            serde_json::from_value(json!({ "error": ["EAPI:Invalid key"] })).unwrap();
+        assert!(body.result.is_none());
+        assert!(error_message(&body.error[0]).contains("authentication failed"));
+    }
+
+    #[test]
+    fn test_provider_setup_requires_keys() {
+        let provider = KrakenProvider::new();
+        let result = provider.setup(&json!({ "apiKey": "key" }));
+        assert!(result.unwrap_err().to_string().contains("apiSecret"));
+    }
+}
@@ -12,11 +12,12 @@ use chrono::{NaiveDate, Utc};
 use reqwest::blocking::Client;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use serde_json::Value as JsonValue;
+use serde_json::{json, Value as JsonValue};
 use uuid::Uuid;
 
+use super::retry::RetryPolicy;
 use crate::domain::result::{Error as DomainError, Result as DomainResult};
-use crate::domain::{Account, BalanceSnapshot, Transaction};
+use crate::domain::{Account, AccountType, BalanceSnapshot, ProviderData, Transaction};
 use crate::ports::{
     DataAggregationProvider, FetchAccountsResult, FetchTransactionsResult, IntegrationProvider,
 };
@@ -159,6 +160,9 @@ pub struct SyncedTransactions {
     /// Tuples of (lunchflow_account_id, transaction)
     pub transactions: Vec<(String, Transaction)>,
     pub warnings: Vec<String>,
+    /// Tuples of (lunchflow_account_id, error) for accounts whose
+    /// transactions couldn't be fetched
+    pub failed_accounts: Vec<(String, String)>,
 }
 
 // =============================================================================
@@ -183,6 +187,7 @@ pub struct LunchflowClient {
     client: Client,
     api_key: String,
     base_url: String,
+    retry: RetryPolicy,
 }
 
 impl LunchflowClient {
@@ -211,18 +216,23 @@ impl LunchflowClient {
             client,
             api_key: api_key.to_string(),
             base_url: base_url.trim_end_matches('/').to_string(),
+            retry: RetryPolicy::default(),
         })
     }
 
+    /// Retry rate-limited and failed requests as `retry` says
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
     /// Fetch all accounts from Lunchflow
     pub fn get_accounts(&self) -> Result<SyncedAccounts> {
         let url = format!("{}/accounts", self.base_url);
 
         let response = self
-            .client
-            .get(&url)
-            .header("x-api-key", &self.api_key)
-            .send()
+            .retry
+            .send(|| self.client.get(&url).header("x-api-key", &self.api_key))
             .map_err(|e| self.map_request_error(e))?;
 
         self.check_response_status(&response)?;
@@ -259,6 +269,7 @@ impl LunchflowClient {
                         balance,
                         snapshot_time: Utc::now().naive_utc(),
                         source: Some("sync".to_string()),
+                        csv_batch_id: None,
                         created_at: Utc::now(),
                         updated_at: Utc::now(),
                     });
@@ -266,13 +277,15 @@ impl LunchflowClient {
                     let mut account = account;
                     account.balance = Some(balance);
                     if !currency.is_empty() {
-                        // Update both core currency and lf_currency from balance response
+                        // Update both core currency and Lunchflow's currency from balance response
                         if account.currency == "USD" {
                             account.currency = currency.clone();
                         }
-                        // Always set lf_currency if not already set
-                        if account.lf_currency.is_none() {
-                            account.lf_currency = Some(currency);
+                        // Always set Lunchflow's currency if not already set
+                        if account.provider.metadata("lunchflow", "currency").is_none() {
+                            account
+                                .provider
+                                .set_metadata("lunchflow", "currency", currency);
                         }
                     }
                     domain_accounts.push(account);
@@ -299,10 +312,8 @@ impl LunchflowClient {
         let url = format!("{}/accounts/{}/balance", self.base_url, account_id);
 
         let response = self
-            .client
-            .get(&url)
-            .header("x-api-key", &self.api_key)
-            .send()
+            .retry
+            .send(|| self.client.get(&url).header("x-api-key", &self.api_key))
             .map_err(|e| self.map_request_error(e))?;
 
         self.check_response_status(&response)?;
@@ -328,7 +339,7 @@ impl LunchflowClient {
         account_ids: Option<&[String]>,
     ) -> Result<SyncedTransactions> {
         let mut all_transactions = Vec::new();
-        let mut warnings = Vec::new();
+        let mut failed_accounts = Vec::new();
 
         // If no account IDs specified, we need to fetch accounts first
         let ids_to_fetch: Vec<String> = match account_ids {
@@ -339,7 +350,7 @@ impl LunchflowClient {
                 accounts
                     .accounts
                     .iter()
-                    .filter_map(|a| a.lf_id.clone())
+                    .filter_map(|a| a.provider.external_id("lunchflow").map(String::from))
                     .collect()
             }
         };
@@ -352,18 +363,15 @@ impl LunchflowClient {
                         all_transactions.push((account_id.clone(), tx));
                     }
                 }
-                Err(e) => {
-                    warnings.push(format!(
-                        "Failed to fetch transactions for account {}: {}",
-                        account_id, e
-                    ));
-                }
+                // The other accounts' transactions still count
+                Err(e) => failed_accounts.push((account_id, e.to_string())),
             }
         }
 
         Ok(SyncedTransactions {
             transactions: all_transactions,
-            warnings,
+            warnings: Vec::new(),
+            failed_accounts,
         })
     }
 
@@ -379,10 +387,8 @@ impl LunchflowClient {
         );
 
         let response = self
-            .client
-            .get(&url)
-            .header("x-api-key", &self.api_key)
-            .send()
+            .retry
+            .send(|| self.client.get(&url).header("x-api-key", &self.api_key))
             .map_err(|e| self.map_request_error(e))?;
 
         self.check_response_status(&response)?;
@@ -397,10 +403,12 @@ impl LunchflowClient {
 
     /// Map Lunchflow account to domain Account
     fn map_account(&self, lf_account: &LunchflowAccount) -> Account {
-        // Compute classification based on account_type
-        // Lunchflow doesn't provide account type, so default to asset
-        // Users can override in the UI after sync
-        let classification = Some(Account::compute_classification(None));
+        // Lunchflow doesn't provide an account type, so guess it from the name
+        // Users can override it in the UI or with `tl accounts set-type`
+        let account_type = AccountType::infer(&lf_account.name, Some(&lf_account.institution_name));
+        let classification = Some(Account::compute_classification(
+            account_type.map(|t| t.as_str()),
+        ));
 
         let now = Utc::now();
         Account {
@@ -411,7 +419,7 @@ impl LunchflowClient {
                 .currency
                 .clone()
                 .unwrap_or_else(|| "USD".to_string()),
-            account_type: None, // Lunchflow doesn't provide account type
+            account_type: account_type.map(|t| t.to_string()),
             classification,
             balance: None, // Will be set after fetching balance
             institution_name: Some(lf_account.institution_name.clone()),
@@ -421,25 +429,19 @@ impl LunchflowClient {
             updated_at: now,
             // Manual flag
             is_manual: false,
-            // SimpleFIN fields (not applicable)
-            sf_id: None,
-            sf_name: None,
-            sf_currency: None,
-            sf_balance: None,
-            sf_available_balance: None,
-            sf_balance_date: None,
-            sf_org_name: None,
-            sf_org_url: None,
-            sf_org_domain: None,
-            sf_extra: None,
             // Lunchflow: Store ALL raw fields from API
-            lf_id: Some(lf_account.id.clone()),
-            lf_name: Some(lf_account.name.clone()),
-            lf_institution_name: Some(lf_account.institution_name.clone()),
-            lf_institution_logo: lf_account.institution_logo.clone(),
-            lf_provider: lf_account.provider.clone(),
-            lf_currency: lf_account.currency.clone(),
-            lf_status: lf_account.status.clone(),
+            provider: ProviderData::new(
+                "lunchflow",
+                &lf_account.id,
+                json!({
+                    "name": lf_account.name,
+                    "institution_name": lf_account.institution_name,
+                    "institution_logo": lf_account.institution_logo,
+                    "provider": lf_account.provider,
+                    "currency": lf_account.currency,
+                    "status": lf_account.status,
+                }),
+            ),
         }
     }
 
@@ -448,8 +450,8 @@ impl LunchflowClient {
         let posted_date = NaiveDate::parse_from_str(&lf_tx.date, "%Y-%m-%d")
             .unwrap_or_else(|_| Utc::now().naive_utc().date());
 
-        // Map lf_description to core description (full transaction description)
-        // Falls back to lf_merchant if description is missing or empty
+        // Map Lunchflow's description to core description (full transaction description)
+        // Falls back to the merchant if description is missing or empty
         // Both raw fields are stored for power users
         let description = lf_tx
             .description
@@ -469,7 +471,7 @@ impl LunchflowClient {
             id: Uuid::new_v4(),
             account_id: Uuid::nil(), // Will be set by sync service after mapping
             amount: lf_tx.amount,
-            description, // Core field mapped from lf_description
+            description, // Core field mapped from Lunchflow's description
             transaction_date: posted_date,
             posted_date,
             tags: vec![],
@@ -480,27 +482,33 @@ impl LunchflowClient {
             // CSV Import tracking (not applicable)
             csv_fingerprint: None,
             csv_batch_id: None,
+            // Reconciliation identifiers (CSV import only)
+            check_number: None,
+            reference: None,
+            // Currency (CSV import only; the account's currency applies)
+            currency: None,
             // Manual flag
             is_manual: false,
             // Auto-tag tracking (starts false, set true when rules apply)
             tags_auto_applied: false,
-            // SimpleFIN fields (not applicable)
-            sf_id: None,
-            sf_posted: None,
-            sf_amount: None,
-            sf_description: None,
-            sf_transacted_at: None,
-            sf_pending: None,
-            sf_extra: None,
+            // Report exclusion (set by the user or a rule)
+            excluded_from_reports: false,
+            // Pending until the bank posts it
+            pending: lf_tx.is_pending,
             // Lunchflow: Store ALL raw fields from API
-            lf_id: Some(lf_tx.id.clone()),
-            lf_account_id: lf_tx.account_id.clone(),
-            lf_amount: Some(lf_tx.amount),
-            lf_currency: Some(lf_tx.currency.clone()),
-            lf_date: Some(posted_date),
-            lf_merchant: lf_tx.merchant.clone(),
-            lf_description: lf_tx.description.clone(),
-            lf_is_pending: Some(lf_tx.is_pending),
+            provider: ProviderData::new(
+                "lunchflow",
+                &lf_tx.id,
+                json!({
+                    "account_id": lf_tx.account_id,
+                    "amount": lf_tx.amount,
+                    "currency": lf_tx.currency,
+                    "date": posted_date.to_string(),
+                    "merchant": lf_tx.merchant,
+                    "description": lf_tx.description,
+                    "is_pending": lf_tx.is_pending,
+                }),
+            ),
         }
     }
 
@@ -602,6 +610,8 @@ impl DataAggregationProvider for LunchflowProvider {
             accounts: synced.accounts,
             balance_snapshots: synced.balance_snapshots,
             warnings: synced.warnings,
+            empty_accounts: Vec::new(),
+            holdings: None,
         })
     }
 
@@ -642,6 +652,8 @@ impl DataAggregationProvider for LunchflowProvider {
         Ok(FetchTransactionsResult {
             transactions: synced.transactions,
             warnings: synced.warnings,
+            failed_accounts: synced.failed_accounts,
+            ..Default::default()
         })
     }
 }
@@ -731,8 +743,11 @@ mod tests {
         assert_eq!(account.name, "Test Account");
         assert_eq!(account.currency, "EUR");
         assert_eq!(account.institution_name, Some("Test Bank".to_string()));
-        assert_eq!(account.lf_id, Some("123".to_string()));
-        assert_eq!(account.lf_currency, Some("EUR".to_string()));
+        assert_eq!(account.provider.external_id("lunchflow"), Some("123"));
+        assert_eq!(
+            account.provider.metadata_str("lunchflow", "currency"),
+            Some("EUR")
+        );
     }
 
     #[test]
@@ -751,13 +766,22 @@ mod tests {
         let client = LunchflowClient::new_with_base_url("test_key", "http://localhost").unwrap();
         let tx = client.map_transaction(&lf_tx);
 
-        // Core description = lf_description (full description), merchant stored separately
+        // Core description = Lunchflow's description (full description), merchant stored separately
         assert_eq!(tx.description, Some("Card payment".to_string()));
-        assert_eq!(tx.lf_merchant, Some("Coffee Shop".to_string()));
-        assert_eq!(tx.lf_description, Some("Card payment".to_string()));
+        assert_eq!(
+            tx.provider.metadata_str("lunchflow", "merchant"),
+            Some("Coffee Shop")
+        );
+        assert_eq!(
+            tx.provider.metadata_str("lunchflow", "description"),
+            Some("Card payment")
+        );
         assert_eq!(tx.amount, Decimal::new(-4250, 2));
-        assert_eq!(tx.lf_id, Some("tx_456".to_string()));
-        assert_eq!(tx.lf_currency, Some("EUR".to_string()));
+        assert_eq!(tx.provider.external_id("lunchflow"), Some("tx_456"));
+        assert_eq!(
+            tx.provider.metadata_str("lunchflow", "currency"),
+            Some("EUR")
+        );
     }
 
     #[test]
@@ -776,10 +800,13 @@ mod tests {
         let client = LunchflowClient::new_with_base_url("test_key", "http://localhost").unwrap();
         let tx = client.map_transaction(&lf_tx);
 
-        // Falls back to lf_merchant when no description
+        // Falls back to the merchant when no description
         assert_eq!(tx.description, Some("Employer Inc".to_string()));
-        assert_eq!(tx.lf_merchant, Some("Employer Inc".to_string()));
-        assert_eq!(tx.lf_description, None);
+        assert_eq!(
+            tx.provider.metadata_str("lunchflow", "merchant"),
+            Some("Employer Inc")
+        );
+        assert_eq!(tx.provider.metadata_str("lunchflow", "description"), None);
     }
 
     #[test]
@@ -798,10 +825,16 @@ mod tests {
         let client = LunchflowClient::new_with_base_url("test_key", "http://localhost").unwrap();
         let tx = client.map_transaction(&lf_tx);
 
-        // Falls back to lf_merchant when description is empty
+        // Falls back to the merchant when description is empty
         assert_eq!(tx.description, Some("Grocery Store".to_string()));
-        assert_eq!(tx.lf_merchant, Some("Grocery Store".to_string()));
-        assert_eq!(tx.lf_description, Some("".to_string()));
+        assert_eq!(
+            tx.provider.metadata_str("lunchflow", "merchant"),
+            Some("Grocery Store")
+        );
+        assert_eq!(
+            tx.provider.metadata_str("lunchflow", "description"),
+            Some("")
+        );
     }
 
     #[test]
@@ -820,9 +853,12 @@ mod tests {
         let client = LunchflowClient::new_with_base_url("test_key", "http://localhost").unwrap();
         let tx = client.map_transaction(&lf_tx);
 
-        // Falls back to lf_merchant when description is whitespace-only
+        // Falls back to the merchant when description is whitespace-only
         assert_eq!(tx.description, Some("Gas Station".to_string()));
-        assert_eq!(tx.lf_merchant, Some("Gas Station".to_string()));
+        assert_eq!(
+            tx.provider.metadata_str("lunchflow", "merchant"),
+            Some("Gas Station")
+        );
     }
 
     #[test]
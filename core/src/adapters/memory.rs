@@ -0,0 +1,143 @@
+//! In-memory repository
+//!
+//! Implements the Repository port with plain vectors behind a mutex, so
+//! service unit tests run without a database file. Nothing is persisted and
+//! no change log is written; ordering and filtering mirror the DuckDB adapter.
+
+use std::sync::Mutex;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+
+use crate::domain::{Account, BalanceSnapshot, Transaction};
+use crate::ports::Repository;
+
+#[derive(Default)]
+struct State {
+    accounts: Vec<Account>,
+    transactions: Vec<Transaction>,
+    snapshots: Vec<BalanceSnapshot>,
+}
+
+/// Repository that keeps everything in memory
+#[derive(Default)]
+pub struct InMemoryRepository {
+    state: Mutex<State>,
+}
+
+impl InMemoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl State {
+    /// The account with its latest snapshot balance, as the DuckDB adapter reads it
+    fn with_latest_balance(&self, account: &Account) -> Account {
+        let mut account = account.clone();
+        account.balance = self
+            .snapshots
+            .iter()
+            .filter(|s| s.account_id == account.id)
+            .max_by_key(|s| s.snapshot_time)
+            .map(|s| s.balance);
+        account
+    }
+}
+
+impl Repository for InMemoryRepository {
+    fn get_accounts(&self) -> Result<Vec<Account>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .accounts
+            .iter()
+            .map(|a| state.with_latest_balance(a))
+            .collect())
+    }
+
+    fn get_account_by_id(&self, id: &str) -> Result<Option<Account>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .accounts
+            .iter()
+            .find(|a| a.id.to_string() == id)
+            .map(|a| state.with_latest_balance(a)))
+    }
+
+    fn upsert_account(&self, account: &Account) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        match state.accounts.iter_mut().find(|a| a.id == account.id) {
+            Some(existing) => *existing = account.clone(),
+            None => state.accounts.push(account.clone()),
+        }
+        Ok(())
+    }
+
+    fn get_transactions_by_account(&self, account_id: &str) -> Result<Vec<Transaction>> {
+        let state = self.state.lock().unwrap();
+        let mut transactions: Vec<Transaction> = state
+            .transactions
+            .iter()
+            .filter(|tx| tx.account_id.to_string() == account_id && tx.deleted_at.is_none())
+            .cloned()
+            .collect();
+        transactions.sort_by(|a, b| b.transaction_date.cmp(&a.transaction_date));
+        Ok(transactions)
+    }
+
+    fn get_transaction_count(&self) -> Result<i64> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .transactions
+            .iter()
+            .filter(|tx| tx.deleted_at.is_none())
+            .count() as i64)
+    }
+
+    fn upsert_transaction(&self, tx: &Transaction) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        match state.transactions.iter_mut().find(|t| t.id == tx.id) {
+            Some(existing) => *existing = tx.clone(),
+            None => state.transactions.push(tx.clone()),
+        }
+        Ok(())
+    }
+
+    fn get_balance_snapshots(&self, account_id: Option<&str>) -> Result<Vec<BalanceSnapshot>> {
+        let state = self.state.lock().unwrap();
+        let mut snapshots: Vec<BalanceSnapshot> = state
+            .snapshots
+            .iter()
+            .filter(|s| account_id.map_or(true, |id| s.account_id.to_string() == id))
+            .cloned()
+            .collect();
+        snapshots.sort_by(|a, b| b.snapshot_time.cmp(&a.snapshot_time));
+        Ok(snapshots)
+    }
+
+    fn add_balance_snapshot(&self, snapshot: &BalanceSnapshot) -> Result<()> {
+        self.state.lock().unwrap().snapshots.push(snapshot.clone());
+        Ok(())
+    }
+
+    fn bulk_insert_balance_snapshots(&self, snapshots: &[BalanceSnapshot]) -> Result<usize> {
+        let mut state = self.state.lock().unwrap();
+        state.snapshots.extend_from_slice(snapshots);
+        Ok(snapshots.len())
+    }
+
+    fn delete_balance_snapshots_in_range(
+        &self,
+        account_id: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<usize> {
+        let mut state = self.state.lock().unwrap();
+        let before = state.snapshots.len();
+        state.snapshots.retain(|s| {
+            let date = s.snapshot_time.date();
+            s.account_id.to_string() != account_id || date < start_date || date > end_date
+        });
+        Ok(before - state.snapshots.len())
+    }
+}
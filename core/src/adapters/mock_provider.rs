@@ -0,0 +1,1379 @@
+//! Mock SimpleFIN, Lunchflow, Plaid and GoCardless server for testing sync
+//!
+//! Serves a fixture of accounts and transactions over plain HTTP on a
+//! loopback port, speaking just enough of each provider's API for the real
+//! clients to sync from it. Faults can be injected - latency, and failing
+//! requests by position - so sync, error handling and dedup can be exercised
+//! deterministically without credentials or network access.
+//!
+//! Used by tests and `tl dev mock-provider`.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use chrono::{NaiveDate, NaiveTime};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+
+/// Credentials in the SimpleFIN access URL the mock hands out
+const SIMPLEFIN_USER: &str = "mock";
+const SIMPLEFIN_PASSWORD: &str = "mock";
+
+/// Access token and item the mock's Plaid token exchange hands out
+const PLAID_ACCESS_TOKEN: &str = "access-mock";
+const PLAID_ITEM_ID: &str = "mock-item";
+
+/// Access token and bank the mock's GoCardless API knows
+const GOCARDLESS_ACCESS_TOKEN: &str = "gc-access";
+const GOCARDLESS_INSTITUTION_ID: &str = "MOCKBANK_MOCKGB21";
+
+/// Accounts and transactions the mock serves, and the faults to inject
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MockFixture {
+    pub accounts: Vec<MockAccount>,
+    pub faults: MockFaults,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockAccount {
+    /// Provider-side account ID
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub institution: Option<String>,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    pub balance: Decimal,
+    #[serde(default)]
+    pub transactions: Vec<MockTransaction>,
+}
+
+fn default_currency() -> String {
+    "USD".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockTransaction {
+    /// Provider-side transaction ID (what sync dedups on)
+    pub id: String,
+    pub date: NaiveDate,
+    pub amount: Decimal,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub pending: bool,
+}
+
+/// Faults applied to API requests (the SimpleFIN token claim always succeeds)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MockFaults {
+    /// Delay before every response
+    pub latency_ms: u64,
+    /// Fail the first N requests
+    pub fail_first: u32,
+    /// Fail every Nth request (0 = never)
+    pub fail_every: u32,
+    /// HTTP status of failed requests
+    pub error_status: u16,
+    /// Retry-After seconds sent with failed requests
+    pub retry_after: Option<u64>,
+    /// Always fail requests whose path starts with one of these, e.g.
+    /// "/lunchflow/accounts/mock-card/transactions"
+    pub fail_paths: Vec<String>,
+}
+
+impl Default for MockFaults {
+    fn default() -> Self {
+        Self {
+            latency_ms: 0,
+            fail_first: 0,
+            fail_every: 0,
+            error_status: 500,
+            retry_after: None,
+            fail_paths: Vec::new(),
+        }
+    }
+}
+
+impl MockFixture {
+    /// A checking account and a credit card with a month of transactions up
+    /// to `today`, the last one pending
+    pub fn sample(today: NaiveDate) -> Self {
+        let tx = |id: &str, days_ago: i64, amount: i64, description: &str| MockTransaction {
+            id: id.to_string(),
+            date: today - chrono::Duration::days(days_ago),
+            amount: Decimal::new(amount, 2),
+            description: Some(description.to_string()),
+            pending: false,
+        };
+
+        Self {
+            accounts: vec![
+                MockAccount {
+                    id: "mock-checking".to_string(),
+                    name: "Mock Checking".to_string(),
+                    institution: Some("Mock Bank".to_string()),
+                    currency: default_currency(),
+                    balance: Decimal::new(254_310, 2),
+                    transactions: vec![
+                        tx("chk-1", 28, 250_000, "PAYROLL ACME CORP"),
+                        tx("chk-2", 21, -142_000, "RENT PAYMENT"),
+                        tx("chk-3", 14, -8_423, "GROCERY MART"),
+                        tx("chk-4", 7, -4_599, "ELECTRIC COMPANY"),
+                        MockTransaction {
+                            pending: true,
+                            ..tx("chk-5", 1, -1_250, "COFFEE SHOP")
+                        },
+                    ],
+                },
+                MockAccount {
+                    id: "mock-card".to_string(),
+                    name: "Mock Credit Card".to_string(),
+                    institution: Some("Mock Bank".to_string()),
+                    currency: default_currency(),
+                    balance: Decimal::new(-61_877, 2),
+                    transactions: vec![
+                        tx("card-1", 20, -3_499, "STREAMING SERVICE"),
+                        tx("card-2", 9, -12_650, "HARDWARE STORE"),
+                        tx("card-3", 3, 20_000, "PAYMENT THANK YOU"),
+                    ],
+                },
+            ],
+            faults: MockFaults::default(),
+        }
+    }
+}
+
+/// A running mock provider; stops when dropped
+pub struct MockProviderServer {
+    state: Arc<ServerState>,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+struct ServerState {
+    addr: SocketAddr,
+    fixture: MockFixture,
+    /// Every request served, as "METHOD /path?query"
+    requests: Mutex<Vec<String>>,
+    /// API requests so far, for placing injected failures
+    api_requests: AtomicU32,
+    /// GoCardless requisitions created so far
+    gocardless_requisitions: AtomicU32,
+}
+
+impl MockProviderServer {
+    /// Serve `fixture` on 127.0.0.1 at `port` (0 picks a free port)
+    pub fn start(port: u16, fixture: MockFixture) -> Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .with_context(|| format!("Failed to listen on 127.0.0.1:{}", port))?;
+        let state = Arc::new(ServerState {
+            addr: listener.local_addr()?,
+            fixture,
+            requests: Mutex::new(Vec::new()),
+            api_requests: AtomicU32::new(0),
+            gocardless_requisitions: AtomicU32::new(0),
+        });
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let handle = {
+            let state = Arc::clone(&state);
+            let shutdown = Arc::clone(&shutdown);
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let Ok(stream) = stream else { continue };
+                    // One thread per connection, so latency doesn't serialize requests
+                    let state = Arc::clone(&state);
+                    thread::spawn(move || {
+                        let _ = handle_connection(stream, &state);
+                    });
+                }
+            })
+        };
+
+        Ok(Self {
+            state,
+            shutdown,
+            handle: Some(handle),
+        })
+    }
+
+    /// Base URL of the server, e.g. `http://127.0.0.1:8787`
+    pub fn url(&self) -> String {
+        format!("http://{}", self.state.addr)
+    }
+
+    /// SimpleFIN access URL, as stored after setup
+    pub fn simplefin_access_url(&self) -> String {
+        self.state.simplefin_access_url()
+    }
+
+    /// SimpleFIN setup token for `tl setup simplefin`, claimed from this server
+    pub fn simplefin_setup_token(&self) -> String {
+        base64::engine::general_purpose::STANDARD.encode(format!("{}/simplefin/claim", self.url()))
+    }
+
+    /// Lunchflow base URL for `tl setup lunchflow --base-url`
+    pub fn lunchflow_base_url(&self) -> String {
+        format!("{}/lunchflow", self.url())
+    }
+
+    /// Plaid base URL for `tl setup plaid --base-url`
+    pub fn plaid_base_url(&self) -> String {
+        format!("{}/plaid", self.url())
+    }
+
+    /// GoCardless base URL for `tl setup gocardless --base-url`
+    pub fn gocardless_base_url(&self) -> String {
+        format!("{}/gocardless", self.url())
+    }
+
+    /// Requests served so far, as "METHOD /path?query"
+    pub fn requests(&self) -> Vec<String> {
+        self.state.requests.lock().unwrap().clone()
+    }
+
+    /// Serve until the process is stopped
+    pub fn wait(mut self) {
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for MockProviderServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake the accept loop so it sees the flag
+        let _ = TcpStream::connect(self.state.addr);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+struct Reply {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+    retry_after: Option<u64>,
+}
+
+impl Reply {
+    fn json(status: u16, body: JsonValue) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: body.to_string(),
+            retry_after: None,
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::json(status, json!({ "error": message }))
+    }
+}
+
+fn handle_connection(stream: TcpStream, state: &ServerState) -> Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length = headers
+        .get("content-length")
+        .and_then(|len| len.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or_default();
+    let body = serde_json::from_slice(&body).unwrap_or(JsonValue::Null);
+    let reply = state.respond(method, target, &headers, &body);
+
+    let reason = match reply.status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        402 => "Payment Required",
+        403 => "Forbidden",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        503 => "Service Unavailable",
+        _ => "Error",
+    };
+    let retry_after = reply
+        .retry_after
+        .map(|seconds| format!("Retry-After: {}\r\n", seconds))
+        .unwrap_or_default();
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+        reply.status,
+        reason,
+        reply.content_type,
+        reply.body.len(),
+        retry_after,
+        reply.body
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+impl ServerState {
+    fn simplefin_access_url(&self) -> String {
+        format!(
+            "http://{}:{}@{}/simplefin",
+            SIMPLEFIN_USER, SIMPLEFIN_PASSWORD, self.addr
+        )
+    }
+
+    fn respond(
+        &self,
+        method: &str,
+        target: &str,
+        headers: &HashMap<String, String>,
+        body: &JsonValue,
+    ) -> Reply {
+        self.requests
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", method, target));
+
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let query: Vec<(&str, &str)> = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .collect();
+
+        if method == "POST" && path == "/simplefin/claim" {
+            return Reply {
+                status: 200,
+                content_type: "text/plain",
+                body: self.simplefin_access_url(),
+                retry_after: None,
+            };
+        }
+
+        let faults = &self.fixture.faults;
+        if faults.latency_ms > 0 {
+            thread::sleep(Duration::from_millis(faults.latency_ms));
+        }
+        let n = self.api_requests.fetch_add(1, Ordering::SeqCst) + 1;
+        let failing_path = faults
+            .fail_paths
+            .iter()
+            .any(|p| path.starts_with(p.as_str()));
+        if n <= faults.fail_first
+            || (faults.fail_every > 0 && n % faults.fail_every == 0)
+            || failing_path
+        {
+            return Reply {
+                retry_after: faults.retry_after,
+                ..Reply::error(faults.error_status, "Injected failure")
+            };
+        }
+
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match (method, segments.as_slice()) {
+            ("GET", ["simplefin", "accounts"]) => self.simplefin_accounts(headers, &query),
+            ("GET", ["lunchflow", rest @ ..]) => {
+                if headers.get("x-api-key").map_or(true, |key| key.is_empty()) {
+                    return Reply::error(401, "Missing API key");
+                }
+                match rest {
+                    ["accounts"] => self.lunchflow_accounts(),
+                    ["accounts", id, "balance"] => self.lunchflow_balance(id),
+                    ["accounts", id, "transactions"] => self.lunchflow_transactions(id, &query),
+                    _ => Reply::error(404, "Not found"),
+                }
+            }
+            ("POST", ["plaid", rest @ ..]) => self.plaid(rest, body),
+            (_, ["gocardless", rest @ ..]) => self.gocardless(method, rest, headers, &query, body),
+            _ => Reply::error(404, "Not found"),
+        }
+    }
+
+    /// `GET /simplefin/accounts?start-date=&end-date=&account=`
+    fn simplefin_accounts(
+        &self,
+        headers: &HashMap<String, String>,
+        query: &[(&str, &str)],
+    ) -> Reply {
+        let expected = format!(
+            "Basic {}",
+            base64::engine::general_purpose::STANDARD
+                .encode(format!("{}:{}", SIMPLEFIN_USER, SIMPLEFIN_PASSWORD))
+        );
+        if headers.get("authorization") != Some(&expected) {
+            return Reply::error(403, "Forbidden");
+        }
+
+        let timestamp = |key: &str| {
+            query
+                .iter()
+                .find(|(k, _)| *k == key)
+                .and_then(|(_, v)| v.parse::<i64>().ok())
+        };
+        let start = timestamp("start-date").unwrap_or(i64::MIN);
+        let end = timestamp("end-date").unwrap_or(i64::MAX);
+        let account_ids: Vec<&str> = query
+            .iter()
+            .filter(|(k, _)| *k == "account")
+            .map(|(_, v)| *v)
+            .collect();
+
+        let accounts: Vec<JsonValue> = self
+            .fixture
+            .accounts
+            .iter()
+            .filter(|a| account_ids.is_empty() || account_ids.contains(&a.id.as_str()))
+            .map(|a| {
+                let transactions: Vec<JsonValue> = a
+                    .transactions
+                    .iter()
+                    .map(|t| (t, posted_timestamp(t.date)))
+                    .filter(|(_, posted)| (start..=end).contains(posted))
+                    .map(|(t, posted)| {
+                        json!({
+                            "id": t.id,
+                            "posted": posted,
+                            "amount": t.amount.to_string(),
+                            "description": t.description,
+                            "pending": t.pending,
+                        })
+                    })
+                    .collect();
+                json!({
+                    "id": a.id,
+                    "name": a.name,
+                    "currency": a.currency,
+                    "balance": a.balance.to_string(),
+                    "available-balance": a.balance.to_string(),
+                    "org": { "name": a.institution },
+                    "transactions": transactions,
+                })
+            })
+            .collect();
+
+        Reply::json(200, json!({ "errors": [], "accounts": accounts }))
+    }
+
+    /// `GET /lunchflow/accounts`
+    fn lunchflow_accounts(&self) -> Reply {
+        let accounts: Vec<JsonValue> = self
+            .fixture
+            .accounts
+            .iter()
+            .map(|a| {
+                json!({
+                    "id": a.id,
+                    "name": a.name,
+                    "institution_name": a.institution.as_deref().unwrap_or("Mock Bank"),
+                    "provider": "mock",
+                    "currency": a.currency,
+                    "status": "ACTIVE",
+                })
+            })
+            .collect();
+        let total = accounts.len();
+        Reply::json(200, json!({ "accounts": accounts, "total": total }))
+    }
+
+    /// `GET /lunchflow/accounts/{id}/balance`
+    fn lunchflow_balance(&self, id: &str) -> Reply {
+        let Some(account) = self.fixture.accounts.iter().find(|a| a.id == id) else {
+            return Reply::error(404, "Account not found");
+        };
+        Reply::json(
+            200,
+            json!({
+                "balance": {
+                    "amount": account.balance.to_f64().unwrap_or_default(),
+                    "currency": account.currency,
+                }
+            }),
+        )
+    }
+
+    /// `GET /lunchflow/accounts/{id}/transactions?include_pending=`
+    fn lunchflow_transactions(&self, id: &str, query: &[(&str, &str)]) -> Reply {
+        let Some(account) = self.fixture.accounts.iter().find(|a| a.id == id) else {
+            return Reply::error(404, "Account not found");
+        };
+        let include_pending = query
+            .iter()
+            .any(|(k, v)| *k == "include_pending" && *v == "true");
+
+        let transactions: Vec<JsonValue> = account
+            .transactions
+            .iter()
+            .filter(|t| include_pending || !t.pending)
+            .map(|t| {
+                json!({
+                    "id": t.id,
+                    "accountId": account.id,
+                    "amount": t.amount.to_string(),
+                    "currency": account.currency,
+                    "date": t.date.format("%Y-%m-%d").to_string(),
+                    "description": t.description,
+                    "isPending": t.pending,
+                })
+            })
+            .collect();
+        let total = transactions.len();
+        Reply::json(200, json!({ "transactions": transactions, "total": total }))
+    }
+
+    /// `POST /plaid/...`, with the API keys and access token in the body
+    fn plaid(&self, endpoint: &[&str], body: &JsonValue) -> Reply {
+        let field = |key: &str| body.get(key).and_then(|v| v.as_str()).unwrap_or_default();
+        if field("client_id").is_empty() || field("secret").is_empty() {
+            return plaid_error("INVALID_API_KEYS", "invalid client_id or secret provided");
+        }
+
+        match endpoint {
+            ["link", "token", "create"] => Reply::json(200, json!({ "link_token": "link-mock" })),
+            ["sandbox", "public_token", "create"] => {
+                Reply::json(200, json!({ "public_token": "public-mock" }))
+            }
+            ["item", "public_token", "exchange"] => {
+                if field("public_token") != "public-mock" {
+                    return plaid_error("INVALID_PUBLIC_TOKEN", "provided public token is invalid");
+                }
+                Reply::json(
+                    200,
+                    json!({ "access_token": PLAID_ACCESS_TOKEN, "item_id": PLAID_ITEM_ID }),
+                )
+            }
+            ["institutions", "get_by_id"] => {
+                let name = self
+                    .fixture
+                    .accounts
+                    .iter()
+                    .find_map(|a| a.institution.as_deref())
+                    .unwrap_or("Mock Bank");
+                Reply::json(200, json!({ "institution": { "name": name } }))
+            }
+            _ if field("access_token") != PLAID_ACCESS_TOKEN => {
+                plaid_error("INVALID_ACCESS_TOKEN", "provided access token is invalid")
+            }
+            ["accounts", "get"] => self.plaid_accounts(),
+            ["transactions", "sync"] => self.plaid_transactions(field("cursor")),
+            _ => Reply::error(404, "Not found"),
+        }
+    }
+
+    /// `POST /plaid/accounts/get`; negative balances are credit cards
+    fn plaid_accounts(&self) -> Reply {
+        let accounts: Vec<JsonValue> = self
+            .fixture
+            .accounts
+            .iter()
+            .map(|a| {
+                let (kind, subtype) = if a.balance.is_sign_negative() {
+                    ("credit", "credit card")
+                } else {
+                    ("depository", "checking")
+                };
+                let balance = a.balance.abs().to_f64().unwrap_or_default();
+                json!({
+                    "account_id": a.id,
+                    "name": a.name,
+                    "mask": "0000",
+                    "type": kind,
+                    "subtype": subtype,
+                    "balances": {
+                        "current": balance,
+                        "available": balance,
+                        "iso_currency_code": a.currency,
+                    },
+                })
+            })
+            .collect();
+        Reply::json(
+            200,
+            json!({
+                "accounts": accounts,
+                "item": { "item_id": PLAID_ITEM_ID, "institution_id": "ins_mock" },
+            }),
+        )
+    }
+
+    /// `POST /plaid/transactions/sync`
+    ///
+    /// The first sync pages through the accounts one at a time. The next one
+    /// posts the pending transactions under new IDs, withdrawing the pending
+    /// ones as Plaid does, and after that nothing changes.
+    fn plaid_transactions(&self, cursor: &str) -> Reply {
+        let transaction = |account: &MockAccount, t: &MockTransaction, id: &str, pending| {
+            json!({
+                "transaction_id": id,
+                "account_id": account.id,
+                // Plaid's amounts are positive for money out
+                "amount": (-t.amount).to_f64().unwrap_or_default(),
+                "iso_currency_code": account.currency,
+                "date": t.date.format("%Y-%m-%d").to_string(),
+                "name": t.description,
+                "pending": pending,
+            })
+        };
+        let accounts = &self.fixture.accounts;
+        let page = match cursor {
+            "" => Some(0),
+            _ => cursor
+                .strip_prefix("page-")
+                .and_then(|n| n.parse::<usize>().ok()),
+        };
+
+        let (added, removed, next_cursor, has_more) = match (page, cursor) {
+            (Some(n), _) => {
+                let added: Vec<JsonValue> = accounts
+                    .get(n)
+                    .map(|a| {
+                        a.transactions
+                            .iter()
+                            .map(|t| transaction(a, t, &t.id, t.pending))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let has_more = n + 1 < accounts.len();
+                let next = if has_more {
+                    format!("page-{}", n + 1)
+                } else {
+                    "synced".to_string()
+                };
+                (added, Vec::new(), next, has_more)
+            }
+            (None, "synced") => {
+                let pending: Vec<(&MockAccount, &MockTransaction)> = accounts
+                    .iter()
+                    .flat_map(|a| a.transactions.iter().map(move |t| (a, t)))
+                    .filter(|(_, t)| t.pending)
+                    .collect();
+                let added: Vec<JsonValue> = pending
+                    .iter()
+                    .map(|(a, t)| transaction(a, t, &format!("{}-posted", t.id), false))
+                    .collect();
+                let removed: Vec<JsonValue> = pending
+                    .iter()
+                    .map(|(_, t)| json!({ "transaction_id": t.id }))
+                    .collect();
+                (added, removed, "posted".to_string(), false)
+            }
+            _ => (Vec::new(), Vec::new(), cursor.to_string(), false),
+        };
+
+        Reply::json(
+            200,
+            json!({
+                "added": added,
+                "modified": [],
+                "removed": removed,
+                "next_cursor": next_cursor,
+                "has_more": has_more,
+            }),
+        )
+    }
+
+    /// `/gocardless/...`, with a bearer token from `POST /token/new/`
+    ///
+    /// Every requisition is linked as soon as it's created. Each one after
+    /// the first gives the accounts new IDs, as renewing access does.
+    fn gocardless(
+        &self,
+        method: &str,
+        endpoint: &[&str],
+        headers: &HashMap<String, String>,
+        query: &[(&str, &str)],
+        body: &JsonValue,
+    ) -> Reply {
+        let field = |key: &str| body.get(key).and_then(|v| v.as_str()).unwrap_or_default();
+        if method == "POST" && endpoint == ["token", "new"] {
+            if field("secret_id").is_empty() || field("secret_key").is_empty() {
+                return gocardless_error(401, "Authentication failed");
+            }
+            return Reply::json(
+                200,
+                json!({ "access": GOCARDLESS_ACCESS_TOKEN, "access_expires": 86400 }),
+            );
+        }
+        if headers.get("authorization") != Some(&format!("Bearer {}", GOCARDLESS_ACCESS_TOKEN)) {
+            return gocardless_error(401, "Authentication failed");
+        }
+
+        let agreement = json!({
+            "id": "mock-agreement",
+            "institution_id": GOCARDLESS_INSTITUTION_ID,
+            "access_valid_for_days": 180,
+            "accepted": null,
+        });
+        match (method, endpoint) {
+            ("GET", ["institutions"]) => Reply::json(200, json!([self.gocardless_institution()])),
+            ("GET", ["institutions", id]) if *id == GOCARDLESS_INSTITUTION_ID => {
+                Reply::json(200, self.gocardless_institution())
+            }
+            ("POST", ["agreements", "enduser"]) => Reply::json(201, agreement),
+            ("GET", ["agreements", "enduser", _]) => Reply::json(200, agreement),
+            ("POST", ["requisitions"]) => {
+                let n = self.gocardless_requisitions.fetch_add(1, Ordering::SeqCst) + 1;
+                Reply::json(201, self.gocardless_requisition(n, "CR"))
+            }
+            ("GET", ["requisitions", id]) => match id
+                .strip_prefix("mock-requisition-")
+                .and_then(|n| n.parse().ok())
+            {
+                Some(n) => Reply::json(200, self.gocardless_requisition(n, "LN")),
+                None => gocardless_error(404, "Not found"),
+            },
+            ("GET", ["accounts", id, rest @ ..]) => {
+                // Renewed accounts are the first requisition's under a new ID
+                let fixture_id = id
+                    .split_once("~r")
+                    .map_or(*id, |(fixture_id, _)| fixture_id);
+                let Some(account) = self.fixture.accounts.iter().find(|a| a.id == fixture_id)
+                else {
+                    return gocardless_error(404, "Account not found");
+                };
+                match rest {
+                    ["details"] => self.gocardless_details(account),
+                    ["balances"] => Reply::json(
+                        200,
+                        json!({
+                            "balances": [{
+                                "balanceAmount": {
+                                    "amount": account.balance.to_string(),
+                                    "currency": account.currency,
+                                },
+                                "balanceType": "interimBooked",
+                            }]
+                        }),
+                    ),
+                    ["transactions"] => gocardless_transactions(account, query),
+                    _ => gocardless_error(404, "Not found"),
+                }
+            }
+            _ => gocardless_error(404, "Not found"),
+        }
+    }
+
+    fn gocardless_institution(&self) -> JsonValue {
+        let name = self
+            .fixture
+            .accounts
+            .iter()
+            .find_map(|a| a.institution.as_deref())
+            .unwrap_or("Mock Bank");
+        json!({
+            "id": GOCARDLESS_INSTITUTION_ID,
+            "name": name,
+            "bic": "MOCKGB21",
+            "transaction_total_days": "730",
+            "max_access_valid_for_days": "180",
+            "countries": ["GB"],
+            "logo": format!("http://{}/gocardless/logo.png", self.addr),
+        })
+    }
+
+    /// Requisition `n`, whose accounts have new IDs after the first
+    fn gocardless_requisition(&self, n: u32, status: &str) -> JsonValue {
+        let id = format!("mock-requisition-{}", n);
+        // Consent is only given once the user follows the link
+        let accounts: Vec<String> = match status {
+            "LN" => self
+                .fixture
+                .accounts
+                .iter()
+                .map(|a| match n {
+                    1 => a.id.clone(),
+                    _ => format!("{}~r{}", a.id, n),
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        json!({
+            "id": id,
+            "status": status,
+            "institution_id": GOCARDLESS_INSTITUTION_ID,
+            "agreement": "mock-agreement",
+            "accounts": accounts,
+            "link": format!("http://{}/gocardless/consent/{}", self.addr, id),
+        })
+    }
+
+    /// `GET /gocardless/accounts/{id}/details/`; negative balances are cards
+    fn gocardless_details(&self, account: &MockAccount) -> Reply {
+        let position = self
+            .fixture
+            .accounts
+            .iter()
+            .position(|a| a.id == account.id)
+            .unwrap_or_default();
+        let cash_account_type = if account.balance.is_sign_negative() {
+            "CARD"
+        } else {
+            "CACC"
+        };
+        Reply::json(
+            200,
+            json!({
+                "account": {
+                    "resourceId": account.id,
+                    "iban": format!("GB29MOCK6016133192{:04}", position),
+                    "currency": account.currency,
+                    "name": account.name,
+                    "cashAccountType": cash_account_type,
+                }
+            }),
+        )
+    }
+}
+
+/// `GET /gocardless/accounts/{id}/transactions/?date_from=&date_to=`
+fn gocardless_transactions(account: &MockAccount, query: &[(&str, &str)]) -> Reply {
+    let date = |key: &str| {
+        query
+            .iter()
+            .find(|(k, _)| *k == key)
+            .and_then(|(_, v)| NaiveDate::parse_from_str(v, "%Y-%m-%d").ok())
+    };
+    let from = date("date_from").unwrap_or(NaiveDate::MIN);
+    let to = date("date_to").unwrap_or(NaiveDate::MAX);
+
+    let transaction = |t: &MockTransaction| {
+        let date = t.date.format("%Y-%m-%d").to_string();
+        json!({
+            "transactionId": t.id,
+            "bookingDate": date,
+            "valueDate": date,
+            "transactionAmount": {
+                "amount": t.amount.to_string(),
+                "currency": account.currency,
+            },
+            "remittanceInformationUnstructured": t.description,
+        })
+    };
+    let listed = |pending: bool| -> Vec<JsonValue> {
+        account
+            .transactions
+            .iter()
+            .filter(|t| t.pending == pending && (from..=to).contains(&t.date))
+            .map(transaction)
+            .collect()
+    };
+    Reply::json(
+        200,
+        json!({ "transactions": { "booked": listed(false), "pending": listed(true) } }),
+    )
+}
+
+/// A GoCardless error response
+fn gocardless_error(status: u16, summary: &str) -> Reply {
+    Reply::json(
+        status,
+        json!({ "summary": summary, "detail": summary, "status_code": status }),
+    )
+}
+
+/// A Plaid error response
+fn plaid_error(code: &str, message: &str) -> Reply {
+    Reply::json(
+        400,
+        json!({
+            "error_type": "INVALID_INPUT",
+            "error_code": code,
+            "error_message": message,
+            "display_message": null,
+        }),
+    )
+}
+
+/// SimpleFIN's `posted` timestamp for a date (noon UTC)
+fn posted_timestamp(date: NaiveDate) -> i64 {
+    date.and_time(NaiveTime::from_hms_opt(12, 0, 0).unwrap())
+        .and_utc()
+        .timestamp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::duckdb::DuckDbRepository;
+    #[cfg(feature = "sync-lunchflow")]
+    use crate::adapters::lunchflow::LunchflowClient;
+    #[cfg(feature = "sync-plaid")]
+    use crate::adapters::plaid::PlaidToken;
+    use crate::adapters::retry::RetryPolicy;
+    #[cfg(feature = "sync-simplefin")]
+    use crate::adapters::simplefin::SimpleFINClient;
+    use crate::services::{CancellationToken, SyncProgress, SyncService, SyncStage};
+    use chrono::Utc;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, 31).unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "sync-simplefin")]
+    fn test_simplefin_client_against_mock() {
+        let server = MockProviderServer::start(0, MockFixture::sample(today())).unwrap();
+        let client = SimpleFINClient::new(&server.simplefin_access_url()).unwrap();
+
+        let accounts = client.get_accounts().unwrap();
+        assert_eq!(accounts.accounts.len(), 2);
+        assert_eq!(accounts.balance_snapshots.len(), 2);
+
+        let start = today() - chrono::Duration::days(10);
+        let synced = client.get_transactions(start, today(), None).unwrap();
+        assert_eq!(synced.transactions.len(), 4);
+
+        let card = ["mock-card".to_string()];
+        let synced = client
+            .get_transactions(start, today(), Some(&card))
+            .unwrap();
+        assert_eq!(synced.transactions.len(), 2);
+        assert!(synced.transactions.iter().all(|(id, _)| id == "mock-card"));
+    }
+
+    #[test]
+    #[cfg(feature = "sync-lunchflow")]
+    fn test_lunchflow_client_against_mock() {
+        let server = MockProviderServer::start(0, MockFixture::sample(today())).unwrap();
+        let client =
+            LunchflowClient::new_with_base_url("mock-key", &server.lunchflow_base_url()).unwrap();
+
+        let accounts = client.get_accounts().unwrap();
+        assert_eq!(accounts.accounts.len(), 2);
+        assert_eq!(accounts.accounts[0].balance, Some(Decimal::new(254_310, 2)));
+
+        let synced = client.get_transactions(today(), today(), None).unwrap();
+        assert_eq!(synced.transactions.len(), 8);
+        assert!(synced.warnings.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "sync-simplefin")]
+    fn test_injected_failures() {
+        let mut fixture = MockFixture::sample(today());
+        fixture.faults.fail_first = 1;
+        fixture.faults.error_status = 503;
+        let server = MockProviderServer::start(0, fixture).unwrap();
+        let client = SimpleFINClient::new(&server.simplefin_access_url())
+            .unwrap()
+            .with_retry(RetryPolicy::none());
+
+        let err = client.get_accounts().unwrap_err();
+        assert!(err.to_string().contains("HTTP 503"));
+        assert!(client.get_accounts().is_ok());
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "sync-simplefin")]
+    fn test_rate_limits_are_retried() {
+        let mut fixture = MockFixture::sample(today());
+        fixture.faults.fail_first = 2;
+        fixture.faults.error_status = 429;
+        fixture.faults.retry_after = Some(0);
+        let server = MockProviderServer::start(0, fixture).unwrap();
+        let client = SimpleFINClient::new(&server.simplefin_access_url()).unwrap();
+
+        // Retry-After: 0 means no waiting between attempts
+        let accounts = client.get_accounts().unwrap();
+        assert_eq!(accounts.accounts.len(), 2);
+        assert_eq!(server.requests().len(), 3);
+
+        // Asked to wait longer than the policy allows, the error comes back
+        let mut fixture = MockFixture::sample(today());
+        fixture.faults.fail_first = 1;
+        fixture.faults.error_status = 429;
+        fixture.faults.retry_after = Some(3600);
+        let server = MockProviderServer::start(0, fixture).unwrap();
+        let client = SimpleFINClient::new(&server.simplefin_access_url()).unwrap();
+        let err = client.get_accounts().unwrap_err();
+        assert!(err.to_string().contains("rate limit"));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "sync-simplefin")]
+    fn test_sync_against_mock_dedups() {
+        let server =
+            MockProviderServer::start(0, MockFixture::sample(Utc::now().date_naive())).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        let service = SyncService::new(repository, dir.path().to_path_buf());
+        service
+            .setup_simplefin(&server.simplefin_setup_token())
+            .unwrap();
+
+        let first = service.sync(None, false, false, false).unwrap();
+        assert_eq!(first.results[0].accounts_synced, 2);
+        assert_eq!(first.results[0].transaction_stats.new, 8);
+
+        // The next sync picks up a week before where the first left off
+        let second = service.sync(None, false, false, false).unwrap();
+        assert_eq!(second.results[0].sync_type, "incremental");
+        assert_eq!(second.results[0].transaction_stats.new, 0);
+        assert_eq!(second.results[0].transaction_stats.discovered, 3);
+
+        let full = service.sync(None, false, false, true).unwrap();
+        assert_eq!(full.results[0].sync_type, "full");
+        assert_eq!(full.results[0].transaction_stats.discovered, 8);
+        assert_eq!(full.results[0].transaction_stats.new, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "sync-simplefin")]
+    fn test_sync_progress_and_cancellation() {
+        let server =
+            MockProviderServer::start(0, MockFixture::sample(Utc::now().date_naive())).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        SyncService::new(repository.clone(), dir.path().to_path_buf())
+            .setup_simplefin(&server.simplefin_setup_token())
+            .unwrap();
+
+        // Cancelled once the first account's transactions are saved
+        let token = CancellationToken::new();
+        let reports: Arc<Mutex<Vec<SyncProgress>>> = Arc::default();
+        let service = SyncService::new(repository.clone(), dir.path().to_path_buf())
+            .with_cancellation(token.clone())
+            .with_progress({
+                let reports = reports.clone();
+                move |progress| {
+                    if progress.stage == SyncStage::Saving {
+                        token.cancel();
+                    }
+                    reports.lock().unwrap().push(progress.clone());
+                }
+            });
+        let err = service.sync(None, false, false, false).unwrap_err();
+        assert_eq!(err.to_string(), "Sync cancelled");
+        let stages: Vec<SyncStage> = reports.lock().unwrap().iter().map(|p| p.stage).collect();
+        assert_eq!(
+            stages,
+            vec![SyncStage::Accounts, SyncStage::Fetching, SyncStage::Saving]
+        );
+        let saved = repository.get_transactions().unwrap().len() as i64;
+        assert_eq!(saved, reports.lock().unwrap()[2].inserted);
+        assert!(saved > 0 && saved < 8);
+        let settings = repository.get_integrations().unwrap().remove(0).settings;
+        assert!(settings.get("syncedThrough").is_none());
+
+        // Without a cancel, every account reports and the rest is saved
+        reports.lock().unwrap().clear();
+        let service =
+            SyncService::new(repository.clone(), dir.path().to_path_buf()).with_progress({
+                let reports = reports.clone();
+                move |progress| reports.lock().unwrap().push(progress.clone())
+            });
+        service.sync(None, false, false, false).unwrap();
+        let reports = reports.lock().unwrap();
+        let saving: Vec<&SyncProgress> = reports
+            .iter()
+            .filter(|p| p.stage == SyncStage::Saving)
+            .collect();
+        assert_eq!(saving.len(), 2);
+        assert_eq!(saving[1].accounts_done, 2);
+        assert!(saving.iter().all(|p| p.account_name.is_some()));
+        let last = reports.last().unwrap();
+        assert_eq!(last.stage, SyncStage::Finished);
+        assert_eq!(last.fetched, 8);
+        assert_eq!(last.fraction(), 1.0);
+        assert_eq!(repository.get_transactions().unwrap().len(), 8);
+    }
+
+    #[test]
+    #[cfg(feature = "sync-lunchflow")]
+    fn test_lunchflow_account_selection_and_removal() {
+        let server =
+            MockProviderServer::start(0, MockFixture::sample(Utc::now().date_naive())).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        let service = SyncService::new(repository.clone(), dir.path().to_path_buf());
+        service
+            .setup_lunchflow("mock-key", Some(&server.lunchflow_base_url()))
+            .unwrap();
+
+        let remote = service.list_remote_accounts("lunchflow").unwrap();
+        assert_eq!(remote.len(), 2);
+        assert!(remote.iter().all(|a| a.enabled && a.account_id.is_none()));
+
+        service
+            .set_account_enabled("lunchflow", "mock-card", false)
+            .unwrap();
+        let result = service.sync(None, false, false, false).unwrap();
+        assert_eq!(result.results[0].accounts_synced, 1);
+        let accounts = repository.get_accounts().unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(
+            accounts[0].provider.external_id("lunchflow"),
+            Some("mock-checking")
+        );
+
+        let remote = service.list_remote_accounts("lunchflow").unwrap();
+        let card = remote
+            .iter()
+            .find(|a| a.external_id == "mock-card")
+            .unwrap();
+        assert!(!card.enabled);
+        assert_eq!(card.account_id, None);
+
+        // A new key keeps the account choices
+        service.rotate_credentials("lunchflow", "new-key").unwrap();
+        let integration = repository.get_integrations().unwrap().remove(0);
+        assert_eq!(integration.settings["apiKey"], "new-key");
+        let remote = service.list_remote_accounts("lunchflow").unwrap();
+        assert!(remote
+            .iter()
+            .any(|a| a.external_id == "mock-card" && !a.enabled));
+
+        assert_eq!(service.remove_integration("lunchflow", true).unwrap(), 1);
+        assert!(service.list_integrations().unwrap().is_empty());
+        assert_eq!(
+            repository.get_accounts().unwrap()[0]
+                .provider
+                .external_id("lunchflow"),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sync-lunchflow")]
+    fn test_lunchflow_sync_keeps_accounts_that_fetched() {
+        let mut fixture = MockFixture::sample(Utc::now().date_naive());
+        fixture.faults.fail_paths = vec!["/lunchflow/accounts/mock-card/transactions".to_string()];
+        fixture.faults.error_status = 503;
+        fixture.faults.retry_after = Some(0);
+        let server = MockProviderServer::start(0, fixture).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        let service = SyncService::new(repository.clone(), dir.path().to_path_buf());
+        service
+            .setup_lunchflow("mock-key", Some(&server.lunchflow_base_url()))
+            .unwrap();
+
+        let result = service.sync(None, false, false, false).unwrap();
+        let lunchflow = &result.results[0];
+        assert!(lunchflow.error.is_none());
+        assert_eq!(lunchflow.accounts_synced, 2);
+        assert_eq!(lunchflow.transaction_stats.new, 5);
+        assert_eq!(lunchflow.failed_accounts.len(), 1);
+        assert_eq!(lunchflow.failed_accounts[0].external_id, "mock-card");
+        assert_eq!(
+            lunchflow.failed_accounts[0].account_name,
+            "Mock Credit Card"
+        );
+        assert!(lunchflow.failed_accounts[0].error.contains("HTTP 503"));
+        assert_eq!(lunchflow.provider_warnings.len(), 1);
+
+        // Every attempt was made before giving up on the card
+        let card_requests = server
+            .requests()
+            .iter()
+            .filter(|r| r.contains("mock-card/transactions"))
+            .count();
+        assert_eq!(card_requests, RetryPolicy::default().max_attempts as usize);
+
+        // Only the account that fetched moves its cursor
+        let integration = repository.get_integrations().unwrap().remove(0);
+        let synced_through = &integration.settings["syncedThrough"];
+        assert!(synced_through.get("mock-checking").is_some());
+        assert!(synced_through.get("mock-card").is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "sync-plaid")]
+    fn test_plaid_sync_follows_cursor() {
+        let server =
+            MockProviderServer::start(0, MockFixture::sample(Utc::now().date_naive())).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        let service = SyncService::new(repository.clone(), dir.path().to_path_buf());
+        service
+            .setup_plaid(
+                "mock-client",
+                "mock-secret",
+                "sandbox",
+                PlaidToken::Sandbox,
+                Some(&server.plaid_base_url()),
+            )
+            .unwrap();
+        let settings = repository.get_integrations().unwrap().remove(0).settings;
+        assert_eq!(settings["items"][0]["institutionName"], "Mock Bank");
+
+        let first = service.sync(None, false, false, false).unwrap();
+        assert_eq!(first.results[0].accounts_synced, 2);
+        assert_eq!(first.results[0].transaction_stats.new, 8);
+        let card = repository
+            .get_accounts()
+            .unwrap()
+            .into_iter()
+            .find(|a| a.provider.external_id("plaid") == Some("mock-card"))
+            .unwrap();
+        assert_eq!(card.balance, Some(Decimal::new(-61_877, 2)));
+
+        // The pending coffee posts under a new ID and replaces the pending one
+        let pending_id = repository
+            .get_transactions()
+            .unwrap()
+            .into_iter()
+            .find(|t| t.provider.external_id("plaid") == Some("chk-5"))
+            .unwrap()
+            .id;
+        let second = service.sync(None, false, false, false).unwrap();
+        assert_eq!(second.results[0].transaction_stats.new, 0);
+        assert_eq!(second.results[0].transaction_stats.updated, 1);
+        let posted = repository
+            .get_transaction_by_id(&pending_id.to_string())
+            .unwrap()
+            .unwrap();
+        assert!(!posted.pending);
+        assert_eq!(posted.provider.external_id("plaid"), Some("chk-5-posted"));
+        let ids: Vec<String> = repository
+            .get_transactions()
+            .unwrap()
+            .into_iter()
+            .filter_map(|t| t.provider.external_id("plaid").map(String::from))
+            .collect();
+        assert_eq!(ids.len(), 8);
+        assert!(ids.contains(&"chk-5-posted".to_string()));
+        assert!(!ids.contains(&"chk-5".to_string()));
+
+        let third = service.sync(None, false, false, false).unwrap();
+        assert_eq!(third.results[0].transaction_stats.discovered, 0);
+
+        // Linking the same bank again replaces its item rather than adding one
+        service
+            .setup_plaid(
+                "mock-client",
+                "mock-secret",
+                "sandbox",
+                PlaidToken::Access(PLAID_ACCESS_TOKEN),
+                Some(&server.plaid_base_url()),
+            )
+            .unwrap();
+        let settings = repository.get_integrations().unwrap().remove(0).settings;
+        assert_eq!(settings["items"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "sync-gocardless")]
+    fn test_gocardless_sync_and_renewal() {
+        let server =
+            MockProviderServer::start(0, MockFixture::sample(Utc::now().date_naive())).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        let service = SyncService::new(repository.clone(), dir.path().to_path_buf());
+        let base_url = server.gocardless_base_url();
+
+        let institutions = service
+            .list_gocardless_institutions("mock-id", "mock-key", "GB", Some(&base_url))
+            .unwrap();
+        assert_eq!(institutions[0].id, GOCARDLESS_INSTITUTION_ID);
+        assert_eq!(institutions[0].max_access_valid_for_days, Some(180));
+
+        let link = service
+            .setup_gocardless(
+                "mock-id",
+                "mock-key",
+                GOCARDLESS_INSTITUTION_ID,
+                None,
+                Some(&base_url),
+            )
+            .unwrap();
+        assert!(link.ends_with("/gocardless/consent/mock-requisition-1"));
+
+        // The pending coffee isn't booked yet
+        let first = service.sync(None, false, false, false).unwrap();
+        assert_eq!(first.results[0].accounts_synced, 2);
+        assert_eq!(first.results[0].transaction_stats.new, 7);
+        let card = repository
+            .get_accounts()
+            .unwrap()
+            .into_iter()
+            .find(|a| a.provider.external_id("gocardless") == Some("mock-card"))
+            .unwrap();
+        assert_eq!(card.account_type.as_deref(), Some("credit_card"));
+        assert_eq!(card.balance, Some(Decimal::new(-61_877, 2)));
+
+        // Renewed access lists the accounts under new IDs, and they carry on
+        // the accounts already synced
+        service
+            .setup_gocardless(
+                "mock-id",
+                "mock-key",
+                GOCARDLESS_INSTITUTION_ID,
+                None,
+                Some(&base_url),
+            )
+            .unwrap();
+        let second = service.sync(None, false, false, false).unwrap();
+        assert_eq!(second.results[0].accounts_synced, 0);
+        assert_eq!(second.results[0].transaction_stats.new, 0);
+        let accounts = repository.get_accounts().unwrap();
+        assert_eq!(accounts.len(), 2);
+        assert!(accounts.iter().all(|a| a
+            .provider
+            .external_id("gocardless")
+            .is_some_and(|id| id.ends_with("~r2"))));
+        let settings = repository.get_integrations().unwrap().remove(0).settings;
+        assert_eq!(settings["requisitions"].as_array().unwrap().len(), 1);
+
+        // A new secret key keeps the connected banks
+        service
+            .rotate_credentials("gocardless", "new-mock-key")
+            .unwrap();
+        let settings = repository.get_integrations().unwrap().remove(0).settings;
+        assert_eq!(settings["secretKey"], "new-mock-key");
+        assert_eq!(
+            settings["requisitions"][0]["requisitionId"],
+            "mock-requisition-2"
+        );
+    }
+}
@@ -2,12 +2,40 @@
 //!
 //! Adapters implement the port traits with concrete technologies:
 //! - DuckDB for the Repository port
-//! - SimpleFIN HTTP client for DataAggregationProvider
-//! - Lunchflow HTTP client for DataAggregationProvider (global banks)
+//! - In-memory Repository for service unit tests
+//! - SimpleFIN HTTP client for DataAggregationProvider (`sync-simplefin` feature)
+//! - Lunchflow HTTP client for DataAggregationProvider (global banks, `sync-lunchflow` feature)
+//! - Plaid HTTP client for DataAggregationProvider (cursor-based sync, `sync-plaid` feature)
+//! - GoCardless HTTP client for DataAggregationProvider (European banks, `sync-gocardless` feature)
+//! - Coinbase and Kraken HTTP clients for DataAggregationProvider (crypto balances,
+//!   `sync-coinbase` and `sync-kraken` features)
+//! - Retries with backoff for provider HTTP requests (any sync provider)
 //! - Demo data provider for testing
+//! - Mock SimpleFIN/Lunchflow/Plaid/GoCardless HTTP server for testing sync (any sync provider)
 //! - Local filesystem for BackupStorageProvider
+//! - AES-GCM with an OS keychain key for integration credentials
+//! - A prioritized in-process queue in front of the database lock
+//! - Explicit loading of optional DuckDB extensions (autoloading is off)
 
+#[cfg(feature = "sync-coinbase")]
+pub mod coinbase;
+pub mod credentials;
 pub mod demo;
 pub mod duckdb;
+pub mod extensions;
+#[cfg(feature = "sync-gocardless")]
+pub mod gocardless;
+#[cfg(feature = "sync-kraken")]
+pub mod kraken;
+#[cfg(feature = "sync-lunchflow")]
 pub mod lunchflow;
+pub mod memory;
+#[cfg(feature = "sync")]
+pub mod mock_provider;
+#[cfg(feature = "sync-plaid")]
+pub mod plaid;
+#[cfg(feature = "sync")]
+pub mod retry;
+#[cfg(feature = "sync-simplefin")]
 pub mod simplefin;
+pub mod write_queue;
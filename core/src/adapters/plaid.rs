@@ -0,0 +1,1063 @@
+//! Plaid API client
+//!
+//! Handles communication with the Plaid API for account and transaction sync.
+//! Plaid connects to most banks in the US and Canada. Each bank login is an
+//! *item* with its own access token, which setup gets by exchanging the public
+//! token Plaid Link hands back, so one Plaid integration can hold several
+//! items. Transactions come from `/transactions/sync`: each item keeps a
+//! cursor, and a sync fetches only what was added, modified or removed since.
+//!
+//! API Documentation: https://plaid.com/docs/api/
+
+use std::collections::HashSet;
+use std::fmt;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, Utc};
+use reqwest::blocking::Client;
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use uuid::Uuid;
+
+use crate::domain::result::{Error as DomainError, Result as DomainResult};
+use crate::domain::{Account, AccountType, BalanceSnapshot, ProviderData, Transaction};
+use crate::ports::{
+    DataAggregationProvider, FetchAccountsResult, FetchTransactionsResult, IntegrationProvider,
+};
+
+// =============================================================================
+// API Response Models (matching Plaid API spec)
+// =============================================================================
+
+/// Error body Plaid sends with any non-200 response
+#[derive(Debug, Clone, Deserialize)]
+struct ErrorResponse {
+    error_code: String,
+    error_message: String,
+    #[serde(default)]
+    display_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct LinkTokenResponse {
+    link_token: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PublicTokenResponse {
+    public_token: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ExchangeResponse {
+    access_token: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct InstitutionResponse {
+    institution: Institution,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Institution {
+    name: String,
+}
+
+/// Wrapper for /accounts/get response
+#[derive(Debug, Clone, Deserialize)]
+struct AccountsResponse {
+    accounts: Vec<PlaidAccount>,
+    item: PlaidItem,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PlaidItem {
+    item_id: String,
+    #[serde(default)]
+    institution_id: Option<String>,
+}
+
+/// Plaid account from API
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlaidAccount {
+    pub account_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub official_name: Option<String>,
+    /// Last digits of the account number
+    #[serde(default)]
+    pub mask: Option<String>,
+    /// depository, credit, loan, investment or other
+    #[serde(rename = "type")]
+    pub account_type: String,
+    #[serde(default)]
+    pub subtype: Option<String>,
+    pub balances: PlaidBalances,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PlaidBalances {
+    #[serde(default)]
+    pub current: Option<f64>,
+    #[serde(default)]
+    pub available: Option<f64>,
+    #[serde(default)]
+    pub iso_currency_code: Option<String>,
+    /// Set instead of iso_currency_code for currencies like crypto
+    #[serde(default)]
+    pub unofficial_currency_code: Option<String>,
+}
+
+/// One page of /transactions/sync
+#[derive(Debug, Clone, Deserialize)]
+struct SyncResponse {
+    #[serde(default)]
+    added: Vec<PlaidTransaction>,
+    #[serde(default)]
+    modified: Vec<PlaidTransaction>,
+    #[serde(default)]
+    removed: Vec<RemovedTransaction>,
+    next_cursor: String,
+    has_more: bool,
+}
+
+/// Plaid transaction from API
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlaidTransaction {
+    pub transaction_id: String,
+    pub account_id: String,
+    /// Positive when money leaves the account
+    pub amount: f64,
+    /// Posted date (or expected posting date while pending), YYYY-MM-DD
+    pub date: String,
+    #[serde(default)]
+    pub authorized_date: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub merchant_name: Option<String>,
+    #[serde(default)]
+    pub pending: bool,
+    #[serde(default)]
+    pub personal_finance_category: Option<PersonalFinanceCategory>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PersonalFinanceCategory {
+    /// e.g. FOOD_AND_DRINK
+    pub primary: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RemovedTransaction {
+    transaction_id: String,
+}
+
+/// An error Plaid reported, e.g. `ITEM_LOGIN_REQUIRED`
+#[derive(Debug, Clone)]
+pub struct PlaidApiError {
+    pub code: String,
+    pub message: String,
+}
+
+impl fmt::Display for PlaidApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.code.as_str() {
+            "INVALID_API_KEYS" => write!(
+                f,
+                "Plaid authentication failed. Check your client ID and secret."
+            ),
+            "ITEM_LOGIN_REQUIRED" => write!(
+                f,
+                "The bank needs you to log in again. Set up Plaid for it again to reconnect."
+            ),
+            _ => write!(f, "Plaid error {}: {}", self.code, self.message),
+        }
+    }
+}
+
+impl std::error::Error for PlaidApiError {}
+
+impl From<ErrorResponse> for PlaidApiError {
+    fn from(error: ErrorResponse) -> Self {
+        Self {
+            code: error.error_code,
+            message: error.display_message.unwrap_or(error.error_message),
+        }
+    }
+}
+
+/// Result of syncing one item's accounts from Plaid
+#[derive(Debug)]
+pub struct SyncedAccounts {
+    pub item_id: String,
+    pub institution_id: Option<String>,
+    pub accounts: Vec<Account>,
+    pub balance_snapshots: Vec<BalanceSnapshot>,
+}
+
+/// One item's transaction changes since a cursor
+#[derive(Debug)]
+pub struct SyncedTransactions {
+    /// Tuples of (plaid_account_id, transaction), added or modified
+    pub transactions: Vec<(String, Transaction)>,
+    /// IDs of transactions Plaid withdrew
+    pub removed: Vec<String>,
+    /// Cursor to resume from next time
+    pub next_cursor: String,
+}
+
+// =============================================================================
+// Plaid HTTP Client
+// =============================================================================
+
+const PLAID_SANDBOX_URL: &str = "https://sandbox.plaid.com";
+const PLAID_PRODUCTION_URL: &str = "https://production.plaid.com";
+
+/// Transactions per /transactions/sync page (Plaid's maximum)
+const SYNC_PAGE_SIZE: u32 = 500;
+
+/// How often a sync starts over when the item changes while paging
+const MAX_SYNC_RESTARTS: usize = 3;
+
+/// Plaid's test bank, linked when setting up the sandbox without Plaid Link
+const SANDBOX_INSTITUTION_ID: &str = "ins_109508";
+
+/// Investment subtypes that are retirement accounts
+const RETIREMENT_SUBTYPES: &[&str] = &[
+    "401a",
+    "401k",
+    "403b",
+    "457b",
+    "ira",
+    "roth",
+    "roth 401k",
+    "sep ira",
+    "simple ira",
+    "sarsep",
+    "keogh",
+    "pension",
+    "retirement",
+    "thrift savings plan",
+    "rrsp",
+    "rrif",
+    "lira",
+    "lif",
+];
+
+/// The API URL of a Plaid environment ("sandbox" or "production")
+pub fn base_url_for(environment: &str) -> Result<&'static str> {
+    match environment {
+        "sandbox" => Ok(PLAID_SANDBOX_URL),
+        "production" => Ok(PLAID_PRODUCTION_URL),
+        other => anyhow::bail!(
+            "Unknown Plaid environment '{}' (use sandbox or production)",
+            other
+        ),
+    }
+}
+
+/// The bank login Plaid setup starts from
+#[derive(Debug, Clone, Copy)]
+pub enum PlaidToken<'a> {
+    /// Handed back by Plaid Link, exchanged for an access token
+    Public(&'a str),
+    /// Already exchanged, e.g. with Plaid's quickstart app
+    Access(&'a str),
+    /// Plaid's test bank, linked without Plaid Link (sandbox only)
+    Sandbox,
+}
+
+/// Plaid API client
+#[derive(Debug)]
+pub struct PlaidClient {
+    client: Client,
+    client_id: String,
+    secret: String,
+    base_url: String,
+}
+
+impl PlaidClient {
+    /// Create a new Plaid client with the API keys from the Plaid dashboard
+    pub fn new(client_id: &str, secret: &str, base_url: &str) -> Result<Self> {
+        if client_id.is_empty() || secret.is_empty() {
+            anyhow::bail!("Plaid client ID and secret cannot be empty");
+        }
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(120))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self {
+            client,
+            client_id: client_id.to_string(),
+            secret: secret.to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Create a link token for opening Plaid Link
+    pub fn create_link_token(&self, client_user_id: &str) -> Result<String> {
+        let response: LinkTokenResponse = self.post(
+            "/link/token/create",
+            json!({
+                "client_name": "Treeline",
+                "language": "en",
+                "country_codes": ["US", "CA"],
+                "products": ["transactions"],
+                "user": { "client_user_id": client_user_id },
+            }),
+        )?;
+        Ok(response.link_token)
+    }
+
+    /// Create a public token for Plaid's test bank without Plaid Link
+    ///
+    /// Only the sandbox environment offers this.
+    pub fn create_sandbox_public_token(&self) -> Result<String> {
+        let response: PublicTokenResponse = self.post(
+            "/sandbox/public_token/create",
+            json!({
+                "institution_id": SANDBOX_INSTITUTION_ID,
+                "initial_products": ["transactions"],
+            }),
+        )?;
+        Ok(response.public_token)
+    }
+
+    /// Exchange a Plaid Link public token for the item's access token
+    pub fn exchange_public_token(&self, public_token: &str) -> Result<String> {
+        let response: ExchangeResponse = self.post(
+            "/item/public_token/exchange",
+            json!({ "public_token": public_token }),
+        )?;
+        Ok(response.access_token)
+    }
+
+    /// Name of a Plaid institution
+    pub fn get_institution_name(&self, institution_id: &str) -> Result<String> {
+        let response: InstitutionResponse = self.post(
+            "/institutions/get_by_id",
+            json!({
+                "institution_id": institution_id,
+                "country_codes": ["US", "CA"],
+            }),
+        )?;
+        Ok(response.institution.name)
+    }
+
+    /// Fetch an item's accounts with their current balances
+    pub fn get_accounts(
+        &self,
+        access_token: &str,
+        institution_name: Option<&str>,
+    ) -> Result<SyncedAccounts> {
+        let response: AccountsResponse =
+            self.post("/accounts/get", json!({ "access_token": access_token }))?;
+
+        let mut accounts = Vec::new();
+        let mut balance_snapshots = Vec::new();
+        for pl_account in &response.accounts {
+            let account = map_account(pl_account, &response.item.item_id, institution_name);
+            if let Some(balance) = account.balance {
+                balance_snapshots.push(BalanceSnapshot {
+                    id: Uuid::new_v4(),
+                    account_id: account.id,
+                    balance,
+                    snapshot_time: Utc::now().naive_utc(),
+                    source: Some("sync".to_string()),
+                    csv_batch_id: None,
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                });
+            }
+            accounts.push(account);
+        }
+
+        Ok(SyncedAccounts {
+            item_id: response.item.item_id,
+            institution_id: response.item.institution_id,
+            accounts,
+            balance_snapshots,
+        })
+    }
+
+    /// Fetch an item's transaction changes since `cursor`
+    ///
+    /// Without a cursor this is the item's whole history. Pages are followed
+    /// until Plaid has nothing more.
+    pub fn sync_transactions(
+        &self,
+        access_token: &str,
+        cursor: Option<&str>,
+    ) -> Result<SyncedTransactions> {
+        let mut restarts = 0;
+        'restart: loop {
+            let mut synced = SyncedTransactions {
+                transactions: Vec::new(),
+                removed: Vec::new(),
+                next_cursor: cursor.unwrap_or_default().to_string(),
+            };
+            loop {
+                let mut body = json!({
+                    "access_token": access_token,
+                    "count": SYNC_PAGE_SIZE,
+                });
+                if !synced.next_cursor.is_empty() {
+                    body["cursor"] = json!(synced.next_cursor);
+                }
+                let page: SyncResponse = match self.post("/transactions/sync", body) {
+                    Ok(page) => page,
+                    // The item changed while paging; Plaid asks to start over
+                    // from the cursor the sync began with
+                    Err(e)
+                        if restarts < MAX_SYNC_RESTARTS
+                            && is_error_code(
+                                &e,
+                                "TRANSACTIONS_SYNC_MUTATION_DURING_PAGINATION",
+                            ) =>
+                    {
+                        restarts += 1;
+                        continue 'restart;
+                    }
+                    Err(e) => return Err(e),
+                };
+
+                // Modified transactions are usually stored already, and dedup
+                // skips those so the user's edits stay
+                for pl_tx in page.added.iter().chain(&page.modified) {
+                    synced
+                        .transactions
+                        .push((pl_tx.account_id.clone(), map_transaction(pl_tx)));
+                }
+                synced
+                    .removed
+                    .extend(page.removed.into_iter().map(|r| r.transaction_id));
+                synced.next_cursor = page.next_cursor;
+
+                if !page.has_more {
+                    return Ok(synced);
+                }
+            }
+        }
+    }
+
+    /// POST to a Plaid endpoint, which all take the API keys in the body
+    fn post<T: DeserializeOwned>(&self, path: &str, mut body: JsonValue) -> Result<T> {
+        body["client_id"] = json!(self.client_id);
+        body["secret"] = json!(self.secret);
+
+        let url = format!("{}{}", self.base_url, path);
+        let response = self
+            .client
+            .post(&url)
+            .json(&body)
+            .send()
+            .map_err(|e| self.map_request_error(e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            // Plaid explains what went wrong in the body
+            return match response.json::<ErrorResponse>() {
+                Ok(error) => Err(PlaidApiError::from(error).into()),
+                Err(_) => Err(anyhow::anyhow!("Plaid API error: HTTP {}", status.as_u16())),
+            };
+        }
+
+        response
+            .json()
+            .with_context(|| format!("Failed to parse Plaid {} response", path))
+    }
+
+    /// Map request errors to user-friendly messages
+    fn map_request_error(&self, error: reqwest::Error) -> anyhow::Error {
+        if error.is_timeout() {
+            anyhow::anyhow!("Connection timed out after 120 seconds")
+        } else if error.is_connect() {
+            anyhow::anyhow!("Unable to connect to Plaid servers")
+        } else {
+            anyhow::anyhow!("Plaid request failed: {}", error)
+        }
+    }
+}
+
+/// Whether `error` is a Plaid error with this code
+fn is_error_code(error: &anyhow::Error, code: &str) -> bool {
+    error
+        .downcast_ref::<PlaidApiError>()
+        .is_some_and(|e| e.code == code)
+}
+
+/// Parse an amount the way it reads, not as its nearest binary float
+fn to_decimal(value: f64) -> Decimal {
+    value.to_string().parse().unwrap_or_default()
+}
+
+/// Treeline account type for a Plaid account's type and subtype
+fn plaid_account_type(
+    pl_account: &PlaidAccount,
+    institution_name: Option<&str>,
+) -> Option<AccountType> {
+    let subtype = pl_account.subtype.as_deref().unwrap_or_default();
+    match pl_account.account_type.as_str() {
+        "depository" => match subtype {
+            "savings" | "money market" | "cd" => Some(AccountType::Savings),
+            "checking" | "cash management" | "paypal" | "prepaid" => Some(AccountType::Checking),
+            _ => AccountType::infer(&pl_account.name, institution_name),
+        },
+        "credit" => Some(AccountType::CreditCard),
+        "loan" => match subtype {
+            "mortgage" | "home equity" => Some(AccountType::Mortgage),
+            _ => Some(AccountType::Loan),
+        },
+        "investment" => match subtype {
+            "crypto exchange" => Some(AccountType::Crypto),
+            s if RETIREMENT_SUBTYPES.contains(&s) => Some(AccountType::Retirement),
+            _ => Some(AccountType::Brokerage),
+        },
+        _ => AccountType::infer(&pl_account.name, institution_name),
+    }
+}
+
+/// Map Plaid account to domain Account
+fn map_account(
+    pl_account: &PlaidAccount,
+    item_id: &str,
+    institution_name: Option<&str>,
+) -> Account {
+    let account_type = plaid_account_type(pl_account, institution_name);
+    let classification = Some(Account::compute_classification(
+        account_type.map(|t| t.as_str()),
+    ));
+
+    // Plaid reports what's owed on cards and loans as a positive balance
+    let owed = matches!(pl_account.account_type.as_str(), "credit" | "loan");
+    let balance = pl_account
+        .balances
+        .current
+        .or(pl_account.balances.available)
+        .map(to_decimal)
+        .map(|b| if owed { -b } else { b });
+
+    let now = Utc::now();
+    Account {
+        id: Uuid::new_v4(),
+        name: pl_account.name.clone(),
+        nickname: None,
+        currency: pl_account
+            .balances
+            .iso_currency_code
+            .clone()
+            .or_else(|| pl_account.balances.unofficial_currency_code.clone())
+            .unwrap_or_else(|| "USD".to_string()),
+        account_type: account_type.map(|t| t.to_string()),
+        classification,
+        balance,
+        institution_name: institution_name.map(|n| n.to_string()),
+        institution_url: None,
+        institution_domain: None,
+        created_at: now,
+        updated_at: now,
+        // Manual flag
+        is_manual: false,
+        // Plaid: identity fields from /accounts/get
+        provider: ProviderData::new(
+            "plaid",
+            &pl_account.account_id,
+            json!({
+                "item_id": item_id,
+                "mask": pl_account.mask,
+                "subtype": pl_account.subtype,
+            }),
+        ),
+    }
+}
+
+/// Map Plaid transaction to domain Transaction
+fn map_transaction(pl_tx: &PlaidTransaction) -> Transaction {
+    let parse = |date: &str| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok();
+    let posted_date = parse(&pl_tx.date).unwrap_or_else(|| Utc::now().naive_utc().date());
+    let transaction_date = pl_tx
+        .authorized_date
+        .as_deref()
+        .and_then(parse)
+        .unwrap_or(posted_date);
+
+    // Plaid's name is the cleaned-up statement description; the merchant
+    // is only known for some transactions
+    let description = pl_tx
+        .name
+        .as_ref()
+        .filter(|n| !n.trim().is_empty())
+        .or(pl_tx.merchant_name.as_ref())
+        .cloned();
+
+    let now = Utc::now();
+    Transaction {
+        id: Uuid::new_v4(),
+        account_id: Uuid::nil(), // Will be set by sync service after mapping
+        // Plaid's amounts are positive for money out
+        amount: -to_decimal(pl_tx.amount),
+        description,
+        transaction_date,
+        posted_date,
+        tags: vec![],
+        created_at: now,
+        updated_at: now,
+        deleted_at: None,
+        parent_transaction_id: None,
+        // CSV Import tracking (not applicable)
+        csv_fingerprint: None,
+        csv_batch_id: None,
+        // Reconciliation identifiers (CSV import only)
+        check_number: None,
+        reference: None,
+        // Currency (CSV import only; the account's currency applies)
+        currency: None,
+        // Manual flag
+        is_manual: false,
+        // Auto-tag tracking (starts false, set true when rules apply)
+        tags_auto_applied: false,
+        // Report exclusion (set by the user or a rule)
+        excluded_from_reports: false,
+        // Pending until the bank posts it
+        pending: pl_tx.pending,
+        // Plaid: raw fields from /transactions/sync
+        provider: ProviderData::new(
+            "plaid",
+            &pl_tx.transaction_id,
+            json!({
+                "account_id": pl_tx.account_id,
+                "merchant_name": pl_tx.merchant_name,
+                "pending": pl_tx.pending,
+                "category": pl_tx.personal_finance_category.as_ref().map(|c| &c.primary),
+            }),
+        ),
+    }
+}
+
+// =============================================================================
+// PlaidProvider - implements DataAggregationProvider trait
+// =============================================================================
+
+/// A linked bank in the integration's `items`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ItemSettings {
+    item_id: String,
+    access_token: String,
+    #[serde(default)]
+    institution_name: Option<String>,
+    /// Where the last transaction sync left off (absent before the first)
+    #[serde(default)]
+    cursor: Option<String>,
+}
+
+impl ItemSettings {
+    /// How warnings name the item
+    fn label(&self) -> &str {
+        self.institution_name.as_deref().unwrap_or(&self.item_id)
+    }
+}
+
+/// Client for the keys and environment in settings or setup options
+fn client_from(settings: &JsonValue) -> DomainResult<PlaidClient> {
+    let field = |key: &str| settings.get(key).and_then(|v| v.as_str());
+    let (Some(client_id), Some(secret)) = (field("clientId"), field("secret")) else {
+        return Err(DomainError::Config(
+            "Plaid clientId and secret not found in settings".to_string(),
+        ));
+    };
+
+    // A custom base URL (for testing with mock server) wins over the environment
+    let base_url = match field("baseUrl") {
+        Some(url) => url,
+        None => base_url_for(field("environment").unwrap_or("production"))
+            .map_err(|e| DomainError::Config(e.to_string()))?,
+    };
+
+    PlaidClient::new(client_id, secret, base_url).map_err(|e| DomainError::Sync(e.to_string()))
+}
+
+/// The linked banks in settings
+fn items(settings: &JsonValue) -> DomainResult<Vec<ItemSettings>> {
+    let items: Vec<ItemSettings> = match settings.get("items") {
+        Some(items) => serde_json::from_value(items.clone())
+            .map_err(|e| DomainError::Config(format!("Invalid Plaid items in settings: {}", e)))?,
+        None => Vec::new(),
+    };
+    if items.is_empty() {
+        return Err(DomainError::Config(
+            "No banks are linked to Plaid yet".to_string(),
+        ));
+    }
+    Ok(items)
+}
+
+/// Settings with every item's cursor dropped, so the next sync fetches each
+/// linked bank's whole history again
+pub fn without_cursors(settings: &JsonValue) -> JsonValue {
+    let mut settings = settings.clone();
+    if let Some(items) = settings.get_mut("items").and_then(|i| i.as_array_mut()) {
+        for item in items.iter_mut().filter_map(|i| i.as_object_mut()) {
+            item.remove("cursor");
+        }
+    }
+    settings
+}
+
+/// Plaid data provider
+///
+/// Implements DataAggregationProvider and IntegrationProvider traits
+/// for syncing financial data via Plaid.
+pub struct PlaidProvider;
+
+impl PlaidProvider {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for PlaidProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DataAggregationProvider for PlaidProvider {
+    fn name(&self) -> &str {
+        "plaid"
+    }
+
+    fn can_get_accounts(&self) -> bool {
+        true
+    }
+
+    fn can_get_transactions(&self) -> bool {
+        true
+    }
+
+    fn can_get_balances(&self) -> bool {
+        true
+    }
+
+    /// Accounts of every linked bank
+    ///
+    /// A bank that fails (e.g. it needs a fresh login) becomes a warning so
+    /// the others still sync; only when all of them fail is it an error.
+    fn get_accounts(&self, settings: &JsonValue) -> DomainResult<FetchAccountsResult> {
+        let client = client_from(settings)?;
+        let items = items(settings)?;
+
+        let mut result = FetchAccountsResult::default();
+        for item in &items {
+            match client.get_accounts(&item.access_token, item.institution_name.as_deref()) {
+                Ok(synced) => {
+                    result.accounts.extend(synced.accounts);
+                    result.balance_snapshots.extend(synced.balance_snapshots);
+                }
+                Err(e) => result.warnings.push(format!("{}: {}", item.label(), e)),
+            }
+        }
+
+        if result.warnings.len() == items.len() {
+            return Err(DomainError::Sync(result.warnings.remove(0)));
+        }
+        Ok(result)
+    }
+
+    /// Transactions each linked bank added since its cursor
+    ///
+    /// Plaid sends everything since the cursor regardless of dates, so
+    /// `start_date` and `end_date` are ignored. The advanced cursors come back
+    /// in the result's settings.
+    fn get_transactions(
+        &self,
+        _start_date: NaiveDate,
+        _end_date: NaiveDate,
+        account_ids: &[String],
+        settings: &JsonValue,
+    ) -> DomainResult<FetchTransactionsResult> {
+        let client = client_from(settings)?;
+        let wanted: HashSet<&str> = account_ids.iter().map(|id| id.as_str()).collect();
+
+        let mut result = FetchTransactionsResult::default();
+        let mut updated = settings.clone();
+        for (index, item) in items(settings)?.iter().enumerate() {
+            let cursor = item.cursor.as_deref().filter(|c| !c.is_empty());
+            match client.sync_transactions(&item.access_token, cursor) {
+                Ok(synced) => {
+                    result.transactions.extend(
+                        synced
+                            .transactions
+                            .into_iter()
+                            .filter(|(id, _)| wanted.is_empty() || wanted.contains(id.as_str())),
+                    );
+                    result.removed.extend(synced.removed);
+                    updated["items"][index]["cursor"] = json!(synced.next_cursor);
+                }
+                Err(e) => result.warnings.push(format!("{}: {}", item.label(), e)),
+            }
+        }
+
+        result.settings = Some(updated);
+        Ok(result)
+    }
+}
+
+impl IntegrationProvider for PlaidProvider {
+    /// Link one bank
+    ///
+    /// Takes `clientId`, `secret`, `environment` (and `baseUrl` for testing)
+    /// plus the bank login as a Plaid Link `publicToken` or an already
+    /// exchanged `accessToken`. In the sandbox, `sandbox: true` links Plaid's
+    /// test bank instead. The settings hold that bank as the only item.
+    fn setup(&self, options: &JsonValue) -> DomainResult<JsonValue> {
+        let client = client_from(options)?;
+        let option = |key: &str| options.get(key).and_then(|v| v.as_str());
+        let sync_err = |e: anyhow::Error| DomainError::Sync(e.to_string());
+
+        let access_token = match (option("publicToken"), option("accessToken")) {
+            (Some(public_token), _) => client
+                .exchange_public_token(public_token)
+                .map_err(sync_err)?,
+            (None, Some(access_token)) => access_token.to_string(),
+            (None, None) if options.get("sandbox").and_then(|v| v.as_bool()) == Some(true) => {
+                let public_token = client.create_sandbox_public_token().map_err(sync_err)?;
+                client
+                    .exchange_public_token(&public_token)
+                    .map_err(sync_err)?
+            }
+            (None, None) => {
+                return Err(DomainError::Config(
+                    "Plaid publicToken or accessToken required for setup".to_string(),
+                ))
+            }
+        };
+
+        // Validate the token by fetching the item's accounts
+        let synced = client.get_accounts(&access_token, None).map_err(|e| {
+            DomainError::Sync(format!("Failed to validate Plaid access token: {}", e))
+        })?;
+        // The name is only for display, so a failed lookup isn't fatal
+        let institution_name = synced
+            .institution_id
+            .and_then(|id| client.get_institution_name(&id).ok());
+
+        let mut item = json!({
+            "itemId": synced.item_id,
+            "accessToken": access_token,
+        });
+        if let Some(name) = institution_name {
+            item["institutionName"] = json!(name);
+        }
+
+        let mut settings = json!({
+            "clientId": option("clientId"),
+            "secret": option("secret"),
+            "environment": option("environment").unwrap_or("production"),
+            "items": [item],
+        });
+
+        // Include base URL if custom (for testing)
+        if let Some(url) = option("baseUrl") {
+            settings["baseUrl"] = json!(url);
+        }
+
+        Ok(settings)
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pl_account(account_type: &str, subtype: &str, current: f64) -> PlaidAccount {
+        PlaidAccount {
+            account_id: "acc_1".to_string(),
+            name: "Plaid Account".to_string(),
+            official_name: None,
+            mask: Some("0000".to_string()),
+            account_type: account_type.to_string(),
+            subtype: Some(subtype.to_string()),
+            balances: PlaidBalances {
+                current: Some(current),
+                available: None,
+                iso_currency_code: Some("USD".to_string()),
+                unofficial_currency_code: None,
+            },
+        }
+    }
+
+    fn pl_tx(amount: f64) -> PlaidTransaction {
+        PlaidTransaction {
+            transaction_id: "tx_1".to_string(),
+            account_id: "acc_1".to_string(),
+            amount,
+            date: "2025-01-16".to_string(),
+            authorized_date: Some("2025-01-15".to_string()),
+            name: Some("STARBUCKS STORE 1234".to_string()),
+            merchant_name: Some("Starbucks".to_string()),
+            pending: false,
+            personal_finance_category: Some(PersonalFinanceCategory {
+                primary: "FOOD_AND_DRINK".to_string(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_provider_name() {
+        let provider = PlaidProvider::new();
+        assert_eq!(provider.name(), "plaid");
+    }
+
+    #[test]
+    fn test_reject_empty_keys() {
+        let result = PlaidClient::new("client", "", "http://localhost");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("cannot be empty"));
+    }
+
+    #[test]
+    fn test_base_url_for_environment() {
+        assert_eq!(
+            base_url_for("sandbox").unwrap(),
+            "https://sandbox.plaid.com"
+        );
+        assert_eq!(
+            base_url_for("production").unwrap(),
+            "https://production.plaid.com"
+        );
+        assert!(base_url_for("development").is_err());
+    }
+
+    #[test]
+    fn test_account_mapping() {
+        let account = map_account(
+            &pl_account("depository", "savings", 1250.5),
+            "item_1",
+            Some("First Platypus Bank"),
+        );
+
+        assert_eq!(account.account_type, Some("savings".to_string()));
+        assert_eq!(account.balance, Some(Decimal::new(125050, 2)));
+        assert_eq!(
+            account.institution_name,
+            Some("First Platypus Bank".to_string())
+        );
+        assert_eq!(account.provider.external_id("plaid"), Some("acc_1"));
+        assert_eq!(
+            account.provider.metadata_str("plaid", "item_id"),
+            Some("item_1")
+        );
+        assert_eq!(account.provider.metadata_str("plaid", "mask"), Some("0000"));
+    }
+
+    #[test]
+    fn test_account_mapping_liabilities_are_negative() {
+        let card = map_account(&pl_account("credit", "credit card", 410.22), "item_1", None);
+        assert_eq!(card.account_type, Some("credit_card".to_string()));
+        assert_eq!(card.balance, Some(Decimal::new(-41022, 2)));
+
+        let mortgage = map_account(&pl_account("loan", "mortgage", 200000.0), "item_1", None);
+        assert_eq!(mortgage.account_type, Some("mortgage".to_string()));
+        assert_eq!(mortgage.balance, Some(Decimal::new(-200000, 0)));
+
+        let ira = map_account(&pl_account("investment", "roth", 5000.0), "item_1", None);
+        assert_eq!(ira.account_type, Some("retirement".to_string()));
+        assert_eq!(ira.balance, Some(Decimal::new(5000, 0)));
+    }
+
+    #[test]
+    fn test_transaction_mapping() {
+        let tx = map_transaction(&pl_tx(4.33));
+
+        // Plaid's positive amounts are money out
+        assert_eq!(tx.amount, Decimal::new(-433, 2));
+        assert_eq!(tx.description, Some("STARBUCKS STORE 1234".to_string()));
+        assert_eq!(tx.transaction_date.to_string(), "2025-01-15");
+        assert_eq!(tx.posted_date.to_string(), "2025-01-16");
+        assert_eq!(tx.provider.external_id("plaid"), Some("tx_1"));
+        assert_eq!(
+            tx.provider.metadata_str("plaid", "merchant_name"),
+            Some("Starbucks")
+        );
+        assert_eq!(
+            tx.provider.metadata_str("plaid", "category"),
+            Some("FOOD_AND_DRINK")
+        );
+        assert_eq!(
+            tx.provider.metadata("plaid", "pending"),
+            Some(&json!(false))
+        );
+
+        let refund = map_transaction(&pl_tx(-500.0));
+        assert_eq!(refund.amount, Decimal::new(500, 0));
+    }
+
+    #[test]
+    fn test_transaction_mapping_no_name() {
+        let mut pl_tx = pl_tx(12.0);
+        pl_tx.name = Some(" ".to_string());
+        pl_tx.authorized_date = None;
+        let tx = map_transaction(&pl_tx);
+
+        // Falls back to the merchant, and to the posted date
+        assert_eq!(tx.description, Some("Starbucks".to_string()));
+        assert_eq!(tx.transaction_date, tx.posted_date);
+    }
+
+    #[test]
+    fn test_error_messages() {
+        let error = PlaidApiError::from(ErrorResponse {
+            error_code: "PRODUCT_NOT_READY".to_string(),
+            error_message: "the requested product is not yet ready".to_string(),
+            display_message: None,
+        });
+        assert_eq!(
+            error.to_string(),
+            "Plaid error PRODUCT_NOT_READY: the requested product is not yet ready"
+        );
+
+        let error: anyhow::Error = PlaidApiError {
+            code: "ITEM_LOGIN_REQUIRED".to_string(),
+            message: String::new(),
+        }
+        .into();
+        assert!(is_error_code(&error, "ITEM_LOGIN_REQUIRED"));
+        assert!(error.to_string().contains("log in again"));
+    }
+
+    #[test]
+    fn test_provider_setup_requires_token() {
+        let provider = PlaidProvider::new();
+        let result = provider.setup(&json!({
+            "clientId": "client",
+            "secret": "secret",
+            "baseUrl": "http://localhost",
+        }));
+        assert!(result.unwrap_err().to_string().contains("publicToken"));
+
+        assert!(provider.setup(&json!({})).is_err());
+    }
+
+    #[test]
+    fn test_settings_require_linked_items() {
+        let settings = json!({ "clientId": "client", "secret": "secret", "items": [] });
+        assert!(items(&settings).is_err());
+        let settings = json!({
+            "items": [{ "itemId": "item_1", "accessToken": "access", "cursor": "c1" }]
+        });
+        let items = items(&settings).unwrap();
+        assert_eq!(items[0].cursor.as_deref(), Some("c1"));
+        assert_eq!(items[0].label(), "item_1");
+    }
+}
@@ -0,0 +1,173 @@
+//! Retries for provider HTTP requests
+//!
+//! Providers answer 429 when a client is over its rate limit and 5xx when
+//! they're briefly unavailable. Such requests are tried again a few times,
+//! waiting twice as long before each attempt, or as long as the provider's
+//! `Retry-After` header asks.
+
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::blocking::{RequestBuilder, Response};
+use reqwest::StatusCode;
+
+/// How often and how patiently to retry a request
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first
+    pub max_attempts: u32,
+    /// Wait before the first retry; doubles for each one after
+    pub base_delay: Duration,
+    /// Longest wait between attempts. A provider asking for a longer wait
+    /// gets its error reported instead
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Send each request once
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Send the request `build` makes, retrying rate limits, server errors
+    /// and failed connections
+    ///
+    /// Returns the last response, which the caller checks as usual.
+    pub fn send(&self, build: impl Fn() -> RequestBuilder) -> reqwest::Result<Response> {
+        let mut attempt = 0;
+        loop {
+            let (wait, outcome) = match build().send() {
+                Ok(response) if is_transient(response.status()) => {
+                    let retry_after = response
+                        .headers()
+                        .get(reqwest::header::RETRY_AFTER)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|v| parse_retry_after(v, Utc::now()));
+                    (self.delay(attempt, retry_after), Ok(response))
+                }
+                Err(error) if error.is_connect() => (self.delay(attempt, None), Err(error)),
+                outcome => return outcome,
+            };
+            match wait {
+                Some(wait) => thread::sleep(wait),
+                None => return outcome,
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Wait before the retry after attempt `attempt` (0-based) failed, or
+    /// None to give up
+    fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if attempt + 1 >= self.max_attempts {
+            return None;
+        }
+        match retry_after {
+            Some(wait) if wait > self.max_delay => None,
+            Some(wait) => Some(wait),
+            None => Some(
+                self.base_delay
+                    .saturating_mul(2u32.saturating_pow(attempt))
+                    .min(self.max_delay),
+            ),
+        }
+    }
+}
+
+/// Statuses worth trying again: rate limits and server trouble
+pub fn is_transient(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::REQUEST_TIMEOUT
+            | StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// How long a `Retry-After` header asks to wait: either seconds or an HTTP date
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    // A date in the past means now
+    Some((at.with_timezone(&Utc) - now).to_std().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_delay_backs_off_and_gives_up() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(0, None), Some(Duration::from_secs(1)));
+        assert_eq!(policy.delay(1, None), Some(Duration::from_secs(2)));
+        assert_eq!(policy.delay(2, None), Some(Duration::from_secs(4)));
+        assert_eq!(policy.delay(3, None), None);
+        assert_eq!(RetryPolicy::none().delay(0, None), None);
+
+        let patient = RetryPolicy {
+            max_attempts: 10,
+            ..RetryPolicy::default()
+        };
+        assert_eq!(patient.delay(8, None), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_delay_honors_retry_after() {
+        let policy = RetryPolicy::default();
+        assert_eq!(
+            policy.delay(0, Some(Duration::from_secs(12))),
+            Some(Duration::from_secs(12))
+        );
+        assert_eq!(policy.delay(0, Some(Duration::ZERO)), Some(Duration::ZERO));
+        // Longer than we're willing to wait
+        assert_eq!(policy.delay(0, Some(Duration::from_secs(3600))), None);
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 31, 12, 0, 0).unwrap();
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Mon, 31 Mar 2025 12:00:45 GMT", now),
+            Some(Duration::from_secs(45))
+        );
+        assert_eq!(
+            parse_retry_after("Mon, 31 Mar 2025 11:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_transient_statuses() {
+        assert!(is_transient(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_transient(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_transient(StatusCode::FORBIDDEN));
+        assert!(!is_transient(StatusCode::OK));
+    }
+}
@@ -0,0 +1,257 @@
+//! Prioritized access queue for a DuckDB database
+//!
+//! Every repository operation takes the database's exclusive file lock.
+//! Inside one process, a long sync doing many small writes would otherwise
+//! compete on equal terms with a tag edit the user is waiting for. Threads
+//! queue here before taking the file lock: interactive work goes first, and
+//! background work (sync) waits its turn between its own operations.
+//!
+//! Background work is only deferred for `MAX_BACKGROUND_DEFER`, so a stream
+//! of interactive operations can't starve a sync.
+//!
+//! Priority is per thread: code runs as interactive unless it holds a
+//! `background()` scope.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Longest a background operation yields to queued interactive ones
+const MAX_BACKGROUND_DEFER: Duration = Duration::from_secs(2);
+
+thread_local! {
+    static PRIORITY: Cell<Priority> = const { Cell::new(Priority::Interactive) };
+}
+
+/// Who is waiting for the database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// A user is waiting on the result (the default)
+    Interactive,
+    /// Long-running work that can yield, such as sync
+    Background,
+}
+
+impl Priority {
+    fn index(self) -> usize {
+        match self {
+            Priority::Interactive => 0,
+            Priority::Background => 1,
+        }
+    }
+}
+
+/// Run this thread's database operations as background work until dropped
+pub fn background() -> PriorityScope {
+    let previous = PRIORITY.with(|p| p.replace(Priority::Background));
+    PriorityScope { previous }
+}
+
+/// Restores the thread's previous priority when dropped
+pub struct PriorityScope {
+    previous: Priority,
+}
+
+impl Drop for PriorityScope {
+    fn drop(&mut self) {
+        PRIORITY.with(|p| p.set(self.previous));
+    }
+}
+
+/// Queue counters for one priority
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PriorityStats {
+    /// Operations that got access
+    pub acquired: u64,
+    /// Operations waiting right now
+    pub waiting: usize,
+    pub total_wait_ms: u64,
+    pub max_wait_ms: u64,
+}
+
+/// Queue counters since the process started
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WriteQueueStats {
+    pub interactive: PriorityStats,
+    pub background: PriorityStats,
+}
+
+#[derive(Default)]
+struct QueueState {
+    held: bool,
+    stats: [PriorityStats; 2],
+}
+
+/// Access queue shared by every repository for one database file
+pub struct WriteQueue {
+    state: Mutex<QueueState>,
+    turn: Condvar,
+    /// Write operations finished by this process
+    writes: AtomicU64,
+}
+
+/// The same key for every spelling of a database path, for per-database
+/// registries
+pub fn database_key(db_path: &Path) -> PathBuf {
+    // The file may not exist yet, so resolve its directory instead
+    match (db_path.parent(), db_path.file_name()) {
+        (Some(dir), Some(name)) => std::fs::canonicalize(dir)
+            .map(|dir| dir.join(name))
+            .unwrap_or_else(|_| db_path.to_path_buf()),
+        _ => db_path.to_path_buf(),
+    }
+}
+
+impl WriteQueue {
+    /// The queue for `db_path`, shared within the process
+    pub fn for_path(db_path: &Path) -> Arc<WriteQueue> {
+        static QUEUES: OnceLock<Mutex<HashMap<PathBuf, Arc<WriteQueue>>>> = OnceLock::new();
+        QUEUES
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap()
+            .entry(database_key(db_path))
+            .or_insert_with(|| Arc::new(WriteQueue::new()))
+            .clone()
+    }
+
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(QueueState::default()),
+            turn: Condvar::new(),
+            writes: AtomicU64::new(0),
+        }
+    }
+
+    /// Count a finished write, so caches of the data know it changed
+    pub fn record_write(&self) {
+        self.writes.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Writes counted so far
+    pub fn writes(&self) -> u64 {
+        self.writes.load(Ordering::SeqCst)
+    }
+
+    /// Wait for this thread's turn; access is released when the slot drops
+    pub fn acquire(self: &Arc<Self>) -> QueueSlot {
+        let priority = PRIORITY.with(Cell::get);
+        let started = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        state.stats[priority.index()].waiting += 1;
+
+        loop {
+            let yielding = priority == Priority::Background
+                && state.stats[Priority::Interactive.index()].waiting > 0
+                && started.elapsed() < MAX_BACKGROUND_DEFER;
+            if !state.held && !yielding {
+                break;
+            }
+            state = if yielding {
+                self.turn
+                    .wait_timeout(
+                        state,
+                        MAX_BACKGROUND_DEFER.saturating_sub(started.elapsed()),
+                    )
+                    .unwrap()
+                    .0
+            } else {
+                self.turn.wait(state).unwrap()
+            };
+        }
+
+        state.held = true;
+        let waited_ms = started.elapsed().as_millis() as u64;
+        let stats = &mut state.stats[priority.index()];
+        stats.waiting -= 1;
+        stats.acquired += 1;
+        stats.total_wait_ms += waited_ms;
+        stats.max_wait_ms = stats.max_wait_ms.max(waited_ms);
+
+        QueueSlot {
+            queue: Arc::clone(self),
+        }
+    }
+
+    /// Counters for both priorities
+    pub fn stats(&self) -> WriteQueueStats {
+        let state = self.state.lock().unwrap();
+        WriteQueueStats {
+            interactive: state.stats[Priority::Interactive.index()].clone(),
+            background: state.stats[Priority::Background.index()].clone(),
+        }
+    }
+}
+
+/// A turn at the database; the next waiter goes when this drops
+pub struct QueueSlot {
+    queue: Arc<WriteQueue>,
+}
+
+impl Drop for QueueSlot {
+    fn drop(&mut self) {
+        self.queue.state.lock().unwrap().held = false;
+        self.queue.turn.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+
+    #[test]
+    fn test_interactive_goes_before_background() {
+        let queue = Arc::new(WriteQueue::new());
+        let slot = queue.acquire();
+
+        let (order_tx, order_rx) = mpsc::channel();
+        let background = {
+            let (queue, order_tx) = (queue.clone(), order_tx.clone());
+            thread::spawn(move || {
+                let _scope = background();
+                let _slot = queue.acquire();
+                order_tx.send("background").unwrap();
+            })
+        };
+        while queue.stats().background.waiting == 0 {
+            thread::yield_now();
+        }
+        let interactive = {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                let _slot = queue.acquire();
+                order_tx.send("interactive").unwrap();
+            })
+        };
+        while queue.stats().interactive.waiting == 0 {
+            thread::yield_now();
+        }
+
+        drop(slot);
+        background.join().unwrap();
+        interactive.join().unwrap();
+        let order: Vec<_> = order_rx.iter().collect();
+        assert_eq!(order, ["interactive", "background"]);
+
+        let stats = queue.stats();
+        assert_eq!(stats.interactive.acquired, 2);
+        assert_eq!(stats.background.acquired, 1);
+        assert_eq!(stats.background.waiting, 0);
+    }
+
+    #[test]
+    fn test_background_scope_restores_priority() {
+        {
+            let _scope = background();
+            assert_eq!(PRIORITY.with(Cell::get), Priority::Background);
+        }
+        assert_eq!(PRIORITY.with(Cell::get), Priority::Interactive);
+    }
+}
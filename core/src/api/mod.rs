@@ -0,0 +1,21 @@
+//! Stable public API for embedders
+//!
+//! The services behind `TreelineContext` change shape whenever the CLI or
+//! the app needs them to. Plugin hosts and other programs embedding
+//! treeline-core should go through this module instead: each version
+//! (`v1`, ...) is a facade with its own request and response types, which
+//! only change in ways semver allows within a major version of the crate.
+//!
+//! Within a version:
+//! - requests and responses are `#[non_exhaustive]`, so fields can be
+//!   added; build requests with their `new` and setter methods
+//! - existing fields, methods and serialized names don't change or go away
+//! - errors carry an [`v1::ErrorKind`] to match on, not just a message
+//!
+//! Breaking changes get a new version module, next to the old one for at
+//! least one major release.
+
+pub mod v1;
+
+/// The newest API version this build provides
+pub const LATEST_VERSION: u32 = 1;
@@ -0,0 +1,607 @@
+//! Version 1 of the stable API
+//!
+//! [`Client`] opens a Treeline data directory and answers requests with
+//! plain, serializable responses. Amounts are decimals, dates ISO 8601 and
+//! IDs strings, the same as in `--json` output.
+
+use std::fmt;
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::services::{AccountInfo, IntegrationSyncResult, TransactionFilter, TransactionInfo};
+use crate::TreelineContext;
+
+// =============================================================================
+// Errors
+// =============================================================================
+
+/// What kind of failure an [`ApiError`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// An account, transaction or integration that doesn't exist
+    NotFound,
+    /// The request itself is wrong, e.g. a write statement passed to `query`
+    InvalidRequest,
+    /// A sync provider failed
+    Sync,
+    /// The database is encrypted and the password is missing or wrong
+    Locked,
+    /// Anything else; the message says what
+    Internal,
+}
+
+/// Why a request failed
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[non_exhaustive]
+pub struct ApiError {
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        let kind = match error.downcast_ref::<crate::Error>() {
+            Some(crate::Error::NotFound(_)) => ErrorKind::NotFound,
+            Some(crate::Error::Validation(_) | crate::Error::Config(_)) => {
+                ErrorKind::InvalidRequest
+            }
+            Some(crate::Error::Sync(_)) => ErrorKind::Sync,
+            Some(crate::Error::Encryption(_)) => ErrorKind::Locked,
+            _ => ErrorKind::Internal,
+        };
+        Self {
+            kind,
+            message: format!("{:#}", error),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ApiError>;
+
+// =============================================================================
+// Requests
+// =============================================================================
+
+/// Which accounts `accounts` lists
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct ListAccountsRequest {
+    /// Include archived accounts too
+    pub include_archived: bool,
+}
+
+impl ListAccountsRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn include_archived(mut self, include_archived: bool) -> Self {
+        self.include_archived = include_archived;
+        self
+    }
+}
+
+/// Which transactions `transactions` lists; every set field must match
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct ListTransactionsRequest {
+    pub account_id: Option<String>,
+    /// First transaction date (inclusive)
+    pub start: Option<NaiveDate>,
+    /// Last transaction date (inclusive)
+    pub end: Option<NaiveDate>,
+    /// Has this tag (case-insensitive)
+    pub tag: Option<String>,
+    /// Description contains this text (case-insensitive)
+    pub text: Option<String>,
+    /// Newest first, at most this many
+    pub limit: Option<usize>,
+}
+
+impl ListTransactionsRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn account_id(mut self, account_id: impl Into<String>) -> Self {
+        self.account_id = Some(account_id.into());
+        self
+    }
+
+    pub fn between(mut self, start: NaiveDate, end: NaiveDate) -> Self {
+        self.start = Some(start);
+        self.end = Some(end);
+        self
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+/// A read-only SQL query against the `accounts`, `transactions` and other views
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[non_exhaustive]
+pub struct QueryRequest {
+    pub sql: String,
+}
+
+impl QueryRequest {
+    pub fn new(sql: impl Into<String>) -> Self {
+        Self { sql: sql.into() }
+    }
+}
+
+/// What `sync` syncs
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct SyncRequest {
+    /// Only this integration, e.g. "simplefin" (None = all of them)
+    pub integration: Option<String>,
+    /// Fetch and report without saving anything
+    pub dry_run: bool,
+    /// Update balances but skip transactions
+    pub balances_only: bool,
+    /// Fetch the whole history window again instead of from the last sync
+    pub full: bool,
+}
+
+impl SyncRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn integration(mut self, integration: impl Into<String>) -> Self {
+        self.integration = Some(integration.into());
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn balances_only(mut self, balances_only: bool) -> Self {
+        self.balances_only = balances_only;
+        self
+    }
+
+    pub fn full(mut self, full: bool) -> Self {
+        self.full = full;
+        self
+    }
+}
+
+/// Tags to put on transactions
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[non_exhaustive]
+pub struct TagRequest {
+    pub transaction_ids: Vec<String>,
+    pub tags: Vec<String>,
+    /// Replace the transactions' tags instead of adding to them
+    #[serde(default)]
+    pub replace: bool,
+}
+
+impl TagRequest {
+    pub fn new(transaction_ids: Vec<String>, tags: Vec<String>) -> Self {
+        Self {
+            transaction_ids,
+            tags,
+            replace: false,
+        }
+    }
+
+    pub fn replace(mut self, replace: bool) -> Self {
+        self.replace = replace;
+        self
+    }
+}
+
+// =============================================================================
+// Responses
+// =============================================================================
+
+/// Totals across the data directory
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[non_exhaustive]
+pub struct StatusResponse {
+    pub accounts: i64,
+    pub transactions: i64,
+    pub balance_snapshots: i64,
+    /// Names of the configured integrations
+    pub integrations: Vec<String>,
+    pub earliest_transaction: Option<String>,
+    pub latest_transaction: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[non_exhaustive]
+pub struct AccountResponse {
+    pub id: String,
+    pub name: String,
+    pub nickname: Option<String>,
+    pub account_type: Option<String>,
+    /// "asset" or "liability"
+    pub classification: Option<String>,
+    pub currency: String,
+    /// Latest balance snapshot
+    #[schemars(with = "Option<String>")]
+    pub balance: Option<Decimal>,
+    pub institution_name: Option<String>,
+    /// The integration it syncs from; None for manual and imported accounts
+    pub integration: Option<String>,
+    /// None while the account is active
+    pub archived_at: Option<DateTime<Utc>>,
+}
+
+impl From<AccountInfo> for AccountResponse {
+    fn from(info: AccountInfo) -> Self {
+        Self {
+            id: info.id,
+            name: info.name,
+            nickname: info.nickname,
+            account_type: info.account_type,
+            classification: info.classification,
+            currency: info.currency,
+            balance: info.balance,
+            institution_name: info.institution_name,
+            integration: info.integration,
+            archived_at: info.archived_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[non_exhaustive]
+pub struct TransactionResponse {
+    pub id: String,
+    pub account_id: String,
+    pub account_name: String,
+    pub date: NaiveDate,
+    pub posted_date: NaiveDate,
+    pub description: Option<String>,
+    #[schemars(with = "String")]
+    pub amount: Decimal,
+    pub tags: Vec<String>,
+    /// Set on the parts of a split transaction
+    pub parent_id: Option<String>,
+    /// The integration it came from, or "csv_import", "split" or "manual"
+    pub source: String,
+    pub excluded_from_reports: bool,
+    /// Not posted by the bank yet
+    pub pending: bool,
+}
+
+impl From<TransactionInfo> for TransactionResponse {
+    fn from(info: TransactionInfo) -> Self {
+        Self {
+            id: info.id,
+            account_id: info.account_id,
+            account_name: info.account_name,
+            date: info.date,
+            posted_date: info.posted_date,
+            description: info.description,
+            amount: info.amount,
+            tags: info.tags,
+            parent_id: info.parent_id,
+            source: info.source,
+            excluded_from_reports: info.excluded_from_reports,
+            pending: info.pending,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[non_exhaustive]
+pub struct QueryResponse {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[non_exhaustive]
+pub struct SyncResponse {
+    pub integrations: Vec<IntegrationSyncResponse>,
+}
+
+/// How one integration's sync went
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[non_exhaustive]
+pub struct IntegrationSyncResponse {
+    pub integration: String,
+    /// New accounts
+    pub accounts_synced: i64,
+    /// New transactions
+    pub transactions_synced: i64,
+    /// Transactions fetched that were already stored
+    pub transactions_skipped: i64,
+    /// Problems that didn't stop the sync, e.g. one bank failing
+    pub warnings: Vec<String>,
+    /// Why the integration failed; the others still sync
+    pub error: Option<String>,
+}
+
+impl From<IntegrationSyncResult> for IntegrationSyncResponse {
+    fn from(result: IntegrationSyncResult) -> Self {
+        Self {
+            integration: result.integration,
+            accounts_synced: result.accounts_synced,
+            transactions_synced: result.transactions_synced,
+            transactions_skipped: result.transaction_stats.skipped,
+            warnings: result.provider_warnings,
+            error: result.error,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[non_exhaustive]
+pub struct TagResponse {
+    /// Transactions tagged
+    pub succeeded: i64,
+    /// Transactions that couldn't be, e.g. an unknown ID
+    pub failed: i64,
+}
+
+// =============================================================================
+// Client
+// =============================================================================
+
+/// A Treeline data directory, opened for embedding
+pub struct Client {
+    ctx: TreelineContext,
+}
+
+impl Client {
+    /// Open the data directory (e.g. `~/.treeline`), creating the database
+    /// if there is none
+    ///
+    /// `password` unlocks an encrypted database.
+    pub fn open(treeline_dir: &Path, password: Option<&str>) -> Result<Self> {
+        Ok(Self {
+            ctx: TreelineContext::new(treeline_dir, password)?,
+        })
+    }
+
+    /// Wrap a context the host already opened
+    pub fn from_context(ctx: TreelineContext) -> Self {
+        Self { ctx }
+    }
+
+    pub fn status(&self) -> Result<StatusResponse> {
+        let status = self.ctx.status_service.get_status()?;
+        Ok(StatusResponse {
+            accounts: status.total_accounts,
+            transactions: status.total_transactions,
+            balance_snapshots: status.total_snapshots,
+            integrations: status.integration_names,
+            earliest_transaction: status.date_range.earliest,
+            latest_transaction: status.date_range.latest,
+        })
+    }
+
+    /// Accounts sorted by name
+    pub fn accounts(&self, request: &ListAccountsRequest) -> Result<Vec<AccountResponse>> {
+        let accounts = self.ctx.account_service.list(request.include_archived)?;
+        Ok(accounts.into_iter().map(AccountResponse::from).collect())
+    }
+
+    /// Matching transactions, newest first
+    pub fn transactions(
+        &self,
+        request: &ListTransactionsRequest,
+    ) -> Result<Vec<TransactionResponse>> {
+        let filter = TransactionFilter {
+            account_id: request.account_id.clone(),
+            start: request.start,
+            end: request.end,
+            tag: request.tag.clone(),
+            text: request.text.clone(),
+            limit: request.limit,
+            ..TransactionFilter::default()
+        };
+        let transactions = self.ctx.transaction_service.list(&filter)?;
+        Ok(transactions
+            .into_iter()
+            .map(TransactionResponse::from)
+            .collect())
+    }
+
+    /// Run a query on a read-only connection; writes are rejected
+    pub fn query(&self, request: &QueryRequest) -> Result<QueryResponse> {
+        let result = self
+            .ctx
+            .query_service
+            .execute_readonly(&request.sql)
+            .map_err(|e| ApiError {
+                kind: ErrorKind::InvalidRequest,
+                message: format!("{:#}", e),
+            })?;
+        Ok(QueryResponse {
+            columns: result.columns,
+            rows: result.rows,
+        })
+    }
+
+    /// Sync integrations; one that fails is reported in its entry
+    pub fn sync(&self, request: &SyncRequest) -> Result<SyncResponse> {
+        let result = self.ctx.sync_service.sync(
+            request.integration.as_deref(),
+            request.dry_run,
+            request.balances_only,
+            request.full,
+        )?;
+        Ok(SyncResponse {
+            integrations: result
+                .results
+                .into_iter()
+                .map(IntegrationSyncResponse::from)
+                .collect(),
+        })
+    }
+
+    pub fn tag(&self, request: &TagRequest) -> Result<TagResponse> {
+        let result = self.ctx.tag_service.apply_tags(
+            &request.transaction_ids,
+            &request.tags,
+            request.replace,
+        )?;
+        Ok(TagResponse {
+            succeeded: result.succeeded,
+            failed: result.failed,
+        })
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    /// Field names of a serialized object, sorted
+    fn fields(value: serde_json::Value) -> Vec<String> {
+        let mut fields: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+        fields.sort();
+        fields
+    }
+
+    #[test]
+    fn test_requests_accept_partial_json() {
+        let request: ListTransactionsRequest =
+            serde_json::from_value(json!({ "tag": "groceries", "limit": 5 })).unwrap();
+        assert_eq!(request.tag.as_deref(), Some("groceries"));
+        assert_eq!(request.limit, Some(5));
+        assert_eq!(request.account_id, None);
+
+        let request: SyncRequest = serde_json::from_value(json!({})).unwrap();
+        assert!(!request.dry_run);
+
+        let request: TagRequest =
+            serde_json::from_value(json!({ "transaction_ids": ["t1"], "tags": ["x"] })).unwrap();
+        assert!(!request.replace);
+    }
+
+    /// Renaming or removing a field breaks embedders; adding one is fine but
+    /// belongs in this list too
+    #[test]
+    fn test_response_fields_are_stable() {
+        let status = StatusResponse {
+            accounts: 0,
+            transactions: 0,
+            balance_snapshots: 0,
+            integrations: vec![],
+            earliest_transaction: None,
+            latest_transaction: None,
+        };
+        assert_eq!(
+            fields(serde_json::to_value(status).unwrap()),
+            [
+                "accounts",
+                "balance_snapshots",
+                "earliest_transaction",
+                "integrations",
+                "latest_transaction",
+                "transactions",
+            ]
+        );
+
+        let sync = IntegrationSyncResponse {
+            integration: "simplefin".to_string(),
+            accounts_synced: 0,
+            transactions_synced: 0,
+            transactions_skipped: 0,
+            warnings: vec![],
+            error: None,
+        };
+        assert_eq!(
+            fields(serde_json::to_value(sync).unwrap()),
+            [
+                "accounts_synced",
+                "error",
+                "integration",
+                "transactions_skipped",
+                "transactions_synced",
+                "warnings",
+            ]
+        );
+
+        let error = ApiError {
+            kind: ErrorKind::NotFound,
+            message: "Account not found".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(error).unwrap(),
+            json!({ "kind": "not_found", "message": "Account not found" })
+        );
+    }
+
+    #[test]
+    fn test_error_kinds() {
+        let error = ApiError::from(anyhow::Error::new(crate::Error::NotFound(
+            "Transaction t1".to_string(),
+        )));
+        assert_eq!(error.kind, ErrorKind::NotFound);
+        let error = ApiError::from(anyhow::anyhow!("disk on fire"));
+        assert_eq!(error.kind, ErrorKind::Internal);
+        assert_eq!(error.to_string(), "disk on fire");
+    }
+
+    #[test]
+    fn test_client_on_empty_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let client = Client::open(temp_dir.path(), None).unwrap();
+
+        let status = client.status().unwrap();
+        assert_eq!(status.accounts, 0);
+        assert!(client
+            .accounts(&ListAccountsRequest::new())
+            .unwrap()
+            .is_empty());
+        assert!(client
+            .transactions(&ListTransactionsRequest::new().limit(10))
+            .unwrap()
+            .is_empty());
+
+        let result = client.query(&QueryRequest::new("SELECT 1 AS one")).unwrap();
+        assert_eq!(result.columns, ["one"]);
+
+        let error = client
+            .query(&QueryRequest::new("DELETE FROM sys_accounts"))
+            .unwrap_err();
+        assert_eq!(error.kind, ErrorKind::InvalidRequest);
+    }
+}
@@ -10,11 +10,15 @@
 //! ```
 
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
+use crate::domain::{Argon2Params, BusinessCalendar, HolidayCalendar};
+use crate::json_file::{read_json, write_json};
+
 /// Raw settings.json structure (matching Python/App format)
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -34,10 +38,160 @@ struct SettingsFile {
 struct AppSettings {
     #[serde(default)]
     demo_mode: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    argon2_params: Option<Argon2Params>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hook_timeout_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    home_assistant: Option<HomeAssistantSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    device_sync: Option<DeviceSyncSettings>,
+    #[serde(default)]
+    usage_ping: bool,
+    #[serde(default, skip_serializing_if = "LoggingSettings::is_default")]
+    logging: LoggingSettings,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    query_budget_share: Option<f64>,
+    #[serde(default, skip_serializing_if = "BackupCompression::is_default")]
+    backup_compression: BackupCompression,
+    #[serde(default, skip_serializing_if = "BusinessDaySettings::is_default")]
+    business_days: BusinessDaySettings,
     #[serde(flatten)]
     other: HashMap<String, serde_json::Value>,
 }
 
+/// Where `tl publish` sends sensors (settings.json `app.homeAssistant`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HomeAssistantSettings {
+    /// Base URL, e.g. http://homeassistant.local:8123
+    pub url: String,
+    /// Long-lived access token (TREELINE_HA_TOKEN overrides it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Publish after every successful sync
+    #[serde(default)]
+    pub publish_after_sync: bool,
+}
+
+/// Where `tl device-sync` exchanges changes (settings.json `app.deviceSync`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceSyncSettings {
+    /// Folder every device can read and write, e.g. a NAS share or synced folder
+    pub relay: PathBuf,
+    /// Random ID naming this device's folder on the relay
+    pub device_id: String,
+    /// Shown to the other devices
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_name: Option<String>,
+}
+
+/// How much of one category of events is logged
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    /// Only events carrying an error
+    Errors,
+    /// Errors plus what was done (commands, syncs, imports, clicks)
+    Actions,
+    /// Everything, including page views
+    #[default]
+    Full,
+}
+
+/// Kinds of log events that can get their own level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogCategory {
+    /// Desktop screens and clicks
+    Ui,
+    /// CLI commands
+    Cli,
+    /// Syncs with an integration
+    Sync,
+    /// Everything else (imports, backups, startup)
+    App,
+}
+
+/// Which log events are recorded (settings.json `app.logging`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoggingSettings {
+    /// Level for categories without their own
+    #[serde(default)]
+    pub level: LogLevel,
+    /// Per-category overrides
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub categories: HashMap<LogCategory, LogLevel>,
+}
+
+impl LoggingSettings {
+    /// Effective level for a category
+    pub fn level_for(&self, category: LogCategory) -> LogLevel {
+        self.categories
+            .get(&category)
+            .copied()
+            .unwrap_or(self.level)
+    }
+
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// How backup archives are compressed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionMethod {
+    /// Readable by any unzip tool
+    #[default]
+    Deflate,
+    /// Smaller and faster than deflate, but older unzip tools can't read it
+    Zstd,
+    /// Stored as is; fastest, for encrypted databases that barely shrink
+    None,
+}
+
+/// Backup compression (settings.json `app.backupCompression`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupCompression {
+    #[serde(default)]
+    pub method: CompressionMethod,
+    /// Higher is smaller and slower: 1-9 for deflate, 1-22 for zstd
+    /// (None uses the method's default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<i64>,
+}
+
+impl BackupCompression {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Days banks are closed besides weekends (settings.json `app.businessDays`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusinessDaySettings {
+    #[serde(default)]
+    pub calendar: HolidayCalendar,
+    /// Closures the calendar doesn't know about
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_holidays: Vec<NaiveDate>,
+}
+
+impl BusinessDaySettings {
+    pub fn business_calendar(&self) -> BusinessCalendar {
+        BusinessCalendar::new(self.calendar, self.extra_holidays.iter().copied())
+    }
+
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ImportProfilesContainer {
@@ -52,6 +206,27 @@ struct ImportProfilesContainer {
 pub struct Config {
     pub demo_mode: bool,
     pub import_profiles: HashMap<String, ImportProfile>,
+    /// Profile name to use for each account ID (assigned in the desktop import dialog)
+    pub import_account_mappings: HashMap<String, String>,
+    /// Argon2 params chosen by `tl encrypt calibrate` (None uses the defaults)
+    pub argon2_params: Option<Argon2Params>,
+    /// How long a hook script may run before it is killed (None uses the default)
+    pub hook_timeout_secs: Option<u64>,
+    /// Home Assistant sensor publishing (None when not set up)
+    pub home_assistant: Option<HomeAssistantSettings>,
+    /// Device sync relay (None when not set up)
+    pub device_sync: Option<DeviceSyncSettings>,
+    /// Send the weekly anonymous usage ping (off unless the user opts in)
+    pub usage_ping: bool,
+    /// Log levels enforced by LoggingService
+    pub logging: LoggingSettings,
+    /// Share of desktop query time one view or plugin may use before
+    /// diagnostics flags it (None uses the default)
+    pub query_budget_share: Option<f64>,
+    /// How BackupService compresses new backups
+    pub backup_compression: BackupCompression,
+    /// Bank holidays that projected paydays move around
+    pub business_days: BusinessDaySettings,
     // Keep the raw settings for preservation when saving
     _raw_settings: SettingsFile,
 }
@@ -61,6 +236,16 @@ impl Default for Config {
         Self {
             demo_mode: false,
             import_profiles: HashMap::new(),
+            import_account_mappings: HashMap::new(),
+            argon2_params: None,
+            hook_timeout_secs: None,
+            home_assistant: None,
+            device_sync: None,
+            usage_ping: false,
+            logging: LoggingSettings::default(),
+            query_budget_share: None,
+            backup_compression: BackupCompression::default(),
+            business_days: BusinessDaySettings::default(),
             _raw_settings: SettingsFile::default(),
         }
     }
@@ -75,12 +260,7 @@ impl Config {
     pub fn load(treeline_dir: &Path) -> Result<Self> {
         let settings_path = treeline_dir.join("settings.json");
 
-        let raw: SettingsFile = if settings_path.exists() {
-            let content = std::fs::read_to_string(&settings_path)?;
-            serde_json::from_str(&content).unwrap_or_default()
-        } else {
-            SettingsFile::default()
-        };
+        let raw: SettingsFile = read_json(&settings_path)?.unwrap_or_default();
 
         // Check env var for demo mode override (for CI/testing)
         let demo_mode = match std::env::var("TREELINE_DEMO_MODE").ok().as_deref() {
@@ -92,6 +272,16 @@ impl Config {
         Ok(Self {
             demo_mode,
             import_profiles: raw.import_profiles.profiles.clone(),
+            import_account_mappings: raw.import_profiles.account_mappings.clone(),
+            argon2_params: raw.app.argon2_params.clone(),
+            hook_timeout_secs: raw.app.hook_timeout_secs,
+            home_assistant: raw.app.home_assistant.clone(),
+            device_sync: raw.app.device_sync.clone(),
+            usage_ping: raw.app.usage_ping,
+            logging: raw.app.logging.clone(),
+            query_budget_share: raw.app.query_budget_share,
+            backup_compression: raw.app.backup_compression,
+            business_days: raw.app.business_days.clone(),
             _raw_settings: raw,
         })
     }
@@ -102,20 +292,23 @@ impl Config {
         let settings_path = treeline_dir.join("settings.json");
 
         // Load existing settings to preserve fields we don't manage
-        let mut settings = if settings_path.exists() {
-            let content = std::fs::read_to_string(&settings_path)?;
-            serde_json::from_str::<SettingsFile>(&content).unwrap_or_default()
-        } else {
-            SettingsFile::default()
-        };
+        let mut settings: SettingsFile = read_json(&settings_path)?.unwrap_or_default();
 
         // Update only the fields we manage
         settings.app.demo_mode = self.demo_mode;
         settings.import_profiles.profiles = self.import_profiles.clone();
+        settings.import_profiles.account_mappings = self.import_account_mappings.clone();
+        settings.app.argon2_params = self.argon2_params.clone();
+        settings.app.hook_timeout_secs = self.hook_timeout_secs;
+        settings.app.home_assistant = self.home_assistant.clone();
+        settings.app.device_sync = self.device_sync.clone();
+        settings.app.usage_ping = self.usage_ping;
+        settings.app.logging = self.logging.clone();
+        settings.app.query_budget_share = self.query_budget_share;
+        settings.app.backup_compression = self.backup_compression;
+        settings.app.business_days = self.business_days.clone();
 
-        let content = serde_json::to_string_pretty(&settings)?;
-        std::fs::write(&settings_path, content)?;
-        Ok(())
+        write_json(&settings_path, &settings)
     }
 
     /// Enable demo mode
@@ -131,7 +324,7 @@ impl Config {
 
 /// Import profile for CSV imports
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", from = "ImportProfileFile")]
 pub struct ImportProfile {
     pub column_mappings: ColumnMappings,
     #[serde(default)]
@@ -142,6 +335,40 @@ pub struct ImportProfile {
     pub options: ImportOptions,
 }
 
+/// An import profile as found in settings.json, where profiles saved by
+/// older desktop versions keep `skipRows` under `options`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportProfileFile {
+    column_mappings: ColumnMappings,
+    #[serde(default)]
+    date_format: Option<String>,
+    #[serde(default)]
+    skip_rows: Option<usize>,
+    #[serde(default)]
+    options: ImportProfileFileOptions,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportProfileFileOptions {
+    #[serde(flatten)]
+    options: ImportOptions,
+    #[serde(default)]
+    skip_rows: Option<usize>,
+}
+
+impl From<ImportProfileFile> for ImportProfile {
+    fn from(file: ImportProfileFile) -> Self {
+        Self {
+            column_mappings: file.column_mappings,
+            date_format: file.date_format,
+            skip_rows: file.skip_rows.or(file.options.skip_rows).unwrap_or(0),
+            options: file.options.options,
+        }
+    }
+}
+
 /// Import options for profile storage
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -153,6 +380,21 @@ pub struct ImportOptions {
     /// Number format string: "us", "eu", or "eu_space". None defaults to US.
     #[serde(default)]
     pub number_format: Option<String>,
+    /// Per-column number format overrides (column name -> format string)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub column_number_formats: HashMap<String, String>,
+    /// Account assignment for account-column values (CSV value -> account ID or name)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub account_map: HashMap<String, String>,
+    /// Create accounts for account-column values that match no existing account
+    #[serde(default)]
+    pub create_missing_accounts: bool,
+    /// Translation of the bank's category names to tags (empty tag drops the category)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub category_map: HashMap<String, String>,
+    /// The file has no header row; columns are named "Column 1".."Column N"
+    #[serde(default)]
+    pub no_header: bool,
 }
 
 /// Column mappings for CSV import
@@ -169,6 +411,27 @@ pub struct ColumnMappings {
     /// Optional running balance column for balance snapshots
     #[serde(default)]
     pub balance: Option<String>,
+    /// Optional account column for files that cover several accounts
+    #[serde(default)]
+    pub account: Option<String>,
+    /// Optional category column, imported as a tag
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Optional tags column (values separated by commas, semicolons or pipes)
+    #[serde(default)]
+    pub tags: Option<String>,
+    /// Optional check number column
+    #[serde(default, alias = "checkNumber")]
+    pub check_number: Option<String>,
+    /// Optional reference/confirmation number column
+    #[serde(default)]
+    pub reference: Option<String>,
+    /// Optional debit/credit indicator column for unsigned amounts
+    #[serde(default)]
+    pub direction: Option<String>,
+    /// Optional currency column for files mixing currencies (e.g. Wise, Revolut)
+    #[serde(default)]
+    pub currency: Option<String>,
 }
 
 impl Default for ColumnMappings {
@@ -180,6 +443,13 @@ impl Default for ColumnMappings {
             credit: None,
             debit: None,
             balance: None,
+            account: None,
+            category: None,
+            tags: None,
+            check_number: None,
+            reference: None,
+            direction: None,
+            currency: None,
         }
     }
 }
@@ -1,15 +1,19 @@
 //! Account domain model
 
+use std::fmt;
+use std::str::FromStr;
+
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use serde_json::Value as JsonValue;
 use uuid::Uuid;
 
+use super::ProviderData;
+
 /// A financial account owned by the user
-/// Note: account_type is a freeform string using Plaid nomenclature.
-/// Common values include "depository", "credit", "investment", "loan", "other"
-/// but any string is accepted.
+/// account_type holds an [`AccountType`] in its string form ("checking",
+/// "credit_card", ...), or None when the type isn't known.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Account {
     pub id: Uuid,
@@ -35,46 +39,11 @@ pub struct Account {
     pub is_manual: bool,
 
     // =========================================================================
-    // SimpleFIN: ALL fields from API (https://www.simplefin.org/protocol.html)
-    // =========================================================================
-    /// SimpleFIN account ID (required for dedup)
-    pub sf_id: Option<String>,
-    /// Account name
-    pub sf_name: Option<String>,
-    /// ISO 4217 currency code
-    pub sf_currency: Option<String>,
-    /// Raw balance string
-    pub sf_balance: Option<String>,
-    /// Available balance (optional)
-    pub sf_available_balance: Option<String>,
-    /// UNIX timestamp of balance
-    pub sf_balance_date: Option<i64>,
-    /// Institution name
-    pub sf_org_name: Option<String>,
-    /// Institution URL
-    pub sf_org_url: Option<String>,
-    /// Institution domain
-    pub sf_org_domain: Option<String>,
-    /// Extra blob pass-through (optional)
-    pub sf_extra: Option<JsonValue>,
-
+    // Sync providers
     // =========================================================================
-    // Lunchflow: ALL fields from API
-    // =========================================================================
-    /// Lunchflow account ID (required for dedup)
-    pub lf_id: Option<String>,
-    /// Account name
-    pub lf_name: Option<String>,
-    /// Bank/institution name
-    pub lf_institution_name: Option<String>,
-    /// Logo URL
-    pub lf_institution_logo: Option<String>,
-    /// Provider: "gocardless", "quiltt", etc.
-    pub lf_provider: Option<String>,
-    /// Currency code
-    pub lf_currency: Option<String>,
-    /// Status: "ACTIVE", "DISCONNECTED", "ERROR"
-    pub lf_status: Option<String>,
+    /// IDs and raw fields from each provider that has synced this account
+    #[serde(flatten)]
+    pub provider: ProviderData,
 }
 
 impl Account {
@@ -96,35 +65,20 @@ impl Account {
             updated_at: now,
             // Manual flag
             is_manual: false,
-            // SimpleFIN fields
-            sf_id: None,
-            sf_name: None,
-            sf_currency: None,
-            sf_balance: None,
-            sf_available_balance: None,
-            sf_balance_date: None,
-            sf_org_name: None,
-            sf_org_url: None,
-            sf_org_domain: None,
-            sf_extra: None,
-            // Lunchflow fields
-            lf_id: None,
-            lf_name: None,
-            lf_institution_name: None,
-            lf_institution_logo: None,
-            lf_provider: None,
-            lf_currency: None,
-            lf_status: None,
+            // Sync providers
+            provider: ProviderData::default(),
         }
     }
 
-    /// Compute classification based on account_type (Plaid nomenclature)
-    /// credit and loan are liabilities, everything else is an asset
+    /// Compute classification based on account_type
+    /// Credit cards, loans and mortgages are liabilities, everything else
+    /// (including unknown types) is an asset
     pub fn compute_classification(account_type: Option<&str>) -> String {
-        match account_type.map(|t| t.to_lowercase()).as_deref() {
-            Some("credit") | Some("loan") => "liability".to_string(),
-            _ => "asset".to_string(),
-        }
+        account_type
+            .and_then(|t| t.parse::<AccountType>().ok())
+            .unwrap_or(AccountType::Other)
+            .classification()
+            .to_string()
     }
 
     /// Normalize currency code to uppercase
@@ -144,6 +98,224 @@ impl Account {
     }
 }
 
+/// The kinds of account Treeline knows, which decide asset vs liability
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountType {
+    Checking,
+    Savings,
+    Cash,
+    CreditCard,
+    Loan,
+    Mortgage,
+    Brokerage,
+    Retirement,
+    Crypto,
+    Property,
+    Vehicle,
+    Other,
+}
+
+impl AccountType {
+    pub const ALL: [AccountType; 12] = [
+        AccountType::Checking,
+        AccountType::Savings,
+        AccountType::Cash,
+        AccountType::CreditCard,
+        AccountType::Loan,
+        AccountType::Mortgage,
+        AccountType::Brokerage,
+        AccountType::Retirement,
+        AccountType::Crypto,
+        AccountType::Property,
+        AccountType::Vehicle,
+        AccountType::Other,
+    ];
+
+    /// The value stored in sys_accounts.account_type
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountType::Checking => "checking",
+            AccountType::Savings => "savings",
+            AccountType::Cash => "cash",
+            AccountType::CreditCard => "credit_card",
+            AccountType::Loan => "loan",
+            AccountType::Mortgage => "mortgage",
+            AccountType::Brokerage => "brokerage",
+            AccountType::Retirement => "retirement",
+            AccountType::Crypto => "crypto",
+            AccountType::Property => "property",
+            AccountType::Vehicle => "vehicle",
+            AccountType::Other => "other",
+        }
+    }
+
+    /// "asset" or "liability"
+    pub fn classification(&self) -> &'static str {
+        match self {
+            AccountType::CreditCard | AccountType::Loan | AccountType::Mortgage => "liability",
+            _ => "asset",
+        }
+    }
+
+    /// Guess the type from what a provider tells us about an account
+    ///
+    /// SimpleFIN and Lunchflow send no account type, so this goes by words
+    /// in the account name ("Sapphire Visa", "Roth IRA"), falling back to
+    /// the institution for crypto exchanges. None when nothing matches.
+    pub fn infer(account_name: &str, institution: Option<&str>) -> Option<AccountType> {
+        let text = account_name
+            .to_lowercase()
+            .replace("credit union", "")
+            .replace(['(', ')', '.'], "");
+        let words: Vec<&str> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .collect();
+        let has = |candidates: &[&str]| candidates.iter().any(|c| words.contains(c));
+        let has_phrase = |phrases: &[&str]| phrases.iter().any(|p| text.contains(p));
+
+        // Most specific first: "auto loan" is a loan, "home equity" a mortgage
+        let inferred = if has(&["mortgage", "heloc"]) || has_phrase(&["home equity"]) {
+            Some(AccountType::Mortgage)
+        } else if has(&["loan", "loans", "lending"]) {
+            Some(AccountType::Loan)
+        } else if has(&["credit", "card", "visa", "mastercard", "amex"])
+            || has_phrase(&["american express"])
+        {
+            Some(AccountType::CreditCard)
+        } else if has(&[
+            "401k",
+            "403b",
+            "457b",
+            "ira",
+            "roth",
+            "sep",
+            "pension",
+            "retirement",
+        ]) || has(&["tsp", "hsa"])
+        {
+            Some(AccountType::Retirement)
+        } else if has(&["crypto", "bitcoin", "btc", "ethereum", "eth", "wallet"]) {
+            Some(AccountType::Crypto)
+        } else if has(&[
+            "brokerage",
+            "investment",
+            "investments",
+            "invest",
+            "trading",
+        ]) {
+            Some(AccountType::Brokerage)
+        } else if has(&["savings", "saving", "cd"]) || has_phrase(&["money market"]) {
+            Some(AccountType::Savings)
+        } else if has(&["checking", "chequing", "current"]) {
+            Some(AccountType::Checking)
+        } else if has(&["property", "house", "home"]) || has_phrase(&["real estate"]) {
+            Some(AccountType::Property)
+        } else if has(&["vehicle", "car", "truck"]) {
+            Some(AccountType::Vehicle)
+        } else {
+            None
+        };
+
+        inferred.or_else(|| {
+            let institution = institution?.to_lowercase();
+            ["coinbase", "kraken", "gemini", "binance", "crypto.com"]
+                .iter()
+                .any(|exchange| institution.contains(exchange))
+                .then_some(AccountType::Crypto)
+        })
+    }
+}
+
+impl fmt::Display for AccountType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AccountType {
+    type Err = String;
+
+    /// Parse a type name, also accepting the Plaid-style values older
+    /// versions stored ("depository", "credit", "investment")
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_lowercase().replace([' ', '-'], "_");
+        let account_type = match normalized.as_str() {
+            "depository" => AccountType::Checking,
+            "credit" => AccountType::CreditCard,
+            "investment" => AccountType::Brokerage,
+            other => *AccountType::ALL
+                .iter()
+                .find(|t| t.as_str() == other)
+                .ok_or_else(|| {
+                    let names: Vec<&str> = AccountType::ALL.iter().map(|t| t.as_str()).collect();
+                    format!(
+                        "Unknown account type '{}'. Expected one of: {}",
+                        s.trim(),
+                        names.join(", ")
+                    )
+                })?,
+        };
+        Ok(account_type)
+    }
+}
+
+/// How an account's source signs transaction amounts
+///
+/// Treeline stores money leaving an account as negative. Some providers and
+/// card exports report charges as positive instead; setting those accounts to
+/// `Inverted` flips every amount sync and import bring in, so spending reads
+/// the same across accounts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SignPolicy {
+    /// Outflows arrive negative, as Treeline stores them
+    #[default]
+    AsReported,
+    /// Outflows arrive positive and are flipped on the way in
+    Inverted,
+}
+
+impl SignPolicy {
+    /// The value stored in sys_accounts.sign_policy
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignPolicy::AsReported => "as_reported",
+            SignPolicy::Inverted => "inverted",
+        }
+    }
+
+    /// An amount as the source reported it, in Treeline's convention
+    pub fn apply(&self, amount: Decimal) -> Decimal {
+        match self {
+            SignPolicy::AsReported => amount,
+            SignPolicy::Inverted => -amount,
+        }
+    }
+}
+
+impl fmt::Display for SignPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SignPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace([' ', '-'], "_").as_str() {
+            "as_reported" => Ok(SignPolicy::AsReported),
+            "inverted" => Ok(SignPolicy::Inverted),
+            _ => Err(format!(
+                "Unknown sign policy '{}'. Expected as_reported or inverted",
+                s.trim()
+            )),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -162,4 +334,58 @@ mod tests {
         account.name = "".to_string();
         assert!(account.validate().is_err());
     }
+
+    #[test]
+    fn test_account_type_parsing_and_classification() {
+        assert_eq!("credit_card".parse(), Ok(AccountType::CreditCard));
+        assert_eq!("Credit Card".parse(), Ok(AccountType::CreditCard));
+        assert_eq!("depository".parse(), Ok(AccountType::Checking));
+        assert_eq!("investment".parse(), Ok(AccountType::Brokerage));
+        assert!("chequebook".parse::<AccountType>().is_err());
+
+        assert_eq!(Account::compute_classification(Some("credit")), "liability");
+        assert_eq!(
+            Account::compute_classification(Some("mortgage")),
+            "liability"
+        );
+        assert_eq!(Account::compute_classification(Some("brokerage")), "asset");
+        assert_eq!(Account::compute_classification(Some("anything")), "asset");
+        assert_eq!(Account::compute_classification(None), "asset");
+    }
+
+    #[test]
+    fn test_account_type_inference() {
+        let infer = |name| AccountType::infer(name, None);
+        assert_eq!(
+            infer("Sapphire Preferred Visa"),
+            Some(AccountType::CreditCard)
+        );
+        assert_eq!(
+            infer("Navy Federal Credit Union Checking"),
+            Some(AccountType::Checking)
+        );
+        assert_eq!(infer("Auto Loan"), Some(AccountType::Loan));
+        assert_eq!(infer("Home Equity Line"), Some(AccountType::Mortgage));
+        assert_eq!(infer("Fidelity 401(k)"), Some(AccountType::Retirement));
+        assert_eq!(infer("Roth IRA"), Some(AccountType::Retirement));
+        assert_eq!(infer("High Yield Savings"), Some(AccountType::Savings));
+        assert_eq!(infer("Individual Brokerage"), Some(AccountType::Brokerage));
+        assert_eq!(infer("Main Street House"), Some(AccountType::Property));
+        assert_eq!(infer("Everyday"), None);
+        assert_eq!(
+            AccountType::infer("Main", Some("Coinbase")),
+            Some(AccountType::Crypto)
+        );
+    }
+
+    #[test]
+    fn test_sign_policy() {
+        assert_eq!("as-reported".parse(), Ok(SignPolicy::AsReported));
+        assert_eq!("Inverted".parse(), Ok(SignPolicy::Inverted));
+        assert!("flipped".parse::<SignPolicy>().is_err());
+
+        let charge = Decimal::new(4250, 2);
+        assert_eq!(SignPolicy::AsReported.apply(charge), charge);
+        assert_eq!(SignPolicy::Inverted.apply(charge), -charge);
+    }
 }
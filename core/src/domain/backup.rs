@@ -1,10 +1,11 @@
 //! Backup domain model
 
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Metadata for a backup file
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BackupMetadata {
     /// Backup filename (e.g., "treeline-2025-01-15T10-30-00.duckdb")
     pub name: String,
@@ -12,6 +13,20 @@ pub struct BackupMetadata {
     pub created_at: DateTime<Utc>,
     /// File size in bytes
     pub size_bytes: u64,
+    /// How the database is compressed ("deflate", "zstd" or "none"); None
+    /// for legacy .duckdb backups
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<String>,
+    /// Compression level, when one was set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<i64>,
+    /// Size of the archived files before compression
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uncompressed_bytes: Option<u64>,
+    /// How long creating the backup took; None for backups made before
+    /// this was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
 }
 
 impl BackupMetadata {
@@ -20,24 +35,40 @@ impl BackupMetadata {
             name: name.into(),
             created_at,
             size_bytes,
+            compression: None,
+            level: None,
+            uncompressed_bytes: None,
+            duration_ms: None,
         }
     }
 
     /// Format size for human display
     pub fn size_display(&self) -> String {
-        const KB: u64 = 1024;
-        const MB: u64 = KB * 1024;
-        const GB: u64 = MB * 1024;
-
-        if self.size_bytes >= GB {
-            format!("{:.1} GB", self.size_bytes as f64 / GB as f64)
-        } else if self.size_bytes >= MB {
-            format!("{:.1} MB", self.size_bytes as f64 / MB as f64)
-        } else if self.size_bytes >= KB {
-            format!("{:.1} KB", self.size_bytes as f64 / KB as f64)
-        } else {
-            format!("{} bytes", self.size_bytes)
-        }
+        format_bytes(self.size_bytes)
+    }
+
+    /// Archive size as a share of the uncompressed files, e.g. 0.25
+    pub fn compression_ratio(&self) -> Option<f64> {
+        self.uncompressed_bytes
+            .filter(|&bytes| bytes > 0)
+            .map(|bytes| self.size_bytes as f64 / bytes as f64)
+    }
+}
+
+/// Format a byte count for human display, e.g. "1.5 GB"
+pub fn format_bytes(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = KB * 1024;
+    const GB: u64 = MB * 1024;
+
+    if bytes >= GB {
+        format!("{:.1} GB", bytes as f64 / GB as f64)
+    } else if bytes >= MB {
+        format!("{:.1} MB", bytes as f64 / MB as f64)
+    } else if bytes >= KB {
+        format!("{:.1} KB", bytes as f64 / KB as f64)
+    } else {
+        format!("{} bytes", bytes)
     }
 }
 
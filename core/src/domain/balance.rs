@@ -1,10 +1,14 @@
 //! Balance snapshot domain model
 
-use chrono::{DateTime, NaiveDateTime, Utc};
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::Money;
+
 /// Represents an account balance captured at a point in time
 /// Note: source is a freeform string to match Python CLI behavior.
 /// Common values include "sync", "manual", "backfill" but any string is accepted.
@@ -19,6 +23,8 @@ pub struct BalanceSnapshot {
     pub updated_at: DateTime<Utc>,
     /// How this snapshot was created (e.g., "sync", "manual", "backfill")
     pub source: Option<String>,
+    /// Import that created this snapshot, so undoing the import removes it
+    pub csv_batch_id: Option<String>,
 }
 
 impl BalanceSnapshot {
@@ -33,9 +39,15 @@ impl BalanceSnapshot {
             created_at: now,
             updated_at: now,
             source: None,
+            csv_batch_id: None,
         }
     }
 
+    /// The balance in the account's currency
+    pub fn money(&self, account_currency: &str) -> Money {
+        Money::new(self.balance, account_currency)
+    }
+
     /// Create a snapshot from a sync operation
     pub fn from_sync(account_id: Uuid, balance: Decimal, snapshot_time: NaiveDateTime) -> Self {
         let mut snapshot = Self::new(account_id, balance, snapshot_time);
@@ -58,6 +70,26 @@ impl BalanceSnapshot {
     }
 }
 
+/// The latest snapshot of each day, keyed by date
+///
+/// Accounts synced several times a day have several snapshots per day; the
+/// last one is that day's closing balance. Matches the `balance_snapshots_daily` view.
+pub fn latest_per_day(snapshots: &[BalanceSnapshot]) -> HashMap<NaiveDate, &BalanceSnapshot> {
+    let mut latest: HashMap<NaiveDate, &BalanceSnapshot> = HashMap::new();
+    for snapshot in snapshots {
+        let date = snapshot.snapshot_time.date();
+        match latest.get(&date) {
+            Some(current)
+                if (current.snapshot_time, current.updated_at)
+                    >= (snapshot.snapshot_time, snapshot.updated_at) => {}
+            _ => {
+                latest.insert(date, snapshot);
+            }
+        }
+    }
+    latest
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,4 +107,21 @@ mod tests {
         assert_eq!(snapshot.balance, balance);
         assert_eq!(snapshot.source, Some("sync".to_string()));
     }
+
+    #[test]
+    fn test_latest_per_day() {
+        let account_id = Uuid::new_v4();
+        let at = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+        let snapshots = vec![
+            BalanceSnapshot::from_sync(account_id, Decimal::new(100, 0), at("2025-01-15 08:00:00")),
+            BalanceSnapshot::from_sync(account_id, Decimal::new(80, 0), at("2025-01-15 18:00:00")),
+            BalanceSnapshot::from_sync(account_id, Decimal::new(90, 0), at("2025-01-15 12:00:00")),
+            BalanceSnapshot::from_sync(account_id, Decimal::new(70, 0), at("2025-01-16 09:00:00")),
+        ];
+
+        let latest = latest_per_day(&snapshots);
+        assert_eq!(latest.len(), 2);
+        let day = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        assert_eq!(latest[&day].balance, Decimal::new(80, 0));
+    }
 }
@@ -0,0 +1,276 @@
+//! Business day domain model - when banks actually move money
+//!
+//! A bill due on a Saturday is paid on Monday, and a payday that falls on a
+//! bank holiday arrives the day before. A [`BusinessCalendar`] knows which
+//! days banks are closed (weekends, a country's bank holidays and any extra
+//! dates) and [`Roll`] says which way a date moves off one.
+
+use std::collections::BTreeSet;
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Bank holidays to skip besides weekends
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HolidayCalendar {
+    /// Weekends only
+    #[default]
+    Weekends,
+    /// Federal Reserve holidays; one falling on a Sunday is observed Monday
+    Us,
+    /// Bank holidays in England and Wales, with substitute days
+    Uk,
+    /// Nationwide public holidays in Germany
+    De,
+}
+
+impl HolidayCalendar {
+    /// Bank holidays in `year`, as observed
+    pub fn holidays(&self, year: i32) -> Vec<NaiveDate> {
+        let fixed = |month, day| NaiveDate::from_ymd_opt(year, month, day);
+        let nth = |month, weekday, n| NaiveDate::from_weekday_of_month_opt(year, month, weekday, n);
+        let easter = easter_sunday(year);
+        let days: Vec<Option<NaiveDate>> = match self {
+            HolidayCalendar::Weekends => Vec::new(),
+            HolidayCalendar::Us => {
+                let mut days = vec![
+                    fixed(1, 1),
+                    nth(1, Weekday::Mon, 3),
+                    nth(2, Weekday::Mon, 3),
+                    last_weekday_of_month(year, 5, Weekday::Mon),
+                    fixed(7, 4),
+                    nth(9, Weekday::Mon, 1),
+                    nth(10, Weekday::Mon, 2),
+                    fixed(11, 11),
+                    nth(11, Weekday::Thu, 4),
+                    fixed(12, 25),
+                ];
+                if year >= 2022 {
+                    days.push(fixed(6, 19));
+                }
+                // The Fed stays open the Friday before a Saturday holiday
+                days.into_iter()
+                    .map(|day| {
+                        day.map(|d| match d.weekday() {
+                            Weekday::Sun => d + Duration::days(1),
+                            _ => d,
+                        })
+                    })
+                    .collect()
+            }
+            HolidayCalendar::Uk => {
+                let mut days = vec![
+                    easter.map(|e| e - Duration::days(2)),
+                    easter.map(|e| e + Duration::days(1)),
+                    nth(5, Weekday::Mon, 1),
+                    last_weekday_of_month(year, 5, Weekday::Mon),
+                    last_weekday_of_month(year, 8, Weekday::Mon),
+                ];
+                // New Year, Christmas and Boxing Day on a weekend move to the
+                // next weekday that isn't already a holiday
+                let mut taken: BTreeSet<NaiveDate> = BTreeSet::new();
+                let mut weekend = Vec::new();
+                for day in [fixed(1, 1), fixed(12, 25), fixed(12, 26)]
+                    .into_iter()
+                    .flatten()
+                {
+                    if is_weekend(day) {
+                        weekend.push(day);
+                    } else {
+                        taken.insert(day);
+                    }
+                }
+                for day in weekend {
+                    let mut substitute = day;
+                    while is_weekend(substitute) || taken.contains(&substitute) {
+                        substitute += Duration::days(1);
+                    }
+                    taken.insert(substitute);
+                }
+                days.extend(taken.into_iter().map(Some));
+                days
+            }
+            HolidayCalendar::De => vec![
+                fixed(1, 1),
+                easter.map(|e| e - Duration::days(2)),
+                easter.map(|e| e + Duration::days(1)),
+                fixed(5, 1),
+                easter.map(|e| e + Duration::days(39)),
+                easter.map(|e| e + Duration::days(50)),
+                fixed(10, 3),
+                fixed(12, 25),
+                fixed(12, 26),
+            ],
+        };
+        let mut days: Vec<NaiveDate> = days.into_iter().flatten().collect();
+        days.sort();
+        days.dedup();
+        days
+    }
+}
+
+/// Which way a date that isn't a business day moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Roll {
+    /// To the next business day, as bill due dates do
+    Following,
+    /// To the business day before, as paydays do
+    Preceding,
+    /// To the next business day unless that's in the next month, then back
+    ModifiedFollowing,
+}
+
+/// Days banks are open
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BusinessCalendar {
+    holidays: HolidayCalendar,
+    /// Closures the calendar doesn't know, e.g. a one-off royal funeral
+    extra_holidays: BTreeSet<NaiveDate>,
+}
+
+impl BusinessCalendar {
+    pub fn new(
+        holidays: HolidayCalendar,
+        extra_holidays: impl IntoIterator<Item = NaiveDate>,
+    ) -> Self {
+        Self {
+            holidays,
+            extra_holidays: extra_holidays.into_iter().collect(),
+        }
+    }
+
+    /// Whether banks are open on `date`
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        !is_weekend(date)
+            && !self.extra_holidays.contains(&date)
+            && !self.holidays.holidays(date.year()).contains(&date)
+    }
+
+    /// `date` if it's a business day, otherwise the business day `roll` moves it to
+    pub fn roll(&self, date: NaiveDate, roll: Roll) -> NaiveDate {
+        let step = |mut day: NaiveDate, days: i64| {
+            while !self.is_business_day(day) {
+                day += Duration::days(days);
+            }
+            day
+        };
+        match roll {
+            Roll::Following => step(date, 1),
+            Roll::Preceding => step(date, -1),
+            Roll::ModifiedFollowing => {
+                let following = step(date, 1);
+                if following.month() == date.month() {
+                    following
+                } else {
+                    step(date, -1)
+                }
+            }
+        }
+    }
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+fn last_weekday_of_month(year: i32, month: u32, weekday: Weekday) -> Option<NaiveDate> {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, 5)
+        .or_else(|| NaiveDate::from_weekday_of_month_opt(year, month, weekday, 4))
+}
+
+/// Easter Sunday in the Gregorian calendar (the anonymous computus)
+fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_easter() {
+        assert_eq!(easter_sunday(2024), Some(date("2024-03-31")));
+        assert_eq!(easter_sunday(2025), Some(date("2025-04-20")));
+        assert_eq!(easter_sunday(2026), Some(date("2026-04-05")));
+    }
+
+    #[test]
+    fn test_us_holidays_observed_on_monday() {
+        let holidays = HolidayCalendar::Us.holidays(2023);
+        // New Year's Day 2023 was a Sunday
+        assert!(holidays.contains(&date("2023-01-02")));
+        assert!(holidays.contains(&date("2023-05-29")));
+        assert!(holidays.contains(&date("2023-11-23")));
+        assert_eq!(holidays.len(), 11);
+        // July 4th 2026 is a Saturday and the Fed is open the day before
+        let calendar = BusinessCalendar::new(HolidayCalendar::Us, []);
+        assert!(calendar.is_business_day(date("2026-07-03")));
+    }
+
+    #[test]
+    fn test_uk_substitute_days() {
+        // Christmas 2021 was a Saturday, Boxing Day a Sunday
+        let holidays = HolidayCalendar::Uk.holidays(2021);
+        assert!(holidays.contains(&date("2021-12-27")));
+        assert!(holidays.contains(&date("2021-12-28")));
+        // Christmas 2022 was a Sunday, so its substitute follows Boxing Day
+        let holidays = HolidayCalendar::Uk.holidays(2022);
+        assert!(holidays.contains(&date("2022-12-26")));
+        assert!(holidays.contains(&date("2022-12-27")));
+        assert!(holidays.contains(&date("2022-01-03")));
+        assert!(holidays.contains(&date("2022-04-15")));
+    }
+
+    #[test]
+    fn test_roll() {
+        let calendar = BusinessCalendar::new(HolidayCalendar::Us, [date("2025-12-24")]);
+        // Independence Day 2025 was a Friday
+        assert_eq!(
+            calendar.roll(date("2025-07-04"), Roll::Following),
+            date("2025-07-07")
+        );
+        assert_eq!(
+            calendar.roll(date("2025-07-04"), Roll::Preceding),
+            date("2025-07-03")
+        );
+        assert_eq!(
+            calendar.roll(date("2025-07-08"), Roll::Preceding),
+            date("2025-07-08")
+        );
+        // Christmas and the extra day off before it
+        assert_eq!(
+            calendar.roll(date("2025-12-25"), Roll::Preceding),
+            date("2025-12-23")
+        );
+        // Saturday the 31st can't roll into June
+        assert_eq!(
+            calendar.roll(date("2025-05-31"), Roll::ModifiedFollowing),
+            date("2025-05-30")
+        );
+        assert_eq!(
+            BusinessCalendar::default().roll(date("2025-07-04"), Roll::Following),
+            date("2025-07-04")
+        );
+    }
+}
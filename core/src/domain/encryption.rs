@@ -1,8 +1,11 @@
 //! Encryption domain models
 
 use std::collections::HashMap;
+use std::fmt;
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
 
 /// Default Argon2id parameters
 pub const DEFAULT_TIME_COST: u32 = 3;
@@ -30,6 +33,16 @@ impl Default for Argon2Params {
     }
 }
 
+impl Argon2Params {
+    /// Whether these params cost less memory or fewer passes than `target`
+    ///
+    /// Used to spot databases encrypted with older, cheaper settings so the
+    /// key can be re-derived with the configured params.
+    pub fn is_weaker_than(&self, target: &Argon2Params) -> bool {
+        self.memory_cost < target.memory_cost || self.time_cost < target.time_cost
+    }
+}
+
 /// Encryption configuration metadata stored in encryption.json
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EncryptionMetadata {
@@ -76,7 +89,7 @@ impl EncryptionMetadata {
 }
 
 /// Status of database encryption for display
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EncryptionStatus {
     pub encrypted: bool,
     pub algorithm: Option<String>,
@@ -101,6 +114,35 @@ impl EncryptionStatus {
     }
 }
 
+/// Hex-encoded database encryption key
+///
+/// The buffer is wiped when dropped and never printed by `Debug`, so the key
+/// can't linger in freed memory or end up in logs.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionKey(Zeroizing<String>);
+
+impl EncryptionKey {
+    pub fn new(hex: String) -> Self {
+        Self(Zeroizing::new(hex))
+    }
+
+    /// Hex-encode raw key bytes
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self::new(hex::encode(bytes))
+    }
+
+    /// The hex key, for building ATTACH statements
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncryptionKey(<redacted>)")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -120,4 +162,30 @@ mod tests {
         assert!(!status.encrypted);
         assert!(status.algorithm.is_none());
     }
+
+    #[test]
+    fn test_argon2_params_is_weaker_than() {
+        let legacy = Argon2Params::default();
+        let calibrated = Argon2Params {
+            memory_cost: DEFAULT_MEMORY_COST * 4,
+            ..Argon2Params::default()
+        };
+        assert!(legacy.is_weaker_than(&calibrated));
+        assert!(!calibrated.is_weaker_than(&legacy));
+        assert!(!legacy.is_weaker_than(&Argon2Params::default()));
+
+        let fewer_passes = Argon2Params {
+            time_cost: 1,
+            memory_cost: DEFAULT_MEMORY_COST * 8,
+            ..Argon2Params::default()
+        };
+        assert!(fewer_passes.is_weaker_than(&legacy));
+    }
+
+    #[test]
+    fn test_encryption_key_debug_is_redacted() {
+        let key = EncryptionKey::from_bytes(&[0xab; 32]);
+        assert_eq!(key.expose().len(), 64);
+        assert!(!format!("{:?}", key).contains("abab"));
+    }
 }
@@ -0,0 +1,150 @@
+//! Investment holding domain model
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::ProviderData;
+
+/// A position in a brokerage account: how much of one security it holds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Holding {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    /// Ticker, when the provider knows one
+    pub symbol: Option<String>,
+    pub description: Option<String>,
+    /// Shares or units held
+    pub quantity: Decimal,
+    /// What the position cost in total
+    pub cost_basis: Option<Decimal>,
+    /// What the position is worth in total, as of `as_of`
+    pub market_value: Option<Decimal>,
+    pub currency: String,
+    /// When the provider valued the position
+    pub as_of: NaiveDateTime,
+    /// Purchases that make up the position, when they're known
+    #[serde(default)]
+    pub lots: Vec<HoldingLot>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Provider IDs and raw provider fields
+    #[serde(default)]
+    pub provider: ProviderData,
+}
+
+/// One purchase within a holding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HoldingLot {
+    pub id: Uuid,
+    pub acquired_date: Option<NaiveDate>,
+    pub quantity: Decimal,
+    pub cost_basis: Option<Decimal>,
+}
+
+/// A security's price on one day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SecurityPrice {
+    pub symbol: String,
+    pub price_date: NaiveDate,
+    pub price: Decimal,
+    pub currency: String,
+    /// Where the price came from, e.g. "simplefin"
+    pub source: Option<String>,
+}
+
+impl Holding {
+    /// Create a holding valued now
+    pub fn new(account_id: Uuid, quantity: Decimal, currency: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            account_id,
+            symbol: None,
+            description: None,
+            quantity,
+            cost_basis: None,
+            market_value: None,
+            currency: currency.into(),
+            as_of: now.naive_utc(),
+            lots: Vec::new(),
+            created_at: now,
+            updated_at: now,
+            provider: ProviderData::default(),
+        }
+    }
+
+    /// Price per share or unit, from the market value
+    ///
+    /// None without a market value, or for a position of zero.
+    pub fn price(&self) -> Option<Decimal> {
+        let value = self.market_value?;
+        if self.quantity.is_zero() {
+            None
+        } else {
+            Some((value / self.quantity).round_dp(6))
+        }
+    }
+
+    /// Market value minus cost basis, when both are known
+    pub fn unrealized_gain(&self) -> Option<Decimal> {
+        Some(self.market_value? - self.cost_basis?)
+    }
+
+    /// The holding's price as a point in its symbol's price history
+    ///
+    /// None without a symbol or a price.
+    pub fn security_price(&self, source: &str) -> Option<SecurityPrice> {
+        Some(SecurityPrice {
+            symbol: self.symbol.clone()?,
+            price_date: self.as_of.date(),
+            price: self.price()?,
+            currency: self.currency.clone(),
+            source: Some(source.to_string()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holding(quantity: i64, market_value: Option<i64>) -> Holding {
+        let mut holding = Holding::new(Uuid::new_v4(), Decimal::new(quantity, 0), "USD");
+        holding.symbol = Some("VTI".to_string());
+        holding.market_value = market_value.map(|v| Decimal::new(v, 0));
+        holding
+    }
+
+    #[test]
+    fn test_price_from_market_value() {
+        assert_eq!(holding(4, Some(1000)).price(), Some(Decimal::new(250, 0)));
+        assert_eq!(
+            holding(3, Some(100)).price(),
+            Some(Decimal::new(33_333_333, 6))
+        );
+        assert_eq!(holding(0, Some(0)).price(), None);
+        assert_eq!(holding(4, None).price(), None);
+    }
+
+    #[test]
+    fn test_unrealized_gain_needs_both_values() {
+        let mut h = holding(4, Some(1000));
+        assert_eq!(h.unrealized_gain(), None);
+        h.cost_basis = Some(Decimal::new(800, 0));
+        assert_eq!(h.unrealized_gain(), Some(Decimal::new(200, 0)));
+    }
+
+    #[test]
+    fn test_security_price_needs_symbol() {
+        let mut h = holding(2, Some(50));
+        let price = h.security_price("simplefin").unwrap();
+        assert_eq!(price.symbol, "VTI");
+        assert_eq!(price.price, Decimal::new(25, 0));
+        assert_eq!(price.price_date, h.as_of.date());
+
+        h.symbol = None;
+        assert!(h.security_price("simplefin").is_none());
+    }
+}
@@ -6,16 +6,28 @@
 mod account;
 mod backup;
 pub mod balance;
+mod business_days;
 mod encryption;
+mod holding;
+mod money;
+mod provider;
 pub mod result;
 mod rule;
+mod statement_cycle;
+mod token;
 mod transaction;
 mod user;
 
-pub use account::Account;
-pub use backup::BackupMetadata;
-pub use balance::BalanceSnapshot;
-pub use encryption::{Argon2Params, EncryptionMetadata, EncryptionStatus};
+pub use account::{Account, AccountType, SignPolicy};
+pub use backup::{format_bytes, BackupMetadata};
+pub use balance::{latest_per_day, BalanceSnapshot};
+pub use business_days::{BusinessCalendar, HolidayCalendar, Roll};
+pub use encryption::{Argon2Params, EncryptionKey, EncryptionMetadata, EncryptionStatus};
+pub use holding::{Holding, HoldingLot, SecurityPrice};
+pub use money::{minor_units, Money};
+pub use provider::ProviderData;
 pub use rule::AutoTagRule;
+pub use statement_cycle::StatementCycle;
+pub use token::{AccessToken, TokenScope};
 pub use transaction::Transaction;
 pub use user::User;
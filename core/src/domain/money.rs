@@ -0,0 +1,168 @@
+//! Money domain model
+//!
+//! Amounts are `Decimal` throughout Treeline, never floats. What a decimal
+//! alone doesn't say is how many places its currency has: yen have none,
+//! dollars two and Bahraini dinars three. `Money` pairs the two so amounts
+//! can be rounded, shown and converted to integer minor units correctly.
+
+use std::fmt;
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// ISO 4217 currencies without minor units
+const ZERO_DECIMAL: &[&str] = &[
+    "BIF", "CLP", "DJF", "GNF", "ISK", "JPY", "KMF", "KRW", "PYG", "RWF", "UGX", "VND", "VUV",
+    "XAF", "XOF", "XPF",
+];
+
+/// ISO 4217 currencies with three decimal places
+const THREE_DECIMAL: &[&str] = &["BHD", "IQD", "JOD", "KWD", "LYD", "OMR", "TND"];
+
+/// Decimal places of a currency's minor unit: 0 for JPY, 3 for BHD, 2 for
+/// everything else (including codes Treeline doesn't know)
+pub fn minor_units(currency: &str) -> u32 {
+    let code = currency.trim().to_uppercase();
+    if ZERO_DECIMAL.contains(&code.as_str()) {
+        0
+    } else if THREE_DECIMAL.contains(&code.as_str()) {
+        3
+    } else {
+        2
+    }
+}
+
+/// An exact amount in a currency
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Money {
+    /// Decimal string, e.g. "-12.50"
+    #[schemars(with = "String")]
+    pub amount: Decimal,
+    /// ISO 4217 code, uppercase
+    pub currency: String,
+}
+
+impl Money {
+    pub fn new(amount: Decimal, currency: &str) -> Self {
+        Self {
+            amount,
+            currency: currency.trim().to_uppercase(),
+        }
+    }
+
+    /// Parse a decimal string exactly, as the frontend sends amounts
+    ///
+    /// Rejects more decimal places than the currency has, so "1.005" dollars
+    /// is an error rather than a silently rounded cent.
+    pub fn parse(amount: &str, currency: &str) -> Result<Self, String> {
+        let value = Decimal::from_str_exact(amount.trim())
+            .map_err(|_| format!("Invalid amount '{}'", amount.trim()))?;
+        let money = Self::new(value.normalize(), currency);
+        if money.amount.scale() > money.minor_units() {
+            return Err(format!(
+                "{} has {} decimal place(s), but '{}' has more",
+                money.currency,
+                money.minor_units(),
+                amount.trim()
+            ));
+        }
+        Ok(money)
+    }
+
+    /// Decimal places of this currency's minor unit
+    pub fn minor_units(&self) -> u32 {
+        minor_units(&self.currency)
+    }
+
+    /// Rounded to the currency's minor unit, halves away from zero
+    pub fn rounded(&self) -> Self {
+        Self {
+            amount: self
+                .amount
+                .round_dp_with_strategy(self.minor_units(), RoundingStrategy::MidpointAwayFromZero),
+            currency: self.currency.clone(),
+        }
+    }
+
+    /// Whole minor units (cents, fils, yen), rounding first. None if the
+    /// amount doesn't fit in an i64.
+    pub fn to_minor_units(&self) -> Option<i64> {
+        let rounded = self.rounded().amount;
+        let scaled = rounded * Decimal::from(10i64.pow(self.minor_units()));
+        i64::try_from(scaled.trunc()).ok()
+    }
+
+    pub fn from_minor_units(minor: i64, currency: &str) -> Self {
+        let currency = currency.trim().to_uppercase();
+        Self {
+            amount: Decimal::new(minor, minor_units(&currency)),
+            currency,
+        }
+    }
+}
+
+impl fmt::Display for Money {
+    /// The amount with exactly the currency's decimal places, then the code
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.*} {}",
+            self.minor_units() as usize,
+            self.rounded().amount,
+            self.currency
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str_exact(s).unwrap()
+    }
+
+    #[test]
+    fn test_minor_units() {
+        assert_eq!(minor_units("USD"), 2);
+        assert_eq!(minor_units("jpy"), 0);
+        assert_eq!(minor_units(" BHD "), 3);
+        assert_eq!(minor_units("XYZ"), 2);
+    }
+
+    #[test]
+    fn test_rounding_and_display() {
+        assert_eq!(Money::new(dec("1234.5"), "usd").to_string(), "1234.50 USD");
+        assert_eq!(Money::new(dec("1500"), "JPY").to_string(), "1500 JPY");
+        assert_eq!(Money::new(dec("-2.5"), "JPY").rounded().amount, dec("-3"));
+        assert_eq!(Money::new(dec("1.2345"), "BHD").to_string(), "1.235 BHD");
+    }
+
+    #[test]
+    fn test_minor_unit_conversion() {
+        assert_eq!(
+            Money::new(dec("-12.34"), "USD").to_minor_units(),
+            Some(-1234)
+        );
+        assert_eq!(Money::new(dec("1500"), "JPY").to_minor_units(), Some(1500));
+        assert_eq!(Money::new(dec("1.234"), "BHD").to_minor_units(), Some(1234));
+        assert_eq!(Money::from_minor_units(1234, "bhd").amount, dec("1.234"));
+        assert_eq!(Money::from_minor_units(-5, "USD").amount, dec("-0.05"));
+        assert_eq!(Money::from_minor_units(700, "JPY").amount, dec("700"));
+    }
+
+    #[test]
+    fn test_parse_is_exact() {
+        assert_eq!(Money::parse("0.1", "USD").unwrap().amount, dec("0.1"));
+        assert_eq!(
+            Money::parse("1234567890.12", "USD").unwrap().amount,
+            dec("1234567890.12")
+        );
+        assert_eq!(Money::parse("1.250", "USD").unwrap().amount, dec("1.25"));
+        assert!(Money::parse("1.005", "USD").is_err());
+        assert!(Money::parse("12.5", "JPY").is_err());
+        assert!(Money::parse("1.234", "BHD").is_ok());
+        assert!(Money::parse("abc", "USD").is_err());
+    }
+}
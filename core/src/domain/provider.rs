@@ -0,0 +1,185 @@
+//! Provider data domain model - what sync providers know about a record
+//!
+//! Every provider that has seen an account or transaction files its ID for
+//! it under the provider's name in `external_ids` ("simplefin" -> "ACT-123"),
+//! and the raw fields it reported under the same name in `provider_metadata`.
+//! Supporting a new provider means picking a name, not adding columns.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+
+/// Provider IDs and raw provider fields for an account or transaction
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProviderData {
+    /// Provider name -> the provider's ID for the record (used for dedup)
+    #[serde(default)]
+    pub external_ids: BTreeMap<String, String>,
+    /// Provider name -> object of fields as the provider sent them
+    #[serde(default)]
+    pub provider_metadata: BTreeMap<String, JsonValue>,
+}
+
+impl ProviderData {
+    /// Data for a record `provider` knows as `id`
+    ///
+    /// `metadata` is an object of the provider's fields; null fields are left out.
+    pub fn new(provider: &str, id: impl Into<String>, metadata: JsonValue) -> Self {
+        let mut data = Self::default();
+        data.external_ids.insert(provider.to_string(), id.into());
+        if let JsonValue::Object(fields) = metadata {
+            for (field, value) in fields {
+                data.set_metadata(provider, &field, value);
+            }
+        }
+        data
+    }
+
+    /// Parse the external_ids and provider_metadata columns as stored
+    ///
+    /// Missing or malformed JSON reads as empty, so one bad row can't break a listing.
+    pub fn from_json(external_ids: Option<&str>, provider_metadata: Option<&str>) -> Self {
+        Self {
+            external_ids: external_ids
+                .and_then(|s| serde_json::from_str::<BTreeMap<String, JsonValue>>(s).ok())
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|(provider, id)| match id {
+                    JsonValue::String(id) => Some((provider, id)),
+                    JsonValue::Number(id) => Some((provider, id.to_string())),
+                    _ => None,
+                })
+                .collect(),
+            provider_metadata: provider_metadata
+                .and_then(|s| serde_json::from_str::<BTreeMap<String, JsonValue>>(s).ok())
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(_, fields)| fields.is_object())
+                .collect(),
+        }
+    }
+
+    /// external_ids as a JSON object for storage
+    pub fn external_ids_json(&self) -> String {
+        serde_json::to_string(&self.external_ids).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// provider_metadata as a JSON object for storage
+    pub fn provider_metadata_json(&self) -> String {
+        serde_json::to_string(&self.provider_metadata).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// The ID `provider` has for the record
+    pub fn external_id(&self, provider: &str) -> Option<&str> {
+        self.external_ids.get(provider).map(String::as_str)
+    }
+
+    /// Set or (with None) clear the ID `provider` has for the record
+    pub fn set_external_id(&mut self, provider: &str, id: Option<String>) {
+        match id {
+            Some(id) => {
+                self.external_ids.insert(provider.to_string(), id);
+            }
+            None => {
+                self.external_ids.remove(provider);
+            }
+        }
+    }
+
+    /// The provider the record came from, first by name if several know it
+    pub fn source(&self) -> Option<&str> {
+        self.external_ids.keys().next().map(String::as_str)
+    }
+
+    /// A field `provider` reported, None when absent or null
+    pub fn metadata(&self, provider: &str, field: &str) -> Option<&JsonValue> {
+        self.provider_metadata
+            .get(provider)?
+            .get(field)
+            .filter(|value| !value.is_null())
+    }
+
+    /// A string field `provider` reported
+    pub fn metadata_str(&self, provider: &str, field: &str) -> Option<&str> {
+        self.metadata(provider, field)?.as_str()
+    }
+
+    /// Record a field `provider` reported; a null value removes it
+    pub fn set_metadata(&mut self, provider: &str, field: &str, value: impl Serialize) {
+        let value = serde_json::to_value(value).unwrap_or(JsonValue::Null);
+        if value.is_null() {
+            if let Some(JsonValue::Object(fields)) = self.provider_metadata.get_mut(provider) {
+                fields.remove(field);
+                if fields.is_empty() {
+                    self.provider_metadata.remove(provider);
+                }
+            }
+            return;
+        }
+        let fields = self
+            .provider_metadata
+            .entry(provider.to_string())
+            .or_insert_with(|| JsonValue::Object(Map::new()));
+        if !fields.is_object() {
+            *fields = JsonValue::Object(Map::new());
+        }
+        if let JsonValue::Object(fields) = fields {
+            fields.insert(field.to_string(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_new_drops_null_fields() {
+        let data = ProviderData::new(
+            "simplefin",
+            "ACT-1",
+            json!({"name": "Checking", "available_balance": null, "balance_date": 1700000000}),
+        );
+        assert_eq!(data.external_id("simplefin"), Some("ACT-1"));
+        assert_eq!(data.source(), Some("simplefin"));
+        assert_eq!(data.metadata_str("simplefin", "name"), Some("Checking"));
+        assert_eq!(
+            data.metadata("simplefin", "balance_date"),
+            Some(&json!(1700000000))
+        );
+        assert_eq!(data.metadata("simplefin", "available_balance"), None);
+        assert_eq!(data.metadata("lunchflow", "name"), None);
+    }
+
+    #[test]
+    fn test_set_metadata_null_removes() {
+        let mut data = ProviderData::default();
+        data.set_metadata("lunchflow", "status", "ACTIVE");
+        assert_eq!(data.metadata_str("lunchflow", "status"), Some("ACTIVE"));
+        data.set_metadata("lunchflow", "status", None::<String>);
+        assert!(data.provider_metadata.is_empty());
+
+        data.set_external_id("plaid", Some("pl-1".to_string()));
+        data.set_external_id("plaid", None);
+        assert_eq!(data.source(), None);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let data = ProviderData::new("gocardless", "gc-1", json!({"iban": "GB00TEST"}));
+        let parsed = ProviderData::from_json(
+            Some(&data.external_ids_json()),
+            Some(&data.provider_metadata_json()),
+        );
+        assert_eq!(parsed, data);
+
+        // Columns written before provider data existed, or damaged by hand
+        let empty = ProviderData::from_json(Some("not json"), None);
+        assert_eq!(empty, ProviderData::default());
+        let legacy = ProviderData::from_json(Some(r#"{"simplefin": 42, "x": null}"#), Some("[]"));
+        assert_eq!(legacy.external_id("simplefin"), Some("42"));
+        assert_eq!(legacy.external_ids.len(), 1);
+    }
+}
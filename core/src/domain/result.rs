@@ -1,10 +1,13 @@
 //! Result and error types for the core library
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use super::format_bytes;
+
 /// COMMENT: these error types are new compared to
 /// the old Python CLI code. Explain why you added these,
 /// I think they are unnecessary but I'm open to it.
@@ -29,6 +32,20 @@ pub enum Error {
     #[error("Sync error: {0}")]
     Sync(String),
 
+    /// Too little free disk space to start an operation that writes file copies
+    #[error(
+        "Not enough disk space to {operation}: needs {}, {} available on {}",
+        format_bytes(*.required),
+        format_bytes(*.available),
+        .path.display()
+    )]
+    InsufficientSpace {
+        operation: String,
+        path: PathBuf,
+        required: u64,
+        available: u64,
+    },
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -13,6 +13,8 @@ pub struct AutoTagRule {
     pub sql_condition: String,
     /// Tags to apply when rule matches
     pub tags: Vec<String>,
+    /// Exclude matching transactions from reports
+    pub exclude_from_reports: bool,
     /// Whether the rule is active
     pub enabled: bool,
     /// Sort order for rule priority
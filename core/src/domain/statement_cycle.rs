@@ -0,0 +1,128 @@
+//! Credit card statement cycle domain model
+
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// When a credit card's statements close and their payments are due
+///
+/// Days past the end of a short month fall on its last day, so a cycle
+/// closing on the 31st closes on February 28th (or 29th).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct StatementCycle {
+    /// Day of the month the statement closes (1-31)
+    pub closing_day: u32,
+    /// Day of the month payment is due (1-31), the first one after closing
+    pub due_day: u32,
+}
+
+impl StatementCycle {
+    pub fn new(closing_day: u32, due_day: u32) -> Result<Self, &'static str> {
+        if !(1..=31).contains(&closing_day) {
+            return Err("closing day must be between 1 and 31");
+        }
+        if !(1..=31).contains(&due_day) {
+            return Err("due day must be between 1 and 31");
+        }
+        Ok(Self {
+            closing_day,
+            due_day,
+        })
+    }
+
+    /// The first closing date on or after `date`, which ends the open statement
+    pub fn next_closing(&self, date: NaiveDate) -> NaiveDate {
+        let this_month = day_in_month(date, self.closing_day);
+        if this_month >= date {
+            this_month
+        } else {
+            day_in_month(first_of_month(date) + Months::new(1), self.closing_day)
+        }
+    }
+
+    /// The last closing date before `date`
+    pub fn previous_closing(&self, date: NaiveDate) -> NaiveDate {
+        let this_month = day_in_month(date, self.closing_day);
+        if this_month < date {
+            this_month
+        } else {
+            day_in_month(first_of_month(date) - Months::new(1), self.closing_day)
+        }
+    }
+
+    /// First and last day of the statement that is open on `date`
+    pub fn period_containing(&self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        (
+            self.previous_closing(date) + Duration::days(1),
+            self.next_closing(date),
+        )
+    }
+
+    /// When the statement closing on `closing` has to be paid
+    pub fn due_date(&self, closing: NaiveDate) -> NaiveDate {
+        let this_month = day_in_month(closing, self.due_day);
+        if this_month > closing {
+            this_month
+        } else {
+            day_in_month(first_of_month(closing) + Months::new(1), self.due_day)
+        }
+    }
+}
+
+fn first_of_month(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(i64::from(date.day0()))
+}
+
+/// `day` of the month `date` is in, moved back to the month's last day if needed
+fn day_in_month(date: NaiveDate, day: u32) -> NaiveDate {
+    let first = first_of_month(date);
+    let last = first + Months::new(1) - Duration::days(1);
+    first + Duration::days(i64::from(day.min(last.day()) - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_periods_and_due_dates() {
+        let cycle = StatementCycle::new(15, 10).unwrap();
+
+        assert_eq!(
+            cycle.period_containing(date(2025, 3, 20)),
+            (date(2025, 3, 16), date(2025, 4, 15))
+        );
+        // The closing day still belongs to the statement it closes
+        assert_eq!(
+            cycle.period_containing(date(2025, 3, 15)),
+            (date(2025, 2, 16), date(2025, 3, 15))
+        );
+        assert_eq!(cycle.previous_closing(date(2025, 1, 5)), date(2024, 12, 15));
+        assert_eq!(cycle.due_date(date(2025, 3, 15)), date(2025, 4, 10));
+        assert_eq!(
+            StatementCycle::new(5, 28)
+                .unwrap()
+                .due_date(date(2025, 3, 5)),
+            date(2025, 3, 28)
+        );
+
+        assert!(StatementCycle::new(0, 10).is_err());
+        assert!(StatementCycle::new(15, 32).is_err());
+    }
+
+    #[test]
+    fn test_short_months_use_their_last_day() {
+        let cycle = StatementCycle::new(31, 30).unwrap();
+        assert_eq!(cycle.next_closing(date(2025, 2, 10)), date(2025, 2, 28));
+        assert_eq!(cycle.next_closing(date(2024, 2, 10)), date(2024, 2, 29));
+        assert_eq!(
+            cycle.period_containing(date(2025, 3, 1)),
+            (date(2025, 3, 1), date(2025, 3, 31))
+        );
+        assert_eq!(cycle.due_date(date(2025, 1, 31)), date(2025, 2, 28));
+    }
+}
@@ -0,0 +1,106 @@
+//! Access token domain model
+//!
+//! Tokens let external scripts (notebooks, the Python client) use the
+//! database without the raw password. Only a hash of the secret is kept.
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// What a token holder may do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenScope {
+    ReadOnly,
+    ReadWrite,
+}
+
+impl TokenScope {
+    pub fn allows_writes(&self) -> bool {
+        matches!(self, TokenScope::ReadWrite)
+    }
+}
+
+impl fmt::Display for TokenScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenScope::ReadOnly => write!(f, "read-only"),
+            TokenScope::ReadWrite => write!(f, "read-write"),
+        }
+    }
+}
+
+/// An issued access token, as listed by `tl token list`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AccessToken {
+    /// Public part of the token, used to look it up and revoke it
+    pub id: String,
+    pub name: Option<String>,
+    pub scope: TokenScope,
+    pub created_at: DateTime<Utc>,
+    /// None means the token never expires
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub use_count: u64,
+}
+
+impl AccessToken {
+    /// Whether the token can still be used at `now`
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at.map_or(true, |e| now < e)
+    }
+
+    /// "active", "expired" or "revoked"
+    pub fn status(&self, now: DateTime<Utc>) -> &'static str {
+        if self.revoked_at.is_some() {
+            "revoked"
+        } else if self.is_active(now) {
+            "active"
+        } else {
+            "expired"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn token(expires_in: Option<Duration>) -> AccessToken {
+        let now = Utc::now();
+        AccessToken {
+            id: "abc123".to_string(),
+            name: None,
+            scope: TokenScope::ReadOnly,
+            created_at: now,
+            expires_at: expires_in.map(|d| now + d),
+            revoked_at: None,
+            last_used_at: None,
+            use_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_token_status() {
+        let now = Utc::now();
+        assert_eq!(token(Some(Duration::hours(1))).status(now), "active");
+        assert_eq!(token(None).status(now), "active");
+        assert_eq!(token(Some(Duration::hours(-1))).status(now), "expired");
+
+        let mut revoked = token(Some(Duration::hours(1)));
+        revoked.revoked_at = Some(now);
+        assert!(!revoked.is_active(now));
+        assert_eq!(revoked.status(now), "revoked");
+    }
+
+    #[test]
+    fn test_scope_allows_writes() {
+        assert!(!TokenScope::ReadOnly.allows_writes());
+        assert!(TokenScope::ReadWrite.allows_writes());
+    }
+}
@@ -4,10 +4,11 @@ use chrono::{DateTime, NaiveDate, Utc};
 use regex::Regex;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use serde_json::Value as JsonValue;
 use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
+use super::{Money, ProviderData};
+
 /// A single financial transaction belonging to an account
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transaction {
@@ -34,6 +35,21 @@ pub struct Transaction {
     /// Which import batch this transaction belongs to
     pub csv_batch_id: Option<String>,
 
+    // =========================================================================
+    // Reconciliation identifiers
+    // =========================================================================
+    /// Check number (US checking accounts)
+    pub check_number: Option<String>,
+    /// Bank reference or confirmation number
+    pub reference: Option<String>,
+
+    // =========================================================================
+    // Currency
+    // =========================================================================
+    /// Currency of `amount` as given by a multi-currency CSV (e.g. Wise,
+    /// Revolut); None means the account's currency
+    pub currency: Option<String>,
+
     // =========================================================================
     // Manual flag
     // =========================================================================
@@ -47,42 +63,28 @@ pub struct Transaction {
     pub tags_auto_applied: bool,
 
     // =========================================================================
-    // SimpleFIN: ALL fields from API (https://www.simplefin.org/protocol.html)
+    // Report exclusion
     // =========================================================================
-    /// SimpleFIN transaction ID (required for dedup)
-    pub sf_id: Option<String>,
-    /// UNIX timestamp when posted (required)
-    pub sf_posted: Option<i64>,
-    /// Raw amount string (required)
-    pub sf_amount: Option<String>,
-    /// Raw description (required)
-    pub sf_description: Option<String>,
-    /// UNIX timestamp of actual transaction (optional)
-    pub sf_transacted_at: Option<i64>,
-    /// Is transaction pending (optional)
-    pub sf_pending: Option<bool>,
-    /// Extra blob pass-through (optional)
-    pub sf_extra: Option<JsonValue>,
+    /// Left out of reports, budgets and income detection (e.g. a reimbursement
+    /// passing through); still part of the account's balance
+    #[serde(default)]
+    pub excluded_from_reports: bool,
 
     // =========================================================================
-    // Lunchflow: ALL fields from API
+    // Pending
     // =========================================================================
-    /// Lunchflow transaction ID (required for dedup)
-    pub lf_id: Option<String>,
-    /// Lunchflow account ID
-    pub lf_account_id: Option<String>,
-    /// Raw amount
-    pub lf_amount: Option<Decimal>,
-    /// Currency code
-    pub lf_currency: Option<String>,
-    /// Transaction date
-    pub lf_date: Option<NaiveDate>,
-    /// Merchant/counterparty name
-    pub lf_merchant: Option<String>,
-    /// Description/memo
-    pub lf_description: Option<String>,
-    /// Is transaction pending
-    pub lf_is_pending: Option<bool>,
+    /// Authorized but not yet posted by the bank. Sync updates the row in
+    /// place once the provider reports it posted, often under a new ID and
+    /// a slightly different amount
+    #[serde(default)]
+    pub pending: bool,
+
+    // =========================================================================
+    // Sync providers
+    // =========================================================================
+    /// IDs and raw fields from each provider that has synced this transaction
+    #[serde(flatten)]
+    pub provider: ProviderData,
 }
 
 impl Transaction {
@@ -104,30 +106,73 @@ impl Transaction {
             // CSV Import tracking
             csv_fingerprint: None,
             csv_batch_id: None,
+            // Reconciliation identifiers
+            check_number: None,
+            reference: None,
+            currency: None,
             // Manual flag
             is_manual: false,
             // Auto-tag tracking
             tags_auto_applied: false,
-            // SimpleFIN fields
-            sf_id: None,
-            sf_posted: None,
-            sf_amount: None,
-            sf_description: None,
-            sf_transacted_at: None,
-            sf_pending: None,
-            sf_extra: None,
-            // Lunchflow fields
-            lf_id: None,
-            lf_account_id: None,
-            lf_amount: None,
-            lf_currency: None,
-            lf_date: None,
-            lf_merchant: None,
-            lf_description: None,
-            lf_is_pending: None,
+            // Report exclusion
+            excluded_from_reports: false,
+            // Pending
+            pending: false,
+            // Sync providers
+            provider: ProviderData::default(),
         }
     }
 
+    /// The amount in the transaction's own currency, or the account's when it has none
+    pub fn money(&self, account_currency: &str) -> Money {
+        Money::new(
+            self.amount,
+            self.currency.as_deref().unwrap_or(account_currency),
+        )
+    }
+
+    /// Bring a pending transaction up to date with the provider's latest
+    /// version of it, which may have posted under a new ID
+    ///
+    /// Takes the amount, dates, pending flag and the provider's IDs and
+    /// fields. Keeps the ID, description, tags and flags, which the user may
+    /// have edited. Returns whether anything changed.
+    pub fn settle(&mut self, latest: &Transaction) -> bool {
+        let new_ids = latest
+            .provider
+            .external_ids
+            .iter()
+            .any(|(provider, id)| self.provider.external_ids.get(provider) != Some(id));
+        if self.amount == latest.amount
+            && self.transaction_date == latest.transaction_date
+            && self.posted_date == latest.posted_date
+            && self.pending == latest.pending
+            && !new_ids
+        {
+            return false;
+        }
+
+        self.amount = latest.amount;
+        self.transaction_date = latest.transaction_date;
+        self.posted_date = latest.posted_date;
+        self.pending = latest.pending;
+        if self.description.is_none() {
+            self.description = latest.description.clone();
+        }
+        for (provider, id) in &latest.provider.external_ids {
+            self.provider
+                .external_ids
+                .insert(provider.clone(), id.clone());
+        }
+        for (provider, fields) in &latest.provider.provider_metadata {
+            self.provider
+                .provider_metadata
+                .insert(provider.clone(), fields.clone());
+        }
+        self.updated_at = Utc::now();
+        true
+    }
+
     /// Ensure csv_fingerprint is set
     pub fn ensure_fingerprint(&mut self) {
         if self.csv_fingerprint.is_none() {
@@ -269,6 +314,40 @@ mod tests {
         assert!(normalized.contains("7070"));
     }
 
+    #[test]
+    fn test_settle_pending() {
+        let date = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        let mut pending =
+            Transaction::new(Uuid::new_v4(), Uuid::new_v4(), Decimal::new(-40, 0), date);
+        pending.description = Some("Dinner with Sam".to_string());
+        pending.tags = vec!["dining".to_string()];
+        pending.pending = true;
+        pending.provider = ProviderData::new("simplefin", "TX-PENDING", serde_json::json!({}));
+
+        // Posted two days later with the tip, under a new ID
+        let mut posted = Transaction::new(
+            Uuid::new_v4(),
+            pending.account_id,
+            Decimal::new(-48, 0),
+            date + chrono::Duration::days(2),
+        );
+        posted.description = Some("SQ *BISTRO".to_string());
+        posted.provider = ProviderData::new("simplefin", "TX-POSTED", serde_json::json!({}));
+
+        let id = pending.id;
+        assert!(pending.settle(&posted));
+        assert_eq!(pending.id, id);
+        assert!(!pending.pending);
+        assert_eq!(pending.amount, Decimal::new(-48, 0));
+        assert_eq!(pending.posted_date, posted.posted_date);
+        assert_eq!(pending.provider.external_id("simplefin"), Some("TX-POSTED"));
+        assert_eq!(pending.description.as_deref(), Some("Dinner with Sam"));
+        assert_eq!(pending.tags, ["dining"]);
+
+        // Nothing new the second time
+        assert!(!pending.settle(&posted));
+    }
+
     #[test]
     fn test_tag_normalization() {
         let tags = vec![
@@ -0,0 +1,113 @@
+//! Crash-safe JSON files
+//!
+//! Settings, plugin state, tokens and encryption metadata are small JSON
+//! files rewritten often. Writing them in place can leave a truncated file
+//! if the process dies or the machine loses power mid-write, so every
+//! writer goes through `write_atomic`: write a temp file in the same
+//! directory, fsync it, then rename it over the original.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Replace `path` with `contents` so readers see the old file or the new one, never a mix
+///
+/// Keeps the permissions of the file being replaced.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut temp = tempfile::NamedTempFile::new_in(dir)
+        .with_context(|| format!("Failed to create a temp file in {}", dir.display()))?;
+    temp.write_all(contents.as_ref())?;
+    if let Ok(metadata) = fs::metadata(path) {
+        temp.as_file().set_permissions(metadata.permissions())?;
+    }
+    temp.as_file().sync_all()?;
+    temp.persist(path)
+        .map_err(|e| e.error)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    // Make the rename itself durable; not possible (or needed) on Windows
+    #[cfg(unix)]
+    if let Ok(dir) = fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// Write `value` as pretty-printed JSON with `write_atomic`
+pub fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<()> {
+    write_atomic(path, serde_json::to_string_pretty(value)?)
+}
+
+/// Read a JSON file; None when it is missing or corrupted
+///
+/// A file that can't be parsed is first copied to `<name>.corrupt` (see
+/// `corrupt_backup_path`), so nothing is lost when the caller falls back to
+/// defaults and later writes a fresh file. Errors are for I/O failures only.
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    let content = match fs::read(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+
+    match serde_json::from_slice(&content) {
+        Ok(value) => Ok(Some(value)),
+        Err(_) => {
+            let backup = corrupt_backup_path(path);
+            write_atomic(&backup, &content).with_context(|| {
+                format!("{} is corrupted and could not be backed up", path.display())
+            })?;
+            Ok(None)
+        }
+    }
+}
+
+/// Where `read_json` keeps a copy of a corrupted file: `settings.json` -> `settings.json.corrupt`
+///
+/// Only the latest corrupted version is kept.
+pub fn corrupt_backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".corrupt");
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic_replaces_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        fs::write(&path, "old").unwrap();
+
+        write_json(&path, &serde_json::json!({"app": {"demoMode": true}})).unwrap();
+        let value: serde_json::Value = read_json(&path).unwrap().unwrap();
+        assert_eq!(value["app"]["demoMode"], true);
+
+        // Only the target is left behind, no temp files
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_read_json_backs_up_corrupted_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        assert!(read_json::<serde_json::Value>(&path).unwrap().is_none());
+
+        fs::write(&path, "{\"budget\": 12").unwrap();
+        assert!(read_json::<serde_json::Value>(&path).unwrap().is_none());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("state.json.corrupt")).unwrap(),
+            "{\"budget\": 12"
+        );
+    }
+}
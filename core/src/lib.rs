@@ -6,13 +6,22 @@
 //! - **ports**: Trait definitions for external dependencies (Repository, DataProvider)
 //! - **services**: Business logic orchestration
 //! - **adapters**: Concrete implementations (DuckDB, SimpleFIN, etc.)
+//! - **api**: Versioned facade with semver-guarded types, for embedders
+//!
+//! Sync providers, plugins and reports sit behind default features
+//! (`sync-simplefin`, `sync-lunchflow`, `plugins`, `reports`), so embedders
+//! that only need the database and queries can build with
+//! `default-features = false`.
 
 pub mod adapters;
+pub mod api;
 pub mod config;
 pub mod domain;
+pub mod json_file;
 pub mod log_migrations;
 pub mod migrations;
 pub mod ports;
+pub mod schema;
 pub mod services;
 
 use std::path::Path;
@@ -28,8 +37,8 @@ use services::*;
 pub use adapters::duckdb::QueryResult;
 pub use domain::result::{Error, OperationResult};
 pub use domain::{
-    Account, BackupMetadata, BalanceSnapshot, EncryptionMetadata, EncryptionStatus, Transaction,
-    User,
+    Account, AccountType, BackupMetadata, BalanceSnapshot, EncryptionKey, EncryptionMetadata,
+    EncryptionStatus, Transaction, User,
 };
 pub use services::{EntryPoint, LogEntry, LogEvent, LoggingService};
 
@@ -49,8 +58,15 @@ pub struct TreelineContext {
     pub doctor_service: DoctorService,
     pub encryption_service: EncryptionService,
     pub import_service: ImportService,
+    pub ledger_import_service: LedgerImportService,
     pub balance_service: BalanceService,
+    pub account_service: AccountService,
+    #[cfg(feature = "plugins")]
     pub plugin_service: services::PluginService,
+    #[cfg(feature = "reports")]
+    pub report_service: ReportService,
+    pub tool_service: ToolService,
+    pub transaction_service: TransactionService,
 }
 
 impl TreelineContext {
@@ -71,6 +87,46 @@ impl TreelineContext {
         // Initialize schema
         repository.ensure_schema()?;
 
+        Ok(Self::with_repository(
+            treeline_dir,
+            config,
+            db_filename,
+            repository,
+        ))
+    }
+
+    /// Open an existing database without writing to it
+    ///
+    /// The repository is read-only, so no migrations run and every write
+    /// through a service fails. The schema must already be current.
+    pub fn open_read_only(treeline_dir: &Path, password: Option<&str>) -> Result<Self> {
+        let config = Config::load(treeline_dir)?;
+        let db_filename = if config.demo_mode {
+            "demo.duckdb"
+        } else {
+            "treeline.duckdb"
+        };
+
+        let db_path = treeline_dir.join(db_filename);
+        let repository = Arc::new(DuckDbRepository::open_read_only(&db_path, password)?);
+        repository.require_current_schema()?;
+
+        Ok(Self::with_repository(
+            treeline_dir,
+            config,
+            db_filename,
+            repository,
+        ))
+    }
+
+    fn with_repository(
+        treeline_dir: &Path,
+        config: Config,
+        db_filename: &str,
+        repository: Arc<DuckDbRepository>,
+    ) -> Self {
+        let db_path = repository.db_path().to_path_buf();
+
         // Create services
         let status_service = StatusService::new(Arc::clone(&repository));
         let sync_service = SyncService::new(Arc::clone(&repository), treeline_dir.to_path_buf());
@@ -88,10 +144,17 @@ impl TreelineContext {
             EncryptionService::new(treeline_dir.to_path_buf(), db_path.clone());
         let import_service =
             ImportService::new(Arc::clone(&repository), treeline_dir.to_path_buf());
-        let balance_service = BalanceService::new(Arc::clone(&repository));
+        let ledger_import_service = LedgerImportService::new(Arc::clone(&repository));
+        let balance_service = BalanceService::new(repository.clone());
+        let account_service = AccountService::new(Arc::clone(&repository));
+        #[cfg(feature = "plugins")]
         let plugin_service = services::PluginService::new(treeline_dir);
+        #[cfg(feature = "reports")]
+        let report_service = ReportService::new(Arc::clone(&repository));
+        let tool_service = ToolService::new(Arc::clone(&repository));
+        let transaction_service = TransactionService::new(Arc::clone(&repository));
 
-        Ok(Self {
+        Self {
             config,
             repository,
             status_service,
@@ -103,8 +166,15 @@ impl TreelineContext {
             doctor_service,
             encryption_service,
             import_service,
+            ledger_import_service,
             balance_service,
+            account_service,
+            #[cfg(feature = "plugins")]
             plugin_service,
-        })
+            #[cfg(feature = "reports")]
+            report_service,
+            tool_service,
+            transaction_service,
+        }
     }
 }
@@ -0,0 +1,23 @@
+-- Query audit log
+-- One row per SQL statement run from the desktop SQL console or a plugin,
+-- with literals replaced by ? so no user data is stored
+
+CREATE TABLE sys_query_log (
+    id UBIGINT PRIMARY KEY,
+    timestamp BIGINT NOT NULL,
+
+    entry_point VARCHAR NOT NULL,
+    app_version VARCHAR NOT NULL,
+
+    -- "user" for the SQL console, otherwise the plugin id
+    caller VARCHAR NOT NULL,
+    query_hash VARCHAR NOT NULL,
+    statement VARCHAR NOT NULL,
+    duration_ms BIGINT NOT NULL,
+    row_count BIGINT,
+
+    error_message VARCHAR
+);
+
+CREATE INDEX idx_query_log_timestamp ON sys_query_log(timestamp);
+CREATE INDEX idx_query_log_duration ON sys_query_log(duration_ms);
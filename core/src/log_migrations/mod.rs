@@ -16,4 +16,5 @@ pub const LOG_MIGRATIONS: &[(&str, &str)] = &[
         "001_initial_schema.sql",
         include_str!("001_initial_schema.sql"),
     ),
+    ("002_query_log.sql", include_str!("002_query_log.sql")),
 ];
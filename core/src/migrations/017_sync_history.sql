@@ -0,0 +1,37 @@
+-- Migration: Sync history
+-- One row per integration per sync run, so users can see when each account
+-- last produced data and spot integrations that fail or go quiet
+
+CREATE TABLE IF NOT EXISTS sys_sync_history (
+    sync_id VARCHAR PRIMARY KEY,
+    integration_name VARCHAR NOT NULL,
+    started_at TIMESTAMP NOT NULL,
+    duration_ms BIGINT NOT NULL,
+    sync_type VARCHAR,
+    accounts_synced INTEGER NOT NULL DEFAULT 0,
+    transactions_discovered INTEGER NOT NULL DEFAULT 0,
+    transactions_new INTEGER NOT NULL DEFAULT 0,
+    transactions_skipped INTEGER NOT NULL DEFAULT 0,
+    -- New transactions per account: {"<account_id>": count}
+    account_stats JSON,
+    warnings JSON,
+    error VARCHAR
+);
+
+CREATE INDEX IF NOT EXISTS idx_sys_sync_history_started_at ON sys_sync_history(started_at);
+
+CREATE OR REPLACE VIEW sync_history AS
+SELECT
+    sync_id,
+    integration_name AS integration,
+    started_at,
+    duration_ms,
+    sync_type,
+    accounts_synced,
+    transactions_discovered,
+    transactions_new,
+    transactions_skipped,
+    account_stats,
+    warnings,
+    error
+FROM sys_sync_history;
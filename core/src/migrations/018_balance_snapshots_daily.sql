@@ -0,0 +1,27 @@
+-- Migration: Latest balance per account per day
+-- Syncing several times a day leaves several timestamped snapshots per day.
+-- They all stay in sys_balance_snapshots for intraday precision; charts and
+-- reports that want one balance per day read this view instead
+
+CREATE INDEX IF NOT EXISTS idx_sys_balance_snapshots_account_time
+    ON sys_balance_snapshots(account_id, snapshot_time);
+
+CREATE OR REPLACE VIEW balance_snapshots_daily AS
+SELECT
+    s.account_id,
+    CAST(s.snapshot_time AS DATE) AS snapshot_date,
+    s.snapshot_id,
+    s.balance,
+    s.snapshot_time,
+    s.source,
+    -- How many snapshots were taken that day (1 = end-of-day only)
+    COUNT(*) OVER (PARTITION BY s.account_id, CAST(s.snapshot_time AS DATE)) AS snapshots_that_day,
+    -- Account details
+    a.name AS account_name,
+    a.institution_name
+FROM sys_balance_snapshots s
+LEFT JOIN sys_accounts a ON s.account_id = a.account_id
+QUALIFY ROW_NUMBER() OVER (
+    PARTITION BY s.account_id, CAST(s.snapshot_time AS DATE)
+    ORDER BY s.snapshot_time DESC, s.updated_at DESC
+) = 1;
@@ -0,0 +1,11 @@
+-- Migration: Archived accounts
+-- Closed accounts keep their history but drop out of account lists.
+-- NULL means active; the timestamp records when the account was archived
+
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS archived_at TIMESTAMP;
+
+-- The accounts view caches its column list (see 013), so recreate it
+DROP VIEW IF EXISTS accounts;
+
+CREATE VIEW accounts AS
+SELECT * FROM sys_accounts;
@@ -0,0 +1,22 @@
+-- Migration: Device sync
+-- Tracks what was last exchanged with other devices through a sync relay.
+-- Each replicated field remembers its last value, when it changed and on
+-- which device, so concurrent edits resolve per field (latest change wins)
+
+CREATE TABLE IF NOT EXISTS sys_device_sync_fields (
+    entity VARCHAR NOT NULL,
+    entity_id VARCHAR NOT NULL,
+    field VARCHAR NOT NULL,
+    value JSON,
+    updated_at TIMESTAMP NOT NULL,
+    device_id VARCHAR NOT NULL,
+    PRIMARY KEY (entity, entity_id, field)
+);
+
+-- The last bundle applied from each other device
+CREATE TABLE IF NOT EXISTS sys_device_sync_peers (
+    device_id VARCHAR PRIMARY KEY,
+    device_name VARCHAR,
+    last_seq BIGINT NOT NULL DEFAULT 0,
+    last_synced_at TIMESTAMP
+);
@@ -0,0 +1,35 @@
+-- Migration: Change log
+-- Append-only record of every account, transaction and balance snapshot
+-- change made through Treeline, so external tools can mirror the data
+-- incrementally (tl cdc tail, tl cdc serve) instead of re-exporting it.
+-- seq is assigned by the writer as MAX(seq) + 1 (writes hold the database
+-- lock), so it survives compaction without depending on sequence state
+
+CREATE TABLE IF NOT EXISTS sys_change_log (
+    seq BIGINT PRIMARY KEY,
+    -- account, transaction or balance_snapshot
+    entity VARCHAR NOT NULL,
+    entity_id VARCHAR NOT NULL,
+    -- upsert or delete
+    op VARCHAR NOT NULL,
+    -- The sys_* row after an upsert, or before a delete
+    payload JSON,
+    changed_at TIMESTAMP NOT NULL
+);
+
+CREATE OR REPLACE VIEW change_log AS
+SELECT seq, entity, entity_id, op, payload, changed_at
+FROM sys_change_log;
+
+-- Start the log with every existing row, so a consumer reading from seq 0
+-- gets a complete copy (accounts first, as the other rows reference them)
+INSERT INTO sys_change_log (seq, entity, entity_id, op, payload, changed_at)
+SELECT ROW_NUMBER() OVER (ORDER BY entity, entity_id), entity, entity_id, 'upsert', payload, CURRENT_TIMESTAMP
+FROM (
+    SELECT 'account' AS entity, account_id AS entity_id, to_json(a) AS payload FROM sys_accounts a
+    UNION ALL
+    SELECT 'balance_snapshot', snapshot_id, to_json(s) FROM sys_balance_snapshots s
+    UNION ALL
+    SELECT 'transaction', transaction_id, to_json(t) FROM sys_transactions t
+)
+WHERE NOT EXISTS (SELECT 1 FROM sys_change_log);
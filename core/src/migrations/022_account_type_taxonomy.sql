@@ -0,0 +1,35 @@
+-- Migration: Account type taxonomy
+-- account_type was free text, mostly Plaid-style values. Map it onto the
+-- fixed set in domain::AccountType, which decides asset vs liability:
+--   depository -> savings if the name says so, else checking
+--   credit     -> credit_card
+--   investment -> retirement for 401(k)/IRA-style names, else brokerage
+--   loan       -> mortgage if the name says so, else loan
+--   anything else that isn't already a known type -> other
+-- classification is left alone, as it may hold a user's override
+
+UPDATE sys_accounts SET account_type = CASE
+    WHEN trim(account_type) = '' THEN NULL
+    WHEN lower(trim(account_type)) = 'depository' THEN
+        CASE WHEN lower(name) LIKE '%saving%' OR lower(name) LIKE '%money market%'
+             THEN 'savings' ELSE 'checking' END
+    WHEN lower(trim(account_type)) IN ('credit', 'credit card', 'credit-card', 'creditcard')
+        THEN 'credit_card'
+    WHEN lower(trim(account_type)) = 'investment' THEN
+        CASE WHEN regexp_matches(lower(name), '(401|403|457|\bira\b|roth|pension|retirement)')
+             THEN 'retirement' ELSE 'brokerage' END
+    WHEN lower(trim(account_type)) = 'loan' AND lower(name) LIKE '%mortgage%' THEN 'mortgage'
+    WHEN replace(lower(trim(account_type)), ' ', '_') IN (
+        'checking', 'savings', 'cash', 'credit_card', 'loan', 'mortgage',
+        'brokerage', 'retirement', 'crypto', 'property', 'vehicle', 'other'
+    ) THEN replace(lower(trim(account_type)), ' ', '_')
+    ELSE 'other'
+END
+WHERE account_type IS NOT NULL;
+
+-- Tell change log consumers about the new values
+INSERT INTO sys_change_log (seq, entity, entity_id, op, payload, changed_at)
+SELECT (SELECT COALESCE(MAX(seq), 0) FROM sys_change_log) + ROW_NUMBER() OVER (ORDER BY account_id),
+       'account', account_id, 'upsert', to_json(a), CURRENT_TIMESTAMP
+FROM sys_accounts a
+WHERE account_type IS NOT NULL;
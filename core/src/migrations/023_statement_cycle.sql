@@ -0,0 +1,13 @@
+-- Migration: Credit card statement cycles
+-- Day of the month a card's statement closes and its payment is due, so
+-- reports can show the open statement and when the last one must be paid.
+-- Both are NULL for accounts without a cycle
+
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS statement_closing_day INTEGER;
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS statement_due_day INTEGER;
+
+-- The accounts view caches its column list (see 013), so recreate it
+DROP VIEW IF EXISTS accounts;
+
+CREATE VIEW accounts AS
+SELECT * FROM sys_accounts;
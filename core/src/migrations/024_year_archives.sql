@@ -0,0 +1,15 @@
+-- Migration: Year archives
+-- Old years can be moved out of the main database into their own DuckDB
+-- files (tl archive year), which keeps everyday queries fast. A mounted
+-- archive is attached read-only to SQL queries for full-history reports
+
+CREATE TABLE IF NOT EXISTS sys_archives (
+    year INTEGER PRIMARY KEY,
+    -- Inside the archives folder next to the database, so a moved data
+    -- directory keeps its archives
+    file_name VARCHAR NOT NULL,
+    transaction_count BIGINT NOT NULL,
+    balance_snapshot_count BIGINT NOT NULL,
+    archived_at TIMESTAMP NOT NULL,
+    mounted BOOLEAN NOT NULL DEFAULT FALSE
+);
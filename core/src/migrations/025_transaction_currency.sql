@@ -0,0 +1,44 @@
+-- Migration: Transaction currency
+-- Multi-currency CSVs (Wise, Revolut) give each row's currency; it is stored
+-- per transaction so those rows aren't assumed to be in the account currency.
+-- NULL means the account's currency
+
+ALTER TABLE sys_transactions ADD COLUMN IF NOT EXISTS currency VARCHAR;
+
+-- Update the transactions view: currency is the transaction's own, falling
+-- back to the account's, and the account's stays available as account_currency
+CREATE OR REPLACE VIEW transactions AS
+SELECT
+    -- Core fields (pass-through, already mapped by adapters)
+    t.transaction_id,
+    t.account_id,
+    t.amount,
+    t.description,
+    t.transaction_date,
+    t.posted_date,
+    t.tags,
+    t.parent_transaction_id,
+    t.tags_auto_applied,
+    t.check_number,
+    t.reference,
+
+    -- Computed: source identification
+    -- Note: Demo mode uses its own database, so no 'demo' case needed here
+    CASE
+        WHEN t.sf_id IS NOT NULL THEN 'simplefin'
+        WHEN t.lf_id IS NOT NULL THEN 'lunchflow'
+        WHEN t.csv_batch_id IS NOT NULL THEN 'csv_import'
+        WHEN t.parent_transaction_id IS NOT NULL THEN 'split'
+        WHEN t.is_manual THEN 'manual'
+        ELSE 'unknown'
+    END AS source,
+
+    -- Account info (joined)
+    a.name AS account_name,
+    a.account_type,
+    COALESCE(t.currency, a.currency) AS currency,
+    a.institution_name,
+    a.currency AS account_currency
+FROM sys_transactions t
+LEFT JOIN sys_accounts a ON t.account_id = a.account_id
+WHERE t.deleted_at IS NULL;
@@ -0,0 +1,9 @@
+-- Migration: Undo imports by batch
+-- Balance snapshots from a CSV import record the import's batch, like its
+-- transactions already do, so `tl import undo` can remove both.
+-- NULL for snapshots from sync, backfill and manual entry
+
+ALTER TABLE sys_balance_snapshots ADD COLUMN IF NOT EXISTS csv_batch_id VARCHAR;
+
+CREATE INDEX IF NOT EXISTS idx_sys_transactions_csv_batch_id ON sys_transactions(csv_batch_id);
+CREATE INDEX IF NOT EXISTS idx_sys_balance_snapshots_csv_batch_id ON sys_balance_snapshots(csv_batch_id);
@@ -0,0 +1,14 @@
+-- Migration: Duplicate review queue
+-- Likely duplicates (same account and amount, close dates, similar
+-- descriptions) are found on demand; this records the pairs a user has
+-- resolved so they aren't suggested again. The pair is stored with the
+-- smaller transaction ID first.
+
+CREATE TABLE IF NOT EXISTS sys_duplicate_reviews (
+    transaction_id_a VARCHAR NOT NULL,
+    transaction_id_b VARCHAR NOT NULL,
+    -- 'merged' (one side deleted) or 'dismissed' (not duplicates)
+    resolution VARCHAR NOT NULL,
+    resolved_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (transaction_id_a, transaction_id_b)
+);
@@ -0,0 +1,12 @@
+-- Migration: Per-account sign policy
+-- Some providers and card exports report charges as positive amounts.
+-- 'inverted' accounts have every synced and imported amount flipped on the
+-- way in; NULL means amounts are stored as reported
+
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS sign_policy VARCHAR;
+
+-- The accounts view caches its column list (see 013), so recreate it
+DROP VIEW IF EXISTS accounts;
+
+CREATE VIEW accounts AS
+SELECT * FROM sys_accounts;
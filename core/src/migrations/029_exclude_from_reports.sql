@@ -0,0 +1,47 @@
+-- Migration: Exclude transactions from reports
+-- Money that only passes through (an employer reimbursement, a test
+-- transaction) stays in the ledger and in account balances, but reports,
+-- budgets and income detection leave it out. Rules can set the flag on
+-- the transactions they match
+
+ALTER TABLE sys_transactions ADD COLUMN IF NOT EXISTS excluded_from_reports BOOLEAN DEFAULT FALSE;
+
+ALTER TABLE sys_transactions_rules ADD COLUMN IF NOT EXISTS exclude_from_reports BOOLEAN DEFAULT FALSE;
+
+-- Update the transactions view to include the new field
+CREATE OR REPLACE VIEW transactions AS
+SELECT
+    -- Core fields (pass-through, already mapped by adapters)
+    t.transaction_id,
+    t.account_id,
+    t.amount,
+    t.description,
+    t.transaction_date,
+    t.posted_date,
+    t.tags,
+    t.parent_transaction_id,
+    t.tags_auto_applied,
+    t.check_number,
+    t.reference,
+    COALESCE(t.excluded_from_reports, FALSE) AS excluded_from_reports,
+
+    -- Computed: source identification
+    -- Note: Demo mode uses its own database, so no 'demo' case needed here
+    CASE
+        WHEN t.sf_id IS NOT NULL THEN 'simplefin'
+        WHEN t.lf_id IS NOT NULL THEN 'lunchflow'
+        WHEN t.csv_batch_id IS NOT NULL THEN 'csv_import'
+        WHEN t.parent_transaction_id IS NOT NULL THEN 'split'
+        WHEN t.is_manual THEN 'manual'
+        ELSE 'unknown'
+    END AS source,
+
+    -- Account info (joined)
+    a.name AS account_name,
+    a.account_type,
+    COALESCE(t.currency, a.currency) AS currency,
+    a.institution_name,
+    a.currency AS account_currency
+FROM sys_transactions t
+LEFT JOIN sys_accounts a ON t.account_id = a.account_id
+WHERE t.deleted_at IS NULL;
@@ -0,0 +1,62 @@
+-- Migration: Plaid provider columns
+-- Plaid joins SimpleFIN and Lunchflow as a sync provider. Its account and
+-- transaction IDs get their own columns, like theirs, so sync can match
+-- accounts and dedup transactions; a few raw fields are kept alongside
+
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS pl_id VARCHAR;
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS pl_item_id VARCHAR;
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS pl_mask VARCHAR;
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS pl_subtype VARCHAR;
+
+ALTER TABLE sys_transactions ADD COLUMN IF NOT EXISTS pl_id VARCHAR;
+ALTER TABLE sys_transactions ADD COLUMN IF NOT EXISTS pl_account_id VARCHAR;
+ALTER TABLE sys_transactions ADD COLUMN IF NOT EXISTS pl_merchant_name VARCHAR;
+ALTER TABLE sys_transactions ADD COLUMN IF NOT EXISTS pl_pending BOOLEAN;
+ALTER TABLE sys_transactions ADD COLUMN IF NOT EXISTS pl_category VARCHAR;
+
+CREATE INDEX IF NOT EXISTS idx_sys_transactions_pl_id ON sys_transactions(pl_id);
+
+-- The accounts view caches its column list (see 013), so recreate it
+DROP VIEW IF EXISTS accounts;
+
+CREATE VIEW accounts AS
+SELECT * FROM sys_accounts;
+
+-- Update the transactions view so Plaid transactions get their source
+CREATE OR REPLACE VIEW transactions AS
+SELECT
+    -- Core fields (pass-through, already mapped by adapters)
+    t.transaction_id,
+    t.account_id,
+    t.amount,
+    t.description,
+    t.transaction_date,
+    t.posted_date,
+    t.tags,
+    t.parent_transaction_id,
+    t.tags_auto_applied,
+    t.check_number,
+    t.reference,
+    COALESCE(t.excluded_from_reports, FALSE) AS excluded_from_reports,
+
+    -- Computed: source identification
+    -- Note: Demo mode uses its own database, so no 'demo' case needed here
+    CASE
+        WHEN t.sf_id IS NOT NULL THEN 'simplefin'
+        WHEN t.lf_id IS NOT NULL THEN 'lunchflow'
+        WHEN t.pl_id IS NOT NULL THEN 'plaid'
+        WHEN t.csv_batch_id IS NOT NULL THEN 'csv_import'
+        WHEN t.parent_transaction_id IS NOT NULL THEN 'split'
+        WHEN t.is_manual THEN 'manual'
+        ELSE 'unknown'
+    END AS source,
+
+    -- Account info (joined)
+    a.name AS account_name,
+    a.account_type,
+    COALESCE(t.currency, a.currency) AS currency,
+    a.institution_name,
+    a.currency AS account_currency
+FROM sys_transactions t
+LEFT JOIN sys_accounts a ON t.account_id = a.account_id
+WHERE t.deleted_at IS NULL;
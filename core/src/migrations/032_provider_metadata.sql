@@ -0,0 +1,146 @@
+-- Migration: Provider-agnostic provider data
+-- Each sync provider used to get its own set of columns (sf_*, lf_*, pl_*,
+-- gc_*), so adding one meant a migration and changes across the codebase.
+-- Provider IDs now live in external_ids, keyed by provider name
+-- ({"simplefin": "TX-123"}), and the raw fields each provider reported in
+-- provider_metadata under the same name ({"simplefin": {"posted": ...}}).
+--
+-- The old columns are left in place, no longer written, due to DuckDB ALTER
+-- TABLE limitations. external_ids was unused since 012 and only ever held
+-- '{}', so it's overwritten here
+
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS provider_metadata JSON DEFAULT '{}';
+ALTER TABLE sys_transactions ADD COLUMN IF NOT EXISTS provider_metadata JSON DEFAULT '{}';
+
+-- The per-provider ID indexes cover columns nothing reads any more
+DROP INDEX IF EXISTS idx_sys_transactions_sf_id;
+DROP INDEX IF EXISTS idx_sys_transactions_lf_id;
+DROP INDEX IF EXISTS idx_sys_transactions_pl_id;
+DROP INDEX IF EXISTS idx_sys_transactions_gc_id;
+
+-- =============================================================================
+-- Backfill from the provider columns
+-- json_merge_patch onto '{}' drops null members, so providers that never saw
+-- a row, and fields a provider didn't send, are left out
+-- =============================================================================
+
+UPDATE sys_accounts
+SET
+    external_ids = json_merge_patch('{}', json_object(
+        'simplefin', sf_id,
+        'lunchflow', lf_id,
+        'plaid', pl_id,
+        'gocardless', gc_id
+    )),
+    provider_metadata = json_merge_patch('{}', json_object(
+        'simplefin', CASE WHEN sf_id IS NOT NULL THEN json_object(
+            'name', sf_name,
+            'currency', sf_currency,
+            'balance', sf_balance,
+            'available_balance', sf_available_balance,
+            'balance_date', sf_balance_date,
+            'org_name', sf_org_name,
+            'org_url', sf_org_url,
+            'org_domain', sf_org_domain,
+            'extra', sf_extra
+        ) END,
+        'lunchflow', CASE WHEN lf_id IS NOT NULL THEN json_object(
+            'name', lf_name,
+            'institution_name', lf_institution_name,
+            'institution_logo', lf_institution_logo,
+            'provider', lf_provider,
+            'currency', lf_currency,
+            'status', lf_status
+        ) END,
+        'plaid', CASE WHEN pl_id IS NOT NULL THEN json_object(
+            'item_id', pl_item_id,
+            'mask', pl_mask,
+            'subtype', pl_subtype
+        ) END,
+        'gocardless', CASE WHEN gc_id IS NOT NULL THEN json_object(
+            'institution_id', gc_institution_id,
+            'iban', gc_iban
+        ) END
+    ));
+
+UPDATE sys_transactions
+SET
+    external_ids = json_merge_patch('{}', json_object(
+        'simplefin', sf_id,
+        'lunchflow', lf_id,
+        'plaid', pl_id,
+        'gocardless', gc_id
+    )),
+    provider_metadata = json_merge_patch('{}', json_object(
+        'simplefin', CASE WHEN sf_id IS NOT NULL THEN json_object(
+            'posted', sf_posted,
+            'amount', sf_amount,
+            'description', sf_description,
+            'transacted_at', sf_transacted_at,
+            'pending', sf_pending,
+            'extra', sf_extra
+        ) END,
+        'lunchflow', CASE WHEN lf_id IS NOT NULL THEN json_object(
+            'account_id', lf_account_id,
+            'amount', CAST(lf_amount AS VARCHAR),
+            'currency', lf_currency,
+            'date', CAST(lf_date AS VARCHAR),
+            'merchant', lf_merchant,
+            'description', lf_description,
+            'is_pending', lf_is_pending
+        ) END,
+        'plaid', CASE WHEN pl_id IS NOT NULL THEN json_object(
+            'account_id', pl_account_id,
+            'merchant_name', pl_merchant_name,
+            'pending', pl_pending,
+            'category', pl_category
+        ) END,
+        'gocardless', CASE WHEN gc_id IS NOT NULL THEN json_object(
+            'account_id', gc_account_id,
+            'counterparty', gc_counterparty
+        ) END
+    ));
+
+-- The accounts view caches its column list (see 013), so recreate it
+DROP VIEW IF EXISTS accounts;
+
+CREATE VIEW accounts AS
+SELECT * FROM sys_accounts;
+
+-- The transaction's source is now whichever provider knows it
+CREATE OR REPLACE VIEW transactions AS
+SELECT
+    -- Core fields (pass-through, already mapped by adapters)
+    t.transaction_id,
+    t.account_id,
+    t.amount,
+    t.description,
+    t.transaction_date,
+    t.posted_date,
+    t.tags,
+    t.parent_transaction_id,
+    t.tags_auto_applied,
+    t.check_number,
+    t.reference,
+    COALESCE(t.excluded_from_reports, FALSE) AS excluded_from_reports,
+
+    -- Computed: source identification
+    -- Note: Demo mode uses its own database, so no 'demo' case needed here
+    CASE
+        WHEN len(json_keys(COALESCE(t.external_ids, '{}'))) > 0
+            THEN list_sort(json_keys(t.external_ids))[1]
+        WHEN t.csv_batch_id IS NOT NULL THEN 'csv_import'
+        WHEN t.parent_transaction_id IS NOT NULL THEN 'split'
+        WHEN t.is_manual THEN 'manual'
+        ELSE 'unknown'
+    END AS source,
+
+    -- Account info (joined)
+    a.name AS account_name,
+    a.account_type,
+    COALESCE(t.currency, a.currency) AS currency,
+    a.institution_name,
+    a.currency AS account_currency
+FROM sys_transactions t
+LEFT JOIN sys_accounts a ON t.account_id = a.account_id
+WHERE t.deleted_at IS NULL;
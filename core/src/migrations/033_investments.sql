@@ -0,0 +1,68 @@
+-- Migration: Investment holdings
+-- Brokerage accounts synced from providers that report positions (SimpleFIN)
+-- keep what they hold, not only their cash balance. Each sync replaces an
+-- account's holdings with the ones the provider reports; a holding keeps its
+-- ID across syncs as long as the provider's ID for it stays the same.
+--
+-- Lots are the purchases that make up a holding, for providers or imports
+-- that report them. Security prices build up a per-symbol price history from
+-- every sync, for charting holdings over time.
+
+CREATE TABLE IF NOT EXISTS sys_holdings (
+    holding_id VARCHAR PRIMARY KEY,
+    account_id VARCHAR NOT NULL,
+    -- Ticker, when the provider knows one (cash sweeps and funds often don't)
+    symbol VARCHAR,
+    description VARCHAR,
+    quantity DECIMAL(20,8) NOT NULL,
+    cost_basis DECIMAL(15,2),
+    market_value DECIMAL(15,2),
+    currency VARCHAR NOT NULL DEFAULT 'USD',
+    -- When the provider last valued the position
+    as_of TIMESTAMP NOT NULL,
+    external_ids JSON DEFAULT '{}',
+    provider_metadata JSON DEFAULT '{}',
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_sys_holdings_account_id ON sys_holdings(account_id);
+
+CREATE TABLE IF NOT EXISTS sys_holding_lots (
+    lot_id VARCHAR PRIMARY KEY,
+    holding_id VARCHAR NOT NULL,
+    acquired_date DATE,
+    quantity DECIMAL(20,8) NOT NULL,
+    cost_basis DECIMAL(15,2)
+);
+
+CREATE INDEX IF NOT EXISTS idx_sys_holding_lots_holding_id ON sys_holding_lots(holding_id);
+
+-- One price per symbol and day; a later sync the same day overwrites it
+CREATE TABLE IF NOT EXISTS sys_security_prices (
+    symbol VARCHAR NOT NULL,
+    price_date DATE NOT NULL,
+    price DECIMAL(18,6) NOT NULL,
+    currency VARCHAR NOT NULL DEFAULT 'USD',
+    -- Where the price came from, e.g. 'simplefin'
+    source VARCHAR,
+    PRIMARY KEY (symbol, price_date)
+);
+
+-- Holdings with their account, for queries and plugins
+CREATE OR REPLACE VIEW holdings AS
+SELECT
+    h.holding_id,
+    h.account_id,
+    a.name AS account_name,
+    h.symbol,
+    h.description,
+    h.quantity,
+    CASE WHEN h.quantity <> 0 THEN h.market_value / h.quantity END AS price,
+    h.market_value,
+    h.cost_basis,
+    h.market_value - h.cost_basis AS unrealized_gain,
+    h.currency,
+    h.as_of
+FROM sys_holdings h
+LEFT JOIN sys_accounts a ON h.account_id = a.account_id;
@@ -0,0 +1,56 @@
+-- Migration: Pending transactions
+-- Providers report card authorizations before the bank posts them, often
+-- under a different ID and amount once they do. The flag lets sync update
+-- the pending row in place when it posts instead of adding a second one,
+-- and lets the doctor find pending rows that never posted.
+--
+-- Backfilled from the raw fields each provider stored (see 032)
+
+ALTER TABLE sys_transactions ADD COLUMN IF NOT EXISTS pending BOOLEAN DEFAULT FALSE;
+
+UPDATE sys_transactions
+SET pending = COALESCE(
+    TRY_CAST(json_extract_string(provider_metadata, '$.simplefin.pending') AS BOOLEAN)
+        OR TRY_CAST(json_extract_string(provider_metadata, '$.lunchflow.is_pending') AS BOOLEAN)
+        OR TRY_CAST(json_extract_string(provider_metadata, '$.plaid.pending') AS BOOLEAN),
+    FALSE
+);
+
+-- Update the transactions view to include the new field
+CREATE OR REPLACE VIEW transactions AS
+SELECT
+    -- Core fields (pass-through, already mapped by adapters)
+    t.transaction_id,
+    t.account_id,
+    t.amount,
+    t.description,
+    t.transaction_date,
+    t.posted_date,
+    t.tags,
+    t.parent_transaction_id,
+    t.tags_auto_applied,
+    t.check_number,
+    t.reference,
+    COALESCE(t.excluded_from_reports, FALSE) AS excluded_from_reports,
+    COALESCE(t.pending, FALSE) AS pending,
+
+    -- Computed: source identification
+    -- Note: Demo mode uses its own database, so no 'demo' case needed here
+    CASE
+        WHEN len(json_keys(COALESCE(t.external_ids, '{}'))) > 0
+            THEN list_sort(json_keys(t.external_ids))[1]
+        WHEN t.csv_batch_id IS NOT NULL THEN 'csv_import'
+        WHEN t.parent_transaction_id IS NOT NULL THEN 'split'
+        WHEN t.is_manual THEN 'manual'
+        ELSE 'unknown'
+    END AS source,
+
+    -- Account info (joined)
+    a.name AS account_name,
+    a.account_type,
+    COALESCE(t.currency, a.currency) AS currency,
+    a.institution_name,
+    a.currency AS account_currency
+FROM sys_transactions t
+LEFT JOIN sys_accounts a ON t.account_id = a.account_id
+WHERE t.deleted_at IS NULL;
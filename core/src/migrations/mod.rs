@@ -61,7 +61,7 @@ pub const MIGRATIONS: &[(&str, &str)] = &[
         include_str!("012_provider_specific_columns.sql"),
     ),
     // Note: external_ids column is orphaned but kept due to DuckDB ALTER TABLE limitations
-    // The column stores '{}' for new data and is not read by any code
+    // The column stores '{}' for new data and is not read by any code (until 032)
     (
         "013_refresh_accounts_view.sql",
         include_str!("013_refresh_accounts_view.sql"),
@@ -74,4 +74,64 @@ pub const MIGRATIONS: &[(&str, &str)] = &[
         "015_recreate_dedup_indexes.sql",
         include_str!("015_recreate_dedup_indexes.sql"),
     ),
+    (
+        "016_check_number_reference.sql",
+        include_str!("016_check_number_reference.sql"),
+    ),
+    ("017_sync_history.sql", include_str!("017_sync_history.sql")),
+    (
+        "018_balance_snapshots_daily.sql",
+        include_str!("018_balance_snapshots_daily.sql"),
+    ),
+    (
+        "019_account_archive.sql",
+        include_str!("019_account_archive.sql"),
+    ),
+    ("020_device_sync.sql", include_str!("020_device_sync.sql")),
+    ("021_change_log.sql", include_str!("021_change_log.sql")),
+    (
+        "022_account_type_taxonomy.sql",
+        include_str!("022_account_type_taxonomy.sql"),
+    ),
+    ("023_statement_cycle.sql", include_str!("023_statement_cycle.sql")),
+    ("024_year_archives.sql", include_str!("024_year_archives.sql")),
+    (
+        "025_transaction_currency.sql",
+        include_str!("025_transaction_currency.sql"),
+    ),
+    (
+        "026_import_batch_undo.sql",
+        include_str!("026_import_batch_undo.sql"),
+    ),
+    (
+        "027_duplicate_reviews.sql",
+        include_str!("027_duplicate_reviews.sql"),
+    ),
+    (
+        "028_account_sign_policy.sql",
+        include_str!("028_account_sign_policy.sql"),
+    ),
+    (
+        "029_exclude_from_reports.sql",
+        include_str!("029_exclude_from_reports.sql"),
+    ),
+    (
+        "030_plaid_columns.sql",
+        include_str!("030_plaid_columns.sql"),
+    ),
+    (
+        "031_gocardless_columns.sql",
+        include_str!("031_gocardless_columns.sql"),
+    ),
+    // Note: the sf_/lf_/pl_/gc_ columns are orphaned from here on, kept due to the
+    // same DuckDB ALTER TABLE limitations; external_ids is read and written again
+    (
+        "032_provider_metadata.sql",
+        include_str!("032_provider_metadata.sql"),
+    ),
+    ("033_investments.sql", include_str!("033_investments.sql")),
+    (
+        "034_pending_transactions.sql",
+        include_str!("034_pending_transactions.sql"),
+    ),
 ];
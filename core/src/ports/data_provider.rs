@@ -1,13 +1,14 @@
 //! Data aggregation provider port
 //!
 //! Defines the interface for fetching account and transaction data from
-//! external sources (SimpleFIN, demo data, CSV files, etc.)
+//! external sources (SimpleFIN, Lunchflow, Plaid, GoCardless, Coinbase, Kraken,
+//! demo data, etc.)
 
 use chrono::NaiveDate;
 use serde_json::Value as JsonValue;
 
 use crate::domain::result::Result;
-use crate::domain::{Account, BalanceSnapshot, Transaction};
+use crate::domain::{Account, BalanceSnapshot, Holding, Transaction};
 
 /// Result of fetching accounts from a provider
 #[derive(Debug, Default)]
@@ -15,6 +16,15 @@ pub struct FetchAccountsResult {
     pub accounts: Vec<Account>,
     pub balance_snapshots: Vec<BalanceSnapshot>,
     pub warnings: Vec<String>,
+    /// Provider IDs of accounts that hold nothing, e.g. the wallet an exchange
+    /// keeps for every asset it lists. Sync keeps the ones it already has up
+    /// to date but doesn't add the others.
+    pub empty_accounts: Vec<String>,
+    /// Investment positions keyed by provider account ID, for providers that
+    /// report them. Each account's holdings replace the stored ones, so an
+    /// account missing from the list has sold everything; None leaves stored
+    /// holdings alone.
+    pub holdings: Option<Vec<(String, Holding)>>,
 }
 
 /// Result of fetching transactions from a provider
@@ -23,6 +33,15 @@ pub struct FetchTransactionsResult {
     /// Transactions keyed by provider account ID
     pub transactions: Vec<(String, Transaction)>,
     pub warnings: Vec<String>,
+    /// (provider account ID, error) for accounts whose transactions couldn't
+    /// be fetched; the other accounts' transactions still stand
+    pub failed_accounts: Vec<(String, String)>,
+    /// Provider transaction IDs the provider has withdrawn, e.g. pending
+    /// transactions that posted under a new ID
+    pub removed: Vec<String>,
+    /// Settings to store once the transactions are saved, for providers that
+    /// keep a sync cursor (None leaves the stored settings alone)
+    pub settings: Option<JsonValue>,
 }
 
 /// Data aggregation provider trait
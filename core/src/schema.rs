@@ -0,0 +1,119 @@
+//! JSON Schemas for command output
+//!
+//! Every type printed by a `--json` flag derives `JsonSchema`, so plugin
+//! authors and script writers can validate payloads and get editor
+//! autocomplete from `tl schema dump`.
+
+use schemars::schema::RootSchema;
+use schemars::{schema_for, JsonSchema};
+
+use crate::adapters::duckdb::{ArchiveInfo, ChangeEvent, QueryResult, SyncHistoryEntry};
+use crate::domain::{AccessToken, BackupMetadata, EncryptionStatus};
+#[cfg(feature = "http")]
+use crate::services::PublishResult;
+use crate::services::{
+    AccountDetails, AccountInfo, AccountSyncActivity, BackfillExecuteResult,
+    BalanceSnapshotPreview, DeviceSyncResult, DeviceSyncStatus, DoctorResult, FolderImportSummary,
+    HoldingsSummary, ImportResult, IncomeSummary, IssuedToken, LogEntry, MoveResult, PruneResult,
+    QueryLogEntry, StatusSummary, StorageBreakdown, SyncResult, TagResult, TransactionInfo,
+    UsagePing,
+};
+#[cfg(feature = "reports")]
+use crate::services::{CardCycle, CashFlows, PeriodComparison, Statement};
+
+/// The schema of one output type
+pub struct OutputSchema {
+    /// Type name, also the schema title
+    pub name: String,
+    /// Command whose `--json` output it describes
+    pub command: &'static str,
+    pub schema: RootSchema,
+}
+
+impl OutputSchema {
+    pub fn of<T: JsonSchema>(command: &'static str) -> Self {
+        Self {
+            name: T::schema_name(),
+            command,
+            schema: schema_for!(T),
+        }
+    }
+}
+
+/// Schemas for every core type printed by `--json`
+///
+/// Commands that print a list (e.g. `tl backup list --json`) print an array
+/// of the listed type.
+pub fn output_schemas() -> Vec<OutputSchema> {
+    #[allow(unused_mut)]
+    let mut schemas = vec![
+        OutputSchema::of::<StatusSummary>("tl status"),
+        OutputSchema::of::<SyncResult>("tl sync"),
+        OutputSchema::of::<SyncHistoryEntry>("tl sync history"),
+        OutputSchema::of::<AccountSyncActivity>("tl sync history --accounts"),
+        OutputSchema::of::<ImportResult>("tl import"),
+        OutputSchema::of::<FolderImportSummary>("tl import --watch-folder"),
+        OutputSchema::of::<QueryResult>("tl query"),
+        OutputSchema::of::<TagResult>("tl tag"),
+        OutputSchema::of::<TransactionInfo>("tl tx list / show / edit"),
+        OutputSchema::of::<BackupMetadata>("tl backup create / tl backup list"),
+        OutputSchema::of::<DoctorResult>("tl doctor"),
+        OutputSchema::of::<EncryptionStatus>("tl encrypt status"),
+        OutputSchema::of::<IssuedToken>("tl token issue"),
+        OutputSchema::of::<AccessToken>("tl token list / tl token revoke"),
+        OutputSchema::of::<LogEntry>("tl logs list"),
+        OutputSchema::of::<QueryLogEntry>("tl logs queries"),
+        OutputSchema::of::<UsagePing>("tl logs ping --preview"),
+        OutputSchema::of::<AccountInfo>(
+            "tl accounts list / rename / set-nickname / set-type / set-cycle / archive",
+        ),
+        OutputSchema::of::<AccountDetails>("tl accounts show"),
+        OutputSchema::of::<BalanceSnapshotPreview>("tl balance backfill --dry-run"),
+        OutputSchema::of::<BackfillExecuteResult>("tl balance backfill"),
+        OutputSchema::of::<IncomeSummary>("tl income"),
+        OutputSchema::of::<HoldingsSummary>("tl holdings"),
+        OutputSchema::of::<MoveResult>("tl move-data"),
+        OutputSchema::of::<DeviceSyncResult>("tl device-sync run"),
+        OutputSchema::of::<DeviceSyncStatus>("tl device-sync status"),
+        OutputSchema::of::<ChangeEvent>("tl cdc tail (one per line)"),
+        OutputSchema::of::<ArchiveInfo>("tl archive list / year / mount / unmount"),
+        OutputSchema::of::<StorageBreakdown>("tl storage show"),
+        OutputSchema::of::<PruneResult>("tl storage prune"),
+    ];
+    #[cfg(feature = "http")]
+    schemas.push(OutputSchema::of::<PublishResult>("tl publish"));
+    #[cfg(feature = "reports")]
+    schemas.extend([
+        OutputSchema::of::<Statement>("tl report statement"),
+        OutputSchema::of::<PeriodComparison>("tl report compare"),
+        OutputSchema::of::<CardCycle>("tl report cards"),
+        OutputSchema::of::<CashFlows>("tl report flows"),
+    ]);
+    schemas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_schema_names_are_unique() {
+        let schemas = output_schemas();
+        let mut names: Vec<&str> = schemas.iter().map(|s| s.name.as_str()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), schemas.len());
+    }
+
+    #[test]
+    fn test_schema_includes_nested_types() {
+        let schema = OutputSchema::of::<SyncResult>("tl sync");
+        let json = serde_json::to_value(&schema.schema).unwrap();
+        assert_eq!(json["title"], "SyncResult");
+        assert!(json["definitions"]["IntegrationSyncResult"].is_object());
+        // Fields hidden from JSON output stay out of the schema
+        assert!(json["definitions"]["IntegrationSyncResult"]["properties"]
+            .get("new_by_account")
+            .is_none());
+    }
+}
@@ -0,0 +1,384 @@
+//! Account service - list and edit accounts
+//!
+//! Synced accounts get their name from the bank on every sync, so they are
+//! relabelled with a nickname instead; names only change for accounts that
+//! were created by hand or by an import. Archiving hides an account from
+//! lists but keeps its transactions and balance history.
+//!
+//! An account's sign policy flips amounts from sources that report charges as
+//! positive. Changing it only affects what arrives later, unless the existing
+//! transactions are normalized along with it.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::adapters::duckdb::DuckDbRepository;
+use crate::domain::{Account, AccountType, SignPolicy, StatementCycle};
+
+/// An account as shown by `tl accounts`
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct AccountInfo {
+    pub id: String,
+    pub name: String,
+    pub nickname: Option<String>,
+    pub account_type: Option<String>,
+    /// "asset" or "liability"
+    pub classification: Option<String>,
+    pub currency: String,
+    /// Latest balance snapshot
+    #[schemars(with = "Option<String>")]
+    pub balance: Option<Decimal>,
+    pub institution_name: Option<String>,
+    /// "simplefin", "lunchflow", "plaid", "gocardless", "coinbase" or "kraken"; None for manual and imported accounts
+    pub integration: Option<String>,
+    /// None while the account is active
+    pub archived_at: Option<DateTime<Utc>>,
+    /// Credit cards only; None when not configured
+    pub statement_cycle: Option<StatementCycle>,
+    /// How synced and imported amounts are signed before they're stored
+    pub sign_policy: SignPolicy,
+}
+
+/// An account after its sign policy changed
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SignPolicyChange {
+    #[serde(flatten)]
+    pub account: AccountInfo,
+    /// Existing transactions whose amounts were flipped to match
+    pub transactions_flipped: usize,
+}
+
+/// One account with its activity, for `tl accounts show`
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct AccountDetails {
+    #[serde(flatten)]
+    pub account: AccountInfo,
+    pub transaction_count: usize,
+    pub first_transaction_date: Option<NaiveDate>,
+    pub last_transaction_date: Option<NaiveDate>,
+    pub balance_snapshot_count: usize,
+    pub last_balance_at: Option<DateTime<Utc>>,
+}
+
+/// Account service for listing and editing accounts
+pub struct AccountService {
+    repository: Arc<DuckDbRepository>,
+}
+
+impl AccountService {
+    pub fn new(repository: Arc<DuckDbRepository>) -> Self {
+        Self { repository }
+    }
+
+    /// Accounts sorted by name, leaving out archived ones unless asked
+    pub fn list(&self, include_archived: bool) -> Result<Vec<AccountInfo>> {
+        let archived = self.repository.get_archived_accounts()?;
+        let cycles = self.repository.get_statement_cycles()?;
+        let policies = self.repository.get_sign_policies()?;
+        let mut accounts: Vec<_> = self
+            .repository
+            .get_accounts()?
+            .into_iter()
+            .map(|account| {
+                let id = account.id.to_string();
+                to_info(
+                    account,
+                    archived.get(&id).copied(),
+                    cycles.get(&id).copied(),
+                    policies.get(&id).copied().unwrap_or_default(),
+                )
+            })
+            .filter(|info| include_archived || info.archived_at.is_none())
+            .collect();
+        accounts.sort_by_key(|info| info.name.to_lowercase());
+        Ok(accounts)
+    }
+
+    pub fn get(&self, account_id: &str) -> Result<AccountInfo> {
+        let account = self.find(account_id)?;
+        let archived_at = self
+            .repository
+            .get_archived_accounts()?
+            .get(account_id)
+            .copied();
+        let statement_cycle = self
+            .repository
+            .get_statement_cycles()?
+            .get(account_id)
+            .copied();
+        let sign_policy = self
+            .repository
+            .get_sign_policies()?
+            .get(account_id)
+            .copied()
+            .unwrap_or_default();
+        Ok(to_info(account, archived_at, statement_cycle, sign_policy))
+    }
+
+    /// An account with its transaction and balance history counts
+    pub fn details(&self, account_id: &str) -> Result<AccountDetails> {
+        let account = self.get(account_id)?;
+        let transactions = self.repository.get_transactions_by_account(account_id)?;
+        let snapshots = self.repository.get_balance_snapshots(Some(account_id))?;
+
+        Ok(AccountDetails {
+            account,
+            transaction_count: transactions.len(),
+            first_transaction_date: transactions.iter().map(|t| t.transaction_date).min(),
+            last_transaction_date: transactions.iter().map(|t| t.transaction_date).max(),
+            balance_snapshot_count: snapshots.len(),
+            last_balance_at: snapshots.iter().map(|s| s.snapshot_time.and_utc()).max(),
+        })
+    }
+
+    /// Rename a manual or imported account
+    pub fn rename(&self, account_id: &str, name: &str) -> Result<AccountInfo> {
+        let account = self.find(account_id)?;
+        let name = name.trim();
+        if name.is_empty() {
+            anyhow::bail!("Account name cannot be empty");
+        }
+        if let Some(integration) = account.provider.source() {
+            anyhow::bail!(
+                "'{}' is synced from {}, which sets its name on every sync. Set a nickname instead.",
+                account.name,
+                integration
+            );
+        }
+        self.repository.update_account_name(account_id, name)?;
+        self.get(account_id)
+    }
+
+    /// Set the nickname shown instead of the name, or clear it with None
+    pub fn set_nickname(&self, account_id: &str, nickname: Option<&str>) -> Result<AccountInfo> {
+        self.find(account_id)?;
+        let nickname = nickname.map(str::trim).filter(|n| !n.is_empty());
+        self.repository
+            .update_account_nickname(account_id, nickname)?;
+        self.get(account_id)
+    }
+
+    /// Set the account type; the classification follows from it
+    pub fn set_type(&self, account_id: &str, account_type: &str) -> Result<AccountInfo> {
+        self.find(account_id)?;
+        let account_type: AccountType = account_type.parse().map_err(anyhow::Error::msg)?;
+        self.repository.update_account_type(
+            account_id,
+            account_type.as_str(),
+            account_type.classification(),
+        )?;
+        self.get(account_id)
+    }
+
+    /// Set when a credit card's statements close and are due, or clear it with None
+    pub fn set_statement_cycle(
+        &self,
+        account_id: &str,
+        cycle: Option<StatementCycle>,
+    ) -> Result<AccountInfo> {
+        let account = self.find(account_id)?;
+        let is_card = account
+            .account_type
+            .as_deref()
+            .and_then(|t| t.parse::<AccountType>().ok())
+            == Some(AccountType::CreditCard);
+        if cycle.is_some() && !is_card {
+            anyhow::bail!(
+                "'{}' isn't a credit card. Set its type with `tl accounts set-type` first.",
+                account.nickname.unwrap_or(account.name)
+            );
+        }
+        self.repository
+            .set_account_statement_cycle(account_id, cycle)?;
+        self.get(account_id)
+    }
+
+    /// Set how an account's source signs amounts
+    ///
+    /// With `normalize_existing`, transactions already stored under the old
+    /// policy are flipped once to match the new one. Manual transactions keep
+    /// their amounts, and nothing is flipped when the policy doesn't change,
+    /// so running this twice is harmless.
+    pub fn set_sign_policy(
+        &self,
+        account_id: &str,
+        policy: SignPolicy,
+        normalize_existing: bool,
+    ) -> Result<SignPolicyChange> {
+        let current = self.get(account_id)?.sign_policy;
+        let mut transactions_flipped = 0;
+        if policy != current {
+            if normalize_existing {
+                transactions_flipped = self.repository.invert_transaction_amounts(account_id)?;
+            }
+            self.repository
+                .set_account_sign_policy(account_id, policy)?;
+        }
+        Ok(SignPolicyChange {
+            account: self.get(account_id)?,
+            transactions_flipped,
+        })
+    }
+
+    /// Archive an account, or restore it with `archived = false`
+    pub fn set_archived(&self, account_id: &str, archived: bool) -> Result<AccountInfo> {
+        self.find(account_id)?;
+        self.repository.set_account_archived(account_id, archived)?;
+        self.get(account_id)
+    }
+
+    fn find(&self, account_id: &str) -> Result<Account> {
+        self.repository
+            .get_account_by_id(account_id)?
+            .ok_or_else(|| anyhow::anyhow!("Account not found: {}", account_id))
+    }
+}
+
+fn to_info(
+    account: Account,
+    archived_at: Option<DateTime<Utc>>,
+    statement_cycle: Option<StatementCycle>,
+    sign_policy: SignPolicy,
+) -> AccountInfo {
+    AccountInfo {
+        id: account.id.to_string(),
+        integration: account.provider.source().map(str::to_string),
+        name: account.name,
+        nickname: account.nickname,
+        account_type: account.account_type,
+        classification: account.classification,
+        currency: account.currency,
+        balance: account.balance,
+        institution_name: account.institution_name,
+        archived_at,
+        statement_cycle,
+        sign_policy,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn setup() -> (tempfile::TempDir, Arc<DuckDbRepository>) {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        (dir, repository)
+    }
+
+    #[test]
+    fn test_edit_and_archive() {
+        let (_dir, repository) = setup();
+        let id = Uuid::new_v4();
+        repository
+            .upsert_account(&Account::new(id, "Old Savings"))
+            .unwrap();
+        let id = id.to_string();
+        let service = AccountService::new(repository.clone());
+
+        assert_eq!(service.rename(&id, "Savings").unwrap().name, "Savings");
+        let info = service.set_nickname(&id, Some("Rainy day")).unwrap();
+        assert_eq!(info.nickname.as_deref(), Some("Rainy day"));
+        assert_eq!(service.set_nickname(&id, None).unwrap().nickname, None);
+
+        let info = service.set_type(&id, "Credit Card").unwrap();
+        assert_eq!(info.account_type.as_deref(), Some("credit_card"));
+        assert_eq!(info.classification.as_deref(), Some("liability"));
+        assert!(service.set_type(&id, "piggy bank").is_err());
+
+        assert!(service
+            .set_archived(&id, true)
+            .unwrap()
+            .archived_at
+            .is_some());
+        assert!(service.list(false).unwrap().is_empty());
+        assert_eq!(service.list(true).unwrap().len(), 1);
+        assert!(service
+            .set_archived(&id, false)
+            .unwrap()
+            .archived_at
+            .is_none());
+        assert_eq!(service.list(false).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_sign_policy_normalizes_existing_transactions() {
+        use crate::domain::Transaction;
+
+        let (_dir, repository) = setup();
+        let account = Account::new(Uuid::new_v4(), "Rewards Card");
+        repository.upsert_account(&account).unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let mut charge = Transaction::new(Uuid::new_v4(), account.id, Decimal::new(4250, 2), date);
+        charge
+            .provider
+            .set_external_id("simplefin", Some("sf-1".to_string()));
+        repository.upsert_transaction(&charge).unwrap();
+        let mut manual = Transaction::new(Uuid::new_v4(), account.id, Decimal::new(-500, 2), date);
+        manual.is_manual = true;
+        repository.upsert_transaction(&manual).unwrap();
+        let id = account.id.to_string();
+        let service = AccountService::new(repository.clone());
+
+        assert_eq!(
+            service.get(&id).unwrap().sign_policy,
+            SignPolicy::AsReported
+        );
+        let change = service
+            .set_sign_policy(&id, SignPolicy::Inverted, true)
+            .unwrap();
+        assert_eq!(change.account.sign_policy, SignPolicy::Inverted);
+        assert_eq!(change.transactions_flipped, 1);
+        let amount = |tx: &Transaction| {
+            repository
+                .get_transaction_by_id(&tx.id.to_string())
+                .unwrap()
+                .unwrap()
+                .amount
+        };
+        assert_eq!(amount(&charge), Decimal::new(-4250, 2));
+        assert_eq!(amount(&manual), Decimal::new(-500, 2));
+
+        // Setting the same policy again doesn't flip anything twice
+        let change = service
+            .set_sign_policy(&id, SignPolicy::Inverted, true)
+            .unwrap();
+        assert_eq!(change.transactions_flipped, 0);
+        assert_eq!(amount(&charge), Decimal::new(-4250, 2));
+
+        // Without normalizing, only the policy changes
+        let change = service
+            .set_sign_policy(&id, SignPolicy::AsReported, false)
+            .unwrap();
+        assert_eq!(change.account.sign_policy, SignPolicy::AsReported);
+        assert_eq!(change.transactions_flipped, 0);
+        assert_eq!(amount(&charge), Decimal::new(-4250, 2));
+    }
+
+    #[test]
+    fn test_synced_account_keeps_its_name() {
+        let (_dir, repository) = setup();
+        let id = Uuid::new_v4();
+        let mut account = Account::new(id, "CHK 1234");
+        account
+            .provider
+            .set_external_id("simplefin", Some("ACT-1".to_string()));
+        repository.upsert_account(&account).unwrap();
+        let service = AccountService::new(repository);
+
+        let error = service.rename(&id.to_string(), "Checking").unwrap_err();
+        assert!(error.to_string().contains("nickname"));
+        assert_eq!(
+            service.get(&id.to_string()).unwrap().integration.as_deref(),
+            Some("simplefin")
+        );
+    }
+}
@@ -0,0 +1,227 @@
+//! Archive service - move old years out of the main database
+//!
+//! `tl archive year 2019` copies that year's transactions and balance
+//! snapshots to `archives/treeline-2019.duckdb` next to the database and
+//! removes them from it, so the main file stays small and everyday queries
+//! stay fast. Archives are ordinary DuckDB files, encrypted with the
+//! database key when encryption is on. Besides the queryable `transactions`
+//! and `balance_snapshots` tables they keep the raw `sys_*` rows, deleted
+//! transactions included, so a year can be restored as it was.
+//!
+//! Mounting an archive attaches it read-only to SQL queries as
+//! `archive_2019`, along with `all_transactions` and `all_balance_snapshots`
+//! views spanning the main database and every mounted archive. Reports,
+//! balances and duplicate detection only see the main database.
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use chrono::{Datelike, NaiveDate};
+
+use crate::adapters::duckdb::{ArchiveInfo, DuckDbRepository};
+
+/// Archive service for archiving, listing and mounting years
+pub struct ArchiveService {
+    repository: Arc<DuckDbRepository>,
+}
+
+impl ArchiveService {
+    pub fn new(repository: Arc<DuckDbRepository>) -> Self {
+        Self { repository }
+    }
+
+    /// Move `year` into its own archive file
+    ///
+    /// Only years before the one `today` is in, which syncs still add to.
+    pub fn archive_year(&self, year: i32, today: NaiveDate) -> Result<ArchiveInfo> {
+        if year >= today.year() {
+            anyhow::bail!(
+                "Only past years can be archived; {} is still being added to",
+                year
+            );
+        }
+        if self.list()?.iter().any(|a| a.year == year) {
+            anyhow::bail!("{} is already archived", year);
+        }
+        self.repository.archive_year(year)
+    }
+
+    /// Every archived year, oldest first
+    pub fn list(&self) -> Result<Vec<ArchiveInfo>> {
+        self.repository.get_archives()
+    }
+
+    /// Attach archives to SQL queries from now on (every archive when `years` is empty)
+    ///
+    /// Checks each one opens first, so a missing file or an archive written
+    /// under an earlier encryption key is reported here rather than skipped.
+    pub fn mount(&self, years: &[i32]) -> Result<Vec<ArchiveInfo>> {
+        let archives = self.select(years)?;
+        for archive in &archives {
+            if archive.size_bytes.is_none() {
+                anyhow::bail!(
+                    "The {} archive is missing from {}",
+                    archive.year,
+                    archive.path.display()
+                );
+            }
+            self.repository.set_archive_mounted(archive.year, true)?;
+            let opened = self.repository.execute_query_readonly(&format!(
+                "SELECT COUNT(*) FROM archive_{}.transactions",
+                archive.year
+            ));
+            if opened.is_err() {
+                self.repository.set_archive_mounted(archive.year, false)?;
+            }
+            opened.with_context(|| {
+                format!(
+                    "Couldn't open {} (was it archived before the encryption password changed?)",
+                    archive.path.display()
+                )
+            })?;
+        }
+        self.select(years)
+    }
+
+    /// Stop attaching archives to SQL queries (every archive when `years` is empty)
+    pub fn unmount(&self, years: &[i32]) -> Result<Vec<ArchiveInfo>> {
+        for archive in self.select(years)? {
+            self.repository.set_archive_mounted(archive.year, false)?;
+        }
+        self.select(years)
+    }
+
+    /// The archives for `years`, or all of them when it's empty
+    fn select(&self, years: &[i32]) -> Result<Vec<ArchiveInfo>> {
+        let archives = self.list()?;
+        if let Some(year) = years
+            .iter()
+            .find(|year| !archives.iter().any(|a| a.year == **year))
+        {
+            anyhow::bail!("{} isn't archived (see `tl archive list`)", year);
+        }
+        Ok(archives
+            .into_iter()
+            .filter(|a| years.is_empty() || years.contains(&a.year))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Account, BalanceSnapshot, Transaction};
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_archive_and_mount_year() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        let account = Account::new(Uuid::new_v4(), "Checking");
+        repository.upsert_account(&account).unwrap();
+        let tx = |amount: i64, day: &str| {
+            Transaction::new(
+                Uuid::new_v4(),
+                account.id,
+                Decimal::new(amount, 0),
+                date(day),
+            )
+        };
+        let deleted = tx(-5, "2019-03-01");
+        repository
+            .bulk_insert_transactions(&[
+                tx(-10, "2019-01-01"),
+                tx(-20, "2019-12-31"),
+                tx(-30, "2020-01-01"),
+                deleted.clone(),
+            ])
+            .unwrap();
+        repository
+            .delete_transaction(&deleted.id.to_string())
+            .unwrap();
+        let time = date("2019-06-30").and_hms_opt(23, 0, 0).unwrap();
+        repository
+            .add_balance_snapshot(&BalanceSnapshot::new(
+                account.id,
+                Decimal::new(500, 0),
+                time,
+            ))
+            .unwrap();
+
+        let service = ArchiveService::new(Arc::clone(&repository));
+        let today = date("2021-03-01");
+        assert!(service.archive_year(2021, today).is_err());
+        assert!(service.archive_year(2018, today).is_err());
+        assert!(!repository
+            .archive_dir()
+            .join("treeline-2018.duckdb")
+            .exists());
+
+        let last_seq = repository.get_changes(0, 100).unwrap().last().unwrap().seq;
+        let archive = service.archive_year(2019, today).unwrap();
+        assert_eq!(archive.file_name, "treeline-2019.duckdb");
+        assert_eq!(archive.transaction_count, 2);
+        assert_eq!(archive.balance_snapshot_count, 1);
+        assert!(archive.path.is_file());
+        assert!(!archive.mounted);
+        assert_eq!(repository.get_transaction_count().unwrap(), 1);
+        assert_eq!(repository.get_balance_snapshot_count().unwrap(), 0);
+        assert!(service.archive_year(2019, today).is_err());
+
+        // The change feed sees the moved rows leave
+        let changes: Vec<(String, String)> = repository
+            .get_changes(last_seq, 100)
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.entity, c.op))
+            .collect();
+        assert_eq!(changes.len(), 4);
+        assert!(changes.iter().all(|(_, op)| op == "delete"));
+        assert_eq!(
+            changes
+                .iter()
+                .filter(|(entity, _)| entity == "balance_snapshot")
+                .count(),
+            1
+        );
+
+        let count = |sql: &str| {
+            repository
+                .execute_query_readonly(sql)
+                .map(|r| r.rows[0][0].as_i64().unwrap())
+        };
+        assert!(count("SELECT COUNT(*) FROM archive_2019.transactions").is_err());
+
+        let mounted = service.mount(&[]).unwrap();
+        assert!(mounted[0].mounted);
+        assert_eq!(
+            count("SELECT COUNT(*) FROM archive_2019.transactions").unwrap(),
+            2
+        );
+        // The raw rows, deleted one included, went along for restoring
+        assert_eq!(
+            count("SELECT COUNT(*) FROM archive_2019.sys_transactions").unwrap(),
+            3
+        );
+        assert_eq!(
+            count("SELECT COUNT(*) FROM archive_2019.sys_balance_snapshots").unwrap(),
+            1
+        );
+        assert_eq!(count("SELECT COUNT(*) FROM all_transactions").unwrap(), 3);
+        assert_eq!(
+            count("SELECT COUNT(*) FROM all_balance_snapshots").unwrap(),
+            1
+        );
+        assert!(service.mount(&[2018]).is_err());
+
+        service.unmount(&[2019]).unwrap();
+        assert!(count("SELECT COUNT(*) FROM all_transactions").is_err());
+    }
+}
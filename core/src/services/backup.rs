@@ -2,24 +2,43 @@
 //!
 //! Creates ZIP archives containing the database and config files,
 //! compatible with the Python CLI backup format.
+//!
+//! Entries are deflated unless settings.json `app.backupCompression` says
+//! otherwise: zstd is smaller and faster, "none" skips compression for
+//! encrypted databases that barely shrink. Restore reads any of them. Each
+//! archive's comment records the level and how long it took to create, so
+//! the backup list can show what a setting costs and saves.
 
 use std::fs::{self, File};
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::{Context, Result};
 use chrono::Utc;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use zip::write::SimpleFileOptions;
 use zip::{ZipArchive, ZipWriter};
 
 use crate::adapters::duckdb::DuckDbRepository;
+use crate::config::{BackupCompression, CompressionMethod, Config};
 use crate::domain::BackupMetadata;
+use crate::services::disk_space::ensure_free_space;
+use crate::services::{HookEvent, HookService};
 
 /// Config files to include in backup (relative to treeline dir)
 const CONFIG_FILES: &[&str] = &["settings.json", "encryption.json"];
 
+/// What a backup's archive comment records
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ArchiveInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    level: Option<i64>,
+    duration_ms: u64,
+}
+
 /// Backup service for database backup management
 ///
 /// The repository is optional - if provided, create() will checkpoint
@@ -64,12 +83,29 @@ impl BackupService {
         self.treeline_dir.join("backups")
     }
 
+    /// Compression from settings.json, or the default when it can't be read
+    fn configured_compression(&self) -> BackupCompression {
+        Config::load(&self.treeline_dir)
+            .map(|config| config.backup_compression)
+            .unwrap_or_default()
+    }
+
     /// Create a backup of the database and config files as a ZIP archive
     ///
     /// If a repository is available, this method first forces a checkpoint
     /// to flush any pending WAL data to the main database file, ensuring
     /// backup consistency.
     pub fn create(&self, max_backups: Option<usize>) -> Result<BackupMetadata> {
+        self.create_with_compression(max_backups, self.configured_compression())
+    }
+
+    /// Create a backup compressed as given rather than as configured
+    pub fn create_with_compression(
+        &self,
+        max_backups: Option<usize>,
+        compression: BackupCompression,
+    ) -> Result<BackupMetadata> {
+        let options = file_options(compression)?;
         let backups_dir = self.backups_dir();
         fs::create_dir_all(&backups_dir)?;
 
@@ -78,6 +114,24 @@ impl BackupService {
             anyhow::bail!("Database file not found");
         }
 
+        // Compression can't be relied on (encrypted databases barely shrink),
+        // so expect the archive to be as large as its contents
+        let mut estimate = fs::metadata(&db_path)?.len();
+        for config_file in CONFIG_FILES {
+            if let Ok(metadata) = fs::metadata(self.treeline_dir.join(config_file)) {
+                estimate += metadata.len();
+            }
+        }
+        ensure_free_space(&backups_dir, estimate, "create a backup")?;
+
+        HookService::new(self.treeline_dir.clone()).run(
+            HookEvent::PreBackup,
+            &serde_json::json!({
+                "database": self.db_filename,
+                "backups_dir": backups_dir,
+            }),
+        );
+
         // Force checkpoint to flush WAL to main database file before backup.
         // This ensures the backup contains all committed data.
         // If no repository is available (e.g., during encryption), skip checkpointing.
@@ -95,10 +149,9 @@ impl BackupService {
         let backup_path = backups_dir.join(&backup_name);
 
         // Create ZIP archive
+        let started = Instant::now();
         let file = File::create(&backup_path).context("Failed to create backup file")?;
         let mut zip = ZipWriter::new(file);
-        let options =
-            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
 
         // Add database file
         zip.start_file(&self.db_filename, options)?;
@@ -119,21 +172,21 @@ impl BackupService {
             }
         }
 
+        let info = ArchiveInfo {
+            level: compression.level,
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+        zip.set_comment(serde_json::to_string(&info)?);
         zip.finish()?;
 
-        let metadata = fs::metadata(&backup_path)?;
-        let size_bytes = metadata.len();
-
         // Apply retention policy
         if let Some(max) = max_backups {
             self.apply_retention(max)?;
         }
 
-        Ok(BackupMetadata {
-            name: backup_name,
-            created_at: Utc::now(),
-            size_bytes,
-        })
+        let mut metadata = BackupMetadata::new(backup_name, Utc::now(), 0);
+        describe_archive(&backup_path, &mut metadata)?;
+        Ok(metadata)
     }
 
     /// List all backups (both .zip and legacy .duckdb formats)
@@ -170,11 +223,12 @@ impl BackupService {
             // Parse timestamp from filename
             let created_at = self.parse_backup_time(&name);
 
-            backups.push(BackupMetadata {
-                name,
-                created_at,
-                size_bytes,
-            });
+            let mut backup = BackupMetadata::new(name, created_at, size_bytes);
+            if ext == Some("zip") {
+                // An unreadable archive still lists; restore reports why
+                let _ = describe_archive(&path, &mut backup);
+            }
+            backups.push(backup);
         }
 
         backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
@@ -208,6 +262,23 @@ impl BackupService {
 
         let db_path = self.treeline_dir.join(&self.db_filename);
 
+        // Room for the pre-restore backup, plus whatever the restored files
+        // add beyond the database they overwrite: max(current, restored)
+        let current_size = fs::metadata(&db_path).map(|m| m.len()).unwrap_or(0);
+        let restored_size = if backup_name.ends_with(".zip") {
+            let mut archive = ZipArchive::new(File::open(&backup_path)?)?;
+            (0..archive.len())
+                .filter_map(|i| archive.by_index(i).ok().map(|f| f.size()))
+                .sum()
+        } else {
+            fs::metadata(&backup_path)?.len()
+        };
+        ensure_free_space(
+            &self.treeline_dir,
+            current_size.max(restored_size),
+            "restore the backup",
+        )?;
+
         // Create a backup of current state first
         if db_path.exists() {
             let now = Utc::now();
@@ -220,8 +291,7 @@ impl BackupService {
             // Create a quick backup of just the DB
             let file = File::create(&pre_restore_path)?;
             let mut zip = ZipWriter::new(file);
-            let options =
-                SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+            let options = file_options(self.configured_compression())?;
 
             zip.start_file(&self.db_filename, options)?;
             let mut db_file = File::open(&db_path)?;
@@ -309,6 +379,62 @@ impl BackupService {
     }
 }
 
+/// Zip entry options for a compression setting
+fn file_options(compression: BackupCompression) -> Result<SimpleFileOptions> {
+    let (method, levels) = match compression.method {
+        CompressionMethod::Deflate => (zip::CompressionMethod::Deflated, 1..=9),
+        CompressionMethod::Zstd => (zip::CompressionMethod::Zstd, 1..=22),
+        CompressionMethod::None => {
+            return Ok(
+                SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored)
+            );
+        }
+    };
+    if let Some(level) = compression.level {
+        if !levels.contains(&level) {
+            anyhow::bail!(
+                "Backup compression level {} is out of range ({}-{})",
+                level,
+                levels.start(),
+                levels.end()
+            );
+        }
+    }
+    Ok(SimpleFileOptions::default()
+        .compression_method(method)
+        .compression_level(compression.level))
+}
+
+/// Fill in a backup's size, compression and creation time from its archive
+fn describe_archive(path: &Path, backup: &mut BackupMetadata) -> Result<()> {
+    backup.size_bytes = fs::metadata(path)?.len();
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    let info: Option<ArchiveInfo> = serde_json::from_slice(archive.comment()).ok();
+
+    let mut uncompressed = 0;
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i)?;
+        uncompressed += file.size();
+        if file.name().ends_with(".duckdb") {
+            backup.compression = Some(
+                match file.compression() {
+                    zip::CompressionMethod::Stored => "none",
+                    zip::CompressionMethod::Deflated => "deflate",
+                    zip::CompressionMethod::Zstd => "zstd",
+                    _ => "other",
+                }
+                .to_string(),
+            );
+        }
+    }
+    backup.uncompressed_bytes = Some(uncompressed);
+    if let Some(info) = info {
+        backup.level = info.level;
+        backup.duration_ms = Some(info.duration_ms);
+    }
+    Ok(())
+}
+
 #[derive(Debug, Serialize)]
 pub struct ClearResult {
     pub deleted: i64,
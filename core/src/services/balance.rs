@@ -7,19 +7,20 @@ use anyhow::Result;
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Utc};
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::adapters::duckdb::DuckDbRepository;
-use crate::domain::BalanceSnapshot;
+use crate::domain::{latest_per_day, BalanceSnapshot};
+use crate::ports::Repository;
 
 /// Balance service for balance snapshot management
 pub struct BalanceService {
-    repository: Arc<DuckDbRepository>,
+    repository: Arc<dyn Repository>,
 }
 
 impl BalanceService {
-    pub fn new(repository: Arc<DuckDbRepository>) -> Self {
+    pub fn new(repository: Arc<dyn Repository>) -> Self {
         Self { repository }
     }
 
@@ -63,6 +64,7 @@ impl BalanceService {
             balance,
             snapshot_time,
             source: Some("manual".to_string()),
+            csv_batch_id: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -111,11 +113,14 @@ impl BalanceService {
         // Get existing balance snapshots for this account
         let existing_snapshots = self.repository.get_balance_snapshots(Some(account_id))?;
 
-        // Build map of existing snapshot dates to their details
-        let existing_by_date: HashMap<NaiveDate, &BalanceSnapshot> = existing_snapshots
-            .iter()
-            .map(|s| (s.snapshot_time.date(), s))
-            .collect();
+        // Each day's latest snapshot (the closing balance when synced several times a day)
+        let existing_by_date = latest_per_day(&existing_snapshots);
+        let mut existing_counts: HashMap<NaiveDate, usize> = HashMap::new();
+        for snapshot in &existing_snapshots {
+            *existing_counts
+                .entry(snapshot.snapshot_time.date())
+                .or_insert(0) += 1;
+        }
 
         // Get transactions for this account
         let transactions = self.repository.get_transactions_by_account(account_id)?;
@@ -194,6 +199,7 @@ impl BalanceService {
                     is_new: existing.is_none(),
                     existing_balance: existing_balance.map(|b| b.to_f64().unwrap_or(0.0)),
                     existing_source,
+                    existing_count: existing_counts.get(&date).copied().unwrap_or(0),
                 });
             }
 
@@ -209,7 +215,8 @@ impl BalanceService {
     /// Execute balance backfill from a known balance
     ///
     /// Creates balance snapshots based on transaction history, replacing any
-    /// existing snapshots in the date range (regardless of source).
+    /// existing snapshots in the date range (regardless of source), including
+    /// intraday ones: each day keeps a single end-of-day snapshot.
     ///
     /// Parameters:
     /// - account_id: The account to backfill
@@ -323,6 +330,7 @@ impl BalanceService {
                     balance: current_balance,
                     snapshot_time: end_of_day,
                     source: Some("backfill".to_string()),
+                    csv_batch_id: None,
                     created_at: Utc::now(),
                     updated_at: Utc::now(),
                 });
@@ -354,14 +362,16 @@ pub struct BalanceResult {
 }
 
 /// Summary of a transaction for preview display
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct TransactionSummary {
     pub description: String,
     pub amount: f64,
 }
 
 /// Preview of a single balance snapshot for UI display
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct BalanceSnapshotPreview {
     pub date: String,
     /// The calculated balance for this date (end of day)
@@ -376,12 +386,134 @@ pub struct BalanceSnapshotPreview {
     pub existing_balance: Option<f64>,
     /// Source of existing snapshot: "sync", "manual", "backfill", "import", or null
     pub existing_source: Option<String>,
+    /// Existing snapshots on this day, all replaced (more than one when synced several times a day)
+    pub existing_count: usize,
 }
 
 /// Result of executing balance backfill
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct BackfillExecuteResult {
     pub snapshots_created: i64,
     pub snapshots_updated: i64,
     pub snapshots_skipped: i64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::memory::InMemoryRepository;
+    use crate::domain::{Account, Transaction};
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    /// An in-memory account with one transaction per (date, amount)
+    fn setup(transactions: &[(&str, i64)]) -> (Arc<InMemoryRepository>, String) {
+        let repository = Arc::new(InMemoryRepository::new());
+        let account = Account::new(Uuid::new_v4(), "Checking");
+        repository.upsert_account(&account).unwrap();
+        for (day, cents) in transactions {
+            let tx = Transaction::new(
+                Uuid::new_v4(),
+                account.id,
+                Decimal::new(*cents, 2),
+                date(day),
+            );
+            repository.upsert_transaction(&tx).unwrap();
+        }
+        (repository, account.id.to_string())
+    }
+
+    #[test]
+    fn test_backfill_works_backwards_from_known_balance() {
+        let (repository, account_id) = setup(&[
+            ("2024-01-01", 10000),
+            ("2024-01-03", -2500),
+            ("2024-01-03", -500),
+        ]);
+        let service = BalanceService::new(repository.clone());
+
+        let result = service
+            .backfill_execute(
+                &account_id,
+                Decimal::new(100000, 2),
+                date("2024-01-03"),
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(result.snapshots_created, 2);
+
+        let balances: Vec<(NaiveDate, Decimal)> = repository
+            .get_balance_snapshots(Some(&account_id))
+            .unwrap()
+            .iter()
+            .map(|s| (s.snapshot_time.date(), s.balance))
+            .collect();
+        assert_eq!(
+            balances,
+            vec![
+                (date("2024-01-03"), Decimal::new(100000, 2)),
+                (date("2024-01-01"), Decimal::new(103000, 2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_backfill_replaces_existing_snapshots() {
+        let (repository, account_id) = setup(&[("2024-01-02", -1000)]);
+        let service = BalanceService::new(repository.clone());
+        service
+            .add_balance(&account_id, Decimal::new(5000, 2), Some(date("2024-01-02")))
+            .unwrap();
+
+        let preview = service
+            .backfill_preview(
+                &account_id,
+                Decimal::new(2000, 2),
+                date("2024-01-02"),
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(preview.len(), 1);
+        assert!(!preview[0].is_new);
+        assert_eq!(preview[0].existing_balance, Some(50.0));
+
+        let result = service
+            .backfill_execute(
+                &account_id,
+                Decimal::new(2000, 2),
+                date("2024-01-02"),
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!((result.snapshots_created, result.snapshots_updated), (1, 1));
+        let snapshots = repository.get_balance_snapshots(Some(&account_id)).unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].balance, Decimal::new(2000, 2));
+    }
+
+    #[test]
+    fn test_add_balance_rejects_duplicates_and_unknown_accounts() {
+        let (repository, account_id) = setup(&[]);
+        let service = BalanceService::new(repository);
+        let day = Some(date("2024-02-01"));
+
+        service
+            .add_balance(&account_id, Decimal::new(100, 0), day)
+            .unwrap();
+        assert!(service
+            .add_balance(&account_id, Decimal::new(100, 0), day)
+            .is_err());
+        assert!(service
+            .add_balance(&account_id, Decimal::new(101, 0), day)
+            .is_ok());
+        assert!(service
+            .add_balance(&Uuid::new_v4().to_string(), Decimal::new(1, 0), day)
+            .is_err());
+    }
+}
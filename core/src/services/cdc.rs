@@ -0,0 +1,125 @@
+//! CDC service - the change log external tools mirror Treeline data from
+//!
+//! The repository appends to sys_change_log for every account, transaction
+//! and balance snapshot it writes (see migration 021). A consumer remembers
+//! the last `seq` it processed and asks for what came after it, through
+//! `tl cdc tail` or the server-sent events stream of `tl cdc serve`.
+//! Changes made with raw SQL (`tl query` write statements, plugins) bypass
+//! the repository and are not logged.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::adapters::duckdb::{ChangeEvent, DuckDbRepository};
+
+/// Most changes returned by one call
+pub const MAX_CHANGES_PER_PAGE: usize = 1000;
+
+/// CDC service for reading the change log
+pub struct CdcService {
+    repository: Arc<DuckDbRepository>,
+}
+
+impl CdcService {
+    pub fn new(repository: Arc<DuckDbRepository>) -> Self {
+        Self { repository }
+    }
+
+    /// Changes after `since`, oldest first, at most `limit` (capped at MAX_CHANGES_PER_PAGE)
+    pub fn changes(&self, since: i64, limit: usize) -> Result<Vec<ChangeEvent>> {
+        self.repository
+            .get_changes(since, limit.clamp(1, MAX_CHANGES_PER_PAGE))
+    }
+
+    /// Sequence number of the newest change (0 when nothing is logged)
+    pub fn latest_seq(&self) -> Result<i64> {
+        self.repository.get_latest_change_seq()
+    }
+}
+
+/// A change as a server-sent event
+///
+/// The seq is the event ID, so a reconnecting EventSource resumes where it
+/// left off by sending it back as Last-Event-ID.
+pub fn sse_event(change: &ChangeEvent) -> Result<String> {
+    Ok(format!(
+        "id: {}\nevent: {}\ndata: {}\n\n",
+        change.seq,
+        change.entity,
+        serde_json::to_string(change)?
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Account, BalanceSnapshot, Transaction};
+    use chrono::NaiveDate;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_repository_writes_are_logged() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        let service = CdcService::new(Arc::clone(&repository));
+        assert_eq!(service.latest_seq().unwrap(), 0);
+
+        let account_id = Uuid::new_v4();
+        repository
+            .upsert_account(&Account::new(account_id, "Checking"))
+            .unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let tx = Transaction::new(Uuid::new_v4(), account_id, "-4.50".parse().unwrap(), date);
+        let tx_id = tx.id.to_string();
+        repository.upsert_transaction(&tx).unwrap();
+        repository
+            .update_transaction_tags(&tx_id, &["coffee".to_string()])
+            .unwrap();
+        repository.delete_transaction(&tx_id).unwrap();
+        // Already deleted, so nothing changes and nothing is logged
+        repository.delete_transaction(&tx_id).unwrap();
+        let time = date.and_hms_opt(12, 0, 0).unwrap();
+        repository
+            .add_balance_snapshot(&BalanceSnapshot::new(
+                account_id,
+                "100.00".parse().unwrap(),
+                time,
+            ))
+            .unwrap();
+        repository
+            .delete_balance_snapshots_in_range(&account_id.to_string(), date, date)
+            .unwrap();
+
+        let changes = service.changes(0, 100).unwrap();
+        let summary: Vec<(&str, &str)> = changes
+            .iter()
+            .map(|c| (c.entity.as_str(), c.op.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("account", "upsert"),
+                ("transaction", "upsert"),
+                ("transaction", "upsert"),
+                ("transaction", "delete"),
+                ("balance_snapshot", "upsert"),
+                ("balance_snapshot", "delete"),
+            ]
+        );
+        assert!(changes.windows(2).all(|w| w[1].seq == w[0].seq + 1));
+        assert_eq!(changes[2].payload["tags"], serde_json::json!(["coffee"]));
+        assert!(!changes[3].payload["deleted_at"].is_null());
+        assert_eq!(service.latest_seq().unwrap(), changes[5].seq);
+
+        // Resuming after the second change returns the other four
+        assert_eq!(service.changes(changes[1].seq, 100).unwrap().len(), 4);
+        assert_eq!(service.changes(0, 2).unwrap().len(), 2);
+
+        let event = sse_event(&changes[0]).unwrap();
+        assert!(event.starts_with(&format!("id: {}\nevent: account\ndata: {{", changes[0].seq)));
+        assert!(event.ends_with("}\n\n"));
+    }
+}
@@ -6,6 +6,7 @@ use anyhow::Result;
 use serde::Serialize;
 
 use crate::adapters::duckdb::DuckDbRepository;
+use crate::services::disk_space::ensure_free_space;
 
 /// Compact service for database maintenance
 pub struct CompactService {
@@ -21,6 +22,11 @@ impl CompactService {
     pub fn compact(&self) -> Result<CompactResult> {
         let original_size = self.repository.get_db_size()?;
 
+        // The compacted copy is written next to the database before replacing it
+        if let Some(dir) = self.repository.db_path().parent() {
+            ensure_free_space(dir, original_size, "compact the database")?;
+        }
+
         self.repository.compact()?;
 
         let compacted_size = self.repository.get_db_size()?;
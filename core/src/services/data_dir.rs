@@ -0,0 +1,377 @@
+//! Data directory service - cloud-sync detection and relocation
+//!
+//! DuckDB files are rewritten in place while Treeline runs. iCloud Drive,
+//! Dropbox and friends upload (and sometimes "resolve conflicts" in) those
+//! files mid-write, which corrupts the database. This service spots data
+//! directories inside synced folders and moves them somewhere safe.
+//!
+//! After a move, the old directory keeps only a `moved-to` file naming the
+//! new location, so `~/.treeline` and any `TREELINE_DIR` setting keep
+//! working without changes.
+//!
+//! In portable mode (a `portable.flag` file next to the executable) data
+//! lives in a `data` folder beside it instead, so Treeline can run from a
+//! USB stick without leaving anything in the home directory.
+
+use std::fs::{self, File, OpenOptions};
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result};
+use fs2::FileExt;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::json_file::write_atomic;
+
+/// File left in the old data directory after a move
+pub const MOVED_TO_FILE: &str = "moved-to";
+/// File next to the executable that turns on portable mode
+pub const PORTABLE_FLAG_FILE: &str = "portable.flag";
+
+/// A file-syncing service that can corrupt a live database
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CloudSyncProvider {
+    ICloud,
+    Dropbox,
+    OneDrive,
+    GoogleDrive,
+    Box,
+    PCloud,
+}
+
+impl CloudSyncProvider {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CloudSyncProvider::ICloud => "iCloud Drive",
+            CloudSyncProvider::Dropbox => "Dropbox",
+            CloudSyncProvider::OneDrive => "OneDrive",
+            CloudSyncProvider::GoogleDrive => "Google Drive",
+            CloudSyncProvider::Box => "Box",
+            CloudSyncProvider::PCloud => "pCloud",
+        }
+    }
+
+    /// Provider for one path component, e.g. "Dropbox (Personal)" or "OneDrive - Contoso"
+    fn from_component(component: &str) -> Option<Self> {
+        let lower = component.to_lowercase();
+        // macOS File Provider folders: ~/Library/CloudStorage/<Provider>-<account>
+        let lower = lower.split('-').next().unwrap_or(&lower).trim().to_string();
+        match lower.as_str() {
+            "mobile documents" | "icloud drive" | "iclouddrive" => Some(Self::ICloud),
+            "onedrive" => Some(Self::OneDrive),
+            "google drive" | "googledrive" | "my drive" => Some(Self::GoogleDrive),
+            "box" | "box sync" => Some(Self::Box),
+            "pcloud drive" | "pclouddrive" => Some(Self::PCloud),
+            _ if lower == "dropbox" || lower.starts_with("dropbox (") => Some(Self::Dropbox),
+            _ => None,
+        }
+    }
+}
+
+/// Which cloud-sync service, if any, manages this path
+///
+/// Looks at the path's folder names (following symlinks when the path
+/// exists) and for the `.dropbox` marker Dropbox keeps in its root.
+pub fn detect_cloud_sync(path: &Path) -> Option<CloudSyncProvider> {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+    let from_names = path.components().find_map(|c| match c {
+        Component::Normal(name) => CloudSyncProvider::from_component(&name.to_string_lossy()),
+        _ => None,
+    });
+    if from_names.is_some() {
+        return from_names;
+    }
+
+    path.ancestors()
+        .any(|dir| dir.join(".dropbox").is_file())
+        .then_some(CloudSyncProvider::Dropbox)
+}
+
+/// Follow a `moved-to` file left by a previous move, if there is one
+///
+/// Only follows it when the new location exists, so a copied or synced
+/// pointer file can't send another machine somewhere empty.
+pub fn resolve_data_dir(treeline_dir: PathBuf) -> PathBuf {
+    fs::read_to_string(treeline_dir.join(MOVED_TO_FILE))
+        .ok()
+        .map(|target| PathBuf::from(target.trim()))
+        .filter(|target| target.is_dir())
+        .unwrap_or(treeline_dir)
+}
+
+/// The `data` folder beside the running executable, in portable mode
+///
+/// None unless a `portable.flag` file sits next to the executable. The
+/// folder itself may not exist yet. `TREELINE_DIR` still takes precedence.
+pub fn portable_data_dir() -> Option<PathBuf> {
+    portable_data_dir_for(&std::env::current_exe().ok()?)
+}
+
+fn portable_data_dir_for(exe: &Path) -> Option<PathBuf> {
+    let dir = exe.parent()?;
+    dir.join(PORTABLE_FLAG_FILE)
+        .is_file()
+        .then(|| dir.join("data"))
+}
+
+/// Result of moving the data directory
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct MoveResult {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub files_moved: usize,
+    pub bytes_moved: u64,
+}
+
+/// Data directory service for checking and relocating `~/.treeline`
+pub struct DataDirService {
+    treeline_dir: PathBuf,
+}
+
+impl DataDirService {
+    pub fn new(treeline_dir: PathBuf) -> Self {
+        Self { treeline_dir }
+    }
+
+    /// The cloud-sync service the data directory is inside, if any
+    pub fn cloud_sync_provider(&self) -> Option<CloudSyncProvider> {
+        detect_cloud_sync(&self.treeline_dir)
+    }
+
+    /// Move all data to `destination` and leave a `moved-to` pointer behind
+    ///
+    /// Holds the database locks for the whole move and fails straight away if
+    /// another process (the app, a running sync) is using a database. Every
+    /// file is copied and its size checked before anything is deleted.
+    /// Refuses a destination inside a synced folder unless `allow_cloud_sync`.
+    pub fn move_to(&self, destination: &Path, allow_cloud_sync: bool) -> Result<MoveResult> {
+        let source = fs::canonicalize(&self.treeline_dir).with_context(|| {
+            format!("Data directory not found: {}", self.treeline_dir.display())
+        })?;
+        let destination = absolute(destination)?;
+
+        if destination.starts_with(&source) || source.starts_with(&destination) {
+            anyhow::bail!(
+                "Destination must be outside the current data directory ({})",
+                source.display()
+            );
+        }
+        if destination.exists() && fs::read_dir(&destination)?.next().is_some() {
+            anyhow::bail!(
+                "Destination is not empty: {} (choose a new or empty folder)",
+                destination.display()
+            );
+        }
+        if !allow_cloud_sync {
+            if let Some(provider) = detect_cloud_sync(&destination) {
+                anyhow::bail!(
+                    "{} is inside {}, which can corrupt the database. \
+                     Choose a folder that isn't synced.",
+                    destination.display(),
+                    provider.name()
+                );
+            }
+        }
+
+        let locks = lock_databases(&source)?;
+
+        let created = !destination.exists();
+        let mut result = MoveResult {
+            from: source.clone(),
+            to: destination.clone(),
+            files_moved: 0,
+            bytes_moved: 0,
+        };
+        if let Err(e) = copy_dir(&source, &destination, &mut result) {
+            if created {
+                let _ = fs::remove_dir_all(&destination);
+            }
+            return Err(e.context("Failed to copy data; nothing was removed"));
+        }
+
+        // Pointer first, so an interrupted cleanup still resolves to the copy
+        write_atomic(
+            &source.join(MOVED_TO_FILE),
+            destination.to_string_lossy().as_bytes(),
+        )
+        .context("Failed to record the new location")?;
+
+        drop(locks);
+        for entry in fs::read_dir(&source)? {
+            let path = entry?.path();
+            if path.file_name().is_some_and(|n| n == MOVED_TO_FILE) {
+                continue;
+            }
+            let removed = if path.is_dir() {
+                fs::remove_dir_all(&path)
+            } else {
+                fs::remove_file(&path)
+            };
+            removed.with_context(|| format!("Moved, but failed to remove {}", path.display()))?;
+        }
+
+        Ok(result)
+    }
+}
+
+fn absolute(path: &Path) -> Result<PathBuf> {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+    // Canonicalize the nearest existing ancestor so symlinks can't hide a synced folder
+    let mut existing = path.as_path();
+    let mut rest = Vec::new();
+    while !existing.exists() {
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => return Ok(path),
+        }
+    }
+    let mut resolved = fs::canonicalize(existing)?;
+    resolved.extend(rest.iter().rev());
+    Ok(resolved)
+}
+
+/// Take every database's lock without waiting
+fn lock_databases(dir: &Path) -> Result<Vec<File>> {
+    let mut locks = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|e| e == "duckdb") {
+            let lock = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(path.with_extension("duckdb.lock"))?;
+            lock.try_lock_exclusive().map_err(|_| {
+                anyhow::anyhow!(
+                    "{} is in use. Close the Treeline app and wait for running commands to finish.",
+                    path.display()
+                )
+            })?;
+            locks.push(lock);
+        }
+    }
+    Ok(locks)
+}
+
+/// Copy a directory tree, skipping lock files, and check each file's size
+fn copy_dir(from: &Path, to: &Path, result: &mut MoveResult) -> Result<()> {
+    fs::create_dir_all(to).with_context(|| format!("Failed to create {}", to.display()))?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let path = entry.path();
+        let target = to.join(entry.file_name());
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            copy_dir(&path, &target, result)?;
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.ends_with(".lock") || name == MOVED_TO_FILE {
+            continue;
+        }
+
+        let copied = fs::copy(&path, &target)
+            .with_context(|| format!("Failed to copy {}", path.display()))?;
+        if copied != fs::metadata(&path)?.len() || copied != fs::metadata(&target)?.len() {
+            anyhow::bail!("Copy of {} is incomplete", path.display());
+        }
+        result.files_moved += 1;
+        result.bytes_moved += copied;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_cloud_sync_by_folder_name() {
+        let cases = [
+            (
+                "/Users/a/Library/Mobile Documents/com~apple~CloudDocs/.treeline",
+                Some(CloudSyncProvider::ICloud),
+            ),
+            (
+                "/Users/a/Library/CloudStorage/Dropbox-Personal/.treeline",
+                Some(CloudSyncProvider::Dropbox),
+            ),
+            (
+                "/home/a/Dropbox (Work)/treeline",
+                Some(CloudSyncProvider::Dropbox),
+            ),
+            (
+                "C:\\Users\\a\\OneDrive - Contoso\\treeline",
+                Some(CloudSyncProvider::OneDrive),
+            ),
+            ("/home/a/.treeline", None),
+            ("/home/a/boxes/.treeline", None),
+        ];
+        for (path, expected) in cases {
+            let path: PathBuf = path.split(['/', '\\']).collect();
+            assert_eq!(detect_cloud_sync(&path), expected, "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn test_detect_dropbox_marker() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = dir.path().join("sync/treeline");
+        fs::create_dir_all(&data).unwrap();
+        assert_eq!(detect_cloud_sync(&data), None);
+
+        fs::write(dir.path().join("sync/.dropbox"), "").unwrap();
+        assert_eq!(detect_cloud_sync(&data), Some(CloudSyncProvider::Dropbox));
+    }
+
+    #[test]
+    fn test_portable_data_dir_needs_flag() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = dir.path().join("tl.exe");
+        assert_eq!(portable_data_dir_for(&exe), None);
+
+        fs::write(dir.path().join(PORTABLE_FLAG_FILE), "").unwrap();
+        assert_eq!(portable_data_dir_for(&exe), Some(dir.path().join("data")));
+    }
+
+    #[test]
+    fn test_move_leaves_pointer() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join("old");
+        fs::create_dir_all(old.join("backups")).unwrap();
+        fs::write(old.join("treeline.duckdb"), "db").unwrap();
+        fs::write(old.join("backups/treeline-1.zip"), "zip").unwrap();
+
+        let new = dir.path().join("new");
+        let result = DataDirService::new(old.clone())
+            .move_to(&new, false)
+            .unwrap();
+        assert_eq!(result.files_moved, 2);
+        assert_eq!(
+            fs::read_to_string(new.join("treeline.duckdb")).unwrap(),
+            "db"
+        );
+        assert!(!old.join("treeline.duckdb").exists());
+        assert!(!new.join("treeline.duckdb.lock").exists());
+        assert_eq!(
+            resolve_data_dir(old.clone()),
+            fs::canonicalize(&new).unwrap()
+        );
+
+        // Moving into a non-empty folder or into itself is refused
+        let service = DataDirService::new(new.clone());
+        assert!(service.move_to(&old, false).is_err());
+        assert!(service.move_to(&new.join("inner"), false).is_err());
+    }
+}
@@ -0,0 +1,805 @@
+//! Device sync service - replicate accounts and transactions between devices
+//!
+//! Devices exchange changes through a relay: any folder they can all reach,
+//! such as a NAS share, a Syncthing folder or a USB stick. Each run writes the
+//! fields that changed locally as one bundle under
+//! `<relay>/devices/<device_id>/`, then applies the bundles the other devices
+//! wrote since the last run.
+//!
+//! Bundles are sealed with AES-256-GCM under a key derived (Argon2id) from a
+//! passphrase every device is given, so the relay only ever holds ciphertext.
+//! Conflicts resolve per field: the latest change wins, with the device ID
+//! breaking ties so every device settles on the same value. A change is
+//! stamped by the run that first notices it, so syncing often keeps "latest"
+//! close to when the edit was made.
+//!
+//! Bank syncs should run on one device; the others receive its transactions
+//! from here. Balance snapshots are not replicated, and deletes are final.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use chrono::{DateTime, NaiveDate, SubsecRound, Utc};
+use rand::Rng;
+use rust_decimal::Decimal;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use uuid::Uuid;
+
+use crate::adapters::credentials::CredentialCipher;
+use crate::adapters::duckdb::{DuckDbRepository, SyncedField};
+use crate::config::{Config, DeviceSyncSettings};
+use crate::domain::{Account, Argon2Params, Transaction};
+use crate::json_file::{read_json, write_atomic, write_json};
+use crate::services::EncryptionService;
+
+/// Environment variable holding the relay passphrase, for unattended runs
+pub const DEVICE_SYNC_PASSPHRASE_ENV: &str = "TREELINE_SYNC_PASSPHRASE";
+
+const RELAY_FILE: &str = "relay.json";
+const DEVICES_DIR: &str = "devices";
+const BUNDLE_EXTENSION: &str = "bundle";
+/// Sealed into relay.json so a wrong passphrase fails before any bundle is read
+const PASSPHRASE_CHECK: &str = "treeline-device-sync";
+
+const ACCOUNT: &str = "account";
+const TRANSACTION: &str = "transaction";
+
+/// (entity, entity_id, field)
+type FieldKey = (String, String, String);
+
+/// Key derivation settings shared by every device on a relay
+#[derive(Serialize, Deserialize)]
+struct RelayInfo {
+    version: u32,
+    /// Base64-encoded random salt
+    salt: String,
+    argon2_params: Argon2Params,
+    check: String,
+}
+
+/// The fields one device changed between two runs
+#[derive(Serialize, Deserialize)]
+struct Bundle {
+    device_id: String,
+    device_name: Option<String>,
+    seq: i64,
+    created_at: DateTime<Utc>,
+    changes: Vec<SyncedField>,
+}
+
+/// Result of one device sync run
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct DeviceSyncResult {
+    /// Fields changed here and written to the relay
+    pub pushed: usize,
+    /// Bundles read from other devices
+    pub bundles_read: usize,
+    /// Changes from other devices applied here
+    pub applied: usize,
+    /// Changes from other devices older than the value here
+    pub outdated: usize,
+    /// Changes to records this device couldn't create, e.g. for an unknown account
+    pub skipped: usize,
+}
+
+/// Another device on the relay
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DevicePeerStatus {
+    pub device_id: String,
+    pub device_name: Option<String>,
+    /// Newest bundle it has written
+    pub latest_seq: i64,
+    /// Newest bundle applied here
+    pub applied_seq: i64,
+    pub last_synced_at: Option<DateTime<Utc>>,
+}
+
+/// Device sync setup and how far behind this device is
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DeviceSyncStatus {
+    pub relay: PathBuf,
+    pub device_id: String,
+    pub device_name: Option<String>,
+    /// Fields exchanged with other devices so far
+    pub tracked_fields: usize,
+    pub peers: Vec<DevicePeerStatus>,
+}
+
+/// Device sync service for exchanging changes through a relay folder
+pub struct DeviceSyncService {
+    repository: Arc<DuckDbRepository>,
+    treeline_dir: PathBuf,
+}
+
+impl DeviceSyncService {
+    pub fn new(repository: Arc<DuckDbRepository>, treeline_dir: PathBuf) -> Self {
+        Self {
+            repository,
+            treeline_dir,
+        }
+    }
+
+    /// Create or join a relay and save it in settings.json
+    ///
+    /// Creating a relay fixes its passphrase; joining one checks it. Moving to
+    /// another relay forgets what was exchanged, so everything is sent again.
+    pub fn init(
+        &self,
+        relay: &Path,
+        passphrase: &str,
+        device_name: Option<String>,
+    ) -> Result<DeviceSyncSettings> {
+        if passphrase.is_empty() {
+            anyhow::bail!("The passphrase cannot be empty");
+        }
+        let mut config = Config::load(&self.treeline_dir)?;
+
+        fs::create_dir_all(relay.join(DEVICES_DIR))
+            .with_context(|| format!("Failed to create relay at {}", relay.display()))?;
+        let relay = relay.canonicalize()?;
+        let relay_file = relay.join(RELAY_FILE);
+        if relay_file.exists() {
+            open_relay(&relay, passphrase)?;
+        } else {
+            let params = config.argon2_params.clone().unwrap_or_default();
+            let salt: [u8; 16] = rand::thread_rng().gen();
+            let cipher = derive_cipher(passphrase, &salt, &params)?;
+            let info = RelayInfo {
+                version: 1,
+                salt: base64::engine::general_purpose::STANDARD.encode(salt),
+                argon2_params: params,
+                check: cipher.seal(PASSPHRASE_CHECK)?,
+            };
+            write_json(&relay_file, &info)?;
+        }
+
+        let previous = config.device_sync.take();
+        if previous.as_ref().is_none_or(|p| p.relay != relay) {
+            self.repository.clear_device_sync()?;
+        }
+        let (device_id, previous_name) = match previous {
+            Some(p) => (p.device_id, p.device_name),
+            None => (Uuid::new_v4().to_string(), None),
+        };
+        // Lets the other devices list this one before it has sent anything
+        fs::create_dir_all(relay.join(DEVICES_DIR).join(&device_id))?;
+        let settings = DeviceSyncSettings {
+            relay,
+            device_id,
+            device_name: device_name.or(previous_name),
+        };
+        config.device_sync = Some(settings.clone());
+        config.save(&self.treeline_dir)?;
+        Ok(settings)
+    }
+
+    /// Send local changes to the relay, then apply what other devices sent
+    pub fn sync(&self, passphrase: &str) -> Result<DeviceSyncResult> {
+        let settings = self.settings()?;
+        let cipher = open_relay(&settings.relay, passphrase)?;
+        let mut known: HashMap<FieldKey, SyncedField> = self
+            .repository
+            .get_synced_fields()?
+            .into_iter()
+            .map(|f| (key(&f), f))
+            .collect();
+        let mut result = DeviceSyncResult::default();
+
+        // Push first, so local edits are stamped before remote ones are weighed against them
+        let changes = self.local_changes(&settings.device_id, &known)?;
+        if !changes.is_empty() {
+            let own_dir = settings.relay.join(DEVICES_DIR).join(&settings.device_id);
+            fs::create_dir_all(&own_dir)?;
+            let bundle = Bundle {
+                device_id: settings.device_id.clone(),
+                device_name: settings.device_name.clone(),
+                seq: latest_seq(&own_dir)? + 1,
+                created_at: Utc::now(),
+                changes,
+            };
+            write_bundle(&own_dir, &bundle, &cipher)?;
+            self.repository.upsert_synced_fields(&bundle.changes)?;
+            result.pushed = bundle.changes.len();
+            known.extend(bundle.changes.into_iter().map(|f| (key(&f), f)));
+        }
+
+        let applied_seqs: HashMap<String, i64> = self
+            .repository
+            .get_device_sync_peers()?
+            .into_iter()
+            .map(|p| (p.device_id, p.last_seq))
+            .collect();
+        for (device_id, dir) in peer_dirs(&settings)? {
+            let applied_seq = applied_seqs.get(&device_id).copied().unwrap_or(0);
+            for (seq, path) in bundle_paths(&dir)? {
+                if seq <= applied_seq {
+                    continue;
+                }
+                let bundle = read_bundle(&path, &cipher)?;
+                self.apply(bundle.changes, &mut known, &mut result)?;
+                self.repository.set_device_sync_peer(
+                    &device_id,
+                    bundle.device_name.as_deref(),
+                    seq,
+                )?;
+                result.bundles_read += 1;
+            }
+        }
+
+        Ok(result)
+    }
+
+    pub fn status(&self) -> Result<DeviceSyncStatus> {
+        let settings = self.settings()?;
+        let peers: HashMap<_, _> = self
+            .repository
+            .get_device_sync_peers()?
+            .into_iter()
+            .map(|p| (p.device_id.clone(), p))
+            .collect();
+
+        let mut statuses = Vec::new();
+        for (device_id, dir) in peer_dirs(&settings)? {
+            let peer = peers.get(&device_id);
+            statuses.push(DevicePeerStatus {
+                latest_seq: latest_seq(&dir)?,
+                applied_seq: peer.map_or(0, |p| p.last_seq),
+                device_name: peer.and_then(|p| p.device_name.clone()),
+                last_synced_at: peer.and_then(|p| p.last_synced_at),
+                device_id,
+            });
+        }
+
+        Ok(DeviceSyncStatus {
+            tracked_fields: self.repository.get_synced_fields()?.len(),
+            relay: settings.relay,
+            device_id: settings.device_id,
+            device_name: settings.device_name,
+            peers: statuses,
+        })
+    }
+
+    fn settings(&self) -> Result<DeviceSyncSettings> {
+        Config::load(&self.treeline_dir)?
+            .device_sync
+            .context("Device sync is not set up. Run `tl device-sync init --relay <folder>` first.")
+    }
+
+    /// Fields whose value differs from the one last exchanged, stamped now
+    fn local_changes(
+        &self,
+        device_id: &str,
+        known: &HashMap<FieldKey, SyncedField>,
+    ) -> Result<Vec<SyncedField>> {
+        let mut current = Vec::new();
+        for account in self.repository.get_accounts()? {
+            let id = account.id.to_string();
+            for (field, value) in account_fields(&account) {
+                current.push((ACCOUNT, id.clone(), field, value));
+            }
+        }
+        for tx in self.repository.get_transactions()? {
+            let id = tx.id.to_string();
+            for (field, value) in transaction_fields(&tx) {
+                current.push((TRANSACTION, id.clone(), field, value));
+            }
+        }
+        for id in self.repository.get_deleted_transaction_ids()? {
+            // Other devices only need deletes of transactions they were sent
+            let key = (TRANSACTION.to_string(), id.clone(), "deleted".to_string());
+            if known.contains_key(&key) {
+                current.push((TRANSACTION, id, "deleted", json!(true)));
+            }
+        }
+
+        // DuckDB keeps microseconds, so round now to compare equal after a round trip
+        let now = Utc::now().trunc_subsecs(6);
+        Ok(current
+            .into_iter()
+            .filter(|(entity, id, field, value)| {
+                known
+                    .get(&(entity.to_string(), id.clone(), field.to_string()))
+                    .is_none_or(|last| last.value != *value)
+            })
+            .map(|(entity, entity_id, field, value)| SyncedField {
+                entity: entity.to_string(),
+                entity_id,
+                field: field.to_string(),
+                value,
+                updated_at: now,
+                device_id: device_id.to_string(),
+            })
+            .collect())
+    }
+
+    /// Apply the changes that are newer than what this device has
+    fn apply(
+        &self,
+        changes: Vec<SyncedField>,
+        known: &mut HashMap<FieldKey, SyncedField>,
+        result: &mut DeviceSyncResult,
+    ) -> Result<()> {
+        // Grouped by record; "account" sorts first, so transactions find their account
+        let mut records: BTreeMap<(String, String), Vec<SyncedField>> = BTreeMap::new();
+        for change in changes {
+            if known
+                .get(&key(&change))
+                .is_some_and(|local| !is_newer(&change, local))
+            {
+                result.outdated += 1;
+                continue;
+            }
+            records
+                .entry((change.entity.clone(), change.entity_id.clone()))
+                .or_default()
+                .push(change);
+        }
+
+        let mut applied = Vec::new();
+        for ((entity, id), fields) in records {
+            let ok = match entity.as_str() {
+                ACCOUNT => self.apply_account(&id, &fields)?,
+                TRANSACTION => self.apply_transaction(&id, &fields)?,
+                _ => false,
+            };
+            if ok {
+                applied.extend(fields);
+            } else {
+                result.skipped += fields.len();
+            }
+        }
+
+        self.repository.upsert_synced_fields(&applied)?;
+        result.applied += applied.len();
+        known.extend(applied.into_iter().map(|f| (key(&f), f)));
+        Ok(())
+    }
+
+    /// Set an account's fields, creating it if needed; false if it can't be created
+    fn apply_account(&self, id: &str, fields: &[SyncedField]) -> Result<bool> {
+        let mut account = match self.repository.get_account_by_id(id)? {
+            Some(account) => account,
+            None => {
+                let Some(name) = value(fields, "name").and_then(as_text) else {
+                    return Ok(false);
+                };
+                Account::new(Uuid::parse_str(id)?, name)
+            }
+        };
+
+        for field in fields {
+            match field.field.as_str() {
+                "name" => {
+                    if let Some(name) = as_text(&field.value) {
+                        account.name = name;
+                    }
+                }
+                "nickname" => account.nickname = as_text(&field.value),
+                "account_type" => account.account_type = as_text(&field.value),
+                "classification" => account.classification = as_text(&field.value),
+                "currency" => {
+                    if let Some(currency) = as_text(&field.value) {
+                        account.currency = currency;
+                    }
+                }
+                "institution_name" => account.institution_name = as_text(&field.value),
+                _ => {}
+            }
+        }
+
+        account.updated_at = Utc::now();
+        self.repository.upsert_account(&account)?;
+        // upsert_account keeps existing nicknames and types, so set those directly
+        self.repository
+            .update_account_nickname(id, account.nickname.as_deref())?;
+        if let Some(account_type) = &account.account_type {
+            let classification = account
+                .classification
+                .clone()
+                .unwrap_or_else(|| Account::compute_classification(Some(account_type)));
+            self.repository
+                .update_account_type(id, account_type, &classification)?;
+        }
+        Ok(true)
+    }
+
+    /// Set a transaction's fields, creating it if needed; false if it can't be created
+    fn apply_transaction(&self, id: &str, fields: &[SyncedField]) -> Result<bool> {
+        let mut tx = match self.repository.get_transaction_by_id(id)? {
+            Some(tx) => tx,
+            None => {
+                let account_id = value(fields, "account_id")
+                    .and_then(as_text)
+                    .and_then(|a| Uuid::parse_str(&a).ok());
+                let amount = value(fields, "amount")
+                    .and_then(as_text)
+                    .and_then(|a| a.parse::<Decimal>().ok());
+                let date = value(fields, "transaction_date").and_then(as_date);
+                let (Some(account_id), Some(amount), Some(date)) = (account_id, amount, date)
+                else {
+                    return Ok(false);
+                };
+                if self
+                    .repository
+                    .get_account_by_id(&account_id.to_string())?
+                    .is_none()
+                {
+                    return Ok(false);
+                }
+
+                let mut tx = Transaction::new(Uuid::parse_str(id)?, account_id, amount, date);
+                let mut external_ids: BTreeMap<String, String> = value(fields, "external_ids")
+                    .and_then(|ids| serde_json::from_value(ids.clone()).ok())
+                    .unwrap_or_default();
+                // Devices on older versions send a field per provider
+                for (field, provider) in LEGACY_ID_FIELDS {
+                    if let Some(id) = value(fields, field).and_then(as_text) {
+                        external_ids.entry(provider.to_string()).or_insert(id);
+                    }
+                }
+                // A bank sync on this device already imported it under another ID
+                for (provider, id) in &external_ids {
+                    if self
+                        .repository
+                        .transaction_exists_by_external_id(provider, id)?
+                    {
+                        return Ok(false);
+                    }
+                }
+                tx.provider.external_ids = external_ids;
+                tx
+            }
+        };
+
+        // Deletes are final; the remote values are only recorded
+        if tx.deleted_at.is_some() {
+            return Ok(true);
+        }
+
+        for field in fields {
+            match field.field.as_str() {
+                "account_id" => {
+                    if let Some(account_id) =
+                        as_text(&field.value).and_then(|a| Uuid::parse_str(&a).ok())
+                    {
+                        tx.account_id = account_id;
+                    }
+                }
+                "amount" => {
+                    if let Some(amount) =
+                        as_text(&field.value).and_then(|a| a.parse::<Decimal>().ok())
+                    {
+                        tx.amount = amount;
+                    }
+                }
+                "description" => tx.description = as_text(&field.value),
+                "transaction_date" => {
+                    if let Some(date) = as_date(&field.value) {
+                        tx.transaction_date = date;
+                    }
+                }
+                "posted_date" => {
+                    if let Some(date) = as_date(&field.value) {
+                        tx.posted_date = date;
+                    }
+                }
+                "tags" => tx.tags = serde_json::from_value(field.value.clone()).unwrap_or_default(),
+                "excluded_from_reports" => {
+                    tx.excluded_from_reports = field.value.as_bool().unwrap_or(false)
+                }
+                "pending" => tx.pending = field.value.as_bool().unwrap_or(false),
+                _ => {}
+            }
+        }
+
+        tx.updated_at = Utc::now();
+        self.repository.upsert_transaction(&tx)?;
+        if value(fields, "deleted").and_then(JsonValue::as_bool) == Some(true) {
+            self.repository.delete_transaction(id)?;
+        }
+        Ok(true)
+    }
+}
+
+/// The replicated fields of an account
+fn account_fields(account: &Account) -> Vec<(&'static str, JsonValue)> {
+    vec![
+        ("name", json!(account.name)),
+        ("nickname", json!(account.nickname)),
+        ("account_type", json!(account.account_type)),
+        ("classification", json!(account.classification)),
+        ("currency", json!(account.currency)),
+        ("institution_name", json!(account.institution_name)),
+    ]
+}
+
+/// The replicated fields of a live transaction
+///
+/// Provider IDs only matter when the transaction is created, so a bank
+/// sync on the receiving device recognises it instead of importing it again.
+fn transaction_fields(tx: &Transaction) -> Vec<(&'static str, JsonValue)> {
+    vec![
+        ("account_id", json!(tx.account_id.to_string())),
+        ("amount", json!(tx.amount.to_string())),
+        ("description", json!(tx.description)),
+        ("transaction_date", json!(tx.transaction_date.to_string())),
+        ("posted_date", json!(tx.posted_date.to_string())),
+        ("tags", json!(tx.tags)),
+        ("excluded_from_reports", json!(tx.excluded_from_reports)),
+        ("pending", json!(tx.pending)),
+        ("external_ids", json!(tx.provider.external_ids)),
+        ("deleted", json!(false)),
+    ]
+}
+
+/// Provider ID fields replicated before external_ids, and the provider each names
+const LEGACY_ID_FIELDS: [(&str, &str); 4] = [
+    ("sf_id", "simplefin"),
+    ("lf_id", "lunchflow"),
+    ("pl_id", "plaid"),
+    ("gc_id", "gocardless"),
+];
+
+fn key(field: &SyncedField) -> FieldKey {
+    (
+        field.entity.clone(),
+        field.entity_id.clone(),
+        field.field.clone(),
+    )
+}
+
+/// Whether `change` wins over `current`: later wins, then the larger device ID
+fn is_newer(change: &SyncedField, current: &SyncedField) -> bool {
+    (change.updated_at, &change.device_id) > (current.updated_at, &current.device_id)
+}
+
+fn value<'a>(fields: &'a [SyncedField], name: &str) -> Option<&'a JsonValue> {
+    fields.iter().find(|f| f.field == name).map(|f| &f.value)
+}
+
+fn as_text(value: &JsonValue) -> Option<String> {
+    value.as_str().map(String::from)
+}
+
+fn as_date(value: &JsonValue) -> Option<NaiveDate> {
+    value
+        .as_str()
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+}
+
+/// Derive the relay key and check it against relay.json
+fn open_relay(relay: &Path, passphrase: &str) -> Result<CredentialCipher> {
+    let info: RelayInfo = read_json(&relay.join(RELAY_FILE))?.with_context(|| {
+        format!(
+            "{} is not a device sync relay (no readable {})",
+            relay.display(),
+            RELAY_FILE
+        )
+    })?;
+    let salt = base64::engine::general_purpose::STANDARD
+        .decode(&info.salt)
+        .context("Malformed salt in relay.json")?;
+    let cipher = derive_cipher(passphrase, &salt, &info.argon2_params)?;
+    match cipher.open(&info.check) {
+        Ok(check) if check == PASSPHRASE_CHECK => Ok(cipher),
+        _ => anyhow::bail!("Wrong passphrase for the relay at {}", relay.display()),
+    }
+}
+
+fn derive_cipher(passphrase: &str, salt: &[u8], params: &Argon2Params) -> Result<CredentialCipher> {
+    let key = EncryptionService::derive_key(passphrase, salt, params)?;
+    let key: &[u8; 32] = key
+        .as_slice()
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Relay key must be 32 bytes"))?;
+    Ok(CredentialCipher::new(key))
+}
+
+/// Folders of the other devices on the relay, by device ID
+fn peer_dirs(settings: &DeviceSyncSettings) -> Result<Vec<(String, PathBuf)>> {
+    let devices = settings.relay.join(DEVICES_DIR);
+    let entries = fs::read_dir(&devices)
+        .with_context(|| format!("Failed to read relay at {}", settings.relay.display()))?;
+
+    let mut dirs = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if entry.file_type()?.is_dir() && name != settings.device_id && !name.starts_with('.') {
+            dirs.push((name, entry.path()));
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// Bundles in a device folder as (seq, path), oldest first
+fn bundle_paths(dir: &Path) -> Result<Vec<(i64, PathBuf)>> {
+    let mut bundles = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|e| e != BUNDLE_EXTENSION) {
+            continue;
+        }
+        if let Some(seq) = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| s.parse().ok())
+        {
+            bundles.push((seq, path));
+        }
+    }
+    bundles.sort();
+    Ok(bundles)
+}
+
+fn latest_seq(dir: &Path) -> Result<i64> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+    Ok(bundle_paths(dir)?.last().map_or(0, |(seq, _)| *seq))
+}
+
+fn write_bundle(dir: &Path, bundle: &Bundle, cipher: &CredentialCipher) -> Result<()> {
+    let sealed = cipher.seal(&serde_json::to_string(bundle)?)?;
+    // Atomic, so other devices never read half a bundle
+    write_atomic(
+        &dir.join(format!("{:010}.{}", bundle.seq, BUNDLE_EXTENSION)),
+        sealed,
+    )
+}
+
+fn read_bundle(path: &Path, cipher: &CredentialCipher) -> Result<Bundle> {
+    let sealed = fs::read_to_string(path)?;
+    if !CredentialCipher::is_sealed(&sealed) {
+        anyhow::bail!("{} is not an encrypted bundle", path.display());
+    }
+    let json = cipher
+        .open(&sealed)
+        .with_context(|| format!("Failed to decrypt {}", path.display()))?;
+    serde_json::from_str(&json).with_context(|| format!("Malformed bundle {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSPHRASE: &str = "correct horse battery staple";
+
+    struct Device {
+        dir: tempfile::TempDir,
+        repository: Arc<DuckDbRepository>,
+        service: DeviceSyncService,
+    }
+
+    fn device(relay: &Path, name: &str) -> Device {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+
+        // Cheap key derivation keeps the tests fast
+        let mut config = Config::load(dir.path()).unwrap();
+        config.argon2_params = Some(Argon2Params {
+            time_cost: 1,
+            memory_cost: 1024,
+            parallelism: 1,
+            hash_len: 32,
+        });
+        config.save(dir.path()).unwrap();
+
+        let service = DeviceSyncService::new(Arc::clone(&repository), dir.path().to_path_buf());
+        service
+            .init(relay, PASSPHRASE, Some(name.to_string()))
+            .unwrap();
+        Device {
+            dir,
+            repository,
+            service,
+        }
+    }
+
+    fn edit(device: &Device, tx_id: &str, change: impl FnOnce(&mut Transaction)) {
+        let mut tx = device
+            .repository
+            .get_transaction_by_id(tx_id)
+            .unwrap()
+            .unwrap();
+        change(&mut tx);
+        device.repository.upsert_transaction(&tx).unwrap();
+    }
+
+    #[test]
+    fn test_replicates_and_resolves_conflicts_per_field() {
+        let relay = tempfile::tempdir().unwrap();
+        let laptop = device(relay.path(), "Laptop");
+
+        let account_id = Uuid::new_v4();
+        let mut account = Account::new(account_id, "Checking");
+        account.account_type = Some("checking".to_string());
+        laptop.repository.upsert_account(&account).unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let mut ids = Vec::new();
+        for (amount, description) in [("-4.50", "Coffee"), ("-1200.00", "Rent")] {
+            let mut tx =
+                Transaction::new(Uuid::new_v4(), account_id, amount.parse().unwrap(), date);
+            tx.description = Some(description.to_string());
+            laptop.repository.upsert_transaction(&tx).unwrap();
+            ids.push(tx.id.to_string());
+        }
+        assert!(laptop.service.sync(PASSPHRASE).unwrap().pushed > 0);
+
+        let phone = device(relay.path(), "Phone");
+        let result = phone.service.sync(PASSPHRASE).unwrap();
+        assert_eq!(result.bundles_read, 1);
+        assert_eq!(phone.repository.get_transactions().unwrap().len(), 2);
+        let copy = phone
+            .repository
+            .get_account_by_id(&account_id.to_string())
+            .unwrap()
+            .unwrap();
+        assert_eq!(copy.account_type.as_deref(), Some("checking"));
+
+        // Both rename the coffee; only the laptop changes its amount
+        edit(&laptop, &ids[0], |tx| {
+            tx.description = Some("Cafe".to_string());
+            tx.amount = "-5.00".parse().unwrap();
+        });
+        laptop.service.sync(PASSPHRASE).unwrap();
+        edit(&phone, &ids[0], |tx| {
+            tx.description = Some("Espresso".to_string())
+        });
+        let result = phone.service.sync(PASSPHRASE).unwrap();
+        assert_eq!(result.outdated, 1, "the laptop's earlier rename loses");
+        assert_eq!(result.applied, 1, "the laptop's amount still applies");
+
+        laptop.repository.delete_transaction(&ids[1]).unwrap();
+        laptop.service.sync(PASSPHRASE).unwrap();
+        phone.service.sync(PASSPHRASE).unwrap();
+
+        for device in [&laptop, &phone] {
+            let tx = device
+                .repository
+                .get_transaction_by_id(&ids[0])
+                .unwrap()
+                .unwrap();
+            assert_eq!(tx.description.as_deref(), Some("Espresso"));
+            assert_eq!(tx.amount.to_string(), "-5.00");
+            assert_eq!(device.repository.get_transactions().unwrap().len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_relay_holds_only_ciphertext_and_checks_passphrase() {
+        let relay = tempfile::tempdir().unwrap();
+        let laptop = device(relay.path(), "Laptop");
+        let account_id = Uuid::new_v4();
+        laptop
+            .repository
+            .upsert_account(&Account::new(account_id, "Secret Savings"))
+            .unwrap();
+        laptop.service.sync(PASSPHRASE).unwrap();
+
+        let settings = laptop.service.settings().unwrap();
+        let own_dir = settings.relay.join(DEVICES_DIR).join(&settings.device_id);
+        let (_, path) = bundle_paths(&own_dir).unwrap().pop().unwrap();
+        let contents = fs::read_to_string(path).unwrap();
+        assert!(!contents.contains("Secret Savings"));
+
+        assert!(laptop.service.sync("wrong passphrase").is_err());
+        let phone = device(relay.path(), "Phone");
+        let other = DeviceSyncService::new(Arc::clone(&phone.repository), phone.dir.path().into());
+        assert!(other.init(relay.path(), "wrong passphrase", None).is_err());
+
+        let status = laptop.service.status().unwrap();
+        assert_eq!(status.device_name.as_deref(), Some("Laptop"));
+        assert_eq!(status.peers.len(), 1);
+        assert_eq!(status.peers[0].latest_seq, 0, "the phone hasn't pushed yet");
+    }
+}
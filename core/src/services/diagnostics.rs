@@ -0,0 +1,180 @@
+//! Diagnostics service - one snapshot of database, backup and log health
+//!
+//! Gathers what support usually asks for (file sizes, lock state, schema
+//! version, backup freshness, recent errors) without changing anything.
+//! The lock state is read first and never waits, so a stuck lock shows up
+//! as `locked` instead of hanging the snapshot.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::adapters::duckdb::DuckDbRepository;
+use crate::adapters::write_queue::WriteQueueStats;
+use crate::services::{BackupService, LoggingService};
+#[cfg(feature = "reports")]
+use crate::services::{ReportCache, ReportCacheStats};
+
+/// Row count of one table
+#[derive(Debug, Serialize)]
+pub struct TableRowCount {
+    pub table: String,
+    pub rows: i64,
+}
+
+/// The most recently applied schema migration
+#[derive(Debug, Serialize)]
+pub struct MigrationInfo {
+    pub name: String,
+    pub applied_at: DateTime<Utc>,
+}
+
+/// Error entries in the logs database
+#[derive(Debug, Serialize)]
+pub struct LogErrorCounts {
+    pub last_24h: u64,
+    pub last_7d: u64,
+    pub total: u64,
+}
+
+/// Snapshot of database, backup and log health
+#[derive(Debug, Serialize)]
+pub struct Diagnostics {
+    pub database_path: PathBuf,
+    pub database_size_bytes: u64,
+    /// Size of the write-ahead log; 0 when everything is checkpointed
+    pub wal_size_bytes: u64,
+    /// Whether another operation (a sync, the CLI) held the database lock
+    pub locked: bool,
+    pub tables: Vec<TableRowCount>,
+    /// How long this process's operations waited for the database
+    pub write_queue: WriteQueueStats,
+    /// How often this process's reports were served from the cache
+    #[cfg(feature = "reports")]
+    pub report_cache: ReportCacheStats,
+    pub last_migration: Option<MigrationInfo>,
+    pub backup_count: usize,
+    pub last_backup_at: Option<DateTime<Utc>>,
+    pub last_backup_age_seconds: Option<i64>,
+    /// None when the logs database isn't available
+    pub log_errors: Option<LogErrorCounts>,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Diagnostics service for support and troubleshooting screens
+pub struct DiagnosticsService {
+    repository: Arc<DuckDbRepository>,
+    treeline_dir: PathBuf,
+    db_filename: String,
+}
+
+impl DiagnosticsService {
+    pub fn new(
+        repository: Arc<DuckDbRepository>,
+        treeline_dir: PathBuf,
+        db_filename: String,
+    ) -> Self {
+        Self {
+            repository,
+            treeline_dir,
+            db_filename,
+        }
+    }
+
+    /// Collect a diagnostics snapshot, including log error counts when a logger is given
+    pub fn collect(&self, logger: Option<&LoggingService>) -> Result<Diagnostics> {
+        let locked = self.repository.is_locked();
+        let now = Utc::now();
+
+        let db_path = self.repository.db_path().to_path_buf();
+        let tables = self
+            .repository
+            .get_table_row_counts()?
+            .into_iter()
+            .map(|(table, rows)| TableRowCount { table, rows })
+            .collect();
+        let last_migration = self
+            .repository
+            .get_last_migration()?
+            .map(|(name, applied_at)| MigrationInfo { name, applied_at });
+
+        let backups =
+            BackupService::new(self.treeline_dir.clone(), self.db_filename.clone()).list()?;
+        let last_backup_at = backups.first().map(|b| b.created_at);
+
+        let log_errors = logger.and_then(|logger| {
+            let since = |age: Duration| (now - age).timestamp_millis();
+            Some(LogErrorCounts {
+                last_24h: logger.count_errors_since(since(Duration::hours(24))).ok()?,
+                last_7d: logger.count_errors_since(since(Duration::days(7))).ok()?,
+                total: logger.count_errors_since(0).ok()?,
+            })
+        });
+
+        Ok(Diagnostics {
+            database_size_bytes: file_size(&db_path),
+            wal_size_bytes: file_size(&wal_path(&db_path)),
+            database_path: db_path,
+            locked,
+            tables,
+            write_queue: self.repository.write_queue_stats(),
+            #[cfg(feature = "reports")]
+            report_cache: ReportCache::for_path(self.repository.db_path()).stats(),
+            last_migration,
+            backup_count: backups.len(),
+            last_backup_at,
+            last_backup_age_seconds: last_backup_at.map(|at| (now - at).num_seconds()),
+            log_errors,
+            generated_at: now,
+        })
+    }
+}
+
+/// DuckDB keeps its write-ahead log next to the database: `treeline.duckdb.wal`
+fn wal_path(db_path: &Path) -> PathBuf {
+    let mut name = db_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".wal");
+    db_path.with_file_name(name)
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::EntryPoint;
+
+    #[test]
+    fn test_collect_diagnostics() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("treeline.duckdb");
+        let repository = Arc::new(DuckDbRepository::new(&db_path, None).unwrap());
+        repository.ensure_schema().unwrap();
+        let logger = LoggingService::new(dir.path(), EntryPoint::Cli, "1.0.0").unwrap();
+        logger.log_error("sync_failed", "timeout", None).unwrap();
+
+        let service = DiagnosticsService::new(
+            repository,
+            dir.path().to_path_buf(),
+            "treeline.duckdb".to_string(),
+        );
+        let diagnostics = service.collect(Some(&logger)).unwrap();
+
+        assert!(diagnostics.database_size_bytes > 0);
+        assert!(!diagnostics.locked);
+        assert!(diagnostics
+            .tables
+            .iter()
+            .any(|t| t.table == "sys_transactions" && t.rows == 0));
+        assert!(diagnostics.last_migration.is_some());
+        assert_eq!(diagnostics.backup_count, 0);
+        assert_eq!(diagnostics.last_backup_age_seconds, None);
+        assert_eq!(diagnostics.log_errors.unwrap().last_24h, 1);
+    }
+}
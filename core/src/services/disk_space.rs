@@ -0,0 +1,64 @@
+//! Disk space preflight checks
+//!
+//! Backups, restores, compaction and encryption changes all write a full
+//! copy of the database before the old one goes away. Checking free space
+//! first turns a half-written file on a full disk into a clear error.
+
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::domain::result::Error;
+
+/// Kept free on top of the estimate, for the WAL, logs and filesystem overhead
+const HEADROOM_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Fail with `Error::InsufficientSpace` unless `dir`'s filesystem can take `estimate` bytes
+///
+/// `dir` may not exist yet; its nearest existing ancestor is checked. If
+/// free space can't be determined the check passes, so an unusual
+/// filesystem never blocks the operation.
+pub(super) fn ensure_free_space(dir: &Path, estimate: u64, operation: &str) -> Result<()> {
+    let Some(existing) = dir.ancestors().find(|p| p.exists()) else {
+        return Ok(());
+    };
+    let Ok(available) = fs2::available_space(existing) else {
+        return Ok(());
+    };
+
+    let required = estimate.saturating_add(HEADROOM_BYTES);
+    if available < required {
+        return Err(Error::InsufficientSpace {
+            operation: operation.to_string(),
+            path: existing.to_path_buf(),
+            required,
+            available,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ensure_free_space() {
+        let dir = tempfile::tempdir().unwrap();
+        ensure_free_space(&dir.path().join("not/yet/created"), 1024, "copy").unwrap();
+
+        let err = ensure_free_space(dir.path(), u64::MAX / 2, "create a backup").unwrap_err();
+        match err.downcast_ref::<Error>() {
+            Some(Error::InsufficientSpace {
+                required,
+                available,
+                ..
+            }) => assert!(required > available),
+            other => panic!("expected InsufficientSpace, got {:?}", other),
+        }
+        assert!(err
+            .to_string()
+            .starts_with("Not enough disk space to create a backup"));
+    }
+}
@@ -1,18 +1,37 @@
 //! Doctor service - database health checks
 
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
 
 use anyhow::Result;
+use chrono::{Duration, NaiveDate, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use schemars::JsonSchema;
 use serde::Serialize;
 use serde_json::json;
 
 use crate::adapters::duckdb::DuckDbRepository;
+use crate::adapters::extensions::ExtensionStatus;
+use crate::domain::Transaction;
+use crate::services::{detect_cloud_sync, format_bytes, StorageService};
+
+/// Identical amounts on one day in one account before they look like a glitch
+const REPEATED_AMOUNT_THRESHOLD: usize = 3;
+
+/// Days ahead a transaction may be dated (pending and scheduled payments)
+const FUTURE_DATE_GRACE_DAYS: i64 = 7;
+
+/// Most clusters listed in the check's details
+const MAX_CLUSTER_DETAILS: usize = 100;
+
+/// Days a transaction may stay pending before it has likely been cancelled
+const STALE_PENDING_DAYS: i64 = 14;
 
 /// Doctor service for health checks
 pub struct DoctorService {
     repository: Arc<DuckDbRepository>,
-    #[allow(dead_code)]
     treeline_dir: PathBuf,
 }
 
@@ -185,19 +204,13 @@ impl DoctorService {
             },
         );
 
-        // Duplicate transactions check - finds duplicate sf_ids or lf_ids
-        let duplicate_sf_ids = self.repository.check_duplicate_sf_ids()?;
-        let duplicate_lf_ids = self.repository.check_duplicate_lf_ids()?;
-        let total_duplicates = duplicate_sf_ids.len() + duplicate_lf_ids.len();
+        // Duplicate transactions check - finds provider IDs held by more than one transaction
+        let duplicate_ids = self.repository.check_duplicate_external_ids()?;
+        let total_duplicates = duplicate_ids.len();
 
-        let dup_details: Vec<serde_json::Value> = duplicate_sf_ids
+        let dup_details: Vec<serde_json::Value> = duplicate_ids
             .iter()
-            .map(|id| json!({"type": "sf_id", "id": id}))
-            .chain(
-                duplicate_lf_ids
-                    .iter()
-                    .map(|id| json!({"type": "lf_id", "id": id})),
-            )
+            .map(|(provider, id)| json!({"type": provider, "id": id}))
             .collect();
 
         checks.insert(
@@ -212,11 +225,18 @@ impl DoctorService {
                 message: if total_duplicates == 0 {
                     "No duplicate transactions found".to_string()
                 } else {
+                    let mut by_provider: BTreeMap<&str, usize> = BTreeMap::new();
+                    for (provider, _) in &duplicate_ids {
+                        *by_provider.entry(provider.as_str()).or_insert(0) += 1;
+                    }
                     format!(
-                        "{} duplicate provider ID(s) found ({} sf_id, {} lf_id)",
+                        "{} duplicate provider ID(s) found ({})",
                         total_duplicates,
-                        duplicate_sf_ids.len(),
-                        duplicate_lf_ids.len()
+                        by_provider
+                            .iter()
+                            .map(|(provider, count)| format!("{} {}", count, provider))
+                            .collect::<Vec<_>>()
+                            .join(", ")
                     )
                 },
                 details: if total_duplicates == 0 {
@@ -227,6 +247,82 @@ impl DoctorService {
             },
         );
 
+        // Data sanity - clusters of transactions that point to importer bugs or provider glitches
+        let transactions = self.repository.get_transactions()?;
+        let clusters = find_suspicious_clusters(&transactions, Utc::now().date_naive());
+        let count_of = |kind: ClusterKind| clusters.iter().filter(|c| c.kind == kind).count();
+        checks.insert(
+            "data_sanity".to_string(),
+            CheckResult {
+                status: if clusters.is_empty() {
+                    "pass"
+                } else {
+                    "warning"
+                }
+                .to_string(),
+                message: if clusters.is_empty() {
+                    "No suspicious transaction clusters found".to_string()
+                } else {
+                    format!(
+                        "{} suspicious cluster(s): {} duplicated across accounts, \
+                         {} repeated amount(s) on one day, {} future-dated",
+                        clusters.len(),
+                        count_of(ClusterKind::CrossAccountDuplicate),
+                        count_of(ClusterKind::RepeatedAmount),
+                        count_of(ClusterKind::FutureDate)
+                    )
+                },
+                details: if clusters.is_empty() {
+                    None
+                } else {
+                    Some(
+                        clusters
+                            .iter()
+                            .take(MAX_CLUSTER_DETAILS)
+                            .map(SuspiciousCluster::to_json)
+                            .collect(),
+                    )
+                },
+            },
+        );
+
+        // Pending transactions that never posted; the merchant likely cancelled them
+        let stale = find_stale_pending(&transactions, Utc::now().date_naive());
+        checks.insert(
+            "stale_pending".to_string(),
+            CheckResult {
+                status: if stale.is_empty() { "pass" } else { "warning" }.to_string(),
+                message: if stale.is_empty() {
+                    "No stale pending transactions".to_string()
+                } else {
+                    format!(
+                        "{} transaction(s) pending for over {} days and may have been \
+                         cancelled; delete them or run `tl sync --full`",
+                        stale.len(),
+                        STALE_PENDING_DAYS
+                    )
+                },
+                details: if stale.is_empty() {
+                    None
+                } else {
+                    Some(
+                        stale
+                            .iter()
+                            .map(|tx| {
+                                json!({
+                                    "transaction_id": tx.id.to_string(),
+                                    "account_id": tx.account_id.to_string(),
+                                    "date": tx.transaction_date.to_string(),
+                                    "description": tx.description,
+                                    "amount": tx.amount.to_f64(),
+                                })
+                            })
+                            .collect(),
+                    )
+                },
+            },
+        );
+
         // Budget double-counting check
         let budget_exists = self.repository.table_exists("plugin_budget.categories")?;
         if budget_exists {
@@ -273,7 +369,7 @@ impl DoctorService {
         }
 
         // Integration connectivity - test via dry-run sync
-        let integrations = self.repository.get_integrations()?;
+        let integrations = self.repository.get_integration_names()?;
         if integrations.is_empty() {
             checks.insert(
                 "integration_connectivity".to_string(),
@@ -283,6 +379,16 @@ impl DoctorService {
                     details: None,
                 },
             );
+        } else if let Err(e) = self.repository.get_integrations() {
+            // Credentials that can't be decrypted (locked keychain, missing key)
+            checks.insert(
+                "integration_connectivity".to_string(),
+                CheckResult {
+                    status: "error".to_string(),
+                    message: format!("{:#}", e),
+                    details: None,
+                },
+            );
         } else {
             // For now, just report integrations are configured
             // Full implementation would do a dry-run sync to test connectivity
@@ -296,6 +402,100 @@ impl DoctorService {
             );
         }
 
+        // Data directory inside a cloud-synced folder
+        let cloud_sync = detect_cloud_sync(&self.treeline_dir);
+        checks.insert(
+            "data_directory".to_string(),
+            CheckResult {
+                status: if cloud_sync.is_none() {
+                    "pass"
+                } else {
+                    "error"
+                }
+                .to_string(),
+                message: match cloud_sync {
+                    None => "Data directory is not in a cloud-synced folder".to_string(),
+                    Some(provider) => format!(
+                        "Data directory is inside {}, which can corrupt the database; \
+                         move it with `tl move-data <folder>`",
+                        provider.name()
+                    ),
+                },
+                details: cloud_sync.map(|provider| {
+                    vec![json!({
+                        "path": self.treeline_dir.display().to_string(),
+                        "provider": provider,
+                    })]
+                }),
+            },
+        );
+
+        // Space that archiving, compacting or pruning would free
+        let storage = StorageService::new(Arc::clone(&self.repository), self.treeline_dir.clone())
+            .analyze(Utc::now().date_naive())?;
+        checks.insert(
+            "storage".to_string(),
+            CheckResult {
+                status: if storage.suggestions.is_empty() {
+                    "pass"
+                } else {
+                    "warning"
+                }
+                .to_string(),
+                message: if storage.suggestions.is_empty() {
+                    format!(
+                        "Database uses {}, nothing to reclaim",
+                        format_bytes(storage.database_bytes)
+                    )
+                } else {
+                    format!(
+                        "Database uses {}; {} way(s) to free space (see `tl storage show`)",
+                        format_bytes(storage.database_bytes),
+                        storage.suggestions.len()
+                    )
+                },
+                details: if storage.suggestions.is_empty() {
+                    None
+                } else {
+                    Some(storage.suggestions.iter().map(|s| json!(s)).collect())
+                },
+            },
+        );
+
+        // Optional DuckDB extensions; without them features fall back
+        let extensions = self.repository.extension_status()?;
+        let unavailable: Vec<&ExtensionStatus> =
+            extensions.iter().filter(|e| !e.available).collect();
+        let failed: Vec<&str> = unavailable
+            .iter()
+            .filter(|e| e.error.is_some())
+            .map(|e| e.name.as_str())
+            .collect();
+        checks.insert(
+            "extensions".to_string(),
+            CheckResult {
+                status: if failed.is_empty() { "pass" } else { "warning" }.to_string(),
+                message: if unavailable.is_empty() {
+                    "All optional DuckDB extensions load".to_string()
+                } else if failed.is_empty() {
+                    format!(
+                        "Not installed, using fallbacks: {}",
+                        unavailable
+                            .iter()
+                            .map(|e| format!("{} ({})", e.name, e.fallback))
+                            .collect::<Vec<_>>()
+                            .join("; ")
+                    )
+                } else {
+                    format!(
+                        "Installed but failed to load, using fallbacks: {}",
+                        failed.join(", ")
+                    )
+                },
+                details: Some(extensions.iter().map(|e| json!(e)).collect()),
+            },
+        );
+
         // Calculate summary
         let passed = checks.values().filter(|c| c.status == "pass").count() as i64;
         let warnings = checks.values().filter(|c| c.status == "warning").count() as i64;
@@ -312,13 +512,131 @@ impl DoctorService {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClusterKind {
+    /// Same date, amount and description in more than one account
+    CrossAccountDuplicate,
+    /// Several identical amounts on one day in one account
+    RepeatedAmount,
+    /// Dated too far after today to be pending
+    FutureDate,
+}
+
+/// Transactions that together are unlikely to be real
+#[derive(Debug)]
+struct SuspiciousCluster {
+    kind: ClusterKind,
+    date: NaiveDate,
+    amount: Decimal,
+    description: String,
+    transaction_ids: Vec<String>,
+}
+
+impl SuspiciousCluster {
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "type": match self.kind {
+                ClusterKind::CrossAccountDuplicate => "cross_account_duplicate",
+                ClusterKind::RepeatedAmount => "repeated_amount",
+                ClusterKind::FutureDate => "future_date",
+            },
+            "date": self.date.to_string(),
+            "description": self.description,
+            "amount": self.amount.to_f64(),
+            "count": self.transaction_ids.len(),
+            "transaction_ids": self.transaction_ids,
+        })
+    }
+}
+
+/// Find clusters that usually come from double imports, a provider
+/// repeating a row, or a misparsed date, newest first
+///
+/// Split children are skipped for the duplicate checks: splitting a
+/// transaction into equal parts is expected to repeat an amount.
+fn find_suspicious_clusters(
+    transactions: &[Transaction],
+    today: NaiveDate,
+) -> Vec<SuspiciousCluster> {
+    let mut by_content: HashMap<(NaiveDate, Decimal, String), Vec<&Transaction>> = HashMap::new();
+    let mut by_account_day: HashMap<(uuid::Uuid, NaiveDate, Decimal), Vec<&Transaction>> =
+        HashMap::new();
+    let mut clusters = Vec::new();
+    let latest_allowed = today + Duration::days(FUTURE_DATE_GRACE_DAYS);
+
+    for tx in transactions {
+        if tx.transaction_date > latest_allowed {
+            clusters.push(SuspiciousCluster {
+                kind: ClusterKind::FutureDate,
+                date: tx.transaction_date,
+                amount: tx.amount,
+                description: tx.description.clone().unwrap_or_default(),
+                transaction_ids: vec![tx.id.to_string()],
+            });
+        }
+        if tx.parent_transaction_id.is_some() {
+            continue;
+        }
+        let description = tx
+            .description
+            .as_deref()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase();
+        by_content
+            .entry((tx.transaction_date, tx.amount, description))
+            .or_default()
+            .push(tx);
+        by_account_day
+            .entry((tx.account_id, tx.transaction_date, tx.amount))
+            .or_default()
+            .push(tx);
+    }
+
+    let cluster = |kind, group: &[&Transaction]| SuspiciousCluster {
+        kind,
+        date: group[0].transaction_date,
+        amount: group[0].amount,
+        description: group[0].description.clone().unwrap_or_default(),
+        transaction_ids: group.iter().map(|tx| tx.id.to_string()).collect(),
+    };
+    for group in by_content.values() {
+        let accounts: HashSet<_> = group.iter().map(|tx| tx.account_id).collect();
+        if accounts.len() > 1 {
+            clusters.push(cluster(ClusterKind::CrossAccountDuplicate, group));
+        }
+    }
+    for group in by_account_day.values() {
+        if group.len() >= REPEATED_AMOUNT_THRESHOLD {
+            clusters.push(cluster(ClusterKind::RepeatedAmount, group));
+        }
+    }
+
+    clusters.sort_by(|a, b| b.date.cmp(&a.date).then(a.description.cmp(&b.description)));
+    clusters
+}
+
+/// Pending transactions dated more than `STALE_PENDING_DAYS` ago, oldest first
+///
+/// A sync replaces a pending transaction once it posts, so one left this
+/// long usually means the charge was dropped.
+fn find_stale_pending(transactions: &[Transaction], today: NaiveDate) -> Vec<&Transaction> {
+    let cutoff = today - Duration::days(STALE_PENDING_DAYS);
+    let mut stale: Vec<&Transaction> = transactions
+        .iter()
+        .filter(|tx| tx.pending && tx.transaction_date < cutoff)
+        .collect();
+    stale.sort_by_key(|tx| tx.transaction_date);
+    stale
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct DoctorResult {
     pub checks: std::collections::HashMap<String, CheckResult>,
     pub summary: DoctorSummary,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct CheckResult {
     pub status: String,
     pub message: String,
@@ -326,9 +644,84 @@ pub struct CheckResult {
     pub details: Option<Vec<serde_json::Value>>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct DoctorSummary {
     pub passed: i64,
     pub warnings: i64,
     pub errors: i64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn tx(account_id: Uuid, amount: i64, date: &str, description: &str) -> Transaction {
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+        let mut tx = Transaction::new(Uuid::new_v4(), account_id, Decimal::new(amount, 0), date);
+        tx.description = Some(description.to_string());
+        tx
+    }
+
+    #[test]
+    fn test_find_suspicious_clusters() {
+        let (checking, card) = (Uuid::new_v4(), Uuid::new_v4());
+        let today = NaiveDate::from_ymd_opt(2025, 6, 15).unwrap();
+        let transactions = vec![
+            // Imported into the wrong account as well
+            tx(checking, -42, "2025-06-01", "Grocery Store"),
+            tx(card, -42, "2025-06-01", "grocery store "),
+            // A transfer has opposite signs, so it is not a duplicate
+            tx(checking, -500, "2025-06-02", "Transfer"),
+            tx(card, 500, "2025-06-02", "Transfer"),
+            // The same row three times
+            tx(card, -9, "2025-06-03", "Parking"),
+            tx(card, -9, "2025-06-03", "Parking"),
+            tx(card, -9, "2025-06-03", "Parking"),
+            // Two coffees is fine
+            tx(card, -4, "2025-06-04", "Coffee"),
+            tx(card, -4, "2025-06-04", "Coffee"),
+            // Pending is fine, a year-2205 typo is not
+            tx(card, -20, "2025-06-18", "Subscription"),
+            tx(card, -20, "2205-06-01", "Rent"),
+        ];
+
+        let clusters = find_suspicious_clusters(&transactions, today);
+        let kinds: Vec<ClusterKind> = clusters.iter().map(|c| c.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                ClusterKind::FutureDate,
+                ClusterKind::RepeatedAmount,
+                ClusterKind::CrossAccountDuplicate
+            ]
+        );
+        assert_eq!(clusters[1].transaction_ids.len(), 3);
+        assert_eq!(clusters[2].to_json()["type"], "cross_account_duplicate");
+    }
+
+    #[test]
+    fn test_find_stale_pending() {
+        let account = Uuid::new_v4();
+        let today = NaiveDate::from_ymd_opt(2025, 6, 15).unwrap();
+        let pending = |date| {
+            let mut tx = tx(account, -12, date, "Hotel hold");
+            tx.pending = true;
+            tx
+        };
+        let transactions = vec![
+            pending("2025-06-10"),
+            pending("2025-05-20"),
+            pending("2025-05-01"),
+            // Posted long ago
+            tx(account, -12, "2025-04-01", "Hotel"),
+        ];
+
+        let stale = find_stale_pending(&transactions, today);
+        let dates: Vec<String> = stale
+            .iter()
+            .map(|tx| tx.transaction_date.to_string())
+            .collect();
+        assert_eq!(dates, ["2025-05-01", "2025-05-20"]);
+    }
+}
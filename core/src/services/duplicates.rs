@@ -0,0 +1,323 @@
+//! Duplicate service - find likely duplicates that exact dedup misses
+//!
+//! CSV imports dedup on a fingerprint and sync on the provider's transaction
+//! ID, so the same purchase arriving once from a CSV and once from SimpleFIN,
+//! worded slightly differently, gets through both. This pairs transactions
+//! in the same account with the same amount, dates a few days apart and
+//! similar descriptions. Pairs form a review queue: each is either merged
+//! (one side deleted) or dismissed, and isn't suggested again.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use schemars::JsonSchema;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::adapters::duckdb::DuckDbRepository;
+use crate::domain::Transaction;
+use crate::services::import::normalize_description;
+use crate::services::transaction::{to_info, TransactionInfo};
+
+/// How close two transactions must be to count as likely duplicates
+#[derive(Debug, Clone, Copy)]
+pub struct DuplicateOptions {
+    /// Most days between the two transaction dates
+    pub max_days: i64,
+    /// Least description similarity, from 0 (nothing shared) to 1 (identical
+    /// once normalized)
+    pub min_similarity: f64,
+}
+
+impl Default for DuplicateOptions {
+    fn default() -> Self {
+        Self {
+            max_days: 3,
+            min_similarity: 0.6,
+        }
+    }
+}
+
+/// Two transactions that look like the same one
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DuplicateCandidate {
+    pub first: TransactionInfo,
+    pub second: TransactionInfo,
+    pub days_apart: i64,
+    /// Description similarity, 0 to 1
+    pub similarity: f64,
+    /// The one to keep when merging: the synced side, so later syncs still
+    /// match it, else the older one
+    pub suggested_keep: String,
+}
+
+/// Duplicate service for finding and resolving likely duplicate transactions
+pub struct DuplicateService {
+    repository: Arc<DuckDbRepository>,
+}
+
+impl DuplicateService {
+    pub fn new(repository: Arc<DuckDbRepository>) -> Self {
+        Self { repository }
+    }
+
+    /// Unresolved likely duplicates, newest first
+    pub fn find(&self, options: &DuplicateOptions) -> Result<Vec<DuplicateCandidate>> {
+        let reviewed = self.repository.get_duplicate_reviews()?;
+        let names = self.account_names()?;
+
+        // Only transactions in the same account with the same amount can match
+        let mut groups: HashMap<(Uuid, Decimal), Vec<Transaction>> = HashMap::new();
+        for tx in self.repository.get_transactions()? {
+            // Split parts share their parent's description on purpose
+            if tx.parent_transaction_id.is_some() {
+                continue;
+            }
+            groups
+                .entry((tx.account_id, tx.amount))
+                .or_default()
+                .push(tx);
+        }
+
+        let mut candidates = Vec::new();
+        for group in groups.values_mut() {
+            group.sort_by_key(|tx| tx.transaction_date);
+            for (i, a) in group.iter().enumerate() {
+                for b in &group[i + 1..] {
+                    let days_apart = (b.transaction_date - a.transaction_date).num_days();
+                    if days_apart > options.max_days {
+                        break;
+                    }
+                    if !could_be_duplicates(a, b) || reviewed.contains(&pair_key(a, b)) {
+                        continue;
+                    }
+                    let similarity = description_similarity(
+                        a.description.as_deref().unwrap_or_default(),
+                        b.description.as_deref().unwrap_or_default(),
+                    );
+                    if similarity < options.min_similarity {
+                        continue;
+                    }
+                    let keep = if is_synced(b) && !is_synced(a) { b } else { a };
+                    candidates.push(DuplicateCandidate {
+                        suggested_keep: keep.id.to_string(),
+                        first: to_info(a.clone(), &names),
+                        second: to_info(b.clone(), &names),
+                        days_apart,
+                        similarity,
+                    });
+                }
+            }
+        }
+
+        candidates.sort_by(|a, b| {
+            b.second
+                .date
+                .cmp(&a.second.date)
+                .then_with(|| a.first.id.cmp(&b.first.id))
+        });
+        Ok(candidates)
+    }
+
+    /// Resolve a pair by deleting one side
+    ///
+    /// The kept transaction gains the removed one's tags. The removed one is
+    /// soft deleted, so sync and re-imports don't bring it back.
+    pub fn merge(&self, keep_id: &str, remove_id: &str) -> Result<TransactionInfo> {
+        if keep_id == remove_id {
+            anyhow::bail!("Can't merge a transaction with itself");
+        }
+        let mut keep = self.find_transaction(keep_id)?;
+        let remove = self.find_transaction(remove_id)?;
+        if keep.account_id != remove.account_id {
+            anyhow::bail!("Transactions are in different accounts");
+        }
+
+        let missing: Vec<String> = remove
+            .tags
+            .iter()
+            .filter(|tag| !keep.tags.contains(tag))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            keep.tags.extend(missing);
+            keep.updated_at = Utc::now();
+            self.repository.upsert_transaction(&keep)?;
+        }
+        self.repository.delete_transaction(remove_id)?;
+        self.repository
+            .record_duplicate_review(keep_id, remove_id, "merged")?;
+
+        Ok(to_info(keep, &self.account_names()?))
+    }
+
+    /// Resolve a pair as not duplicates, so it isn't suggested again
+    pub fn dismiss(&self, first_id: &str, second_id: &str) -> Result<()> {
+        self.find_transaction(first_id)?;
+        self.find_transaction(second_id)?;
+        self.repository
+            .record_duplicate_review(first_id, second_id, "dismissed")
+    }
+
+    fn find_transaction(&self, tx_id: &str) -> Result<Transaction> {
+        self.repository
+            .get_transaction_by_id(tx_id)?
+            .filter(|tx| tx.deleted_at.is_none())
+            .ok_or_else(|| anyhow::anyhow!("Transaction not found: {}", tx_id))
+    }
+
+    fn account_names(&self) -> Result<HashMap<String, String>> {
+        Ok(self
+            .repository
+            .get_accounts()?
+            .into_iter()
+            .map(|a| (a.id.to_string(), a.nickname.unwrap_or(a.name)))
+            .collect())
+    }
+}
+
+/// Providers whose IDs for a transaction can change between syncs
+const UNSTABLE_ID_PROVIDERS: [&str; 1] = ["gocardless"];
+
+fn is_synced(tx: &Transaction) -> bool {
+    !tx.provider.external_ids.is_empty()
+}
+
+/// Pairs exact dedup already vouches for as distinct are left out: two rows
+/// of the same import, or two transactions with IDs from the same provider.
+/// GoCardless is the exception: accounts get new IDs when access is renewed,
+/// and banks without their own transaction IDs get hashed ones that change too
+fn could_be_duplicates(a: &Transaction, b: &Transaction) -> bool {
+    let same_batch = a.csv_batch_id.is_some() && a.csv_batch_id == b.csv_batch_id;
+    let same_provider = a.provider.external_ids.keys().any(|provider| {
+        !UNSTABLE_ID_PROVIDERS.contains(&provider.as_str())
+            && b.provider.external_ids.contains_key(provider)
+    });
+    !(same_batch || same_provider)
+}
+
+fn pair_key(a: &Transaction, b: &Transaction) -> (String, String) {
+    let (a, b) = (a.id.to_string(), b.id.to_string());
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// Dice coefficient of the normalized descriptions' character pairs
+fn description_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (normalize_description(a), normalize_description(b));
+    if a == b {
+        return 1.0;
+    }
+    let bigrams = |s: &str| -> Vec<(char, char)> {
+        let chars: Vec<char> = s.chars().collect();
+        chars.windows(2).map(|w| (w[0], w[1])).collect()
+    };
+    let (a, b) = (bigrams(&a), bigrams(&b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let mut remaining = b.clone();
+    let mut shared = 0;
+    for pair in &a {
+        if let Some(i) = remaining.iter().position(|p| p == pair) {
+            remaining.swap_remove(i);
+            shared += 1;
+        }
+    }
+    2.0 * shared as f64 / (a.len() + b.len()) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Account;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_description_similarity() {
+        assert_eq!(
+            description_similarity("COFFEE SHOP #123", "coffee shop #123"),
+            1.0
+        );
+        assert!(description_similarity("AMAZON MKTPL*2K4", "Amazon Mktpl") > 0.6);
+        assert!(description_similarity("Grocery Mart", "Electric Company") < 0.3);
+        assert_eq!(description_similarity("", "Coffee"), 0.0);
+    }
+
+    #[test]
+    fn test_find_merge_and_dismiss() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        let account = Account::new(Uuid::new_v4(), "Checking");
+        repository.upsert_account(&account).unwrap();
+
+        let add = |day: u32, amount: &str, description: &str, sf_id: Option<&str>| {
+            let date = NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+            let mut tx =
+                Transaction::new(Uuid::new_v4(), account.id, amount.parse().unwrap(), date);
+            tx.description = Some(description.to_string());
+            tx.provider
+                .set_external_id("simplefin", sf_id.map(String::from));
+            tx.csv_batch_id = sf_id.is_none().then(|| "batch-1".to_string());
+            repository.upsert_transaction(&tx).unwrap();
+            tx.id.to_string()
+        };
+        // The same coffee from a CSV and from SimpleFIN, a day apart
+        let csv_coffee = add(3, "-4.50", "COFFEE SHOP 0412", None);
+        let synced_coffee = add(4, "-4.50", "Coffee Shop", Some("sf-1"));
+        // Same amount and close, but clearly something else
+        add(4, "-4.50", "Parking Meter", Some("sf-2"));
+        // Same description and amount, too far apart
+        add(20, "-4.50", "Coffee Shop", Some("sf-3"));
+        // Two rows of one import are trusted to be distinct
+        let lunch = add(5, "-12.00", "Lunch", None);
+        add(6, "-12.00", "Lunch", None);
+        // A rent pair to dismiss
+        let rent_csv = add(1, "-1400.00", "RENT PAYMENT", None);
+        let rent_synced = add(1, "-1400.00", "Rent payment online", Some("sf-4"));
+
+        let service = DuplicateService::new(repository.clone());
+        let candidates = service.find(&DuplicateOptions::default()).unwrap();
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].first.id, csv_coffee);
+        assert_eq!(candidates[0].second.id, synced_coffee);
+        assert_eq!(candidates[0].suggested_keep, synced_coffee);
+        assert_eq!(candidates[0].days_apart, 1);
+        assert_eq!(candidates[1].suggested_keep, rent_synced);
+
+        // A tighter date window leaves only the same-day pair
+        let same_day = DuplicateOptions {
+            max_days: 0,
+            ..Default::default()
+        };
+        assert_eq!(service.find(&same_day).unwrap().len(), 1);
+
+        let kept = service.merge(&synced_coffee, &csv_coffee).unwrap();
+        assert_eq!(kept.id, synced_coffee);
+        assert!(repository
+            .get_transaction_by_id(&csv_coffee)
+            .unwrap()
+            .unwrap()
+            .deleted_at
+            .is_some());
+
+        service.dismiss(&rent_synced, &rent_csv).unwrap();
+        assert_eq!(repository.get_duplicate_reviews().unwrap().len(), 2);
+        assert!(service
+            .find(&DuplicateOptions::default())
+            .unwrap()
+            .is_empty());
+
+        assert!(service.merge(&lunch, &lunch).is_err());
+        assert!(service.dismiss(&lunch, &csv_coffee).is_err());
+    }
+}
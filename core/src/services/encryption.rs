@@ -3,25 +3,38 @@
 //! Uses DuckDB's native AES-256-GCM encryption with Argon2id key derivation.
 
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
 use base64::Engine;
 use duckdb::Connection;
 use serde::Serialize;
+use zeroize::Zeroizing;
 
-use crate::domain::{EncryptionMetadata, EncryptionStatus};
+use crate::config::Config;
+use crate::domain::{Argon2Params, EncryptionKey, EncryptionMetadata, EncryptionStatus};
+use crate::json_file::{corrupt_backup_path, read_json, write_json};
+use crate::services::disk_space::ensure_free_space;
 
-/// Default Argon2 parameters matching Python CLI
-const DEFAULT_TIME_COST: u32 = 3;
-const DEFAULT_MEMORY_COST: u32 = 65536; // 64 MiB
-const DEFAULT_PARALLELISM: u32 = 4;
-const DEFAULT_HASH_LEN: u32 = 32;
+/// Default unlock time targeted by calibration
+pub const DEFAULT_CALIBRATION_TARGET: Duration = Duration::from_millis(500);
+
+/// Upper bound for calibrated memory cost (1 GiB), keeps unlock usable on
+/// machines that are fast but short on RAM
+const MAX_CALIBRATED_MEMORY_COST: u32 = 1024 * 1024;
+
+/// Upper bound for calibrated passes
+const MAX_CALIBRATED_TIME_COST: u32 = 10;
+
+/// Called as a re-encryption moves to its next stage
+type EncryptionProgressFn = Box<dyn Fn(EncryptionStage) + Send + Sync>;
 
 /// Encryption service for database encryption
 pub struct EncryptionService {
     treeline_dir: PathBuf,
     db_path: PathBuf,
+    progress: Option<EncryptionProgressFn>,
 }
 
 impl EncryptionService {
@@ -29,6 +42,22 @@ impl EncryptionService {
         Self {
             treeline_dir,
             db_path,
+            progress: None,
+        }
+    }
+
+    /// Report each stage of `upgrade_params` as it starts
+    pub fn with_progress(
+        mut self,
+        progress: impl Fn(EncryptionStage) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    fn report(&self, stage: EncryptionStage) {
+        if let Some(progress) = &self.progress {
+            progress(stage);
         }
     }
 
@@ -36,13 +65,18 @@ impl EncryptionService {
         self.treeline_dir.join("encryption.json")
     }
 
+    /// Metadata for a re-encrypted database, written before the database is
+    /// swapped in and renamed over `encryption.json` after
+    fn pending_encryption_file(&self) -> PathBuf {
+        self.treeline_dir.join("encryption.json.new")
+    }
+
     /// Derive encryption key from password using Argon2id
-    fn derive_key(
-        &self,
+    pub(crate) fn derive_key(
         password: &str,
         salt: &[u8],
         params: &crate::domain::Argon2Params,
-    ) -> Result<Vec<u8>> {
+    ) -> Result<Zeroizing<Vec<u8>>> {
         let argon2_params = argon2::Params::new(
             params.memory_cost,
             params.time_cost,
@@ -57,7 +91,7 @@ impl EncryptionService {
             argon2_params,
         );
 
-        let mut key = vec![0u8; params.hash_len as usize];
+        let mut key = Zeroizing::new(vec![0u8; params.hash_len as usize]);
         argon2
             .hash_password_into(password.as_bytes(), salt, &mut key)
             .map_err(|e| anyhow::anyhow!("Failed to derive key: {:?}", e))?;
@@ -65,6 +99,221 @@ impl EncryptionService {
         Ok(key)
     }
 
+    /// Only called when encryption.json exists, so None means it is corrupted
+    fn read_metadata(&self) -> Result<EncryptionMetadata> {
+        let path = self.encryption_file();
+        read_json(&path)?.with_context(|| {
+            format!(
+                "{} is corrupted (a copy was saved to {}); restore it from a backup",
+                path.display(),
+                corrupt_backup_path(&path).display()
+            )
+        })
+    }
+
+    fn write_metadata(&self, metadata: &EncryptionMetadata) -> Result<()> {
+        write_json(&self.encryption_file(), metadata)
+    }
+
+    /// Argon2 params for new keys: the calibrated ones from settings, or defaults
+    pub fn target_params(&self) -> Argon2Params {
+        Config::load(&self.treeline_dir)
+            .ok()
+            .and_then(|c| c.argon2_params)
+            .unwrap_or_default()
+    }
+
+    /// Measure this machine and suggest Argon2 params that take about `target`
+    ///
+    /// Starts from the defaults and never goes below them. Memory is doubled
+    /// first (up to 1 GiB) since it is what makes GPU cracking expensive, then
+    /// passes are added until the next one would overshoot the target.
+    pub fn calibrate(target: Duration) -> Result<CalibrationResult> {
+        let mut params = Argon2Params::default();
+        let mut elapsed = Self::time_derivation(&params)?;
+
+        while elapsed * 2 <= target && params.memory_cost * 2 <= MAX_CALIBRATED_MEMORY_COST {
+            params.memory_cost *= 2;
+            elapsed = Self::time_derivation(&params)?;
+        }
+
+        let per_pass = elapsed / params.time_cost;
+        while elapsed + per_pass <= target && params.time_cost < MAX_CALIBRATED_TIME_COST {
+            params.time_cost += 1;
+            elapsed += per_pass;
+        }
+        let elapsed = Self::time_derivation(&params)?;
+
+        Ok(CalibrationResult {
+            params,
+            elapsed_ms: elapsed.as_millis() as u64,
+            target_ms: target.as_millis() as u64,
+        })
+    }
+
+    fn time_derivation(params: &Argon2Params) -> Result<Duration> {
+        use rand::Rng;
+        let salt: [u8; 16] = rand::thread_rng().gen();
+        let start = Instant::now();
+        Self::derive_key("treeline-calibration", &salt, params)?;
+        Ok(start.elapsed())
+    }
+
+    /// Whether the database key was derived with weaker params than configured
+    ///
+    /// When true, callers holding the password should call `upgrade_params`.
+    pub fn needs_param_upgrade(&self) -> Result<bool> {
+        if !self.is_encrypted()? {
+            return Ok(false);
+        }
+        let metadata = self.read_metadata()?;
+        Ok(metadata.argon2_params.is_weaker_than(&self.target_params()))
+    }
+
+    /// Re-encrypt the database with a key derived from the configured params
+    ///
+    /// Generates a fresh salt, so the old key stops working. Returns the new
+    /// key so callers can keep using the database without re-deriving it.
+    ///
+    /// On error the database is untouched and the old key still opens it.
+    /// The new metadata is staged before the database is swapped in; if it
+    /// can't be moved into place after, `finish_interrupted_upgrade` does so
+    /// on the next unlock.
+    pub fn upgrade_params(
+        &self,
+        password: &str,
+        backup_service: &super::BackupService,
+    ) -> Result<EncryptionKey> {
+        if !self.is_encrypted()? {
+            anyhow::bail!("Database is not encrypted");
+        }
+
+        let old_key = self.derive_key_for_connection(password)?;
+        self.verify_key(&old_key)?;
+
+        use rand::Rng;
+        let salt: [u8; 16] = rand::thread_rng().gen();
+        let salt_b64 = base64::engine::general_purpose::STANDARD.encode(salt);
+        let argon2_params = self.target_params();
+        let new_key =
+            EncryptionKey::from_bytes(&Self::derive_key(password, &salt, &argon2_params)?);
+
+        self.ensure_space_for_rewrite("re-encrypt the database")?;
+        self.report(EncryptionStage::BackingUp);
+        backup_service.create(None)?;
+
+        let export_dir =
+            tempfile::tempdir().context("Failed to create temp directory for export")?;
+        let temp_db_path = fresh_temp_db_path()?;
+
+        self.report(EncryptionStage::Exporting);
+        {
+            let conn = attach_encrypted(&self.db_path, &old_key)?;
+            conn.execute_batch(&format!(
+                "EXPORT DATABASE '{}'",
+                export_dir.path().display()
+            ))
+            .context("Failed to export encrypted database")?;
+        }
+
+        self.report(EncryptionStage::Importing);
+        {
+            let conn = attach_encrypted(&temp_db_path, &new_key)?;
+            conn.execute_batch(&format!(
+                "IMPORT DATABASE '{}'",
+                export_dir.path().display()
+            ))
+            .context("Failed to import database")?;
+        }
+
+        self.report(EncryptionStage::Replacing);
+        let pending = self.pending_encryption_file();
+        let metadata = EncryptionMetadata::new_encrypted_with_params(salt_b64, argon2_params);
+        let swapped = write_json(&pending, &metadata).and_then(|_| {
+            replace_database(&temp_db_path, &self.db_path)
+                .context("Failed to replace database with re-encrypted version")
+        });
+        if let Err(e) = swapped {
+            let _ = fs::remove_file(&pending);
+            let _ = fs::remove_file(&temp_db_path);
+            return Err(e);
+        }
+
+        // The database is under the new key from here on; if this rename
+        // fails the next unlock finishes it
+        let _ = fs::rename(&pending, self.encryption_file());
+        Ok(new_key)
+    }
+
+    /// Finish or discard a re-encryption that stopped between swapping in
+    /// the database and renaming its metadata into place
+    ///
+    /// Whichever metadata opens the database with `password` is kept. With
+    /// a wrong password neither does, and both are left alone.
+    pub fn finish_interrupted_upgrade(&self, password: &str) -> Result<()> {
+        let pending = self.pending_encryption_file();
+        if !pending.exists() {
+            return Ok(());
+        }
+        let opens = |metadata: &EncryptionMetadata| -> bool {
+            let Ok(salt) = base64::engine::general_purpose::STANDARD.decode(&metadata.salt) else {
+                return false;
+            };
+            Self::derive_key(password, &salt, &metadata.argon2_params)
+                .is_ok_and(|key| self.verify_key(&EncryptionKey::from_bytes(&key)).is_ok())
+        };
+
+        match read_json::<EncryptionMetadata>(&pending)? {
+            Some(metadata) if opens(&metadata) => fs::rename(&pending, self.encryption_file())
+                .context("Failed to save the re-encrypted database's metadata"),
+            Some(_) if !opens(&self.read_metadata()?) => Ok(()),
+            // The swap never happened
+            _ => fs::remove_file(&pending).context("Failed to remove stale encryption metadata"),
+        }
+    }
+
+    /// Check a key by opening the database read-only and reading the catalog
+    fn verify_key(&self, key: &EncryptionKey) -> Result<()> {
+        let config = duckdb::Config::default()
+            .enable_autoload_extension(false)
+            .context("Failed to configure database")?;
+        let conn = Connection::open_in_memory_with_flags(config)
+            .context("Failed to open in-memory connection")?;
+        conn.execute_batch(&Zeroizing::new(format!(
+            "ATTACH '{}' AS enc (ENCRYPTION_KEY '{}', READ_ONLY)",
+            self.db_path.display(),
+            key.expose()
+        )))
+        .map_err(|_| anyhow::anyhow!("Invalid password"))?;
+
+        conn.execute_batch("USE enc")
+            .map_err(|_| anyhow::anyhow!("Invalid password"))?;
+        conn.query_row(
+            "SELECT table_name FROM information_schema.tables LIMIT 1",
+            [],
+            |_| Ok(()),
+        )
+        .map_err(|_| anyhow::anyhow!("Invalid password"))?;
+        Ok(())
+    }
+
+    /// Check there's room for an export and a new copy of the database
+    ///
+    /// The temp directory holds both, sized at twice the current database:
+    /// the export is usually smaller, but plain-text columns can grow once
+    /// decrypted. When the temp directory is on another filesystem,
+    /// `replace_database` stages the new copy next to the old one, so the
+    /// database's directory needs room for it too. The backup taken first
+    /// checks its own space.
+    fn ensure_space_for_rewrite(&self, operation: &str) -> Result<()> {
+        let db_size = fs::metadata(&self.db_path)?.len();
+        ensure_free_space(&std::env::temp_dir(), db_size.saturating_mul(2), operation)?;
+        if let Some(db_dir) = self.db_path.parent() {
+            ensure_free_space(db_dir, db_size, operation)?;
+        }
+        Ok(())
+    }
+
     /// Get encryption status
     pub fn get_status(&self) -> Result<EncryptionStatus> {
         let enc_file = self.encryption_file();
@@ -72,8 +321,7 @@ impl EncryptionService {
             return Ok(EncryptionStatus::unencrypted());
         }
 
-        let content = fs::read_to_string(&enc_file)?;
-        let metadata: EncryptionMetadata = serde_json::from_str(&content)?;
+        let metadata = self.read_metadata()?;
 
         Ok(EncryptionStatus::from_metadata(&metadata))
     }
@@ -85,14 +333,14 @@ impl EncryptionService {
     }
 
     /// Get the encryption key as hex string for database connections
-    pub fn derive_key_for_connection(&self, password: &str) -> Result<String> {
+    pub fn derive_key_for_connection(&self, password: &str) -> Result<EncryptionKey> {
+        self.finish_interrupted_upgrade(password)?;
         let enc_file = self.encryption_file();
         if !enc_file.exists() {
             anyhow::bail!("Database is not encrypted");
         }
 
-        let content = fs::read_to_string(&enc_file)?;
-        let metadata: EncryptionMetadata = serde_json::from_str(&content)?;
+        let metadata = self.read_metadata()?;
 
         if !metadata.encrypted {
             anyhow::bail!("Database is not encrypted");
@@ -102,8 +350,8 @@ impl EncryptionService {
             .decode(&metadata.salt)
             .context("Invalid salt in encryption metadata")?;
 
-        let key = self.derive_key(password, &salt, &metadata.argon2_params)?;
-        Ok(hex::encode(&key))
+        let key = Self::derive_key(password, &salt, &metadata.argon2_params)?;
+        Ok(EncryptionKey::from_bytes(&key))
     }
 
     /// Enable encryption
@@ -120,6 +368,7 @@ impl EncryptionService {
         if !self.db_path.exists() {
             anyhow::bail!("Database file not found");
         }
+        self.ensure_space_for_rewrite("encrypt the database")?;
 
         // Create backup first
         let backup = backup_service.create(None)?;
@@ -129,17 +378,12 @@ impl EncryptionService {
         let salt: [u8; 16] = rand::thread_rng().gen();
         let salt_b64 = base64::engine::general_purpose::STANDARD.encode(&salt);
 
-        // Create Argon2 params
-        let argon2_params = crate::domain::Argon2Params {
-            time_cost: DEFAULT_TIME_COST,
-            memory_cost: DEFAULT_MEMORY_COST,
-            parallelism: DEFAULT_PARALLELISM,
-            hash_len: DEFAULT_HASH_LEN,
-        };
+        // Use calibrated Argon2 params if the user chose some
+        let argon2_params = self.target_params();
 
         // Derive key
-        let key = self.derive_key(password, &salt, &argon2_params)?;
-        let key_hex = hex::encode(&key);
+        let key = Self::derive_key(password, &salt, &argon2_params)?;
+        let key = EncryptionKey::from_bytes(&key);
 
         // Create temp directory for export
         let export_dir =
@@ -177,11 +421,11 @@ impl EncryptionService {
                 .context("Failed to open in-memory connection")?;
 
             // Attach encrypted database
-            conn.execute_batch(&format!(
+            conn.execute_batch(&Zeroizing::new(format!(
                 "ATTACH '{}' AS enc (ENCRYPTION_KEY '{}')",
                 temp_db_path.display(),
-                key_hex
-            ))
+                key.expose()
+            )))
             .context("Failed to attach encrypted database")?;
 
             // Use the attached database and import
@@ -203,8 +447,7 @@ impl EncryptionService {
 
         // Save encryption metadata
         let metadata = EncryptionMetadata::new_encrypted_with_params(salt_b64, argon2_params);
-        let content = serde_json::to_string_pretty(&metadata)?;
-        fs::write(self.encryption_file(), content)?;
+        self.write_metadata(&metadata)?;
 
         Ok(EncryptResult {
             encrypted: true,
@@ -224,41 +467,18 @@ impl EncryptionService {
 
         // Load metadata
         let enc_file = self.encryption_file();
-        let content = fs::read_to_string(&enc_file)?;
-        let metadata: EncryptionMetadata = serde_json::from_str(&content)?;
+        let metadata = self.read_metadata()?;
 
         // Derive key
         let salt = base64::engine::general_purpose::STANDARD
             .decode(&metadata.salt)
             .context("Invalid salt in encryption metadata")?;
-        let key = self.derive_key(password, &salt, &metadata.argon2_params)?;
-        let key_hex = hex::encode(&key);
+        let key = Self::derive_key(password, &salt, &metadata.argon2_params)?;
+        let key = EncryptionKey::from_bytes(&key);
 
         // Verify password by attempting to read the encrypted database
-        // IMPORTANT: Disable extension autoloading to avoid macOS code signing issues
-        {
-            let config = duckdb::Config::default()
-                .enable_autoload_extension(false)
-                .context("Failed to configure database")?;
-            let conn = Connection::open_in_memory_with_flags(config)
-                .context("Failed to open in-memory connection")?;
-            conn.execute_batch(&format!(
-                "ATTACH '{}' AS enc (ENCRYPTION_KEY '{}', READ_ONLY)",
-                self.db_path.display(),
-                key_hex
-            ))
-            .map_err(|_| anyhow::anyhow!("Invalid password"))?;
-
-            // Try to read something to verify
-            conn.execute_batch("USE enc")
-                .map_err(|_| anyhow::anyhow!("Invalid password"))?;
-            conn.query_row(
-                "SELECT table_name FROM information_schema.tables LIMIT 1",
-                [],
-                |_| Ok(()),
-            )
-            .map_err(|_| anyhow::anyhow!("Invalid password"))?;
-        }
+        self.verify_key(&key)?;
+        self.ensure_space_for_rewrite("decrypt the database")?;
 
         // Create backup first
         let backup = backup_service.create(None)?;
@@ -285,11 +505,11 @@ impl EncryptionService {
                 .context("Failed to configure database")?;
             let conn = Connection::open_in_memory_with_flags(config)
                 .context("Failed to open in-memory connection")?;
-            conn.execute_batch(&format!(
+            conn.execute_batch(&Zeroizing::new(format!(
                 "ATTACH '{}' AS enc (ENCRYPTION_KEY '{}')",
                 self.db_path.display(),
-                key_hex
-            ))
+                key.expose()
+            )))
             .context("Failed to attach encrypted database")?;
 
             conn.execute_batch("USE enc")
@@ -331,9 +551,144 @@ impl EncryptionService {
     }
 }
 
+/// Open an in-memory connection with `path` attached as `enc` and selected
+///
+/// IMPORTANT: Disables extension autoloading to avoid macOS code signing issues
+fn attach_encrypted(path: &Path, key: &EncryptionKey) -> Result<Connection> {
+    let config = duckdb::Config::default()
+        .enable_autoload_extension(false)
+        .context("Failed to configure database")?;
+    let conn = Connection::open_in_memory_with_flags(config)
+        .context("Failed to open in-memory connection")?;
+    conn.execute_batch(&Zeroizing::new(format!(
+        "ATTACH '{}' AS enc (ENCRYPTION_KEY '{}')",
+        path.display(),
+        key.expose()
+    )))
+    .context("Failed to attach encrypted database")?;
+    conn.execute_batch("USE enc")
+        .context("Failed to use encrypted database")?;
+    Ok(conn)
+}
+
+/// Reserve a temp path that doesn't exist yet so DuckDB can create it fresh
+fn fresh_temp_db_path() -> Result<PathBuf> {
+    let temp_db = tempfile::NamedTempFile::new()
+        .context("Failed to create temp file for re-encrypted database")?;
+    let temp_db_path = temp_db.path().to_path_buf();
+    drop(temp_db);
+    if temp_db_path.exists() {
+        fs::remove_file(&temp_db_path)?;
+    }
+    Ok(temp_db_path)
+}
+
+/// Move `temp` over `target` in one rename
+///
+/// Across filesystems `temp` is first copied next to `target`, so a crash
+/// leaves either the old database or the new one, never half of each.
+fn replace_database(temp: &Path, target: &Path) -> std::io::Result<()> {
+    if fs::rename(temp, target).is_ok() {
+        return Ok(());
+    }
+    let mut staged = target.as_os_str().to_owned();
+    staged.push(".new");
+    let staged = PathBuf::from(staged);
+    fs::copy(temp, &staged)?;
+    fs::rename(&staged, target).inspect_err(|_| {
+        let _ = fs::remove_file(&staged);
+    })?;
+    fs::remove_file(temp)
+}
+
+/// Stages of re-encrypting the database, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EncryptionStage {
+    BackingUp,
+    Exporting,
+    Importing,
+    /// Swapping the re-encrypted database in
+    Replacing,
+}
+
+impl EncryptionStage {
+    pub fn label(&self) -> &'static str {
+        match self {
+            EncryptionStage::BackingUp => "Backing up the database",
+            EncryptionStage::Exporting => "Reading the database",
+            EncryptionStage::Importing => "Re-encrypting the database",
+            EncryptionStage::Replacing => "Saving the re-encrypted database",
+        }
+    }
+}
+
+/// Argon2 params suggested by `EncryptionService::calibrate`
+#[derive(Debug, Serialize)]
+pub struct CalibrationResult {
+    pub params: Argon2Params,
+    /// Measured key derivation time with `params`
+    pub elapsed_ms: u64,
+    pub target_ms: u64,
+}
+
 #[derive(Debug, Serialize)]
 pub struct EncryptResult {
     /// Whether the database is now encrypted (true after encrypt, false after decrypt)
     pub encrypted: bool,
     pub backup_name: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::BackupService;
+
+    fn encrypted_db(dir: &Path) -> (EncryptionService, BackupService) {
+        let db_path = dir.join("treeline.duckdb");
+        Connection::open(&db_path)
+            .unwrap()
+            .execute_batch("CREATE TABLE t (x INTEGER); INSERT INTO t VALUES (1)")
+            .unwrap();
+        let service = EncryptionService::new(dir.to_path_buf(), db_path);
+        let backups = BackupService::new(dir.to_path_buf(), "treeline.duckdb".to_string());
+        service.encrypt("hunter2", &backups).unwrap();
+        (service, backups)
+    }
+
+    #[test]
+    fn test_unswapped_upgrade_is_discarded() {
+        let dir = tempfile::tempdir().unwrap();
+        let (service, _) = encrypted_db(dir.path());
+        let params = service.read_metadata().unwrap().argon2_params;
+        let stale = EncryptionMetadata::new_encrypted_with_params(
+            base64::engine::general_purpose::STANDARD.encode([9u8; 16]),
+            params,
+        );
+        write_json(&service.pending_encryption_file(), &stale).unwrap();
+
+        let key = service.derive_key_for_connection("hunter2").unwrap();
+        assert!(!service.pending_encryption_file().exists());
+        service.verify_key(&key).unwrap();
+    }
+
+    #[test]
+    fn test_swapped_upgrade_is_finished_on_unlock() {
+        let dir = tempfile::tempdir().unwrap();
+        let (service, backups) = encrypted_db(dir.path());
+        let old_metadata = fs::read(service.encryption_file()).unwrap();
+        let new_key = service.upgrade_params("hunter2", &backups).unwrap();
+
+        // Stopped after the swap, before the metadata was renamed into place
+        fs::rename(service.encryption_file(), service.pending_encryption_file()).unwrap();
+        fs::write(service.encryption_file(), old_metadata).unwrap();
+
+        service.finish_interrupted_upgrade("wrong").unwrap();
+        assert!(service.pending_encryption_file().exists());
+
+        let key = service.derive_key_for_connection("hunter2").unwrap();
+        assert!(!service.pending_encryption_file().exists());
+        assert_eq!(key.expose(), new_key.expose());
+        service.verify_key(&key).unwrap();
+    }
+}
@@ -0,0 +1,317 @@
+//! Hook service - user scripts run around sync, import and backup
+//!
+//! Executables in `~/.treeline/hooks` named after an event (`post-sync`,
+//! `post-sync.sh`, `post-sync.py`, ...) are run when it happens, with a JSON
+//! payload on stdin. Hooks are best-effort: a failing or slow hook is killed
+//! and noted in its log, but never fails the operation that triggered it.
+//! Script output goes to `hooks/logs/<script>.log`, overwritten on each run,
+//! so it can't interfere with `--json` output.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::config::Config;
+
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// When a hook runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    /// After a successful sync; payload is the sync result
+    PostSync,
+    /// After transactions are imported (not previews); payload is the import result
+    PostImport,
+    /// Before a backup is written
+    PreBackup,
+}
+
+impl HookEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookEvent::PostSync => "post-sync",
+            HookEvent::PostImport => "post-import",
+            HookEvent::PreBackup => "pre-backup",
+        }
+    }
+}
+
+/// What happened when a hook script ran
+#[derive(Debug, Clone, Serialize)]
+pub struct HookOutcome {
+    /// Script file name
+    pub script: String,
+    /// None when the script timed out or couldn't be started
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub duration_ms: u64,
+    /// Why the script couldn't be started
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl HookOutcome {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+#[derive(Serialize)]
+struct HookPayload<'a, T: Serialize> {
+    event: &'static str,
+    timestamp: chrono::DateTime<Utc>,
+    data: &'a T,
+}
+
+/// Hook service for finding and running hook scripts
+pub struct HookService {
+    treeline_dir: PathBuf,
+}
+
+impl HookService {
+    pub fn new(treeline_dir: PathBuf) -> Self {
+        Self { treeline_dir }
+    }
+
+    pub fn hooks_dir(&self) -> PathBuf {
+        self.treeline_dir.join("hooks")
+    }
+
+    fn timeout(&self) -> Duration {
+        let secs = Config::load(&self.treeline_dir)
+            .ok()
+            .and_then(|c| c.hook_timeout_secs)
+            .unwrap_or(DEFAULT_TIMEOUT_SECS);
+        Duration::from_secs(secs)
+    }
+
+    /// Scripts for an event, in name order
+    ///
+    /// Matches `<event>` and `<event>.<ext>`; hidden files and `.sample`
+    /// files are ignored.
+    pub fn scripts(&self, event: HookEvent) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(self.hooks_dir()) else {
+            return Vec::new();
+        };
+
+        let mut scripts: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|path| path.is_file())
+            .filter(|path| {
+                let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                    return false;
+                };
+                if name.starts_with('.') || name.ends_with(".sample") {
+                    return false;
+                }
+                name == event.name()
+                    || name
+                        .strip_prefix(event.name())
+                        .is_some_and(|rest| rest.starts_with('.'))
+            })
+            .collect();
+        scripts.sort();
+        scripts
+    }
+
+    /// Run every script for an event, one after another
+    ///
+    /// Never fails: problems are reported in the outcomes and the script logs.
+    pub fn run<T: Serialize>(&self, event: HookEvent, data: &T) -> Vec<HookOutcome> {
+        let scripts = self.scripts(event);
+        if scripts.is_empty() {
+            return Vec::new();
+        }
+
+        let payload = HookPayload {
+            event: event.name(),
+            timestamp: Utc::now(),
+            data,
+        };
+        let payload = match serde_json::to_vec(&payload) {
+            Ok(payload) => payload,
+            Err(_) => return Vec::new(),
+        };
+        let timeout = self.timeout();
+
+        scripts
+            .iter()
+            .map(|script| self.run_script(event, script, &payload, timeout))
+            .collect()
+    }
+
+    fn run_script(
+        &self,
+        event: HookEvent,
+        script: &Path,
+        payload: &[u8],
+        timeout: Duration,
+    ) -> HookOutcome {
+        let name = script
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let started = Instant::now();
+
+        let mut outcome = HookOutcome {
+            script: name.clone(),
+            exit_code: None,
+            timed_out: false,
+            duration_ms: 0,
+            error: None,
+        };
+
+        let log_path = self.hooks_dir().join("logs").join(format!("{}.log", name));
+        let result = self.spawn_and_wait(event, script, payload, timeout, &log_path);
+        outcome.duration_ms = started.elapsed().as_millis() as u64;
+
+        let summary = match result {
+            Ok(Some(code)) => {
+                outcome.exit_code = Some(code);
+                format!("exited with {} after {}ms", code, outcome.duration_ms)
+            }
+            Ok(None) => {
+                outcome.timed_out = true;
+                format!("killed after {}s timeout", timeout.as_secs())
+            }
+            Err(e) => {
+                let message = format!("{:#}", e);
+                outcome.error = Some(message.clone());
+                message
+            }
+        };
+
+        if let Ok(mut log) = fs::OpenOptions::new().append(true).open(&log_path) {
+            let _ = writeln!(log, "# {}", summary);
+        }
+        outcome
+    }
+
+    /// Start the script and wait for it; Ok(None) if it was killed for timing out
+    fn spawn_and_wait(
+        &self,
+        event: HookEvent,
+        script: &Path,
+        payload: &[u8],
+        timeout: Duration,
+        log_path: &Path,
+    ) -> Result<Option<i32>> {
+        if let Some(dir) = log_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut log = File::create(log_path)
+            .with_context(|| format!("Failed to create {}", log_path.display()))?;
+        writeln!(log, "# {} at {}", event.name(), Utc::now().to_rfc3339())?;
+
+        let mut child = Command::new(script)
+            .current_dir(self.hooks_dir())
+            .env("TREELINE_DIR", &self.treeline_dir)
+            .env("TREELINE_HOOK", event.name())
+            .stdin(Stdio::piped())
+            .stdout(log.try_clone()?)
+            .stderr(log)
+            .spawn()
+            .with_context(|| format!("Failed to run {} (is it executable?)", script.display()))?;
+
+        // Written from a thread so a script that never reads stdin can't
+        // block us past the timeout
+        if let Some(mut stdin) = child.stdin.take() {
+            let payload = payload.to_vec();
+            std::thread::spawn(move || {
+                let _ = stdin.write_all(&payload);
+            });
+        }
+
+        let started = Instant::now();
+        loop {
+            if let Some(status) = child.try_wait()? {
+                return Ok(Some(status.code().unwrap_or(-1)));
+            }
+            if started.elapsed() >= timeout {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Ok(None);
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn write_script(dir: &Path, name: &str, body: &str) -> PathBuf {
+        let hooks = dir.join("hooks");
+        fs::create_dir_all(&hooks).unwrap();
+        let path = hooks.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_scripts_match_event_name() {
+        let dir = tempfile::tempdir().unwrap();
+        write_script(dir.path(), "post-sync", "true");
+        write_script(dir.path(), "post-sync.py", "true");
+        write_script(dir.path(), "post-sync.sample", "true");
+        write_script(dir.path(), "post-synced", "true");
+        write_script(dir.path(), "pre-backup.sh", "true");
+
+        let service = HookService::new(dir.path().to_path_buf());
+        let names: Vec<String> = service
+            .scripts(HookEvent::PostSync)
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["post-sync", "post-sync.py"]);
+        assert!(service.scripts(HookEvent::PostImport).is_empty());
+    }
+
+    #[test]
+    fn test_payload_on_stdin() {
+        let dir = tempfile::tempdir().unwrap();
+        write_script(dir.path(), "post-import", "cat > payload.json; exit 3");
+
+        let service = HookService::new(dir.path().to_path_buf());
+        let outcomes = service.run(HookEvent::PostImport, &serde_json::json!({"imported": 4}));
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].exit_code, Some(3));
+        assert!(!outcomes[0].succeeded());
+
+        let payload: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(dir.path().join("hooks/payload.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(payload["event"], "post-import");
+        assert_eq!(payload["data"]["imported"], 4);
+
+        let log = fs::read_to_string(dir.path().join("hooks/logs/post-import.log")).unwrap();
+        assert!(log.contains("exited with 3"));
+    }
+
+    #[test]
+    fn test_slow_hook_is_killed() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = write_script(dir.path(), "pre-backup", "sleep 10");
+
+        let service = HookService::new(dir.path().to_path_buf());
+        let outcome = service.run_script(
+            HookEvent::PreBackup,
+            &script,
+            b"{}",
+            Duration::from_millis(200),
+        );
+        assert!(outcome.timed_out);
+        assert_eq!(outcome.exit_code, None);
+    }
+}
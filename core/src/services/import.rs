@@ -1,6 +1,7 @@
 //! Import service - CSV transaction import
 
 use std::collections::HashMap;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -8,14 +9,17 @@ use anyhow::{Context, Result};
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Utc};
 use regex::Regex;
 use rust_decimal::Decimal;
+use schemars::JsonSchema;
 use serde::Serialize;
 use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
 use crate::adapters::duckdb::DuckDbRepository;
 use crate::config::{ColumnMappings, Config, ImportOptions as ConfigImportOptions, ImportProfile};
-use crate::domain::{Account, BalanceSnapshot, Transaction};
-use crate::services::TagService;
+use crate::domain::{Account, AccountType, BalanceSnapshot, Transaction};
+#[cfg(feature = "pdf")]
+use crate::services::read_statement_table;
+use crate::services::{HookEvent, HookService, TagService};
 
 /// Number format for parsing amounts
 #[derive(Debug, Clone, Copy, Default, PartialEq)]
@@ -64,13 +68,73 @@ pub struct ImportOptions {
     pub anchor_balance: Option<Decimal>,
     /// Anchor date for the anchor balance (preview only)
     pub anchor_date: Option<NaiveDate>,
+    /// Per-column number format overrides, keyed by CSV column name
+    /// (e.g., an EU-formatted amount column next to a US-formatted balance column)
+    pub column_number_formats: HashMap<String, NumberFormat>,
+    /// Account assignment for account-column values (CSV value -> account ID or name)
+    pub account_map: HashMap<String, String>,
+    /// Create accounts for account-column values that match no existing account
+    pub create_missing_accounts: bool,
+    /// Translation of the bank's category names to tags (empty tag drops the category)
+    pub category_map: HashMap<String, String>,
+    /// strftime format of the date column (e.g. "%d.%m.%Y"); common formats are tried when unset
+    pub date_format: Option<String>,
+    /// The file has no header row: columns are named `Column 1`..`Column N`,
+    /// and mappings may also give a column by 0-based index
+    pub no_header: bool,
 }
 
+impl ImportOptions {
+    /// Options as saved in an import profile
+    pub fn from_profile(profile: &ImportProfile) -> Self {
+        let options = &profile.options;
+        Self {
+            debit_negative: options.debit_negative,
+            flip_signs: options.flip_signs,
+            skip_rows: profile.skip_rows as u32,
+            number_format: options
+                .number_format
+                .as_deref()
+                .map(NumberFormat::from_str)
+                .unwrap_or_default(),
+            column_number_formats: options
+                .column_number_formats
+                .iter()
+                .map(|(column, format)| (column.clone(), NumberFormat::from_str(format)))
+                .collect(),
+            account_map: options.account_map.clone(),
+            create_missing_accounts: options.create_missing_accounts,
+            category_map: options.category_map.clone(),
+            date_format: profile.date_format.clone(),
+            no_header: options.no_header,
+            ..Default::default()
+        }
+    }
+
+    /// Number format to use for a given column, honoring per-column overrides
+    pub fn number_format_for(&self, column: &str) -> NumberFormat {
+        self.column_number_formats
+            .get(column)
+            .copied()
+            .unwrap_or(self.number_format)
+    }
+}
+
+/// Rows parsed between progress reports
+const PROGRESS_EVERY_ROWS: usize = 1000;
+/// Transactions written per insert, so a large file doesn't hold the
+/// database for one long write and progress moves while it's written
+const INSERT_BATCH_ROWS: usize = 5000;
+
+/// Called with each progress report of an import
+type ImportProgressFn = Box<dyn Fn(&ImportProgress) + Send + Sync>;
+
 /// Import service for CSV imports
 pub struct ImportService {
     repository: Arc<DuckDbRepository>,
     tag_service: TagService,
     treeline_dir: PathBuf,
+    progress: Option<ImportProgressFn>,
 }
 
 impl ImportService {
@@ -80,6 +144,23 @@ impl ImportService {
             repository,
             tag_service,
             treeline_dir,
+            progress: None,
+        }
+    }
+
+    /// Report progress while importing: every PROGRESS_EVERY_ROWS rows
+    /// parsed, and after each batch of transactions is written
+    pub fn with_progress(
+        mut self,
+        progress: impl Fn(&ImportProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    fn report(&self, stage: ImportStage, done: usize, total: usize) {
+        if let Some(progress) = &self.progress {
+            progress(&ImportProgress { stage, done, total });
         }
     }
 
@@ -98,98 +179,205 @@ impl ImportService {
         options: &ImportOptions,
         preview_only: bool,
     ) -> Result<ImportResult> {
-        // Verify account exists
-        if self.repository.get_account_by_id(account_id)?.is_none() {
-            anyhow::bail!("Account not found: {}", account_id);
-        }
-
-        let account_uuid = Uuid::parse_str(account_id).context("Invalid account ID")?;
-
-        // Read CSV with optional row skipping
-        let (headers, records) = if options.skip_rows > 0 {
-            // Use raw reader to skip rows before header
-            use std::fs::File;
-            use std::io::{BufRead, BufReader};
-
-            let file = File::open(file_path).context("Failed to open CSV file")?;
-            let buf_reader = BufReader::new(file);
-            let mut lines = buf_reader.lines();
-
-            // Skip leading rows
-            for _ in 0..options.skip_rows {
-                lines.next();
-            }
-
-            // Read header line
-            let header_line = lines
-                .next()
-                .ok_or_else(|| {
-                    anyhow::anyhow!(
-                        "No header row found after skipping {} rows",
-                        options.skip_rows
-                    )
-                })?
-                .context("Failed to read header line")?;
-
-            // Detect delimiter (semicolon common in EU, comma in US)
-            let semicolons = header_line.matches(';').count();
-            let commas = header_line.matches(',').count();
-            let tabs = header_line.matches('\t').count();
-            let delimiter = if semicolons > commas && semicolons > tabs {
-                b';'
-            } else if tabs > commas && tabs > semicolons {
-                b'\t'
-            } else {
-                b','
-            };
+        // Read CSV with optional row skipping (encoding and delimiter are sniffed)
+        let (headers, records) = read_csv_records(file_path, options.skip_rows, options.no_header)?;
+        let result = self.import_rows(
+            &headers,
+            &records,
+            Some(account_id),
+            &resolve_mappings(mappings, options),
+            options,
+            preview_only,
+        )?;
+        Ok(self.after_import(result))
+    }
 
-            // Parse headers using csv crate with detected delimiter
-            let mut header_reader = csv::ReaderBuilder::new()
-                .has_headers(false)
-                .delimiter(delimiter)
-                .from_reader(header_line.as_bytes());
+    /// Import transactions from CSV text, such as a table pasted from a bank's website
+    pub fn import_text(
+        &self,
+        content: &str,
+        account_id: &str,
+        mappings: &ColumnMappings,
+        options: &ImportOptions,
+        preview_only: bool,
+    ) -> Result<ImportResult> {
+        if content.trim().is_empty() {
+            anyhow::bail!("No CSV data to import");
+        }
+        let (headers, records) =
+            parse_csv_records(content.as_bytes(), options.skip_rows, options.no_header)?;
+        let result = self.import_rows(
+            &headers,
+            &records,
+            Some(account_id),
+            &resolve_mappings(mappings, options),
+            options,
+            preview_only,
+        )?;
+        Ok(self.after_import(result))
+    }
 
-            let header_record = header_reader
-                .records()
-                .next()
-                .ok_or_else(|| anyhow::anyhow!("Empty header line"))?
-                .context("Failed to parse header line")?;
+    /// Import the transaction table of a PDF statement
+    ///
+    /// The table's columns are fixed (see [`read_statement_table`]); sign,
+    /// number and date format options apply as they do to a CSV.
+    #[cfg(feature = "pdf")]
+    pub fn import_pdf(
+        &self,
+        file_path: &Path,
+        account_id: &str,
+        options: &ImportOptions,
+        preview_only: bool,
+    ) -> Result<ImportResult> {
+        let table = read_statement_table(file_path)?;
+        let (headers, records) = parse_csv_records(table.csv.as_bytes(), 0, false)?;
+        let result = self.import_rows(
+            &headers,
+            &records,
+            Some(account_id),
+            &table.column_mappings(),
+            options,
+            preview_only,
+        )?;
+        Ok(self.after_import(result))
+    }
 
-            // Clean headers: trim and strip # prefix
-            let headers: Vec<String> = header_record
-                .iter()
-                .map(|h| h.trim().trim_start_matches('#').to_string())
-                .collect();
+    /// Check a whole CSV file against the mappings before importing it
+    ///
+    /// Finds everything an import would skip or trip over: unparseable dates
+    /// and amounts, rows with the wrong number of fields (which an import
+    /// drops without a word), mapped columns the file doesn't have, dates
+    /// that read two ways, and amounts whose signs look reversed. Nothing is
+    /// written.
+    pub fn validate(
+        &self,
+        file_path: &Path,
+        mappings: &ColumnMappings,
+        options: &ImportOptions,
+    ) -> Result<ImportValidation> {
+        let bytes = std::fs::read(file_path).context("Failed to read CSV file")?;
+        let (headers, records) = parse_csv_rows(&bytes, options.skip_rows, options.no_header)?;
+        Ok(validate_records(
+            &headers,
+            &records,
+            &resolve_mappings(mappings, options),
+            options,
+        ))
+    }
 
-            // Collect remaining lines as data
-            let remaining_content: String =
-                lines.filter_map(|l| l.ok()).collect::<Vec<_>>().join("\n");
+    /// Undo an import, deleting the transactions and balance snapshots it created
+    ///
+    /// `batch_id` is the one reported by the import. Accounts the import
+    /// created are kept.
+    pub fn undo(&self, batch_id: &str) -> Result<UndoImportResult> {
+        let batch_id = batch_id.trim();
+        if batch_id.is_empty() {
+            anyhow::bail!("No batch ID given");
+        }
+        let (transactions, snapshots) = self.repository.delete_import_batch(batch_id)?;
+        if transactions == 0 && snapshots == 0 {
+            anyhow::bail!("No imported transactions found for batch {}", batch_id);
+        }
+        Ok(UndoImportResult {
+            batch_id: batch_id.to_string(),
+            transactions_deleted: transactions as i64,
+            balance_snapshots_deleted: snapshots as i64,
+        })
+    }
 
-            // Parse remaining content as CSV records with same delimiter
-            let mut data_reader = csv::ReaderBuilder::new()
-                .has_headers(false)
-                .delimiter(delimiter)
-                .from_reader(remaining_content.as_bytes());
+    /// Import an export from another app or card issuer using its fixed column layout
+    ///
+    /// YNAB and Mint exports name the account on every row, so `account_id` is
+    /// only needed as a fallback for rows with a blank account cell (accounts
+    /// that don't exist yet are created). Apple Card and PayPal exports cover a
+    /// single account and require `account_id`.
+    pub fn import_preset(
+        &self,
+        file_path: &Path,
+        preset: ImportPreset,
+        account_id: Option<&str>,
+        number_format: NumberFormat,
+        preview_only: bool,
+    ) -> Result<ImportResult> {
+        if preset.single_account() && account_id.is_none() {
+            anyhow::bail!("An account is required for {} imports", preset.label());
+        }
+        let is_pdf = file_path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("pdf"));
+        if is_pdf {
+            anyhow::bail!(
+                "PDF statements can't be imported. Export the statement as CSV instead \
+                 (for Apple Card: Wallet > Card Balance > choose a statement > Export Transactions)"
+            );
+        }
 
-            let records: Vec<csv::StringRecord> =
-                data_reader.records().filter_map(|r| r.ok()).collect();
+        let (headers, records) = read_csv_records(file_path, 0, false)?;
+        let mut options = preset.options();
+        options.number_format = number_format;
 
-            (headers, records)
-        } else {
-            // Standard path: first row is header
-            let mut reader =
-                csv::Reader::from_path(file_path).context("Failed to read CSV file")?;
+        if preset == ImportPreset::PayPal {
+            // PayPal rows are regrouped first: fees become their own transactions
+            // and currency conversions collapse onto the payment they funded
+            let account = self
+                .repository
+                .get_account_by_id(account_id.unwrap_or_default())?
+                .context("Account not found")?;
+            let paypal = normalize_paypal(&headers, &records, &account.currency, number_format)?;
+            let mappings = preset.mappings(&paypal.headers)?;
+            let mut result = self.import_rows(
+                &paypal.headers,
+                &paypal.records,
+                account_id,
+                &mappings,
+                &options,
+                preview_only,
+            )?;
+            result.skipped += paypal.dropped.len() as i64;
+            result.errors.extend(paypal.dropped);
+            result.errors.sort_by_key(|e| e.line);
+            return Ok(self.after_import(result));
+        }
 
-            // Clean headers: trim and strip # prefix
-            let headers: Vec<String> = reader
-                .headers()?
-                .iter()
-                .map(|h| h.trim().trim_start_matches('#').to_string())
-                .collect();
+        let mappings = preset.mappings(&headers)?;
+        let result = self.import_rows(
+            &headers,
+            &records,
+            account_id,
+            &mappings,
+            &options,
+            preview_only,
+        )?;
+        Ok(self.after_import(result))
+    }
 
-            let records: Vec<csv::StringRecord> = reader.records().filter_map(|r| r.ok()).collect();
+    /// Run post-import hooks for a completed (non-preview) import
+    fn after_import(&self, result: ImportResult) -> ImportResult {
+        if !result.preview {
+            HookService::new(self.treeline_dir.clone()).run(HookEvent::PostImport, &result);
+        }
+        result
+    }
 
-            (headers, records)
+    fn import_rows(
+        &self,
+        headers: &[String],
+        records: &[csv::StringRecord],
+        account_id: Option<&str>,
+        mappings: &ColumnMappings,
+        options: &ImportOptions,
+        preview_only: bool,
+    ) -> Result<ImportResult> {
+        // Verify the default account exists
+        let account_uuid = match account_id {
+            Some(account_id) => {
+                if self.repository.get_account_by_id(account_id)?.is_none() {
+                    anyhow::bail!("Account not found: {}", account_id);
+                }
+                Some(Uuid::parse_str(account_id).context("Invalid account ID")?)
+            }
+            None if mappings.account.is_some() => None,
+            None => anyhow::bail!("An account is required when the file has no account column"),
         };
 
         // Find column indices
@@ -230,18 +418,107 @@ impl ImportService {
             .as_ref()
             .and_then(|b| headers.iter().position(|h| h == b.as_str()));
 
+        // Optional category and tags columns, imported as tags
+        let category_idx = mappings
+            .category
+            .as_ref()
+            .and_then(|c| headers.iter().position(|h| h == c.as_str()));
+        let tags_idx = mappings
+            .tags
+            .as_ref()
+            .and_then(|t| headers.iter().position(|h| h == t.as_str()));
+
+        // Optional check number and reference columns (kept for reconciliation, used in dedup)
+        let check_number_idx = mappings
+            .check_number
+            .as_ref()
+            .and_then(|c| headers.iter().position(|h| h == c.as_str()));
+        let reference_idx = mappings
+            .reference
+            .as_ref()
+            .and_then(|r| headers.iter().position(|h| h == r.as_str()));
+
+        // Optional currency column for files mixing currencies
+        let currency_idx = mappings
+            .currency
+            .as_ref()
+            .and_then(|c| headers.iter().position(|h| h == c.as_str()));
+
+        // Optional debit/credit indicator column (sign for unsigned amounts)
+        let direction_idx = mappings
+            .direction
+            .as_ref()
+            .and_then(|d| headers.iter().position(|h| h == d.as_str()));
+
+        // Optional account column for multi-account files
+        let account_idx = match &mappings.account {
+            Some(column) => Some(
+                headers
+                    .iter()
+                    .position(|h| h == column.as_str())
+                    .context(format!("Account column '{}' not found", column))?,
+            ),
+            None => None,
+        };
+        let mut account_lookup = if account_idx.is_some() {
+            self.build_account_lookup(options)?
+        } else {
+            HashMap::new()
+        };
+        // Accounts to create for account-column values nothing else matched
+        let mut new_accounts: Vec<Account> = Vec::new();
+        // Accounts whose source reports charges as positive
+        let sign_policies = self.repository.get_sign_policies()?;
+
         let mut transactions = Vec::new();
+        // File line of each transaction, for the preview's per-row statuses
+        let mut lines: Vec<u64> = Vec::new();
         let mut skipped = 0;
-        // Track end-of-day balances: for each date, store the last balance seen
-        let mut end_of_day_balances: HashMap<NaiveDate, Decimal> = HashMap::new();
+        // Track end-of-day balances: for each account and date, store the last balance seen
+        let mut end_of_day_balances: HashMap<(Uuid, NaiveDate), Decimal> = HashMap::new();
         // Track per-row balance for preview display
-        let mut preview_balances: Vec<Option<String>> = Vec::new();
+        let mut preview_balances: Vec<Option<Decimal>> = Vec::new();
+        // Rows rejected because a value could not be parsed
+        let mut errors: Vec<ImportRowError> = Vec::new();
+
+        // Without an explicit format, settle on one for the whole column
+        let detected_date_format = match options.date_format {
+            Some(_) => None,
+            None => detect_date_format(records.iter().map(|r| r.get(date_idx).unwrap_or(""))),
+        };
+        let date_format = options
+            .date_format
+            .as_deref()
+            .or(detected_date_format.as_ref().map(|d| d.format.as_str()));
+        let mut warnings: Vec<String> = detected_date_format
+            .iter()
+            .filter_map(DetectedDateFormat::warning)
+            .collect();
+
+        for (i, record) in records.iter().enumerate() {
+            if i % PROGRESS_EVERY_ROWS == 0 {
+                self.report(ImportStage::Parsing, i, records.len());
+            }
+            // 1-based line in the original file (header is line 1 after skipped rows)
+            let line = record.position().map(|p| p.line()).unwrap_or(0) + options.skip_rows as u64;
 
-        for record in &records {
             // Parse date
             let date_str = record.get(date_idx).unwrap_or("");
-            let date = parse_date(date_str);
+            let date = match date_format {
+                Some(format) => parse_date_with_format(date_str, format),
+                None => parse_date(date_str),
+            };
             if date.is_none() {
+                errors.push(ImportRowError {
+                    line,
+                    column: mappings.date.clone(),
+                    value: date_str.to_string(),
+                    reason: if date_str.trim().is_empty() {
+                        "empty date".to_string()
+                    } else {
+                        "invalid date format".to_string()
+                    },
+                });
                 skipped += 1;
                 continue;
             }
@@ -250,94 +527,208 @@ impl ImportService {
             // Parse amount from either amount column or debit/credit columns
             let amount = if let Some(amt_idx) = amount_idx {
                 let amount_str = record.get(amt_idx).unwrap_or("");
-                parse_amount_with_format(amount_str, options.number_format)
+                let format = options.number_format_for(&mappings.amount);
+                match parse_amount_checked(amount_str, format) {
+                    Ok(amount) => Some(amount),
+                    Err(reason) => {
+                        errors.push(ImportRowError {
+                            line,
+                            column: mappings.amount.clone(),
+                            value: amount_str.to_string(),
+                            reason,
+                        });
+                        skipped += 1;
+                        continue;
+                    }
+                }
             } else {
                 // Handle debit/credit columns
                 // Preserve sign from CSV, only negate if debit_negative option is set
-                let debit = debit_idx.and_then(|i| record.get(i)).and_then(|s| {
-                    if s.is_empty() {
-                        None
-                    } else {
-                        parse_amount_with_format(s, options.number_format)
-                    }
-                });
-                let credit = credit_idx.and_then(|i| record.get(i)).and_then(|s| {
-                    if s.is_empty() {
-                        None
-                    } else {
-                        parse_amount_with_format(s, options.number_format)
+                let debit = parse_optional_amount(
+                    record,
+                    debit_idx,
+                    mappings.debit.as_deref(),
+                    options,
+                    line,
+                );
+                let credit = parse_optional_amount(
+                    record,
+                    credit_idx,
+                    mappings.credit.as_deref(),
+                    options,
+                    line,
+                );
+                let (debit, credit) = match (debit, credit) {
+                    (Ok(d), Ok(c)) => (d, c),
+                    (Err(e), _) | (_, Err(e)) => {
+                        errors.push(e);
+                        skipped += 1;
+                        continue;
                     }
-                });
+                };
 
-                match (debit, credit) {
-                    (Some(d), None) => {
-                        // Debit: preserve sign from CSV by default
-                        // If debit_negative is true, negate positive values (for unsigned CSVs)
-                        let d = if options.debit_negative && d > Decimal::ZERO {
-                            -d
-                        } else {
-                            d
-                        };
-                        Some(d)
-                    }
-                    (None, Some(c)) => {
-                        // Credit: incoming money (preserve sign)
-                        Some(c)
-                    }
-                    (Some(d), Some(c)) => {
-                        // Both present: use the one with larger absolute value
-                        if d.abs() >= c.abs() {
-                            let d = if options.debit_negative && d > Decimal::ZERO {
-                                -d
-                            } else {
-                                d
-                            };
-                            Some(d)
-                        } else {
-                            Some(c)
-                        }
-                    }
-                    (None, None) => None,
-                }
+                combine_debit_credit(debit, credit, options.debit_negative)
             };
 
             if amount.is_none() {
+                errors.push(ImportRowError {
+                    line,
+                    column: [mappings.debit.as_deref(), mappings.credit.as_deref()]
+                        .iter()
+                        .flatten()
+                        .copied()
+                        .collect::<Vec<_>>()
+                        .join(" / "),
+                    value: String::new(),
+                    reason: "no amount in debit or credit column".to_string(),
+                });
                 skipped += 1;
                 continue;
             }
 
             let mut amount = amount.unwrap();
 
+            // Unsigned amounts take their sign from the debit/credit indicator
+            match direction_idx
+                .and_then(|i| record.get(i))
+                .and_then(parse_direction)
+            {
+                Some(true) => amount = -amount.abs(),
+                Some(false) => amount = amount.abs(),
+                None => {}
+            }
+
             // Apply flip_signs if requested (for credit card statements)
             if options.flip_signs {
                 amount = -amount;
             }
 
+            // Route the row to its account (blank account cells use the default account)
+            let row_account = match account_idx.and_then(|i| record.get(i)).map(str::trim) {
+                None | Some("") => match account_uuid {
+                    Some(id) => id,
+                    None => {
+                        errors.push(ImportRowError {
+                            line,
+                            column: mappings.account.clone().unwrap_or_default(),
+                            value: String::new(),
+                            reason: "empty account and no default account".to_string(),
+                        });
+                        skipped += 1;
+                        continue;
+                    }
+                },
+                Some(value) => match account_lookup.get(&value.to_lowercase()) {
+                    Some(id) => *id,
+                    None if options.create_missing_accounts => {
+                        let account = Account::new(Uuid::new_v4(), value);
+                        account_lookup.insert(value.to_lowercase(), account.id);
+                        let id = account.id;
+                        new_accounts.push(account);
+                        id
+                    }
+                    None => {
+                        errors.push(ImportRowError {
+                            line,
+                            column: mappings.account.clone().unwrap_or_default(),
+                            value: value.to_string(),
+                            reason: "no account assigned to this value".to_string(),
+                        });
+                        skipped += 1;
+                        continue;
+                    }
+                },
+            };
+
             // Get description
             let description = desc_idx.and_then(|i| record.get(i)).map(|s| s.to_string());
 
-            // Generate fingerprint for deduplication
-            let fingerprint =
-                generate_fingerprint(account_id, &date, &amount, description.as_deref());
+            // Check number / reference (blank cells are treated as absent)
+            let check_number = check_number_idx
+                .and_then(|i| record.get(i))
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(String::from);
+            let reference = reference_idx
+                .and_then(|i| record.get(i))
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(String::from);
+
+            // Currency of this row (blank means the account's currency)
+            let currency = match currency_idx.and_then(|i| record.get(i)).map(str::trim) {
+                None | Some("") => None,
+                Some(value) => match parse_currency_code(value) {
+                    Some(code) => Some(code),
+                    None => {
+                        errors.push(ImportRowError {
+                            line,
+                            column: mappings.currency.clone().unwrap_or_default(),
+                            value: value.to_string(),
+                            reason: "invalid currency code".to_string(),
+                        });
+                        skipped += 1;
+                        continue;
+                    }
+                },
+            };
+
+            // Generate fingerprint for deduplication (keep the caller's ID string for the
+            // default account so fingerprints match earlier single-account imports)
+            let fingerprint_account = match account_id {
+                Some(id) if Some(row_account) == account_uuid => id.to_string(),
+                _ => row_account.to_string(),
+            };
+            let fingerprint = generate_fingerprint(
+                &fingerprint_account,
+                &date,
+                &amount,
+                description.as_deref(),
+                check_number.as_deref().or(reference.as_deref()),
+                currency.as_deref(),
+            );
+
+            let mut tags: Vec<String> = Vec::new();
+            if let Some(category) = category_idx.and_then(|i| record.get(i)) {
+                push_mapped_tag(&mut tags, category, &options.category_map);
+            }
+            if let Some(values) = tags_idx.and_then(|i| record.get(i)) {
+                for value in values.split([',', ';', '|']) {
+                    push_mapped_tag(&mut tags, value, &options.category_map);
+                }
+            }
+
+            // The account's sign policy applies after fingerprinting, so changing
+            // the policy doesn't make rows already imported look new
+            let amount = sign_policies
+                .get(&row_account.to_string())
+                .map_or(amount, |policy| policy.apply(amount));
 
-            let mut tx = Transaction::new(Uuid::new_v4(), account_uuid, amount, date);
+            let mut tx = Transaction::new(Uuid::new_v4(), row_account, amount, date);
             tx.description = description;
+            tx.tags = tags;
+            tx.check_number = check_number;
+            tx.reference = reference;
+            tx.currency = currency;
             // Use dedicated csv_fingerprint column for deduplication
             tx.csv_fingerprint = Some(fingerprint.clone());
 
             transactions.push(tx);
+            lines.push(line);
 
             // Collect balance for end-of-day snapshot (if balance column is mapped)
             // We store the last balance seen for each date as we iterate through rows
             // Also capture raw balance for preview display
             let row_balance = if let Some(bal_idx) = balance_idx {
                 if let Some(balance_str) = record.get(bal_idx) {
-                    if let Some(balance) =
-                        parse_amount_with_format(balance_str, options.number_format)
-                    {
+                    let format = mappings
+                        .balance
+                        .as_deref()
+                        .map_or(options.number_format, |b| options.number_format_for(b));
+                    if let Some(balance) = parse_amount_with_format(balance_str, format) {
                         // Overwrite - we want the last balance for each date in CSV order
-                        end_of_day_balances.insert(date, balance);
-                        Some(balance.to_string())
+                        end_of_day_balances.insert((row_account, date), balance);
+                        Some(balance)
                     } else {
                         None
                     }
@@ -350,16 +741,68 @@ impl ImportService {
             preview_balances.push(row_balance);
         }
 
+        self.report(ImportStage::Parsing, records.len(), records.len());
+
+        // Check the running balances before they become snapshots
+        let balance_mismatches = if balance_idx.is_some() {
+            let rows: Vec<BalanceRow> = transactions
+                .iter()
+                .zip(&lines)
+                .zip(&preview_balances)
+                .map(|((tx, &line), &balance)| BalanceRow {
+                    line,
+                    account: tx.account_id.to_string(),
+                    amount: tx.amount,
+                    balance,
+                })
+                .collect();
+            reconcile_balances(&rows)
+        } else {
+            Vec::new()
+        };
+        warnings.extend(balance_mismatch_warning(&balance_mismatches));
+
         // Track discovered count (valid transactions before deduplication)
         let discovered = transactions.len() as i64;
         let fingerprints_checked = discovered;
 
-        // Generate batch ID for this import
-        let batch_id = format!("import_{}", chrono::Utc::now().format("%Y%m%d_%H%M%S"));
+        // Generate batch ID for this import (what `tl import undo` takes)
+        let batch_id = Uuid::new_v4().to_string();
+
+        // Same dedup for preview and import, so the preview shows what will happen
+        self.report(ImportStage::Deduplicating, 0, transactions.len());
+        let duplicates = self.find_duplicates(&transactions)?;
+        let duplicate_count = duplicates.iter().filter(|d| **d).count() as i64;
+        let status_of = |i: usize| {
+            if duplicates[i] {
+                RowStatus::Duplicate
+            } else {
+                RowStatus::New
+            }
+        };
 
-        // For preview mode, return all parsed transactions without deduplication
-        // User wants to see what's in the CSV, not what will be imported
+        // For preview mode, return all parsed transactions, each marked new or duplicate
         if preview_only {
+            // A single snapshot can't describe a multi-account file, so only compare
+            // against one when every row goes to the same account
+            let (latest_snapshot, account) = if account_idx.is_none() {
+                let snapshot = self
+                    .repository
+                    .get_balance_snapshots(account_id)?
+                    .into_iter()
+                    .next();
+                (snapshot, self.repository.get_account_by_id(account_id)?)
+            } else {
+                (None, None)
+            };
+            let mut balance_impact =
+                compute_balance_impact(&transactions, &preview_balances, latest_snapshot.as_ref());
+            if let Some(account) = &account {
+                balance_impact
+                    .warnings
+                    .extend(sign_warnings(&transactions, account, options));
+            }
+
             // If anchor balance is provided and no balance column exists, calculate balances
             let final_preview_balances = if options.anchor_balance.is_some()
                 && options.anchor_date.is_some()
@@ -425,6 +868,9 @@ impl ImportService {
                 tx_balances
             } else {
                 preview_balances
+                    .iter()
+                    .map(|b| b.map(|b| b.to_string()))
+                    .collect()
             };
 
             // Sort transactions by date for preview display so running balance flows logically
@@ -433,14 +879,49 @@ impl ImportService {
             sorted_indices.sort_by_key(|&i| transactions[i].transaction_date);
             sorted_indices.reverse(); // Newest first
 
+            // Account names for multi-account previews
+            let account_names: HashMap<Uuid, String> = if account_idx.is_some() {
+                self.repository
+                    .get_accounts()?
+                    .into_iter()
+                    .chain(new_accounts.iter().cloned())
+                    .map(|a| (a.id, a.name))
+                    .collect()
+            } else {
+                HashMap::new()
+            };
+
+            // Every data row in file order: rejected rows plus parsed ones
+            let mut rows: Vec<ImportRowStatus> = errors
+                .iter()
+                .map(|e| ImportRowStatus {
+                    line: e.line,
+                    status: RowStatus::Invalid,
+                    reason: Some(e.reason.clone()),
+                })
+                .chain(lines.iter().enumerate().map(|(i, &line)| ImportRowStatus {
+                    line,
+                    status: status_of(i),
+                    reason: None,
+                }))
+                .collect();
+            rows.sort_by_key(|r| r.line);
+
             return Ok(ImportResult {
                 batch_id,
                 discovered,
                 imported: 0, // Not importing in preview
-                skipped: skipped as i64,
-                fingerprints_checked: 0,      // Not checking in preview
+                skipped: skipped as i64 + duplicate_count,
+                duplicates: duplicate_count,
+                fingerprints_checked,
                 balance_snapshots_created: 0, // Not creating in preview
                 preview: true,
+                errors,
+                accounts_created: new_accounts.into_iter().map(|a| a.name).collect(),
+                date_format: detected_date_format,
+                warnings,
+                balance_impact: Some(balance_impact),
+                balance_mismatches,
                 transactions: Some(
                     sorted_indices
                         .iter()
@@ -451,62 +932,27 @@ impl ImportService {
                                 amount: t.amount.to_string(),
                                 description: t.description.clone(),
                                 balance: final_preview_balances.get(i).cloned().flatten(),
+                                account: account_names.get(&t.account_id).cloned(),
+                                tags: t.tags.clone(),
+                                check_number: t.check_number.clone(),
+                                reference: t.reference.clone(),
+                                currency: t.currency.clone(),
+                                status: status_of(i),
                             }
                         })
                         .collect(),
                 ),
+                rows: Some(rows),
             });
         }
 
-        // Deduplicate using count-based logic:
-        // If CSV has 3 identical rows (same fingerprint) and DB has 1, import 2 more.
-        let all_fingerprints: Vec<String> = transactions
-            .iter()
-            .filter_map(|tx| tx.csv_fingerprint.clone())
+        let mut new_transactions: Vec<Transaction> = transactions
+            .into_iter()
+            .zip(&duplicates)
+            .filter(|(_, duplicate)| !**duplicate)
+            .map(|(tx, _)| tx)
             .collect();
 
-        // Count how many times each fingerprint appears in this CSV batch
-        let mut csv_fingerprint_counts: HashMap<String, usize> = HashMap::new();
-        for fp in &all_fingerprints {
-            *csv_fingerprint_counts.entry(fp.clone()).or_insert(0) += 1;
-        }
-
-        // Get unique fingerprints for DB query
-        let unique_fingerprints: Vec<String> = csv_fingerprint_counts.keys().cloned().collect();
-
-        // Query DB for existing counts
-        let db_fingerprint_counts = self
-            .repository
-            .get_csv_fingerprint_counts(&unique_fingerprints)?;
-
-        // For each fingerprint, calculate how many we're allowed to import:
-        // allowed = max(0, csv_count - db_count)
-        let mut allowed_per_fp: HashMap<String, usize> = HashMap::new();
-        for (fp, csv_count) in &csv_fingerprint_counts {
-            let db_count = db_fingerprint_counts.get(fp).copied().unwrap_or(0);
-            let allowed = csv_count.saturating_sub(db_count);
-            allowed_per_fp.insert(fp.clone(), allowed);
-        }
-
-        // Track how many we've let through per fingerprint
-        let mut admitted_per_fp: HashMap<String, usize> = HashMap::new();
-
-        let mut new_transactions = Vec::new();
-        let mut duplicate_count = 0i64;
-
-        for tx in transactions {
-            if let Some(fp) = tx.csv_fingerprint.as_ref() {
-                let allowed = allowed_per_fp.get(fp).copied().unwrap_or(0);
-                let admitted = admitted_per_fp.entry(fp.clone()).or_insert(0);
-                if *admitted >= allowed {
-                    duplicate_count += 1;
-                    continue;
-                }
-                *admitted += 1;
-            }
-            new_transactions.push(tx);
-        }
-
         let imported = new_transactions.len() as i64;
 
         // Add batch_id to each transaction before inserting
@@ -515,11 +961,35 @@ impl ImportService {
             tx.csv_batch_id = Some(batch_id.clone());
         }
 
+        // Create accounts for unassigned account-column values before their transactions
+        for account in &new_accounts {
+            self.repository.upsert_account(account)?;
+        }
+
         // Collect IDs for auto-tagging
         let new_tx_ids: Vec<Uuid> = new_transactions.iter().map(|tx| tx.id).collect();
 
-        // Bulk insert all new transactions (single connection, single checkpoint)
-        self.repository.bulk_insert_transactions(&new_transactions)?;
+        // Insert in batches (one connection and checkpoint each). A failure
+        // part way leaves earlier batches in place, so the error names the
+        // batch ID for `tl import undo`.
+        self.report(ImportStage::Inserting, 0, new_transactions.len());
+        let mut inserted = 0;
+        for batch in new_transactions.chunks(INSERT_BATCH_ROWS) {
+            self.repository
+                .bulk_insert_transactions(batch)
+                .with_context(|| match inserted {
+                    0 => "Failed to save transactions".to_string(),
+                    _ => format!(
+                        "Import stopped after saving {} transactions; \
+                         remove them with `tl import undo {}`",
+                        inserted, batch_id
+                    ),
+                })?;
+            inserted += batch.len();
+            self.report(ImportStage::Inserting, inserted, new_transactions.len());
+        }
+
+        self.report(ImportStage::Finishing, 0, 1);
 
         // Apply auto-tag rules to newly imported transactions
         if !new_tx_ids.is_empty() {
@@ -530,11 +1000,19 @@ impl ImportService {
         // Create balance snapshots from collected end-of-day balances (single DB operation)
         let mut balance_snapshots_created = 0i64;
         if !end_of_day_balances.is_empty() {
-            // Get existing snapshots for deduplication (single query)
-            let existing_snapshots = self.repository.get_balance_snapshots(Some(account_id))?;
+            // Get existing snapshots for deduplication (one query per account)
+            let mut existing_snapshots: HashMap<Uuid, Vec<BalanceSnapshot>> = HashMap::new();
+            for (row_account, _) in end_of_day_balances.keys() {
+                if !existing_snapshots.contains_key(row_account) {
+                    let snapshots = self
+                        .repository
+                        .get_balance_snapshots(Some(&row_account.to_string()))?;
+                    existing_snapshots.insert(*row_account, snapshots);
+                }
+            }
 
             let mut snapshots_to_insert = Vec::new();
-            for (date, balance) in &end_of_day_balances {
+            for ((row_account, date), balance) in &end_of_day_balances {
                 // Create end-of-day timestamp (23:59:59.999999)
                 let snapshot_time = NaiveDateTime::new(
                     *date,
@@ -542,7 +1020,7 @@ impl ImportService {
                 );
 
                 // Check for duplicate: same account + date + balance (within 0.01)
-                let is_duplicate = existing_snapshots.iter().any(|s| {
+                let is_duplicate = existing_snapshots[row_account].iter().any(|s| {
                     s.snapshot_time.date() == *date
                         && (s.balance - *balance).abs() < Decimal::new(1, 2)
                 });
@@ -553,10 +1031,11 @@ impl ImportService {
 
                 snapshots_to_insert.push(BalanceSnapshot {
                     id: Uuid::new_v4(),
-                    account_id: account_uuid,
+                    account_id: *row_account,
                     balance: *balance,
                     snapshot_time,
                     source: Some("csv_import".to_string()),
+                    csv_batch_id: Some(batch_id.clone()),
                     created_at: Utc::now(),
                     updated_at: Utc::now(),
                 });
@@ -568,16 +1047,158 @@ impl ImportService {
                 .bulk_insert_balance_snapshots(&snapshots_to_insert)?;
             balance_snapshots_created = count as i64;
         }
+        self.report(ImportStage::Finishing, 1, 1);
 
         Ok(ImportResult {
             batch_id,
             discovered,
             imported,
             skipped: skipped + duplicate_count,
+            duplicates: duplicate_count,
             fingerprints_checked,
             balance_snapshots_created,
             preview: false,
+            errors,
+            accounts_created: new_accounts.into_iter().map(|a| a.name).collect(),
+            date_format: detected_date_format,
+            warnings,
+            balance_impact: None,
+            balance_mismatches,
             transactions: None,
+            rows: None,
+        })
+    }
+
+    /// Mark which transactions are already imported, by CSV fingerprint
+    ///
+    /// Count-based: if the file has 3 identical rows (same fingerprint) and the
+    /// database has 1, the first 2 are new and the last is a duplicate.
+    fn find_duplicates(&self, transactions: &[Transaction]) -> Result<Vec<bool>> {
+        // Count how many times each fingerprint appears in this CSV batch
+        let mut csv_fingerprint_counts: HashMap<String, usize> = HashMap::new();
+        for fp in transactions
+            .iter()
+            .filter_map(|tx| tx.csv_fingerprint.as_ref())
+        {
+            *csv_fingerprint_counts.entry(fp.clone()).or_insert(0) += 1;
+        }
+
+        let unique_fingerprints: Vec<String> = csv_fingerprint_counts.keys().cloned().collect();
+        let db_fingerprint_counts = self
+            .repository
+            .get_csv_fingerprint_counts(&unique_fingerprints)?;
+
+        // allowed = max(0, csv_count - db_count); rows past that are duplicates
+        let mut allowed_per_fp: HashMap<String, usize> = csv_fingerprint_counts
+            .into_iter()
+            .map(|(fp, csv_count)| {
+                let db_count = db_fingerprint_counts.get(&fp).copied().unwrap_or(0);
+                (fp, csv_count.saturating_sub(db_count))
+            })
+            .collect();
+
+        Ok(transactions
+            .iter()
+            .map(|tx| match tx.csv_fingerprint.as_ref() {
+                Some(fp) => match allowed_per_fp.get_mut(fp) {
+                    Some(allowed) if *allowed > 0 => {
+                        *allowed -= 1;
+                        false
+                    }
+                    _ => true,
+                },
+                None => false,
+            })
+            .collect())
+    }
+
+    /// Enrich Amazon card charges with the items from an Amazon order history export
+    ///
+    /// Each order's total is matched to a transaction whose description mentions
+    /// Amazon, for the same amount, charged within a week of the order date. Only
+    /// unambiguous matches are updated; the description becomes
+    /// "Amazon: <item titles>". Orders split across several charges are not matched.
+    pub fn match_amazon_orders(
+        &self,
+        file_path: &Path,
+        account_id: Option<&str>,
+        preview_only: bool,
+    ) -> Result<OrderMatchResult> {
+        let (headers, records) = read_csv_records(file_path, 0, false)?;
+        let orders = read_amazon_orders(&headers, &records)?;
+
+        let transactions = match account_id {
+            Some(id) => self.repository.get_transactions_by_account(id)?,
+            None => {
+                let mut all = Vec::new();
+                for account in self.repository.get_accounts()? {
+                    all.extend(
+                        self.repository
+                            .get_transactions_by_account(&account.id.to_string())?,
+                    );
+                }
+                all
+            }
+        };
+        let candidates: Vec<&Transaction> = transactions
+            .iter()
+            .filter(|tx| {
+                let desc = tx.description.as_deref().unwrap_or("");
+                let lower = desc.to_lowercase();
+                !desc.starts_with(AMAZON_PREFIX)
+                    && (lower.contains("amazon") || lower.contains("amzn"))
+            })
+            .collect();
+
+        let tolerance = Decimal::new(1, 2);
+        let mut used: std::collections::HashSet<Uuid> = std::collections::HashSet::new();
+        let mut matched = Vec::new();
+        let mut unmatched = 0;
+        let mut ambiguous = 0;
+        for order in &orders {
+            let hits: Vec<&&Transaction> = candidates
+                .iter()
+                .filter(|tx| !used.contains(&tx.id))
+                .filter(|tx| (tx.amount + order.total).abs() <= tolerance)
+                .filter(|tx| {
+                    let days = (tx.transaction_date - order.date).num_days();
+                    (0..=7).contains(&days)
+                })
+                .collect();
+            let tx = match hits.as_slice() {
+                [tx] => tx,
+                [] => {
+                    unmatched += 1;
+                    continue;
+                }
+                _ => {
+                    ambiguous += 1;
+                    continue;
+                }
+            };
+            used.insert(tx.id);
+
+            let description = amazon_description(&order.titles);
+            if !preview_only {
+                self.repository
+                    .update_transaction_description(&tx.id.to_string(), &description)?;
+            }
+            matched.push(OrderMatch {
+                order_id: order.id.clone(),
+                order_date: order.date.to_string(),
+                total: order.total,
+                transaction_id: tx.id.to_string(),
+                previous_description: tx.description.clone().unwrap_or_default(),
+                description,
+            });
+        }
+
+        Ok(OrderMatchResult {
+            preview: preview_only,
+            orders: orders.len(),
+            matched,
+            unmatched,
+            ambiguous,
         })
     }
 
@@ -588,12 +1209,11 @@ impl ImportService {
         mappings: &ColumnMappings,
         options: &ImportOptions,
     ) -> Result<()> {
-        let mut config = Config::load(&self.treeline_dir)?;
-        config.import_profiles.insert(
-            name.to_string(),
+        self.insert_profile(
+            name,
             ImportProfile {
                 column_mappings: mappings.clone(),
-                date_format: None,
+                date_format: options.date_format.clone(),
                 skip_rows: options.skip_rows as usize,
                 options: ConfigImportOptions {
                     flip_signs: options.flip_signs,
@@ -602,17 +1222,45 @@ impl ImportService {
                         NumberFormat::Us => None, // Default, omit from config
                         _ => Some(options.number_format.to_string()),
                     },
+                    column_number_formats: options
+                        .column_number_formats
+                        .iter()
+                        .map(|(column, format)| (column.clone(), format.to_string()))
+                        .collect(),
+                    account_map: options.account_map.clone(),
+                    create_missing_accounts: options.create_missing_accounts,
+                    category_map: options.category_map.clone(),
+                    no_header: options.no_header,
                 },
             },
-        );
-        config.save(&self.treeline_dir)?;
-        Ok(())
+        )
     }
 
-    /// Get a saved profile
-    pub fn get_profile(&self, name: &str) -> Result<Option<ImportProfile>> {
-        let config = Config::load(&self.treeline_dir)?;
-        Ok(config.import_profiles.get(name).cloned())
+    /// Save an import profile as given, replacing any with the same name
+    pub fn insert_profile(&self, name: &str, profile: ImportProfile) -> Result<()> {
+        let name = name.trim();
+        if name.is_empty() {
+            anyhow::bail!("Import profile name can't be empty");
+        }
+        let mut config = Config::load(&self.treeline_dir)?;
+        config.import_profiles.insert(name.to_string(), profile);
+        config.save(&self.treeline_dir)
+    }
+
+    /// Delete a saved profile, returning whether it existed
+    pub fn delete_profile(&self, name: &str) -> Result<bool> {
+        let mut config = Config::load(&self.treeline_dir)?;
+        if config.import_profiles.remove(name).is_none() {
+            return Ok(false);
+        }
+        config.save(&self.treeline_dir)?;
+        Ok(true)
+    }
+
+    /// Get a saved profile
+    pub fn get_profile(&self, name: &str) -> Result<Option<ImportProfile>> {
+        let config = Config::load(&self.treeline_dir)?;
+        Ok(config.import_profiles.get(name).cloned())
     }
 
     /// Resolve an account identifier (UUID or name) to an account UUID string.
@@ -697,6 +1345,41 @@ impl ImportService {
         );
     }
 
+    /// Map account-column values (lowercased) to account IDs.
+    ///
+    /// Existing accounts match by exact name or nickname; explicit `account_map`
+    /// assignments take precedence. Names shared by several accounts are left
+    /// out so those rows need an explicit assignment.
+    fn build_account_lookup(&self, options: &ImportOptions) -> Result<HashMap<String, Uuid>> {
+        let mut lookup: HashMap<String, Uuid> = HashMap::new();
+        let mut ambiguous: Vec<String> = Vec::new();
+        for account in self.repository.get_accounts()? {
+            let names = std::iter::once(account.name.clone()).chain(account.nickname.clone());
+            for name in names {
+                let key = name.trim().to_lowercase();
+                match lookup.get(&key) {
+                    Some(id) if *id != account.id => ambiguous.push(key),
+                    _ => {
+                        lookup.insert(key, account.id);
+                    }
+                }
+            }
+        }
+        for key in ambiguous {
+            lookup.remove(&key);
+        }
+
+        for (value, account) in &options.account_map {
+            let id = self
+                .resolve_account(account)
+                .with_context(|| format!("Invalid account assignment for '{}'", value))?;
+            let id = Uuid::parse_str(&id).context("Invalid account ID")?;
+            lookup.insert(value.trim().to_lowercase(), id);
+        }
+
+        Ok(lookup)
+    }
+
     /// Get a display name for an account UUID.
     ///
     /// Returns "Name (Nickname)" if a nickname exists, otherwise just "Name".
@@ -721,9 +1404,7 @@ impl ImportService {
     /// Returns best-guess mapping for date, amount, description, and optionally debit/credit columns.
     /// Matches Python CLI behavior with same pattern matching.
     pub fn detect_columns(&self, file_path: &Path) -> Result<DetectedColumns> {
-        let mut reader = csv::Reader::from_path(file_path).context("Failed to read CSV file")?;
-
-        let headers: Vec<String> = reader.headers()?.iter().map(|h| h.to_string()).collect();
+        let headers = sniff_csv(file_path, 0)?.headers;
 
         let date_patterns = [
             "date",
@@ -813,33 +1494,436 @@ impl ImportService {
     }
 }
 
+/// Date formats tried when none is given, in order of preference
+const DATE_FORMATS: &[&str] = &[
+    "%Y-%m-%d", "%m/%d/%Y", "%d/%m/%Y", "%m-%d-%Y", "%d-%m-%Y", "%Y/%m/%d", "%d.%m.%Y",
+];
+
 fn parse_date(s: &str) -> Option<NaiveDate> {
-    // Try common formats
-    let formats = [
-        "%Y-%m-%d", "%m/%d/%Y", "%d/%m/%Y", "%m-%d-%Y", "%d-%m-%Y", "%Y/%m/%d",
-    ];
+    DATE_FORMATS
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(s, fmt).ok())
+}
+
+/// Pick the date format for a whole column rather than row by row, so a file
+/// can't mix MM/DD and DD/MM readings
+///
+/// Returns the first of [`DATE_FORMATS`] that parses every non-empty value,
+/// along with any later format that does too but reads some value as a
+/// different date. None when the column is empty or no single format fits.
+pub fn detect_date_format<'a>(
+    values: impl IntoIterator<Item = &'a str>,
+) -> Option<DetectedDateFormat> {
+    let values: Vec<&str> = values
+        .into_iter()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .collect();
+    if values.is_empty() {
+        return None;
+    }
+
+    let parse_all = |fmt: &str| -> Option<Vec<NaiveDate>> {
+        values
+            .iter()
+            .map(|v| NaiveDate::parse_from_str(v, fmt).ok())
+            .collect()
+    };
+
+    let mut fitting = DATE_FORMATS
+        .iter()
+        .filter_map(|fmt| parse_all(fmt).map(|dates| (*fmt, dates)));
+    let (format, dates) = fitting.next()?;
+    let alternatives = fitting
+        .filter(|(_, other)| *other != dates)
+        .map(|(fmt, _)| fmt.to_string())
+        .collect();
+
+    Some(DetectedDateFormat {
+        format: format.to_string(),
+        alternatives,
+    })
+}
+
+/// A strftime date format spelled the way bank exports document it ("%d/%m/%Y" -> "DD/MM/YYYY")
+pub fn date_format_label(format: &str) -> String {
+    format
+        .replace("%Y", "YYYY")
+        .replace("%m", "MM")
+        .replace("%d", "DD")
+}
+
+/// Parse a date in one explicit format; a time part (e.g. "%Y-%m-%d %H:%M:%S") is dropped
+fn parse_date_with_format(s: &str, format: &str) -> Option<NaiveDate> {
+    let s = s.trim();
+    NaiveDate::parse_from_str(s, format).ok().or_else(|| {
+        NaiveDateTime::parse_from_str(s, format)
+            .ok()
+            .map(|dt| dt.date())
+    })
+}
+
+/// Currency symbols that may appear anywhere in an amount string
+const CURRENCY_SYMBOLS: &[char] = &[
+    '$', '€', '£', '¥', '₹', '₽', '₩', '₺', '₪', '₫', '฿', '₴', '₦', '¢',
+];
+
+/// Currency codes and abbreviations that may prefix or suffix an amount
+/// (compared case-insensitively, e.g. "EUR 100,50", "100.50 PLN", "US$12", "99 kr")
+const CURRENCY_WORDS: &[&str] = &[
+    "PLN", "EUR", "USD", "GBP", "CHF", "CZK", "SEK", "NOK", "DKK", "CAD", "AUD", "JPY", "CNY",
+    "INR", "BRL", "MXN", "KRW", "RUB", "NZD", "HKD", "SGD", "ZAR", "HUF", "RON", "TRY", "ILS",
+    "US", "CA", "AU", "NZ", "HK", "R", "S", "KR", "ZŁ", "KČ", "FT", "LEI",
+];
+
+/// Remove currency symbols and codes from an amount string.
+///
+/// Returns the remaining numeric text (whitespace removed) and the sign implied
+/// by a trailing "DR"/"CR" marker, if present (true = debit/negative).
+fn strip_currency(s: &str) -> std::result::Result<(String, Option<bool>), String> {
+    let mut numeric = String::new();
+    let mut debit_marker = None;
+
+    for token in s.split(|c: char| c.is_whitespace() || CURRENCY_SYMBOLS.contains(&c)) {
+        if token.is_empty() {
+            continue;
+        }
+
+        // Split letters off either end of the token ("EUR100", "100,50zł")
+        let lead_len: usize = token
+            .chars()
+            .take_while(|c| c.is_alphabetic())
+            .map(char::len_utf8)
+            .sum();
+        let (lead, rest) = token.split_at(lead_len);
+        let trail_len: usize = rest
+            .chars()
+            .rev()
+            .take_while(|c| c.is_alphabetic())
+            .map(char::len_utf8)
+            .sum();
+        let (number, trail) = rest.split_at(rest.len() - trail_len);
+
+        for word in [lead, trail] {
+            if word.is_empty() {
+                continue;
+            }
+            match word.to_uppercase().as_str() {
+                "DR" => debit_marker = Some(true),
+                "CR" => debit_marker = Some(false),
+                upper if CURRENCY_WORDS.contains(&upper) => {}
+                _ => return Err(format!("unrecognized text '{}'", word)),
+            }
+        }
+
+        numeric.push_str(number);
+    }
 
-    for fmt in &formats {
-        if let Ok(date) = NaiveDate::parse_from_str(s, fmt) {
-            return Some(date);
+    Ok((numeric, debit_marker))
+}
+
+/// Parse an optional debit/credit cell: missing or empty cells are None,
+/// unparseable cells are reported as row errors
+fn parse_optional_amount(
+    record: &csv::StringRecord,
+    idx: Option<usize>,
+    column: Option<&str>,
+    options: &ImportOptions,
+    line: u64,
+) -> std::result::Result<Option<Decimal>, ImportRowError> {
+    let (value, column) = match (idx.and_then(|i| record.get(i)), column) {
+        (Some(value), Some(column)) if !value.trim().is_empty() => (value, column),
+        _ => return Ok(None),
+    };
+    parse_amount_checked(value, options.number_format_for(column))
+        .map(Some)
+        .map_err(|reason| ImportRowError {
+            line,
+            column: column.to_string(),
+            value: value.to_string(),
+            reason,
+        })
+}
+
+/// The amount of a row with debit and credit columns: whichever one has a
+/// value, or the larger when both do. Signs are kept as in the file unless
+/// `debit_negative` asks for positive debits to be negated.
+fn combine_debit_credit(
+    debit: Option<Decimal>,
+    credit: Option<Decimal>,
+    debit_negative: bool,
+) -> Option<Decimal> {
+    let debit = debit.map(|d| {
+        if debit_negative && d > Decimal::ZERO {
+            -d
+        } else {
+            d
         }
+    });
+    match (debit, credit) {
+        (Some(d), Some(c)) => Some(if d.abs() >= c.abs() { d } else { c }),
+        (debit, credit) => debit.or(credit),
     }
-    None
 }
 
-/// Strip currency suffix from amount string (e.g., "100.50 PLN" -> "100.50")
-fn strip_currency_suffix(s: &str) -> &str {
-    const CURRENCIES: &[&str] = &[
-        "PLN", "EUR", "USD", "GBP", "CHF", "CZK", "SEK", "NOK", "DKK", "CAD", "AUD", "JPY", "CNY",
-        "INR", "BRL", "MXN", "KRW", "RUB",
+/// Amounts needed before their signs are judged
+const SIGN_CHECK_MIN_ROWS: usize = 10;
+/// Percentage of positive amounts taken to mean the signs are reversed
+const SIGN_CHECK_POSITIVE_PERCENT: usize = 95;
+
+/// Check every row the way [`ImportService::import`] would read it; see
+/// [`ImportService::validate`]
+fn validate_records(
+    headers: &[String],
+    records: &[csv::StringRecord],
+    mappings: &ColumnMappings,
+    options: &ImportOptions,
+) -> ImportValidation {
+    let find = |column: &str| headers.iter().position(|h| h == column);
+    let date_idx = find(&mappings.date);
+    let debit_idx = mappings.debit.as_deref().and_then(find);
+    let credit_idx = mappings.credit.as_deref().and_then(find);
+    let split_amounts = debit_idx.is_some() || credit_idx.is_some();
+    let amount_idx = find(&mappings.amount).filter(|_| !split_amounts);
+    let direction_idx = mappings.direction.as_deref().and_then(find);
+    let balance_idx = mappings.balance.as_deref().and_then(find);
+    let account_idx = mappings.account.as_deref().and_then(find);
+
+    // Without these an import fails outright; the rest are just left out
+    let mut required = vec![("Date", Some(mappings.date.as_str()))];
+    if !split_amounts {
+        required.push(("Amount", Some(mappings.amount.as_str())));
+    }
+    required.push(("Account", mappings.account.as_deref()));
+    let optional = [
+        ("Description", mappings.description.as_deref()),
+        ("Debit", mappings.debit.as_deref()),
+        ("Credit", mappings.credit.as_deref()),
+        ("Balance", mappings.balance.as_deref()),
+        ("Category", mappings.category.as_deref()),
+        ("Tags", mappings.tags.as_deref()),
+        ("Check number", mappings.check_number.as_deref()),
+        ("Reference", mappings.reference.as_deref()),
+        ("Direction", mappings.direction.as_deref()),
+        ("Currency", mappings.currency.as_deref()),
     ];
-    let s = s.trim();
-    for currency in CURRENCIES {
-        if s.ends_with(currency) {
-            return s[..s.len() - currency.len()].trim();
+    let missing = |columns: &[(&str, Option<&str>)]| -> Vec<ValidationIssue> {
+        columns
+            .iter()
+            .filter_map(|(role, column)| column.map(|c| (role, c)))
+            .filter(|(_, column)| find(column).is_none())
+            .map(|(role, column)| ValidationIssue {
+                kind: ValidationIssueKind::MissingColumn,
+                line: None,
+                column: Some(column.to_string()),
+                value: None,
+                message: format!("{} column '{}' not found", role, column),
+            })
+            .collect()
+    };
+    let mut issues = missing(&required);
+    let missing_required = !issues.is_empty();
+    issues.extend(missing(&optional));
+
+    // Rows with the wrong number of fields are dropped before an import sees them
+    let well_formed =
+        |record: &csv::StringRecord| options.no_header || record.len() == headers.len();
+    let detected_date_format = match (&options.date_format, date_idx) {
+        (None, Some(i)) => detect_date_format(
+            records
+                .iter()
+                .filter(|r| well_formed(r))
+                .map(|r| r.get(i).unwrap_or("")),
+        ),
+        _ => None,
+    };
+    let date_format = options
+        .date_format
+        .as_deref()
+        .or(detected_date_format.as_ref().map(|d| d.format.as_str()));
+    if let Some(warning) = detected_date_format
+        .as_ref()
+        .and_then(DetectedDateFormat::warning)
+    {
+        issues.push(ValidationIssue {
+            kind: ValidationIssueKind::AmbiguousDates,
+            line: None,
+            column: Some(mappings.date.clone()),
+            value: None,
+            message: warning,
+        });
+    }
+
+    let row_issue = |kind: ValidationIssueKind, error: ImportRowError| ValidationIssue {
+        kind,
+        line: Some(error.line),
+        column: Some(error.column),
+        value: Some(error.value),
+        message: error.reason,
+    };
+    let mut valid_rows = 0;
+    let (mut signed, mut positive) = (0, 0);
+    let mut balance_rows = Vec::new();
+    for record in records {
+        let line = record.position().map(|p| p.line()).unwrap_or(0) + options.skip_rows as u64;
+        if !well_formed(record) {
+            issues.push(ValidationIssue {
+                kind: ValidationIssueKind::ColumnCount,
+                line: Some(line),
+                column: None,
+                value: None,
+                message: format!("expected {} fields, found {}", headers.len(), record.len()),
+            });
+            continue;
+        }
+        let Some(date_idx) = date_idx.filter(|_| !missing_required) else {
+            continue;
+        };
+
+        let date_str = record.get(date_idx).unwrap_or("");
+        let date = match date_format {
+            Some(format) => parse_date_with_format(date_str, format),
+            None => parse_date(date_str),
+        };
+        if date.is_none() {
+            issues.push(row_issue(
+                ValidationIssueKind::InvalidDate,
+                ImportRowError {
+                    line,
+                    column: mappings.date.clone(),
+                    value: date_str.to_string(),
+                    reason: if date_str.trim().is_empty() {
+                        "empty date".to_string()
+                    } else {
+                        "invalid date format".to_string()
+                    },
+                },
+            ));
+        }
+
+        let amount = match amount_idx {
+            Some(i) => {
+                let amount_str = record.get(i).unwrap_or("");
+                parse_amount_checked(amount_str, options.number_format_for(&mappings.amount))
+                    .map_err(|reason| ImportRowError {
+                        line,
+                        column: mappings.amount.clone(),
+                        value: amount_str.to_string(),
+                        reason,
+                    })
+            }
+            None => {
+                let debit = parse_optional_amount(
+                    record,
+                    debit_idx,
+                    mappings.debit.as_deref(),
+                    options,
+                    line,
+                );
+                let credit = parse_optional_amount(
+                    record,
+                    credit_idx,
+                    mappings.credit.as_deref(),
+                    options,
+                    line,
+                );
+                match (debit, credit) {
+                    (Ok(d), Ok(c)) => combine_debit_credit(d, c, options.debit_negative)
+                        .ok_or_else(|| ImportRowError {
+                            line,
+                            column: [mappings.debit.as_deref(), mappings.credit.as_deref()]
+                                .iter()
+                                .flatten()
+                                .copied()
+                                .collect::<Vec<_>>()
+                                .join(" / "),
+                            value: String::new(),
+                            reason: "no amount in debit or credit column".to_string(),
+                        }),
+                    (Err(e), _) | (_, Err(e)) => Err(e),
+                }
+            }
+        };
+        let amount = match amount {
+            Ok(amount) => amount,
+            Err(error) => {
+                issues.push(row_issue(ValidationIssueKind::InvalidAmount, error));
+                continue;
+            }
+        };
+        if date.is_none() {
+            continue;
+        }
+
+        // Signs as they would be stored
+        let mut amount = match direction_idx
+            .and_then(|i| record.get(i))
+            .and_then(parse_direction)
+        {
+            Some(true) => -amount.abs(),
+            Some(false) => amount.abs(),
+            None => amount,
+        };
+        if options.flip_signs {
+            amount = -amount;
         }
+        valid_rows += 1;
+        if let Some(i) = balance_idx {
+            let format = options.number_format_for(mappings.balance.as_deref().unwrap_or_default());
+            balance_rows.push(BalanceRow {
+                line,
+                account: account_idx
+                    .and_then(|i| record.get(i))
+                    .map(|a| a.trim().to_lowercase())
+                    .unwrap_or_default(),
+                amount,
+                balance: record
+                    .get(i)
+                    .and_then(|b| parse_amount_with_format(b, format)),
+            });
+        }
+        if !amount.is_zero() {
+            signed += 1;
+            if amount > Decimal::ZERO {
+                positive += 1;
+            }
+        }
+    }
+
+    // Spending stored as positive would count as income everywhere
+    if signed >= SIGN_CHECK_MIN_ROWS && positive * 100 >= signed * SIGN_CHECK_POSITIVE_PERCENT {
+        issues.push(ValidationIssue {
+            kind: ValidationIssueKind::SignDistribution,
+            line: None,
+            column: None,
+            value: None,
+            message: format!(
+                "{} of {} amounts would import as money coming in; if this is a credit card \
+                 or charges are positive in the file, flip signs",
+                positive, signed
+            ),
+        });
+    }
+
+    issues.extend(
+        reconcile_balances(&balance_rows)
+            .into_iter()
+            .map(|mismatch| ValidationIssue {
+                kind: ValidationIssueKind::BalanceMismatch,
+                line: Some(mismatch.line),
+                column: mappings.balance.clone(),
+                value: Some(mismatch.amount.clone()),
+                message: mismatch.message(),
+            }),
+    );
+
+    ImportValidation {
+        rows: records.len(),
+        valid_rows,
+        importable: !missing_required && valid_rows > 0,
+        date_format: detected_date_format,
+        issues,
     }
-    s
 }
 
 #[allow(dead_code)]
@@ -848,70 +1932,135 @@ fn parse_amount(s: &str) -> Option<Decimal> {
 }
 
 fn parse_amount_with_format(s: &str, format: NumberFormat) -> Option<Decimal> {
+    parse_amount_checked(s, format).ok()
+}
+
+/// Parse an amount string, returning the reason it was rejected on failure.
+///
+/// Handles currency symbols/codes (prefix or suffix), parentheses negatives
+/// "(123.45)", leading or trailing signs "-123.45" / "123.45-", Unicode minus,
+/// and "DR"/"CR" markers. Thousands/decimal separators follow `format`.
+pub fn parse_amount_checked(s: &str, format: NumberFormat) -> std::result::Result<Decimal, String> {
     let s = s.trim();
     if s.is_empty() {
-        return None;
+        return Err("empty amount".to_string());
     }
 
-    // Strip currency suffix first
-    let s = strip_currency_suffix(s);
+    let s = s.replace('\u{2212}', "-");
+    let (body, debit_marker) = strip_currency(&s)?;
+    let mut body = body.as_str();
 
-    // Handle parentheses notation for negative numbers: (100.00) -> -100.00
-    let (is_negative, s) = if s.starts_with('(') && s.ends_with(')') {
-        (true, &s[1..s.len() - 1])
-    } else {
-        (false, s)
-    };
+    // Parentheses notation for negative numbers: (100.00) -> -100.00
+    let mut is_negative = false;
+    if let Some(inner) = body.strip_prefix('(').and_then(|b| b.strip_suffix(')')) {
+        is_negative = true;
+        body = inner;
+    }
+
+    // Trailing sign (common in German and mainframe exports): 100.00- -> -100.00
+    if let Some(rest) = body.strip_suffix('-') {
+        is_negative = true;
+        body = rest;
+    } else if let Some(rest) = body.strip_suffix('+') {
+        body = rest;
+    }
 
-    // Normalize based on format
+    // Leading sign
+    if let Some(rest) = body.strip_prefix('-') {
+        is_negative = true;
+        body = rest;
+    } else if let Some(rest) = body.strip_prefix('+') {
+        body = rest;
+    }
+
+    // Normalize separators based on format (whitespace was already removed)
     let normalized = match format {
-        NumberFormat::Us => {
-            // US: 1,234.56 - remove commas, keep dots
-            s.replace(',', "")
-        }
-        NumberFormat::Eu => {
-            // EU: 1.234,56 - remove dots (thousands), convert comma to dot (decimal)
-            s.replace('.', "").replace(',', ".")
-        }
-        NumberFormat::EuSpace => {
-            // EU with space: 1 234,56 - remove spaces, convert comma to dot
-            s.replace(' ', "").replace(',', ".")
-        }
+        // US: 1,234.56 - remove commas, keep dots
+        NumberFormat::Us => body.replace(',', ""),
+        // EU: 1.234,56 - remove dots (thousands), convert comma to dot (decimal)
+        NumberFormat::Eu => body.replace('.', "").replace(',', "."),
+        // EU with space: 1 234,56 - convert comma to dot
+        NumberFormat::EuSpace => body.replace(',', "."),
     };
 
-    // Keep only digits, dot, minus
-    let cleaned: String = normalized
-        .chars()
-        .filter(|c| c.is_ascii_digit() || *c == '.' || *c == '-')
-        .collect();
-
-    if cleaned.is_empty() {
-        return None;
+    if normalized.is_empty() {
+        return Err("no digits in amount".to_string());
     }
+    if !normalized.chars().all(|c| c.is_ascii_digit() || c == '.')
+        || normalized.matches('.').count() > 1
+    {
+        return Err(format!("invalid number for {} format", format));
+    }
+
+    let amount: Decimal = normalized
+        .parse()
+        .map_err(|_| format!("invalid number for {} format", format))?;
 
-    let mut amount: Decimal = cleaned.parse().ok()?;
+    let negative = is_negative || debit_marker == Some(true);
+    Ok(if negative { -amount.abs() } else { amount })
+}
 
-    // Apply parentheses negation
-    if is_negative && amount > Decimal::ZERO {
-        amount = -amount;
+/// Translate a bank category (or tag) through the category map and add it once.
+///
+/// Map keys match case-insensitively; mapping a category to an empty string drops it.
+/// Tags differing only in case count as one, keeping the first spelling seen.
+fn push_mapped_tag(tags: &mut Vec<String>, raw: &str, category_map: &HashMap<String, String>) {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return;
+    }
+    let tag = category_map
+        .get(raw)
+        .or_else(|| {
+            category_map
+                .iter()
+                .find(|(k, _)| k.trim().eq_ignore_ascii_case(raw))
+                .map(|(_, v)| v)
+        })
+        .map(|v| v.trim())
+        .unwrap_or(raw);
+    let folded = tag.to_lowercase();
+    if !tag.is_empty() && !tags.iter().any(|t| t.to_lowercase() == folded) {
+        tags.push(tag.to_string());
     }
+}
 
-    Some(amount)
+/// Read a debit/credit indicator cell: `Some(true)` for debits, `Some(false)` for credits.
+fn parse_direction(s: &str) -> Option<bool> {
+    match s.trim().to_lowercase().as_str() {
+        "debit" | "dr" | "d" | "withdrawal" | "outflow" => Some(true),
+        "credit" | "cr" | "c" | "deposit" | "inflow" => Some(false),
+        _ => None,
+    }
 }
 
 /// Generate a fingerprint for transaction deduplication
-/// Based on account_id, date, amount, and normalized description
+/// Based on account_id, date, amount, and normalized description, plus the
+/// check number or reference when the file has one (so two same-day checks for
+/// the same amount stay distinct) and the row's currency in multi-currency
+/// files. Rows without either hash exactly as before.
 fn generate_fingerprint(
     account_id: &str,
     date: &NaiveDate,
     amount: &Decimal,
     description: Option<&str>,
+    identifier: Option<&str>,
+    currency: Option<&str>,
 ) -> String {
     let normalized_desc = description
         .map(|d| normalize_description(d))
         .unwrap_or_default();
 
-    let fingerprint_input = format!("{}|{}|{:.2}|{}", account_id, date, amount, normalized_desc);
+    let mut fingerprint_input =
+        format!("{}|{}|{:.2}|{}", account_id, date, amount, normalized_desc);
+    if let Some(identifier) = identifier {
+        fingerprint_input.push('|');
+        fingerprint_input.push_str(identifier);
+    }
+    if let Some(currency) = currency {
+        fingerprint_input.push_str("|currency:");
+        fingerprint_input.push_str(currency);
+    }
 
     let mut hasher = Sha256::new();
     hasher.update(fingerprint_input.as_bytes());
@@ -921,6 +2070,12 @@ fn generate_fingerprint(
     result[..8].iter().map(|b| format!("{:02x}", b)).collect()
 }
 
+/// A three-letter ISO 4217 code, uppercased ("eur" -> "EUR")
+fn parse_currency_code(s: &str) -> Option<String> {
+    let s = s.trim();
+    (s.len() == 3 && s.chars().all(|c| c.is_ascii_alphabetic())).then(|| s.to_ascii_uppercase())
+}
+
 /// Normalize description for fingerprinting
 /// Matches Python implementation exactly:
 /// - Lowercase
@@ -928,7 +2083,7 @@ fn generate_fingerprint(
 /// - Remove card number masks (10+ X's followed by 4 digits)
 /// - Normalize account/phone numbers to last 4 digits
 /// - Remove whitespace and special characters
-fn normalize_description(desc: &str) -> String {
+pub(crate) fn normalize_description(desc: &str) -> String {
     let desc = desc.to_lowercase();
 
     // Remove literal "null" strings (common in CSV exports)
@@ -963,147 +2118,1674 @@ fn normalize_description(desc: &str) -> String {
     special_re.replace_all(&normalized, "").to_string()
 }
 
-/// Result of column auto-detection
-#[derive(Debug, Default, Serialize)]
-pub struct DetectedColumns {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub date: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub amount: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub description: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub debit: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub credit: Option<String>,
+// ============================================================================
+// CSV sniffing (encoding, delimiter, quoting)
+// ============================================================================
+
+/// Maximum number of records examined when sniffing a CSV dialect
+const SNIFF_ROWS: usize = 50;
+
+/// How much of a CSV file is read for sniffing; plenty for SNIFF_ROWS
+const SNIFF_BYTES: u64 = 64 * 1024;
+
+/// Delimiters considered during sniffing, in tie-break order (US default first)
+const DELIMITER_CANDIDATES: &[u8] = b",;\t|";
+
+/// Windows-1252 code points for bytes 0x80-0x9F (the rest map 1:1 to Latin-1)
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+/// Text encoding detected in a CSV file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(rename_all = "snake_case")]
+pub enum CsvEncoding {
+    Utf8,
+    /// UTF-8 with a leading byte order mark (common in Excel exports)
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    /// Fallback for files that are not valid UTF-8 (older Windows bank exports)
+    Windows1252,
 }
 
-#[derive(Debug, Serialize)]
-pub struct ImportResult {
-    /// Unique batch ID for this import
-    pub batch_id: String,
-    /// Total transactions discovered in CSV
-    pub discovered: i64,
-    /// Successfully imported transactions
-    pub imported: i64,
-    /// Skipped transactions (invalid or duplicate)
-    pub skipped: i64,
-    /// Number of fingerprints checked for deduplication
-    pub fingerprints_checked: i64,
-    /// Number of balance snapshots created from running balance column
-    pub balance_snapshots_created: i64,
-    /// Whether this was a preview (no changes applied)
-    pub preview: bool,
-    /// Transaction previews (only in preview mode)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub transactions: Option<Vec<TransactionPreview>>,
+/// How fields are quoted in a CSV file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteStyle {
+    /// No field is quoted
+    None,
+    /// Only some fields are quoted (typically those containing delimiters)
+    Minimal,
+    /// Every field is quoted
+    All,
 }
 
-#[derive(Debug, Serialize)]
-pub struct TransactionPreview {
-    pub date: String,
-    pub amount: String,
-    pub description: Option<String>,
-    /// Running balance (from CSV, if mapped)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub balance: Option<String>,
+/// Headers and dialect detected by sniffing the start of a CSV file
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct CsvHeaders {
+    pub headers: Vec<String>,
+    /// Detected delimiter as a single-character string
+    pub delimiter: String,
+    pub encoding: CsvEncoding,
+    pub quote_style: QuoteStyle,
 }
 
-/// Format a list of accounts for display in error messages.
-fn format_account_list(accounts: &[&Account]) -> String {
-    accounts
-        .iter()
-        .map(|a| {
-            if let Some(nick) = &a.nickname {
-                format!("  {} ({}) — {}", a.name, nick, a.id)
-            } else {
-                format!("  {} — {}", a.name, a.id)
-            }
-        })
-        .collect::<Vec<_>>()
-        .join("\n")
+/// Sniff the header row, delimiter, encoding, and quoting style of a CSV file
+///
+/// The delimiter is chosen by parsing the first rows with each candidate and
+/// scoring how consistently they split, so delimiters inside quoted fields
+/// don't skew detection. `skip_rows` skips leading non-header lines
+/// (e.g., bank letterhead).
+pub fn sniff_csv(file_path: &Path, skip_rows: u32) -> Result<CsvHeaders> {
+    let mut bytes = Vec::new();
+    std::fs::File::open(file_path)
+        .and_then(|file| file.take(SNIFF_BYTES).read_to_end(&mut bytes))
+        .context("Failed to open CSV file")?;
+    if bytes.len() as u64 == SNIFF_BYTES {
+        bytes.truncate(complete_lines_len(&bytes));
+    }
+    sniff_csv_bytes(&bytes, skip_rows)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Length of `bytes` up to the end of its last complete line
+///
+/// Keeps a cut-off prefix from ending mid-character (which would make UTF-8
+/// look like Windows-1252) or mid-record. A prefix without any line break is
+/// kept whole.
+fn complete_lines_len(bytes: &[u8]) -> usize {
+    // UTF-16 line feeds are a whole code unit at an even offset
+    let utf16_newline: Option<[u8; 2]> = match bytes {
+        [0xFF, 0xFE, ..] => Some([0x0A, 0x00]),
+        [0xFE, 0xFF, ..] => Some([0x00, 0x0A]),
+        _ => None,
+    };
+    let end = match utf16_newline {
+        Some(newline) => (2..bytes.len().saturating_sub(1))
+            .step_by(2)
+            .rev()
+            .find(|&i| bytes[i..i + 2] == newline)
+            .map(|i| i + 2),
+        None => bytes.iter().rposition(|&b| b == b'\n').map(|i| i + 1),
+    };
+    end.unwrap_or(bytes.len())
+}
 
-    // ==========================================================================
-    // parse_amount tests - US format (current behavior)
-    // ==========================================================================
+/// Sniff the contents of a CSV file; see [`sniff_csv`]
+pub fn sniff_csv_bytes(bytes: &[u8], skip_rows: u32) -> Result<CsvHeaders> {
+    let (text, encoding) = decode_csv_bytes(bytes);
+    let content = skip_lines(&text, skip_rows);
 
-    #[test]
-    fn test_parse_amount_simple_positive() {
-        assert_eq!(parse_amount("100.50"), Some(Decimal::new(10050, 2)));
+    if content.trim().is_empty() {
+        anyhow::bail!("CSV file is empty or skip_rows too high");
     }
 
-    #[test]
-    fn test_parse_amount_simple_negative() {
-        assert_eq!(parse_amount("-50.25"), Some(Decimal::new(-5025, 2)));
-    }
+    let delimiter = detect_delimiter(content);
+    let headers = parse_header_line(content, delimiter)?;
 
-    #[test]
-    fn test_parse_amount_with_comma_thousands() {
-        // US format: 1,234.56
-        assert_eq!(parse_amount("1,234.56"), Some(Decimal::new(123456, 2)));
-    }
+    Ok(CsvHeaders {
+        headers,
+        delimiter: (delimiter as char).to_string(),
+        encoding,
+        quote_style: detect_quote_style(content, delimiter),
+    })
+}
 
-    #[test]
-    fn test_parse_amount_with_currency_symbol() {
-        assert_eq!(parse_amount("$100.00"), Some(Decimal::new(10000, 2)));
-        assert_eq!(parse_amount("-$50.00"), Some(Decimal::new(-5000, 2)));
-    }
+/// Read headers and data records from a CSV file using the sniffed dialect
+///
+/// With `no_header` every line is data and the headers are
+/// [`headerless_column_names`].
+fn read_csv_records(
+    file_path: &Path,
+    skip_rows: u32,
+    no_header: bool,
+) -> Result<(Vec<String>, Vec<csv::StringRecord>)> {
+    let bytes = std::fs::read(file_path).context("Failed to read CSV file")?;
+    parse_csv_records(&bytes, skip_rows, no_header)
+}
 
-    #[test]
-    fn test_parse_amount_parentheses_negative() {
-        // Accounting notation: (100.00) means -100.00
-        assert_eq!(parse_amount("(100.00)"), Some(Decimal::new(-10000, 2)));
-        assert_eq!(parse_amount("(1,234.56)"), Some(Decimal::new(-123456, 2)));
+/// Read headers and data records from CSV contents; see [`read_csv_records`]
+fn parse_csv_records(
+    bytes: &[u8],
+    skip_rows: u32,
+    no_header: bool,
+) -> Result<(Vec<String>, Vec<csv::StringRecord>)> {
+    let (headers, mut records) = parse_csv_rows(bytes, skip_rows, no_header)?;
+    // Rows that don't line up with the header can't be mapped
+    if !no_header {
+        records.retain(|r| r.len() == headers.len());
     }
+    Ok((headers, records))
+}
 
-    #[test]
-    fn test_parse_amount_with_whitespace() {
-        assert_eq!(parse_amount("  100.50  "), Some(Decimal::new(10050, 2)));
+/// Like [`parse_csv_records`], but keeping rows with more or fewer fields
+/// than the header
+fn parse_csv_rows(
+    bytes: &[u8],
+    skip_rows: u32,
+    no_header: bool,
+) -> Result<(Vec<String>, Vec<csv::StringRecord>)> {
+    let (text, _) = decode_csv_bytes(bytes);
+    let content = skip_lines(&text, skip_rows);
+
+    if skip_rows > 0 && content.trim().is_empty() {
+        anyhow::bail!("No header row found after skipping {} rows", skip_rows);
     }
 
-    #[test]
-    fn test_parse_amount_empty_string() {
-        assert_eq!(parse_amount(""), None);
+    let delimiter = detect_delimiter(content);
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(!no_header)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(content.as_bytes());
+
+    if no_header {
+        let records: Vec<csv::StringRecord> = reader.records().filter_map(|r| r.ok()).collect();
+        let columns = records.iter().map(|r| r.len()).max().unwrap_or(0);
+        return Ok((headerless_column_names(columns), records));
     }
 
-    #[test]
-    fn test_parse_amount_invalid() {
-        assert_eq!(parse_amount("abc"), None);
-    }
+    let headers = clean_headers(reader.headers().context("Failed to parse header line")?);
+    let records: Vec<csv::StringRecord> = reader.records().filter_map(|r| r.ok()).collect();
 
-    // ==========================================================================
-    // parse_date tests
-    // ==========================================================================
+    Ok((headers, records))
+}
 
-    #[test]
-    fn test_parse_date_iso_format() {
-        // YYYY-MM-DD (ISO)
-        assert_eq!(
-            parse_date("2024-01-15"),
-            Some(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap())
-        );
+/// Column names for a file without a header row: `Column 1`..`Column N`
+pub fn headerless_column_names(count: usize) -> Vec<String> {
+    (1..=count).map(|n| format!("Column {}", n)).collect()
+}
+
+/// Mappings with 0-based column indexes (e.g. "3") turned into the names
+/// [`headerless_column_names`] gives, for files without a header row
+fn resolve_mappings(mappings: &ColumnMappings, options: &ImportOptions) -> ColumnMappings {
+    if !options.no_header {
+        return mappings.clone();
     }
+    let resolve = |column: &String| match column.trim().parse::<usize>() {
+        Ok(index) => format!("Column {}", index + 1),
+        Err(_) => column.clone(),
+    };
+    let resolve_opt = |column: &Option<String>| column.as_ref().map(resolve);
+    ColumnMappings {
+        date: resolve(&mappings.date),
+        amount: resolve(&mappings.amount),
+        description: resolve_opt(&mappings.description),
+        credit: resolve_opt(&mappings.credit),
+        debit: resolve_opt(&mappings.debit),
+        balance: resolve_opt(&mappings.balance),
+        account: resolve_opt(&mappings.account),
+        category: resolve_opt(&mappings.category),
+        tags: resolve_opt(&mappings.tags),
+        check_number: resolve_opt(&mappings.check_number),
+        reference: resolve_opt(&mappings.reference),
+        direction: resolve_opt(&mappings.direction),
+        currency: resolve_opt(&mappings.currency),
+    }
+}
 
-    #[test]
-    fn test_parse_date_us_format() {
-        // MM/DD/YYYY (US)
-        assert_eq!(
-            parse_date("12/03/2025"),
-            Some(NaiveDate::from_ymd_opt(2025, 12, 3).unwrap())
+/// Decode raw file bytes, honoring a byte order mark if present
+pub(crate) fn decode_csv_bytes(bytes: &[u8]) -> (String, CsvEncoding) {
+    if let Some(rest) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return (
+            String::from_utf8_lossy(rest).into_owned(),
+            CsvEncoding::Utf8Bom,
         );
     }
-
-    #[test]
-    fn test_parse_date_invalid() {
+    if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        let units: Vec<u16> = rest
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        return (String::from_utf16_lossy(&units), CsvEncoding::Utf16Le);
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        let units: Vec<u16> = rest
+            .chunks_exact(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .collect();
+        return (String::from_utf16_lossy(&units), CsvEncoding::Utf16Be);
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(s) => (s.to_string(), CsvEncoding::Utf8),
+        Err(_) => {
+            let decoded = bytes
+                .iter()
+                .map(|&b| match b {
+                    0x80..=0x9F => WINDOWS_1252_HIGH[(b - 0x80) as usize],
+                    _ => b as char,
+                })
+                .collect();
+            (decoded, CsvEncoding::Windows1252)
+        }
+    }
+}
+
+/// Skip the first `n` lines of text, returning the remainder
+pub(crate) fn skip_lines(text: &str, n: u32) -> &str {
+    let mut rest = text;
+    for _ in 0..n {
+        match rest.find('\n') {
+            Some(pos) => rest = &rest[pos + 1..],
+            None => return "",
+        }
+    }
+    rest
+}
+
+/// Detect the most likely delimiter from a sample of CSV text.
+///
+/// Each candidate is scored by parsing up to SNIFF_ROWS records (respecting
+/// quotes) and measuring how many rows share the most common field count.
+/// Rows with stray quote characters count against a candidate, since they
+/// indicate the quoting was split in the wrong place. Ties go to the
+/// candidate producing more columns, then to candidate order (comma first).
+pub fn detect_delimiter(sample: &str) -> u8 {
+    let mut best = DELIMITER_CANDIDATES[0];
+    // (multi-column, consistent rows, total rows, modal field count)
+    let mut best_score = (false, 0usize, 1usize, 0usize);
+
+    for &delimiter in DELIMITER_CANDIDATES {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .delimiter(delimiter)
+            .from_reader(sample.as_bytes());
+
+        let mut rows: Vec<(usize, bool)> = Vec::new();
+        for record in reader.records().take(SNIFF_ROWS) {
+            match record {
+                Ok(r) => rows.push((r.len(), !r.iter().any(|f| f.contains('"')))),
+                Err(_) => rows.push((0, false)),
+            }
+        }
+        if rows.is_empty() {
+            continue;
+        }
+
+        let mut frequency: HashMap<usize, usize> = HashMap::new();
+        for (len, _) in &rows {
+            *frequency.entry(*len).or_insert(0) += 1;
+        }
+        let modal = frequency
+            .iter()
+            .max_by_key(|(len, count)| (**count, **len))
+            .map(|(len, _)| *len)
+            .unwrap_or(0);
+        let consistent = rows
+            .iter()
+            .filter(|(len, clean)| *len == modal && *clean)
+            .count();
+
+        let score = (modal > 1, consistent, rows.len(), modal);
+        if is_better_score(score, best_score) {
+            best = delimiter;
+            best_score = score;
+        }
+    }
+
+    best
+}
+
+/// Compare sniffing scores: multi-column first, then consistency ratio, then column count
+fn is_better_score(a: (bool, usize, usize, usize), b: (bool, usize, usize, usize)) -> bool {
+    if a.0 != b.0 {
+        return a.0;
+    }
+    // Compare consistent/total ratios without floating point
+    let (lhs, rhs) = (a.1 * b.2, b.1 * a.2);
+    if lhs != rhs {
+        return lhs > rhs;
+    }
+    a.3 > b.3
+}
+
+/// Detect how fields are quoted in the first SNIFF_ROWS rows of a CSV sample
+fn detect_quote_style(sample: &str, delimiter: u8) -> QuoteStyle {
+    let delimiter = delimiter as char;
+    let mut total = 0usize;
+    let mut quoted = 0usize;
+    let mut rows = 0usize;
+    let mut in_quotes = false;
+    let mut at_field_start = true;
+    let mut at_line_start = true;
+
+    let mut chars = sample.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c == '"' {
+                // Escaped quote ("") stays inside the field
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            }
+            continue;
+        }
+
+        match c {
+            '\r' => {}
+            '\n' => {
+                // Blank lines don't count as rows or fields
+                if !at_line_start {
+                    if at_field_start {
+                        total += 1;
+                    }
+                    rows += 1;
+                    if rows >= SNIFF_ROWS {
+                        break;
+                    }
+                }
+                at_field_start = true;
+                at_line_start = true;
+            }
+            c if c == delimiter => {
+                if at_field_start {
+                    total += 1;
+                }
+                at_field_start = true;
+                at_line_start = false;
+            }
+            _ => {
+                if at_field_start {
+                    total += 1;
+                    if c == '"' {
+                        quoted += 1;
+                        in_quotes = true;
+                    }
+                }
+                at_field_start = false;
+                at_line_start = false;
+            }
+        }
+    }
+
+    if quoted == 0 {
+        QuoteStyle::None
+    } else if quoted == total {
+        QuoteStyle::All
+    } else {
+        QuoteStyle::Minimal
+    }
+}
+
+/// Parse the first record of CSV text into cleaned column names
+pub(crate) fn parse_header_line(content: &str, delimiter: u8) -> Result<Vec<String>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(content.as_bytes());
+
+    let record = reader
+        .records()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Empty header line"))?
+        .context("Failed to parse header line")?;
+
+    Ok(clean_headers(&record))
+}
+
+/// Clean headers: trim and strip # prefix
+fn clean_headers(record: &csv::StringRecord) -> Vec<String> {
+    record
+        .iter()
+        .map(|h| h.trim().trim_start_matches('#').to_string())
+        .collect()
+}
+
+// ============================================================================
+// Export presets (YNAB, Mint, Apple Card, PayPal)
+// ============================================================================
+
+/// Fixed CSV layouts exported by other budgeting apps and non-bank sources
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportPreset {
+    /// YNAB register export (current and YNAB 4 layouts)
+    Ynab,
+    /// Mint transactions export
+    Mint,
+    /// Apple Card monthly statement CSV (exported from Wallet)
+    AppleCard,
+    /// PayPal activity download
+    PayPal,
+}
+
+impl ImportPreset {
+    pub const ALL: [ImportPreset; 4] = [
+        ImportPreset::Ynab,
+        ImportPreset::Mint,
+        ImportPreset::AppleCard,
+        ImportPreset::PayPal,
+    ];
+
+    /// Name of the `tl import` subcommand for this export, e.g. "apple-card"
+    pub fn id(&self) -> &'static str {
+        match self {
+            ImportPreset::Ynab => "ynab",
+            ImportPreset::Mint => "mint",
+            ImportPreset::AppleCard => "apple-card",
+            ImportPreset::PayPal => "paypal",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ImportPreset::Ynab => "YNAB",
+            ImportPreset::Mint => "Mint",
+            ImportPreset::AppleCard => "Apple Card",
+            ImportPreset::PayPal => "PayPal",
+        }
+    }
+
+    /// Whether the export covers one account and needs a target account
+    pub fn single_account(&self) -> bool {
+        matches!(self, ImportPreset::AppleCard | ImportPreset::PayPal)
+    }
+
+    /// Column mappings for this export, checked against the file's headers
+    pub fn mappings(&self, headers: &[String]) -> Result<ColumnMappings> {
+        let has = |name: &str| headers.iter().any(|h| h == name);
+        let required: &[&str] = match self {
+            ImportPreset::Ynab => &["Account", "Date", "Payee", "Outflow", "Inflow"],
+            ImportPreset::Mint => &[
+                "Date",
+                "Description",
+                "Amount",
+                "Transaction Type",
+                "Account Name",
+            ],
+            ImportPreset::AppleCard => &["Transaction Date", "Merchant", "Amount (USD)"],
+            // Checked against the regrouped rows built by `normalize_paypal`
+            ImportPreset::PayPal => &["Date", "Name", "Amount", "Reference"],
+        };
+        let missing: Vec<&str> = required.iter().copied().filter(|c| !has(c)).collect();
+        if !missing.is_empty() {
+            anyhow::bail!(
+                "Not a {} export: missing column(s) {}",
+                self.label(),
+                missing.join(", ")
+            );
+        }
+
+        let optional = |name: &str| has(name).then(|| name.to_string());
+        Ok(match self {
+            // YNAB 4 puts "Master: Sub" in Category and the bare name in Sub Category
+            ImportPreset::Ynab => ColumnMappings {
+                date: "Date".to_string(),
+                amount: "Outflow".to_string(),
+                description: Some("Payee".to_string()),
+                debit: Some("Outflow".to_string()),
+                credit: Some("Inflow".to_string()),
+                balance: None,
+                account: Some("Account".to_string()),
+                category: optional("Sub Category").or_else(|| optional("Category")),
+                tags: None,
+                check_number: optional("Check Number"),
+                reference: None,
+                direction: None,
+                currency: None,
+            },
+            ImportPreset::Mint => ColumnMappings {
+                date: "Date".to_string(),
+                amount: "Amount".to_string(),
+                description: Some("Description".to_string()),
+                debit: None,
+                credit: None,
+                balance: None,
+                account: Some("Account Name".to_string()),
+                category: optional("Category"),
+                tags: optional("Labels"),
+                check_number: None,
+                reference: None,
+                direction: Some("Transaction Type".to_string()),
+                currency: None,
+            },
+            ImportPreset::AppleCard => ColumnMappings {
+                date: "Transaction Date".to_string(),
+                amount: "Amount (USD)".to_string(),
+                description: Some("Merchant".to_string()),
+                debit: None,
+                credit: None,
+                balance: None,
+                account: None,
+                category: optional("Category"),
+                // The merchant doubles as a tag, so spending per merchant is a tag filter
+                tags: Some("Merchant".to_string()),
+                check_number: None,
+                reference: None,
+                direction: None,
+                currency: None,
+            },
+            ImportPreset::PayPal => ColumnMappings {
+                date: "Date".to_string(),
+                amount: "Amount".to_string(),
+                description: Some("Name".to_string()),
+                debit: None,
+                credit: None,
+                balance: None,
+                account: None,
+                category: optional("Category"),
+                tags: None,
+                check_number: None,
+                reference: Some("Reference".to_string()),
+                direction: None,
+                currency: None,
+            },
+        })
+    }
+
+    /// Sign convention and category handling for this export
+    pub fn options(&self) -> ImportOptions {
+        // Budget bookkeeping categories carry no spending meaning once imported
+        let dropped: &[&str] = match self {
+            ImportPreset::Ynab => &[
+                "Ready to Assign",
+                "To be Budgeted",
+                "Available this month",
+                "Available next month",
+            ],
+            ImportPreset::Mint => &["Uncategorized"],
+            ImportPreset::AppleCard => &["Other"],
+            ImportPreset::PayPal => &[],
+        };
+        ImportOptions {
+            // YNAB outflows are unsigned; Mint signs come from Transaction Type
+            debit_negative: *self == ImportPreset::Ynab,
+            // Apple Card lists purchases as positive and payments as negative
+            flip_signs: *self == ImportPreset::AppleCard,
+            create_missing_accounts: !self.single_account(),
+            category_map: dropped
+                .iter()
+                .map(|c| (c.to_string(), String::new()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// This export as an ordinary import profile, for importing through the
+    /// profile path (the desktop import modal)
+    ///
+    /// PayPal activity is regrouped before import, which a profile can't express.
+    pub fn profile(&self, headers: &[String]) -> Result<ImportProfile> {
+        if *self == ImportPreset::PayPal {
+            anyhow::bail!("PayPal activity is regrouped on import; use `tl import paypal`");
+        }
+        let options = self.options();
+        Ok(ImportProfile {
+            column_mappings: self.mappings(headers)?,
+            date_format: (*self == ImportPreset::AppleCard).then(|| "%m/%d/%Y".to_string()),
+            skip_rows: 0,
+            options: ConfigImportOptions {
+                flip_signs: options.flip_signs,
+                debit_negative: options.debit_negative,
+                create_missing_accounts: options.create_missing_accounts,
+                category_map: options.category_map,
+                ..Default::default()
+            },
+        })
+    }
+}
+
+// ============================================================================
+// PayPal activity regrouping
+// ============================================================================
+
+/// PayPal activity rows reshaped into one row per money movement
+struct PayPalRows {
+    headers: Vec<String>,
+    records: Vec<csv::StringRecord>,
+    /// Rows left out on purpose (pending, foreign-currency legs), reported as skipped
+    dropped: Vec<ImportRowError>,
+}
+
+/// Reshape a PayPal activity download into Date/Name/Amount/Category/Reference rows.
+///
+/// PayPal reports the fee inside the same row as the payment and splits a
+/// foreign-currency payment into three rows (the payment and two conversion
+/// legs). Fees become their own transactions tagged `fees`; only the leg in
+/// the account's currency is kept, named after the payment it funded. The
+/// Transaction ID becomes the reference so re-imports deduplicate exactly.
+fn normalize_paypal(
+    headers: &[String],
+    records: &[csv::StringRecord],
+    home_currency: &str,
+    number_format: NumberFormat,
+) -> Result<PayPalRows> {
+    let col = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let required = ["Date", "Name", "Gross", "Transaction ID"];
+    let missing: Vec<&str> = required
+        .iter()
+        .copied()
+        .filter(|c| col(c).is_none())
+        .collect();
+    if !missing.is_empty() {
+        anyhow::bail!(
+            "Not a PayPal export: missing column(s) {}",
+            missing.join(", ")
+        );
+    }
+    let (date_idx, name_idx, gross_idx, id_idx) = (
+        col("Date").unwrap(),
+        col("Name").unwrap(),
+        col("Gross").unwrap(),
+        col("Transaction ID").unwrap(),
+    );
+    let status_idx = col("Status");
+    let currency_idx = col("Currency");
+    let fee_idx = col("Fee");
+    let type_idx = col("Type");
+    let ref_idx = col("Reference Txn ID");
+
+    let get = |record: &csv::StringRecord, idx: Option<usize>| {
+        idx.and_then(|i| record.get(i))
+            .unwrap_or("")
+            .trim()
+            .to_string()
+    };
+    let line_of = |record: &csv::StringRecord| record.position().map(|p| p.line()).unwrap_or(0);
+
+    // Payment names by transaction ID, so conversion rows can borrow them
+    let names: HashMap<String, String> = records
+        .iter()
+        .map(|r| (get(r, Some(id_idx)), get(r, Some(name_idx))))
+        .filter(|(id, name)| !id.is_empty() && !name.is_empty())
+        .collect();
+
+    let mut out = Vec::new();
+    let mut dropped = Vec::new();
+    for record in records {
+        let status = get(record, status_idx);
+        if status_idx.is_some() && !status.eq_ignore_ascii_case("completed") {
+            dropped.push(ImportRowError {
+                line: line_of(record),
+                column: "Status".to_string(),
+                value: status,
+                reason: "not completed".to_string(),
+            });
+            continue;
+        }
+        let currency = get(record, currency_idx);
+        if currency_idx.is_some() && !currency.eq_ignore_ascii_case(home_currency) {
+            dropped.push(ImportRowError {
+                line: line_of(record),
+                column: "Currency".to_string(),
+                value: currency,
+                reason: "foreign-currency leg of a conversion".to_string(),
+            });
+            continue;
+        }
+
+        let id = get(record, Some(id_idx));
+        let kind = get(record, type_idx);
+        let mut name = get(record, Some(name_idx));
+        if name.is_empty() {
+            let referenced = names.get(&get(record, ref_idx));
+            name = match referenced {
+                Some(payment) if kind.to_lowercase().contains("conversion") => payment.clone(),
+                _ => kind.clone(),
+            };
+        }
+
+        let mut row = csv::StringRecord::from(vec![
+            get(record, Some(date_idx)),
+            name.clone(),
+            get(record, Some(gross_idx)),
+            String::new(),
+            id.clone(),
+        ]);
+        row.set_position(record.position().cloned());
+        out.push(row);
+
+        let fee = get(record, fee_idx);
+        let has_fee = parse_amount_with_format(&fee, number_format).is_some_and(|f| !f.is_zero());
+        if has_fee {
+            let mut row = csv::StringRecord::from(vec![
+                get(record, Some(date_idx)),
+                format!("PayPal fee: {}", name),
+                fee,
+                "fees".to_string(),
+                format!("{}-fee", id),
+            ]);
+            row.set_position(record.position().cloned());
+            out.push(row);
+        }
+    }
+
+    Ok(PayPalRows {
+        headers: ["Date", "Name", "Amount", "Category", "Reference"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect(),
+        records: out,
+        dropped,
+    })
+}
+
+// ============================================================================
+// Amazon order history
+// ============================================================================
+
+/// Description prefix for transactions enriched from Amazon orders
+const AMAZON_PREFIX: &str = "Amazon: ";
+
+/// One Amazon order with its charged total and item titles
+struct AmazonOrder {
+    id: String,
+    date: NaiveDate,
+    total: Decimal,
+    titles: Vec<String>,
+}
+
+/// Group an Amazon order history export into orders.
+///
+/// Supports the per-item layouts of the privacy data export
+/// ("Total Owed", "Product Name") and the older order reports ("Item Total",
+/// "Title"), whose item totals are summed, and the per-order "Total Charged".
+fn read_amazon_orders(
+    headers: &[String],
+    records: &[csv::StringRecord],
+) -> Result<Vec<AmazonOrder>> {
+    let col = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|n| headers.iter().position(|h| h.eq_ignore_ascii_case(n)))
+    };
+    let (id_idx, date_idx) = match (col(&["Order ID"]), col(&["Order Date"])) {
+        (Some(id), Some(date)) => (id, date),
+        _ => anyhow::bail!("Not an Amazon order history export: missing Order ID or Order Date"),
+    };
+    let order_total_idx = col(&["Total Charged"]);
+    let item_total_idx = col(&["Total Owed", "Item Total"]);
+    if order_total_idx.is_none() && item_total_idx.is_none() {
+        anyhow::bail!("Not an Amazon order history export: no order or item total column");
+    }
+    let title_idx = col(&["Product Name", "Title"]);
+
+    let mut orders: Vec<AmazonOrder> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for record in records {
+        let id = record.get(id_idx).unwrap_or("").trim().to_string();
+        let date_str = record.get(date_idx).unwrap_or("").trim();
+        // The privacy export uses ISO timestamps ("2024-03-02T18:04:11Z")
+        let date = parse_date(date_str).or_else(|| date_str.get(..10).and_then(parse_date));
+        let date = match date {
+            Some(date) if !id.is_empty() => date,
+            _ => continue,
+        };
+        let amount = |idx: Option<usize>| {
+            idx.and_then(|i| record.get(i))
+                .and_then(parse_amount)
+                .unwrap_or(Decimal::ZERO)
+        };
+
+        let pos = *index.entry(id.clone()).or_insert_with(|| {
+            orders.push(AmazonOrder {
+                id,
+                date,
+                total: Decimal::ZERO,
+                titles: Vec::new(),
+            });
+            orders.len() - 1
+        });
+        let order = &mut orders[pos];
+        match order_total_idx {
+            Some(_) if order.total.is_zero() => order.total = amount(order_total_idx),
+            Some(_) => {}
+            None => order.total += amount(item_total_idx),
+        }
+        if let Some(title) = title_idx.and_then(|i| record.get(i)) {
+            let title = title.trim();
+            if !title.is_empty() && !order.titles.iter().any(|t| t == title) {
+                order.titles.push(title.to_string());
+            }
+        }
+    }
+
+    orders.retain(|o| !o.total.is_zero());
+    Ok(orders)
+}
+
+/// Build "Amazon: first item, second item (+2 more)"
+fn amazon_description(titles: &[String]) -> String {
+    const SHOWN: usize = 2;
+    if titles.is_empty() {
+        return "Amazon".to_string();
+    }
+    let mut description = format!(
+        "{}{}",
+        AMAZON_PREFIX,
+        titles[..titles.len().min(SHOWN)].join(", ")
+    );
+    if titles.len() > SHOWN {
+        description.push_str(&format!(" (+{} more)", titles.len() - SHOWN));
+    }
+    description
+}
+
+// ============================================================================
+// Balance impact (dry-run summary)
+// ============================================================================
+
+/// Summarize how parsed transactions would move the account balance.
+///
+/// `csv_balances` is aligned with `transactions` (CSV order) and holds the
+/// running balance column when one is mapped. Warnings flag the mistakes that
+/// are easy to make and hard to notice after the fact, mostly inverted signs.
+fn compute_balance_impact(
+    transactions: &[Transaction],
+    csv_balances: &[Option<Decimal>],
+    latest_snapshot: Option<&BalanceSnapshot>,
+) -> BalanceImpact {
+    // (count, inflow, outflow) per YYYY-MM
+    let mut months: std::collections::BTreeMap<String, (usize, Decimal, Decimal)> =
+        std::collections::BTreeMap::new();
+    for tx in transactions {
+        let month = tx.transaction_date.format("%Y-%m").to_string();
+        let entry = months
+            .entry(month)
+            .or_insert((0, Decimal::ZERO, Decimal::ZERO));
+        entry.0 += 1;
+        if tx.amount >= Decimal::ZERO {
+            entry.1 += tx.amount;
+        } else {
+            entry.2 += tx.amount;
+        }
+    }
+    let net_change: Decimal = transactions.iter().map(|t| t.amount).sum();
+
+    // End-of-day balances from the CSV (last value seen for each date wins)
+    let mut csv_day_balances: std::collections::BTreeMap<NaiveDate, Decimal> =
+        std::collections::BTreeMap::new();
+    for (tx, balance) in transactions.iter().zip(csv_balances) {
+        if let Some(balance) = balance {
+            csv_day_balances.insert(tx.transaction_date, *balance);
+        }
+    }
+    let csv_end_balance = csv_day_balances.values().next_back().copied();
+
+    let mut warnings = Vec::new();
+
+    let (matching, flipped) = balance_sign_agreement(transactions, csv_balances);
+    if flipped > matching {
+        warnings.push(format!(
+            "Amounts move the balance column the wrong way in {} of {} rows; the amount signs are probably flipped",
+            flipped,
+            matching + flipped
+        ));
+    }
+
+    let snapshot_date = latest_snapshot.map(|s| s.snapshot_time.date());
+    let snapshot_balance = latest_snapshot.map(|s| s.balance);
+
+    let projected_balance = latest_snapshot.map(|snapshot| {
+        let after: Decimal = transactions
+            .iter()
+            .filter(|t| t.transaction_date > snapshot.snapshot_time.date())
+            .map(|t| t.amount)
+            .sum();
+        snapshot.balance + after
+    });
+
+    if let (Some(date), Some(snapshot_balance)) = (snapshot_date, snapshot_balance) {
+        let covers_snapshot = csv_day_balances
+            .keys()
+            .next()
+            .is_some_and(|first| *first <= date)
+            && csv_day_balances
+                .keys()
+                .next_back()
+                .is_some_and(|last| *last >= date);
+        let csv_balance_at_snapshot = csv_day_balances.range(..=date).next_back().map(|(_, b)| *b);
+
+        match csv_balance_at_snapshot {
+            Some(csv_balance) if covers_snapshot => {
+                if (csv_balance - snapshot_balance).abs() > Decimal::new(1, 2) {
+                    if csv_balance == -snapshot_balance {
+                        warnings.push(format!(
+                            "CSV balance on {} is {} but the latest snapshot is {}; the balance sign looks inverted",
+                            date, csv_balance, snapshot_balance
+                        ));
+                    } else {
+                        warnings.push(format!(
+                            "CSV balance on {} is {} but the latest snapshot is {}",
+                            date, csv_balance, snapshot_balance
+                        ));
+                    }
+                }
+            }
+            _ => {
+                if let Some(projected) = projected_balance {
+                    let crosses_zero = snapshot_balance != Decimal::ZERO
+                        && projected != Decimal::ZERO
+                        && projected.is_sign_negative() != snapshot_balance.is_sign_negative();
+                    if crosses_zero {
+                        warnings.push(format!(
+                            "Importing would take the balance from {} to {}; check whether amount signs need flipping",
+                            snapshot_balance, projected
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    BalanceImpact {
+        months: months
+            .into_iter()
+            .map(|(month, (count, inflow, outflow))| MonthlyBalanceDelta {
+                month,
+                count,
+                inflow: inflow.to_string(),
+                outflow: outflow.to_string(),
+                net: (inflow + outflow).to_string(),
+            })
+            .collect(),
+        net_change: net_change.to_string(),
+        snapshot_balance: snapshot_balance.map(|b| b.to_string()),
+        snapshot_date: snapshot_date.map(|d| d.to_string()),
+        projected_balance: projected_balance.map(|b| b.to_string()),
+        csv_end_balance: csv_end_balance.map(|b| b.to_string()),
+        warnings,
+    }
+}
+
+/// Share of positive amounts that makes a liability's signs look reversed
+const LIABILITY_POSITIVE_PERCENT: usize = 80;
+
+/// Check the amounts' signs, and the anchor balance's, against the account type
+///
+/// Money going out is negative in Treeline and most rows of a statement are
+/// spending, so a file that comes out nearly all positive was exported with
+/// the opposite convention. Cards get a lower bar than bank accounts, since
+/// a month of purchases has a payment or two at most. A liability's balance
+/// is what's owed, as a negative number.
+fn sign_warnings(
+    transactions: &[Transaction],
+    account: &Account,
+    options: &ImportOptions,
+) -> Vec<String> {
+    let account_type = account
+        .account_type
+        .as_deref()
+        .and_then(|t| t.parse::<AccountType>().ok());
+    let liability = account_type.is_some_and(|t| t.classification() == "liability")
+        || account.classification.as_deref() == Some("liability");
+    let on_account = match account_type {
+        Some(AccountType::CreditCard) => "a credit card",
+        Some(AccountType::Loan) => "a loan",
+        Some(AccountType::Mortgage) => "a mortgage",
+        Some(AccountType::Checking) => "a checking account",
+        Some(AccountType::Savings) => "a savings account",
+        _ if liability => "a liability",
+        _ => "this account",
+    };
+
+    let mut warnings = Vec::new();
+    let signed = transactions.iter().filter(|t| !t.amount.is_zero()).count();
+    let positive = transactions
+        .iter()
+        .filter(|t| t.amount > Decimal::ZERO)
+        .count();
+    let threshold = if liability {
+        LIABILITY_POSITIVE_PERCENT
+    } else {
+        SIGN_CHECK_POSITIVE_PERCENT
+    };
+    if signed >= SIGN_CHECK_MIN_ROWS && positive * 100 >= signed * threshold {
+        warnings.push(format!(
+            "{}% of transactions are positive on {} — did you mean {}?",
+            positive * 100 / signed,
+            on_account,
+            if options.flip_signs {
+                "to leave off --flip-signs"
+            } else {
+                "--flip-signs"
+            }
+        ));
+    }
+    match options.anchor_balance {
+        Some(anchor) if liability && anchor > Decimal::ZERO => warnings.push(format!(
+            "The balance {} is positive on {}, but an amount owed is negative — did you mean {}?",
+            anchor, on_account, -anchor
+        )),
+        _ => {}
+    }
+    warnings
+}
+
+/// Count consecutive rows whose balance change agrees with the amount as-is
+/// versus negated. Files list rows oldest-first or newest-first, so both
+/// orders are scored and the better-fitting one is returned.
+/// One row's part in a running balance check
+struct BalanceRow {
+    line: u64,
+    /// Rows are checked against others for the same account
+    account: String,
+    amount: Decimal,
+    balance: Option<Decimal>,
+}
+
+/// Rows whose amount isn't the change in the balance column
+///
+/// Each account's rows are taken in file order, or reversed for newest-first
+/// files (whichever explains more rows), and each amount compared with the
+/// balance change from the row before it in time. A change that is the
+/// amount negated points to a reversed sign, any other difference to a
+/// missing row or a wrong value. When fewer than half the rows fit, the
+/// column isn't a running balance per row (a daily balance repeated on each
+/// row, say; or every sign reversed, which BalanceImpact warns about) and
+/// nothing is reported.
+fn reconcile_balances(rows: &[BalanceRow]) -> Vec<BalanceMismatch> {
+    let mut by_account: HashMap<&str, Vec<&BalanceRow>> = HashMap::new();
+    for row in rows {
+        by_account.entry(&row.account).or_default().push(row);
+    }
+
+    let mut mismatches = Vec::new();
+    for rows in by_account.values() {
+        // Each row with the balance change it should account for
+        let changes = |newest_first: bool| -> Vec<(&BalanceRow, Decimal)> {
+            rows.windows(2)
+                .filter_map(|pair| {
+                    let (earlier, later) = if newest_first {
+                        (pair[1], pair[0])
+                    } else {
+                        (pair[0], pair[1])
+                    };
+                    Some((later, later.balance? - earlier.balance?))
+                })
+                .collect()
+        };
+        let fits = |changes: &[(&BalanceRow, Decimal)]| {
+            changes
+                .iter()
+                .filter(|(row, change)| *change == row.amount)
+                .count()
+        };
+        let (oldest_first, newest_first) = (changes(false), changes(true));
+        let changes = if fits(&newest_first) > fits(&oldest_first) {
+            newest_first
+        } else {
+            oldest_first
+        };
+        if changes.is_empty() || fits(&changes) * 2 < changes.len() {
+            continue;
+        }
+
+        for (row, change) in changes {
+            if change == row.amount {
+                continue;
+            }
+            mismatches.push(BalanceMismatch {
+                line: row.line,
+                amount: row.amount.to_string(),
+                balance_change: change.to_string(),
+                kind: if change == -row.amount {
+                    BalanceMismatchKind::SignReversed
+                } else {
+                    BalanceMismatchKind::Gap
+                },
+            });
+        }
+    }
+    mismatches.sort_by_key(|m| m.line);
+    mismatches
+}
+
+/// One warning summing up the balance mismatches, if there are any
+fn balance_mismatch_warning(mismatches: &[BalanceMismatch]) -> Option<String> {
+    const LINES_SHOWN: usize = 5;
+    if mismatches.is_empty() {
+        return None;
+    }
+    let mut lines: Vec<String> = mismatches
+        .iter()
+        .take(LINES_SHOWN)
+        .map(|m| m.line.to_string())
+        .collect();
+    if mismatches.len() > LINES_SHOWN {
+        lines.push("…".to_string());
+    }
+    Some(format!(
+        "{} row(s) don't match the running balance (line {}); check for reversed signs \
+         or missing rows before the balances are saved",
+        mismatches.len(),
+        lines.join(", ")
+    ))
+}
+
+fn balance_sign_agreement(
+    transactions: &[Transaction],
+    csv_balances: &[Option<Decimal>],
+) -> (usize, usize) {
+    let mut oldest_first = (0, 0);
+    let mut newest_first = (0, 0);
+
+    for i in 1..transactions.len().min(csv_balances.len()) {
+        let (prev, cur) = match (csv_balances[i - 1], csv_balances[i]) {
+            (Some(prev), Some(cur)) => (prev, cur),
+            _ => continue,
+        };
+        let change = cur - prev;
+        if change == Decimal::ZERO {
+            continue;
+        }
+
+        // Oldest-first: this row's amount produced the change
+        let amount = transactions[i].amount;
+        if change == amount {
+            oldest_first.0 += 1;
+        } else if change == -amount {
+            oldest_first.1 += 1;
+        }
+
+        // Newest-first: the previous row's amount produced the (reversed) change
+        let amount = transactions[i - 1].amount;
+        if -change == amount {
+            newest_first.0 += 1;
+        } else if -change == -amount {
+            newest_first.1 += 1;
+        }
+    }
+
+    if newest_first.0 + newest_first.1 > oldest_first.0 + oldest_first.1 {
+        newest_first
+    } else {
+        oldest_first
+    }
+}
+
+/// Result of matching Amazon orders to card transactions
+#[derive(Debug, Serialize)]
+pub struct OrderMatchResult {
+    /// Whether this was a preview (no descriptions changed)
+    pub preview: bool,
+    /// Orders read from the export
+    pub orders: usize,
+    /// Orders matched to exactly one transaction
+    pub matched: Vec<OrderMatch>,
+    /// Orders with no matching transaction
+    pub unmatched: usize,
+    /// Orders skipped because several transactions matched
+    pub ambiguous: usize,
+}
+
+/// An Amazon order and the transaction it was matched to
+#[derive(Debug, Serialize)]
+pub struct OrderMatch {
+    pub order_id: String,
+    pub order_date: String,
+    pub total: Decimal,
+    pub transaction_id: String,
+    pub previous_description: String,
+    pub description: String,
+}
+
+/// Projected effect of an import on the account balance
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct BalanceImpact {
+    /// Net change per calendar month, oldest first
+    pub months: Vec<MonthlyBalanceDelta>,
+    /// Sum of all parsed transaction amounts
+    pub net_change: String,
+    /// Most recent balance snapshot for the account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot_balance: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot_date: Option<String>,
+    /// Latest snapshot plus transactions dated after it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub projected_balance: Option<String>,
+    /// Last running balance in the CSV (if a balance column is mapped)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub csv_end_balance: Option<String>,
+    /// Suspicious discrepancies worth checking before importing
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// Balance movement for one month of an import
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct MonthlyBalanceDelta {
+    /// Month as YYYY-MM
+    pub month: String,
+    pub count: usize,
+    pub inflow: String,
+    pub outflow: String,
+    pub net: String,
+}
+
+/// Result of column auto-detection
+#[derive(Debug, Default, Serialize)]
+pub struct DetectedColumns {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credit: Option<String>,
+}
+
+/// Where a running import is
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportProgress {
+    pub stage: ImportStage,
+    /// Rows parsed or transactions written so far in this stage
+    pub done: usize,
+    pub total: usize,
+}
+
+impl ImportProgress {
+    /// Share of the whole import done, 0.0 to 1.0; parsing and writing take
+    /// the most time, so they get most of the range
+    pub fn fraction(&self) -> f64 {
+        let (start, end) = match self.stage {
+            ImportStage::Parsing => (0.0, 0.4),
+            ImportStage::Deduplicating => (0.4, 0.5),
+            ImportStage::Inserting => (0.5, 0.95),
+            ImportStage::Finishing => (0.95, 1.0),
+        };
+        let within = if self.total == 0 {
+            0.0
+        } else {
+            self.done as f64 / self.total as f64
+        };
+        start + (end - start) * within
+    }
+}
+
+/// Steps of an import, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStage {
+    Parsing,
+    /// Checking fingerprints against earlier imports
+    Deduplicating,
+    Inserting,
+    /// Balance snapshots and auto-tag rules
+    Finishing,
+}
+
+impl ImportStage {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ImportStage::Parsing => "Reading rows",
+            ImportStage::Deduplicating => "Checking for duplicates",
+            ImportStage::Inserting => "Saving transactions",
+            ImportStage::Finishing => "Saving balances",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ImportResult {
+    /// Unique batch ID for this import
+    pub batch_id: String,
+    /// Total transactions discovered in CSV
+    pub discovered: i64,
+    /// Successfully imported transactions
+    pub imported: i64,
+    /// Skipped transactions (invalid or duplicate)
+    pub skipped: i64,
+    /// Transactions skipped because they were already imported
+    pub duplicates: i64,
+    /// Number of fingerprints checked for deduplication
+    pub fingerprints_checked: i64,
+    /// Number of balance snapshots created from running balance column
+    pub balance_snapshots_created: i64,
+    /// Whether this was a preview (no changes applied)
+    pub preview: bool,
+    /// Rows skipped because a value could not be parsed
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ImportRowError>,
+    /// Accounts created for unassigned account-column values (to be created, in preview)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub accounts_created: Vec<String>,
+    /// Date format detected for the date column (when none was given)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_format: Option<DetectedDateFormat>,
+    /// Things worth checking, such as dates that read differently as DD/MM and MM/DD
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Projected effect on the account balance (only in preview mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance_impact: Option<BalanceImpact>,
+    /// Rows whose amount doesn't match the change in the balance column
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub balance_mismatches: Vec<BalanceMismatch>,
+    /// Transaction previews (only in preview mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transactions: Option<Vec<TransactionPreview>>,
+    /// What will happen to each data row, in file order (only in preview mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rows: Option<Vec<ImportRowStatus>>,
+}
+
+/// What a file would run into on import, found by reading all of it first
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ImportValidation {
+    /// Data rows in the file
+    pub rows: usize,
+    /// Rows with a usable date and amount
+    pub valid_rows: usize,
+    /// Whether an import can go ahead: the required columns exist and some
+    /// rows are valid. Other issues only mean rows get skipped.
+    pub importable: bool,
+    /// Date format detected for the date column (when none was given)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_format: Option<DetectedDateFormat>,
+    /// Whole-file issues first, then rows in file order
+    pub issues: Vec<ValidationIssue>,
+}
+
+/// A row whose amount doesn't account for the change in the balance column
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BalanceMismatch {
+    pub line: u64,
+    /// Amount as it would be imported, decimal string
+    pub amount: String,
+    /// How much the balance column moved at this row, decimal string
+    pub balance_change: String,
+    pub kind: BalanceMismatchKind,
+}
+
+impl BalanceMismatch {
+    pub fn message(&self) -> String {
+        match self.kind {
+            BalanceMismatchKind::SignReversed => format!(
+                "amount {} has the opposite sign of the balance change {}",
+                self.amount, self.balance_change
+            ),
+            BalanceMismatchKind::Gap => format!(
+                "balance changed by {} but the amount is {}; a row may be missing",
+                self.balance_change, self.amount
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceMismatchKind {
+    /// The balance moved by the amount negated
+    SignReversed,
+    /// The balance moved by something else: a missing row or a wrong value
+    Gap,
+}
+
+/// One problem found by [`ImportService::validate`]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ValidationIssue {
+    pub kind: ValidationIssueKind,
+    /// 1-based line in the file, for row issues
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
+    /// Raw cell value as read from the file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationIssueKind {
+    /// A mapped column isn't in the file
+    MissingColumn,
+    /// A row has more or fewer fields than the header
+    ColumnCount,
+    InvalidDate,
+    InvalidAmount,
+    /// Dates that read differently as DD/MM and MM/DD
+    AmbiguousDates,
+    /// Nearly every amount would import as money coming in
+    SignDistribution,
+    /// A row's amount doesn't match the change in the balance column
+    BalanceMismatch,
+}
+
+/// What undoing an import removed
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct UndoImportResult {
+    pub batch_id: String,
+    pub transactions_deleted: i64,
+    pub balance_snapshots_deleted: i64,
+}
+
+/// The date format an import settled on by looking at the whole date column
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct DetectedDateFormat {
+    /// strftime format used (e.g. "%m/%d/%Y")
+    pub format: String,
+    /// Other formats that fit every date but read some of them differently
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<String>,
+}
+
+impl DetectedDateFormat {
+    /// Warning to show when the dates could be read another way
+    pub fn warning(&self) -> Option<String> {
+        if self.alternatives.is_empty() {
+            return None;
+        }
+        let alternatives: Vec<String> = self
+            .alternatives
+            .iter()
+            .map(|f| date_format_label(f))
+            .collect();
+        Some(format!(
+            "Dates read as {}, but could also be {}; set a date format if they look wrong",
+            date_format_label(&self.format),
+            alternatives.join(" or ")
+        ))
+    }
+}
+
+/// A CSV row that will be skipped, with the offending value and why
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ImportRowError {
+    /// 1-based line number in the CSV file
+    pub line: u64,
+    /// Column containing the bad value
+    pub column: String,
+    /// Raw cell value as read from the file
+    pub value: String,
+    /// Why the value was rejected
+    pub reason: String,
+}
+
+/// What an import does with a CSV row
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RowStatus {
+    /// Will be imported
+    New,
+    /// Already imported (same fingerprint), will be skipped
+    Duplicate,
+    /// A value could not be parsed, will be skipped
+    Invalid,
+}
+
+/// One CSV row's status in an import preview
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ImportRowStatus {
+    /// 1-based line number in the CSV file
+    pub line: u64,
+    pub status: RowStatus,
+    /// Why an invalid row is rejected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TransactionPreview {
+    pub date: String,
+    pub amount: String,
+    pub description: Option<String>,
+    /// Running balance (from CSV, if mapped)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance: Option<String>,
+    /// Target account name (multi-account imports only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    /// Tags from the category/tags columns
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    /// Currency (multi-currency imports only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    /// New, or a duplicate that will be skipped
+    pub status: RowStatus,
+}
+
+/// Format a list of accounts for display in error messages.
+fn format_account_list(accounts: &[&Account]) -> String {
+    accounts
+        .iter()
+        .map(|a| {
+            if let Some(nick) = &a.nickname {
+                format!("  {} ({}) — {}", a.name, nick, a.id)
+            } else {
+                format!("  {} — {}", a.name, a.id)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ==========================================================================
+    // parse_amount tests - US format (current behavior)
+    // ==========================================================================
+
+    #[test]
+    fn test_parse_amount_simple_positive() {
+        assert_eq!(parse_amount("100.50"), Some(Decimal::new(10050, 2)));
+    }
+
+    #[test]
+    fn test_parse_amount_simple_negative() {
+        assert_eq!(parse_amount("-50.25"), Some(Decimal::new(-5025, 2)));
+    }
+
+    #[test]
+    fn test_parse_amount_with_comma_thousands() {
+        // US format: 1,234.56
+        assert_eq!(parse_amount("1,234.56"), Some(Decimal::new(123456, 2)));
+    }
+
+    #[test]
+    fn test_parse_amount_with_currency_symbol() {
+        assert_eq!(parse_amount("$100.00"), Some(Decimal::new(10000, 2)));
+        assert_eq!(parse_amount("-$50.00"), Some(Decimal::new(-5000, 2)));
+    }
+
+    #[test]
+    fn test_parse_amount_parentheses_negative() {
+        // Accounting notation: (100.00) means -100.00
+        assert_eq!(parse_amount("(100.00)"), Some(Decimal::new(-10000, 2)));
+        assert_eq!(parse_amount("(1,234.56)"), Some(Decimal::new(-123456, 2)));
+    }
+
+    #[test]
+    fn test_parse_amount_with_whitespace() {
+        assert_eq!(parse_amount("  100.50  "), Some(Decimal::new(10050, 2)));
+    }
+
+    #[test]
+    fn test_parse_amount_empty_string() {
+        assert_eq!(parse_amount(""), None);
+    }
+
+    #[test]
+    fn test_parse_amount_invalid() {
+        assert_eq!(parse_amount("abc"), None);
+    }
+
+    // ==========================================================================
+    // parse_date tests
+    // ==========================================================================
+
+    #[test]
+    fn test_parse_date_iso_format() {
+        // YYYY-MM-DD (ISO)
+        assert_eq!(
+            parse_date("2024-01-15"),
+            Some(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_date_us_format() {
+        // MM/DD/YYYY (US)
+        assert_eq!(
+            parse_date("12/03/2025"),
+            Some(NaiveDate::from_ymd_opt(2025, 12, 3).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_date_with_format() {
+        assert_eq!(
+            parse_date_with_format("05.01.2024", "%d.%m.%Y"),
+            NaiveDate::from_ymd_opt(2024, 1, 5)
+        );
+        assert_eq!(
+            parse_date_with_format("2024-01-05 10:11:12", "%Y-%m-%d %H:%M:%S"),
+            NaiveDate::from_ymd_opt(2024, 1, 5)
+        );
+        // An explicit format never falls back to guessing
+        assert_eq!(parse_date_with_format("2024-01-05", "%d.%m.%Y"), None);
+    }
+
+    #[test]
+    fn test_parse_date_invalid() {
         assert_eq!(parse_date("not-a-date"), None);
         assert_eq!(parse_date(""), None);
     }
 
+    #[test]
+    fn test_detect_date_format() {
+        // A day above 12 settles it for the whole column
+        let detected = detect_date_format(["03/01/2024", "13/01/2024", ""]).unwrap();
+        assert_eq!(detected.format, "%d/%m/%Y");
+        assert!(detected.warning().is_none());
+
+        // Every date reads both ways: US wins, with a warning
+        let detected = detect_date_format(["03/01/2024", "04/01/2024"]).unwrap();
+        assert_eq!(detected.format, "%m/%d/%Y");
+        assert_eq!(detected.alternatives, vec!["%d/%m/%Y"]);
+        assert!(detected.warning().unwrap().contains("DD/MM/YYYY"));
+
+        // Same date either way is not ambiguous
+        let detected = detect_date_format(["05/05/2024"]).unwrap();
+        assert!(detected.alternatives.is_empty());
+
+        assert_eq!(detect_date_format(["2024-01-05", "01/05/2024"]), None);
+        assert_eq!(detect_date_format(["", " "]), None);
+    }
+
     // ==========================================================================
     // European format tests - with proper format parameter
     // ==========================================================================
@@ -1160,4 +3842,890 @@ mod tests {
         assert_eq!(NumberFormat::from_str("eu_space"), NumberFormat::EuSpace);
         assert_eq!(NumberFormat::from_str("unknown"), NumberFormat::Us); // default
     }
+
+    // ==========================================================================
+    // Multi-format amount parsing
+    // ==========================================================================
+
+    #[test]
+    fn test_parse_amount_trailing_minus() {
+        assert_eq!(parse_amount("123.45-"), Some(Decimal::new(-12345, 2)));
+        assert_eq!(
+            parse_amount_with_format("1.234,56-", NumberFormat::Eu),
+            Some(Decimal::new(-123456, 2))
+        );
+    }
+
+    #[test]
+    fn test_parse_amount_currency_prefix_and_symbols() {
+        assert_eq!(parse_amount("USD 100.50"), Some(Decimal::new(10050, 2)));
+        assert_eq!(parse_amount("US$-12.00"), Some(Decimal::new(-1200, 2)));
+        assert_eq!(
+            parse_amount_with_format("-1.234,56 €", NumberFormat::Eu),
+            Some(Decimal::new(-123456, 2))
+        );
+        assert_eq!(
+            parse_amount_with_format("99,90 zł", NumberFormat::Eu),
+            Some(Decimal::new(9990, 2))
+        );
+        assert_eq!(parse_amount("(£1,000.00)"), Some(Decimal::new(-100000, 2)));
+    }
+
+    #[test]
+    fn test_parse_amount_sign_markers() {
+        assert_eq!(parse_amount("+25.00"), Some(Decimal::new(2500, 2)));
+        assert_eq!(parse_amount("\u{2212}25.00"), Some(Decimal::new(-2500, 2)));
+        assert_eq!(parse_amount("25.00 DR"), Some(Decimal::new(-2500, 2)));
+        assert_eq!(parse_amount("25.00 CR"), Some(Decimal::new(2500, 2)));
+    }
+
+    #[test]
+    fn test_parse_amount_checked_reasons() {
+        assert_eq!(
+            parse_amount_checked("", NumberFormat::Us),
+            Err("empty amount".to_string())
+        );
+        assert_eq!(
+            parse_amount_checked("12 apples", NumberFormat::Us),
+            Err("unrecognized text 'apples'".to_string())
+        );
+        assert!(parse_amount_checked("1.2.3", NumberFormat::Us).is_err());
+        assert!(parse_amount_checked("12#34", NumberFormat::Us).is_err());
+    }
+
+    #[test]
+    fn test_number_format_for_column_override() {
+        let mut options = ImportOptions {
+            number_format: NumberFormat::Eu,
+            ..ImportOptions::default()
+        };
+        options
+            .column_number_formats
+            .insert("Balance".to_string(), NumberFormat::Us);
+
+        assert_eq!(options.number_format_for("Amount"), NumberFormat::Eu);
+        assert_eq!(options.number_format_for("Balance"), NumberFormat::Us);
+    }
+
+    // ==========================================================================
+    // Category/tag mapping tests
+    // ==========================================================================
+
+    #[test]
+    fn test_push_mapped_tag_translates_and_dedups() {
+        let mut category_map = HashMap::new();
+        category_map.insert("Restaurants".to_string(), "dining".to_string());
+        category_map.insert("Transfers".to_string(), "".to_string());
+
+        let mut tags = Vec::new();
+        push_mapped_tag(&mut tags, " restaurants ", &category_map);
+        push_mapped_tag(&mut tags, "Dining", &category_map);
+        push_mapped_tag(&mut tags, "dining", &category_map);
+        push_mapped_tag(&mut tags, "Transfers", &category_map);
+        push_mapped_tag(&mut tags, "", &category_map);
+        push_mapped_tag(&mut tags, "Travel", &category_map);
+
+        assert_eq!(tags, vec!["dining", "Travel"]);
+    }
+
+    // ==========================================================================
+    // Export preset tests
+    // ==========================================================================
+
+    #[test]
+    fn test_parse_direction() {
+        assert_eq!(parse_direction("debit"), Some(true));
+        assert_eq!(parse_direction(" DR "), Some(true));
+        assert_eq!(parse_direction("Credit"), Some(false));
+        assert_eq!(parse_direction(""), None);
+        assert_eq!(parse_direction("pending"), None);
+    }
+
+    #[test]
+    fn test_ynab_preset_prefers_sub_category_in_ynab4_exports() {
+        let headers: Vec<String> = [
+            "Account",
+            "Flag",
+            "Check Number",
+            "Date",
+            "Payee",
+            "Category",
+            "Master Category",
+            "Sub Category",
+            "Memo",
+            "Outflow",
+            "Inflow",
+            "Cleared",
+            "Running Balance",
+        ]
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+
+        let mappings = ImportPreset::Ynab.mappings(&headers).unwrap();
+        assert_eq!(mappings.category.as_deref(), Some("Sub Category"));
+        assert_eq!(mappings.check_number.as_deref(), Some("Check Number"));
+        assert_eq!(mappings.debit.as_deref(), Some("Outflow"));
+        assert!(ImportPreset::Ynab.options().debit_negative);
+    }
+
+    #[test]
+    fn test_preset_rejects_other_layouts() {
+        let headers = vec!["Date".to_string(), "Amount".to_string()];
+        let err = ImportPreset::Mint.mappings(&headers).unwrap_err();
+        assert!(err.to_string().contains("Not a Mint export"));
+    }
+
+    #[test]
+    fn test_read_amazon_orders_sums_items_per_order() {
+        let headers: Vec<String> = ["Order ID", "Order Date", "Product Name", "Total Owed"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect();
+        let records = vec![
+            csv::StringRecord::from(vec!["111-1", "2024-02-28T10:00:00Z", "Cable", "$12.45"]),
+            csv::StringRecord::from(vec!["111-1", "2024-02-28T10:00:00Z", "Notebook", "$11.00"]),
+            csv::StringRecord::from(vec!["222-2", "2024-03-04T09:00:00Z", "Gift card", "$0.00"]),
+        ];
+
+        let orders = read_amazon_orders(&headers, &records).unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].total, Decimal::new(2345, 2));
+        assert_eq!(
+            orders[0].date,
+            NaiveDate::from_ymd_opt(2024, 2, 28).unwrap()
+        );
+        assert_eq!(
+            amazon_description(&orders[0].titles),
+            "Amazon: Cable, Notebook"
+        );
+    }
+
+    // ==========================================================================
+    // Balance impact tests
+    // ==========================================================================
+
+    fn impact_tx(date: &str, amount: i64) -> Transaction {
+        Transaction::new(
+            Uuid::new_v4(),
+            Uuid::nil(),
+            Decimal::new(amount, 2),
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+        )
+    }
+
+    fn impact_snapshot(date: &str, balance: i64) -> BalanceSnapshot {
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+        BalanceSnapshot::new(
+            Uuid::nil(),
+            Decimal::new(balance, 2),
+            NaiveDateTime::new(date, NaiveTime::from_hms_opt(23, 59, 59).unwrap()),
+        )
+    }
+
+    #[test]
+    fn test_balance_impact_monthly_deltas() {
+        let transactions = vec![
+            impact_tx("2024-01-05", 100000),
+            impact_tx("2024-01-20", -2500),
+            impact_tx("2024-02-03", -5000),
+        ];
+        let impact = compute_balance_impact(&transactions, &[None, None, None], None);
+
+        assert_eq!(impact.months.len(), 2);
+        assert_eq!(impact.months[0].month, "2024-01");
+        assert_eq!(impact.months[0].count, 2);
+        assert_eq!(impact.months[0].inflow, "1000.00");
+        assert_eq!(impact.months[0].outflow, "-25.00");
+        assert_eq!(impact.months[0].net, "975.00");
+        assert_eq!(impact.months[1].net, "-50.00");
+        assert_eq!(impact.net_change, "925.00");
+        assert!(impact.projected_balance.is_none());
+        assert!(impact.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_balance_impact_projects_from_latest_snapshot() {
+        let transactions = vec![
+            impact_tx("2024-01-05", -1000),
+            impact_tx("2024-01-10", -2000),
+            impact_tx("2024-01-15", 5000),
+        ];
+        let snapshot = impact_snapshot("2024-01-05", 50000);
+        let impact = compute_balance_impact(&transactions, &[None, None, None], Some(&snapshot));
+
+        // Only transactions after the snapshot date move the balance
+        assert_eq!(impact.projected_balance.as_deref(), Some("530.00"));
+        assert!(impact.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_balance_impact_flags_balance_crossing_zero() {
+        let transactions = vec![impact_tx("2024-01-10", -80000)];
+        let snapshot = impact_snapshot("2024-01-01", 50000);
+        let impact = compute_balance_impact(&transactions, &[None], Some(&snapshot));
+
+        assert_eq!(impact.projected_balance.as_deref(), Some("-300.00"));
+        assert_eq!(impact.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_balance_impact_detects_flipped_signs_from_balance_column() {
+        // Oldest-first rows where amounts have the opposite sign of the balance movement
+        let transactions = vec![
+            impact_tx("2024-01-01", 1000),
+            impact_tx("2024-01-02", 2000),
+            impact_tx("2024-01-03", -500),
+        ];
+        let balances = vec![
+            Some(Decimal::new(9000, 2)),
+            Some(Decimal::new(7000, 2)),
+            Some(Decimal::new(7500, 2)),
+        ];
+        let impact = compute_balance_impact(&transactions, &balances, None);
+        assert_eq!(impact.csv_end_balance.as_deref(), Some("75.00"));
+        assert_eq!(impact.warnings.len(), 1);
+
+        // Same file newest-first with correct signs: no warning
+        let transactions = vec![
+            impact_tx("2024-01-03", 500),
+            impact_tx("2024-01-02", -2000),
+            impact_tx("2024-01-01", -1000),
+        ];
+        let balances = vec![
+            Some(Decimal::new(7500, 2)),
+            Some(Decimal::new(7000, 2)),
+            Some(Decimal::new(9000, 2)),
+        ];
+        let impact = compute_balance_impact(&transactions, &balances, None);
+        assert_eq!(impact.csv_end_balance.as_deref(), Some("75.00"));
+        assert!(impact.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_balance_impact_compares_csv_balance_to_snapshot() {
+        let transactions = vec![
+            impact_tx("2024-01-01", -1000),
+            impact_tx("2024-01-05", -1000),
+        ];
+        let balances = vec![Some(Decimal::new(-9000, 2)), Some(Decimal::new(-10000, 2))];
+        let snapshot = impact_snapshot("2024-01-05", 10000);
+        let impact = compute_balance_impact(&transactions, &balances, Some(&snapshot));
+
+        assert_eq!(impact.warnings.len(), 1);
+        assert!(impact.warnings[0].contains("inverted"));
+    }
+
+    #[test]
+    fn test_reconcile_balances() {
+        let row = |line: u64, amount: i64, balance: i64| BalanceRow {
+            line,
+            account: String::new(),
+            amount: Decimal::new(amount, 2),
+            balance: Some(Decimal::new(balance, 2)),
+        };
+        // Oldest first: line 4's sign is reversed and a row is missing before line 6
+        let rows = [
+            row(2, 0, 100000),
+            row(3, -2500, 97500),
+            row(4, 1000, 96500),
+            row(5, -500, 96000),
+            row(6, -4000, 50000),
+            row(7, 20000, 70000),
+        ];
+        let found: Vec<(u64, BalanceMismatchKind)> = reconcile_balances(&rows)
+            .iter()
+            .map(|m| (m.line, m.kind))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (4, BalanceMismatchKind::SignReversed),
+                (6, BalanceMismatchKind::Gap)
+            ]
+        );
+
+        // Newest first, and consistent
+        let rows = [
+            row(2, 20000, 70000),
+            row(3, -500, 50000),
+            row(4, -2500, 50500),
+        ];
+        assert!(reconcile_balances(&rows).is_empty());
+
+        // A daily balance repeated on each row isn't a running balance
+        let rows = [
+            row(2, -100, 5000),
+            row(3, -200, 5000),
+            row(4, -300, 4700),
+            row(5, -400, 4700),
+        ];
+        assert!(reconcile_balances(&rows).is_empty());
+    }
+
+    #[test]
+    fn test_sign_warnings_follow_account_type() {
+        // A card statement exported with charges positive, and one payment
+        let mut transactions: Vec<Transaction> = (1..=9)
+            .map(|day| impact_tx(&format!("2024-01-{:02}", day), 2500))
+            .collect();
+        transactions.push(impact_tx("2024-01-20", -30000));
+
+        let mut card = Account::new(Uuid::nil(), "Visa");
+        card.account_type = Some("credit_card".to_string());
+        let warnings = sign_warnings(&transactions, &card, &ImportOptions::default());
+        assert_eq!(
+            warnings,
+            ["90% of transactions are positive on a credit card — did you mean --flip-signs?"]
+        );
+
+        // The same file is plausible for a checking account
+        let mut checking = Account::new(Uuid::nil(), "Checking");
+        checking.account_type = Some("checking".to_string());
+        assert!(sign_warnings(&transactions, &checking, &ImportOptions::default()).is_empty());
+
+        // A positive statement balance on a card is an amount owed entered the wrong way
+        let options = ImportOptions {
+            anchor_balance: Some(Decimal::new(50000, 2)),
+            ..Default::default()
+        };
+        let warnings = sign_warnings(&transactions[9..], &card, &options);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("did you mean -500.00?"));
+    }
+
+    // ==========================================================================
+    // CSV sniffing tests
+    // ==========================================================================
+
+    #[test]
+    fn test_detect_delimiter_single_line() {
+        assert_eq!(detect_delimiter("Date,Amount,Description"), b',');
+        assert_eq!(detect_delimiter("Date;Amount;Description"), b';');
+        assert_eq!(detect_delimiter("Date\tAmount\tDescription"), b'\t');
+        assert_eq!(detect_delimiter("Date|Amount|Description"), b'|');
+    }
+
+    #[test]
+    fn test_detect_delimiter_mixed_prefers_most_columns() {
+        assert_eq!(detect_delimiter("a;b;c;d,e"), b';');
+        assert_eq!(detect_delimiter("a,b,c,d;e"), b',');
+        assert_eq!(detect_delimiter("a\tb\tc\td,e,f"), b'\t');
+    }
+
+    #[test]
+    fn test_detect_delimiter_defaults_to_comma() {
+        assert_eq!(detect_delimiter("SingleColumn"), b',');
+        assert_eq!(detect_delimiter(""), b',');
+        // Equal counts: comma wins (US default)
+        assert_eq!(detect_delimiter("a,b;c"), b',');
+    }
+
+    #[test]
+    fn test_detect_delimiter_ignores_quoted_commas() {
+        // Header alone has more commas than semicolons, but they're all quoted
+        let sample = "\"Datum, Zeit\";Betrag;\"Text, Notiz, Info\"\n\
+                      \"01.01.2024, 10:00\";-1,50;\"Kaffee, Milch\"\n\
+                      \"02.01.2024, 11:00\";-2,75;\"Brot, Butter, Käse\"\n";
+        assert_eq!(detect_delimiter(sample), b';');
+    }
+
+    #[test]
+    fn test_detect_delimiter_uses_row_consistency() {
+        // EU decimals add commas to data rows, but only semicolons split consistently
+        let sample = "Datum;Betrag;Text\n01.01.2024;1,50;Kaffee\n02.01.2024;22,00;Tanken\n";
+        assert_eq!(detect_delimiter(sample), b';');
+    }
+
+    #[test]
+    fn test_detect_quote_style() {
+        assert_eq!(detect_quote_style("a,b\n1,2\n", b','), QuoteStyle::None);
+        assert_eq!(
+            detect_quote_style("\"a\",\"b\"\n\"1\",\"2, 3\"\n", b','),
+            QuoteStyle::All
+        );
+        assert_eq!(
+            detect_quote_style("a,b\n1,\"2, 3\"\n", b','),
+            QuoteStyle::Minimal
+        );
+    }
+
+    #[test]
+    fn test_decode_csv_bytes_encodings() {
+        let (text, enc) = decode_csv_bytes(b"Date,Amount");
+        assert_eq!((text.as_str(), enc), ("Date,Amount", CsvEncoding::Utf8));
+
+        let (text, enc) = decode_csv_bytes(b"\xEF\xBB\xBFDate,Amount");
+        assert_eq!((text.as_str(), enc), ("Date,Amount", CsvEncoding::Utf8Bom));
+
+        let (text, enc) = decode_csv_bytes(b"\xFF\xFED\x00,\x00A\x00");
+        assert_eq!((text.as_str(), enc), ("D,A", CsvEncoding::Utf16Le));
+
+        // Invalid UTF-8 falls back to Windows-1252
+        let (text, enc) = decode_csv_bytes(b"Caf\xE9 \x80");
+        assert_eq!((text.as_str(), enc), ("Café €", CsvEncoding::Windows1252));
+    }
+
+    #[test]
+    fn test_parse_header_line_cleans_names() {
+        let headers = parse_header_line("  #Date , Amount ,\"Description, Notes\"", b',').unwrap();
+        assert_eq!(headers, vec!["Date", "Amount", "Description, Notes"]);
+    }
+
+    #[test]
+    fn test_profile_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        let service = ImportService::new(repository, dir.path().to_path_buf());
+
+        // Saved by an older desktop version: skipRows under options, camelCase columns
+        std::fs::write(
+            dir.path().join("settings.json"),
+            r#"{"importProfiles": {"profiles": {"Bank": {
+                "columnMappings": {"date": "Posted", "amount": "Amt", "checkNumber": "Check"},
+                "options": {"skipRows": 3, "flipSigns": true}
+            }}, "accountMappings": {"acc-1": "Bank"}}}"#,
+        )
+        .unwrap();
+        let bank = service.get_profile("Bank").unwrap().unwrap();
+        assert_eq!(bank.skip_rows, 3);
+        assert_eq!(bank.column_mappings.check_number.as_deref(), Some("Check"));
+        assert!(bank.options.flip_signs);
+
+        service.insert_profile("Card", bank.clone()).unwrap();
+        assert!(service.insert_profile("  ", bank).is_err());
+        let mut names: Vec<String> = service.list_profiles().unwrap().into_keys().collect();
+        names.sort();
+        assert_eq!(names, ["Bank", "Card"]);
+
+        assert!(service.delete_profile("Bank").unwrap());
+        assert!(!service.delete_profile("Bank").unwrap());
+        assert_eq!(service.get_profile("Card").unwrap().unwrap().skip_rows, 3);
+
+        // Account mappings belong to the desktop app and survive
+        let settings = std::fs::read_to_string(dir.path().join("settings.json")).unwrap();
+        assert!(settings.contains("acc-1"));
+    }
+
+    #[test]
+    fn test_import_text_pasted_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        let account = Account::new(Uuid::new_v4(), "Checking");
+        repository.upsert_account(&account).unwrap();
+        let service = ImportService::new(repository, dir.path().to_path_buf());
+        let account_id = account.id.to_string();
+
+        // Copied from a bank's web table: tab-separated, with a trailing newline
+        let pasted =
+            "Date\tDescription\tAmount\n2024-01-02\tCoffee\t-4.50\n2024-01-03\tPaycheck\t1200.00\n";
+        let mappings = ColumnMappings::default();
+        let options = ImportOptions::default();
+
+        let preview = service
+            .import_text(pasted, &account_id, &mappings, &options, true)
+            .unwrap();
+        assert_eq!(preview.transactions.unwrap().len(), 2);
+
+        let result = service
+            .import_text(pasted, &account_id, &mappings, &options, false)
+            .unwrap();
+        assert_eq!(result.imported, 2);
+
+        assert!(service
+            .import_text("  \n", &account_id, &mappings, &options, false)
+            .is_err());
+    }
+
+    #[test]
+    fn test_import_without_header_row() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        let account = Account::new(Uuid::new_v4(), "Checking");
+        repository.upsert_account(&account).unwrap();
+        let service = ImportService::new(repository, dir.path().to_path_buf());
+        let account_id = account.id.to_string();
+
+        let content = "2024-01-02,POS,Coffee,-4.50\n2024-01-03,ACH,Paycheck,1200.00\n";
+        let options = ImportOptions {
+            no_header: true,
+            ..Default::default()
+        };
+        // Indexes and synthetic names can be mixed
+        let mappings = ColumnMappings {
+            date: "0".to_string(),
+            amount: "Column 4".to_string(),
+            description: Some("2".to_string()),
+            ..Default::default()
+        };
+
+        let preview = service
+            .import_text(content, &account_id, &mappings, &options, true)
+            .unwrap();
+        let transactions = preview.transactions.unwrap();
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0].date, "2024-01-02");
+        assert_eq!(transactions[0].description.as_deref(), Some("Coffee"));
+
+        // Without the option the first row is taken as the header
+        let with_header = ImportOptions::default();
+        assert!(service
+            .import_text(content, &account_id, &mappings, &with_header, true)
+            .is_err());
+
+        assert_eq!(
+            headerless_column_names(3),
+            ["Column 1", "Column 2", "Column 3"]
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_issues_before_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        let service = ImportService::new(repository, dir.path().to_path_buf());
+        let path = dir.path().join("statement.csv");
+        std::fs::write(
+            &path,
+            "Date,Description,Amount\n\
+             2024-01-02,Coffee,-4.50\n\
+             2024-13-40,Bad date,-1.00\n\
+             2024-01-04,Bad amount,abc\n\
+             2024-01-05,Ragged\n\
+             2024-01-06,Lunch,-12.00\n",
+        )
+        .unwrap();
+        let mappings = ColumnMappings {
+            balance: Some("Balance".to_string()),
+            ..Default::default()
+        };
+
+        let validation = service
+            .validate(&path, &mappings, &ImportOptions::default())
+            .unwrap();
+        assert_eq!((validation.rows, validation.valid_rows), (5, 2));
+        assert!(validation.importable);
+        let found: Vec<(ValidationIssueKind, Option<u64>)> = validation
+            .issues
+            .iter()
+            .map(|issue| (issue.kind, issue.line))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (ValidationIssueKind::MissingColumn, None),
+                (ValidationIssueKind::InvalidDate, Some(3)),
+                (ValidationIssueKind::InvalidAmount, Some(4)),
+                (ValidationIssueKind::ColumnCount, Some(5)),
+            ]
+        );
+        assert_eq!(validation.issues[3].message, "expected 3 fields, found 2");
+
+        // Without its date column nothing can be imported
+        let no_dates = ColumnMappings {
+            date: "Posted".to_string(),
+            ..Default::default()
+        };
+        let validation = service
+            .validate(&path, &no_dates, &ImportOptions::default())
+            .unwrap();
+        assert!(!validation.importable);
+        assert_eq!(validation.valid_rows, 0);
+
+        // Charges exported as positive amounts look like income
+        let charges: String = (1..=12)
+            .map(|day| format!("2024-02-{:02},Purchase,{}.00\n", day, day))
+            .collect();
+        std::fs::write(&path, format!("Date,Description,Amount\n{}", charges)).unwrap();
+        let validation = service
+            .validate(&path, &ColumnMappings::default(), &ImportOptions::default())
+            .unwrap();
+        assert_eq!(
+            validation.issues.last().map(|issue| issue.kind),
+            Some(ValidationIssueKind::SignDistribution)
+        );
+        let flipped = ImportOptions {
+            flip_signs: true,
+            ..Default::default()
+        };
+        let validation = service
+            .validate(&path, &ColumnMappings::default(), &flipped)
+            .unwrap();
+        assert!(validation.issues.is_empty());
+    }
+
+    #[test]
+    fn test_import_reports_progress_in_batches() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        let account = Account::new(Uuid::new_v4(), "Checking");
+        repository.upsert_account(&account).unwrap();
+
+        // Enough rows for two parse reports and two insert batches
+        let rows = INSERT_BATCH_ROWS + 1;
+        let mut csv = String::from("Date,Description,Amount\n");
+        for i in 0..rows {
+            let day = i % 28 + 1;
+            csv.push_str(&format!("2024-01-{day:02},Row {i},-{}.00\n", i + 1));
+        }
+        let path = dir.path().join("large.csv");
+        std::fs::write(&path, csv).unwrap();
+
+        let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = reports.clone();
+        let service = ImportService::new(repository.clone(), dir.path().to_path_buf())
+            .with_progress(move |p| seen.lock().unwrap().push((p.stage, p.done, p.total)));
+        let result = service
+            .import(
+                &path,
+                &account.id.to_string(),
+                &ColumnMappings::default(),
+                &ImportOptions::default(),
+                false,
+            )
+            .unwrap();
+        assert_eq!(result.imported, rows as i64);
+
+        let reports = reports.lock().unwrap();
+        let inserting: Vec<usize> = reports
+            .iter()
+            .filter(|(stage, _, _)| *stage == ImportStage::Inserting)
+            .map(|(_, done, _)| *done)
+            .collect();
+        assert_eq!(inserting, [0, INSERT_BATCH_ROWS, rows]);
+        assert!(reports.contains(&(ImportStage::Parsing, PROGRESS_EVERY_ROWS, rows)));
+        assert_eq!(reports.last(), Some(&(ImportStage::Finishing, 1, 1)));
+    }
+
+    #[test]
+    fn test_sign_policy_flips_imported_amounts() {
+        use crate::domain::SignPolicy;
+
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        let account = Account::new(Uuid::new_v4(), "Card");
+        repository.upsert_account(&account).unwrap();
+        let service = ImportService::new(repository.clone(), dir.path().to_path_buf());
+        let account_id = account.id.to_string();
+        let mappings = ColumnMappings::default();
+        let options = ImportOptions::default();
+        let statement = "Date,Description,Amount\n2024-01-02,Coffee,4.50\n";
+
+        service
+            .import_text(statement, &account_id, &mappings, &options, false)
+            .unwrap();
+        repository
+            .set_account_sign_policy(&account_id, SignPolicy::Inverted)
+            .unwrap();
+
+        // Fingerprints use the file's amount, so the row is still a duplicate
+        let preview = service
+            .import_text(statement, &account_id, &mappings, &options, true)
+            .unwrap();
+        assert_eq!(preview.duplicates, 1);
+
+        let statement = "Date,Description,Amount\n2024-01-03,Lunch,12.00\n";
+        let preview = service
+            .import_text(statement, &account_id, &mappings, &options, true)
+            .unwrap();
+        assert_eq!(preview.transactions.unwrap()[0].amount, "-12.00");
+    }
+
+    #[test]
+    fn test_preview_marks_duplicate_and_invalid_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        let account = Account::new(Uuid::new_v4(), "Checking");
+        repository.upsert_account(&account).unwrap();
+        let service = ImportService::new(repository, dir.path().to_path_buf());
+        let account_id = account.id.to_string();
+        let mappings = ColumnMappings::default();
+        let options = ImportOptions::default();
+
+        let first = "Date,Description,Amount\n\
+                     2024-01-02,Coffee,-4.50\n\
+                     2024-01-03,Paycheck,1200.00\n";
+        service
+            .import_text(first, &account_id, &mappings, &options, false)
+            .unwrap();
+
+        // Overlapping export: one old row, a second identical coffee, a bad row, a new row
+        let second = "Date,Description,Amount\n\
+                      2024-01-02,Coffee,-4.50\n\
+                      2024-01-02,Coffee,-4.50\n\
+                      2024-01-03,Rent,oops\n\
+                      2024-01-04,Lunch,-12.00\n";
+        let preview = service
+            .import_text(second, &account_id, &mappings, &options, true)
+            .unwrap();
+        let statuses: Vec<(u64, RowStatus)> = preview
+            .rows
+            .unwrap()
+            .iter()
+            .map(|r| (r.line, r.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                (2, RowStatus::New),
+                (3, RowStatus::Duplicate),
+                (4, RowStatus::Invalid),
+                (5, RowStatus::New),
+            ]
+        );
+        assert_eq!(preview.duplicates, 1);
+        assert_eq!(preview.skipped, 2);
+
+        // The import does what the preview said
+        let result = service
+            .import_text(second, &account_id, &mappings, &options, false)
+            .unwrap();
+        assert_eq!(result.imported, 2);
+        assert_eq!(result.duplicates, 1);
+    }
+
+    #[test]
+    fn test_undo_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        let account = Account::new(Uuid::new_v4(), "Checking");
+        repository.upsert_account(&account).unwrap();
+        let service = ImportService::new(repository.clone(), dir.path().to_path_buf());
+        let account_id = account.id.to_string();
+
+        let csv = "Date,Description,Amount,Balance\n\
+                   2024-01-02,Coffee,-4.50,95.50\n\
+                   2024-01-03,Paycheck,1200.00,1295.50\n";
+        let mappings = ColumnMappings {
+            balance: Some("Balance".to_string()),
+            ..ColumnMappings::default()
+        };
+        let options = ImportOptions::default();
+
+        // A manual snapshot from before the import survives the undo
+        repository
+            .add_balance_snapshot(&BalanceSnapshot::from_manual(
+                account.id,
+                Decimal::new(100, 0),
+                NaiveDate::from_ymd_opt(2024, 1, 1)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap(),
+            ))
+            .unwrap();
+
+        let result = service
+            .import_text(csv, &account_id, &mappings, &options, false)
+            .unwrap();
+        assert_eq!(result.imported, 2);
+        assert_eq!(result.balance_snapshots_created, 2);
+
+        let undone = service.undo(&result.batch_id).unwrap();
+        assert_eq!(undone.transactions_deleted, 2);
+        assert_eq!(undone.balance_snapshots_deleted, 2);
+        assert_eq!(repository.get_transaction_count().unwrap(), 0);
+        let snapshots = repository.get_balance_snapshots(Some(&account_id)).unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].source.as_deref(), Some("manual"));
+
+        // Nothing left to undo, and the same file imports again in full
+        assert!(service.undo(&result.batch_id).is_err());
+        let again = service
+            .import_text(csv, &account_id, &mappings, &options, false)
+            .unwrap();
+        assert_eq!(again.imported, 2);
+        assert_ne!(again.batch_id, result.batch_id);
+    }
+
+    #[test]
+    fn test_import_currency_column() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        let account = Account::new(Uuid::new_v4(), "Wise");
+        repository.upsert_account(&account).unwrap();
+        let service = ImportService::new(repository.clone(), dir.path().to_path_buf());
+        let account_id = account.id.to_string();
+
+        // Same day, amount and description in two currencies: both are kept
+        let csv = "Date,Description,Amount,Currency\n\
+                   2024-01-02,Top up,100.00,eur\n\
+                   2024-01-02,Top up,100.00,GBP\n\
+                   2024-01-03,Coffee,-3.00,\n\
+                   2024-01-04,Lunch,-12.00,euro\n";
+        let mappings = ColumnMappings {
+            currency: Some("Currency".to_string()),
+            ..Default::default()
+        };
+        let options = ImportOptions::default();
+        let result = service
+            .import_text(csv, &account_id, &mappings, &options, false)
+            .unwrap();
+        assert_eq!(result.imported, 3);
+        assert_eq!(result.errors[0].reason, "invalid currency code");
+
+        let mut currencies: Vec<Option<String>> = repository
+            .get_transactions_by_account(&account_id)
+            .unwrap()
+            .into_iter()
+            .map(|tx| tx.currency)
+            .collect();
+        currencies.sort();
+        assert_eq!(
+            currencies,
+            vec![None, Some("EUR".to_string()), Some("GBP".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_sniff_csv_with_skip_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.csv");
+        std::fs::write(
+            &path,
+            "\u{FEFF}Bank Statement\nDatum;Betrag;Text\n01.01.2024;-1,50;Kaffee\n",
+        )
+        .unwrap();
+
+        let sniffed = sniff_csv(&path, 1).unwrap();
+        assert_eq!(sniffed.headers, vec!["Datum", "Betrag", "Text"]);
+        assert_eq!(sniffed.delimiter, ";");
+        assert_eq!(sniffed.encoding, CsvEncoding::Utf8Bom);
+        assert_eq!(sniffed.quote_style, QuoteStyle::None);
+
+        assert!(sniff_csv(&path, 10).is_err());
+    }
+
+    #[test]
+    fn test_sniff_csv_reads_a_prefix_of_large_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("large.csv");
+        let mut content = String::from("Date,Amount,Description\n");
+        for _ in 0..5000 {
+            content.push_str("2024-01-01,-4.50,Café crème\n");
+        }
+        std::fs::write(&path, &content).unwrap();
+
+        // The prefix is cut at a line end, never inside the é
+        let sniffed = sniff_csv(&path, 0).unwrap();
+        assert_eq!(sniffed.headers, vec!["Date", "Amount", "Description"]);
+        assert_eq!(sniffed.encoding, CsvEncoding::Utf8);
+
+        let utf16: Vec<u8> = [0xFF, 0xFE]
+            .into_iter()
+            .chain(content.encode_utf16().flat_map(u16::to_le_bytes))
+            .collect();
+        let len = complete_lines_len(&utf16[..SNIFF_BYTES as usize]);
+        assert_eq!(len % 2, 0);
+        assert_eq!(&utf16[len - 2..len], &[0x0A, 0x00]);
+    }
 }
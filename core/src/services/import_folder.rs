@@ -0,0 +1,418 @@
+//! Import folder - batch import of the CSVs waiting in `imports/`
+//!
+//! Each file goes to an account that has an import profile assigned
+//! (settings.json `importProfiles.accountMappings`, set from the desktop
+//! import dialog). The account is chosen by its name or nickname appearing
+//! in the file name, or else by the file's header row fitting exactly one of
+//! those profiles. Imported files move to `imports/imported/`; files that
+//! match no account or fail to import stay where they are.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::adapters::duckdb::DuckDbRepository;
+use crate::config::{Config, ImportProfile};
+use crate::domain::Account;
+use crate::services::import::{sniff_csv, ImportOptions, ImportService};
+
+/// Folder watched for CSV files, inside the treeline directory
+pub const IMPORTS_DIR: &str = "imports";
+
+/// Subfolder of `IMPORTS_DIR` that imported files are moved to
+pub const IMPORTED_DIR: &str = "imported";
+
+/// Shortest account name matched against file names (avoids matching "cd")
+const MIN_NAME_MATCH_LEN: usize = 3;
+
+/// What happened to one file in the imports folder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FolderImportStatus {
+    /// Imported and moved to `imported/` (or would be, in a preview)
+    Imported,
+    /// No account with a profile matched; left in place
+    Unmatched,
+    /// The import failed; left in place
+    Failed,
+}
+
+/// One file in the imports folder
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct FolderImportFile {
+    pub file: String,
+    pub status: FolderImportStatus,
+    pub account_id: Option<String>,
+    pub account_name: Option<String>,
+    pub profile: Option<String>,
+    pub imported: i64,
+    /// Rows skipped as duplicates or unparseable
+    pub skipped: i64,
+    /// Where the file was moved to
+    pub moved_to: Option<PathBuf>,
+    /// Why the file was left in place
+    pub message: Option<String>,
+}
+
+impl FolderImportFile {
+    /// A file not (yet) matched to any account
+    pub fn new(path: &Path) -> Self {
+        Self {
+            file: path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            status: FolderImportStatus::Unmatched,
+            account_id: None,
+            account_name: None,
+            profile: None,
+            imported: 0,
+            skipped: 0,
+            moved_to: None,
+            message: None,
+        }
+    }
+}
+
+/// Result of importing everything in the imports folder
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct FolderImportSummary {
+    pub files: Vec<FolderImportFile>,
+    pub imported_files: usize,
+    pub unmatched_files: usize,
+    pub failed_files: usize,
+    pub transactions_imported: i64,
+    /// Nothing was imported or moved
+    pub preview: bool,
+}
+
+/// The imports folder inside `treeline_dir`
+pub fn imports_dir(treeline_dir: &Path) -> PathBuf {
+    treeline_dir.join(IMPORTS_DIR)
+}
+
+/// CSV files waiting in the imports folder, sorted by name
+///
+/// Creates the folder when it doesn't exist yet.
+pub fn list_pending_imports(treeline_dir: &Path) -> Result<Vec<PathBuf>> {
+    let dir = imports_dir(treeline_dir);
+    if !dir.exists() {
+        fs::create_dir_all(&dir).context("Failed to create imports directory")?;
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(&dir).context("Failed to read imports directory")? {
+        let path = entry.context("Failed to read directory entry")?.path();
+        let is_csv = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        if path.is_file() && is_csv {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Move an imported file to `imports/imported/`, returning where it went
+///
+/// A file of the same name already there gets a timestamp suffix.
+pub fn move_to_imported(treeline_dir: &Path, file: &Path) -> Result<PathBuf> {
+    if !file.exists() {
+        anyhow::bail!("File not found: {}", file.display());
+    }
+    let imported_dir = imports_dir(treeline_dir).join(IMPORTED_DIR);
+    fs::create_dir_all(&imported_dir).context("Failed to create imported directory")?;
+
+    let filename = file.file_name().context("Invalid file path")?;
+    let mut destination = imported_dir.join(filename);
+    if destination.exists() {
+        let stem = file.file_stem().and_then(|s| s.to_str()).unwrap_or("file");
+        let ext = file.extension().and_then(|s| s.to_str()).unwrap_or("csv");
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        destination = imported_dir.join(format!("{}_{}.{}", stem, timestamp, ext));
+    }
+
+    fs::rename(file, &destination).context("Failed to move file")?;
+    Ok(destination)
+}
+
+/// An account with its assigned profile
+struct Target {
+    account: Account,
+    profile_name: String,
+    profile: ImportProfile,
+}
+
+/// Import folder service for importing every pending file
+pub struct ImportFolderService {
+    repository: Arc<DuckDbRepository>,
+    treeline_dir: PathBuf,
+}
+
+impl ImportFolderService {
+    pub fn new(repository: Arc<DuckDbRepository>, treeline_dir: PathBuf) -> Self {
+        Self {
+            repository,
+            treeline_dir,
+        }
+    }
+
+    /// Import every pending file into its account and move it to `imported/`
+    ///
+    /// A file that fails doesn't stop the others. With `preview_only`,
+    /// nothing is imported or moved.
+    pub fn import_all(&self, preview_only: bool) -> Result<FolderImportSummary> {
+        let targets = self.targets()?;
+        let import_service =
+            ImportService::new(Arc::clone(&self.repository), self.treeline_dir.clone());
+
+        let files: Vec<FolderImportFile> = list_pending_imports(&self.treeline_dir)?
+            .iter()
+            .map(|path| self.import_one(&import_service, &targets, path, preview_only))
+            .collect();
+
+        let count = |status| files.iter().filter(|f| f.status == status).count();
+        Ok(FolderImportSummary {
+            imported_files: count(FolderImportStatus::Imported),
+            unmatched_files: count(FolderImportStatus::Unmatched),
+            failed_files: count(FolderImportStatus::Failed),
+            transactions_imported: files.iter().map(|f| f.imported).sum(),
+            files,
+            preview: preview_only,
+        })
+    }
+
+    /// Import one file from the imports folder, as `import_all` would
+    pub fn import_file(&self, path: &Path, preview_only: bool) -> Result<FolderImportFile> {
+        let targets = self.targets()?;
+        let import_service =
+            ImportService::new(Arc::clone(&self.repository), self.treeline_dir.clone());
+        Ok(self.import_one(&import_service, &targets, path, preview_only))
+    }
+
+    fn import_one(
+        &self,
+        import_service: &ImportService,
+        targets: &[Target],
+        path: &Path,
+        preview_only: bool,
+    ) -> FolderImportFile {
+        let mut file = FolderImportFile::new(path);
+
+        let Some(target) = match_target(path, targets) else {
+            file.message = Some(if targets.is_empty() {
+                "No account has an import profile assigned".to_string()
+            } else {
+                "No account with an import profile matches the file name or columns".to_string()
+            });
+            return file;
+        };
+        file.account_id = Some(target.account.id.to_string());
+        file.account_name = Some(target.account.name.clone());
+        file.profile = Some(target.profile_name.clone());
+
+        let imported = import_service
+            .import(
+                path,
+                &target.account.id.to_string(),
+                &target.profile.column_mappings,
+                &ImportOptions::from_profile(&target.profile),
+                preview_only,
+            )
+            .and_then(|result| {
+                file.imported = result.imported;
+                file.skipped = result.skipped;
+                if !preview_only {
+                    file.moved_to = Some(move_to_imported(&self.treeline_dir, path)?);
+                }
+                Ok(())
+            });
+        match imported {
+            Ok(()) => file.status = FolderImportStatus::Imported,
+            Err(e) => {
+                file.status = FolderImportStatus::Failed;
+                file.message = Some(e.to_string());
+            }
+        }
+        file
+    }
+
+    /// Accounts with a profile assigned whose profile still exists
+    fn targets(&self) -> Result<Vec<Target>> {
+        let config = Config::load(&self.treeline_dir)?;
+        let accounts = self.repository.get_accounts()?;
+        Ok(accounts
+            .into_iter()
+            .filter_map(|account| {
+                let profile_name = config
+                    .import_account_mappings
+                    .get(&account.id.to_string())?
+                    .clone();
+                let profile = config.import_profiles.get(&profile_name)?.clone();
+                Some(Target {
+                    account,
+                    profile_name,
+                    profile,
+                })
+            })
+            .collect())
+    }
+}
+
+/// The account a file belongs to: by name in the file name (longest wins),
+/// else by the only profile whose columns are in the file's header row
+fn match_target<'a>(path: &Path, targets: &'a [Target]) -> Option<&'a Target> {
+    let stem = normalize(&path.file_stem()?.to_string_lossy());
+    let mut by_name: Vec<(usize, &Target)> = targets
+        .iter()
+        .filter_map(|target| {
+            [Some(&target.account.name), target.account.nickname.as_ref()]
+                .into_iter()
+                .flatten()
+                .map(|name| normalize(name))
+                .filter(|name| name.len() >= MIN_NAME_MATCH_LEN && stem.contains(name.as_str()))
+                .map(|name| name.len())
+                .max()
+                .map(|len| (len, target))
+        })
+        .collect();
+    by_name.sort_by(|a, b| b.0.cmp(&a.0));
+    match by_name.as_slice() {
+        [(_, only)] => return Some(*only),
+        [(best, target), (next, _), ..] if best > next => return Some(*target),
+        _ => {}
+    }
+
+    let mut fitting = targets
+        .iter()
+        .filter(|target| profile_fits(path, &target.profile));
+    match (fitting.next(), fitting.next()) {
+        (Some(target), None) => Some(target),
+        _ => None,
+    }
+}
+
+/// Whether the file's header row has the profile's date and amount columns
+fn profile_fits(path: &Path, profile: &ImportProfile) -> bool {
+    let Ok(sniffed) = sniff_csv(path, profile.skip_rows as u32) else {
+        return false;
+    };
+    let has = |column: &str| sniffed.headers.iter().any(|h| h == column);
+    let mappings = &profile.column_mappings;
+    let has_amount = has(&mappings.amount)
+        || mappings.debit.as_deref().is_some_and(has)
+        || mappings.credit.as_deref().is_some_and(has);
+    has(&mappings.date) && has_amount
+}
+
+/// Lowercase letters and digits only, so "Chase Checking" matches "chase_checking_2024"
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn profile(date: &str, amount: &str) -> serde_json::Value {
+        serde_json::json!({"columnMappings": {"date": date, "amount": amount}})
+    }
+
+    fn write_settings(dir: &Path, mappings: &[(&Account, &str)]) {
+        let account_mappings: serde_json::Map<String, serde_json::Value> = mappings
+            .iter()
+            .map(|(account, profile)| (account.id.to_string(), (*profile).into()))
+            .collect();
+        let settings = serde_json::json!({"importProfiles": {
+            "profiles": {
+                "chase": profile("Posting Date", "Amount"),
+                "amex": profile("Date", "Charge"),
+            },
+            "accountMappings": account_mappings,
+        }});
+        fs::write(dir.join("settings.json"), settings.to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_import_all_matches_by_name_then_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        let checking = Account::new(Uuid::new_v4(), "Chase Checking");
+        let card = Account::new(Uuid::new_v4(), "Amex Gold");
+        repository.upsert_account(&checking).unwrap();
+        repository.upsert_account(&card).unwrap();
+        write_settings(dir.path(), &[(&checking, "chase"), (&card, "amex")]);
+
+        let imports = imports_dir(dir.path());
+        fs::create_dir_all(&imports).unwrap();
+        fs::write(
+            imports.join("chase_checking_may.csv"),
+            "Posting Date,Description,Amount\n2024-05-01,Coffee,-4.50\n2024-05-02,Pay,1000\n",
+        )
+        .unwrap();
+        fs::write(
+            imports.join("activity.csv"),
+            "Date,Description,Charge\n2024-05-03,Books,-20\n",
+        )
+        .unwrap();
+        fs::write(
+            imports.join("unknown.csv"),
+            "When,What,How Much\n2024-05-04,x,1\n",
+        )
+        .unwrap();
+
+        let service = ImportFolderService::new(Arc::clone(&repository), dir.path().to_path_buf());
+        let preview = service.import_all(true).unwrap();
+        assert!(preview.preview);
+        assert_eq!(preview.imported_files, 2);
+        assert_eq!(repository.get_transaction_count().unwrap(), 0);
+        assert_eq!(list_pending_imports(dir.path()).unwrap().len(), 3);
+
+        let summary = service.import_all(false).unwrap();
+        assert_eq!(summary.imported_files, 2);
+        assert_eq!(summary.unmatched_files, 1);
+        assert_eq!(summary.transactions_imported, 3);
+        let activity = &summary.files[0];
+        assert_eq!(activity.file, "activity.csv");
+        assert_eq!(activity.account_name.as_deref(), Some("Amex Gold"));
+        assert_eq!(summary.files[1].profile.as_deref(), Some("chase"));
+        assert_eq!(summary.files[2].status, FolderImportStatus::Unmatched);
+
+        let pending = list_pending_imports(dir.path()).unwrap();
+        assert_eq!(pending, [imports.join("unknown.csv")]);
+        assert!(imports.join(IMPORTED_DIR).join("activity.csv").is_file());
+
+        let unknown = service.import_file(&pending[0], false).unwrap();
+        assert_eq!(unknown.status, FolderImportStatus::Unmatched);
+        assert!(pending[0].exists());
+    }
+
+    #[test]
+    fn test_move_to_imported_keeps_existing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let imports = imports_dir(dir.path());
+        fs::create_dir_all(imports.join(IMPORTED_DIR)).unwrap();
+        fs::write(imports.join(IMPORTED_DIR).join("may.csv"), "old").unwrap();
+        fs::write(imports.join("may.csv"), "new").unwrap();
+
+        let moved = move_to_imported(dir.path(), &imports.join("may.csv")).unwrap();
+        assert_ne!(moved, imports.join(IMPORTED_DIR).join("may.csv"));
+        assert_eq!(fs::read_to_string(moved).unwrap(), "new");
+        assert!(!imports.join("may.csv").exists());
+    }
+}
@@ -0,0 +1,515 @@
+//! Income service - recurring income streams detected from deposits
+//!
+//! Deposits into asset accounts are grouped by payer (the description with
+//! numbers and ACH noise stripped) and account. A group becomes a stream when
+//! it has at least three deposits whose gaps mostly fit one cadence: weekly,
+//! biweekly, twice a month or monthly. Each stream gets its typical amount,
+//! how much that varies, a monthly equivalent and the next expected payday,
+//! which is what budgets and forecasts build on. Projected paydays that land
+//! on a weekend or bank holiday move to the business day before, as
+//! employers pay early rather than late.
+//!
+//! Transactions tagged `transfer` are ignored, so moving money between your
+//! own accounts doesn't look like income, and so are transactions excluded
+//! from reports (a reimbursement isn't pay).
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::adapters::duckdb::DuckDbRepository;
+use crate::domain::{BusinessCalendar, Roll};
+
+/// Fewest deposits that make a stream
+const MIN_DEPOSITS: usize = 3;
+/// Share of gaps that must fit the cadence, in percent
+const MIN_REGULARITY_PERCENT: usize = 80;
+/// Words in bank descriptions that say nothing about the payer
+const NOISE_WORDS: &[&str] = &[
+    "ach", "co", "dep", "deposit", "des", "dir", "direct", "from", "id", "indn", "ppd", "web",
+];
+
+/// How often an income stream pays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cadence {
+    Weekly,
+    Biweekly,
+    /// Twice a month on fixed days, e.g. the 15th and the last day
+    SemiMonthly,
+    Monthly,
+}
+
+impl Cadence {
+    /// Payments in a year
+    pub fn per_year(&self) -> u32 {
+        match self {
+            Cadence::Weekly => 52,
+            Cadence::Biweekly => 26,
+            Cadence::SemiMonthly => 24,
+            Cadence::Monthly => 12,
+        }
+    }
+
+    /// Gaps in days that fit this cadence, allowing for weekends and holidays
+    fn accepts(&self, days: i64) -> bool {
+        match self {
+            Cadence::Weekly => (5..=9).contains(&days),
+            Cadence::Biweekly => (12..=16).contains(&days),
+            Cadence::SemiMonthly => (12..=19).contains(&days),
+            Cadence::Monthly => (26..=35).contains(&days),
+        }
+    }
+
+    /// The cadence most gaps between deposits fit, if any
+    fn detect(gaps: &[i64]) -> Option<Self> {
+        let mut sorted = gaps.to_vec();
+        sorted.sort_unstable();
+        let median = *sorted.get(sorted.len() / 2)?;
+        let cadence = match median {
+            5..=9 => Cadence::Weekly,
+            // Biweekly pay is always 14 days apart give or take a holiday;
+            // twice-monthly pay swings between 13 and 17 with month lengths
+            12..=17 => {
+                let steady = gaps
+                    .iter()
+                    .filter(|d| (12..=17).contains(*d))
+                    .all(|d| (13..=15).contains(d));
+                if median == 14 && steady {
+                    Cadence::Biweekly
+                } else {
+                    Cadence::SemiMonthly
+                }
+            }
+            26..=35 => Cadence::Monthly,
+            _ => return None,
+        };
+        let fitting = gaps.iter().filter(|d| cadence.accepts(**d)).count();
+        (fitting * 100 >= gaps.len() * MIN_REGULARITY_PERCENT).then_some(cadence)
+    }
+}
+
+/// A recurring source of income
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct IncomeStream {
+    /// Payer, from the latest deposit's description
+    pub name: String,
+    pub account_id: String,
+    pub account_name: String,
+    pub currency: String,
+    pub cadence: Cadence,
+    pub deposits: usize,
+    #[schemars(with = "String")]
+    pub average_amount: Decimal,
+    #[schemars(with = "String")]
+    pub last_amount: Decimal,
+    #[schemars(with = "String")]
+    pub min_amount: Decimal,
+    #[schemars(with = "String")]
+    pub max_amount: Decimal,
+    /// Standard deviation of the amounts relative to their average, in percent
+    pub amount_variation_percent: f64,
+    /// Average amount spread over a month
+    #[schemars(with = "String")]
+    pub monthly_amount: Decimal,
+    pub first_date: NaiveDate,
+    pub last_date: NaiveDate,
+    /// None once a deposit is well overdue, e.g. after a job change
+    pub next_date: Option<NaiveDate>,
+}
+
+/// A deposit expected from a stream
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ExpectedDeposit {
+    pub date: NaiveDate,
+    pub name: String,
+    pub account_id: String,
+    pub currency: String,
+    /// The stream's average amount
+    #[schemars(with = "String")]
+    pub amount: Decimal,
+}
+
+/// Income streams with what they add up to, for `tl income`
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct IncomeSummary {
+    /// Largest monthly amount first
+    pub streams: Vec<IncomeStream>,
+    /// Monthly income from streams still paying, by currency
+    #[schemars(with = "BTreeMap<String, String>")]
+    pub monthly_totals: BTreeMap<String, Decimal>,
+    /// Deposits expected over the next 30 days, soonest first
+    pub upcoming: Vec<ExpectedDeposit>,
+}
+
+/// One deposit considered for a stream
+struct Deposit {
+    date: NaiveDate,
+    amount: Decimal,
+    description: String,
+}
+
+/// Income service for detecting and projecting recurring income
+pub struct IncomeService {
+    repository: Arc<DuckDbRepository>,
+    calendar: BusinessCalendar,
+}
+
+impl IncomeService {
+    pub fn new(repository: Arc<DuckDbRepository>) -> Self {
+        Self {
+            repository,
+            calendar: BusinessCalendar::default(),
+        }
+    }
+
+    /// This service, projecting paydays around `calendar`'s holidays rather
+    /// than weekends alone
+    pub fn with_calendar(self, calendar: BusinessCalendar) -> Self {
+        Self { calendar, ..self }
+    }
+
+    /// Streams found in the deposits of the last `months` months, as of `today`
+    pub fn streams(&self, today: NaiveDate, months: u32) -> Result<Vec<IncomeStream>> {
+        let since = today - Months::new(months);
+        let accounts: HashMap<String, (String, String)> = self
+            .repository
+            .get_accounts()?
+            .into_iter()
+            .filter(|a| a.classification.as_deref() != Some("liability"))
+            .map(|a| {
+                let name = a.nickname.unwrap_or(a.name);
+                (a.id.to_string(), (name, a.currency))
+            })
+            .collect();
+
+        // Keyed by account ID and payer
+        let mut groups: HashMap<(String, String), Vec<Deposit>> = HashMap::new();
+        for tx in self.repository.get_transactions()? {
+            let account_id = tx.account_id.to_string();
+            if tx.amount <= Decimal::ZERO
+                || tx.transaction_date < since
+                || tx.transaction_date > today
+                || !accounts.contains_key(&account_id)
+                || tx.excluded_from_reports
+                || tx.tags.iter().any(|t| t == "transfer")
+            {
+                continue;
+            }
+            let description = tx.description.unwrap_or_default();
+            let key = payer_key(&description);
+            if key.is_empty() {
+                continue;
+            }
+            groups.entry((account_id, key)).or_default().push(Deposit {
+                date: tx.transaction_date,
+                amount: tx.amount,
+                description: description.trim().to_string(),
+            });
+        }
+
+        let mut streams = Vec::new();
+        for ((account_id, _), mut deposits) in groups {
+            if deposits.len() < MIN_DEPOSITS {
+                continue;
+            }
+            deposits.sort_by_key(|d| d.date);
+            let gaps: Vec<i64> = deposits
+                .windows(2)
+                .map(|w| (w[1].date - w[0].date).num_days())
+                .collect();
+            let Some(cadence) = Cadence::detect(&gaps) else {
+                continue;
+            };
+
+            let amounts: Vec<Decimal> = deposits.iter().map(|d| d.amount).collect();
+            let count = Decimal::from(amounts.len());
+            let average = amounts.iter().sum::<Decimal>() / count;
+            let variance = amounts
+                .iter()
+                .map(|a| (*a - average) * (*a - average))
+                .sum::<Decimal>()
+                / count;
+            let variation = match (variance.to_f64(), average.to_f64()) {
+                (Some(variance), Some(average)) if average > 0.0 => {
+                    variance.sqrt() / average * 100.0
+                }
+                _ => 0.0,
+            };
+
+            let first_date = deposits[0].date;
+            let typical_gap = median(gaps.iter().copied().filter(|d| cadence.accepts(*d)));
+            let typical_day = median(deposits.iter().map(|d| i64::from(d.date.day())));
+            let Some(last) = deposits.pop() else {
+                continue;
+            };
+            let last_date = last.date;
+            let next = self.calendar.roll(
+                next_payday(cadence, last_date, typical_gap, typical_day as u32),
+                Roll::Preceding,
+            );
+            // Overdue by more than half a period means the stream has stopped
+            let grace = Duration::days(365 / i64::from(cadence.per_year()) / 2);
+            let (account_name, currency) = accounts[&account_id].clone();
+
+            streams.push(IncomeStream {
+                name: last.description,
+                account_id,
+                account_name,
+                currency,
+                cadence,
+                deposits: amounts.len(),
+                average_amount: average.round_dp(2),
+                last_amount: last.amount,
+                min_amount: amounts.iter().copied().min().unwrap_or_default(),
+                max_amount: amounts.iter().copied().max().unwrap_or_default(),
+                amount_variation_percent: (variation * 10.0).round() / 10.0,
+                monthly_amount: (average * Decimal::from(cadence.per_year()) / Decimal::from(12))
+                    .round_dp(2),
+                first_date,
+                last_date,
+                next_date: (today <= next + grace).then_some(next),
+            });
+        }
+        streams.sort_by(|a, b| {
+            b.monthly_amount
+                .cmp(&a.monthly_amount)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(streams)
+    }
+
+    /// Streams, monthly totals and the deposits expected in the next 30 days
+    pub fn summary(&self, today: NaiveDate, months: u32) -> Result<IncomeSummary> {
+        let streams = self.streams(today, months)?;
+        let mut monthly_totals: BTreeMap<String, Decimal> = BTreeMap::new();
+        for stream in streams.iter().filter(|s| s.next_date.is_some()) {
+            *monthly_totals.entry(stream.currency.clone()).or_default() += stream.monthly_amount;
+        }
+        let upcoming =
+            expected_deposits(&streams, today, today + Duration::days(30), &self.calendar);
+        Ok(IncomeSummary {
+            streams,
+            monthly_totals,
+            upcoming,
+        })
+    }
+}
+
+/// Deposits the streams should make between `start` and `end`, soonest first
+///
+/// Overdue deposits that haven't arrived yet are expected on `start`. Each
+/// payday is scheduled from the one before it as it would have fallen, not
+/// as `calendar` moved it, so a holiday doesn't shift the ones after.
+pub fn expected_deposits(
+    streams: &[IncomeStream],
+    start: NaiveDate,
+    end: NaiveDate,
+    calendar: &BusinessCalendar,
+) -> Vec<ExpectedDeposit> {
+    let mut expected = Vec::new();
+    for stream in streams {
+        let Some(next) = stream.next_date else {
+            continue;
+        };
+        let gap = 365 / i64::from(stream.cadence.per_year());
+        let typical_day = stream.last_date.day();
+        // The next payday as it would have fallen, when that's what moved it
+        let unmoved = next_payday(stream.cadence, stream.last_date, gap, typical_day);
+        let mut scheduled = if calendar.roll(unmoved, Roll::Preceding) == next {
+            unmoved
+        } else {
+            next
+        };
+        loop {
+            let date = calendar.roll(scheduled, Roll::Preceding);
+            if date > end {
+                break;
+            }
+            expected.push(ExpectedDeposit {
+                date: date.max(start),
+                name: stream.name.clone(),
+                account_id: stream.account_id.clone(),
+                currency: stream.currency.clone(),
+                amount: stream.average_amount,
+            });
+            scheduled = next_payday(stream.cadence, scheduled, gap, typical_day);
+        }
+    }
+    expected.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.name.cmp(&b.name)));
+    expected
+}
+
+/// The payday after `last`, before moving it off weekends and holidays
+fn next_payday(cadence: Cadence, last: NaiveDate, typical_gap: i64, typical_day: u32) -> NaiveDate {
+    match cadence {
+        Cadence::Weekly => last + Duration::days(7),
+        Cadence::Biweekly => last + Duration::days(14),
+        Cadence::SemiMonthly => last + Duration::days(typical_gap),
+        Cadence::Monthly => {
+            let next_month = last + Months::new(1);
+            (1..=typical_day.max(1))
+                .rev()
+                .find_map(|day| next_month.with_day(day))
+                .unwrap_or(next_month)
+        }
+    }
+}
+
+/// Words that identify the payer: lowercase letters only, noise removed
+fn payer_key(description: &str) -> String {
+    description
+        .to_lowercase()
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| word.len() > 1 && !NOISE_WORDS.contains(word))
+        .take(3)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn median(values: impl Iterator<Item = i64>) -> i64 {
+    let mut values: Vec<i64> = values.collect();
+    values.sort_unstable();
+    values.get(values.len() / 2).copied().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Account, HolidayCalendar, Transaction};
+    use uuid::Uuid;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_cadence_detection() {
+        assert_eq!(Cadence::detect(&[7, 7, 6, 8]), Some(Cadence::Weekly));
+        assert_eq!(
+            Cadence::detect(&[14, 14, 13, 15, 14]),
+            Some(Cadence::Biweekly)
+        );
+        // 1st and 15th
+        assert_eq!(
+            Cadence::detect(&[14, 17, 14, 16, 14, 15]),
+            Some(Cadence::SemiMonthly)
+        );
+        assert_eq!(Cadence::detect(&[31, 28, 31, 30]), Some(Cadence::Monthly));
+        // A missed month still reads as monthly when the rest are regular
+        assert_eq!(
+            Cadence::detect(&[31, 30, 31, 61, 31, 30]),
+            Some(Cadence::Monthly)
+        );
+        assert_eq!(Cadence::detect(&[3, 40, 90, 12]), None);
+        assert_eq!(Cadence::detect(&[]), None);
+    }
+
+    #[test]
+    fn test_payer_key_drops_numbers_and_noise() {
+        assert_eq!(
+            payer_key("ACME CORP PAYROLL PPD ID: 99812"),
+            "acme corp payroll"
+        );
+        assert_eq!(payer_key("DIRECT DEP 0331 ACME CORP"), "acme corp");
+        assert_eq!(payer_key("#4411"), "");
+    }
+
+    #[test]
+    fn test_detects_paycheck_and_predicts_next_payday() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        let account = Account::new(Uuid::new_v4(), "Checking");
+        repository.upsert_account(&account).unwrap();
+
+        let tx = |amount: i64, day: &str, description: &str| {
+            let mut tx = Transaction::new(
+                Uuid::new_v4(),
+                account.id,
+                Decimal::new(amount, 2),
+                date(day),
+            );
+            tx.description = Some(description.to_string());
+            tx
+        };
+        let mut transactions = Vec::new();
+        let mut day = date("2025-01-03");
+        for (i, amount) in [250000, 250000, 251200, 250000, 248800, 250000]
+            .into_iter()
+            .enumerate()
+        {
+            transactions.push(tx(amount, &day.to_string(), &format!("ACME PAYROLL {}", i)));
+            day += Duration::days(14);
+        }
+        // Irregular deposits and a transfer aren't streams
+        transactions.push(tx(4000, "2025-01-20", "Venmo from Sam"));
+        transactions.push(tx(6000, "2025-02-11", "Venmo from Sam"));
+        transactions.push(tx(3500, "2025-03-02", "Venmo from Sam"));
+        for day in ["2025-01-05", "2025-02-05", "2025-03-05"] {
+            let mut transfer = tx(50000, day, "Online transfer from savings");
+            transfer.tags = vec!["transfer".to_string()];
+            transactions.push(transfer);
+        }
+        repository.bulk_insert_transactions(&transactions).unwrap();
+
+        let service = IncomeService::new(repository);
+        let summary = service.summary(date("2025-03-20"), 12).unwrap();
+        assert_eq!(summary.streams.len(), 1);
+        let paycheck = &summary.streams[0];
+        assert_eq!(paycheck.cadence, Cadence::Biweekly);
+        assert_eq!(paycheck.deposits, 6);
+        assert_eq!(paycheck.average_amount, Decimal::new(250000, 2));
+        assert_eq!(paycheck.monthly_amount, Decimal::new(541667, 2));
+        assert!(paycheck.amount_variation_percent > 0.0);
+        assert_eq!(paycheck.last_date, date("2025-03-14"));
+        assert_eq!(paycheck.next_date, Some(date("2025-03-28")));
+        assert_eq!(summary.monthly_totals["USD"], Decimal::new(541667, 2));
+        let upcoming: Vec<NaiveDate> = summary.upcoming.iter().map(|d| d.date).collect();
+        assert_eq!(upcoming, [date("2025-03-28"), date("2025-04-11")]);
+
+        // Months later with no new deposits, the stream has stopped
+        let later = service.streams(date("2025-06-01"), 12).unwrap();
+        assert_eq!(later[0].next_date, None);
+    }
+
+    #[test]
+    fn test_expected_deposits_move_around_holidays() {
+        let stream = IncomeStream {
+            name: "ACME PAYROLL".to_string(),
+            account_id: Uuid::new_v4().to_string(),
+            account_name: "Checking".to_string(),
+            currency: "USD".to_string(),
+            cadence: Cadence::Biweekly,
+            deposits: 6,
+            average_amount: Decimal::new(250000, 2),
+            last_amount: Decimal::new(250000, 2),
+            min_amount: Decimal::new(250000, 2),
+            max_amount: Decimal::new(250000, 2),
+            amount_variation_percent: 0.0,
+            monthly_amount: Decimal::new(541667, 2),
+            first_date: date("2025-04-11"),
+            last_date: date("2025-06-20"),
+            // Friday July 4th is paid the day before
+            next_date: Some(date("2025-07-03")),
+        };
+        let calendar = BusinessCalendar::new(HolidayCalendar::Us, []);
+        let dates: Vec<NaiveDate> =
+            expected_deposits(&[stream], date("2025-06-25"), date("2025-08-01"), &calendar)
+                .iter()
+                .map(|d| d.date)
+                .collect();
+        // Later paydays are still Fridays
+        assert_eq!(
+            dates,
+            [date("2025-07-03"), date("2025-07-18"), date("2025-08-01")]
+        );
+    }
+}
@@ -0,0 +1,436 @@
+//! Institution service - bank names and logos for display
+//!
+//! Accounts carry the web domain of their bank (SimpleFIN reports it, or it
+//! comes from the institution URL). The app shows each bank's logo, so
+//! logos are fetched once and kept in `cache/institutions/` in the data
+//! directory; from then on they're read from disk, offline included.
+//!
+//! Logos come straight from the bank's own site (its touch icon, then its
+//! favicon), never a third-party favicon service, so nobody else learns
+//! which banks you use. `list` only reads the cache; nothing is fetched
+//! until `refresh` is called. A bank whose logo couldn't be fetched isn't
+//! tried again for a week.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+#[cfg(feature = "http")]
+use std::time::Duration as StdDuration;
+
+#[cfg(feature = "http")]
+use anyhow::Context;
+use anyhow::Result;
+use base64::Engine;
+#[cfg(feature = "http")]
+use chrono::Duration;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use url::{Host, Url};
+
+use crate::adapters::duckdb::DuckDbRepository;
+use crate::json_file::{read_json, write_json};
+
+/// Where logos are kept, under the data directory
+const CACHE_DIR: &str = "cache/institutions";
+
+const INDEX_FILE: &str = "index.json";
+
+/// Paths tried on the bank's site, best first
+#[cfg(feature = "http")]
+const LOGO_PATHS: &[&str] = &["/apple-touch-icon.png", "/favicon.ico"];
+
+/// Larger responses aren't icons
+#[cfg(feature = "http")]
+const MAX_LOGO_BYTES: usize = 512 * 1024;
+
+/// Days before a failed fetch is tried again
+#[cfg(feature = "http")]
+const RETRY_FAILED_DAYS: i64 = 7;
+
+#[cfg(feature = "http")]
+const FETCH_TIMEOUT: StdDuration = StdDuration::from_secs(10);
+
+/// What's cached for one domain
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LogoEntry {
+    /// File in the cache directory; None when no logo was found
+    file: Option<String>,
+    mime: Option<String>,
+    checked_at: Option<DateTime<Utc>>,
+}
+
+/// A bank, as shown next to its accounts
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct InstitutionInfo {
+    /// e.g. "chase.com"
+    pub domain: String,
+    /// The name most of its accounts use
+    pub name: Option<String>,
+    pub url: Option<String>,
+    /// Accounts at this bank
+    pub account_ids: Vec<String>,
+    /// Cached logo as a data URL; None until fetched, or when the bank has none
+    pub logo: Option<String>,
+    /// When the logo was last fetched or found missing
+    pub checked_at: Option<DateTime<Utc>>,
+}
+
+/// Outcome of fetching logos
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct InstitutionRefreshResult {
+    /// Domains whose logo was fetched
+    pub fetched: Vec<String>,
+    /// Domains with no logo to fetch, or that couldn't be reached
+    pub failed: Vec<String>,
+    /// Domains already cached, or that failed within the last week
+    pub skipped: usize,
+}
+
+/// Resolves and caches bank display names and logos
+pub struct InstitutionService {
+    repository: Arc<DuckDbRepository>,
+    treeline_dir: PathBuf,
+}
+
+impl InstitutionService {
+    pub fn new(repository: Arc<DuckDbRepository>, treeline_dir: PathBuf) -> Self {
+        Self {
+            repository,
+            treeline_dir,
+        }
+    }
+
+    /// Banks with accounts, with whatever logos are cached; never fetches
+    pub fn list(&self) -> Result<Vec<InstitutionInfo>> {
+        let index = self.load_index()?;
+        let mut institutions = self.institutions()?;
+        for institution in &mut institutions {
+            let Some(entry) = index.get(&institution.domain) else {
+                continue;
+            };
+            institution.checked_at = entry.checked_at;
+            if let (Some(file), Some(mime)) = (&entry.file, &entry.mime) {
+                // A logo deleted from the cache is fetched again on refresh
+                if let Ok(bytes) = fs::read(self.cache_dir().join(file)) {
+                    institution.logo = Some(data_url(mime, &bytes));
+                }
+            }
+        }
+        Ok(institutions)
+    }
+
+    /// Fetch logos for banks that don't have one cached yet
+    ///
+    /// With `force`, every bank's logo is fetched again.
+    #[cfg(feature = "http")]
+    pub fn refresh(&self, force: bool) -> Result<InstitutionRefreshResult> {
+        let mut index = self.load_index()?;
+        let mut result = InstitutionRefreshResult::default();
+        let retry_before = Utc::now() - Duration::days(RETRY_FAILED_DAYS);
+        let client = reqwest::blocking::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .build()?;
+
+        for institution in self.institutions()? {
+            let domain = institution.domain;
+            let due = match index.get(&domain) {
+                _ if force => true,
+                None => true,
+                Some(entry) => match &entry.file {
+                    Some(file) => !self.cache_dir().join(file).exists(),
+                    None => entry.checked_at.is_none_or(|at| at < retry_before),
+                },
+            };
+            if !due {
+                result.skipped += 1;
+                continue;
+            }
+
+            let mut entry = LogoEntry {
+                checked_at: Some(Utc::now()),
+                ..Default::default()
+            };
+            match fetch_logo(&client, &domain) {
+                Some((mime, bytes)) => {
+                    let file = format!("{}.{}", domain, extension(mime));
+                    fs::create_dir_all(self.cache_dir())?;
+                    fs::write(self.cache_dir().join(&file), bytes)
+                        .with_context(|| format!("Failed to cache the logo for {}", domain))?;
+                    entry.file = Some(file);
+                    entry.mime = Some(mime.to_string());
+                    result.fetched.push(domain.clone());
+                }
+                None => result.failed.push(domain.clone()),
+            }
+            index.insert(domain, entry);
+        }
+
+        self.save_index(&index)?;
+        Ok(result)
+    }
+
+    /// One entry per domain, from the accounts that have one
+    fn institutions(&self) -> Result<Vec<InstitutionInfo>> {
+        let mut names: HashMap<String, HashMap<String, usize>> = HashMap::new();
+        let mut institutions: BTreeMap<String, InstitutionInfo> = BTreeMap::new();
+        for account in self.repository.get_accounts()? {
+            let domain = account
+                .institution_domain
+                .as_deref()
+                .or(account.institution_url.as_deref())
+                .and_then(normalize_domain);
+            let Some(domain) = domain else {
+                continue;
+            };
+            let institution =
+                institutions
+                    .entry(domain.clone())
+                    .or_insert_with(|| InstitutionInfo {
+                        domain: domain.clone(),
+                        name: None,
+                        url: None,
+                        account_ids: Vec::new(),
+                        logo: None,
+                        checked_at: None,
+                    });
+            institution.account_ids.push(account.id.to_string());
+            if institution.url.is_none() {
+                institution.url = account.institution_url.clone();
+            }
+            if let Some(name) = account.institution_name.filter(|n| !n.trim().is_empty()) {
+                *names.entry(domain).or_default().entry(name).or_default() += 1;
+            }
+        }
+
+        Ok(institutions
+            .into_values()
+            .map(|mut institution| {
+                // Most used name, alphabetical on a tie so it doesn't flip
+                institution.name = names.get(&institution.domain).and_then(|counts| {
+                    counts
+                        .iter()
+                        .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+                        .map(|(name, _)| name.clone())
+                });
+                institution
+            })
+            .collect())
+    }
+
+    fn cache_dir(&self) -> PathBuf {
+        self.treeline_dir.join(CACHE_DIR)
+    }
+
+    fn load_index(&self) -> Result<HashMap<String, LogoEntry>> {
+        Ok(read_json(&self.cache_dir().join(INDEX_FILE))?.unwrap_or_default())
+    }
+
+    #[cfg(feature = "http")]
+    fn save_index(&self, index: &HashMap<String, LogoEntry>) -> Result<()> {
+        fs::create_dir_all(self.cache_dir())?;
+        write_json(&self.cache_dir().join(INDEX_FILE), index)
+    }
+}
+
+/// "https://www.Chase.com/personal" -> "chase.com"
+///
+/// None for anything that isn't a public domain name, so IP addresses and
+/// local hosts are never fetched from.
+fn normalize_domain(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    let url = if value.contains("://") {
+        Url::parse(value).ok()?
+    } else {
+        Url::parse(&format!("https://{}", value)).ok()?
+    };
+    let domain = match url.host()? {
+        Host::Domain(domain) => domain.to_lowercase(),
+        Host::Ipv4(_) | Host::Ipv6(_) => return None,
+    };
+    let domain = domain.trim_end_matches('.');
+    let domain = domain.strip_prefix("www.").unwrap_or(domain);
+    let valid = domain.contains('.')
+        && !domain.ends_with(".local")
+        && domain
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+    valid.then(|| domain.to_string())
+}
+
+/// The first logo the bank's site serves, with its type
+#[cfg(feature = "http")]
+fn fetch_logo(client: &reqwest::blocking::Client, domain: &str) -> Option<(&'static str, Vec<u8>)> {
+    LOGO_PATHS.iter().find_map(|path| {
+        let response = client
+            .get(format!("https://{}{}", domain, path))
+            .send()
+            .ok()?
+            .error_for_status()
+            .ok()?;
+        if response
+            .content_length()
+            .is_some_and(|len| len > MAX_LOGO_BYTES as u64)
+        {
+            return None;
+        }
+        let bytes = response.bytes().ok()?;
+        if bytes.len() > MAX_LOGO_BYTES {
+            return None;
+        }
+        image_type(&bytes).map(|mime| (mime, bytes.to_vec()))
+    })
+}
+
+/// The image type from the file's first bytes
+///
+/// Sites often answer a missing icon with an HTML page and a 200, so the
+/// Content-Type header isn't trusted. SVG isn't accepted.
+#[cfg(feature = "http")]
+fn image_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0, 0, 1, 0]) {
+        Some("image/x-icon")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+#[cfg(feature = "http")]
+fn extension(mime: &str) -> &'static str {
+    match mime {
+        "image/png" => "png",
+        "image/x-icon" => "ico",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        _ => "webp",
+    }
+}
+
+fn data_url(mime: &str, bytes: &[u8]) -> String {
+    format!(
+        "data:{};base64,{}",
+        mime,
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "http")]
+    use crate::domain::Account;
+    #[cfg(feature = "http")]
+    use uuid::Uuid;
+
+    #[cfg(feature = "http")]
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\nrest";
+
+    #[test]
+    fn test_normalize_domain() {
+        assert_eq!(normalize_domain("chase.com").as_deref(), Some("chase.com"));
+        assert_eq!(
+            normalize_domain("https://www.Chase.com/personal").as_deref(),
+            Some("chase.com")
+        );
+        assert_eq!(
+            normalize_domain("online.citi.com.").as_deref(),
+            Some("online.citi.com")
+        );
+        assert_eq!(normalize_domain("192.168.1.1"), None);
+        assert_eq!(normalize_domain("localhost"), None);
+        assert_eq!(normalize_domain("nas.local"), None);
+        assert_eq!(normalize_domain(" "), None);
+    }
+
+    #[test]
+    #[cfg(feature = "http")]
+    fn test_image_type() {
+        assert_eq!(image_type(PNG), Some("image/png"));
+        assert_eq!(image_type(&[0, 0, 1, 0, 1, 0]), Some("image/x-icon"));
+        assert_eq!(image_type(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(image_type(b"<!DOCTYPE html>"), None);
+        assert_eq!(image_type(b"<svg xmlns="), None);
+    }
+
+    #[test]
+    #[cfg(feature = "http")]
+    fn test_list_reads_cache_without_fetching() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        for (name, institution, domain, url) in [
+            ("Checking", Some("Chase"), Some("chase.com"), None),
+            (
+                "Sapphire",
+                Some("JPMorgan Chase"),
+                None,
+                Some("https://www.chase.com"),
+            ),
+            ("Freedom", Some("Chase"), Some("CHASE.COM"), None),
+            ("Savings", Some("Marcus"), Some("marcus.com"), None),
+            ("Cash", None, None, None),
+        ] {
+            let mut account = Account::new(Uuid::new_v4(), name);
+            account.institution_name = institution.map(String::from);
+            account.institution_domain = domain.map(String::from);
+            account.institution_url = url.map(String::from);
+            repository.upsert_account(&account).unwrap();
+        }
+
+        let service = InstitutionService::new(repository, dir.path().to_path_buf());
+        fs::create_dir_all(service.cache_dir()).unwrap();
+        fs::write(service.cache_dir().join("chase.com.png"), PNG).unwrap();
+        let checked_at = Utc::now();
+        let index = HashMap::from([
+            (
+                "chase.com".to_string(),
+                LogoEntry {
+                    file: Some("chase.com.png".to_string()),
+                    mime: Some("image/png".to_string()),
+                    checked_at: Some(checked_at),
+                },
+            ),
+            (
+                "marcus.com".to_string(),
+                LogoEntry {
+                    checked_at: Some(checked_at),
+                    ..Default::default()
+                },
+            ),
+        ]);
+        service.save_index(&index).unwrap();
+
+        let institutions = service.list().unwrap();
+        assert_eq!(institutions.len(), 2);
+        let chase = &institutions[0];
+        assert_eq!(chase.domain, "chase.com");
+        assert_eq!(chase.name.as_deref(), Some("Chase"));
+        assert_eq!(chase.account_ids.len(), 3);
+        assert_eq!(chase.url.as_deref(), Some("https://www.chase.com"));
+        assert!(chase
+            .logo
+            .as_deref()
+            .unwrap()
+            .starts_with("data:image/png;base64,"));
+        assert_eq!(institutions[1].logo, None);
+
+        // Both were checked recently, so a refresh doesn't go to the network
+        let result = service.refresh(false).unwrap();
+        assert_eq!(result.skipped, 2);
+        assert!(result.fetched.is_empty() && result.failed.is_empty());
+    }
+}
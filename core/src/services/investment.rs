@@ -0,0 +1,256 @@
+//! Investment service - holdings in brokerage accounts
+//!
+//! Providers that report positions (SimpleFIN) hand them over with the
+//! account sync. Each sync replaces an account's holdings, keeping the ID of
+//! any holding the provider reported before, and adds the day's price of
+//! every holding with a symbol to that symbol's price history.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use schemars::JsonSchema;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::adapters::duckdb::DuckDbRepository;
+use crate::domain::{Holding, SecurityPrice};
+
+/// A holding with its account and what it's worth, for `tl holdings`
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct HoldingInfo {
+    pub holding_id: String,
+    pub account_id: String,
+    pub account_name: String,
+    pub symbol: Option<String>,
+    pub description: Option<String>,
+    #[schemars(with = "String")]
+    pub quantity: Decimal,
+    /// Market value per share or unit
+    #[schemars(with = "Option<String>")]
+    pub price: Option<Decimal>,
+    #[schemars(with = "Option<String>")]
+    pub market_value: Option<Decimal>,
+    #[schemars(with = "Option<String>")]
+    pub cost_basis: Option<Decimal>,
+    /// Market value minus cost basis
+    #[schemars(with = "Option<String>")]
+    pub unrealized_gain: Option<Decimal>,
+    pub currency: String,
+    /// When the provider valued the holding
+    pub as_of: NaiveDateTime,
+    /// Purchases that make up the holding, when they're known
+    pub lots: Vec<LotInfo>,
+}
+
+/// One purchase within a holding
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct LotInfo {
+    pub acquired_date: Option<NaiveDate>,
+    #[schemars(with = "String")]
+    pub quantity: Decimal,
+    #[schemars(with = "Option<String>")]
+    pub cost_basis: Option<Decimal>,
+}
+
+/// Holdings with what they add up to
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct HoldingsSummary {
+    /// Largest market value first
+    pub holdings: Vec<HoldingInfo>,
+    /// Market value of all holdings, by currency
+    #[schemars(with = "BTreeMap<String, String>")]
+    pub market_value_totals: BTreeMap<String, Decimal>,
+    /// Unrealized gain of the holdings with a known cost basis, by currency
+    #[schemars(with = "BTreeMap<String, String>")]
+    pub unrealized_gain_totals: BTreeMap<String, Decimal>,
+}
+
+/// Investment service for brokerage holdings and security prices
+pub struct InvestmentService {
+    repository: Arc<DuckDbRepository>,
+}
+
+impl InvestmentService {
+    pub fn new(repository: Arc<DuckDbRepository>) -> Self {
+        Self { repository }
+    }
+
+    /// Holdings of one account (by ID) or of all accounts, with totals
+    pub fn holdings(&self, account_id: Option<&str>) -> Result<HoldingsSummary> {
+        let account_names: HashMap<Uuid, String> = self
+            .repository
+            .get_accounts()?
+            .into_iter()
+            .map(|a| (a.id, a.nickname.unwrap_or(a.name)))
+            .collect();
+
+        let mut market_value_totals = BTreeMap::new();
+        let mut unrealized_gain_totals = BTreeMap::new();
+        let holdings = self
+            .repository
+            .get_holdings(account_id)?
+            .into_iter()
+            .map(|holding| {
+                if let Some(value) = holding.market_value {
+                    *market_value_totals
+                        .entry(holding.currency.clone())
+                        .or_insert(Decimal::ZERO) += value;
+                }
+                if let Some(gain) = holding.unrealized_gain() {
+                    *unrealized_gain_totals
+                        .entry(holding.currency.clone())
+                        .or_insert(Decimal::ZERO) += gain;
+                }
+                HoldingInfo {
+                    holding_id: holding.id.to_string(),
+                    account_id: holding.account_id.to_string(),
+                    account_name: account_names
+                        .get(&holding.account_id)
+                        .cloned()
+                        .unwrap_or_default(),
+                    price: holding.price(),
+                    unrealized_gain: holding.unrealized_gain(),
+                    symbol: holding.symbol,
+                    description: holding.description,
+                    quantity: holding.quantity,
+                    market_value: holding.market_value,
+                    cost_basis: holding.cost_basis,
+                    currency: holding.currency,
+                    as_of: holding.as_of,
+                    lots: holding
+                        .lots
+                        .into_iter()
+                        .map(|lot| LotInfo {
+                            acquired_date: lot.acquired_date,
+                            quantity: lot.quantity,
+                            cost_basis: lot.cost_basis,
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+
+        Ok(HoldingsSummary {
+            holdings,
+            market_value_totals,
+            unrealized_gain_totals,
+        })
+    }
+
+    /// A symbol's recorded prices, oldest first
+    pub fn price_history(&self, symbol: &str) -> Result<Vec<SecurityPrice>> {
+        self.repository
+            .get_security_prices(&symbol.trim().to_uppercase())
+    }
+
+    /// Store the holdings `provider` reported for an account
+    ///
+    /// Replaces the account's holdings. A holding the provider reported
+    /// before (same provider ID) keeps its ID and creation time. Returns the
+    /// number of holdings stored.
+    pub fn save_synced(
+        &self,
+        provider: &str,
+        account_id: Uuid,
+        holdings: Vec<Holding>,
+    ) -> Result<usize> {
+        let account_key = account_id.to_string();
+        let existing: HashMap<String, Holding> = self
+            .repository
+            .get_holdings(Some(&account_key))?
+            .into_iter()
+            .filter_map(|h| Some((h.provider.external_id(provider)?.to_string(), h)))
+            .collect();
+
+        let holdings: Vec<Holding> = holdings
+            .into_iter()
+            .map(|mut holding| {
+                holding.account_id = account_id;
+                let known = holding
+                    .provider
+                    .external_id(provider)
+                    .and_then(|id| existing.get(id));
+                if let Some(known) = known {
+                    holding.id = known.id;
+                    holding.created_at = known.created_at;
+                }
+                holding
+            })
+            .collect();
+
+        self.repository.replace_holdings(&account_key, &holdings)?;
+        let prices: Vec<SecurityPrice> = holdings
+            .iter()
+            .filter_map(|h| h.security_price(provider))
+            .collect();
+        self.repository.upsert_security_prices(&prices)?;
+
+        Ok(holdings.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Account, ProviderData};
+    use serde_json::json;
+
+    fn synced(symbol: &str, id: &str, quantity: i64, market_value: i64) -> Holding {
+        let mut holding = Holding::new(Uuid::nil(), Decimal::new(quantity, 0), "USD");
+        holding.symbol = Some(symbol.to_string());
+        holding.market_value = Some(Decimal::new(market_value, 0));
+        holding.provider = ProviderData::new("simplefin", id, json!({}));
+        holding
+    }
+
+    #[test]
+    fn test_sync_replaces_holdings_and_keeps_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        let account = Account::new(Uuid::new_v4(), "Brokerage");
+        repository.upsert_account(&account).unwrap();
+        let service = InvestmentService::new(repository);
+
+        let mut vti = synced("VTI", "HOL-1", 4, 1000);
+        vti.cost_basis = Some(Decimal::new(800, 0));
+        let stored = service
+            .save_synced(
+                "simplefin",
+                account.id,
+                vec![vti, synced("BND", "HOL-2", 10, 700)],
+            )
+            .unwrap();
+        assert_eq!(stored, 2);
+
+        let first = service.holdings(None).unwrap();
+        assert_eq!(first.holdings.len(), 2);
+        assert_eq!(first.holdings[0].symbol.as_deref(), Some("VTI"));
+        assert_eq!(first.holdings[0].account_name, "Brokerage");
+        assert_eq!(first.holdings[0].price, Some(Decimal::new(250, 0)));
+        assert_eq!(first.market_value_totals["USD"], Decimal::new(1700, 0));
+        assert_eq!(first.unrealized_gain_totals["USD"], Decimal::new(200, 0));
+
+        // BND was sold; VTI keeps its ID
+        service
+            .save_synced(
+                "simplefin",
+                account.id,
+                vec![synced("VTI", "HOL-1", 5, 1300)],
+            )
+            .unwrap();
+        let second = service.holdings(Some(&account.id.to_string())).unwrap();
+        assert_eq!(second.holdings.len(), 1);
+        assert_eq!(second.holdings[0].holding_id, first.holdings[0].holding_id);
+        assert_eq!(second.holdings[0].quantity, Decimal::new(5, 0));
+
+        // Same-day prices overwrite each other
+        let prices = service.price_history("vti").unwrap();
+        assert_eq!(prices.len(), 1);
+        assert_eq!(prices[0].price, Decimal::new(260, 0));
+    }
+}
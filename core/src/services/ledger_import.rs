@@ -0,0 +1,1273 @@
+//! Ledger import service - migrate books from GnuCash and Money Manager EX
+//!
+//! Both sources are read into a source-neutral `LedgerBook` (accounts,
+//! per-account transactions, and a list of things that could not be
+//! translated), which is then mapped onto Treeline accounts and transactions.
+//!
+//! Mapping rules:
+//! - Balance-sheet accounts (bank, cash, credit card, asset, liability) become
+//!   Treeline accounts, matched to existing accounts by name.
+//! - Income/expense accounts (GnuCash) and categories (MMEX) become tags.
+//! - Each split that touches a Treeline account becomes one transaction, so a
+//!   transfer between two migrated accounts shows up on both sides.
+//! - Re-importing the same book is idempotent: each transaction is fingerprinted
+//!   by its source ID.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use flate2::read::GzDecoder;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use rusqlite::{Connection, OpenFlags};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::adapters::duckdb::DuckDbRepository;
+use crate::domain::{Account, AccountType, Transaction};
+use crate::services::TagService;
+
+/// Source application of a ledger file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LedgerFormat {
+    /// GnuCash book (compressed or plain XML, or sqlite)
+    GnuCash,
+    /// Money Manager EX database (.mmb, sqlite)
+    Mmex,
+}
+
+impl LedgerFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LedgerFormat::GnuCash => "gnucash",
+            LedgerFormat::Mmex => "mmex",
+        }
+    }
+}
+
+/// Ledger import service
+pub struct LedgerImportService {
+    repository: Arc<DuckDbRepository>,
+    tag_service: TagService,
+}
+
+impl LedgerImportService {
+    pub fn new(repository: Arc<DuckDbRepository>) -> Self {
+        let tag_service = TagService::new(repository.clone());
+        Self {
+            repository,
+            tag_service,
+        }
+    }
+
+    /// Import a ledger file, or just report what would be imported when `preview` is set
+    pub fn import(
+        &self,
+        file_path: &Path,
+        format: LedgerFormat,
+        preview: bool,
+    ) -> Result<LedgerImportResult> {
+        let book = match format {
+            LedgerFormat::GnuCash => read_gnucash(file_path)?,
+            LedgerFormat::Mmex => read_mmex(file_path)?,
+        };
+
+        let batch_id = Uuid::new_v4().to_string();
+
+        // Match ledger accounts to existing accounts by name, creating the rest
+        let mut existing: HashMap<String, Uuid> = HashMap::new();
+        for account in self.repository.get_accounts()? {
+            existing
+                .entry(account.name.trim().to_lowercase())
+                .or_insert(account.id);
+        }
+
+        let mut account_ids: HashMap<&str, Uuid> = HashMap::new();
+        let mut new_accounts: Vec<Account> = Vec::new();
+        let mut reports: Vec<LedgerAccountReport> = Vec::new();
+        for ledger_account in &book.accounts {
+            let (id, created) = match existing.get(&ledger_account.name.to_lowercase()) {
+                Some(id) => (*id, false),
+                None => {
+                    let mut account = Account::new(Uuid::new_v4(), ledger_account.name.clone());
+                    account.account_type = Some(ledger_account.account_type.to_string());
+                    account.classification =
+                        Some(ledger_account.account_type.classification().to_string());
+                    account.currency = ledger_account.currency.to_uppercase();
+                    existing.insert(ledger_account.name.to_lowercase(), account.id);
+                    let id = account.id;
+                    new_accounts.push(account);
+                    (id, true)
+                }
+            };
+            account_ids.insert(ledger_account.source_id.as_str(), id);
+            reports.push(LedgerAccountReport {
+                name: ledger_account.name.clone(),
+                account_type: ledger_account.account_type.to_string(),
+                created,
+                transactions: 0,
+            });
+        }
+
+        let mut transactions: Vec<(usize, Transaction)> = Vec::new();
+        for ledger_tx in &book.transactions {
+            let account_index = match book
+                .accounts
+                .iter()
+                .position(|a| a.source_id == ledger_tx.account)
+            {
+                Some(index) => index,
+                None => continue,
+            };
+            let account_id = account_ids[ledger_tx.account.as_str()];
+
+            let mut tx =
+                Transaction::new(Uuid::new_v4(), account_id, ledger_tx.amount, ledger_tx.date);
+            tx.description = ledger_tx.description.clone();
+            tx.tags = ledger_tx.tags.clone();
+            tx.check_number = ledger_tx.check_number.clone();
+            tx.csv_fingerprint = Some(ledger_fingerprint(format, &ledger_tx.source_id));
+            tx.csv_batch_id = Some(batch_id.clone());
+            transactions.push((account_index, tx));
+        }
+        let discovered = transactions.len() as i64;
+
+        // Drop transactions already imported from this book
+        let fingerprints: Vec<String> = transactions
+            .iter()
+            .filter_map(|(_, tx)| tx.csv_fingerprint.clone())
+            .collect();
+        let already_imported = self.repository.get_csv_fingerprint_counts(&fingerprints)?;
+        let new_transactions: Vec<Transaction> = transactions
+            .into_iter()
+            .filter(|(_, tx)| {
+                tx.csv_fingerprint
+                    .as_ref()
+                    .is_none_or(|fp| !already_imported.contains_key(fp))
+            })
+            .map(|(account_index, tx)| {
+                reports[account_index].transactions += 1;
+                tx
+            })
+            .collect();
+        let imported = new_transactions.len() as i64;
+
+        if !preview {
+            for account in &new_accounts {
+                self.repository.upsert_account(account)?;
+            }
+
+            let new_tx_ids: Vec<Uuid> = new_transactions.iter().map(|tx| tx.id).collect();
+            self.repository
+                .bulk_insert_transactions(&new_transactions)?;
+
+            if !new_tx_ids.is_empty() {
+                // Best-effort tagging - don't fail import if rules fail
+                let _ = self.tag_service.apply_auto_tag_rules(&new_tx_ids);
+            }
+        }
+
+        Ok(LedgerImportResult {
+            source: format.as_str().to_string(),
+            batch_id,
+            preview,
+            accounts: reports,
+            discovered,
+            imported,
+            skipped: discovered - imported,
+            untranslated: book.untranslated,
+        })
+    }
+}
+
+/// Fingerprint a ledger transaction by its source ID
+///
+/// Source IDs are stable across exports, so re-importing a book never
+/// duplicates transactions even after descriptions are edited.
+fn ledger_fingerprint(format: LedgerFormat, source_id: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}|{}", format.as_str(), source_id).as_bytes());
+    let result = hasher.finalize();
+
+    // First 16 characters of hex hash, same width as CSV fingerprints
+    result[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// ============================================================================
+// Source-neutral book
+// ============================================================================
+
+/// Accounts, transactions and untranslatable items read from a ledger file
+#[derive(Debug, Default)]
+struct LedgerBook {
+    accounts: Vec<LedgerAccount>,
+    transactions: Vec<LedgerTransaction>,
+    untranslated: Vec<UntranslatedItem>,
+}
+
+#[derive(Debug)]
+struct LedgerAccount {
+    source_id: String,
+    name: String,
+    account_type: AccountType,
+    currency: String,
+}
+
+#[derive(Debug)]
+struct LedgerTransaction {
+    /// Unique ID within the source book (GnuCash split GUID, MMEX transaction ID)
+    source_id: String,
+    /// `LedgerAccount::source_id` of the account this transaction belongs to
+    account: String,
+    date: NaiveDate,
+    amount: Decimal,
+    description: Option<String>,
+    check_number: Option<String>,
+    tags: Vec<String>,
+}
+
+impl LedgerBook {
+    fn untranslated(&mut self, kind: &str, name: Option<&str>, count: usize, reason: &str) {
+        self.untranslated.push(UntranslatedItem {
+            kind: kind.to_string(),
+            name: name.map(String::from),
+            count,
+            reason: reason.to_string(),
+        });
+    }
+}
+
+/// Parse the date part of a ledger timestamp ("2024-01-15 10:59:00 +0000")
+fn parse_ledger_date(s: &str) -> Option<NaiveDate> {
+    let date = s.trim().get(..10)?;
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+fn non_empty(s: Option<String>) -> Option<String> {
+    s.map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+fn push_tag(tags: &mut Vec<String>, tag: &str) {
+    let tag = tag.trim();
+    if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+        tags.push(tag.to_string());
+    }
+}
+
+fn open_sqlite(file_path: &Path) -> Result<Connection> {
+    Connection::open_with_flags(file_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open database: {}", file_path.display()))
+}
+
+fn sqlite_has_table(conn: &Connection, table: &str) -> bool {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        [table],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+    .unwrap_or(false)
+}
+
+fn sqlite_has_column(conn: &Connection, table: &str, column: &str) -> bool {
+    let sql = format!(
+        "SELECT COUNT(*) FROM pragma_table_info('{}') WHERE name = ?1",
+        table
+    );
+    conn.query_row(&sql, [column], |row| row.get::<_, i64>(0))
+        .map(|count| count > 0)
+        .unwrap_or(false)
+}
+
+/// Row count of an optional table (0 when the table doesn't exist)
+fn sqlite_count(conn: &Connection, table: &str) -> usize {
+    if !sqlite_has_table(conn, table) {
+        return 0;
+    }
+    conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+        row.get::<_, i64>(0)
+    })
+    .map(|count| count as usize)
+    .unwrap_or(0)
+}
+
+// ============================================================================
+// GnuCash (XML and sqlite)
+// ============================================================================
+
+#[derive(Debug, Default)]
+struct GncAccount {
+    guid: String,
+    name: String,
+    kind: String,
+    /// ISO currency code, or None when the commodity is a security
+    currency: Option<String>,
+    parent: Option<String>,
+    placeholder: bool,
+}
+
+#[derive(Debug, Default)]
+struct GncSplit {
+    guid: String,
+    account: String,
+    memo: Option<String>,
+    /// Amount in the account's commodity as a rational ("-4500/100")
+    quantity: String,
+}
+
+#[derive(Debug, Default)]
+struct GncTransaction {
+    guid: String,
+    num: Option<String>,
+    date_posted: String,
+    description: Option<String>,
+    splits: Vec<GncSplit>,
+}
+
+#[derive(Debug, Default)]
+struct GncBook {
+    accounts: Vec<GncAccount>,
+    transactions: Vec<GncTransaction>,
+    scheduled: usize,
+    budgets: usize,
+    prices: usize,
+    invoices: usize,
+}
+
+/// Read a GnuCash book, detecting sqlite vs (gzipped) XML from the file header
+fn read_gnucash(file_path: &Path) -> Result<LedgerBook> {
+    let mut header = [0u8; 16];
+    let read = std::fs::File::open(file_path)
+        .with_context(|| format!("Failed to open {}", file_path.display()))?
+        .read(&mut header)?;
+
+    let gnc = if header[..read].starts_with(b"SQLite format 3") {
+        read_gnucash_sqlite(file_path)?
+    } else {
+        let file = std::fs::File::open(file_path)?;
+        if header[..read].starts_with(&[0x1f, 0x8b]) {
+            read_gnucash_xml(BufReader::new(GzDecoder::new(file)))?
+        } else {
+            read_gnucash_xml(BufReader::new(file))?
+        }
+    };
+
+    Ok(gnucash_book(gnc))
+}
+
+fn read_gnucash_xml<R: BufRead>(source: R) -> Result<GncBook> {
+    let mut reader = Reader::from_reader(source);
+    reader.config_mut().trim_text(true);
+
+    let mut book = GncBook::default();
+    let mut stack: Vec<String> = Vec::new();
+    let mut account: Option<GncAccount> = None;
+    let mut transaction: Option<GncTransaction> = None;
+    let mut split: Option<GncSplit> = None;
+    let mut commodity_space: Option<String> = None;
+    let mut slot_key: Option<String> = None;
+    let mut buf = Vec::new();
+
+    loop {
+        let event = reader
+            .read_event_into(&mut buf)
+            .context("Failed to parse GnuCash XML")?;
+        match event {
+            Event::Start(e) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+                // Scheduled-transaction templates live in their own account tree
+                let in_template = stack.iter().any(|s| s == "gnc:template-transactions");
+                if !in_template {
+                    match name.as_str() {
+                        "gnc:account" => account = Some(GncAccount::default()),
+                        "gnc:transaction" => transaction = Some(GncTransaction::default()),
+                        "trn:split" => split = Some(GncSplit::default()),
+                        "gnc:schedxaction" => book.scheduled += 1,
+                        "gnc:budget" => book.budgets += 1,
+                        "price" => book.prices += 1,
+                        "gnc:GncInvoice" => book.invoices += 1,
+                        _ => {}
+                    }
+                }
+                stack.push(name);
+            }
+            Event::End(_) => {
+                let name = stack.pop().unwrap_or_default();
+                match name.as_str() {
+                    "gnc:account" => {
+                        if let Some(a) = account.take() {
+                            book.accounts.push(a);
+                        }
+                    }
+                    "gnc:transaction" => {
+                        if let Some(t) = transaction.take() {
+                            book.transactions.push(t);
+                        }
+                    }
+                    "trn:split" => {
+                        if let (Some(s), Some(t)) = (split.take(), transaction.as_mut()) {
+                            t.splits.push(s);
+                        }
+                    }
+                    "act:commodity" => commodity_space = None,
+                    _ => {}
+                }
+            }
+            Event::Text(e) => {
+                let text = e
+                    .unescape()
+                    .context("Invalid text in GnuCash XML")?
+                    .into_owned();
+                let current = stack.last().map(String::as_str).unwrap_or_default();
+                let parent = stack
+                    .len()
+                    .checked_sub(2)
+                    .map(|i| stack[i].as_str())
+                    .unwrap_or_default();
+
+                if let Some(s) = split.as_mut() {
+                    match current {
+                        "split:id" => s.guid = text,
+                        "split:account" => s.account = text,
+                        "split:memo" => s.memo = Some(text),
+                        "split:quantity" => s.quantity = text,
+                        _ => {}
+                    }
+                } else if let Some(t) = transaction.as_mut() {
+                    match (parent, current) {
+                        ("gnc:transaction", "trn:id") => t.guid = text,
+                        ("gnc:transaction", "trn:num") => t.num = Some(text),
+                        ("gnc:transaction", "trn:description") => t.description = Some(text),
+                        ("trn:date-posted", "ts:date") => t.date_posted = text,
+                        _ => {}
+                    }
+                } else if let Some(a) = account.as_mut() {
+                    match (parent, current) {
+                        ("gnc:account", "act:name") => a.name = text,
+                        ("gnc:account", "act:id") => a.guid = text,
+                        ("gnc:account", "act:type") => a.kind = text,
+                        ("gnc:account", "act:parent") => a.parent = Some(text),
+                        ("act:commodity", "cmdty:space") => commodity_space = Some(text),
+                        ("act:commodity", "cmdty:id") => {
+                            if matches!(commodity_space.as_deref(), Some("CURRENCY" | "ISO4217")) {
+                                a.currency = Some(text);
+                            }
+                        }
+                        (_, "slot:key") => slot_key = Some(text),
+                        (_, "slot:value")
+                            if slot_key.as_deref() == Some("placeholder") && text == "true" =>
+                        {
+                            a.placeholder = true;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(book)
+}
+
+fn read_gnucash_sqlite(file_path: &Path) -> Result<GncBook> {
+    let conn = open_sqlite(file_path)?;
+    let mut book = GncBook::default();
+
+    let mut currencies: HashMap<String, String> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT guid, namespace, mnemonic FROM commodities")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+    for row in rows {
+        let (guid, namespace, mnemonic) = row?;
+        if namespace == "CURRENCY" || namespace == "ISO4217" {
+            currencies.insert(guid, mnemonic);
+        }
+    }
+
+    // Accounts under the template root belong to scheduled transactions
+    let template_root: Option<String> = conn
+        .query_row("SELECT root_template_guid FROM books", [], |row| row.get(0))
+        .ok();
+
+    let mut stmt = conn.prepare(
+        "SELECT guid, name, account_type, commodity_guid, parent_guid, placeholder FROM accounts",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        let commodity: Option<String> = row.get(3)?;
+        Ok(GncAccount {
+            guid: row.get(0)?,
+            name: row.get(1)?,
+            kind: row.get(2)?,
+            currency: commodity.and_then(|c| currencies.get(&c).cloned()),
+            parent: row.get(4)?,
+            placeholder: row.get::<_, Option<i64>>(5)?.unwrap_or(0) != 0,
+        })
+    })?;
+    let accounts: Vec<GncAccount> = rows.collect::<rusqlite::Result<_>>()?;
+    let parents: HashMap<&str, Option<&str>> = accounts
+        .iter()
+        .map(|a| (a.guid.as_str(), a.parent.as_deref()))
+        .collect();
+    let in_template = |guid: &str| {
+        let mut current = Some(guid);
+        while let Some(g) = current {
+            if Some(g) == template_root.as_deref() {
+                return true;
+            }
+            current = parents.get(g).copied().flatten();
+        }
+        false
+    };
+    let template_accounts: HashSet<String> = accounts
+        .iter()
+        .filter(|a| in_template(&a.guid))
+        .map(|a| a.guid.clone())
+        .collect();
+    book.accounts = accounts
+        .into_iter()
+        .filter(|a| !template_accounts.contains(&a.guid))
+        .collect();
+
+    let mut splits: HashMap<String, Vec<GncSplit>> = HashMap::new();
+    let mut stmt = conn.prepare(
+        "SELECT guid, tx_guid, account_guid, memo, quantity_num, quantity_denom FROM splits",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        let tx_guid: String = row.get(1)?;
+        let num: i64 = row.get(4)?;
+        let denom: i64 = row.get(5)?;
+        Ok((
+            tx_guid,
+            GncSplit {
+                guid: row.get(0)?,
+                account: row.get(2)?,
+                memo: row.get(3)?,
+                quantity: format!("{}/{}", num, denom),
+            },
+        ))
+    })?;
+    for row in rows {
+        let (tx_guid, split) = row?;
+        splits.entry(tx_guid).or_default().push(split);
+    }
+
+    let mut stmt = conn.prepare("SELECT guid, num, post_date, description FROM transactions")?;
+    let rows = stmt.query_map([], |row| {
+        Ok(GncTransaction {
+            guid: row.get(0)?,
+            num: row.get(1)?,
+            date_posted: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+            description: row.get(3)?,
+            splits: Vec::new(),
+        })
+    })?;
+    for row in rows {
+        let mut transaction = row?;
+        transaction.splits = splits.remove(&transaction.guid).unwrap_or_default();
+        book.transactions.push(transaction);
+    }
+
+    book.scheduled = sqlite_count(&conn, "schedxactions");
+    book.budgets = sqlite_count(&conn, "budgets");
+    book.prices = sqlite_count(&conn, "prices");
+    book.invoices = sqlite_count(&conn, "invoices");
+
+    Ok(book)
+}
+
+/// Parse a GnuCash rational amount ("-4500/100")
+fn parse_gnc_amount(s: &str) -> Option<Decimal> {
+    let (num, denom) = s.trim().split_once('/').unwrap_or((s.trim(), "1"));
+    let num: i64 = num.parse().ok()?;
+    let denom: i64 = denom.parse().ok()?;
+    if denom <= 0 {
+        return None;
+    }
+
+    // Denominators are almost always powers of ten (the commodity's fraction)
+    let mut scale = 0u32;
+    let mut d = denom;
+    while d % 10 == 0 {
+        d /= 10;
+        scale += 1;
+    }
+    if d == 1 {
+        Some(Decimal::new(num, scale))
+    } else {
+        Some((Decimal::from(num) / Decimal::from(denom)).round_dp(4))
+    }
+}
+
+/// Map a GnuCash book onto ledger accounts, transactions and tags
+fn gnucash_book(gnc: GncBook) -> LedgerBook {
+    let mut book = LedgerBook::default();
+    let by_guid: HashMap<&str, &GncAccount> =
+        gnc.accounts.iter().map(|a| (a.guid.as_str(), a)).collect();
+
+    // Splits per account, used to report how much history an untranslated account holds
+    let mut split_counts: HashMap<&str, usize> = HashMap::new();
+    for split in gnc.transactions.iter().flat_map(|t| &t.splits) {
+        *split_counts.entry(split.account.as_str()).or_insert(0) += 1;
+    }
+
+    // Full account path without the root, e.g. "Assets:Current Assets:Checking"
+    let full_name = |account: &GncAccount| {
+        let mut parts = vec![account.name.as_str()];
+        let mut parent = account.parent.as_deref();
+        while let Some(p) = parent.and_then(|g| by_guid.get(g)) {
+            if p.kind == "ROOT" {
+                break;
+            }
+            parts.push(p.name.as_str());
+            parent = p.parent.as_deref();
+        }
+        parts.reverse();
+        parts.join(":")
+    };
+
+    let mut tracked: Vec<&GncAccount> = Vec::new();
+    for account in &gnc.accounts {
+        let splits = split_counts
+            .get(account.guid.as_str())
+            .copied()
+            .unwrap_or(0);
+        match account.kind.as_str() {
+            "ROOT" | "INCOME" | "EXPENSE" | "EQUITY" => {}
+            "STOCK" | "MUTUAL" => book.untranslated(
+                "account",
+                Some(&full_name(account)),
+                splits,
+                "investment holdings (share quantities and prices) are not supported",
+            ),
+            "TRADING" => book.untranslated(
+                "account",
+                Some(&full_name(account)),
+                splits,
+                "currency trading accounts are not supported",
+            ),
+            _ if account.placeholder => {
+                if splits > 0 {
+                    book.untranslated(
+                        "account",
+                        Some(&full_name(account)),
+                        splits,
+                        "placeholder account holds transactions of its own",
+                    );
+                }
+            }
+            _ if account.currency.is_none() => book.untranslated(
+                "account",
+                Some(&full_name(account)),
+                splits,
+                "account is denominated in a security, not a currency",
+            ),
+            _ => tracked.push(account),
+        }
+    }
+
+    // Use leaf names unless two migrated accounts would collide
+    let mut leaf_counts: HashMap<String, usize> = HashMap::new();
+    for account in &tracked {
+        *leaf_counts.entry(account.name.to_lowercase()).or_insert(0) += 1;
+    }
+    for account in &tracked {
+        let name = if leaf_counts[&account.name.to_lowercase()] > 1 {
+            full_name(account)
+        } else {
+            account.name.clone()
+        };
+        book.accounts.push(LedgerAccount {
+            source_id: account.guid.clone(),
+            name,
+            account_type: gnucash_account_type(&account.kind),
+            currency: account.currency.clone().unwrap_or_default(),
+        });
+    }
+    let tracked_guids: HashSet<&str> = tracked.iter().map(|a| a.guid.as_str()).collect();
+
+    for transaction in &gnc.transactions {
+        let mut tags = Vec::new();
+        for split in &transaction.splits {
+            if let Some(account) = by_guid.get(split.account.as_str()) {
+                if account.kind == "INCOME" || account.kind == "EXPENSE" {
+                    push_tag(&mut tags, &account.name);
+                }
+            }
+        }
+
+        for split in &transaction.splits {
+            if !tracked_guids.contains(split.account.as_str()) {
+                continue;
+            }
+            let description = non_empty(transaction.description.clone())
+                .or_else(|| non_empty(split.memo.clone()));
+
+            let date = match parse_ledger_date(&transaction.date_posted) {
+                Some(d) => d,
+                None => {
+                    book.untranslated(
+                        "transaction",
+                        description.as_deref(),
+                        1,
+                        "invalid posted date",
+                    );
+                    continue;
+                }
+            };
+            let amount = match parse_gnc_amount(&split.quantity) {
+                Some(a) => a,
+                None => {
+                    book.untranslated("transaction", description.as_deref(), 1, "invalid amount");
+                    continue;
+                }
+            };
+
+            book.transactions.push(LedgerTransaction {
+                source_id: split.guid.clone(),
+                account: split.account.clone(),
+                date,
+                amount,
+                description,
+                check_number: non_empty(transaction.num.clone()),
+                tags: tags.clone(),
+            });
+        }
+    }
+
+    let extras = [
+        (
+            gnc.scheduled,
+            "scheduled transaction",
+            "scheduled transactions are not imported",
+        ),
+        (gnc.budgets, "budget", "budgets are not imported"),
+        (gnc.prices, "price", "commodity prices are not imported"),
+        (
+            gnc.invoices,
+            "invoice",
+            "invoices and bills are not imported",
+        ),
+    ];
+    for (count, kind, reason) in extras {
+        if count > 0 {
+            book.untranslated(kind, None, count, reason);
+        }
+    }
+
+    book
+}
+
+/// Map a GnuCash account type to a Treeline account type
+fn gnucash_account_type(kind: &str) -> AccountType {
+    match kind {
+        "BANK" => AccountType::Checking,
+        "CASH" => AccountType::Cash,
+        "CREDIT" => AccountType::CreditCard,
+        "LIABILITY" | "PAYABLE" => AccountType::Loan,
+        _ => AccountType::Other,
+    }
+}
+
+// ============================================================================
+// Money Manager EX (sqlite)
+// ============================================================================
+
+/// Read a Money Manager EX database
+fn read_mmex(file_path: &Path) -> Result<LedgerBook> {
+    let conn = open_sqlite(file_path)?;
+    if !sqlite_has_table(&conn, "CHECKINGACCOUNT_V1") {
+        anyhow::bail!("{} is not a Money Manager EX database", file_path.display());
+    }
+    let mut book = LedgerBook::default();
+
+    let mut currencies: HashMap<i64, String> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT CURRENCYID, CURRENCY_SYMBOL FROM CURRENCYFORMATS_V1")?;
+    for row in stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))? {
+        let (id, symbol) = row?;
+        currencies.insert(id, symbol);
+    }
+
+    // Accounts, with the initial balance carried as an opening transaction
+    let initial_date_column = sqlite_has_column(&conn, "ACCOUNTLIST_V1", "INITIALDATE");
+    let sql = format!(
+        "SELECT ACCOUNTID, ACCOUNTNAME, ACCOUNTTYPE, INITIALBAL, CURRENCYID, {} FROM ACCOUNTLIST_V1",
+        if initial_date_column { "INITIALDATE" } else { "NULL" }
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, Option<f64>>(3)?.unwrap_or(0.0),
+            row.get::<_, Option<i64>>(4)?,
+            row.get::<_, Option<String>>(5)?,
+        ))
+    })?;
+    let mut account_names: HashMap<i64, String> = HashMap::new();
+    let mut opening: Vec<(i64, Decimal, Option<NaiveDate>)> = Vec::new();
+    for row in rows {
+        let (id, name, kind, initial_balance, currency, initial_date) = row?;
+        account_names.insert(id, name.clone());
+        book.accounts.push(LedgerAccount {
+            source_id: id.to_string(),
+            name,
+            account_type: mmex_account_type(&kind),
+            currency: currency
+                .and_then(|c| currencies.get(&c).cloned())
+                .unwrap_or_else(|| "USD".to_string()),
+        });
+        let initial_balance = mmex_amount(initial_balance);
+        if !initial_balance.is_zero() {
+            opening.push((
+                id,
+                initial_balance,
+                initial_date.as_deref().and_then(parse_ledger_date),
+            ));
+        }
+    }
+
+    // Categories: newer databases nest via PARENTID, older ones use SUBCATEGORY_V1
+    let mut categories: HashMap<i64, String> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT CATEGID, CATEGNAME FROM CATEGORY_V1")?;
+    for row in stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))? {
+        let (id, name) = row?;
+        categories.insert(id, name);
+    }
+    let mut subcategories: HashMap<i64, String> = HashMap::new();
+    if sqlite_has_table(&conn, "SUBCATEGORY_V1") {
+        let mut stmt = conn.prepare("SELECT SUBCATEGID, SUBCATEGNAME FROM SUBCATEGORY_V1")?;
+        for row in stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))? {
+            let (id, name) = row?;
+            subcategories.insert(id, name);
+        }
+    }
+    let has_subcategory = sqlite_has_column(&conn, "CHECKINGACCOUNT_V1", "SUBCATEGID");
+    let category_tag = |categ: Option<i64>, subcateg: Option<i64>| {
+        subcateg
+            .and_then(|s| subcategories.get(&s))
+            .or_else(|| categ.and_then(|c| categories.get(&c)))
+            .cloned()
+    };
+
+    let mut split_tags: HashMap<i64, Vec<String>> = HashMap::new();
+    if sqlite_has_table(&conn, "SPLITTRANSACTIONS_V1") {
+        let sql = format!(
+            "SELECT TRANSID, CATEGID, {} FROM SPLITTRANSACTIONS_V1",
+            if sqlite_has_column(&conn, "SPLITTRANSACTIONS_V1", "SUBCATEGID") {
+                "SUBCATEGID"
+            } else {
+                "NULL"
+            }
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        for row in stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))? {
+            let (trans_id, categ, subcateg): (i64, Option<i64>, Option<i64>) = row?;
+            if let Some(tag) = category_tag(categ, subcateg) {
+                push_tag(split_tags.entry(trans_id).or_default(), &tag);
+            }
+        }
+    }
+
+    let mut payees: HashMap<i64, String> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT PAYEEID, PAYEENAME FROM PAYEE_V1")?;
+    for row in stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))? {
+        let (id, name) = row?;
+        payees.insert(id, name);
+    }
+
+    let sql = format!(
+        "SELECT TRANSID, ACCOUNTID, TOACCOUNTID, PAYEEID, TRANSCODE, TRANSAMOUNT, STATUS,
+                TRANSACTIONNUMBER, NOTES, CATEGID, {}, TRANSDATE, TOTRANSAMOUNT, {}
+         FROM CHECKINGACCOUNT_V1 ORDER BY TRANSID",
+        if has_subcategory {
+            "SUBCATEGID"
+        } else {
+            "NULL"
+        },
+        if sqlite_has_column(&conn, "CHECKINGACCOUNT_V1", "DELETEDTIME") {
+            "DELETEDTIME"
+        } else {
+            "NULL"
+        }
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map([], |row| {
+        Ok(MmexTransaction {
+            id: row.get(0)?,
+            account: row.get(1)?,
+            to_account: row.get(2)?,
+            payee: row.get(3)?,
+            code: row.get(4)?,
+            amount: row.get::<_, Option<f64>>(5)?.unwrap_or(0.0),
+            status: row.get(6)?,
+            number: row.get(7)?,
+            notes: row.get(8)?,
+            category: row.get(9)?,
+            subcategory: row.get(10)?,
+            date: row.get(11)?,
+            to_amount: row.get(12)?,
+            deleted: row.get(13)?,
+        })
+    })?;
+
+    let mut void_count = 0usize;
+    let mut split_count = 0usize;
+    for row in rows {
+        let t = row?;
+        if non_empty(t.deleted.clone()).is_some() {
+            continue;
+        }
+        if t.status.as_deref() == Some("V") {
+            void_count += 1;
+            continue;
+        }
+
+        let notes = non_empty(t.notes.clone());
+        let date = match t.date.as_deref().and_then(parse_ledger_date) {
+            Some(d) => d,
+            None => {
+                book.untranslated("transaction", notes.as_deref(), 1, "invalid date");
+                continue;
+            }
+        };
+
+        let mut tags = Vec::new();
+        if let Some(tag) = category_tag(t.category, t.subcategory) {
+            push_tag(&mut tags, &tag);
+        }
+        if let Some(splits) = split_tags.get(&t.id) {
+            split_count += 1;
+            for tag in splits {
+                push_tag(&mut tags, tag);
+            }
+        }
+
+        let amount = mmex_amount(t.amount);
+        let payee = t.payee.and_then(|p| payees.get(&p).cloned());
+        let check_number = non_empty(t.number.clone());
+
+        if t.code == "Transfer" {
+            let to_account = t.to_account.unwrap_or(-1);
+            let from_name = account_names.get(&t.account).cloned().unwrap_or_default();
+            let to_name = match account_names.get(&to_account) {
+                Some(name) => name.clone(),
+                None => {
+                    book.untranslated(
+                        "transaction",
+                        notes.as_deref(),
+                        1,
+                        "transfer to an unknown account",
+                    );
+                    continue;
+                }
+            };
+            let to_amount = t.to_amount.map(mmex_amount).unwrap_or(amount);
+
+            book.transactions.push(LedgerTransaction {
+                source_id: t.id.to_string(),
+                account: t.account.to_string(),
+                date,
+                amount: -amount,
+                description: notes
+                    .clone()
+                    .or_else(|| Some(format!("Transfer to {}", to_name))),
+                check_number: check_number.clone(),
+                tags: tags.clone(),
+            });
+            book.transactions.push(LedgerTransaction {
+                source_id: format!("{}|to", t.id),
+                account: to_account.to_string(),
+                date,
+                amount: to_amount,
+                description: notes.or_else(|| Some(format!("Transfer from {}", from_name))),
+                check_number,
+                tags,
+            });
+        } else {
+            book.transactions.push(LedgerTransaction {
+                source_id: t.id.to_string(),
+                account: t.account.to_string(),
+                date,
+                amount: if t.code == "Withdrawal" {
+                    -amount
+                } else {
+                    amount
+                },
+                description: payee.or(notes),
+                check_number,
+                tags,
+            });
+        }
+    }
+
+    // Opening balances are dated at INITIALDATE, or the account's first transaction
+    for (account, amount, initial_date) in opening {
+        let account_id = account.to_string();
+        let first_date = book
+            .transactions
+            .iter()
+            .filter(|t| t.account == account_id)
+            .map(|t| t.date)
+            .min();
+        let name = account_names.get(&account).cloned().unwrap_or_default();
+        match initial_date.or(first_date) {
+            Some(date) => book.transactions.push(LedgerTransaction {
+                source_id: format!("opening|{}", account_id),
+                account: account_id,
+                date,
+                amount,
+                description: Some("Opening Balance".to_string()),
+                check_number: None,
+                tags: Vec::new(),
+            }),
+            None => book.untranslated(
+                "account",
+                Some(&name),
+                0,
+                "initial balance has no date to attach to",
+            ),
+        }
+    }
+
+    if void_count > 0 {
+        book.untranslated(
+            "transaction",
+            None,
+            void_count,
+            "void transactions are not imported",
+        );
+    }
+    if split_count > 0 {
+        book.untranslated(
+            "split transaction",
+            None,
+            split_count,
+            "split amounts are not kept; every split category is added as a tag",
+        );
+    }
+    let extras = [
+        (
+            "BILLSDEPOSITS_V1",
+            "scheduled transaction",
+            "scheduled transactions are not imported",
+        ),
+        ("BUDGETTABLE_V1", "budget", "budgets are not imported"),
+        ("STOCK_V1", "stock", "stock holdings are not imported"),
+        ("ASSETS_V1", "asset", "asset valuations are not imported"),
+    ];
+    for (table, kind, reason) in extras {
+        let count = sqlite_count(&conn, table);
+        if count > 0 {
+            book.untranslated(kind, None, count, reason);
+        }
+    }
+
+    Ok(book)
+}
+
+struct MmexTransaction {
+    id: i64,
+    account: i64,
+    to_account: Option<i64>,
+    payee: Option<i64>,
+    /// "Withdrawal", "Deposit" or "Transfer"
+    code: String,
+    amount: f64,
+    status: Option<String>,
+    number: Option<String>,
+    notes: Option<String>,
+    category: Option<i64>,
+    subcategory: Option<i64>,
+    date: Option<String>,
+    to_amount: Option<f64>,
+    deleted: Option<String>,
+}
+
+/// MMEX stores amounts as floating point; round to cents
+fn mmex_amount(value: f64) -> Decimal {
+    Decimal::try_from(value).unwrap_or_default().round_dp(2)
+}
+
+/// Map an MMEX account type to a Treeline account type
+fn mmex_account_type(kind: &str) -> AccountType {
+    match kind {
+        "Checking" => AccountType::Checking,
+        "Savings" | "Term" => AccountType::Savings,
+        "Cash" => AccountType::Cash,
+        "Credit Card" => AccountType::CreditCard,
+        "Loan" => AccountType::Loan,
+        "Investment" | "Shares" => AccountType::Brokerage,
+        _ => AccountType::Other,
+    }
+}
+
+// ============================================================================
+// Result types
+// ============================================================================
+
+/// Result of a ledger import (or preview)
+#[derive(Debug, Serialize)]
+pub struct LedgerImportResult {
+    /// "gnucash" or "mmex"
+    pub source: String,
+    pub batch_id: String,
+    pub preview: bool,
+    /// Migrated accounts and how many new transactions each receives
+    pub accounts: Vec<LedgerAccountReport>,
+    pub discovered: i64,
+    pub imported: i64,
+    /// Transactions already imported from this book
+    pub skipped: i64,
+    /// Everything in the source that has no Treeline equivalent
+    pub untranslated: Vec<UntranslatedItem>,
+}
+
+/// Mapping of one ledger account onto a Treeline account
+#[derive(Debug, Serialize)]
+pub struct LedgerAccountReport {
+    pub name: String,
+    pub account_type: String,
+    /// False when the ledger account matched an existing account by name
+    pub created: bool,
+    pub transactions: i64,
+}
+
+/// Something in the ledger that could not be translated
+#[derive(Debug, Serialize)]
+pub struct UntranslatedItem {
+    /// "account", "transaction", "scheduled transaction", "budget", ...
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Number of source records affected
+    pub count: usize,
+    pub reason: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gnc_amount() {
+        assert_eq!(parse_gnc_amount("-4500/100"), Some(Decimal::new(-4500, 2)));
+        assert_eq!(parse_gnc_amount("12/1"), Some(Decimal::from(12)));
+        assert_eq!(parse_gnc_amount("1/3"), Some(Decimal::new(3333, 4)));
+        assert_eq!(parse_gnc_amount("abc"), None);
+        assert_eq!(parse_gnc_amount("1/0"), None);
+    }
+
+    #[test]
+    fn test_parse_ledger_date() {
+        assert_eq!(
+            parse_ledger_date("2024-01-15 10:59:00 +0000"),
+            NaiveDate::from_ymd_opt(2024, 1, 15)
+        );
+        assert_eq!(
+            parse_ledger_date("2024-01-15"),
+            NaiveDate::from_ymd_opt(2024, 1, 15)
+        );
+        assert_eq!(parse_ledger_date("15/01/2024"), None);
+    }
+
+    #[test]
+    fn test_gnucash_xml_maps_accounts_splits_and_categories() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8" ?>
+<gnc-v2 xmlns:gnc="http://www.gnucash.org/XML/gnc">
+<gnc:book version="2.0.0">
+<gnc:account version="2.0.0">
+  <act:name>Root Account</act:name>
+  <act:id type="guid">root</act:id>
+  <act:type>ROOT</act:type>
+</gnc:account>
+<gnc:account version="2.0.0">
+  <act:name>Assets</act:name>
+  <act:id type="guid">assets</act:id>
+  <act:type>ASSET</act:type>
+  <act:commodity><cmdty:space>CURRENCY</cmdty:space><cmdty:id>USD</cmdty:id></act:commodity>
+  <act:slots><slot><slot:key>placeholder</slot:key><slot:value type="string">true</slot:value></slot></act:slots>
+  <act:parent type="guid">root</act:parent>
+</gnc:account>
+<gnc:account version="2.0.0">
+  <act:name>Checking</act:name>
+  <act:id type="guid">checking</act:id>
+  <act:type>BANK</act:type>
+  <act:commodity><cmdty:space>CURRENCY</cmdty:space><cmdty:id>USD</cmdty:id></act:commodity>
+  <act:parent type="guid">assets</act:parent>
+</gnc:account>
+<gnc:account version="2.0.0">
+  <act:name>Groceries</act:name>
+  <act:id type="guid">groceries</act:id>
+  <act:type>EXPENSE</act:type>
+  <act:commodity><cmdty:space>CURRENCY</cmdty:space><cmdty:id>USD</cmdty:id></act:commodity>
+  <act:parent type="guid">root</act:parent>
+</gnc:account>
+<gnc:account version="2.0.0">
+  <act:name>ACME Shares</act:name>
+  <act:id type="guid">acme</act:id>
+  <act:type>STOCK</act:type>
+  <act:commodity><cmdty:space>NASDAQ</cmdty:space><cmdty:id>ACME</cmdty:id></act:commodity>
+  <act:parent type="guid">assets</act:parent>
+</gnc:account>
+<gnc:transaction version="2.0.0">
+  <trn:id type="guid">tx1</trn:id>
+  <trn:num>101</trn:num>
+  <trn:date-posted><ts:date>2024-01-15 10:59:00 +0000</ts:date></trn:date-posted>
+  <trn:description>Grocer &amp; Co</trn:description>
+  <trn:splits>
+    <trn:split>
+      <split:id type="guid">s1</split:id>
+      <split:value>-4500/100</split:value>
+      <split:quantity>-4500/100</split:quantity>
+      <split:account type="guid">checking</split:account>
+    </trn:split>
+    <trn:split>
+      <split:id type="guid">s2</split:id>
+      <split:value>4500/100</split:value>
+      <split:quantity>4500/100</split:quantity>
+      <split:account type="guid">groceries</split:account>
+    </trn:split>
+  </trn:splits>
+</gnc:transaction>
+<gnc:schedxaction version="2.0.0"><sx:name>Rent</sx:name></gnc:schedxaction>
+</gnc:book>
+</gnc-v2>"#;
+
+        let gnc = read_gnucash_xml(BufReader::new(xml.as_bytes())).unwrap();
+        let book = gnucash_book(gnc);
+
+        assert_eq!(book.accounts.len(), 1);
+        assert_eq!(book.accounts[0].name, "Checking");
+        assert_eq!(book.accounts[0].account_type, AccountType::Checking);
+        assert_eq!(book.accounts[0].currency, "USD");
+
+        assert_eq!(book.transactions.len(), 1);
+        let tx = &book.transactions[0];
+        assert_eq!(tx.amount, Decimal::new(-4500, 2));
+        assert_eq!(tx.description.as_deref(), Some("Grocer & Co"));
+        assert_eq!(tx.check_number.as_deref(), Some("101"));
+        assert_eq!(tx.tags, vec!["Groceries"]);
+
+        let kinds: Vec<&str> = book.untranslated.iter().map(|u| u.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["account", "scheduled transaction"]);
+        assert_eq!(
+            book.untranslated[0].name.as_deref(),
+            Some("Assets:ACME Shares")
+        );
+    }
+}
@@ -2,6 +2,8 @@
 //!
 //! Provides a privacy-safe logging system that stores events in logs.duckdb.
 //! No user data (transactions, accounts, balances, descriptions) is ever logged.
+//! How much is logged is up to the user: `app.logging` in settings.json sets a
+//! level (errors, actions or full) overall and per category of event.
 //!
 //! This service is designed to be used by both CLI and desktop applications.
 //! Uses per-operation locking to allow external tools to query logs while the app runs.
@@ -9,15 +11,25 @@
 use std::fs::{File, OpenOptions};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::{anyhow, Result};
 use duckdb::Connection;
 use fs2::FileExt;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
+use crate::config::{Config, LogCategory, LogLevel, LoggingSettings};
 use crate::log_migrations::LOG_MIGRATIONS;
 
+/// Event name used by `LoggingService::log_page`
+const PAGE_OPENED: &str = "page_opened";
+
+/// Caller recorded for statements typed into the SQL console
+pub const QUERY_CALLER_USER: &str = "user";
+
 /// Counter for generating unique IDs within the same millisecond
 static ID_COUNTER: AtomicU64 = AtomicU64::new(0);
 
@@ -101,6 +113,30 @@ impl LogEvent {
         }
     }
 
+    /// The category whose log level applies to this event
+    pub fn category(&self) -> LogCategory {
+        if self.integration.is_some() {
+            LogCategory::Sync
+        } else if self.page.is_some() {
+            LogCategory::Ui
+        } else if self.command.is_some() {
+            LogCategory::Cli
+        } else {
+            LogCategory::App
+        }
+    }
+
+    /// Whether a category at `level` records this event
+    pub fn is_logged_at(&self, level: LogLevel) -> bool {
+        if self.error_message.is_some() {
+            true
+        } else if self.event == PAGE_OPENED {
+            level >= LogLevel::Full
+        } else {
+            level >= LogLevel::Actions
+        }
+    }
+
     /// Set the integration context
     pub fn with_integration(mut self, integration: impl Into<String>) -> Self {
         self.integration = Some(integration.into());
@@ -133,7 +169,7 @@ impl LogEvent {
 }
 
 /// A log entry as stored in the database
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LogEntry {
     pub id: u64,
     pub timestamp: i64,
@@ -148,6 +184,42 @@ pub struct LogEntry {
     pub error_details: Option<String>,
 }
 
+/// One SQL statement run from the desktop, to be recorded in the query log
+#[derive(Debug, Clone)]
+pub struct QueryAudit<'a> {
+    /// `QUERY_CALLER_USER` or the plugin id
+    pub caller: &'a str,
+    pub sql: &'a str,
+    pub duration_ms: u64,
+    /// Rows returned, or None when the statement failed
+    pub row_count: Option<usize>,
+    pub error: Option<&'a str>,
+}
+
+/// A statement as stored in the query log
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct QueryLogEntry {
+    pub id: u64,
+    pub timestamp: i64,
+    pub entry_point: String,
+    pub caller: String,
+    /// Same for every run of a statement, whatever its literals
+    pub query_hash: String,
+    /// The statement with literals replaced by `?`
+    pub statement: String,
+    pub duration_ms: i64,
+    pub row_count: Option<i64>,
+    pub error_message: Option<String>,
+}
+
+/// Names of what was used, from `LoggingService::names_used_since`
+#[derive(Debug, Clone, Default)]
+pub struct UsedNames {
+    pub commands: Vec<String>,
+    pub pages: Vec<String>,
+    pub integrations: Vec<String>,
+}
+
 /// Service for structured event logging
 ///
 /// This service manages the logs.duckdb database and provides methods
@@ -160,6 +232,7 @@ pub struct LoggingService {
     entry_point: EntryPoint,
     app_version: String,
     platform: &'static str,
+    settings: RwLock<LoggingSettings>,
 }
 
 impl LoggingService {
@@ -179,11 +252,15 @@ impl LoggingService {
             std::fs::create_dir_all(parent)?;
         }
 
+        let settings = Config::load(treeline_dir)
+            .map(|config| config.logging)
+            .unwrap_or_default();
         let service = Self {
             db_path,
             entry_point,
             app_version: app_version.into(),
             platform: detect_platform(),
+            settings: RwLock::new(settings),
         };
 
         // Run migrations on startup
@@ -294,8 +371,11 @@ impl LoggingService {
     ///
     /// This is the main method for recording events. The entry_point,
     /// app_version, and platform are automatically added from the service
-    /// configuration.
+    /// configuration. Events below the category's log level are dropped.
     pub fn log(&self, event: LogEvent) -> Result<()> {
+        if !event.is_logged_at(self.settings().level_for(event.category())) {
+            return Ok(());
+        }
         self.with_connection_write(|conn| {
             conn.execute(
                 r#"
@@ -334,7 +414,7 @@ impl LoggingService {
 
     /// Log a frontend page navigation
     pub fn log_page(&self, page: &str) -> Result<()> {
-        self.log(LogEvent::new("page_opened").with_page(page))
+        self.log(LogEvent::new(PAGE_OPENED).with_page(page))
     }
 
     /// Log an error
@@ -346,6 +426,84 @@ impl LoggingService {
         self.log(log_event)
     }
 
+    /// Record a SQL statement in the query log
+    ///
+    /// Statements are kept at the `full` level of the ui category, failed ones
+    /// at any level. Only the normalized statement is stored, never its literals
+    /// or bound parameters.
+    pub fn log_query(&self, query: &QueryAudit) -> Result<()> {
+        if query.error.is_none() && self.settings().level_for(LogCategory::Ui) < LogLevel::Full {
+            return Ok(());
+        }
+        let statement = normalize_statement(query.sql);
+        self.with_connection_write(|conn| {
+            conn.execute(
+                r#"
+                INSERT INTO sys_query_log (
+                    id, timestamp, entry_point, app_version, caller,
+                    query_hash, statement, duration_ms, row_count, error_message
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+                duckdb::params![
+                    generate_id(),
+                    now_ms(),
+                    self.entry_point.as_str(),
+                    &self.app_version,
+                    query.caller,
+                    statement_hash(&statement),
+                    &statement,
+                    query.duration_ms as i64,
+                    query.row_count.map(|n| n as i64),
+                    query.error,
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Recent statements from the query log, or only those that took at least
+    /// `min_duration_ms`, slowest first
+    pub fn get_queries(
+        &self,
+        min_duration_ms: Option<u64>,
+        limit: usize,
+    ) -> Result<Vec<QueryLogEntry>> {
+        self.with_connection(|conn| {
+            let order = if min_duration_ms.is_some() {
+                "duration_ms DESC, timestamp DESC"
+            } else {
+                "timestamp DESC"
+            };
+            let mut stmt = conn.prepare(&format!(
+                "SELECT id, timestamp, entry_point, caller, query_hash, statement,
+                        duration_ms, row_count, error_message
+                 FROM sys_query_log
+                 WHERE duration_ms >= ?
+                 ORDER BY {order}
+                 LIMIT ?"
+            ))?;
+
+            let entries = stmt
+                .query_map([min_duration_ms.unwrap_or(0) as i64, limit as i64], |row| {
+                    Ok(QueryLogEntry {
+                        id: row.get(0)?,
+                        timestamp: row.get(1)?,
+                        entry_point: row.get(2)?,
+                        caller: row.get(3)?,
+                        query_hash: row.get(4)?,
+                        statement: row.get(5)?,
+                        duration_ms: row.get(6)?,
+                        row_count: row.get(7)?,
+                        error_message: row.get(8)?,
+                    })
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            Ok(entries)
+        })
+    }
+
     /// Query recent log entries
     ///
     /// Returns the most recent entries, up to the specified limit.
@@ -430,15 +588,75 @@ impl LoggingService {
         })
     }
 
-    /// Delete logs older than the specified timestamp (unix ms)
+    /// Count error entries logged at or after the specified timestamp (unix ms)
+    pub fn count_errors_since(&self, timestamp_ms: i64) -> Result<u64> {
+        self.with_connection(|conn| {
+            let count: u64 = conn.query_row(
+                "SELECT COUNT(*) FROM sys_logs WHERE error_message IS NOT NULL AND timestamp >= ?",
+                [timestamp_ms],
+                |row| row.get(0),
+            )?;
+            Ok(count)
+        })
+    }
+
+    /// Distinct command, page and integration names logged at or after the
+    /// timestamp (unix ms), each sorted
+    ///
+    /// Names only; used for the usage ping.
+    pub fn names_used_since(&self, timestamp_ms: i64) -> Result<UsedNames> {
+        self.with_connection(|conn| {
+            let distinct = |column: &str| -> Result<Vec<String>> {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT DISTINCT {column} FROM sys_logs
+                     WHERE {column} IS NOT NULL AND timestamp >= ?
+                     ORDER BY {column}"
+                ))?;
+                let names = stmt
+                    .query_map([timestamp_ms], |row| row.get(0))?
+                    .collect::<duckdb::Result<Vec<String>>>()?;
+                Ok(names)
+            };
+            Ok(UsedNames {
+                commands: distinct("command")?,
+                pages: distinct("page")?,
+                integrations: distinct("integration")?,
+            })
+        })
+    }
+
+    /// Delete logs and query log entries older than the specified timestamp (unix ms)
     pub fn delete_before(&self, timestamp_ms: i64) -> Result<u64> {
         self.with_connection_write(|conn| {
             let deleted =
                 conn.execute("DELETE FROM sys_logs WHERE timestamp < ?", [timestamp_ms])?;
-            Ok(deleted as u64)
+            let queries = conn.execute(
+                "DELETE FROM sys_query_log WHERE timestamp < ?",
+                [timestamp_ms],
+            )?;
+            Ok((deleted + queries) as u64)
+        })
+    }
+
+    /// Delete every log entry, query log included
+    pub fn purge(&self) -> Result<u64> {
+        self.with_connection_write(|conn| {
+            let deleted = conn.execute("DELETE FROM sys_logs", [])?;
+            let queries = conn.execute("DELETE FROM sys_query_log", [])?;
+            Ok((deleted + queries) as u64)
         })
     }
 
+    /// Log levels in effect
+    pub fn settings(&self) -> LoggingSettings {
+        self.settings.read().unwrap().clone()
+    }
+
+    /// Change the log levels for this service (e.g. after settings.json changed)
+    pub fn set_settings(&self, settings: LoggingSettings) {
+        *self.settings.write().unwrap() = settings;
+    }
+
     /// Export logs to a file for troubleshooting
     ///
     /// Creates a copy of the logs database that can be sent for analysis.
@@ -460,6 +678,74 @@ impl LoggingService {
     }
 }
 
+/// A statement with string and number literals replaced by `?`, comments
+/// dropped and whitespace collapsed, so it carries no user data
+pub fn normalize_statement(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    // Digits inside identifiers (e.g. `archive_2019`) aren't literals
+    let mut in_identifier = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                // '' inside a string is an escaped quote
+                while let Some(next) = chars.next() {
+                    if next == '\'' {
+                        if chars.peek() == Some(&'\'') {
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                }
+                out.push('?');
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                chars.by_ref().find(|&next| next == '\n');
+                push_space(&mut out);
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                chars.by_ref().find(|&next| {
+                    let end = previous == '*' && next == '/';
+                    previous = next;
+                    end
+                });
+                push_space(&mut out);
+            }
+            c if c.is_ascii_digit() && !in_identifier => {
+                while chars
+                    .peek()
+                    .is_some_and(|next| next.is_ascii_digit() || *next == '.')
+                {
+                    chars.next();
+                }
+                out.push('?');
+            }
+            c if c.is_whitespace() => push_space(&mut out),
+            c => out.push(c),
+        }
+        in_identifier = out
+            .chars()
+            .last()
+            .is_some_and(|last| last.is_alphanumeric() || last == '_');
+    }
+    out.trim().to_string()
+}
+
+fn push_space(out: &mut String) {
+    if !out.is_empty() && !out.ends_with(' ') {
+        out.push(' ');
+    }
+}
+
+/// Short hash identifying a normalized statement
+fn statement_hash(statement: &str) -> String {
+    let digest = Sha256::digest(statement.as_bytes());
+    hex::encode(&digest[..8])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -525,6 +811,107 @@ mod tests {
             Some("Connection timeout".to_string())
         );
         assert_eq!(errors[0].error_details, Some("at line 42".to_string()));
+
+        service.log_event("sync_completed").unwrap();
+        assert_eq!(service.count_errors_since(0).unwrap(), 1);
+        assert_eq!(service.count_errors_since(i64::MAX).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_log_levels() {
+        let dir = tempdir().unwrap();
+        let service = LoggingService::new(dir.path(), EntryPoint::Desktop, "1.0.0").unwrap();
+        service.set_settings(LoggingSettings {
+            level: LogLevel::Actions,
+            categories: [(LogCategory::Sync, LogLevel::Errors)].into(),
+        });
+
+        service.log_page("accounts").unwrap();
+        service.log_event("app_started").unwrap();
+        service
+            .log(LogEvent::new("sync_started").with_integration("simplefin"))
+            .unwrap();
+        service
+            .log(
+                LogEvent::new("sync_failed")
+                    .with_integration("simplefin")
+                    .with_error("timeout"),
+            )
+            .unwrap();
+
+        let events: Vec<String> = service
+            .get_recent(10)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.event)
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert!(events.contains(&"app_started".to_string()));
+        assert!(events.contains(&"sync_failed".to_string()));
+
+        assert_eq!(service.purge().unwrap(), 2);
+        assert_eq!(service.count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_normalize_statement() {
+        assert_eq!(
+            normalize_statement(
+                "SELECT * FROM transactions\n  WHERE description ILIKE '%doctor''s%' -- pharmacy\n  AND amount < -42.50 LIMIT 10"
+            ),
+            "SELECT * FROM transactions WHERE description ILIKE ? AND amount < -? LIMIT ?"
+        );
+        assert_eq!(
+            normalize_statement("SELECT /* 2019 */ * FROM archive_2019.transactions WHERE x = ?"),
+            "SELECT * FROM archive_2019.transactions WHERE x = ?"
+        );
+    }
+
+    #[test]
+    fn test_log_query() {
+        let dir = tempdir().unwrap();
+        let service = LoggingService::new(dir.path(), EntryPoint::Desktop, "1.0.0").unwrap();
+
+        let query = |sql, duration_ms| QueryAudit {
+            caller: QUERY_CALLER_USER,
+            sql,
+            duration_ms,
+            row_count: Some(1),
+            error: None,
+        };
+        service
+            .log_query(&query("SELECT * FROM accounts WHERE name = 'Checking'", 5))
+            .unwrap();
+        service
+            .log_query(&query("SELECT * FROM accounts WHERE name = 'Savings'", 900))
+            .unwrap();
+        service
+            .log_query(&QueryAudit {
+                caller: "budget",
+                error: Some("Table not found"),
+                row_count: None,
+                ..query("SELECT * FROM nope", 1)
+            })
+            .unwrap();
+
+        let all = service.get_queries(None, 10).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[1].query_hash, all[2].query_hash);
+        assert_eq!(all[1].statement, "SELECT * FROM accounts WHERE name = ?");
+
+        let slow = service.get_queries(Some(100), 10).unwrap();
+        assert_eq!(slow.len(), 1);
+        assert_eq!(slow[0].duration_ms, 900);
+
+        // Below full, only failures are kept
+        service.set_settings(LoggingSettings {
+            level: LogLevel::Actions,
+            ..Default::default()
+        });
+        service.log_query(&query("SELECT 1", 1)).unwrap();
+        assert_eq!(service.get_queries(None, 10).unwrap().len(), 3);
+
+        assert_eq!(service.purge().unwrap(), 3);
     }
 
     #[test]
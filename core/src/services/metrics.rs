@@ -0,0 +1,305 @@
+//! Metrics service - Prometheus text exposition of sync and storage health
+//!
+//! Treeline has no long-running server, so metrics are rendered on demand
+//! (`tl metrics`) for node_exporter's textfile collector or any scraper
+//! that can run a command. Everything comes from state that is already
+//! recorded: sync history, the database file and the backups directory.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+use crate::adapters::duckdb::{DuckDbRepository, SyncHistoryEntry};
+use crate::services::BackupService;
+
+/// Sync runs considered for the counters and histogram
+const SYNC_HISTORY_LIMIT: usize = 10_000;
+
+/// Upper bounds of the sync duration histogram, in seconds
+const DURATION_BUCKETS: &[f64] = &[1.0, 5.0, 15.0, 30.0, 60.0, 120.0, 300.0];
+
+/// Metrics service for rendering Prometheus metrics
+pub struct MetricsService {
+    repository: Arc<DuckDbRepository>,
+    treeline_dir: PathBuf,
+    db_filename: String,
+}
+
+impl MetricsService {
+    pub fn new(
+        repository: Arc<DuckDbRepository>,
+        treeline_dir: PathBuf,
+        db_filename: String,
+    ) -> Self {
+        Self {
+            repository,
+            treeline_dir,
+            db_filename,
+        }
+    }
+
+    /// All metrics in the Prometheus text format
+    pub fn render(&self) -> Result<String> {
+        let history = self.repository.get_sync_history(None, SYNC_HISTORY_LIMIT)?;
+        let db_size = fs::metadata(self.treeline_dir.join(&self.db_filename))
+            .map(|m| m.len())
+            .unwrap_or(0);
+        let backups =
+            BackupService::new(self.treeline_dir.clone(), self.db_filename.clone()).list()?;
+
+        let mut out = String::new();
+        render_sync_metrics(&mut out, &history);
+
+        gauge(
+            &mut out,
+            "treeline_database_size_bytes",
+            "Size of the database file",
+        );
+        sample(&mut out, "treeline_database_size_bytes", "", db_size as f64);
+
+        gauge(
+            &mut out,
+            "treeline_backups",
+            "Number of backups in the backups directory",
+        );
+        sample(&mut out, "treeline_backups", "", backups.len() as f64);
+        if let Some(latest) = backups.first() {
+            gauge(
+                &mut out,
+                "treeline_backup_last_timestamp_seconds",
+                "When the newest backup was created",
+            );
+            sample(
+                &mut out,
+                "treeline_backup_last_timestamp_seconds",
+                "",
+                latest.created_at.timestamp() as f64,
+            );
+        }
+
+        gauge(
+            &mut out,
+            "treeline_metrics_generated_timestamp_seconds",
+            "When these metrics were rendered",
+        );
+        sample(
+            &mut out,
+            "treeline_metrics_generated_timestamp_seconds",
+            "",
+            Utc::now().timestamp() as f64,
+        );
+
+        Ok(out)
+    }
+}
+
+#[derive(Default)]
+struct IntegrationStats {
+    successes: u64,
+    errors: u64,
+    last_success: Option<DateTime<Utc>>,
+    last_duration_secs: Option<f64>,
+    /// Cumulative counts per bucket, then +Inf
+    buckets: Vec<u64>,
+    duration_sum: f64,
+}
+
+fn render_sync_metrics(out: &mut String, history: &[SyncHistoryEntry]) {
+    let mut stats: BTreeMap<&str, IntegrationStats> = BTreeMap::new();
+    // History is newest first
+    for entry in history {
+        let s = stats
+            .entry(entry.integration.as_str())
+            .or_insert_with(|| IntegrationStats {
+                buckets: vec![0; DURATION_BUCKETS.len() + 1],
+                ..Default::default()
+            });
+        let secs = entry.duration_ms as f64 / 1000.0;
+        s.last_duration_secs.get_or_insert(secs);
+        if entry.error.is_some() {
+            s.errors += 1;
+        } else {
+            s.successes += 1;
+            s.last_success.get_or_insert(entry.started_at);
+        }
+        for (i, bound) in DURATION_BUCKETS.iter().enumerate() {
+            if secs <= *bound {
+                s.buckets[i] += 1;
+            }
+        }
+        s.buckets[DURATION_BUCKETS.len()] += 1;
+        s.duration_sum += secs;
+    }
+
+    counter(
+        out,
+        "treeline_sync_runs_total",
+        "Recorded sync runs by result",
+    );
+    for (integration, s) in &stats {
+        let label = label_value(integration);
+        sample(
+            out,
+            "treeline_sync_runs_total",
+            &format!("integration=\"{}\",result=\"success\"", label),
+            s.successes as f64,
+        );
+        sample(
+            out,
+            "treeline_sync_runs_total",
+            &format!("integration=\"{}\",result=\"error\"", label),
+            s.errors as f64,
+        );
+    }
+
+    gauge(
+        out,
+        "treeline_sync_last_success_timestamp_seconds",
+        "When the last successful sync started",
+    );
+    for (integration, s) in &stats {
+        if let Some(at) = s.last_success {
+            sample(
+                out,
+                "treeline_sync_last_success_timestamp_seconds",
+                &format!("integration=\"{}\"", label_value(integration)),
+                at.timestamp() as f64,
+            );
+        }
+    }
+
+    gauge(
+        out,
+        "treeline_sync_last_duration_seconds",
+        "Duration of the most recent sync",
+    );
+    for (integration, s) in &stats {
+        if let Some(secs) = s.last_duration_secs {
+            sample(
+                out,
+                "treeline_sync_last_duration_seconds",
+                &format!("integration=\"{}\"", label_value(integration)),
+                secs,
+            );
+        }
+    }
+
+    let _ = writeln!(
+        out,
+        "# HELP treeline_sync_duration_seconds Duration of recorded sync runs"
+    );
+    let _ = writeln!(out, "# TYPE treeline_sync_duration_seconds histogram");
+    for (integration, s) in &stats {
+        let label = label_value(integration);
+        for (i, bound) in DURATION_BUCKETS.iter().enumerate() {
+            sample(
+                out,
+                "treeline_sync_duration_seconds_bucket",
+                &format!("integration=\"{}\",le=\"{}\"", label, bound),
+                s.buckets[i] as f64,
+            );
+        }
+        let total = s.buckets[DURATION_BUCKETS.len()] as f64;
+        sample(
+            out,
+            "treeline_sync_duration_seconds_bucket",
+            &format!("integration=\"{}\",le=\"+Inf\"", label),
+            total,
+        );
+        sample(
+            out,
+            "treeline_sync_duration_seconds_sum",
+            &format!("integration=\"{}\"", label),
+            s.duration_sum,
+        );
+        sample(
+            out,
+            "treeline_sync_duration_seconds_count",
+            &format!("integration=\"{}\"", label),
+            total,
+        );
+    }
+}
+
+fn gauge(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+}
+
+fn counter(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} counter", name);
+}
+
+fn sample(out: &mut String, name: &str, labels: &str, value: f64) {
+    if labels.is_empty() {
+        let _ = writeln!(out, "{} {}", name, value);
+    } else {
+        let _ = writeln!(out, "{}{{{}}} {}", name, labels, value);
+    }
+}
+
+/// Escape a label value: backslash, double quote and newline
+fn label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(integration: &str, duration_ms: i64, error: Option<&str>) -> SyncHistoryEntry {
+        SyncHistoryEntry {
+            sync_id: "s".to_string(),
+            integration: integration.to_string(),
+            started_at: Utc::now(),
+            duration_ms,
+            sync_type: None,
+            accounts_synced: 0,
+            transactions_discovered: 0,
+            transactions_new: 0,
+            transactions_skipped: 0,
+            account_stats: Default::default(),
+            warnings: Vec::new(),
+            error: error.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_sync_metrics() {
+        let history = vec![
+            entry("simplefin", 2_000, Some("timeout")),
+            entry("simplefin", 40_000, None),
+            entry("simplefin", 500, None),
+        ];
+        let mut out = String::new();
+        render_sync_metrics(&mut out, &history);
+
+        assert!(out
+            .contains("treeline_sync_runs_total{integration=\"simplefin\",result=\"success\"} 2"));
+        assert!(
+            out.contains("treeline_sync_runs_total{integration=\"simplefin\",result=\"error\"} 1")
+        );
+        assert!(out.contains("treeline_sync_last_duration_seconds{integration=\"simplefin\"} 2\n"));
+        assert!(out.contains(
+            "treeline_sync_duration_seconds_bucket{integration=\"simplefin\",le=\"5\"} 2"
+        ));
+        assert!(out.contains(
+            "treeline_sync_duration_seconds_bucket{integration=\"simplefin\",le=\"+Inf\"} 3"
+        ));
+        assert!(out.contains("# TYPE treeline_sync_duration_seconds histogram"));
+    }
+
+    #[test]
+    fn test_label_value_escaping() {
+        assert_eq!(label_value("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
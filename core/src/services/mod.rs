@@ -3,32 +3,148 @@
 //! Services coordinate domain logic and port interactions. Each service
 //! focuses on a specific use case or feature area.
 
+mod account;
+mod archive;
 mod backup;
 mod balance;
+mod cdc;
 mod compact;
+mod data_dir;
 mod demo;
+mod device_sync;
+mod diagnostics;
+mod disk_space;
 mod doctor;
+mod duplicates;
 pub mod encryption;
+mod hooks;
 pub mod import;
+mod import_folder;
+mod income;
+mod institution;
+mod investment;
+pub mod ledger_import;
 pub mod logging;
+mod metrics;
 pub mod migration;
+mod nl_query;
+mod operations;
+mod pdf_statement;
+#[cfg(feature = "plugins")]
 pub mod plugin;
+#[cfg(feature = "plugins")]
+mod plugin_permissions;
+mod presets;
+#[cfg(feature = "http")]
+mod publish;
 mod query;
+mod query_budget;
+#[cfg(feature = "reports")]
+mod refresh;
+#[cfg(feature = "reports")]
+mod report;
+#[cfg(feature = "reports")]
+mod report_cache;
 mod status;
+mod storage;
 mod sync;
 mod tag;
+pub mod token;
+mod tools;
+mod transaction;
+mod usage_ping;
 
+pub use account::{AccountDetails, AccountInfo, AccountService, SignPolicyChange};
+pub use archive::ArchiveService;
 pub use backup::BackupService;
 pub use balance::{BackfillExecuteResult, BalanceService, BalanceSnapshotPreview};
+pub use cdc::{sse_event, CdcService, MAX_CHANGES_PER_PAGE};
 pub use compact::CompactService;
+pub use data_dir::{
+    detect_cloud_sync, portable_data_dir, resolve_data_dir, CloudSyncProvider, DataDirService,
+    MoveResult, MOVED_TO_FILE, PORTABLE_FLAG_FILE,
+};
 pub use demo::DemoService;
-pub use doctor::DoctorService;
-pub use encryption::EncryptionService;
-pub use import::{ImportOptions, ImportResult, ImportService, NumberFormat};
-pub use logging::{EntryPoint, LogEntry, LogEvent, LoggingService};
+pub use device_sync::{
+    DevicePeerStatus, DeviceSyncResult, DeviceSyncService, DeviceSyncStatus,
+    DEVICE_SYNC_PASSPHRASE_ENV,
+};
+pub use diagnostics::{
+    Diagnostics, DiagnosticsService, LogErrorCounts, MigrationInfo, TableRowCount,
+};
+pub use doctor::{DoctorResult, DoctorService};
+pub use duplicates::{DuplicateCandidate, DuplicateOptions, DuplicateService};
+pub use encryption::{CalibrationResult, EncryptionService, EncryptionStage};
+pub use hooks::{HookEvent, HookOutcome, HookService};
+pub use import::{
+    date_format_label, detect_date_format, headerless_column_names, sniff_csv, BalanceImpact,
+    BalanceMismatch, BalanceMismatchKind, CsvEncoding, CsvHeaders, DetectedDateFormat,
+    ImportOptions, ImportPreset, ImportProgress, ImportResult, ImportRowError, ImportRowStatus,
+    ImportService, ImportStage, ImportValidation, MonthlyBalanceDelta, NumberFormat, OrderMatch,
+    OrderMatchResult, QuoteStyle, RowStatus, UndoImportResult, ValidationIssue,
+    ValidationIssueKind,
+};
+pub use import_folder::{
+    imports_dir, list_pending_imports, move_to_imported, FolderImportFile, FolderImportStatus,
+    FolderImportSummary, ImportFolderService, IMPORTED_DIR, IMPORTS_DIR,
+};
+pub use income::{
+    expected_deposits, Cadence, ExpectedDeposit, IncomeService, IncomeStream, IncomeSummary,
+};
+pub use institution::{InstitutionInfo, InstitutionRefreshResult, InstitutionService};
+pub use investment::{HoldingInfo, HoldingsSummary, InvestmentService, LotInfo};
+pub use ledger_import::{
+    LedgerAccountReport, LedgerFormat, LedgerImportResult, LedgerImportService, UntranslatedItem,
+};
+pub use logging::{
+    normalize_statement, EntryPoint, LogEntry, LogEvent, LoggingService, QueryAudit, QueryLogEntry,
+    UsedNames, QUERY_CALLER_USER,
+};
+pub use metrics::MetricsService;
 pub use migration::{MigrationResult, MigrationService};
-pub use plugin::{PluginInfo, PluginManifest, PluginResult, PluginService, UpdateInfo};
-pub use query::QueryService;
+pub use nl_query::{NlQuery, NlQueryResult};
+pub use operations::{
+    CancellationToken, OnceOutcome, OperationHandle, OperationKind, OperationRegistry,
+    OperationState, OperationStatus,
+};
+#[cfg(feature = "pdf")]
+pub use pdf_statement::read_statement_table;
+pub use pdf_statement::{extract_statement_table, StatementTable};
+#[cfg(feature = "plugins")]
+pub use plugin::{
+    PluginInfo, PluginManifest, PluginResult, PluginService, UpdateInfo, VersionedPluginState,
+};
+#[cfg(feature = "plugins")]
+pub use plugin_permissions::{validate_query_permissions, PluginContext};
+pub use presets::{
+    detect_bank_preset, detect_import_preset, find_bank_preset, BankPreset, PresetMatch,
+    BANK_PRESETS,
+};
+#[cfg(feature = "http")]
+pub use publish::{PublishResult, PublishService, Sensor, HOME_ASSISTANT_TOKEN_ENV};
+pub use query::{QueryService, SavedQuery};
+pub use query_budget::{ConsumerUsage, QueryBudget, QueryBudgetReport, DEFAULT_QUERY_BUDGET_SHARE};
+#[cfg(feature = "reports")]
+pub use refresh::{RefreshResult, RefreshService, PAYMENT_DUE_DAYS};
+#[cfg(feature = "reports")]
+pub use report::{
+    parse_month, CardCycle, CashFlows, CategoryTotal, CompareBy, ComparisonRow, FlowLink,
+    FlowNode, FlowNodeKind, Period, PeriodComparison, ReportService, Statement, StatementLine,
+    MAX_FLOW_MERCHANTS,
+};
+#[cfg(feature = "reports")]
+pub use report_cache::{ReportCache, ReportCacheStats};
 pub use status::{AccountSummary, DateRange, StatusService, StatusSummary};
-pub use sync::SyncService;
+pub use storage::{
+    format_bytes, PruneResult, StorageAction, StorageBreakdown, StorageGroup, StorageService,
+    StorageSuggestion, TableStorage, PRUNABLE_TABLES,
+};
+pub use sync::{
+    AccountSyncActivity, AccountSyncFailure, IntegrationSyncResult, RemoteAccount, SyncProgress,
+    SyncResult, SyncService, SyncStage,
+};
 pub use tag::{AutoTagResult, RuleFailure, TagResult, TagResultEntry, TagService};
+pub use token::{IssuedToken, TokenGrant, TokenService};
+pub use tools::{ToolDefinition, ToolService};
+pub use transaction::{TransactionEdit, TransactionFilter, TransactionInfo, TransactionService};
+pub use usage_ping::{UsagePing, UsagePingService, UsagePingStatus, USAGE_PING_URL};
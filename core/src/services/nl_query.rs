@@ -0,0 +1,444 @@
+//! Natural-language questions translated to SQL through local templates
+//!
+//! Only a fixed set of question shapes is understood ("how much did I spend
+//! on groceries last month", "top 5 expenses this year", ...). Each maps to
+//! a parameterized, read-only query; nothing is sent anywhere. The SQL is
+//! returned with the results so the user can see exactly what was run.
+
+use anyhow::Result;
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use super::tools::Filters;
+use crate::adapters::duckdb::QueryResult;
+
+const DEFAULT_TOP_LIMIT: usize = 10;
+/// Also caps transaction searches
+const MAX_TOP_LIMIT: usize = 100;
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+const EXAMPLES: &str = "\"how much did I spend on groceries last month\", \
+    \"how much did I spend at amazon this year\", \"spending by category in march\", \
+    \"top 5 expenses last 30 days\", \"show transactions matching netflix\", \
+    \"how much did I earn last year\", \"my balances\", \"my net worth\"";
+
+/// A question translated to SQL
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct NlQuery {
+    /// Template that matched, e.g. "spending_on_tag"
+    pub template: String,
+    /// How the question was understood, for the user to confirm
+    pub interpretation: String,
+    pub sql: String,
+    /// Values bound to the ? placeholders in `sql`
+    pub params: Vec<serde_json::Value>,
+}
+
+/// A translated question together with its results
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct NlQueryResult {
+    #[serde(flatten)]
+    pub query: NlQuery,
+    pub result: QueryResult,
+}
+
+/// Inclusive date range from the end of a question; open when not given
+#[derive(Debug, PartialEq)]
+struct Period {
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+}
+
+impl Period {
+    const ALL_TIME: Period = Period {
+        start: None,
+        end: None,
+    };
+
+    fn between(start: NaiveDate, end: NaiveDate) -> Self {
+        Self {
+            start: Some(start),
+            end: Some(end),
+        }
+    }
+
+    fn label(&self) -> String {
+        match (self.start, self.end) {
+            (Some(start), Some(end)) if start == end => format!("on {}", start),
+            (Some(start), Some(end)) => format!("from {} to {}", start, end),
+            (Some(start), None) => format!("since {}", start),
+            (None, Some(end)) => format!("until {}", end),
+            (None, None) => "all time".to_string(),
+        }
+    }
+}
+
+/// Translate a question into a parameterized query
+///
+/// Relative periods ("last month") are resolved against `today`. Fails with
+/// example questions when the question doesn't fit any template.
+pub fn translate(question: &str, today: NaiveDate) -> Result<NlQuery> {
+    let normalized = normalize(question);
+    let (rest, period) = split_period(&normalized, today)?;
+    let rest = rest.as_str();
+
+    let spend = Regex::new(r"^how much (?:did|have) i spen[dt](?: (on|at) (.+))?$").unwrap();
+    let earn = Regex::new(r"^how much (?:did|have) i (?:earn|earned|make|made|receive|received)$")
+        .unwrap();
+    let by_category =
+        Regex::new(r"^(?:show )?(?:my )?(?:spending|expenses) by (?:category|tag)$").unwrap();
+    let top = Regex::new(
+        r"^(?:what were |show |list )?(?:my )?(?:top|biggest|largest)(?: (\d+))? (?:expenses|purchases|transactions)$",
+    )
+    .unwrap();
+    let search = Regex::new(
+        r"^(?:show|list|find)(?: me)?(?: all)?(?: my)? transactions (?:at|from|for|with|matching) (.+)$",
+    )
+    .unwrap();
+    let balances = Regex::new(r"^(?:what (?:are|is) |show )?my (?:account )?balances?$").unwrap();
+    let net_worth = Regex::new(r"^(?:what is |what's |show )?my net worth$").unwrap();
+
+    if let Some(caps) = spend.captures(rest) {
+        return Ok(spending(&caps, &period));
+    }
+    if earn.is_match(rest) {
+        let mut filters = Filters::default();
+        filters.push("amount > 0", &[]);
+        filters.date_range(period.start, period.end);
+        return Ok(query(
+            "income",
+            format!("Income {}", period.label()),
+            format!(
+                "SELECT SUM(amount) AS income, COUNT(*) AS transactions FROM transactions {}",
+                filters.where_clause()
+            ),
+            filters,
+        ));
+    }
+    if by_category.is_match(rest) {
+        let mut filters = Filters::default();
+        filters.push("amount < 0", &[]);
+        filters.date_range(period.start, period.end);
+        return Ok(query(
+            "spending_by_category",
+            format!("Spending per tag {}", period.label()),
+            format!(
+                "SELECT category, SUM(-amount) AS spent, COUNT(*) AS transactions \
+                 FROM ( \
+                     SELECT amount, UNNEST(CASE WHEN tags IS NULL OR len(tags) = 0 \
+                         THEN ['(untagged)'] ELSE tags END) AS category \
+                     FROM transactions {} \
+                 ) \
+                 GROUP BY category ORDER BY spent DESC",
+                filters.where_clause()
+            ),
+            filters,
+        ));
+    }
+    if let Some(caps) = top.captures(rest) {
+        let limit = caps
+            .get(1)
+            .and_then(|n| n.as_str().parse().ok())
+            .unwrap_or(DEFAULT_TOP_LIMIT)
+            .clamp(1, MAX_TOP_LIMIT);
+        let mut filters = Filters::default();
+        filters.push("amount < 0", &[]);
+        filters.date_range(period.start, period.end);
+        return Ok(query(
+            "top_expenses",
+            format!("The {} largest expenses {}", limit, period.label()),
+            format!(
+                "SELECT transaction_date, description, amount, account_name, tags \
+                 FROM transactions {} ORDER BY amount ASC LIMIT {}",
+                filters.where_clause(),
+                limit
+            ),
+            filters,
+        ));
+    }
+    if let Some(caps) = search.captures(rest) {
+        let text = caps[1].to_string();
+        let mut filters = Filters::default();
+        filters.push("contains(lower(description), ?)", &[text.clone().into()]);
+        filters.date_range(period.start, period.end);
+        return Ok(query(
+            "search_transactions",
+            format!("Transactions mentioning '{}' {}", text, period.label()),
+            format!(
+                "SELECT transaction_date, description, amount, account_name, tags \
+                 FROM transactions {} ORDER BY transaction_date DESC LIMIT {}",
+                filters.where_clause(),
+                MAX_TOP_LIMIT
+            ),
+            filters,
+        ));
+    }
+    if balances.is_match(rest) {
+        return Ok(query(
+            "balances",
+            "Current balance of each account".to_string(),
+            "SELECT COALESCE(nickname, name) AS account, classification, balance, currency \
+             FROM accounts ORDER BY classification, account"
+                .to_string(),
+            Filters::default(),
+        ));
+    }
+    if net_worth.is_match(rest) {
+        return Ok(query(
+            "net_worth",
+            "Current net worth (assets minus liabilities) per currency".to_string(),
+            "SELECT currency, \
+             SUM(CASE WHEN classification = 'liability' THEN -ABS(balance) ELSE balance END) \
+             AS net_worth \
+             FROM accounts WHERE balance IS NOT NULL GROUP BY currency ORDER BY currency"
+                .to_string(),
+            Filters::default(),
+        ));
+    }
+
+    anyhow::bail!(
+        "Couldn't understand \"{}\". Try questions like {}.",
+        question.trim(),
+        EXAMPLES
+    )
+}
+
+/// "how much did I spend [on <tag> | at <merchant>]"
+fn spending(caps: &Captures, period: &Period) -> NlQuery {
+    let mut filters = Filters::default();
+    filters.push("amount < 0", &[]);
+    let (template, subject) = match (caps.get(1).map(|m| m.as_str()), caps.get(2)) {
+        (Some("on"), Some(tag)) => {
+            filters.push(
+                "list_contains(list_transform(tags, t -> lower(t)), ?)",
+                &[tag.as_str().into()],
+            );
+            ("spending_on_tag", format!(" tagged '{}'", tag.as_str()))
+        }
+        (Some(_), Some(merchant)) => {
+            filters.push(
+                "contains(lower(description), ?)",
+                &[merchant.as_str().into()],
+            );
+            ("spending_at", format!(" at '{}'", merchant.as_str()))
+        }
+        _ => ("spending", String::new()),
+    };
+    filters.date_range(period.start, period.end);
+
+    query(
+        template,
+        format!("Spending{} {}", subject, period.label()),
+        format!(
+            "SELECT SUM(-amount) AS spent, COUNT(*) AS transactions FROM transactions {}",
+            filters.where_clause()
+        ),
+        filters,
+    )
+}
+
+fn query(template: &str, interpretation: String, sql: String, filters: Filters) -> NlQuery {
+    NlQuery {
+        template: template.to_string(),
+        interpretation,
+        sql,
+        params: filters.params,
+    }
+}
+
+/// Lowercase, collapse whitespace and drop trailing punctuation
+fn normalize(question: &str) -> String {
+    question
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches(['?', '.', '!'])
+        .trim()
+        .to_lowercase()
+}
+
+/// Split a trailing period ("last month", "in march 2024") off a question
+fn split_period(question: &str, today: NaiveDate) -> Result<(String, Period)> {
+    let period_re = Regex::new(&format!(
+        r"^(?P<rest>.*?)(?: (?:in|during|for|over|from))?(?: the)? (?P<period>today|yesterday|this week|last week|this month|last month|this year|last year|(?:last|past) (?P<n>\d+) (?P<unit>day|week|month)s?|(?P<month>{})(?: (?P<month_year>\d{{4}}))?|(?P<year>\d{{4}})|since (?P<since>\d{{4}}-\d{{2}}-\d{{2}}))$",
+        MONTHS.join("|")
+    ))
+    .unwrap();
+
+    let Some(caps) = period_re.captures(question) else {
+        return Ok((question.to_string(), Period::ALL_TIME));
+    };
+    let rest = caps["rest"].to_string();
+    let month_start = today.with_day(1).unwrap();
+
+    let period = match &caps["period"] {
+        "today" => Period::between(today, today),
+        "yesterday" => {
+            let day = today - Duration::days(1);
+            Period::between(day, day)
+        }
+        "this week" => Period::between(week_start(today), today),
+        "last week" => {
+            let start = week_start(today) - Duration::days(7);
+            Period::between(start, start + Duration::days(6))
+        }
+        "this month" => Period::between(month_start, today),
+        "last month" => {
+            let start = month_start - Months::new(1);
+            Period::between(start, month_start - Duration::days(1))
+        }
+        "this year" => Period::between(year_start(today.year())?, today),
+        "last year" => Period::between(
+            year_start(today.year() - 1)?,
+            year_start(today.year())? - Duration::days(1),
+        ),
+        _ => {
+            if let Some(n) = caps.name("n") {
+                let n: u32 = n.as_str().parse()?;
+                if n == 0 {
+                    anyhow::bail!("The period must be at least one {}", &caps["unit"]);
+                }
+                let start = match &caps["unit"] {
+                    "day" => today - Duration::days(i64::from(n) - 1),
+                    "week" => today - Duration::weeks(i64::from(n)) + Duration::days(1),
+                    _ => today - Months::new(n) + Duration::days(1),
+                };
+                Period::between(start, today)
+            } else if let Some(month) = caps.name("month") {
+                let month = MONTHS.iter().position(|m| *m == month.as_str()).unwrap() as u32 + 1;
+                // Without a year, the most recent such month
+                let year = match caps.name("month_year") {
+                    Some(year) => year.as_str().parse()?,
+                    None if month > today.month() => today.year() - 1,
+                    None => today.year(),
+                };
+                let start = NaiveDate::from_ymd_opt(year, month, 1)
+                    .ok_or_else(|| anyhow::anyhow!("Invalid month"))?;
+                Period::between(start, start + Months::new(1) - Duration::days(1))
+            } else if let Some(year) = caps.name("year") {
+                let year: i32 = year.as_str().parse()?;
+                Period::between(year_start(year)?, year_start(year + 1)? - Duration::days(1))
+            } else {
+                let since = NaiveDate::parse_from_str(&caps["since"], "%Y-%m-%d")
+                    .map_err(|_| anyhow::anyhow!("Invalid date '{}'", &caps["since"]))?;
+                Period::between(since, today)
+            }
+        }
+    };
+
+    Ok((rest, period))
+}
+
+fn week_start(day: NaiveDate) -> NaiveDate {
+    day - Duration::days(i64::from(day.weekday().num_days_from_monday()))
+}
+
+fn year_start(year: i32) -> Result<NaiveDate> {
+    NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(|| anyhow::anyhow!("Invalid year {}", year))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        // A Friday
+        NaiveDate::from_ymd_opt(2026, 10, 16).unwrap()
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_periods() {
+        let cases = [
+            ("x today", "2026-10-16", "2026-10-16"),
+            ("x last month", "2026-09-01", "2026-09-30"),
+            ("x this week", "2026-10-12", "2026-10-16"),
+            ("x last week", "2026-10-05", "2026-10-11"),
+            ("x in the last 30 days", "2026-09-17", "2026-10-16"),
+            ("x in march", "2026-03-01", "2026-03-31"),
+            ("x in november", "2025-11-01", "2025-11-30"),
+            ("x in february 2024", "2024-02-01", "2024-02-29"),
+            ("x last year", "2025-01-01", "2025-12-31"),
+            ("x since 2026-07-04", "2026-07-04", "2026-10-16"),
+        ];
+        for (question, start, end) in cases {
+            let (rest, period) = split_period(question, today()).unwrap();
+            assert_eq!(rest, "x", "{}", question);
+            assert_eq!(
+                period,
+                Period::between(date(start), date(end)),
+                "{}",
+                question
+            );
+        }
+
+        let (rest, period) = split_period("my balances", today()).unwrap();
+        assert_eq!(rest, "my balances");
+        assert_eq!(period, Period::ALL_TIME);
+    }
+
+    #[test]
+    fn test_spending_on_tag() {
+        let query = translate("How much did I spend on Groceries last month?", today()).unwrap();
+        assert_eq!(query.template, "spending_on_tag");
+        assert_eq!(
+            query.interpretation,
+            "Spending tagged 'groceries' from 2026-09-01 to 2026-09-30"
+        );
+        // User text is bound, never spliced into the SQL
+        assert!(!query.sql.contains("groceries"));
+        assert_eq!(
+            query.params,
+            vec![
+                serde_json::json!("groceries"),
+                serde_json::json!("2026-09-01"),
+                serde_json::json!("2026-09-30"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_other_templates() {
+        let templates = [
+            ("how much did I spend at Amazon this year", "spending_at"),
+            ("how much did i spend", "spending"),
+            ("Spending by category in March", "spending_by_category"),
+            ("top 5 expenses last 30 days", "top_expenses"),
+            ("show transactions matching netflix", "search_transactions"),
+            ("how much did I earn last year", "income"),
+            ("What are my balances?", "balances"),
+            ("what's my net worth", "net_worth"),
+        ];
+        for (question, template) in templates {
+            assert_eq!(translate(question, today()).unwrap().template, template);
+        }
+
+        let top = translate("top 500 purchases", today()).unwrap();
+        assert!(top.sql.ends_with("LIMIT 100"));
+    }
+
+    #[test]
+    fn test_unknown_question_suggests_examples() {
+        let err = translate("delete everything", today()).unwrap_err();
+        assert!(err.to_string().contains("how much did I spend"));
+    }
+}
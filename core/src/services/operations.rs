@@ -0,0 +1,487 @@
+//! Operation registry - visibility into long-running work
+//!
+//! Sync, import, backfill, backup, compact and refresh run on background
+//! threads. Each one registers here for its lifetime so a UI can list what
+//! is running, poll progress and ask for cancellation. Finished operations
+//! are kept for a while so a poller still sees how they ended.
+//!
+//! Cancellation is cooperative: `cancel` only sets a flag, and the operation
+//! stops at its next `check_cancelled`. Single-step operations finish normally.
+//!
+//! Work that mustn't run twice at once (a double-clicked Sync button) goes
+//! through `run_once` with a key naming the request. A request whose key
+//! matches a running operation joins it: it waits for that operation and
+//! gets the same result, instead of racing it.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Finished operations kept for status polling
+const FINISHED_TO_KEEP: usize = 20;
+
+/// What an operation is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationKind {
+    Sync,
+    Import,
+    Backfill,
+    Backup,
+    Restore,
+    Compact,
+    /// Precomputing derived state after new data
+    Refresh,
+}
+
+/// Where an operation is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Snapshot of one operation
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationStatus {
+    pub id: String,
+    pub kind: OperationKind,
+    pub state: OperationState,
+    /// Fraction done (0.0 to 1.0), None when the operation can't tell
+    pub progress: Option<f64>,
+    /// Current step, e.g. "Creating backup"
+    pub message: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+    pub cancel_requested: bool,
+    /// Key of a `run_once` request, e.g. "sync"
+    pub key: Option<String>,
+    /// Duplicate requests that joined this operation instead of starting another
+    pub joined: u32,
+}
+
+/// Outcome of `run_once`
+#[derive(Debug, Clone)]
+pub struct OnceOutcome<T> {
+    /// The operation that did the work
+    pub operation_id: String,
+    /// Whether this request joined an operation already running
+    pub joined: bool,
+    pub result: Result<T, String>,
+}
+
+/// A keyed operation's result, once it has one
+#[derive(Default)]
+struct InFlight {
+    operation_id: String,
+    result: Mutex<Option<Arc<dyn Any + Send + Sync>>>,
+    done: Condvar,
+}
+
+struct Entry {
+    status: OperationStatus,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Shared registry of running and recently finished operations
+///
+/// Cheap to clone; clones share the same operations.
+#[derive(Clone, Default)]
+pub struct OperationRegistry {
+    entries: Arc<Mutex<Vec<Entry>>>,
+    /// Running `run_once` operations by key
+    in_flight: Arc<Mutex<HashMap<String, Arc<InFlight>>>>,
+}
+
+impl OperationRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new running operation
+    ///
+    /// The returned handle reports progress and the outcome; dropping it
+    /// without `finish` marks the operation failed.
+    pub fn start(&self, kind: OperationKind) -> OperationHandle {
+        self.start_keyed(kind, None)
+    }
+
+    fn start_keyed(&self, kind: OperationKind, key: Option<&str>) -> OperationHandle {
+        let id = Uuid::new_v4().to_string();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let mut entries = self.entries.lock().unwrap();
+
+        // Entries are oldest first. Leave room for this one, which finishes too.
+        let finished = entries
+            .iter()
+            .filter(|e| e.status.state != OperationState::Running)
+            .count();
+        let mut to_drop = finished.saturating_sub(FINISHED_TO_KEEP - 1);
+        entries.retain(|e| {
+            if to_drop > 0 && e.status.state != OperationState::Running {
+                to_drop -= 1;
+                false
+            } else {
+                true
+            }
+        });
+
+        entries.push(Entry {
+            status: OperationStatus {
+                id: id.clone(),
+                kind,
+                state: OperationState::Running,
+                progress: None,
+                message: None,
+                started_at: Utc::now(),
+                finished_at: None,
+                error: None,
+                cancel_requested: false,
+                key: key.map(str::to_string),
+                joined: 0,
+            },
+            cancel: cancel.clone(),
+        });
+
+        OperationHandle {
+            registry: self.clone(),
+            id,
+            cancel,
+            finished: false,
+        }
+    }
+
+    /// Run `f` as an operation, unless one with the same `key` is running
+    ///
+    /// Then this waits for that one and returns its result without calling
+    /// `f`. Blocks until the result is in, so call it off the UI thread.
+    /// Requests sharing a key must share `T`.
+    pub fn run_once<T, F>(&self, kind: OperationKind, key: &str, f: F) -> OnceOutcome<T>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce(&OperationHandle) -> Result<T, String>,
+    {
+        let (in_flight, operation) = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get(key) {
+                Some(running) => (running.clone(), None),
+                None => {
+                    let operation = self.start_keyed(kind, Some(key));
+                    let running = Arc::new(InFlight {
+                        operation_id: operation.id().to_string(),
+                        ..Default::default()
+                    });
+                    in_flight.insert(key.to_string(), running.clone());
+                    (running, Some(operation))
+                }
+            }
+        };
+
+        let Some(operation) = operation else {
+            self.update(&in_flight.operation_id, |status| status.joined += 1);
+            let mut result = in_flight.result.lock().unwrap();
+            while result.is_none() {
+                result = in_flight.done.wait(result).unwrap();
+            }
+            let result = result.as_ref().unwrap();
+            return OnceOutcome {
+                operation_id: in_flight.operation_id.clone(),
+                joined: true,
+                result: match result.downcast_ref::<Result<T, String>>() {
+                    Some(result) => result.clone(),
+                    None => Err(format!("Operation {} returned another type", key)),
+                },
+            };
+        };
+
+        // Stand-in result in case `f` panics, so joined requests don't wait forever
+        let mut publish = Publish {
+            registry: self,
+            key,
+            in_flight: &in_flight,
+            result: Arc::new(Err::<T, String>(
+                "Operation stopped unexpectedly".to_string(),
+            )),
+        };
+        let result = f(&operation);
+        operation.finish(&result);
+        publish.result = Arc::new(result.clone());
+        drop(publish);
+
+        OnceOutcome {
+            operation_id: in_flight.operation_id.clone(),
+            joined: false,
+            result,
+        }
+    }
+
+    /// All known operations, newest first
+    pub fn list(&self) -> Vec<OperationStatus> {
+        let entries = self.entries.lock().unwrap();
+        entries.iter().rev().map(|e| e.status.clone()).collect()
+    }
+
+    /// One operation by id
+    pub fn get(&self, id: &str) -> Option<OperationStatus> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .find(|e| e.status.id == id)
+            .map(|e| e.status.clone())
+    }
+
+    /// Ask a running operation to stop
+    ///
+    /// Returns false if there is no such operation or it already finished.
+    pub fn cancel(&self, id: &str) -> bool {
+        let mut entries = self.entries.lock().unwrap();
+        match entries
+            .iter_mut()
+            .find(|e| e.status.id == id && e.status.state == OperationState::Running)
+        {
+            Some(entry) => {
+                entry.cancel.store(true, Ordering::SeqCst);
+                entry.status.cancel_requested = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn update(&self, id: &str, f: impl FnOnce(&mut OperationStatus)) {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.iter_mut().find(|e| e.status.id == id) {
+            f(&mut entry.status);
+        }
+    }
+}
+
+/// Hands a `run_once` result to joined requests, even when unwinding
+struct Publish<'a> {
+    registry: &'a OperationRegistry,
+    key: &'a str,
+    in_flight: &'a InFlight,
+    result: Arc<dyn Any + Send + Sync>,
+}
+
+impl Drop for Publish<'_> {
+    fn drop(&mut self) {
+        // Remove the key first: a request arriving now starts a new operation
+        self.registry.in_flight.lock().unwrap().remove(self.key);
+        *self.in_flight.result.lock().unwrap() = Some(self.result.clone());
+        self.in_flight.done.notify_all();
+    }
+}
+
+/// Cooperative cancellation flag, for work handed to code that doesn't know
+/// about operations (e.g. a service checking it between steps)
+///
+/// Cheap to clone; clones share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Handle held by the code doing the work
+pub struct OperationHandle {
+    registry: OperationRegistry,
+    id: String,
+    cancel: Arc<AtomicBool>,
+    finished: bool,
+}
+
+impl OperationHandle {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Report the current step and, if known, the fraction done
+    pub fn set_progress(&self, progress: Option<f64>, message: impl Into<String>) {
+        let message = message.into();
+        self.registry.update(&self.id, |status| {
+            status.progress = progress.map(|p| p.clamp(0.0, 1.0));
+            status.message = Some(message);
+        });
+    }
+
+    /// Whether cancellation was requested
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::SeqCst)
+    }
+
+    /// Error out if cancellation was requested; call between steps
+    pub fn check_cancelled(&self) -> Result<()> {
+        if self.is_cancelled() {
+            anyhow::bail!("Cancelled");
+        }
+        Ok(())
+    }
+
+    /// Token that is cancelled along with this operation
+    pub fn cancellation_token(&self) -> CancellationToken {
+        CancellationToken(self.cancel.clone())
+    }
+
+    /// Record the outcome: completed, failed, or cancelled if it failed after a cancel request
+    pub fn finish<T, E: Display>(mut self, result: &std::result::Result<T, E>) {
+        let cancelled = self.is_cancelled();
+        self.registry.update(&self.id, |status| {
+            status.finished_at = Some(Utc::now());
+            match result {
+                Ok(_) => {
+                    status.state = OperationState::Completed;
+                    status.progress = Some(1.0);
+                }
+                Err(_) if cancelled => status.state = OperationState::Cancelled,
+                Err(e) => {
+                    status.state = OperationState::Failed;
+                    status.error = Some(e.to_string());
+                }
+            }
+        });
+        self.finished = true;
+    }
+}
+
+impl Drop for OperationHandle {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.registry.update(&self.id, |status| {
+                status.state = OperationState::Failed;
+                status.finished_at = Some(Utc::now());
+                status.error = Some("Operation stopped unexpectedly".to_string());
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operation_lifecycle() {
+        let registry = OperationRegistry::new();
+        let sync = registry.start(OperationKind::Sync);
+        let id = sync.id().to_string();
+
+        sync.set_progress(Some(0.5), "Syncing");
+        let status = registry.get(&id).unwrap();
+        assert_eq!(status.state, OperationState::Running);
+        assert_eq!(status.progress, Some(0.5));
+
+        let token = sync.cancellation_token();
+        assert!(!token.is_cancelled());
+        assert!(registry.cancel(&id));
+        assert!(sync.check_cancelled().is_err());
+        assert!(token.is_cancelled());
+        sync.finish(&Err::<(), _>("Cancelled"));
+        let status = registry.get(&id).unwrap();
+        assert_eq!(status.state, OperationState::Cancelled);
+        assert!(!registry.cancel(&id));
+
+        // A handle dropped mid-way (e.g. a panic) counts as failed
+        let backup = registry.start(OperationKind::Backup);
+        let backup_id = backup.id().to_string();
+        drop(backup);
+        assert_eq!(
+            registry.get(&backup_id).unwrap().state,
+            OperationState::Failed
+        );
+        assert_eq!(registry.list()[0].id, backup_id);
+    }
+
+    #[test]
+    fn test_keeps_limited_history() {
+        let registry = OperationRegistry::new();
+        let running = registry.start(OperationKind::Import);
+        for _ in 0..FINISHED_TO_KEEP + 5 {
+            registry
+                .start(OperationKind::Compact)
+                .finish(&Ok::<(), String>(()));
+        }
+        let list = registry.list();
+        assert_eq!(list.len(), FINISHED_TO_KEEP + 1);
+        assert!(list.iter().any(|s| s.id == running.id()));
+    }
+
+    #[test]
+    fn test_run_once_joins_running_operation() {
+        let registry = OperationRegistry::new();
+        let calls = Arc::new(Mutex::new(0));
+        let (started_tx, started_rx) = std::sync::mpsc::channel();
+        let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+
+        let first = {
+            let registry = registry.clone();
+            let calls = calls.clone();
+            std::thread::spawn(move || {
+                registry.run_once(OperationKind::Sync, "sync", move |_| {
+                    *calls.lock().unwrap() += 1;
+                    started_tx.send(()).unwrap();
+                    release_rx.recv().unwrap();
+                    Ok::<_, String>("synced".to_string())
+                })
+            })
+        };
+        started_rx.recv().unwrap();
+
+        let second = {
+            let registry = registry.clone();
+            let calls = calls.clone();
+            std::thread::spawn(move || {
+                registry.run_once(OperationKind::Sync, "sync", move |_| {
+                    *calls.lock().unwrap() += 1;
+                    Ok("synced again".to_string())
+                })
+            })
+        };
+        // A different key doesn't wait
+        let backup = registry.run_once(OperationKind::Backup, "backup", |_| Ok(1));
+        assert!(!backup.joined);
+
+        while registry.list().iter().all(|s| s.joined == 0) {
+            std::thread::yield_now();
+        }
+        release_tx.send(()).unwrap();
+        let first = first.join().unwrap();
+        let second = second.join().unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), 1);
+        assert!(!first.joined);
+        assert!(second.joined);
+        assert_eq!(second.operation_id, first.operation_id);
+        assert_eq!(second.result, Ok("synced".to_string()));
+        let status = registry.get(&first.operation_id).unwrap();
+        assert_eq!(status.key.as_deref(), Some("sync"));
+        assert_eq!(status.joined, 1);
+
+        // Once finished, the same key runs again
+        let again = registry.run_once(OperationKind::Sync, "sync", |_| Ok("later".to_string()));
+        assert!(!again.joined);
+        assert_eq!(again.result, Ok("later".to_string()));
+    }
+}
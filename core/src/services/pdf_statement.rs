@@ -0,0 +1,289 @@
+//! PDF statement import - transaction tables from a statement's text layer
+//!
+//! Some banks only offer statements as PDFs. When the PDF was generated
+//! rather than scanned, its text layer holds each transaction row as a line
+//! that starts with a date and ends with the amount, often followed by the
+//! running balance. Those lines become CSV with fixed Date, Description,
+//! Amount and Balance columns, which then goes through the normal CSV
+//! import, so dedup, sign options and rules all apply. Scanned statements
+//! have no text to read; there is no OCR.
+//!
+//! Reading PDFs needs the `pdf` feature. Table extraction works on text and
+//! is always available.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use regex::Regex;
+
+use crate::config::ColumnMappings;
+
+/// Transaction rows found in a statement's text, as CSV
+#[derive(Debug, Clone)]
+pub struct StatementTable {
+    /// CSV with a Date, Description, Amount and (when any row has one)
+    /// Balance column
+    pub csv: String,
+    pub rows: usize,
+    pub has_balance: bool,
+}
+
+impl StatementTable {
+    /// Mappings for the CSV's columns
+    pub fn column_mappings(&self) -> ColumnMappings {
+        ColumnMappings {
+            balance: self.has_balance.then(|| "Balance".to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+/// One transaction line of a statement
+struct StatementRow {
+    date: String,
+    description: String,
+    amount: String,
+    balance: Option<String>,
+}
+
+/// Read the transaction table of a PDF statement
+#[cfg(feature = "pdf")]
+pub fn read_statement_table(path: &std::path::Path) -> Result<StatementTable> {
+    let text = pdf_extract::extract_text(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read PDF {}: {}", path.display(), e))?;
+    let table = extract_statement_table(&text)?;
+    if table.rows == 0 {
+        anyhow::bail!(
+            "No transactions found in {} (scanned statements have no text to read)",
+            path.display()
+        );
+    }
+    Ok(table)
+}
+
+/// Find the transaction rows in a statement's text
+///
+/// A row is a line starting with a date (a second date, the posting date on
+/// card statements, is skipped) and ending with one or two amounts: the
+/// transaction and, if present, the balance after it. Dates without a year
+/// get the year that appears most often in the text.
+pub fn extract_statement_table(text: &str) -> Result<StatementTable> {
+    let patterns = Patterns::new();
+    let year = statement_year(text, &patterns);
+    let rows: Vec<StatementRow> = text
+        .lines()
+        .filter_map(|line| parse_row(line, year.as_deref(), &patterns))
+        .collect();
+    let has_balance = rows.iter().any(|row| row.balance.is_some());
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let mut header = vec!["Date", "Description", "Amount"];
+    if has_balance {
+        header.push("Balance");
+    }
+    writer.write_record(&header)?;
+    for row in &rows {
+        let mut record = vec![
+            row.date.as_str(),
+            row.description.as_str(),
+            row.amount.as_str(),
+        ];
+        if has_balance {
+            record.push(row.balance.as_deref().unwrap_or_default());
+        }
+        writer.write_record(&record)?;
+    }
+    let csv = String::from_utf8(writer.into_inner().map_err(|e| e.into_error())?)?;
+
+    Ok(StatementTable {
+        csv,
+        rows: rows.len(),
+        has_balance,
+    })
+}
+
+struct Patterns {
+    date: Regex,
+    amount: Regex,
+    year: Regex,
+}
+
+impl Patterns {
+    fn new() -> Self {
+        Self {
+            // 2024-01-15, 01/15/2024, 15.01.24, 01/15
+            date: Regex::new(
+                r"^(?:(\d{4})-(\d{1,2})-(\d{1,2})|(\d{1,2})([/.\-])(\d{1,2})(?:[/.\-](\d{4}|\d{2}))?)$",
+            )
+            .unwrap(),
+            // Cents are required, so reference numbers and quantities don't match:
+            // 12.50, -1,234.56, (45.00), 1.234,56, $12.50, 12.50-, 12.50CR
+            amount: Regex::new(
+                r"(?i)^[-+(\u{2212}]?[$€£]?(?:\d{1,3}(?:[,.' \u{a0}]\d{3})+|\d+)[.,]\d{2}\)?(?:-|\s?CR|\s?DR)?$",
+            )
+            .unwrap(),
+            year: Regex::new(r"\b(?:19|20)\d{2}\b").unwrap(),
+        }
+    }
+}
+
+/// Balance lines that look like rows, with a date and an amount
+const SUMMARY_LINES: &[&str] = &[
+    "opening balance",
+    "closing balance",
+    "beginning balance",
+    "ending balance",
+    "previous balance",
+    "new balance",
+    "balance forward",
+    "balance brought forward",
+    "balance carried forward",
+];
+
+fn is_summary_line(description: &str) -> bool {
+    let description = description.to_lowercase();
+    SUMMARY_LINES.iter().any(|s| description.starts_with(s))
+}
+
+/// The year that appears most often in the text, for dates without one
+fn statement_year(text: &str, patterns: &Patterns) -> Option<String> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for m in patterns.year.find_iter(text) {
+        *counts.entry(m.as_str()).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by_key(|(year, count)| (*count, *year))
+        .map(|(year, _)| year.to_string())
+}
+
+/// A date token with a four-digit year, or None if it isn't a date
+fn normalize_date(token: &str, year: Option<&str>, patterns: &Patterns) -> Option<String> {
+    let caps = patterns.date.captures(token)?;
+    if caps.get(1).is_some() {
+        return Some(token.to_string());
+    }
+
+    let (first, separator, second) = (&caps[4], &caps[5], &caps[6]);
+    // Both parts must fit as a day or month in one order or the other, so
+    // decimals like 12.50 aren't taken for dates
+    let (a, b): (u32, u32) = (first.parse().ok()?, second.parse().ok()?);
+    if a == 0 || b == 0 || a > 31 || b > 31 || (a > 12 && b > 12) {
+        return None;
+    }
+    let year = match caps.get(7) {
+        Some(y) if y.as_str().len() == 2 => format!("20{}", y.as_str()),
+        Some(y) => y.as_str().to_string(),
+        None => year?.to_string(),
+    };
+    Some(format!("{first}{separator}{second}{separator}{year}"))
+}
+
+fn parse_row(line: &str, year: Option<&str>, patterns: &Patterns) -> Option<StatementRow> {
+    let mut tokens: Vec<String> = Vec::new();
+    for token in line.split_whitespace() {
+        // Keep "12.50 CR" together and drop currency symbols standing alone
+        let marker = token.eq_ignore_ascii_case("CR") || token.eq_ignore_ascii_case("DR");
+        match tokens.last_mut() {
+            Some(last) if marker && patterns.amount.is_match(last) => {
+                last.push(' ');
+                last.push_str(token);
+            }
+            _ if matches!(token, "$" | "€" | "£") => {}
+            _ => tokens.push(token.to_string()),
+        }
+    }
+
+    let mut tokens = tokens.into_iter().peekable();
+    let date = normalize_date(&tokens.next()?, year, patterns)?;
+    if tokens
+        .peek()
+        .is_some_and(|t| normalize_date(t, year, patterns).is_some())
+    {
+        tokens.next();
+    }
+
+    let mut rest: Vec<String> = tokens.collect();
+    let mut amounts = Vec::new();
+    while amounts.len() < 2 && rest.last().is_some_and(|t| patterns.amount.is_match(t)) {
+        amounts.insert(0, rest.pop()?);
+    }
+    if amounts.is_empty() || rest.is_empty() {
+        return None;
+    }
+    let description = rest.join(" ");
+    if is_summary_line(&description) {
+        return None;
+    }
+
+    let mut amounts = amounts.into_iter();
+    Some(StatementRow {
+        date,
+        description,
+        amount: amounts.next()?,
+        balance: amounts.next(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATEMENT: &str = "\
+FIRST EXAMPLE BANK
+Statement period 03/01/2024 - 03/31/2024
+Account 0012-3456
+
+Date        Description                     Amount      Balance
+03/01       Opening balance                             1,500.00
+03/02       COFFEE SHOP #123                  -4.50     1,495.50
+03/05       PAYROLL ACME, INC.             2,000.00     3,495.50
+03/07 03/08 RENT PAYMENT                   (1,400.00)   2,095.50
+03/31       Closing balance                             2,095.50
+Page 1 of 1
+";
+
+    #[test]
+    fn test_extract_statement_table() {
+        let table = extract_statement_table(STATEMENT).unwrap();
+        assert_eq!(table.rows, 3);
+        assert!(table.has_balance);
+        assert_eq!(
+            table.csv,
+            "Date,Description,Amount,Balance\n\
+             03/02/2024,COFFEE SHOP #123,-4.50,\"1,495.50\"\n\
+             03/05/2024,\"PAYROLL ACME, INC.\",\"2,000.00\",\"3,495.50\"\n\
+             03/07/2024,RENT PAYMENT,\"(1,400.00)\",\"2,095.50\"\n"
+        );
+        assert_eq!(table.column_mappings().balance.as_deref(), Some("Balance"));
+    }
+
+    #[test]
+    fn test_statement_rows_without_balance() {
+        let text = "2024-01-15 Grocery Mart 45.20 DR\n15.01.24 Refund $ 5.00\n";
+        let table = extract_statement_table(text).unwrap();
+        assert!(!table.has_balance);
+        assert_eq!(
+            table.csv,
+            "Date,Description,Amount\n\
+             2024-01-15,Grocery Mart,45.20 DR\n\
+             15.01.2024,Refund,5.00\n"
+        );
+        assert!(table.column_mappings().balance.is_none());
+    }
+
+    #[test]
+    fn test_lines_that_are_not_rows() {
+        let patterns = Patterns::new();
+        let year = Some("2024");
+        // Decimals aren't dates, and a row needs a description and an amount
+        assert!(parse_row("12.50 Service fee 3.00", year, &patterns).is_none());
+        assert!(parse_row("03/02 Coffee", year, &patterns).is_none());
+        assert!(parse_row("03/02 1,234.00", year, &patterns).is_none());
+        assert!(parse_row("Invoice 4411 due 03/02 12.00", year, &patterns).is_none());
+        assert!(parse_row("03/01 Balance forward 1,500.00", year, &patterns).is_none());
+        // Dates without a year need one from the text
+        assert!(parse_row("03/02 Coffee 4.50", None, &patterns).is_none());
+        assert!(parse_row("03/02/2024 Coffee 4.50", None, &patterns).is_some());
+    }
+}
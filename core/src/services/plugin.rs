@@ -6,11 +6,14 @@ use std::fs;
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Mutex;
 
 use anyhow::{Context, Result};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
+use crate::json_file::{read_json, write_json};
+
 // Embed plugin template files at compile time
 // These point to the actual template directory, so there's no duplication
 mod embedded_template {
@@ -80,6 +83,20 @@ pub struct UpdateInfo {
     pub reason: Option<String>,
 }
 
+/// A plugin's state.json with its schema version
+///
+/// Stored as `{"schemaVersion": N, "state": ...}`. Files written by the
+/// unversioned API are version 0; a missing file is version 0 with null state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionedPluginState {
+    #[serde(rename = "schemaVersion")]
+    pub version: u32,
+    pub state: serde_json::Value,
+}
+
+/// Serializes versioned state writes so a compare-and-swap can't interleave
+static STATE_WRITE_LOCK: Mutex<()> = Mutex::new(());
+
 impl PluginService {
     pub fn new(treeline_dir: &Path) -> Self {
         let plugins_dir = treeline_dir.join("plugins");
@@ -693,6 +710,63 @@ impl PluginService {
         // Reinstall from source
         self.install_plugin(&manifest.source, None, false)
     }
+
+    fn state_path(&self, plugin_id: &str) -> PathBuf {
+        self.plugins_dir.join(plugin_id).join("state.json")
+    }
+
+    /// Read a plugin's state along with its schema version
+    pub fn read_state_versioned(&self, plugin_id: &str) -> Result<VersionedPluginState> {
+        let Some(value) = read_json::<serde_json::Value>(&self.state_path(plugin_id))? else {
+            return Ok(VersionedPluginState {
+                version: 0,
+                state: serde_json::Value::Null,
+            });
+        };
+
+        let is_envelope = value.as_object().is_some_and(|o| {
+            o.len() == 2 && o.contains_key("state") && o.contains_key("schemaVersion")
+        });
+        if is_envelope {
+            if let Ok(versioned) = serde_json::from_value(value.clone()) {
+                return Ok(versioned);
+            }
+        }
+        Ok(VersionedPluginState {
+            version: 0,
+            state: value,
+        })
+    }
+
+    /// Write a plugin's state at `version`
+    ///
+    /// With `expected_version`, only writes if the stored version still
+    /// matches and returns false otherwise. Migrations use this so state is
+    /// upgraded once even if two views load it at the same time.
+    pub fn write_state_versioned(
+        &self,
+        plugin_id: &str,
+        state: serde_json::Value,
+        version: u32,
+        expected_version: Option<u32>,
+    ) -> Result<bool> {
+        let _guard = STATE_WRITE_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if let Some(expected) = expected_version {
+            if self.read_state_versioned(plugin_id)?.version != expected {
+                return Ok(false);
+            }
+        }
+
+        let path = self.state_path(plugin_id);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        write_json(&path, &VersionedPluginState { version, state })?;
+        Ok(true)
+    }
 }
 
 impl Default for PluginResult {
@@ -739,3 +813,36 @@ fn version_compare(v1: &str, v2: &str) -> i32 {
 
     0
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versioned_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = PluginService::new(dir.path());
+        let unversioned = serde_json::json!({"goals": [1, 2]});
+
+        assert_eq!(service.read_state_versioned("budget").unwrap().version, 0);
+
+        // State from the unversioned API reads as version 0
+        fs::create_dir_all(dir.path().join("plugins/budget")).unwrap();
+        write_json(&service.state_path("budget"), &unversioned).unwrap();
+        let stored = service.read_state_versioned("budget").unwrap();
+        assert_eq!(stored.version, 0);
+        assert_eq!(stored.state, unversioned);
+
+        // Only the first migration from version 0 is written
+        let migrated = serde_json::json!({"goals": [{"id": 1}, {"id": 2}]});
+        assert!(service
+            .write_state_versioned("budget", migrated.clone(), 1, Some(0))
+            .unwrap());
+        assert!(!service
+            .write_state_versioned("budget", unversioned, 1, Some(0))
+            .unwrap());
+        let stored = service.read_state_versioned("budget").unwrap();
+        assert_eq!(stored.version, 1);
+        assert_eq!(stored.state, migrated);
+    }
+}
@@ -2,20 +2,23 @@
 //!
 //! This module provides SQL-level permission validation for plugins using sqlparser-rs.
 //! It parses SQL queries and validates that plugins only access tables they're permitted to use.
+//! The desktop app runs it on every query a plugin sends; it is fuzzed in `core/fuzz`.
 
 use serde::Deserialize;
 use sqlparser::ast::{
-    Expr, FromTable, FunctionArgumentList, FunctionArguments, ObjectName, Query, Select,
-    SelectItem, SetExpr, Statement, TableFactor, TableObject, TableWithJoins, UpdateTableFromKind,
-    With,
+    CopySource, Expr, FromTable, FunctionArgumentList, FunctionArguments, ObjectName, Query,
+    Select, SelectItem, SetExpr, Statement, TableFactor, TableObject, TableWithJoins,
+    UpdateTableFromKind, Visit, Visitor, With,
 };
 use sqlparser::dialect::DuckDbDialect;
 use sqlparser::parser::Parser;
 use std::collections::HashSet;
+use std::ops::ControlFlow;
 
 /// Context for plugin permission validation.
 /// Passed from TypeScript SDK when executing queries on behalf of a plugin.
 #[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct PluginContext {
     /// The plugin's unique identifier (e.g., "goals", "budget")
     pub plugin_id: String,
@@ -53,14 +56,53 @@ pub fn validate_query_permissions(sql: &str, ctx: &PluginContext) -> Result<(),
     for stmt in statements {
         let table_refs = extract_table_references(&stmt);
 
-        for table_ref in table_refs {
+        for table_ref in &table_refs {
             validate_table_access(&table_ref.name, table_ref.is_write, ctx)?;
         }
+
+        // Tables the walk above didn't reach (it only follows the expressions
+        // it knows) must at least be readable
+        let mut collector = RelationCollector::default();
+        let _ = stmt.visit(&mut collector);
+        for relation in collector.relations {
+            let known = table_refs
+                .iter()
+                .any(|r| r.name.eq_ignore_ascii_case(&relation));
+            if !known && !collector.cte_names.contains(&relation.to_lowercase()) {
+                validate_table_access(&relation, false, ctx)?;
+            }
+        }
     }
 
     Ok(())
 }
 
+/// Collects every table name in a statement, wherever it appears, and the
+/// names of CTEs (which aren't tables)
+#[derive(Default)]
+struct RelationCollector {
+    relations: Vec<String>,
+    cte_names: HashSet<String>,
+}
+
+impl Visitor for RelationCollector {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        if let Some(with) = &query.with {
+            for cte in &with.cte_tables {
+                self.cte_names.insert(cte.alias.name.value.to_lowercase());
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_relation(&mut self, relation: &ObjectName) -> ControlFlow<Self::Break> {
+        self.relations.push(object_name_to_string(relation));
+        ControlFlow::Continue(())
+    }
+}
+
 /// Extract all table references from a SQL statement.
 /// Returns a list of (table_name, is_write) pairs.
 fn extract_table_references(stmt: &Statement) -> Vec<TableRef> {
@@ -182,6 +224,19 @@ fn extract_table_references(stmt: &Statement) -> Vec<TableRef> {
             });
         }
 
+        // COPY - TO a file reads the table, FROM a file writes it
+        Statement::Copy { source, to, .. } => match source {
+            CopySource::Table { table_name, .. } => {
+                refs.push(TableRef {
+                    name: object_name_to_string(table_name),
+                    is_write: !*to,
+                });
+            }
+            CopySource::Query(query) => {
+                extract_from_query(query, &mut refs, &mut cte_names, false);
+            }
+        },
+
         // CREATE SCHEMA - allowed if it matches plugin schema
         Statement::CreateSchema { schema_name, .. } => {
             // Extract schema name from SchemaName
@@ -457,12 +512,11 @@ fn object_name_to_string(name: &ObjectName) -> String {
 
 /// Validate access to a specific table
 fn validate_table_access(table: &str, is_write: bool, ctx: &PluginContext) -> Result<(), String> {
-    // Parse table name (may be schema-qualified)
-    let (schema, table_name) = if table.contains('.') {
-        let parts: Vec<&str> = table.split('.').collect();
-        (Some(parts[0].to_lowercase()), parts[1].to_lowercase())
-    } else {
-        (None, table.to_lowercase())
+    // Parse table name (may be schema-qualified). The table is the last part,
+    // so a quoted name containing dots can't pass for the plugin's schema.
+    let (schema, table_name) = match table.rsplit_once('.') {
+        Some((schema, name)) => (Some(schema.to_lowercase()), name.to_lowercase()),
+        None => (None, table.to_lowercase()),
     };
 
     // Plugin's own schema is always allowed (read and write)
@@ -1323,6 +1377,52 @@ mod tests {
         assert!(result.is_err());
     }
 
+    // ============================================================================
+    // Bypass Tests (found by fuzzing)
+    // ============================================================================
+
+    #[test]
+    fn test_subquery_in_cast_denied() {
+        let ctx = test_ctx();
+        let result = validate_query_permissions(
+            "SELECT CAST((SELECT max(amount) FROM sys_transactions) AS INT) FROM accounts",
+            &ctx,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_subquery_in_between_denied() {
+        let ctx = test_ctx();
+        let result = validate_query_permissions(
+            "SELECT * FROM accounts WHERE 1 BETWEEN 0 AND (SELECT count(*) FROM sys_transactions)",
+            &ctx,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_copy_table_to_file_denied() {
+        let ctx = test_ctx();
+        let result = validate_query_permissions("COPY sys_transactions TO 'out.csv'", &ctx);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_copy_from_file_requires_write() {
+        let ctx = test_ctx();
+        let result = validate_query_permissions("COPY accounts FROM 'in.csv'", &ctx);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_dotted_quoted_schema_denied() {
+        let ctx = test_ctx();
+        let result =
+            validate_query_permissions("SELECT * FROM \"plugin_goals.x\".sys_transactions", &ctx);
+        assert!(result.is_err());
+    }
+
     // ============================================================================
     // Function Tests
     // ============================================================================
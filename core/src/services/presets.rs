@@ -0,0 +1,483 @@
+//! Bank presets - built-in column mappings for common bank exports
+//!
+//! Each preset knows the header names, date format, number format and sign
+//! convention of one bank's CSV download, so `tl import --preset chase` works
+//! without any column flags. Detection looks for a preset's columns in the
+//! first lines of a file, which also finds the header row below any account
+//! summary the bank puts on top.
+//!
+//! Unlike the export presets in the import service (YNAB, Mint, ...), bank
+//! presets produce an ordinary [`ImportProfile`], so explicit flags still
+//! override any part of them.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::config::{ColumnMappings, ImportOptions, ImportProfile};
+use crate::services::import::{
+    decode_csv_bytes, detect_delimiter, parse_header_line, skip_lines, ImportPreset, NumberFormat,
+};
+
+/// Lines searched for the header row (banks put account summaries above it)
+const HEADER_SCAN_ROWS: u32 = 20;
+
+/// One bank's CSV export layout
+#[derive(Debug, Clone, Copy)]
+pub struct BankPreset {
+    /// Name used with `--preset`, e.g. "chase"
+    pub id: &'static str,
+    /// Display name, e.g. "Chase (checking and savings)"
+    pub name: &'static str,
+    date: &'static str,
+    /// Single signed amount column, or the debit column when `credit` is set
+    amount: &'static str,
+    description: &'static str,
+    credit: Option<&'static str>,
+    balance: Option<&'static str>,
+    category: Option<&'static str>,
+    reference: Option<&'static str>,
+    check_number: Option<&'static str>,
+    /// Per-row currency, for accounts holding several currencies
+    currency: Option<&'static str>,
+    /// Columns only this bank's export has, so detection doesn't claim generic files
+    signature: &'static [&'static str],
+    date_format: &'static str,
+    number_format: NumberFormat,
+    /// Charges are positive in the export
+    flip_signs: bool,
+}
+
+const DEFAULTS: BankPreset = BankPreset {
+    id: "",
+    name: "",
+    date: "Date",
+    amount: "Amount",
+    description: "Description",
+    credit: None,
+    balance: None,
+    category: None,
+    reference: None,
+    check_number: None,
+    currency: None,
+    signature: &[],
+    date_format: "%Y-%m-%d",
+    number_format: NumberFormat::Us,
+    flip_signs: false,
+};
+
+/// Every built-in bank preset
+pub const BANK_PRESETS: &[BankPreset] = &[
+    BankPreset {
+        id: "chase",
+        name: "Chase (checking and savings)",
+        date: "Posting Date",
+        balance: Some("Balance"),
+        check_number: Some("Check or Slip #"),
+        signature: &["Details"],
+        date_format: "%m/%d/%Y",
+        ..DEFAULTS
+    },
+    BankPreset {
+        id: "chase-card",
+        name: "Chase (credit card)",
+        date: "Transaction Date",
+        category: Some("Category"),
+        signature: &["Post Date", "Memo"],
+        date_format: "%m/%d/%Y",
+        ..DEFAULTS
+    },
+    BankPreset {
+        id: "amex",
+        name: "American Express",
+        category: Some("Category"),
+        reference: Some("Reference"),
+        signature: &["Card Member", "Account #"],
+        date_format: "%m/%d/%Y",
+        flip_signs: true,
+        ..DEFAULTS
+    },
+    BankPreset {
+        id: "capital-one",
+        name: "Capital One (credit card)",
+        date: "Transaction Date",
+        amount: "Debit",
+        credit: Some("Credit"),
+        category: Some("Category"),
+        signature: &["Card No."],
+        ..DEFAULTS
+    },
+    BankPreset {
+        id: "bank-of-america",
+        name: "Bank of America (checking and savings)",
+        balance: Some("Running Bal."),
+        signature: &["Running Bal."],
+        date_format: "%m/%d/%Y",
+        ..DEFAULTS
+    },
+    BankPreset {
+        id: "wise",
+        name: "Wise",
+        balance: Some("Running Balance"),
+        reference: Some("TransferWise ID"),
+        currency: Some("Currency"),
+        signature: &["TransferWise ID"],
+        date_format: "%d-%m-%Y",
+        ..DEFAULTS
+    },
+    BankPreset {
+        id: "revolut",
+        name: "Revolut",
+        // Pending rows have no completed date and are skipped until they settle
+        date: "Completed Date",
+        balance: Some("Balance"),
+        currency: Some("Currency"),
+        signature: &["Product", "State"],
+        date_format: "%Y-%m-%d %H:%M:%S",
+        ..DEFAULTS
+    },
+    BankPreset {
+        id: "n26",
+        name: "N26",
+        date: "Booking Date",
+        amount: "Amount (EUR)",
+        description: "Partner Name",
+        signature: &["Partner Iban"],
+        ..DEFAULTS
+    },
+    BankPreset {
+        id: "dkb",
+        name: "DKB (Girokonto)",
+        date: "Buchungsdatum",
+        amount: "Betrag (€)",
+        description: "Zahlungsempfänger*in",
+        signature: &["Umsatztyp"],
+        date_format: "%d.%m.%y",
+        number_format: NumberFormat::Eu,
+        ..DEFAULTS
+    },
+    BankPreset {
+        id: "monzo",
+        name: "Monzo",
+        description: "Name",
+        category: Some("Category"),
+        reference: Some("Transaction ID"),
+        signature: &["Emoji"],
+        date_format: "%d/%m/%Y",
+        ..DEFAULTS
+    },
+    BankPreset {
+        id: "starling",
+        name: "Starling",
+        amount: "Amount (GBP)",
+        description: "Counter Party",
+        balance: Some("Balance (GBP)"),
+        category: Some("Spending Category"),
+        signature: &["Counter Party"],
+        date_format: "%d/%m/%Y",
+        ..DEFAULTS
+    },
+];
+
+/// A preset recognized in a file, with the profile to import it
+#[derive(Debug, Clone, Serialize)]
+pub struct PresetMatch {
+    pub id: String,
+    pub name: String,
+    /// Header row found after this many lines
+    pub skip_rows: u32,
+    pub headers: Vec<String>,
+    pub profile: ImportProfile,
+}
+
+/// Look up a preset by id (case-insensitive)
+pub fn find_bank_preset(id: &str) -> Option<&'static BankPreset> {
+    BANK_PRESETS.iter().find(|p| p.id.eq_ignore_ascii_case(id))
+}
+
+/// Recognize the bank a CSV file came from by its header row
+///
+/// When several presets fit, the one using the most of the file's columns wins.
+pub fn detect_bank_preset(file_path: &Path) -> Result<Option<PresetMatch>> {
+    let mut best: Option<(usize, PresetMatch)> = None;
+    for (skip_rows, headers) in header_candidates(file_path)? {
+        for preset in BANK_PRESETS {
+            if !preset
+                .signature
+                .iter()
+                .all(|c| headers.iter().any(|h| h == c))
+            {
+                continue;
+            }
+            let Ok(profile) = preset.profile_for(&headers, skip_rows) else {
+                continue;
+            };
+            let used = preset
+                .columns()
+                .filter(|c| headers.iter().any(|h| h == c))
+                .count();
+            if best.as_ref().is_some_and(|(score, _)| *score >= used) {
+                continue;
+            }
+            best = Some((
+                used,
+                PresetMatch {
+                    id: preset.id.to_string(),
+                    name: preset.name.to_string(),
+                    skip_rows,
+                    headers: headers.clone(),
+                    profile,
+                },
+            ));
+        }
+        if best.is_some() {
+            break;
+        }
+    }
+    Ok(best.map(|(_, found)| found))
+}
+
+/// Recognize a CSV file as a bank's download or, failing that, as one of
+/// the export presets that import through a profile (YNAB, Mint, Apple Card)
+///
+/// An export preset's id is its `tl import` subcommand rather than a `--preset`.
+pub fn detect_import_preset(file_path: &Path) -> Result<Option<PresetMatch>> {
+    if let Some(found) = detect_bank_preset(file_path)? {
+        return Ok(Some(found));
+    }
+    for (skip_rows, headers) in header_candidates(file_path)? {
+        for preset in ImportPreset::ALL {
+            let Ok(mut profile) = preset.profile(&headers) else {
+                continue;
+            };
+            profile.skip_rows = skip_rows as usize;
+            return Ok(Some(PresetMatch {
+                id: preset.id().to_string(),
+                name: preset.label().to_string(),
+                skip_rows,
+                headers,
+                profile,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+impl BankPreset {
+    /// Profile for importing `file_path`, with the header row located
+    pub fn profile(&self, file_path: &Path) -> Result<ImportProfile> {
+        let candidates = header_candidates(file_path)?;
+        if let Some(profile) = candidates
+            .iter()
+            .find_map(|(skip_rows, headers)| self.profile_for(headers, *skip_rows).ok())
+        {
+            return Ok(profile);
+        }
+        // Report what the first line is missing
+        let (skip_rows, headers) = candidates.first().context("CSV file is empty")?;
+        self.profile_for(headers, *skip_rows)
+    }
+
+    /// Column mappings for a header row, keeping only the optional columns it has
+    pub fn mappings(&self, headers: &[String]) -> Result<ColumnMappings> {
+        let has = |name: &str| headers.iter().any(|h| h == name);
+        let missing: Vec<&str> = [Some(self.date), Some(self.amount), Some(self.description)]
+            .into_iter()
+            .chain([self.credit])
+            .flatten()
+            .filter(|c| !has(c))
+            .collect();
+        if !missing.is_empty() {
+            anyhow::bail!(
+                "Not a {} export: missing column(s) {}",
+                self.name,
+                missing.join(", ")
+            );
+        }
+
+        let optional = |column: Option<&str>| column.filter(|c| has(c)).map(String::from);
+        Ok(ColumnMappings {
+            date: self.date.to_string(),
+            amount: self.amount.to_string(),
+            description: Some(self.description.to_string()),
+            debit: self.credit.map(|_| self.amount.to_string()),
+            credit: self.credit.map(String::from),
+            balance: optional(self.balance),
+            account: None,
+            category: optional(self.category),
+            tags: None,
+            check_number: optional(self.check_number),
+            reference: optional(self.reference),
+            direction: None,
+            currency: optional(self.currency),
+        })
+    }
+
+    fn profile_for(&self, headers: &[String], skip_rows: u32) -> Result<ImportProfile> {
+        Ok(ImportProfile {
+            column_mappings: self.mappings(headers)?,
+            date_format: Some(self.date_format.to_string()),
+            skip_rows: skip_rows as usize,
+            options: ImportOptions {
+                flip_signs: self.flip_signs,
+                // Separate debit columns hold unsigned charges
+                debit_negative: self.credit.is_some(),
+                number_format: match self.number_format {
+                    NumberFormat::Us => None,
+                    format => Some(format.to_string()),
+                },
+                ..Default::default()
+            },
+        })
+    }
+
+    /// Every column the preset reads
+    fn columns(&self) -> impl Iterator<Item = &'static str> {
+        [self.date, self.amount, self.description]
+            .into_iter()
+            .chain(
+                [
+                    self.credit,
+                    self.balance,
+                    self.category,
+                    self.reference,
+                    self.check_number,
+                ]
+                .into_iter()
+                .flatten(),
+            )
+    }
+}
+
+/// Possible header rows: each of the first lines, parsed as a header
+fn header_candidates(file_path: &Path) -> Result<Vec<(u32, Vec<String>)>> {
+    let bytes = std::fs::read(file_path).context("Failed to open CSV file")?;
+    let (text, _) = decode_csv_bytes(&bytes);
+    let mut candidates = Vec::new();
+    for skip_rows in 0..HEADER_SCAN_ROWS {
+        let content = skip_lines(&text, skip_rows);
+        if content.trim().is_empty() {
+            break;
+        }
+        // A blank line can't be the header (the parser would read the next one)
+        if content
+            .lines()
+            .next()
+            .is_some_and(|line| line.trim().is_empty())
+        {
+            continue;
+        }
+        if let Ok(headers) = parse_header_line(content, detect_delimiter(content)) {
+            candidates.push((skip_rows, headers));
+        }
+    }
+    Ok(candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_csv(dir: &tempfile::TempDir, content: &str) -> std::path::PathBuf {
+        let path = dir.path().join("export.csv");
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_preset_ids_are_unique() {
+        for (i, preset) in BANK_PRESETS.iter().enumerate() {
+            assert!(BANK_PRESETS[..i].iter().all(|p| p.id != preset.id));
+        }
+        assert_eq!(find_bank_preset("Chase").unwrap().id, "chase");
+        assert!(find_bank_preset("unknown").is_none());
+    }
+
+    #[test]
+    fn test_detect_bank_preset() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let chase = write_csv(
+            &dir,
+            "Transaction Date,Post Date,Description,Category,Type,Amount,Memo\n\
+             01/05/2024,01/06/2024,COFFEE SHOP,Food & Drink,Sale,-4.50,\n",
+        );
+        let found = detect_bank_preset(&chase).unwrap().unwrap();
+        assert_eq!(found.id, "chase-card");
+        assert_eq!(found.skip_rows, 0);
+        assert_eq!(
+            found.profile.column_mappings.category.as_deref(),
+            Some("Category")
+        );
+        assert_eq!(found.profile.date_format.as_deref(), Some("%m/%d/%Y"));
+
+        // DKB puts the account and balance above the semicolon-separated header
+        let dkb = write_csv(
+            &dir,
+            "\"Girokonto\";\"DE00 1234\"\n\"Zeitraum:\";\"01.01.2024 - 31.01.2024\"\n\n\
+             \"Buchungsdatum\";\"Wertstellung\";\"Status\";\"Zahlungspflichtige*r\";\
+             \"Zahlungsempfänger*in\";\"Verwendungszweck\";\"Umsatztyp\";\"IBAN\";\"Betrag (€)\"\n\
+             \"05.01.24\";\"05.01.24\";\"Gebucht\";\"Ich\";\"Bäckerei\";\"Brot\";\"Ausgang\";\"DE00\";\"-3,20\"\n",
+        );
+        let found = detect_bank_preset(&dkb).unwrap().unwrap();
+        assert_eq!(found.id, "dkb");
+        assert_eq!(found.skip_rows, 3);
+        assert_eq!(found.profile.options.number_format.as_deref(), Some("eu"));
+
+        // A generic layout isn't claimed by any bank
+        let generic = write_csv(&dir, "Date,Description,Amount\n2024-01-05,Coffee,-4.50\n");
+        assert!(detect_bank_preset(&generic).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_detect_apple_card_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let apple = write_csv(
+            &dir,
+            "Transaction Date,Clearing Date,Description,Merchant,Category,Type,Amount (USD),Purchased By\n\
+             01/05/2024,01/06/2024,WHOLEFDS #123,Whole Foods,Grocery,Purchase,45.20,Jo\n",
+        );
+        assert!(detect_bank_preset(&apple).unwrap().is_none());
+        let found = detect_import_preset(&apple).unwrap().unwrap();
+        assert_eq!(found.id, "apple-card");
+        let mappings = &found.profile.column_mappings;
+        assert_eq!(mappings.description.as_deref(), Some("Merchant"));
+        assert_eq!(mappings.tags.as_deref(), Some("Merchant"));
+        assert_eq!(mappings.category.as_deref(), Some("Category"));
+        assert!(found.profile.options.flip_signs);
+        assert_eq!(found.profile.date_format.as_deref(), Some("%m/%d/%Y"));
+
+        let generic = write_csv(&dir, "Date,Description,Amount\n2024-01-05,Coffee,-4.50\n");
+        assert!(detect_import_preset(&generic).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_preset_profile_reports_missing_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_csv(&dir, "Date,Description,Amount\n2024-01-05,Coffee,-4.50\n");
+        let err = find_bank_preset("n26")
+            .unwrap()
+            .profile(&path)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Booking Date"), "{}", err);
+
+        let capital_one = find_bank_preset("capital-one").unwrap();
+        let headers: Vec<String> = [
+            "Transaction Date",
+            "Posted Date",
+            "Card No.",
+            "Description",
+            "Category",
+            "Debit",
+            "Credit",
+        ]
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+        let mappings = capital_one.mappings(&headers).unwrap();
+        assert_eq!(mappings.debit.as_deref(), Some("Debit"));
+        assert_eq!(mappings.credit.as_deref(), Some("Credit"));
+    }
+}
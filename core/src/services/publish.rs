@@ -0,0 +1,229 @@
+//! Publish service - balances and sync freshness as Home Assistant sensors
+//!
+//! Pushes account balances, net worth and days since the last successful
+//! sync to Home Assistant's REST API (`POST /api/states/<entity_id>`), so
+//! dashboards and automations can use them. Runs after each sync when
+//! `publishAfterSync` is set, or on demand with `tl publish`.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use rust_decimal::Decimal;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::adapters::duckdb::DuckDbRepository;
+use crate::config::{Config, HomeAssistantSettings};
+
+/// Entity IDs are `sensor.treeline_<key>`
+const ENTITY_PREFIX: &str = "sensor.treeline_";
+
+/// Environment variable that overrides the token in settings.json
+pub const HOME_ASSISTANT_TOKEN_ENV: &str = "TREELINE_HA_TOKEN";
+
+/// One value published as a sensor
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Sensor {
+    pub entity_id: String,
+    pub friendly_name: String,
+    /// Sensor state; "unknown" when there is no value
+    pub state: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+}
+
+/// Result of publishing (or previewing) sensors
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct PublishResult {
+    /// False for a dry run
+    pub published: bool,
+    pub sensors: Vec<Sensor>,
+}
+
+/// Publish service for Home Assistant sensors
+pub struct PublishService {
+    repository: Arc<DuckDbRepository>,
+    treeline_dir: PathBuf,
+}
+
+impl PublishService {
+    pub fn new(repository: Arc<DuckDbRepository>, treeline_dir: PathBuf) -> Self {
+        Self {
+            repository,
+            treeline_dir,
+        }
+    }
+
+    /// Current sensor values
+    pub fn sensors(&self) -> Result<Vec<Sensor>> {
+        let accounts = self.repository.get_accounts()?;
+        let mut sensors = Vec::new();
+
+        // Net worth per currency: liabilities count against it whatever their sign
+        let mut net_worth: BTreeMap<String, Decimal> = BTreeMap::new();
+        for account in &accounts {
+            if let Some(balance) = account.balance {
+                let value = if account.classification.as_deref() == Some("liability") {
+                    -balance.abs()
+                } else {
+                    balance
+                };
+                *net_worth.entry(account.currency.clone()).or_default() += value;
+            }
+        }
+        for (currency, total) in &net_worth {
+            sensors.push(Sensor {
+                entity_id: format!("{}net_worth_{}", ENTITY_PREFIX, currency.to_lowercase()),
+                friendly_name: format!("Net worth ({})", currency),
+                state: total.round_dp(2).to_string(),
+                unit: Some(currency.clone()),
+            });
+        }
+
+        let mut used = HashSet::new();
+        for account in &accounts {
+            let name = account.nickname.as_deref().unwrap_or(&account.name);
+            let mut key = format!("balance_{}", slugify(name));
+            if !used.insert(key.clone()) {
+                key = format!("{}_{}", key, &account.id.simple().to_string()[..8]);
+                used.insert(key.clone());
+            }
+            sensors.push(Sensor {
+                entity_id: format!("{}{}", ENTITY_PREFIX, key),
+                friendly_name: format!("{} balance", name),
+                state: account
+                    .balance
+                    .map(|b| b.round_dp(2).to_string())
+                    .unwrap_or_else(|| "unknown".to_string()),
+                unit: Some(account.currency.clone()),
+            });
+        }
+
+        let last_sync = self
+            .repository
+            .get_sync_history(None, 100)?
+            .into_iter()
+            .find(|entry| entry.error.is_none())
+            .map(|entry| entry.started_at);
+        sensors.push(Sensor {
+            entity_id: format!("{}days_since_sync", ENTITY_PREFIX),
+            friendly_name: "Days since last sync".to_string(),
+            state: last_sync
+                .map(|at| (Utc::now() - at).num_days().to_string())
+                .unwrap_or_else(|| "unknown".to_string()),
+            unit: Some("d".to_string()),
+        });
+
+        Ok(sensors)
+    }
+
+    /// Send the sensors to Home Assistant, or only compute them for a dry run
+    pub fn publish(&self, dry_run: bool) -> Result<PublishResult> {
+        let sensors = self.sensors()?;
+        if dry_run {
+            return Ok(PublishResult {
+                published: false,
+                sensors,
+            });
+        }
+
+        let settings = Config::load(&self.treeline_dir)?
+            .home_assistant
+            .context("Home Assistant is not configured (add homeAssistant to settings.json)")?;
+        let token = std::env::var(HOME_ASSISTANT_TOKEN_ENV)
+            .ok()
+            .or_else(|| settings.token.clone())
+            .with_context(|| {
+                format!(
+                    "No Home Assistant token: set homeAssistant.token or {}",
+                    HOME_ASSISTANT_TOKEN_ENV
+                )
+            })?;
+
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(15))
+            .build()?;
+        for sensor in &sensors {
+            post_state(&client, &settings, &token, sensor)?;
+        }
+
+        Ok(PublishResult {
+            published: true,
+            sensors,
+        })
+    }
+
+    /// Publish after a sync when enabled in settings; failures are ignored
+    pub fn publish_after_sync(&self) {
+        let enabled = Config::load(&self.treeline_dir)
+            .ok()
+            .and_then(|c| c.home_assistant)
+            .is_some_and(|s| s.publish_after_sync);
+        if enabled {
+            let _ = self.publish(false);
+        }
+    }
+}
+
+fn post_state(
+    client: &reqwest::blocking::Client,
+    settings: &HomeAssistantSettings,
+    token: &str,
+    sensor: &Sensor,
+) -> Result<()> {
+    let url = format!(
+        "{}/api/states/{}",
+        settings.url.trim_end_matches('/'),
+        sensor.entity_id
+    );
+    let mut attributes = serde_json::json!({"friendly_name": sensor.friendly_name});
+    if let Some(unit) = &sensor.unit {
+        attributes["unit_of_measurement"] = unit.clone().into();
+    }
+
+    let response = client
+        .post(&url)
+        .bearer_auth(token)
+        .json(&serde_json::json!({"state": sensor.state, "attributes": attributes}))
+        .send()
+        .context("Failed to reach Home Assistant")?;
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "Home Assistant rejected {} (HTTP {})",
+            sensor.entity_id,
+            response.status().as_u16()
+        );
+    }
+    Ok(())
+}
+
+/// Lowercase ASCII letters and digits joined by single underscores
+fn slugify(name: &str) -> String {
+    let slug = name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+    if slug.is_empty() {
+        "account".to_string()
+    } else {
+        slug
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Chase Checking (...1234)"), "chase_checking_1234");
+        assert_eq!(slugify("Épargne"), "pargne");
+        assert_eq!(slugify("???"), "account");
+    }
+}
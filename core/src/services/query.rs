@@ -3,8 +3,21 @@
 use std::sync::Arc;
 
 use anyhow::Result;
+use chrono::Local;
+use serde::Serialize;
 
+use super::nl_query::{self, NlQueryResult};
 use crate::adapters::duckdb::{DuckDbRepository, QueryResult};
+use crate::adapters::extensions;
+
+/// A query saved from the Query view
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedQuery {
+    pub id: String,
+    pub name: String,
+    pub query: String,
+    pub description: Option<String>,
+}
 
 /// Query service for SQL execution
 pub struct QueryService {
@@ -18,7 +31,9 @@ impl QueryService {
 
     /// Execute a read-only SQL query (SELECT only)
     pub fn execute(&self, sql: &str) -> Result<QueryResult> {
-        self.repository.execute_query(sql)
+        self.repository
+            .execute_query(sql)
+            .map_err(explain_extension_error)
     }
 
     /// Execute a read-only SQL query using a DuckDB read-only connection.
@@ -26,7 +41,9 @@ impl QueryService {
     /// Enforces read-only at both the SQL validation level and the
     /// DuckDB connection level for defense in depth.
     pub fn execute_readonly(&self, sql: &str) -> Result<QueryResult> {
-        self.repository.execute_query_readonly(sql)
+        self.repository
+            .execute_query_readonly(sql)
+            .map_err(explain_extension_error)
     }
 
     /// Execute arbitrary SQL (read or write)
@@ -34,7 +51,9 @@ impl QueryService {
     /// For SELECT queries, returns columns and rows.
     /// For write queries (INSERT/UPDATE/DELETE), returns affected_rows count.
     pub fn execute_sql(&self, sql: &str) -> Result<QueryResult> {
-        self.repository.execute_sql(sql)
+        self.repository
+            .execute_sql(sql)
+            .map_err(explain_extension_error)
     }
 
     /// Execute parameterized SQL (read or write)
@@ -45,6 +64,60 @@ impl QueryService {
         sql: &str,
         params: &[serde_json::Value],
     ) -> Result<QueryResult> {
-        self.repository.execute_sql_with_params(sql, params)
+        self.repository
+            .execute_sql_with_params(sql, params)
+            .map_err(explain_extension_error)
+    }
+
+    /// Execute a parameterized read-only SQL query using a DuckDB read-only connection
+    pub fn execute_readonly_with_params(
+        &self,
+        sql: &str,
+        params: &[serde_json::Value],
+    ) -> Result<QueryResult> {
+        self.repository
+            .execute_query_readonly_with_params(sql, params)
+            .map_err(explain_extension_error)
+    }
+
+    /// Answer a natural-language question using the local query templates
+    ///
+    /// Returns the generated SQL alongside the results so the user can
+    /// confirm what was run. The query uses a read-only connection.
+    pub fn translate_nl_query(&self, question: &str) -> Result<NlQueryResult> {
+        let query = nl_query::translate(question, Local::now().date_naive())?;
+        let result = self
+            .repository
+            .execute_query_readonly_with_params(&query.sql, &query.params)?;
+        Ok(NlQueryResult { query, result })
+    }
+
+    /// Queries saved in the Query view, by name
+    pub fn saved_queries(&self) -> Result<Vec<SavedQuery>> {
+        let result = self.repository.execute_query_readonly(
+            "SELECT saved_query_id, name, query, description FROM plugin_query.saved ORDER BY name",
+        )?;
+        let text = |value: &serde_json::Value| value.as_str().map(str::to_string);
+        Ok(result
+            .rows
+            .iter()
+            .filter_map(|row| {
+                Some(SavedQuery {
+                    id: text(row.first()?)?,
+                    name: text(row.get(1)?)?,
+                    query: text(row.get(2)?)?,
+                    description: row.get(3).and_then(text),
+                })
+            })
+            .collect())
+    }
+}
+
+/// Replace DuckDB's advice to install or autoload an extension, which a
+/// query can't act on, with what's actually wrong
+fn explain_extension_error(error: anyhow::Error) -> anyhow::Error {
+    match extensions::explain_error(&format!("{:#}", error)) {
+        Some(message) => anyhow::anyhow!(message),
+        None => error,
     }
 }
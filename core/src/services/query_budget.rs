@@ -0,0 +1,170 @@
+//! Query budget - which view or plugin is using the database's time
+//!
+//! The desktop app records how long each query took and who asked for it:
+//! the plugin's id, the caller tag a view passes, or `user` for the SQL
+//! console. A consumer taking more than its share of all query time since the
+//! app started is flagged in diagnostics, so a plugin polling an expensive
+//! query shows up before the whole app feels slow.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Share of query time one consumer may use before it's flagged
+pub const DEFAULT_QUERY_BUDGET_SHARE: f64 = 0.5;
+
+/// Below this much query time in total, shares are noise and nothing is flagged
+const MIN_TOTAL_MS: u64 = 10_000;
+
+/// Query time used by one consumer
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsumerUsage {
+    pub consumer: String,
+    pub queries: u64,
+    pub total_ms: u64,
+    pub max_ms: u64,
+    /// Fraction of all query time, 0.0 to 1.0
+    pub share: f64,
+    pub over_budget: bool,
+}
+
+/// Query time by consumer since the app started, heaviest first
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryBudgetReport {
+    pub total_ms: u64,
+    pub max_share: f64,
+    pub consumers: Vec<ConsumerUsage>,
+    /// One message per consumer over budget
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct Usage {
+    queries: u64,
+    total_ms: u64,
+    max_ms: u64,
+}
+
+/// Accumulates query time per consumer
+#[derive(Debug, Default)]
+pub struct QueryBudget {
+    usage: Mutex<HashMap<String, Usage>>,
+}
+
+impl QueryBudget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add one query's time to `consumer`
+    pub fn record(&self, consumer: &str, duration: Duration) {
+        let ms = duration.as_millis() as u64;
+        let mut usage = self.usage.lock().unwrap();
+        let entry = usage.entry(consumer.to_string()).or_default();
+        entry.queries += 1;
+        entry.total_ms += ms;
+        entry.max_ms = entry.max_ms.max(ms);
+    }
+
+    /// Usage so far, flagging consumers above `max_share` of the total
+    ///
+    /// Nothing is flagged while there is a single consumer or less than ten
+    /// seconds of query time in total.
+    pub fn report(&self, max_share: f64) -> QueryBudgetReport {
+        let usage = self.usage.lock().unwrap();
+        let total_ms: u64 = usage.values().map(|u| u.total_ms).sum();
+        let enforced = usage.len() > 1 && total_ms >= MIN_TOTAL_MS;
+
+        let mut consumers: Vec<ConsumerUsage> = usage
+            .iter()
+            .map(|(consumer, u)| {
+                let share = if total_ms > 0 {
+                    u.total_ms as f64 / total_ms as f64
+                } else {
+                    0.0
+                };
+                ConsumerUsage {
+                    consumer: consumer.clone(),
+                    queries: u.queries,
+                    total_ms: u.total_ms,
+                    max_ms: u.max_ms,
+                    share,
+                    over_budget: enforced && share > max_share,
+                }
+            })
+            .collect();
+        consumers.sort_by(|a, b| {
+            b.total_ms
+                .cmp(&a.total_ms)
+                .then_with(|| a.consumer.cmp(&b.consumer))
+        });
+
+        let warnings = consumers
+            .iter()
+            .filter(|c| c.over_budget)
+            .map(|c| {
+                format!(
+                    "{} used {:.0}% of database time ({} queries, {:.1}s; budget {:.0}%)",
+                    c.consumer,
+                    c.share * 100.0,
+                    c.queries,
+                    c.total_ms as f64 / 1000.0,
+                    max_share * 100.0
+                )
+            })
+            .collect();
+
+        QueryBudgetReport {
+            total_ms,
+            max_share,
+            consumers,
+            warnings,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_flags_consumers_over_budget() {
+        let budget = QueryBudget::new();
+        budget.record("budget-plugin", Duration::from_secs(9));
+        budget.record("budget-plugin", Duration::from_secs(3));
+        budget.record("accounts", Duration::from_secs(2));
+        budget.record("user", Duration::from_millis(500));
+
+        let report = budget.report(DEFAULT_QUERY_BUDGET_SHARE);
+        assert_eq!(report.total_ms, 14_500);
+        assert_eq!(report.consumers[0].consumer, "budget-plugin");
+        assert_eq!(report.consumers[0].queries, 2);
+        assert_eq!(report.consumers[0].max_ms, 9_000);
+        assert!(report.consumers[0].over_budget);
+        assert!(!report.consumers[1].over_budget);
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].starts_with("budget-plugin used 83%"));
+
+        assert!(budget.report(0.9).warnings.is_empty());
+    }
+
+    #[test]
+    fn test_report_needs_enough_time_and_consumers() {
+        let budget = QueryBudget::new();
+        budget.record("user", Duration::from_secs(30));
+        assert!(budget
+            .report(DEFAULT_QUERY_BUDGET_SHARE)
+            .warnings
+            .is_empty());
+
+        let budget = QueryBudget::new();
+        budget.record("accounts", Duration::from_secs(4));
+        budget.record("user", Duration::from_secs(1));
+        assert!(budget
+            .report(DEFAULT_QUERY_BUDGET_SHARE)
+            .warnings
+            .is_empty());
+    }
+}
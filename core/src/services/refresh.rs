@@ -0,0 +1,139 @@
+//! Refresh service - bring derived state up to date after new data
+//!
+//! A sync or import invalidates every cached report, so the next time the
+//! dashboard opens it would recompute them all while the user waits. The
+//! app runs this in the background instead, right after new data lands: it
+//! recomputes the reports the dashboard opens on (this and last month's cash
+//! flows and comparisons, card cycles) into the report cache, and checks
+//! which card payments are due soon.
+//!
+//! The result is consistent: if anything was written while it ran, it starts
+//! over, so everything it computed reflects the same data.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::Result;
+use chrono::{Local, NaiveDate};
+use serde::Serialize;
+
+use crate::adapters::duckdb::DuckDbRepository;
+use crate::adapters::write_queue;
+use crate::services::{CardCycle, CompareBy, Period, ReportService};
+
+/// Card payments due within this many days are reported
+pub const PAYMENT_DUE_DAYS: i64 = 3;
+/// Attempts before giving up on data that keeps changing
+const MAX_ATTEMPTS: u32 = 3;
+
+/// What a refresh computed
+#[derive(Debug, Clone, Serialize)]
+pub struct RefreshResult {
+    /// Reports computed into the cache
+    pub reports: usize,
+    /// Cards with something left to pay, due within PAYMENT_DUE_DAYS or overdue
+    pub payments_due: Vec<CardCycle>,
+    /// Times it started over because data changed while it ran
+    pub restarts: u32,
+    pub duration_ms: u64,
+}
+
+/// Refresh service for precomputing derived state
+pub struct RefreshService {
+    repository: Arc<DuckDbRepository>,
+}
+
+impl RefreshService {
+    pub fn new(repository: Arc<DuckDbRepository>) -> Self {
+        Self { repository }
+    }
+
+    /// Recompute derived state as of today, as background work
+    ///
+    /// Fails if the data is still changing after MAX_ATTEMPTS; the writer
+    /// that kept changing it is expected to trigger another refresh.
+    pub fn run(&self) -> Result<RefreshResult> {
+        let _priority = write_queue::background();
+        let started = Instant::now();
+        let today = Local::now().date_naive();
+
+        for attempt in 0..MAX_ATTEMPTS {
+            let version = self.repository.data_version();
+            let (reports, payments_due) = self.refresh(today)?;
+            if self.repository.data_version() == version {
+                return Ok(RefreshResult {
+                    reports,
+                    payments_due,
+                    restarts: attempt,
+                    duration_ms: started.elapsed().as_millis() as u64,
+                });
+            }
+        }
+        anyhow::bail!(
+            "Data changed during each of {} refresh attempts",
+            MAX_ATTEMPTS
+        )
+    }
+
+    fn refresh(&self, today: NaiveDate) -> Result<(usize, Vec<CardCycle>)> {
+        // Recompute rather than trust entries cached before the last write
+        let reports = ReportService::new(self.repository.clone()).with_cache(false);
+        let this_month = Period::parse(&today.format("%Y-%m").to_string())?;
+        let last_month = this_month.preceding();
+
+        let mut count = 0;
+        for period in [this_month, last_month] {
+            reports.cash_flows(period)?;
+            for group_by in [CompareBy::Category, CompareBy::Account] {
+                reports.compare_periods(period, period.preceding(), group_by)?;
+            }
+            count += 3;
+        }
+        let payments_due = reports
+            .card_cycles(today)?
+            .into_iter()
+            .filter(|card| {
+                card.days_until_due <= PAYMENT_DUE_DAYS
+                    && card.payment_due.is_some_and(|due| !due.is_zero())
+            })
+            .collect();
+        count += 1;
+
+        Ok((count, payments_due))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Account, Transaction};
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_refresh_warms_report_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        let account = Account::new(Uuid::new_v4(), "Checking");
+        repository.upsert_account(&account).unwrap();
+        let today = Local::now().date_naive();
+        let tx = Transaction::new(Uuid::new_v4(), account.id, Decimal::new(-25, 0), today);
+        repository.upsert_transaction(&tx).unwrap();
+
+        let result = RefreshService::new(repository.clone()).run().unwrap();
+        assert_eq!(result.reports, 7);
+        assert_eq!(result.restarts, 0);
+        assert!(result.payments_due.is_empty());
+
+        // The dashboard's next request is served from the cache
+        let this_month = Period::parse(&today.format("%Y-%m").to_string()).unwrap();
+        let flows = ReportService::new(repository.clone())
+            .cash_flows(this_month)
+            .unwrap();
+        assert_eq!(flows.total_spending, Decimal::new(25, 0));
+        let stats = crate::services::ReportCache::for_path(repository.db_path()).stats();
+        assert_eq!((stats.entries, stats.hits), (7, 1));
+    }
+}
@@ -0,0 +1,1340 @@
+//! Report service - account statements and period comparisons
+//!
+//! A statement covers one account for one calendar month: opening balance,
+//! every transaction with a running balance, closing balance and a summary
+//! by category. Balances are anchored on the account's balance snapshots
+//! (each day's latest one) and walked through the transactions, the same way
+//! balance backfill does. Statements render to self-contained HTML that
+//! prints cleanly, so "Print > Save as PDF" gives a PDF copy.
+//!
+//! A comparison puts two date ranges side by side (this quarter vs last,
+//! this March vs last March), per category or per account.
+//!
+//! Card cycles follow credit cards with a statement cycle: spending on the
+//! open statement, and what's left to pay on the last one by its due date.
+//!
+//! Cash flows trace a period's money from income sources through categories
+//! to merchants, as the nodes and weighted links a Sankey diagram draws.
+//!
+//! Transactions excluded from reports are left out of comparisons, cash
+//! flows and a statement's category summary. A statement still lists them,
+//! since its running balance has to match the account.
+//!
+//! Results are cached until the data changes (see `report_cache`).
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::adapters::duckdb::DuckDbRepository;
+use crate::domain::{latest_per_day, StatementCycle};
+use crate::services::report_cache::ReportCache;
+
+/// Category for transactions without tags
+const UNCATEGORIZED: &str = "Uncategorized";
+/// Merchants shown per category in cash flows; smaller ones are combined
+pub const MAX_FLOW_MERCHANTS: usize = 8;
+
+/// One transaction on a statement
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct StatementLine {
+    pub date: NaiveDate,
+    pub description: String,
+    #[schemars(with = "String")]
+    pub amount: Decimal,
+    /// Balance after this transaction, None when the opening balance is unknown
+    #[schemars(with = "Option<String>")]
+    pub balance: Option<Decimal>,
+    pub category: String,
+}
+
+/// Transactions of one category within the statement period
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CategoryTotal {
+    pub category: String,
+    pub count: usize,
+    #[schemars(with = "String")]
+    pub total: Decimal,
+}
+
+/// Monthly statement for one account
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Statement {
+    pub account_id: String,
+    pub account_name: String,
+    pub institution_name: Option<String>,
+    pub currency: String,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    /// None when the account has no balance snapshots to anchor on
+    #[schemars(with = "Option<String>")]
+    pub opening_balance: Option<Decimal>,
+    #[schemars(with = "Option<String>")]
+    pub closing_balance: Option<Decimal>,
+    #[schemars(with = "String")]
+    pub total_credits: Decimal,
+    #[schemars(with = "String")]
+    pub total_debits: Decimal,
+    /// Oldest first
+    pub transactions: Vec<StatementLine>,
+    /// By each transaction's first tag, largest outflow first
+    pub categories: Vec<CategoryTotal>,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// What a period comparison groups transactions by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareBy {
+    /// Each transaction's first tag
+    Category,
+    Account,
+}
+
+/// An inclusive date range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct Period {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+/// One category or account in both periods
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ComparisonRow {
+    /// Category, or account name (nickname if set)
+    pub label: String,
+    #[schemars(with = "String")]
+    pub current: Decimal,
+    #[schemars(with = "String")]
+    pub previous: Decimal,
+    /// `current - previous`
+    #[schemars(with = "String")]
+    pub change: Decimal,
+    /// Change relative to the size of the previous amount; None when it was zero
+    pub change_percent: Option<f64>,
+    pub current_count: usize,
+    pub previous_count: usize,
+}
+
+/// Two periods side by side
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PeriodComparison {
+    pub group_by: CompareBy,
+    pub current: Period,
+    pub previous: Period,
+    /// Largest change first
+    pub rows: Vec<ComparisonRow>,
+    /// All transactions in each period
+    pub total: ComparisonRow,
+}
+
+/// Where a credit card stands in its statement cycle
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CardCycle {
+    pub account_id: String,
+    pub account_name: String,
+    pub currency: String,
+    pub cycle: StatementCycle,
+    /// The open statement
+    pub current: Period,
+    /// Purchases on the open statement so far, as a positive amount
+    #[schemars(with = "String")]
+    pub current_spend: Decimal,
+    /// When the last statement closed
+    pub last_closing: NaiveDate,
+    /// Owed when the last statement closed; None without balance snapshots
+    #[schemars(with = "Option<String>")]
+    pub statement_balance: Option<Decimal>,
+    /// Payments and refunds since the last statement closed
+    #[schemars(with = "String")]
+    pub paid_since_closing: Decimal,
+    /// What's left to pay on the last statement; None without balance snapshots
+    #[schemars(with = "Option<String>")]
+    pub payment_due: Option<Decimal>,
+    /// When the last statement has to be paid
+    pub due_date: NaiveDate,
+    /// Negative once the due date has passed
+    pub days_until_due: i64,
+}
+
+/// What a cash flow node stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FlowNodeKind {
+    /// Where income came from: its first tag, or "Income" when untagged
+    Source,
+    /// All income, which every source flows into and every category out of
+    Income,
+    Category,
+    Merchant,
+    /// Income left over after spending
+    Saved,
+    /// Spending beyond income, paid from savings or credit
+    Shortfall,
+}
+
+/// One box in a cash flow diagram
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct FlowNode {
+    /// Unique within the diagram, e.g. "category:groceries"
+    pub id: String,
+    pub label: String,
+    pub kind: FlowNodeKind,
+    /// Everything flowing through this node
+    #[schemars(with = "String")]
+    pub value: Decimal,
+}
+
+/// Money moving from one node to another, always positive
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct FlowLink {
+    pub source: String,
+    pub target: String,
+    #[schemars(with = "String")]
+    pub value: Decimal,
+}
+
+/// A period's income, categories and merchants as a Sankey diagram
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CashFlows {
+    pub period: Period,
+    #[schemars(with = "String")]
+    pub total_income: Decimal,
+    /// As a positive amount
+    #[schemars(with = "String")]
+    pub total_spending: Decimal,
+    /// Sources, the income node, categories (largest first) and their merchants
+    pub nodes: Vec<FlowNode>,
+    pub links: Vec<FlowLink>,
+}
+
+/// Report service for statements and comparisons
+#[derive(Clone)]
+pub struct ReportService {
+    repository: Arc<DuckDbRepository>,
+    cache: Arc<ReportCache>,
+    use_cache: bool,
+}
+
+impl ReportService {
+    pub fn new(repository: Arc<DuckDbRepository>) -> Self {
+        let cache = ReportCache::for_path(repository.db_path());
+        Self {
+            repository,
+            cache,
+            use_cache: true,
+        }
+    }
+
+    /// This service, reading cached results only if `enabled`
+    ///
+    /// Without the cache every report is recomputed; the fresh result still
+    /// replaces the cached one.
+    pub fn with_cache(&self, enabled: bool) -> Self {
+        Self {
+            use_cache: enabled,
+            ..self.clone()
+        }
+    }
+
+    /// The cached result of a report, or `compute`'s
+    fn cached<T, F>(&self, report: &str, params: String, compute: F) -> Result<T>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Result<T>,
+    {
+        // Read before computing, so a write during it leaves the result stale
+        let version = self.repository.data_version();
+        if !self.use_cache {
+            self.cache.record_bypass();
+        } else if let Some(value) = self.cache.get(report, &params, version) {
+            return Ok(value);
+        }
+        let value = compute()?;
+        self.cache.insert(report, &params, version, value.clone());
+        Ok(value)
+    }
+
+    /// Statement for an account (UUID) and month ("YYYY-MM")
+    pub fn generate_statement(&self, account_id: &str, month: &str) -> Result<Statement> {
+        self.cached("statement", format!("{account_id}/{month}"), || {
+            self.build_statement(account_id, month)
+        })
+    }
+
+    fn build_statement(&self, account_id: &str, month: &str) -> Result<Statement> {
+        let account = self
+            .repository
+            .get_account_by_id(account_id)?
+            .ok_or_else(|| anyhow::anyhow!("Account not found: {}", account_id))?;
+        let (period_start, period_end) = parse_month(month)?;
+
+        let transactions = self.repository.get_transactions_by_account(account_id)?;
+        let snapshots = self.repository.get_balance_snapshots(Some(account_id))?;
+
+        let mut daily_totals: BTreeMap<NaiveDate, Decimal> = BTreeMap::new();
+        for tx in &transactions {
+            *daily_totals.entry(tx.transaction_date).or_default() += tx.amount;
+        }
+        let closing_by_day: BTreeMap<NaiveDate, Decimal> = latest_per_day(&snapshots)
+            .into_iter()
+            .map(|(date, snapshot)| (date, snapshot.balance))
+            .collect();
+        let opening_balance = balance_at_end_of(
+            period_start - Duration::days(1),
+            period_end,
+            &closing_by_day,
+            &daily_totals,
+        );
+
+        let mut in_period: Vec<_> = transactions
+            .iter()
+            .filter(|tx| tx.transaction_date >= period_start && tx.transaction_date <= period_end)
+            .collect();
+        in_period.sort_by_key(|tx| (tx.transaction_date, tx.created_at));
+
+        let mut running = opening_balance;
+        let mut total_credits = Decimal::ZERO;
+        let mut total_debits = Decimal::ZERO;
+        let mut categories: HashMap<String, CategoryTotal> = HashMap::new();
+        let mut lines = Vec::with_capacity(in_period.len());
+        for tx in in_period {
+            running = running.map(|b| b + tx.amount);
+            if tx.amount >= Decimal::ZERO {
+                total_credits += tx.amount;
+            } else {
+                total_debits += tx.amount;
+            }
+            let category = tx
+                .tags
+                .first()
+                .cloned()
+                .unwrap_or_else(|| UNCATEGORIZED.to_string());
+            if !tx.excluded_from_reports {
+                let entry = categories
+                    .entry(category.clone())
+                    .or_insert_with(|| CategoryTotal {
+                        category: category.clone(),
+                        count: 0,
+                        total: Decimal::ZERO,
+                    });
+                entry.count += 1;
+                entry.total += tx.amount;
+            }
+
+            lines.push(StatementLine {
+                date: tx.transaction_date,
+                description: tx.description.clone().unwrap_or_default(),
+                amount: tx.amount,
+                balance: running,
+                category,
+            });
+        }
+
+        let mut categories: Vec<CategoryTotal> = categories.into_values().collect();
+        categories.sort_by(|a, b| a.total.cmp(&b.total).then(a.category.cmp(&b.category)));
+
+        Ok(Statement {
+            account_id: account.id.to_string(),
+            account_name: account.nickname.clone().unwrap_or(account.name),
+            institution_name: account.institution_name,
+            currency: account.currency,
+            period_start,
+            period_end,
+            opening_balance,
+            closing_balance: running,
+            total_credits,
+            total_debits,
+            transactions: lines,
+            categories,
+            generated_at: Utc::now(),
+        })
+    }
+
+    /// Compare transaction totals between two periods
+    ///
+    /// Amounts keep their sign, so spending shows as negative and a negative
+    /// change means more was spent. A transaction in both periods (they may
+    /// overlap) counts in both.
+    pub fn compare_periods(
+        &self,
+        current: Period,
+        previous: Period,
+        group_by: CompareBy,
+    ) -> Result<PeriodComparison> {
+        let params = format!("{current:?}/{previous:?}/{group_by:?}");
+        self.cached("comparison", params, || {
+            self.build_comparison(current, previous, group_by)
+        })
+    }
+
+    fn build_comparison(
+        &self,
+        current: Period,
+        previous: Period,
+        group_by: CompareBy,
+    ) -> Result<PeriodComparison> {
+        let account_names: HashMap<String, String> = self
+            .repository
+            .get_accounts()?
+            .into_iter()
+            .map(|a| (a.id.to_string(), a.nickname.unwrap_or(a.name)))
+            .collect();
+
+        // Keyed by account ID when grouping by account, so same-named accounts stay apart
+        let mut groups: HashMap<String, ComparisonRow> = HashMap::new();
+        let mut total = ComparisonRow::new("Total".to_string());
+        for tx in self.repository.get_transactions()? {
+            let in_current = current.contains(tx.transaction_date);
+            let in_previous = previous.contains(tx.transaction_date);
+            if (!in_current && !in_previous) || tx.excluded_from_reports {
+                continue;
+            }
+
+            let (key, label) = match group_by {
+                CompareBy::Category => {
+                    let category = tx
+                        .tags
+                        .first()
+                        .cloned()
+                        .unwrap_or_else(|| UNCATEGORIZED.to_string());
+                    (category.clone(), category)
+                }
+                CompareBy::Account => {
+                    let id = tx.account_id.to_string();
+                    let name = account_names
+                        .get(&id)
+                        .cloned()
+                        .unwrap_or_else(|| id.clone());
+                    (id, name)
+                }
+            };
+            let row = groups
+                .entry(key)
+                .or_insert_with(|| ComparisonRow::new(label));
+            for row in [row, &mut total] {
+                if in_current {
+                    row.current += tx.amount;
+                    row.current_count += 1;
+                }
+                if in_previous {
+                    row.previous += tx.amount;
+                    row.previous_count += 1;
+                }
+            }
+        }
+
+        let mut rows: Vec<ComparisonRow> = groups
+            .into_values()
+            .map(ComparisonRow::with_change)
+            .collect();
+        rows.sort_by(|a, b| {
+            b.change
+                .abs()
+                .cmp(&a.change.abs())
+                .then_with(|| a.label.cmp(&b.label))
+        });
+
+        Ok(PeriodComparison {
+            group_by,
+            current,
+            previous,
+            rows,
+            total: total.with_change(),
+        })
+    }
+
+    /// Statement cycle status of every credit card with a cycle, as of `today`
+    ///
+    /// Liability balances count as owed whatever their sign, like net worth
+    /// does. Card transactions are negative for purchases, positive for
+    /// payments and refunds.
+    pub fn card_cycles(&self, today: NaiveDate) -> Result<Vec<CardCycle>> {
+        self.cached("card_cycles", today.to_string(), || {
+            self.build_card_cycles(today)
+        })
+    }
+
+    fn build_card_cycles(&self, today: NaiveDate) -> Result<Vec<CardCycle>> {
+        let cycles = self.repository.get_statement_cycles()?;
+        let mut cards = Vec::new();
+        for account in self.repository.get_accounts()? {
+            let account_id = account.id.to_string();
+            let Some(cycle) = cycles.get(&account_id).copied() else {
+                continue;
+            };
+            let (start, end) = cycle.period_containing(today);
+            let last_closing = start - Duration::days(1);
+            let due_date = cycle.due_date(last_closing);
+
+            let transactions = self.repository.get_transactions_by_account(&account_id)?;
+            let snapshots = self.repository.get_balance_snapshots(Some(&account_id))?;
+            let mut daily_totals: BTreeMap<NaiveDate, Decimal> = BTreeMap::new();
+            let mut current_spend = Decimal::ZERO;
+            let mut paid_since_closing = Decimal::ZERO;
+            for tx in &transactions {
+                *daily_totals.entry(tx.transaction_date).or_default() += tx.amount;
+                if tx.transaction_date <= last_closing {
+                    continue;
+                }
+                if tx.amount < Decimal::ZERO {
+                    current_spend -= tx.amount;
+                } else {
+                    paid_since_closing += tx.amount;
+                }
+            }
+            let closing_by_day: BTreeMap<NaiveDate, Decimal> = latest_per_day(&snapshots)
+                .into_iter()
+                .map(|(date, snapshot)| (date, snapshot.balance))
+                .collect();
+            let statement_balance =
+                balance_at_end_of(last_closing, today, &closing_by_day, &daily_totals)
+                    .map(|b| b.abs());
+            let payment_due =
+                statement_balance.map(|owed| (owed - paid_since_closing).max(Decimal::ZERO));
+
+            cards.push(CardCycle {
+                account_id,
+                account_name: account.nickname.unwrap_or(account.name),
+                currency: account.currency,
+                cycle,
+                current: Period { start, end },
+                current_spend,
+                last_closing,
+                statement_balance,
+                paid_since_closing,
+                payment_due,
+                due_date,
+                days_until_due: (due_date - today).num_days(),
+            });
+        }
+        cards.sort_by_key(|card| card.due_date);
+        Ok(cards)
+    }
+
+    /// Income, spending by category and spending by merchant within `period`
+    ///
+    /// Transactions tagged `transfer` are left out, as are positive amounts
+    /// on liability accounts (card payments and refunds), so moving money
+    /// between your own accounts isn't counted as income or spending.
+    /// Merchants are descriptions; past MAX_FLOW_MERCHANTS per category the
+    /// smallest are combined as "Other". Amounts in different currencies are
+    /// added together, like comparisons do.
+    pub fn cash_flows(&self, period: Period) -> Result<CashFlows> {
+        self.cached("cash_flows", format!("{period:?}"), || {
+            self.build_cash_flows(period)
+        })
+    }
+
+    fn build_cash_flows(&self, period: Period) -> Result<CashFlows> {
+        let liabilities: HashSet<String> = self
+            .repository
+            .get_accounts()?
+            .into_iter()
+            .filter(|a| a.classification.as_deref() == Some("liability"))
+            .map(|a| a.id.to_string())
+            .collect();
+
+        let mut sources: BTreeMap<String, Decimal> = BTreeMap::new();
+        let mut categories: BTreeMap<String, BTreeMap<String, Decimal>> = BTreeMap::new();
+        for tx in self.repository.get_transactions()? {
+            if !period.contains(tx.transaction_date)
+                || tx.excluded_from_reports
+                || tx.tags.iter().any(|t| t == "transfer")
+            {
+                continue;
+            }
+            if tx.amount > Decimal::ZERO {
+                if liabilities.contains(&tx.account_id.to_string()) {
+                    continue;
+                }
+                let source = tx.tags.first().map_or("Income", String::as_str);
+                *sources.entry(source.to_string()).or_default() += tx.amount;
+            } else if tx.amount < Decimal::ZERO {
+                let category = tx
+                    .tags
+                    .first()
+                    .cloned()
+                    .unwrap_or_else(|| UNCATEGORIZED.to_string());
+                let merchant = tx
+                    .description
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|d| !d.is_empty())
+                    .unwrap_or("Unknown")
+                    .to_string();
+                *categories
+                    .entry(category)
+                    .or_default()
+                    .entry(merchant)
+                    .or_default() -= tx.amount;
+            }
+        }
+
+        let total_income: Decimal = sources.values().sum();
+        let total_spending: Decimal = categories.values().flat_map(|m| m.values()).sum();
+        let mut flows = CashFlows {
+            period,
+            total_income,
+            total_spending,
+            nodes: Vec::new(),
+            links: Vec::new(),
+        };
+        let income_id = "income".to_string();
+
+        let mut sources: Vec<(String, Decimal)> = sources.into_iter().collect();
+        sources.sort_by(|a, b| b.1.cmp(&a.1));
+        for (label, value) in sources {
+            let id = format!("source:{}", label);
+            flows.link(&id, &income_id, value);
+            flows.node(id, label, FlowNodeKind::Source, value);
+        }
+        if total_spending > total_income {
+            let shortfall = total_spending - total_income;
+            flows.link("shortfall", &income_id, shortfall);
+            flows.node(
+                "shortfall".to_string(),
+                "From savings".to_string(),
+                FlowNodeKind::Shortfall,
+                shortfall,
+            );
+        }
+        flows.node(
+            income_id.clone(),
+            "Income".to_string(),
+            FlowNodeKind::Income,
+            total_income.max(total_spending),
+        );
+
+        let mut categories: Vec<(String, Vec<(String, Decimal)>, Decimal)> = categories
+            .into_iter()
+            .map(|(category, merchants)| {
+                let total = merchants.values().sum();
+                let mut merchants: Vec<(String, Decimal)> = merchants.into_iter().collect();
+                merchants.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                (category, merchants, total)
+            })
+            .collect();
+        categories.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+        for (category, mut merchants, total) in categories {
+            let category_id = format!("category:{}", category);
+            flows.link(&income_id, &category_id, total);
+            flows.node(
+                category_id.clone(),
+                category.clone(),
+                FlowNodeKind::Category,
+                total,
+            );
+
+            if merchants.len() > MAX_FLOW_MERCHANTS {
+                let other: Decimal = merchants
+                    .split_off(MAX_FLOW_MERCHANTS - 1)
+                    .into_iter()
+                    .map(|(_, value)| value)
+                    .sum();
+                merchants.push(("Other".to_string(), other));
+            }
+            for (merchant, value) in merchants {
+                let id = format!("merchant:{}/{}", category, merchant);
+                flows.link(&category_id, &id, value);
+                flows.node(id, merchant, FlowNodeKind::Merchant, value);
+            }
+        }
+
+        if total_income > total_spending {
+            let saved = total_income - total_spending;
+            flows.link(&income_id, "saved", saved);
+            flows.node(
+                "saved".to_string(),
+                "Saved".to_string(),
+                FlowNodeKind::Saved,
+                saved,
+            );
+        }
+        Ok(flows)
+    }
+}
+
+/// First and last day of a "YYYY-MM" month
+pub fn parse_month(month: &str) -> Result<(NaiveDate, NaiveDate)> {
+    let start = NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d")
+        .with_context(|| format!("Invalid month '{}', expected YYYY-MM", month))?;
+    let next = if start.month() == 12 {
+        NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+    }
+    .context("Month out of range")?;
+    Ok((start, next - Duration::days(1)))
+}
+
+impl ComparisonRow {
+    fn new(label: String) -> Self {
+        Self {
+            label,
+            current: Decimal::ZERO,
+            previous: Decimal::ZERO,
+            change: Decimal::ZERO,
+            change_percent: None,
+            current_count: 0,
+            previous_count: 0,
+        }
+    }
+
+    fn with_change(mut self) -> Self {
+        self.change = self.current - self.previous;
+        self.change_percent = if self.previous.is_zero() {
+            None
+        } else {
+            (self.change / self.previous.abs() * Decimal::ONE_HUNDRED)
+                .round_dp(1)
+                .to_f64()
+        };
+        self
+    }
+}
+
+impl CashFlows {
+    fn node(&mut self, id: String, label: String, kind: FlowNodeKind, value: Decimal) {
+        self.nodes.push(FlowNode {
+            id,
+            label,
+            kind,
+            value,
+        });
+    }
+
+    fn link(&mut self, source: &str, target: &str, value: Decimal) {
+        self.links.push(FlowLink {
+            source: source.to_string(),
+            target: target.to_string(),
+            value,
+        });
+    }
+}
+
+impl Period {
+    pub fn new(start: NaiveDate, end: NaiveDate) -> Result<Self> {
+        if end < start {
+            anyhow::bail!("Period ends ({}) before it starts ({})", end, start);
+        }
+        Ok(Self { start, end })
+    }
+
+    /// Parse "2025", "2025-Q1", "2025-03", "2025-03-14" or "2025-01-01..2025-02-15"
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let date = |s: &str| {
+            NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+                .with_context(|| format!("Invalid date '{}', expected YYYY-MM-DD", s.trim()))
+        };
+        let invalid = || {
+            anyhow::anyhow!(
+                "Invalid period '{}', expected YYYY, YYYY-Qn, YYYY-MM, YYYY-MM-DD or START..END",
+                text
+            )
+        };
+
+        if let Some((start, end)) = text.split_once("..") {
+            return Self::new(date(start)?, date(end)?);
+        }
+        if let Some((year, quarter)) = text.split_once(['Q', 'q']) {
+            let year: i32 = year.trim_end_matches('-').parse().map_err(|_| invalid())?;
+            let quarter: u32 = quarter.parse().map_err(|_| invalid())?;
+            if !(1..=4).contains(&quarter) {
+                return Err(invalid());
+            }
+            let start = NaiveDate::from_ymd_opt(year, quarter * 3 - 2, 1).ok_or_else(invalid)?;
+            return Ok(Self::months_from(start, 3));
+        }
+        match text.len() {
+            4 => {
+                let year: i32 = text.parse().map_err(|_| invalid())?;
+                let start = NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(invalid)?;
+                Ok(Self::months_from(start, 12))
+            }
+            7 => {
+                let (start, end) = parse_month(text).map_err(|_| invalid())?;
+                Self::new(start, end)
+            }
+            10 => {
+                let day = date(text)?;
+                Self::new(day, day)
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// The period of the same shape just before this one
+    ///
+    /// Whole months step back by their number of months, so a quarter is
+    /// compared with the previous quarter; other ranges by their length in days.
+    pub fn preceding(&self) -> Self {
+        match self.whole_months() {
+            Some(months) => Self::months_from(self.start - Months::new(months), months),
+            None => {
+                let length = self.end - self.start + Duration::days(1);
+                Self {
+                    start: self.start - length,
+                    end: self.end - length,
+                }
+            }
+        }
+    }
+
+    /// The same dates one year earlier, e.g. this March vs last March
+    pub fn year_earlier(&self) -> Self {
+        match self.whole_months() {
+            Some(months) => Self::months_from(self.start - Months::new(12), months),
+            None => Self {
+                start: self.start - Months::new(12),
+                end: self.end - Months::new(12),
+            },
+        }
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start <= date && date <= self.end
+    }
+
+    /// Number of calendar months when the period is exactly whole months
+    fn whole_months(&self) -> Option<u32> {
+        let next = self.end.succ_opt()?;
+        if self.start.day() != 1 || next.day() != 1 {
+            return None;
+        }
+        let index = |d: NaiveDate| d.year() * 12 + d.month() as i32;
+        u32::try_from(index(next) - index(self.start)).ok()
+    }
+
+    fn months_from(start: NaiveDate, months: u32) -> Self {
+        Self {
+            start,
+            end: start + Months::new(months) - Duration::days(1),
+        }
+    }
+}
+
+/// End-of-day balance on `day`, walked from the snapshot nearest `reference`
+///
+/// Prefers the latest snapshot on or before `reference`, else the earliest after it.
+fn balance_at_end_of(
+    day: NaiveDate,
+    reference: NaiveDate,
+    closing_by_day: &BTreeMap<NaiveDate, Decimal>,
+    daily_totals: &BTreeMap<NaiveDate, Decimal>,
+) -> Option<Decimal> {
+    let (anchor_date, anchor_balance) = closing_by_day
+        .range(..=reference)
+        .next_back()
+        .or_else(|| closing_by_day.range(reference..).next())?;
+
+    let balance = if day >= *anchor_date {
+        let later: Decimal = daily_totals
+            .range(anchor_date.succ_opt()?..=day)
+            .map(|(_, total)| *total)
+            .sum();
+        *anchor_balance + later
+    } else {
+        let between: Decimal = daily_totals
+            .range(day.succ_opt()?..=*anchor_date)
+            .map(|(_, total)| *total)
+            .sum();
+        *anchor_balance - between
+    };
+    Some(balance)
+}
+
+impl Statement {
+    /// Self-contained printable HTML
+    pub fn to_html(&self) -> String {
+        let money = |amount: Decimal| format!("{:.2}", amount);
+        let maybe = |amount: Option<Decimal>| amount.map(money).unwrap_or_else(|| "—".to_string());
+        let month = self.period_start.format("%B %Y").to_string();
+
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+  body {{ font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; color: #111; margin: 2rem; font-size: 13px; }}
+  h1 {{ font-size: 20px; margin: 0; }}
+  h2 {{ font-size: 15px; margin: 2rem 0 0.5rem; }}
+  .meta {{ color: #555; margin: 0.25rem 0 1.5rem; }}
+  table {{ width: 100%; border-collapse: collapse; }}
+  th, td {{ padding: 4px 6px; border-bottom: 1px solid #ddd; text-align: left; }}
+  th {{ background: #f4f4f4; }}
+  td.num, th.num {{ text-align: right; font-variant-numeric: tabular-nums; white-space: nowrap; }}
+  .summary td {{ border: none; padding: 2px 6px; }}
+  .summary td:first-child {{ color: #555; }}
+  tr {{ page-break-inside: avoid; }}
+  @media print {{ body {{ margin: 0; }} th {{ background: none; }} }}
+</style>
+</head>
+<body>
+<h1>{account}</h1>
+<p class="meta">{institution}Statement for {month} ({start} to {end}) &middot; {currency}</p>
+<table class="summary">
+<tr><td>Opening balance</td><td class="num">{opening}</td></tr>
+<tr><td>Credits</td><td class="num">{credits}</td></tr>
+<tr><td>Debits</td><td class="num">{debits}</td></tr>
+<tr><td><strong>Closing balance</strong></td><td class="num"><strong>{closing}</strong></td></tr>
+</table>
+"#,
+            title = escape(&format!("{} - {}", self.account_name, month)),
+            account = escape(&self.account_name),
+            institution = self
+                .institution_name
+                .as_deref()
+                .map(|name| format!("{} &middot; ", escape(name)))
+                .unwrap_or_default(),
+            month = escape(&month),
+            start = self.period_start,
+            end = self.period_end,
+            currency = escape(&self.currency),
+            opening = maybe(self.opening_balance),
+            credits = money(self.total_credits),
+            debits = money(self.total_debits),
+            closing = maybe(self.closing_balance),
+        );
+
+        html.push_str("<h2>Transactions</h2>\n");
+        if self.transactions.is_empty() {
+            html.push_str("<p>No transactions in this period.</p>\n");
+        } else {
+            html.push_str(
+                "<table>\n<tr><th>Date</th><th>Description</th><th>Category</th>\
+                 <th class=\"num\">Amount</th><th class=\"num\">Balance</th></tr>\n",
+            );
+            for line in &self.transactions {
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                    line.date,
+                    escape(&line.description),
+                    escape(&line.category),
+                    money(line.amount),
+                    maybe(line.balance),
+                );
+            }
+            html.push_str("</table>\n");
+        }
+
+        if !self.categories.is_empty() {
+            html.push_str(
+                "<h2>By category</h2>\n<table>\n<tr><th>Category</th>\
+                 <th class=\"num\">Transactions</th><th class=\"num\">Total</th></tr>\n",
+            );
+            for category in &self.categories {
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                    escape(&category.category),
+                    category.count,
+                    money(category.total),
+                );
+            }
+            html.push_str("</table>\n");
+        }
+
+        let _ = write!(
+            html,
+            "<p class=\"meta\">Generated by Treeline on {}</p>\n</body>\n</html>\n",
+            self.generated_at.format("%Y-%m-%d %H:%M UTC")
+        );
+        html
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_parse_month() {
+        assert_eq!(
+            parse_month("2024-02").unwrap(),
+            (date("2024-02-01"), date("2024-02-29"))
+        );
+        assert_eq!(parse_month("2024-12").unwrap().1, date("2024-12-31"));
+        assert!(parse_month("2024-13").is_err());
+        assert!(parse_month("March").is_err());
+    }
+
+    #[test]
+    fn test_balance_walks_from_anchor() {
+        let closing: BTreeMap<_, _> = [(date("2025-01-20"), Decimal::new(500, 0))].into();
+        let totals: BTreeMap<_, _> = [
+            (date("2025-01-05"), Decimal::new(-100, 0)),
+            (date("2025-01-25"), Decimal::new(50, 0)),
+        ]
+        .into();
+        let reference = date("2025-01-31");
+
+        // Backwards over the 5th, forwards over the 25th
+        assert_eq!(
+            balance_at_end_of(date("2024-12-31"), reference, &closing, &totals),
+            Some(Decimal::new(600, 0))
+        );
+        assert_eq!(
+            balance_at_end_of(date("2025-01-31"), reference, &closing, &totals),
+            Some(Decimal::new(550, 0))
+        );
+        assert_eq!(
+            balance_at_end_of(date("2025-01-31"), reference, &BTreeMap::new(), &totals),
+            None
+        );
+    }
+
+    #[test]
+    fn test_period_parse_and_shift() {
+        let q2 = Period::parse("2025-Q2").unwrap();
+        assert_eq!((q2.start, q2.end), (date("2025-04-01"), date("2025-06-30")));
+        assert_eq!(q2.preceding(), Period::parse("2025-Q1").unwrap());
+
+        let march = Period::parse("2024-03").unwrap();
+        assert_eq!(march.preceding(), Period::parse("2024-02").unwrap());
+        assert_eq!(march.year_earlier(), Period::parse("2023-03").unwrap());
+        assert_eq!(
+            Period::parse("2024-02").unwrap().year_earlier().end,
+            date("2023-02-28")
+        );
+        assert_eq!(
+            Period::parse("2024").unwrap().preceding(),
+            Period::parse("2023").unwrap()
+        );
+
+        let range = Period::parse("2025-01-10..2025-01-19").unwrap();
+        assert_eq!(
+            range.preceding(),
+            Period::parse("2024-12-31..2025-01-09").unwrap()
+        );
+
+        assert!(Period::parse("2025-Q5").is_err());
+        assert!(Period::parse("2025-02-10..2025-01-01").is_err());
+        assert!(Period::parse("last year").is_err());
+    }
+
+    #[test]
+    fn test_compare_periods() {
+        use crate::domain::{Account, Transaction};
+        use uuid::Uuid;
+
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        let account = Account::new(Uuid::new_v4(), "Checking");
+        repository.upsert_account(&account).unwrap();
+
+        let tx = |amount: i64, day: &str, tag: Option<&str>| {
+            let mut tx = Transaction::new(
+                Uuid::new_v4(),
+                account.id,
+                Decimal::new(amount, 0),
+                date(day),
+            );
+            tx.tags = tag.map(|t| vec![t.to_string()]).unwrap_or_default();
+            tx
+        };
+        repository
+            .bulk_insert_transactions(&[
+                tx(-100, "2025-02-10", Some("groceries")),
+                tx(-150, "2025-03-05", Some("groceries")),
+                tx(-40, "2025-03-20", None),
+                tx(-999, "2025-04-01", Some("groceries")),
+            ])
+            .unwrap();
+
+        let service = ReportService::new(repository);
+        let march = Period::parse("2025-03").unwrap();
+        let comparison = service
+            .compare_periods(march, march.preceding(), CompareBy::Category)
+            .unwrap();
+
+        assert_eq!(comparison.rows[0].label, "groceries");
+        assert_eq!(comparison.rows[0].change, Decimal::new(-50, 0));
+        assert_eq!(comparison.rows[0].change_percent, Some(-50.0));
+        assert_eq!(comparison.rows[1].label, UNCATEGORIZED);
+        assert_eq!(comparison.rows[1].change_percent, None);
+        assert_eq!(comparison.total.current, Decimal::new(-190, 0));
+        assert_eq!(comparison.total.previous_count, 1);
+
+        let by_account = service
+            .compare_periods(march, march.preceding(), CompareBy::Account)
+            .unwrap();
+        assert_eq!(by_account.rows.len(), 1);
+        assert_eq!(by_account.rows[0].label, "Checking");
+    }
+
+    #[test]
+    fn test_card_cycles() {
+        use crate::domain::{Account, BalanceSnapshot, Transaction};
+        use uuid::Uuid;
+
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        let card = Account::new(Uuid::new_v4(), "Visa");
+        repository.upsert_account(&card).unwrap();
+        repository
+            .upsert_account(&Account::new(Uuid::new_v4(), "Checking"))
+            .unwrap();
+        repository
+            .set_account_statement_cycle(
+                &card.id.to_string(),
+                Some(StatementCycle::new(15, 10).unwrap()),
+            )
+            .unwrap();
+
+        let tx = |amount: i64, day: &str| {
+            Transaction::new(Uuid::new_v4(), card.id, Decimal::new(amount, 0), date(day))
+        };
+        repository
+            .bulk_insert_transactions(&[
+                tx(-200, "2025-03-10"),
+                tx(-80, "2025-03-18"),
+                tx(300, "2025-03-25"),
+                tx(-20, "2025-03-26"),
+            ])
+            .unwrap();
+        let time = date("2025-03-20").and_hms_opt(12, 0, 0).unwrap();
+        repository
+            .add_balance_snapshot(&BalanceSnapshot::new(card.id, Decimal::new(-500, 0), time))
+            .unwrap();
+
+        let cards = ReportService::new(repository)
+            .card_cycles(date("2025-03-28"))
+            .unwrap();
+        assert_eq!(cards.len(), 1);
+        let visa = &cards[0];
+        assert_eq!(
+            (visa.current.start, visa.current.end),
+            (date("2025-03-16"), date("2025-04-15"))
+        );
+        assert_eq!(visa.current_spend, Decimal::new(100, 0));
+        // -500 on the 20th, less the 80 spent since the 15th
+        assert_eq!(visa.statement_balance, Some(Decimal::new(420, 0)));
+        assert_eq!(visa.paid_since_closing, Decimal::new(300, 0));
+        assert_eq!(visa.payment_due, Some(Decimal::new(120, 0)));
+        assert_eq!(visa.due_date, date("2025-04-10"));
+        assert_eq!(visa.days_until_due, 13);
+    }
+
+    #[test]
+    fn test_cash_flows() {
+        use crate::domain::{Account, Transaction};
+        use uuid::Uuid;
+
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        let checking = Account::new(Uuid::new_v4(), "Checking");
+        let mut card = Account::new(Uuid::new_v4(), "Visa");
+        card.account_type = Some("credit_card".to_string());
+        card.classification = Some("liability".to_string());
+        repository.upsert_account(&checking).unwrap();
+        repository.upsert_account(&card).unwrap();
+
+        let tx = |account: &Account, amount: i64, day: &str, description: &str, tag: &str| {
+            let mut tx = Transaction::new(
+                Uuid::new_v4(),
+                account.id,
+                Decimal::new(amount, 0),
+                date(day),
+            );
+            tx.description = Some(description.to_string());
+            tx.tags = if tag.is_empty() {
+                Vec::new()
+            } else {
+                vec![tag.to_string()]
+            };
+            tx
+        };
+        let mut transactions = vec![
+            tx(&checking, 3000, "2025-03-01", "ACME PAYROLL", "salary"),
+            tx(&checking, 200, "2025-03-05", "Etsy payout", ""),
+            tx(&card, -120, "2025-03-03", "Whole Foods", "groceries"),
+            tx(&card, -80, "2025-03-10", "Whole Foods", "groceries"),
+            tx(&card, -40, "2025-03-12", "Corner Shop", "groceries"),
+            tx(&checking, -1500, "2025-03-02", "Rent", "housing"),
+            // Card payment, both sides, and a deposit outside the period
+            tx(&checking, -240, "2025-03-20", "Visa payment", "transfer"),
+            tx(&card, 240, "2025-03-20", "Payment thank you", ""),
+            tx(&checking, 3000, "2025-04-01", "ACME PAYROLL", "salary"),
+        ];
+        for i in 0..=MAX_FLOW_MERCHANTS {
+            transactions.push(tx(
+                &card,
+                -10,
+                "2025-03-15",
+                &format!("Cafe {}", i),
+                "dining",
+            ));
+        }
+        // An expense the employer paid back; neither side is income or spending
+        for (amount, description) in [(-450, "Conference hotel"), (450, "ACME reimbursement")] {
+            let mut passthrough = tx(&checking, amount, "2025-03-18", description, "");
+            passthrough.excluded_from_reports = true;
+            transactions.push(passthrough);
+        }
+        repository.bulk_insert_transactions(&transactions).unwrap();
+
+        let period = Period::parse("2025-03").unwrap();
+        let flows = ReportService::new(repository).cash_flows(period).unwrap();
+        assert_eq!(flows.total_income, Decimal::new(3200, 0));
+        assert_eq!(flows.total_spending, Decimal::new(1830, 0));
+
+        let link = |source: &str, target: &str| {
+            flows
+                .links
+                .iter()
+                .find(|l| l.source == source && l.target == target)
+                .map(|l| l.value)
+        };
+        assert_eq!(link("source:salary", "income"), Some(Decimal::new(3000, 0)));
+        assert_eq!(link("source:Income", "income"), Some(Decimal::new(200, 0)));
+        assert_eq!(
+            link("income", "category:groceries"),
+            Some(Decimal::new(240, 0))
+        );
+        assert_eq!(
+            link("category:groceries", "merchant:groceries/Whole Foods"),
+            Some(Decimal::new(200, 0))
+        );
+        assert_eq!(link("income", "saved"), Some(Decimal::new(1370, 0)));
+        assert_eq!(link("shortfall", "income"), None);
+
+        // Nine cafes become seven and "Other"
+        let dining: Vec<&FlowLink> = flows
+            .links
+            .iter()
+            .filter(|l| l.source == "category:dining")
+            .collect();
+        assert_eq!(dining.len(), MAX_FLOW_MERCHANTS);
+        assert_eq!(
+            link("category:dining", "merchant:dining/Other"),
+            Some(Decimal::new(20, 0))
+        );
+
+        // Every link joins two nodes, and the income node balances
+        let ids: HashSet<&str> = flows.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert!(flows
+            .links
+            .iter()
+            .all(|l| ids.contains(l.source.as_str()) && ids.contains(l.target.as_str())));
+        let into_income: Decimal = flows
+            .links
+            .iter()
+            .filter(|l| l.target == "income")
+            .map(|l| l.value)
+            .sum();
+        let out_of_income: Decimal = flows
+            .links
+            .iter()
+            .filter(|l| l.source == "income")
+            .map(|l| l.value)
+            .sum();
+        assert_eq!(into_income, out_of_income);
+        assert_eq!(flows.nodes[0].id, "source:salary");
+    }
+
+    #[test]
+    fn test_results_cached_until_data_changes() {
+        use crate::domain::{Account, Transaction};
+        use uuid::Uuid;
+
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        let account = Account::new(Uuid::new_v4(), "Checking");
+        repository.upsert_account(&account).unwrap();
+        let add = |amount: i64| {
+            let tx = Transaction::new(
+                Uuid::new_v4(),
+                account.id,
+                Decimal::new(amount, 0),
+                date("2025-03-05"),
+            );
+            repository.upsert_transaction(&tx).unwrap();
+        };
+        add(-25);
+
+        let period = Period::parse("2025-03").unwrap();
+        let service = ReportService::new(repository.clone());
+        let spending = |service: &ReportService| service.cash_flows(period).unwrap().total_spending;
+        assert_eq!(spending(&service), Decimal::new(25, 0));
+        // Another service for the same database shares the cache
+        assert_eq!(
+            spending(&ReportService::new(repository.clone())),
+            Decimal::new(25, 0)
+        );
+        let stats = service.cache.stats();
+        assert_eq!((stats.entries, stats.hits, stats.misses), (1, 1, 1));
+
+        // A write invalidates the cached result
+        add(-10);
+        assert_eq!(spending(&service), Decimal::new(35, 0));
+        let stats = service.cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.stale), (1, 2, 1));
+
+        // Skipping the cache recomputes
+        assert_eq!(spending(&service.with_cache(false)), Decimal::new(35, 0));
+        let stats = service.cache.stats();
+        assert_eq!((stats.hits, stats.bypassed), (1, 1));
+    }
+
+    #[test]
+    fn test_html_escapes_descriptions() {
+        let statement = Statement {
+            account_id: "a".to_string(),
+            account_name: "Checking".to_string(),
+            institution_name: None,
+            currency: "USD".to_string(),
+            period_start: date("2025-01-01"),
+            period_end: date("2025-01-31"),
+            opening_balance: None,
+            closing_balance: None,
+            total_credits: Decimal::ZERO,
+            total_debits: Decimal::new(-1250, 2),
+            transactions: vec![StatementLine {
+                date: date("2025-01-03"),
+                description: "<b>Tom & Jerry's</b>".to_string(),
+                amount: Decimal::new(-1250, 2),
+                balance: None,
+                category: UNCATEGORIZED.to_string(),
+            }],
+            categories: Vec::new(),
+            generated_at: Utc::now(),
+        };
+        let html = statement.to_html();
+        assert!(html.contains("&lt;b&gt;Tom &amp; Jerry's&lt;/b&gt;"));
+        assert!(html.contains("-12.50"));
+        assert!(html.contains("January 2025"));
+    }
+}
@@ -0,0 +1,149 @@
+//! Report cache - reuse report results until the data changes
+//!
+//! Cash flows and period comparisons walk every transaction, and the
+//! dashboard asks for the same ones each time it opens. Results are kept
+//! per database, keyed by report and parameters, along with the data
+//! version they were computed at. Any write changes the version, so a stale
+//! result is never returned; it's recomputed on the next request.
+//!
+//! The cache lives in memory and is shared by every `ReportService` for the
+//! same database in the process, so the app's per-command services share it.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use serde::Serialize;
+
+use crate::adapters::duckdb::DataVersion;
+use crate::adapters::write_queue::database_key;
+
+/// Results kept per database; the least recently used goes first
+const MAX_ENTRIES: usize = 64;
+
+/// Cache counters since the process started
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReportCacheStats {
+    /// Results held right now
+    pub entries: usize,
+    pub hits: u64,
+    /// Requests that computed the report, including `stale` ones
+    pub misses: u64,
+    /// Misses where a result was cached but the data had changed since
+    pub stale: u64,
+    /// Requests that skipped the cache (`--no-cache`)
+    pub bypassed: u64,
+}
+
+struct Entry {
+    version: DataVersion,
+    value: Arc<dyn Any + Send + Sync>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    /// Keyed by (report, parameters)
+    entries: HashMap<(String, String), Entry>,
+    /// Lookup counter, for least-recently-used eviction
+    clock: u64,
+    stats: ReportCacheStats,
+}
+
+/// Cached report results for one database
+#[derive(Default)]
+pub struct ReportCache {
+    state: Mutex<CacheState>,
+}
+
+impl ReportCache {
+    /// The cache for `db_path`, shared within the process
+    pub fn for_path(db_path: &Path) -> Arc<ReportCache> {
+        static CACHES: OnceLock<Mutex<HashMap<PathBuf, Arc<ReportCache>>>> = OnceLock::new();
+        CACHES
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap()
+            .entry(database_key(db_path))
+            .or_default()
+            .clone()
+    }
+
+    /// The result cached for this report and parameters at `version`
+    pub fn get<T: Clone + 'static>(
+        &self,
+        report: &str,
+        params: &str,
+        version: DataVersion,
+    ) -> Option<T> {
+        let mut state = self.state.lock().unwrap();
+        state.clock += 1;
+        let clock = state.clock;
+        let key = (report.to_string(), params.to_string());
+
+        let (hit, stale) = match state.entries.get_mut(&key) {
+            Some(entry) if entry.version == version => {
+                entry.last_used = clock;
+                (entry.value.downcast_ref::<T>().cloned(), false)
+            }
+            Some(_) => (None, true),
+            None => (None, false),
+        };
+        if stale {
+            state.entries.remove(&key);
+            state.stats.stale += 1;
+        }
+        if hit.is_some() {
+            state.stats.hits += 1;
+        } else {
+            state.stats.misses += 1;
+        }
+        hit
+    }
+
+    /// Keep a result computed at `version`
+    pub fn insert<T: Send + Sync + 'static>(
+        &self,
+        report: &str,
+        params: &str,
+        version: DataVersion,
+        value: T,
+    ) {
+        let mut state = self.state.lock().unwrap();
+        // Results from before the latest write can't be hit again
+        state.entries.retain(|_, entry| entry.version == version);
+        if state.entries.len() >= MAX_ENTRIES {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(key) = oldest {
+                state.entries.remove(&key);
+            }
+        }
+        let last_used = state.clock;
+        state.entries.insert(
+            (report.to_string(), params.to_string()),
+            Entry {
+                version,
+                value: Arc::new(value),
+                last_used,
+            },
+        );
+    }
+
+    /// Count a request that skipped the cache
+    pub fn record_bypass(&self) {
+        self.state.lock().unwrap().stats.bypassed += 1;
+    }
+
+    pub fn stats(&self) -> ReportCacheStats {
+        let state = self.state.lock().unwrap();
+        ReportCacheStats {
+            entries: state.entries.len(),
+            ..state.stats.clone()
+        }
+    }
+}
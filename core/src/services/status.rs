@@ -3,6 +3,7 @@
 use std::sync::Arc;
 
 use anyhow::Result;
+use schemars::JsonSchema;
 use serde::Serialize;
 
 use crate::adapters::duckdb::DuckDbRepository;
@@ -22,7 +23,7 @@ impl StatusService {
         let accounts = self.repository.get_accounts()?;
         let transaction_count = self.repository.get_transaction_count()?;
         let snapshot_count = self.repository.get_balance_snapshot_count()?;
-        let integrations = self.repository.get_integrations()?;
+        let integrations = self.repository.get_integration_names()?;
         let date_range = self.repository.get_transaction_date_range()?;
 
         Ok(StatusSummary {
@@ -30,7 +31,7 @@ impl StatusService {
             total_transactions: transaction_count,
             total_snapshots: snapshot_count,
             total_integrations: integrations.len() as i64,
-            integration_names: integrations.iter().map(|i| i.name.clone()).collect(),
+            integration_names: integrations,
             accounts: accounts
                 .into_iter()
                 .map(|a| AccountSummary {
@@ -44,7 +45,7 @@ impl StatusService {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct StatusSummary {
     pub total_accounts: i64,
     pub total_transactions: i64,
@@ -55,14 +56,14 @@ pub struct StatusSummary {
     pub date_range: DateRange,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct AccountSummary {
     pub id: String,
     pub name: String,
     pub institution_name: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct DateRange {
     pub earliest: Option<String>,
     pub latest: Option<String>,
@@ -0,0 +1,382 @@
+//! Storage service - what takes up space, and what can go
+//!
+//! Breaks the data directory down by table (grouped into your data, plugin
+//! data, history and system tables), logs, backups and archives, then
+//! suggests what would help: compacting when much of the database file is
+//! free blocks, archiving old years, pruning history tables and clearing old
+//! logs. Only history tables nothing else reads from can be pruned, so a
+//! prune never loses transactions, balances or settings.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::adapters::duckdb::DuckDbRepository;
+
+const MIB: u64 = 1024 * 1024;
+
+/// Free space worth compacting for, if it's also a fifth of the file
+const COMPACT_MIN_FREE_BYTES: u64 = 16 * MIB;
+/// Transactions in years old enough to archive before it's suggested
+const ARCHIVE_MIN_TRANSACTIONS: i64 = 50_000;
+/// Years kept in the main database when suggesting what to archive
+const ARCHIVE_KEEP_YEARS: i32 = 3;
+/// Size of a prunable table before pruning it is suggested
+const PRUNE_MIN_BYTES: u64 = MIB;
+/// Size of the logs database before clearing old logs is suggested
+const LOGS_MAX_BYTES: u64 = 64 * MIB;
+
+/// Tables that only record history, and what they hold
+///
+/// Emptying them loses history only; no balances, reports or settings
+/// are computed from them.
+pub const PRUNABLE_TABLES: &[(&str, &str)] = &[
+    ("plugin_query.history", "Query view history"),
+    ("sys_sync_history", "Sync run history"),
+];
+
+/// What a table holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageGroup {
+    /// Accounts, transactions and balance snapshots
+    Data,
+    /// Tables plugins created for their own data
+    Plugin,
+    /// The change log and other records of what happened
+    History,
+    /// Settings, migrations and sync state
+    System,
+}
+
+/// One table's share of the database
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TableStorage {
+    pub table: String,
+    pub group: StorageGroup,
+    pub rows: i64,
+    /// Estimated from the storage blocks the table uses
+    pub estimated_bytes: u64,
+    /// Whether `tl storage prune` can empty it
+    pub prunable: bool,
+}
+
+/// What a suggestion proposes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageAction {
+    Compact,
+    Archive,
+    Prune,
+    ClearLogs,
+}
+
+/// Something that would free space
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct StorageSuggestion {
+    pub action: StorageAction,
+    pub message: String,
+    /// CLI command that does it
+    pub command: String,
+    /// Roughly how much it frees, when that can be told in advance
+    pub reclaimable_bytes: Option<u64>,
+}
+
+/// Where the data directory's space goes
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct StorageBreakdown {
+    pub database_path: PathBuf,
+    pub database_bytes: u64,
+    pub wal_bytes: u64,
+    /// Free blocks inside the database file, returned by compacting
+    pub free_bytes: u64,
+    /// Largest first
+    pub tables: Vec<TableStorage>,
+    pub logs_bytes: u64,
+    pub backups_bytes: u64,
+    pub archives_bytes: u64,
+    pub suggestions: Vec<StorageSuggestion>,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Rows removed by a prune
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PruneResult {
+    pub table: String,
+    pub rows_deleted: usize,
+}
+
+/// Storage service for analyzing and trimming the data directory
+pub struct StorageService {
+    repository: Arc<DuckDbRepository>,
+    treeline_dir: PathBuf,
+}
+
+impl StorageService {
+    pub fn new(repository: Arc<DuckDbRepository>, treeline_dir: PathBuf) -> Self {
+        Self {
+            repository,
+            treeline_dir,
+        }
+    }
+
+    /// Break down storage use and suggest what to do about it
+    pub fn analyze(&self, today: NaiveDate) -> Result<StorageBreakdown> {
+        let db_path = self.repository.db_path().to_path_buf();
+        let mut tables: Vec<TableStorage> = self
+            .repository
+            .get_table_storage()?
+            .into_iter()
+            .map(|(table, rows, estimated_bytes)| TableStorage {
+                group: group_of(&table),
+                prunable: PRUNABLE_TABLES.iter().any(|(name, _)| *name == table),
+                table,
+                rows,
+                estimated_bytes,
+            })
+            .collect();
+        tables.sort_by(|a, b| {
+            b.estimated_bytes
+                .cmp(&a.estimated_bytes)
+                .then_with(|| a.table.cmp(&b.table))
+        });
+
+        let mut wal_name = db_path.file_name().unwrap_or_default().to_os_string();
+        wal_name.push(".wal");
+        let mut breakdown = StorageBreakdown {
+            database_bytes: file_size(&db_path),
+            wal_bytes: file_size(&db_path.with_file_name(wal_name)),
+            database_path: db_path,
+            free_bytes: self.repository.get_free_bytes()?,
+            tables,
+            logs_bytes: file_size(&self.treeline_dir.join("logs.duckdb")),
+            backups_bytes: dir_size(&self.treeline_dir.join("backups")),
+            archives_bytes: dir_size(&self.repository.archive_dir()),
+            suggestions: Vec::new(),
+            generated_at: Utc::now(),
+        };
+        breakdown.suggestions = self.suggest(&breakdown, today)?;
+        Ok(breakdown)
+    }
+
+    /// Empty prunable tables, all of them when `tables` is empty
+    pub fn prune(&self, tables: &[String]) -> Result<Vec<PruneResult>> {
+        if let Some(table) = tables
+            .iter()
+            .find(|t| !PRUNABLE_TABLES.iter().any(|(name, _)| name == t))
+        {
+            let names: Vec<&str> = PRUNABLE_TABLES.iter().map(|(name, _)| *name).collect();
+            anyhow::bail!(
+                "{} can't be pruned; prunable tables are {}",
+                table,
+                names.join(", ")
+            );
+        }
+
+        let mut results = Vec::new();
+        for (table, _) in PRUNABLE_TABLES {
+            if !tables.is_empty() && !tables.iter().any(|t| t == table) {
+                continue;
+            }
+            // Plugin tables only exist once their plugin has run
+            if !self.repository.table_exists(table)? {
+                continue;
+            }
+            results.push(PruneResult {
+                table: table.to_string(),
+                rows_deleted: self.repository.clear_table(table)?,
+            });
+        }
+        Ok(results)
+    }
+
+    fn suggest(
+        &self,
+        breakdown: &StorageBreakdown,
+        today: NaiveDate,
+    ) -> Result<Vec<StorageSuggestion>> {
+        let mut suggestions = Vec::new();
+
+        if breakdown.free_bytes >= COMPACT_MIN_FREE_BYTES
+            && breakdown.free_bytes * 5 >= breakdown.database_bytes
+        {
+            suggestions.push(StorageSuggestion {
+                action: StorageAction::Compact,
+                message: format!(
+                    "{} of the database file is free space left by deleted data",
+                    format_bytes(breakdown.free_bytes)
+                ),
+                command: "tl compact".to_string(),
+                reclaimable_bytes: Some(breakdown.free_bytes),
+            });
+        }
+
+        let old_years: Vec<(i32, i64)> = self
+            .repository
+            .get_transaction_counts_by_year()?
+            .into_iter()
+            .filter(|(year, _)| *year <= today.year() - ARCHIVE_KEEP_YEARS)
+            .collect();
+        let old_transactions: i64 = old_years.iter().map(|(_, count)| count).sum();
+        if old_transactions >= ARCHIVE_MIN_TRANSACTIONS {
+            if let Some((oldest, _)) = old_years.first() {
+                suggestions.push(StorageSuggestion {
+                    action: StorageAction::Archive,
+                    message: format!(
+                        "{} transactions are from {} to {}; archiving those years keeps \
+                         them queryable outside the main database",
+                        old_transactions,
+                        oldest,
+                        today.year() - ARCHIVE_KEEP_YEARS
+                    ),
+                    command: format!("tl archive year {}", oldest),
+                    reclaimable_bytes: None,
+                });
+            }
+        }
+
+        for table in &breakdown.tables {
+            if table.prunable && table.estimated_bytes >= PRUNE_MIN_BYTES {
+                suggestions.push(StorageSuggestion {
+                    action: StorageAction::Prune,
+                    message: format!(
+                        "{} ({}) takes {}",
+                        describe(&table.table),
+                        table.table,
+                        format_bytes(table.estimated_bytes)
+                    ),
+                    command: format!("tl storage prune {}", table.table),
+                    reclaimable_bytes: Some(table.estimated_bytes),
+                });
+            }
+        }
+
+        if breakdown.logs_bytes >= LOGS_MAX_BYTES {
+            suggestions.push(StorageSuggestion {
+                action: StorageAction::ClearLogs,
+                message: format!(
+                    "The logs database takes {}",
+                    format_bytes(breakdown.logs_bytes)
+                ),
+                command: "tl logs clear --older-than-days 30".to_string(),
+                reclaimable_bytes: None,
+            });
+        }
+
+        Ok(suggestions)
+    }
+}
+
+/// What a prunable table holds
+fn describe(table: &str) -> &'static str {
+    PRUNABLE_TABLES
+        .iter()
+        .find(|(name, _)| *name == table)
+        .map_or("History", |(_, description)| *description)
+}
+
+fn group_of(table: &str) -> StorageGroup {
+    match table {
+        "sys_accounts"
+        | "sys_transactions"
+        | "sys_balance_snapshots"
+        | "sys_holdings"
+        | "sys_holding_lots"
+        | "sys_security_prices" => StorageGroup::Data,
+        "sys_change_log" => StorageGroup::History,
+        _ if PRUNABLE_TABLES.iter().any(|(name, _)| *name == table) => StorageGroup::History,
+        _ if table.starts_with("plugin_") => StorageGroup::Plugin,
+        _ => StorageGroup::System,
+    }
+}
+
+/// Human-readable size, e.g. "12.3 MB"
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["bytes", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} bytes", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+/// Total size of the files in a directory, recursively (0 when it doesn't exist)
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| match entry.file_type() {
+            Ok(kind) if kind.is_dir() => dir_size(&entry.path()),
+            _ => file_size(&entry.path()),
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_and_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+        repository
+            .execute_sql(
+                "INSERT INTO sys_sync_history (sync_id, integration_name, started_at, duration_ms)
+                 VALUES ('a', 'simplefin', '2025-01-01 00:00:00', 10)",
+            )
+            .unwrap();
+        fs::create_dir_all(dir.path().join("backups")).unwrap();
+        fs::write(dir.path().join("backups/treeline-1.zip"), vec![0u8; 100]).unwrap();
+
+        let service = StorageService::new(Arc::clone(&repository), dir.path().to_path_buf());
+        let today = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        let breakdown = service.analyze(today).unwrap();
+        assert!(breakdown.database_bytes > 0);
+        assert_eq!(breakdown.backups_bytes, 100);
+        assert_eq!(breakdown.archives_bytes, 0);
+        let table = |name: &str| breakdown.tables.iter().find(|t| t.table == name).unwrap();
+        assert_eq!(table("sys_transactions").group, StorageGroup::Data);
+        assert_eq!(table("sys_change_log").group, StorageGroup::History);
+        assert!(!table("sys_change_log").prunable);
+        assert!(table("sys_sync_history").prunable);
+        assert_eq!(table("sys_sync_history").rows, 1);
+        // A fresh database has nothing worth doing
+        assert!(breakdown.suggestions.is_empty());
+
+        assert!(service.prune(&["sys_transactions".to_string()]).is_err());
+        let pruned = service.prune(&[]).unwrap();
+        let history = pruned
+            .iter()
+            .find(|p| p.table == "sys_sync_history")
+            .unwrap();
+        assert_eq!(history.rows_deleted, 1);
+        assert_eq!(repository.get_sync_history(None, 10).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 bytes");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(20 * MIB), "20.0 MB");
+    }
+}
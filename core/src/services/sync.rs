@@ -1,28 +1,70 @@
 //! Sync service - synchronize accounts and transactions from integrations
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
 
 use anyhow::Result;
-use chrono::{Duration, Utc};
+use chrono::{Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use schemars::JsonSchema;
 use serde::Serialize;
 use uuid::Uuid;
 
+#[cfg(feature = "sync-coinbase")]
+use crate::adapters::coinbase::CoinbaseProvider;
 use crate::adapters::demo::DemoDataProvider;
-use crate::adapters::duckdb::DuckDbRepository;
+use crate::adapters::duckdb::{DuckDbRepository, SyncHistoryEntry, SYNC_HISTORY_RETENTION};
+#[cfg(feature = "sync-gocardless")]
+use crate::adapters::gocardless::{
+    self, GoCardlessClient, GoCardlessInstitution, GoCardlessProvider,
+};
+#[cfg(feature = "sync-kraken")]
+use crate::adapters::kraken::KrakenProvider;
+#[cfg(feature = "sync-lunchflow")]
 use crate::adapters::lunchflow::LunchflowProvider;
+#[cfg(feature = "sync-plaid")]
+use crate::adapters::plaid::{self, PlaidClient, PlaidProvider, PlaidToken};
+#[cfg(feature = "sync-simplefin")]
 use crate::adapters::simplefin::SimpleFINProvider;
+use crate::adapters::write_queue;
+use crate::domain::{Account, Holding};
 use crate::ports::{DataAggregationProvider, IntegrationProvider};
-use crate::services::TagService;
+#[cfg(feature = "http")]
+use crate::services::PublishService;
+use crate::services::{CancellationToken, HookEvent, HookService, InvestmentService, TagService};
+
+/// (new, updated, skipped, auto-tag failures, new per account) from `process_transactions`
+type ProcessedTransactions = (
+    i64,
+    i64,
+    i64,
+    Vec<crate::services::tag::RuleFailure>,
+    HashMap<Uuid, i64>,
+);
+
+/// Days of history the first sync of an account (or a full resync) fetches
+const INITIAL_SYNC_DAYS: i64 = 90;
+
+/// Days an incremental sync goes back before an account's cursor, for
+/// transactions that post a few days after their date
+const SYNC_OVERLAP_DAYS: i64 = 7;
+
+/// Days after a pending transaction that the version that posts may be dated
+const PENDING_POST_DAYS: i64 = 10;
+
+/// Called with each progress report of a sync
+type SyncProgressFn = Box<dyn Fn(&SyncProgress) + Send + Sync>;
 
 /// Sync service for account and transaction synchronization
 pub struct SyncService {
     repository: Arc<DuckDbRepository>,
     tag_service: TagService,
-    _treeline_dir: PathBuf,
+    treeline_dir: PathBuf,
     providers: HashMap<String, Arc<dyn DataAggregationProvider>>,
     integration_providers: HashMap<String, Arc<dyn IntegrationProvider>>,
+    progress: Option<SyncProgressFn>,
+    cancellation: Option<CancellationToken>,
 }
 
 impl SyncService {
@@ -36,36 +78,117 @@ impl SyncService {
         providers.insert("demo".to_string(), demo.clone());
         integration_providers.insert("demo".to_string(), demo);
 
-        let simplefin = Arc::new(SimpleFINProvider::new());
-        providers.insert("simplefin".to_string(), simplefin.clone());
-        integration_providers.insert("simplefin".to_string(), simplefin);
+        #[cfg(feature = "sync-simplefin")]
+        {
+            let simplefin = Arc::new(SimpleFINProvider::new());
+            providers.insert("simplefin".to_string(), simplefin.clone());
+            integration_providers.insert("simplefin".to_string(), simplefin);
+        }
 
         // Register Lunchflow provider (global bank connections)
-        let lunchflow = Arc::new(LunchflowProvider::new());
-        providers.insert("lunchflow".to_string(), lunchflow.clone());
-        integration_providers.insert("lunchflow".to_string(), lunchflow);
+        #[cfg(feature = "sync-lunchflow")]
+        {
+            let lunchflow = Arc::new(LunchflowProvider::new());
+            providers.insert("lunchflow".to_string(), lunchflow.clone());
+            integration_providers.insert("lunchflow".to_string(), lunchflow);
+        }
+
+        // Register Plaid provider (US/Canada banks via Plaid Link)
+        #[cfg(feature = "sync-plaid")]
+        {
+            let plaid = Arc::new(PlaidProvider::new());
+            providers.insert("plaid".to_string(), plaid.clone());
+            integration_providers.insert("plaid".to_string(), plaid);
+        }
+
+        // Register GoCardless provider (European banks via PSD2)
+        #[cfg(feature = "sync-gocardless")]
+        {
+            let gocardless = Arc::new(GoCardlessProvider::new());
+            providers.insert("gocardless".to_string(), gocardless.clone());
+            integration_providers.insert("gocardless".to_string(), gocardless);
+        }
+
+        // Register crypto exchanges (balances valued in the user's currency)
+        #[cfg(feature = "sync-coinbase")]
+        {
+            let coinbase = Arc::new(CoinbaseProvider::new());
+            providers.insert("coinbase".to_string(), coinbase.clone());
+            integration_providers.insert("coinbase".to_string(), coinbase);
+        }
+
+        #[cfg(feature = "sync-kraken")]
+        {
+            let kraken = Arc::new(KrakenProvider::new());
+            providers.insert("kraken".to_string(), kraken.clone());
+            integration_providers.insert("kraken".to_string(), kraken);
+        }
 
         let tag_service = TagService::new(repository.clone());
 
         Self {
             repository,
             tag_service,
-            _treeline_dir: treeline_dir,
+            treeline_dir,
             providers,
             integration_providers,
+            progress: None,
+            cancellation: None,
+        }
+    }
+
+    /// Report progress while syncing: as each integration fetches, and after
+    /// each account's transactions are saved
+    pub fn with_progress(
+        mut self,
+        progress: impl Fn(&SyncProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Stop a sync once `token` is cancelled
+    ///
+    /// Checked between integrations and between accounts. What was saved
+    /// before stays; cursors only move once an integration's transactions
+    /// are all saved, so the next sync fetches the rest again.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    fn report(&self, progress: SyncProgress) {
+        if let Some(report) = &self.progress {
+            report(&progress);
+        }
+    }
+
+    fn check_cancelled(&self) -> Result<()> {
+        if self.cancellation.as_ref().is_some_and(|t| t.is_cancelled()) {
+            anyhow::bail!("Sync cancelled");
         }
+        Ok(())
     }
 
     /// Sync from all integrations or a specific one
     ///
     /// If `balances_only` is true, skips transaction fetching entirely.
     /// This is useful for users who just want to track account balances.
+    ///
+    /// Transactions are fetched from where each account's last sync left off
+    /// (its cursor in the integration's `syncedThrough` setting). `full`
+    /// ignores the cursors and fetches the whole initial window again.
+    ///
+    /// Runs at background priority, so interactive database work from
+    /// other threads (e.g. tag edits in the app) goes first.
     pub fn sync(
         &self,
         integration: Option<&str>,
         dry_run: bool,
         balances_only: bool,
+        full: bool,
     ) -> Result<SyncResult> {
+        let _priority = write_queue::background();
         let integrations = self.repository.get_integrations()?;
         let mut results = Vec::new();
 
@@ -76,111 +199,223 @@ impl SyncService {
         };
 
         if integrations_to_sync.is_empty() {
-            anyhow::bail!("No integrations configured");
+            match integration {
+                Some(name) => anyhow::bail!("Integration not found: {}", name),
+                None => anyhow::bail!("No integrations configured"),
+            }
         }
 
-        for int in integrations_to_sync {
-            let result = self.sync_integration(&int.name, &int.settings, dry_run, balances_only)?;
-            results.push(result);
+        let count = integrations_to_sync.len();
+        for (index, int) in integrations_to_sync.into_iter().enumerate() {
+            self.check_cancelled()?;
+            let started_at = Utc::now();
+            let position = SyncProgress::new(&int.name, index, count);
+            let result =
+                self.sync_integration(&int.settings, position, dry_run, balances_only, full);
+            if !dry_run {
+                // Best-effort: a history write failure shouldn't fail the sync itself
+                let _ = self.record_history(&int.name, started_at, &result);
+            }
+            results.push(result?);
         }
 
-        Ok(SyncResult {
+        let result = SyncResult {
             results,
             new_accounts_without_type: Vec::new(),
-        })
+        };
+        if !dry_run {
+            HookService::new(self.treeline_dir.clone()).run(HookEvent::PostSync, &result);
+            #[cfg(feature = "http")]
+            PublishService::new(self.repository.clone(), self.treeline_dir.clone())
+                .publish_after_sync();
+        }
+        Ok(result)
+    }
+
+    /// Persist the outcome of one integration's sync in sys_sync_history
+    fn record_history(
+        &self,
+        integration: &str,
+        started_at: chrono::DateTime<Utc>,
+        result: &Result<IntegrationSyncResult>,
+    ) -> Result<()> {
+        let mut entry = SyncHistoryEntry {
+            sync_id: Uuid::new_v4().to_string(),
+            integration: integration.to_string(),
+            started_at,
+            duration_ms: (Utc::now() - started_at).num_milliseconds(),
+            sync_type: None,
+            accounts_synced: 0,
+            transactions_discovered: 0,
+            transactions_new: 0,
+            transactions_skipped: 0,
+            account_stats: Default::default(),
+            warnings: Vec::new(),
+            error: None,
+        };
+        match result {
+            Ok(r) => {
+                entry.sync_type = Some(r.sync_type.clone());
+                entry.accounts_synced = r.accounts_synced;
+                entry.transactions_discovered = r.transaction_stats.discovered;
+                entry.transactions_new = r.transaction_stats.new;
+                entry.transactions_skipped = r.transaction_stats.skipped;
+                entry.account_stats = r
+                    .new_by_account
+                    .iter()
+                    .map(|(id, count)| (id.to_string(), *count))
+                    .collect();
+                entry.warnings = r.provider_warnings.clone();
+                entry.error = r.error.clone();
+            }
+            Err(e) => entry.error = Some(e.to_string()),
+        }
+        self.repository.insert_sync_history(&entry)
+    }
+
+    /// Recorded sync runs, newest first
+    pub fn history(
+        &self,
+        integration: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<SyncHistoryEntry>> {
+        self.repository.get_sync_history(integration, limit)
+    }
+
+    /// When each synced account last synced and last produced new transactions
+    ///
+    /// Accounts linked to an integration that keep syncing without new data
+    /// (or whose integration keeps failing) are the silent failures worth a look.
+    pub fn account_activity(&self) -> Result<Vec<AccountSyncActivity>> {
+        let history = self
+            .repository
+            .get_sync_history(None, SYNC_HISTORY_RETENTION)?;
+        let accounts = self.repository.get_accounts()?;
+
+        let mut activity = Vec::new();
+        for account in accounts {
+            let id = account.id.to_string();
+            let linked = account.provider.source();
+            // Accounts without a provider ID (demo, CSV-only) count if history mentions them
+            let integration = match linked {
+                Some(name) => name.to_string(),
+                None => match history.iter().find(|h| h.account_stats.contains_key(&id)) {
+                    Some(h) => h.integration.clone(),
+                    None => continue,
+                },
+            };
+
+            let runs: Vec<&SyncHistoryEntry> = history
+                .iter()
+                .filter(|h| h.integration == integration)
+                .collect();
+            let last_sync = runs.first();
+            let last_success = runs.iter().find(|h| h.error.is_none());
+            let last_data = runs
+                .iter()
+                .find(|h| h.account_stats.get(&id).is_some_and(|n| *n > 0));
+
+            activity.push(AccountSyncActivity {
+                account_id: id.clone(),
+                account_name: account.name.clone(),
+                integration,
+                last_synced_at: last_success.map(|h| h.started_at.to_rfc3339()),
+                last_new_data_at: last_data.map(|h| h.started_at.to_rfc3339()),
+                last_new_transactions: last_data
+                    .and_then(|h| h.account_stats.get(&id).copied())
+                    .unwrap_or(0),
+                last_error: last_sync.and_then(|h| h.error.clone()),
+            });
+        }
+        Ok(activity)
     }
 
     fn sync_integration(
         &self,
-        name: &str,
         settings: &serde_json::Value,
+        position: SyncProgress,
         dry_run: bool,
         balances_only: bool,
+        full: bool,
     ) -> Result<IntegrationSyncResult> {
+        let name = position.integration.as_str();
         // Look up provider by name
         let provider = self
             .providers
             .get(name)
             .ok_or_else(|| anyhow::anyhow!("Unknown provider: {}", name))?;
 
-        let now = Utc::now();
-        let end_date = now.naive_utc().date();
-
-        // Calculate start date based on sync type
-        let max_tx_date = self.repository.get_max_transaction_date()?;
-        let (start_date, is_incremental) = match max_tx_date {
-            Some(max_date) => (max_date - Duration::days(7), true),
-            None => ((now - Duration::days(90)).naive_utc().date(), false),
-        };
-
-        let sync_type = if is_incremental {
-            "incremental"
-        } else {
-            "initial"
-        };
+        let end_date = Utc::now().naive_utc().date();
 
         // Fetch accounts from provider
+        self.report(position.at(SyncStage::Accounts));
         let accounts_result = provider.get_accounts(settings)?;
         let mut provider_warnings = accounts_result.warnings;
 
         // Build map of provider external ID to internal account ID
-        // Use provider-specific columns (sf_id/lf_id) for mapping
+        // Use the provider's entry in external_ids for mapping
         let existing_accounts = self.repository.get_accounts()?;
         let mut external_to_internal: HashMap<String, Uuid> = HashMap::new();
 
         for existing in &existing_accounts {
-            // Get provider-specific ID column
-            let ext_id = match name {
-                "simplefin" => existing.sf_id.clone(),
-                "lunchflow" => existing.lf_id.clone(),
-                // Demo mode: use the account name as the external ID (stable across syncs)
-                "demo" => Some(existing.name.clone()),
-                _ => None,
-            };
-
-            if let Some(id) = ext_id {
+            if let Some(id) = external_id(name, existing) {
                 external_to_internal.insert(id, existing.id);
             }
         }
 
+        // Accounts turned off in accountSettings are left alone entirely, and
+        // empty ones are only added once they hold something
+        let empty: HashSet<String> = accounts_result.empty_accounts.into_iter().collect();
+        let remote_accounts: Vec<Account> = accounts_result
+            .accounts
+            .into_iter()
+            .filter(|account| {
+                external_id(name, account).map_or(true, |id| {
+                    account_enabled(settings, &id)
+                        && (!empty.contains(&id) || external_to_internal.contains_key(&id))
+                })
+            })
+            .collect();
+
         // Track original account IDs for balance snapshot mapping
         let mut orig_to_ext: HashMap<Uuid, String> = HashMap::new();
-        for account in &accounts_result.accounts {
-            let ext_id = match name {
-                "simplefin" => account.sf_id.clone(),
-                "lunchflow" => account.lf_id.clone(),
-                // Demo mode: use the account name as the external ID
-                "demo" => Some(account.name.clone()),
-                _ => None,
-            };
-
-            if let Some(id) = ext_id {
+        for account in &remote_accounts {
+            if let Some(id) = external_id(name, account) {
                 orig_to_ext.insert(account.id, id);
             }
         }
 
         // Process accounts
         let mut accounts_synced = 0i64;
-        for mut account in accounts_result.accounts {
-            // Get external ID from provider-specific column
-            let ext_id = match name {
-                "simplefin" => account.sf_id.clone(),
-                "lunchflow" => account.lf_id.clone(),
-                // Demo mode: use the account name as the external ID
-                "demo" => Some(account.name.clone()),
-                _ => None,
-            };
-            let ext_id = ext_id.unwrap_or_default();
-
-            if let Some(&existing_id) = external_to_internal.get(&ext_id) {
+        let mut synced_ids: HashSet<Uuid> = HashSet::new();
+        let mut account_names: HashMap<String, String> = HashMap::new();
+        for mut account in remote_accounts {
+            self.check_cancelled()?;
+            let ext_id = external_id(name, &account).unwrap_or_default();
+            account_names.insert(ext_id.clone(), account.name.clone());
+
+            // Renewed GoCardless access comes with new account IDs; the IBAN
+            // finds the account they continue, which then takes the new ID
+            let existing_id = external_to_internal.get(&ext_id).copied().or_else(|| {
+                let key = stable_key(name, &account)?;
+                existing_accounts
+                    .iter()
+                    .find(|e| stable_key(name, e).as_ref() == Some(&key))
+                    .map(|e| e.id)
+            });
+
+            if let Some(existing_id) = existing_id {
                 // Existing account - update ID
                 account.id = existing_id;
+                synced_ids.insert(existing_id);
+                external_to_internal.insert(ext_id, existing_id);
                 if !dry_run {
                     self.repository.upsert_account(&account)?;
                 }
             } else {
                 // New account
                 external_to_internal.insert(ext_id, account.id);
+                synced_ids.insert(account.id);
                 accounts_synced += 1;
                 if !dry_run {
                     self.repository.upsert_account(&account)?;
@@ -201,75 +436,231 @@ impl SyncService {
             }
         }
 
-        // Skip transaction fetching entirely if balances_only mode
-        let (discovered, new_count, skipped_count, auto_tag_failures) = if balances_only {
-            (0, 0, 0, Vec::new())
-        } else {
-            // Fetch transactions (excluding per-account balances-only settings)
-            // Check accountSettings for balancesOnly flag on each account
-            let account_settings = settings.get("accountSettings").and_then(|v| v.as_object());
-
-            let ext_account_ids: Vec<String> = external_to_internal
-                .keys()
-                .filter(|ext_id| {
-                    // Include account only if NOT marked as balancesOnly
-                    if let Some(settings_map) = account_settings {
-                        if let Some(acc_settings) = settings_map.get(*ext_id) {
-                            // Default to false if balancesOnly not present
-                            return !acc_settings
-                                .get("balancesOnly")
-                                .and_then(|v| v.as_bool())
-                                .unwrap_or(false);
-                        }
+        // Replace the holdings of synced accounts, for providers that report
+        // them. Accounts without holdings are only touched if they had some
+        let mut holdings_synced = 0i64;
+        if let Some(holdings) = accounts_result.holdings.filter(|_| !dry_run) {
+            let investments = InvestmentService::new(self.repository.clone());
+            let mut by_account: HashMap<Uuid, Vec<Holding>> = self
+                .repository
+                .get_holdings(None)?
+                .into_iter()
+                .map(|h| (h.account_id, Vec::new()))
+                .filter(|(id, _)| synced_ids.contains(id))
+                .collect();
+            for (ext_id, holding) in holdings {
+                if let Some(&internal_id) = external_to_internal.get(&ext_id) {
+                    if synced_ids.contains(&internal_id) {
+                        by_account.entry(internal_id).or_default().push(holding);
                     }
-                    // No settings for this account = include it
-                    true
+                }
+            }
+            for (account_id, holdings) in by_account {
+                holdings_synced += investments.save_synced(name, account_id, holdings)? as i64;
+            }
+        }
+
+        // Transactions are fetched for accounts that aren't turned off or
+        // marked balancesOnly in accountSettings
+        let ext_account_ids: Vec<String> = external_to_internal
+            .keys()
+            .filter(|ext_id| {
+                account_enabled(settings, ext_id)
+                    && !account_flag(settings, ext_id, "balancesOnly").unwrap_or(false)
+            })
+            .cloned()
+            .collect();
+
+        // One request covers all accounts, so it starts at the earliest
+        // cursor. Accounts synced before cursors existed go by their latest
+        // transaction, and one with neither needs the initial window
+        let latest = self.repository.get_latest_transaction_dates()?;
+        let cursor = ext_account_ids
+            .iter()
+            .map(|ext_id| {
+                synced_through(settings, ext_id).or_else(|| {
+                    let id = external_to_internal.get(ext_id)?;
+                    latest.get(id).copied()
                 })
-                .cloned()
-                .collect();
+            })
+            .collect::<Option<Vec<_>>>()
+            .and_then(|dates| dates.into_iter().min());
+        let initial_start = end_date - Duration::days(INITIAL_SYNC_DAYS);
+        let (start_date, sync_type) = match cursor {
+            _ if full => (initial_start, "full"),
+            Some(date) => (date - Duration::days(SYNC_OVERLAP_DAYS), "incremental"),
+            None => (initial_start, "initial"),
+        };
 
-            let txs_result =
-                provider.get_transactions(start_date, end_date, &ext_account_ids, settings)?;
-            provider_warnings.extend(txs_result.warnings);
+        // Skip transaction fetching entirely if balances_only mode
+        let mut failed_accounts = Vec::new();
+        let mut updated_count = 0;
+        let (discovered, new_count, skipped_count, auto_tag_failures, new_by_account) =
+            if balances_only {
+                (0, 0, 0, Vec::new(), HashMap::new())
+            } else {
+                // Plaid keeps its own cursors, which a full resync starts over
+                #[cfg(feature = "sync-plaid")]
+                let reset = (full && name == "plaid").then(|| plaid::without_cursors(settings));
+                #[cfg(not(feature = "sync-plaid"))]
+                let reset: Option<serde_json::Value> = None;
+                let fetch_settings = reset.as_ref().unwrap_or(settings);
+                self.check_cancelled()?;
+                self.report(position.at(SyncStage::Fetching));
+                let txs_result = provider.get_transactions(
+                    start_date,
+                    end_date,
+                    &ext_account_ids,
+                    fetch_settings,
+                )?;
+                // Cursors only move past a fetch that went without a hitch,
+                // and not for accounts whose transactions couldn't be fetched
+                let fetched_cleanly = txs_result.warnings.is_empty();
+                provider_warnings.extend(txs_result.warnings);
+                for (ext_id, error) in txs_result.failed_accounts {
+                    let account_name = account_names
+                        .get(&ext_id)
+                        .cloned()
+                        .unwrap_or_else(|| ext_id.clone());
+                    provider_warnings.push(format!(
+                        "Transactions for '{}' weren't synced: {}",
+                        account_name, error
+                    ));
+                    failed_accounts.push(AccountSyncFailure {
+                        account_id: external_to_internal.get(&ext_id).map(|id| id.to_string()),
+                        external_id: ext_id,
+                        account_name,
+                        error,
+                    });
+                }
 
-            // Process transactions with deduplication
-            let (new_count, skipped_count, auto_tag_failures) = self.process_transactions(
-                name,
-                txs_result.transactions,
-                &external_to_internal,
-                dry_run,
-            )?;
+                // Process transactions with deduplication, one account at a
+                // time so progress moves and a cancel stops between accounts
+                let mut by_account: BTreeMap<String, Vec<(String, crate::domain::Transaction)>> =
+                    BTreeMap::new();
+                for (ext_id, tx) in txs_result.transactions {
+                    by_account
+                        .entry(ext_id.clone())
+                        .or_default()
+                        .push((ext_id, tx));
+                }
+                let accounts_total = by_account.len();
+                let (mut new_count, mut skipped_count) = (0, 0);
+                let mut auto_tag_failures = Vec::new();
+                let mut new_by_account = HashMap::new();
+                // Pending transactions stored by earlier syncs, by provider ID,
+                // loaded once for all accounts
+                let mut stored_pending: HashMap<String, crate::domain::Transaction> = self
+                    .repository
+                    .get_pending_transactions(name)?
+                    .into_iter()
+                    .filter_map(|tx| Some((tx.provider.external_id(name)?.to_string(), tx)))
+                    .collect();
+                for (done, (ext_id, transactions)) in by_account.into_iter().enumerate() {
+                    self.check_cancelled()?;
+                    let fetched = transactions.len() as i64;
+                    let (new, updated, skipped, failures, by_id) = self.process_transactions(
+                        name,
+                        transactions,
+                        &external_to_internal,
+                        &mut stored_pending,
+                        dry_run,
+                    )?;
+                    new_count += new;
+                    updated_count += updated;
+                    skipped_count += skipped;
+                    auto_tag_failures.extend(failures);
+                    new_by_account.extend(by_id);
+                    self.report(SyncProgress {
+                        stage: SyncStage::Saving,
+                        account_id: external_to_internal.get(&ext_id).map(|id| id.to_string()),
+                        account_name: account_names.get(&ext_id).cloned(),
+                        accounts_done: done + 1,
+                        accounts_total,
+                        fetched,
+                        inserted: new,
+                        ..position.clone()
+                    });
+                }
 
-            let discovered = new_count + skipped_count;
-            (discovered, new_count, skipped_count, auto_tag_failures)
-        };
+                if !dry_run {
+                    // Only Plaid withdraws transactions (pending ones that posted)
+                    if name == "plaid" {
+                        self.repository
+                            .delete_transactions_by_external_ids(name, &txs_result.removed)?;
+                    }
+                    // Saved after the transactions, so a failed sync fetches
+                    // them again from the old cursor
+                    let mut updated = txs_result.settings;
+                    if fetched_cleanly {
+                        let mut with_cursors = updated.unwrap_or_else(|| fetch_settings.clone());
+                        let through = end_date.format("%Y-%m-%d").to_string();
+                        for ext_id in &ext_account_ids {
+                            if failed_accounts.iter().any(|f| &f.external_id == ext_id) {
+                                continue;
+                            }
+                            with_cursors["syncedThrough"][ext_id] = serde_json::json!(through);
+                        }
+                        updated = Some(with_cursors);
+                    }
+                    if let Some(updated) = updated {
+                        self.repository.upsert_integration(name, &updated)?;
+                    }
+                }
+
+                let discovered = new_count + updated_count + skipped_count;
+                (
+                    discovered,
+                    new_count,
+                    skipped_count,
+                    auto_tag_failures,
+                    new_by_account,
+                )
+            };
+
+        self.report(SyncProgress {
+            fetched: discovered,
+            inserted: new_count,
+            ..position.at(SyncStage::Finished)
+        });
 
         Ok(IntegrationSyncResult {
             integration: name.to_string(),
             accounts_synced,
+            holdings_synced,
             transactions_synced: new_count,
             transaction_stats: TransactionStats {
                 discovered,
                 new: new_count,
+                updated: updated_count,
                 skipped: skipped_count,
             },
             sync_type: sync_type.to_string(),
             start_date: start_date.format("%Y-%m-%d").to_string(),
             end_date: end_date.format("%Y-%m-%d").to_string(),
             provider_warnings,
+            failed_accounts,
             error: None,
             auto_tag_failures,
+            new_by_account,
         })
     }
 
     /// Process transactions with deduplication logic
     ///
     /// Deduplication strategy:
-    /// 1. Collect all provider-specific IDs (sf_id or lf_id) from incoming transactions
+    /// 1. Collect the provider's IDs (its entry in external_ids) from incoming transactions
     /// 2. Bulk check which IDs already exist (single connection)
-    /// 3. Filter to new transactions only
+    /// 3. Filter to new transactions only, settling pending ones instead (see below)
     /// 4. Bulk insert new transactions (single connection, single checkpoint)
     ///
+    /// A pending transaction synced before is updated in place while the
+    /// provider still reports it. Once it doesn't, it has posted, usually
+    /// under a new ID and sometimes for a different amount: the posted
+    /// version that matches it (see `pending_match`) takes over its row, so
+    /// the user's tags stay and no second copy appears. Pending transactions
+    /// settled here are taken out of `stored_pending`.
+    ///
     /// This approach eliminates the visibility gap between check and insert that
     /// can cause duplicate transactions when using individual connections per operation.
     fn process_transactions(
@@ -277,12 +668,13 @@ impl SyncService {
         provider_name: &str,
         transactions: Vec<(String, crate::domain::Transaction)>,
         external_to_internal: &HashMap<String, Uuid>,
+        stored_pending: &mut HashMap<String, crate::domain::Transaction>,
         dry_run: bool,
-    ) -> Result<(i64, i64, Vec<crate::services::tag::RuleFailure>)> {
+    ) -> Result<ProcessedTransactions> {
         // 1. Map account IDs and collect provider IDs for bulk check
         let mut mapped_txs: Vec<crate::domain::Transaction> = Vec::new();
-        let mut sf_ids: Vec<String> = Vec::new();
-        let mut lf_ids: Vec<String> = Vec::new();
+        let mut provider_ids: Vec<String> = Vec::new();
+        let sign_policies = self.repository.get_sign_policies()?;
 
         for (ext_account_id, mut tx) in transactions {
             // Map to internal account ID
@@ -292,48 +684,75 @@ impl SyncService {
             };
             tx.account_id = internal_account_id;
 
+            // Flip amounts for accounts whose provider reports charges as positive
+            // (the amount in provider_metadata keeps the provider's sign)
+            if let Some(policy) = sign_policies.get(&internal_account_id.to_string()) {
+                tx.amount = policy.apply(tx.amount);
+            }
+
             // Collect provider IDs for bulk check
-            match provider_name {
-                "simplefin" => {
-                    if let Some(ref id) = tx.sf_id {
-                        sf_ids.push(id.clone());
-                    }
-                }
-                "lunchflow" => {
-                    if let Some(ref id) = tx.lf_id {
-                        lf_ids.push(id.clone());
-                    }
-                }
-                _ => {}
+            if let Some(id) = tx.provider.external_id(provider_name) {
+                provider_ids.push(id.to_string());
             }
             mapped_txs.push(tx);
         }
 
         // 2. Bulk check for existing IDs (single connection)
-        let existing: std::collections::HashSet<String> = match provider_name {
-            "simplefin" => self.repository.get_existing_sf_ids(&sf_ids)?,
-            "lunchflow" => self.repository.get_existing_lf_ids(&lf_ids)?,
-            // Demo mode: no deduplication (demo has its own DB)
-            _ => std::collections::HashSet::new(),
-        };
-
-        // 3. Filter to new transactions only
-        let new_txs: Vec<crate::domain::Transaction> = mapped_txs
-            .into_iter()
-            .filter(|tx| {
-                let id = match provider_name {
-                    "simplefin" => tx.sf_id.as_ref(),
-                    "lunchflow" => tx.lf_id.as_ref(),
-                    _ => None,
-                };
-                // Keep if no provider ID (can't check), or if ID not in existing set
-                id.map(|id| !existing.contains(id)).unwrap_or(true)
-            })
+        // Demo transactions carry no provider IDs, so nothing is checked (demo has its own DB)
+        let existing = self
+            .repository
+            .get_existing_external_ids(provider_name, &provider_ids)?;
+
+        // 3. Filter to new transactions only, settling pending ones
+        let reported: HashSet<&str> = provider_ids.iter().map(String::as_str).collect();
+        let mut posted_elsewhere: Vec<crate::domain::Transaction> = stored_pending
+            .iter()
+            .filter(|(id, _)| !reported.contains(id.as_str()))
+            .map(|(_, tx)| tx.clone())
             .collect();
 
+        let mut new_txs: Vec<crate::domain::Transaction> = Vec::new();
+        let mut settled: Vec<crate::domain::Transaction> = Vec::new();
+        for tx in mapped_txs {
+            match tx.provider.external_id(provider_name) {
+                // Already stored; only a pending one can have changed
+                Some(id) if existing.contains(id) => {
+                    if let Some(mut stored) = stored_pending.remove(id) {
+                        if stored.settle(&tx) {
+                            settled.push(stored);
+                        }
+                    }
+                }
+                _ if !tx.pending => match pending_match(&tx, &posted_elsewhere) {
+                    Some(index) => {
+                        let mut stored = posted_elsewhere.swap_remove(index);
+                        if let Some(id) = stored.provider.external_id(provider_name) {
+                            stored_pending.remove(id);
+                        }
+                        stored.settle(&tx);
+                        settled.push(stored);
+                    }
+                    None => new_txs.push(tx),
+                },
+                // No provider ID (can't check), or not stored yet
+                _ => new_txs.push(tx),
+            }
+        }
+
         let new_count = new_txs.len() as i64;
-        let total_with_ids = (sf_ids.len() + lf_ids.len()) as i64;
-        let skipped_count = total_with_ids - new_count.min(total_with_ids);
+        let updated_count = settled.len() as i64;
+        let mut new_by_account: HashMap<Uuid, i64> = HashMap::new();
+        for tx in &new_txs {
+            *new_by_account.entry(tx.account_id).or_insert(0) += 1;
+        }
+        let total_with_ids = provider_ids.len() as i64;
+        let skipped_count = (total_with_ids - new_count - updated_count).max(0);
+
+        if !dry_run {
+            for tx in &settled {
+                self.repository.upsert_transaction(tx)?;
+            }
+        }
 
         // 4. Bulk insert (single connection, single checkpoint)
         let auto_tag_failures = if !dry_run && !new_txs.is_empty() {
@@ -350,26 +769,131 @@ impl SyncService {
             Vec::new()
         };
 
-        Ok((new_count, skipped_count, auto_tag_failures))
+        Ok((
+            new_count,
+            updated_count,
+            skipped_count,
+            auto_tag_failures,
+            new_by_account,
+        ))
     }
 
     /// List configured integrations
     pub fn list_integrations(&self) -> Result<Vec<IntegrationInfo>> {
-        let integrations = self.repository.get_integrations()?;
-        Ok(integrations
-            .iter()
-            .map(|i| IntegrationInfo {
-                name: i.name.clone(),
-                provider: i.name.clone(),
+        let names = self.repository.get_integration_names()?;
+        Ok(names
+            .into_iter()
+            .map(|name| IntegrationInfo {
+                provider: name.clone(),
+                name,
             })
             .collect())
     }
 
-    /// Remove an integration
-    pub fn remove_integration(&self, name: &str) -> Result<()> {
+    /// Remove an integration along with its stored credentials
+    ///
+    /// Its accounts stay linked by provider ID, so setting the integration up
+    /// again picks them back up. With `unlink_accounts` they keep their
+    /// history as standalone accounts instead. Returns the number unlinked.
+    pub fn remove_integration(&self, name: &str, unlink_accounts: bool) -> Result<usize> {
         if !self.repository.delete_integration(name)? {
             anyhow::bail!("Integration not found: {}", name);
         }
+        if unlink_accounts {
+            return self.repository.unlink_integration_accounts(name);
+        }
+        Ok(0)
+    }
+
+    /// Accounts the integration's provider offers, and how each one syncs
+    ///
+    /// Asks the provider, so accounts that were never synced (or are turned
+    /// off) are listed too.
+    pub fn list_remote_accounts(&self, name: &str) -> Result<Vec<RemoteAccount>> {
+        let integration = self
+            .repository
+            .get_integrations()?
+            .into_iter()
+            .find(|i| i.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Integration not found: {}", name))?;
+        let provider = self
+            .providers
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown provider: {}", name))?;
+
+        let linked: HashMap<String, Uuid> = self
+            .repository
+            .get_accounts()?
+            .iter()
+            .filter_map(|a| external_id(name, a).map(|id| (id, a.id)))
+            .collect();
+        let settings = &integration.settings;
+
+        // Empty accounts (an exchange's wallet for every asset) only once synced
+        let remote = provider.get_accounts(settings)?;
+        let empty: HashSet<String> = remote.empty_accounts.into_iter().collect();
+        Ok(remote
+            .accounts
+            .into_iter()
+            .filter_map(|account| {
+                let id = external_id(name, &account)?;
+                if empty.contains(&id) && !linked.contains_key(&id) {
+                    return None;
+                }
+                Some(RemoteAccount {
+                    account_id: linked.get(&id).map(|u| u.to_string()),
+                    enabled: account_enabled(settings, &id),
+                    balances_only: account_flag(settings, &id, "balancesOnly").unwrap_or(false),
+                    institution_name: account.institution_name,
+                    status: account
+                        .provider
+                        .metadata_str("lunchflow", "status")
+                        .map(String::from),
+                    currency: account.currency,
+                    name: account.name,
+                    external_id: id,
+                })
+            })
+            .collect())
+    }
+
+    /// Turn syncing of one of the integration's accounts on or off
+    ///
+    /// A turned-off account is neither created nor updated by sync, and no
+    /// balances or transactions are fetched for it.
+    ///
+    /// Plaid sends each transaction only once, so turning a Plaid account on
+    /// makes the next sync start its banks over from the beginning; dedup
+    /// skips what's already stored.
+    pub fn set_account_enabled(&self, name: &str, external_id: &str, enabled: bool) -> Result<()> {
+        if !self.repository.set_integration_account_setting(
+            name,
+            external_id,
+            "enabled",
+            serde_json::Value::Bool(enabled),
+        )? {
+            anyhow::bail!("Integration not found: {}", name);
+        }
+        if name == "plaid" && enabled {
+            if let Some(mut integration) = self
+                .repository
+                .get_integrations()?
+                .into_iter()
+                .find(|i| i.name == name)
+            {
+                if let Some(items) = integration
+                    .settings
+                    .get_mut("items")
+                    .and_then(|v| v.as_array_mut())
+                {
+                    for item in items.iter_mut().filter_map(|item| item.as_object_mut()) {
+                        item.remove("cursor");
+                    }
+                }
+                self.repository
+                    .upsert_integration(name, &integration.settings)?;
+            }
+        }
         Ok(())
     }
 
@@ -390,6 +914,68 @@ impl SyncService {
         Ok(())
     }
 
+    /// Replace an integration's credentials, keeping its account settings
+    ///
+    /// `credential` is a SimpleFIN setup token, a Lunchflow API key, a Plaid
+    /// secret or a GoCardless secret key; the provider validates it before
+    /// anything is stored. Per-account settings, a custom base URL and the
+    /// linked banks of Plaid and GoCardless carry over, so history and account
+    /// links are untouched. Coinbase and Kraken keys come in pairs, so those
+    /// are set up again instead.
+    pub fn rotate_credentials(&self, name: &str, credential: &str) -> Result<()> {
+        let current = self
+            .repository
+            .get_integrations()?
+            .into_iter()
+            .find(|i| i.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Integration not found: {}", name))?;
+        #[cfg(feature = "sync-gocardless")]
+        if name == "gocardless" {
+            // Setup would start connecting a bank; getting a token is check enough
+            let secret_id = current.settings["secretId"].as_str().unwrap_or_default();
+            GoCardlessClient::connect(
+                secret_id,
+                credential,
+                gocardless::base_url(&current.settings),
+            )?;
+            let mut settings = current.settings.clone();
+            settings["secretKey"] = serde_json::json!(credential);
+            return self.repository.upsert_integration(name, &settings);
+        }
+        let mut options = match name {
+            "simplefin" => serde_json::json!({ "setupToken": credential }),
+            "lunchflow" => serde_json::json!({ "apiKey": credential }),
+            "plaid" => {
+                // The new secret is checked against the first linked bank
+                let mut options = current.settings.clone();
+                options["secret"] = serde_json::json!(credential);
+                options["accessToken"] = current.settings["items"][0]["accessToken"].clone();
+                options
+            }
+            "coinbase" | "kraken" => anyhow::bail!(
+                "{} secrets belong to one API key; set {} up again with the new key and secret",
+                name,
+                name
+            ),
+            _ => anyhow::bail!("{} has no credentials to rotate", name),
+        };
+        if let Some(url) = current.settings.get("baseUrl") {
+            options["baseUrl"] = url.clone();
+        }
+
+        let provider = self
+            .integration_providers
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown provider: {}", name))?;
+        let mut settings = provider.setup(&options)?;
+        for key in ["accountSettings", "items"] {
+            if let Some(value) = current.settings.get(key) {
+                settings[key] = value.clone();
+            }
+        }
+        self.repository.upsert_integration(name, &settings)
+    }
+
     /// Set up demo integration (convenience method)
     pub fn setup_demo(&self) -> Result<()> {
         self.setup_integration("demo", &serde_json::json!({}))
@@ -419,38 +1005,471 @@ impl SyncService {
         }
         self.setup_integration("lunchflow", &options)
     }
+
+    /// Set up Coinbase integration (convenience method)
+    ///
+    /// # Arguments
+    /// * `api_key`, `api_secret` - A read-only API key from the Coinbase settings
+    /// * `currency` - What balances are valued in (None = the Coinbase account's currency)
+    /// * `base_url` - Optional custom base URL for testing (None = production)
+    pub fn setup_coinbase(
+        &self,
+        api_key: &str,
+        api_secret: &str,
+        currency: Option<&str>,
+        base_url: Option<&str>,
+    ) -> Result<()> {
+        self.setup_integration(
+            "coinbase",
+            &exchange_options(api_key, api_secret, currency, base_url),
+        )
+    }
+
+    /// Set up Kraken integration (convenience method)
+    ///
+    /// # Arguments
+    /// * `api_key`, `api_secret` - An API key and private key with the Query Funds permission
+    /// * `currency` - What balances are valued in (None = USD)
+    /// * `base_url` - Optional custom base URL for testing (None = production)
+    pub fn setup_kraken(
+        &self,
+        api_key: &str,
+        api_secret: &str,
+        currency: Option<&str>,
+        base_url: Option<&str>,
+    ) -> Result<()> {
+        self.setup_integration(
+            "kraken",
+            &exchange_options(api_key, api_secret, currency, base_url),
+        )
+    }
+
+    /// Link a bank through Plaid
+    ///
+    /// Each bank is its own Plaid item with its own access token; linking
+    /// another bank adds it next to those already linked, keeping their
+    /// cursors and the per-account settings.
+    ///
+    /// # Arguments
+    /// * `client_id`, `secret` - API keys from the Plaid dashboard
+    /// * `environment` - "sandbox" or "production"
+    /// * `token` - The bank login to link
+    /// * `base_url` - Optional custom base URL for testing (None = the environment's)
+    #[cfg(feature = "sync-plaid")]
+    pub fn setup_plaid(
+        &self,
+        client_id: &str,
+        secret: &str,
+        environment: &str,
+        token: PlaidToken<'_>,
+        base_url: Option<&str>,
+    ) -> Result<()> {
+        let mut options = serde_json::json!({
+            "clientId": client_id,
+            "secret": secret,
+            "environment": environment,
+        });
+        match token {
+            PlaidToken::Public(public_token) => options["publicToken"] = public_token.into(),
+            PlaidToken::Access(access_token) => options["accessToken"] = access_token.into(),
+            PlaidToken::Sandbox => options["sandbox"] = true.into(),
+        }
+        if let Some(url) = base_url {
+            options["baseUrl"] = serde_json::json!(url);
+        }
+
+        let provider = self
+            .integration_providers
+            .get("plaid")
+            .ok_or_else(|| anyhow::anyhow!("Unknown provider: plaid"))?;
+        let mut settings = provider.setup(&options)?;
+
+        if let Some(current) = self
+            .repository
+            .get_integrations()?
+            .into_iter()
+            .find(|i| i.name == "plaid")
+        {
+            let linked = settings["items"][0].clone();
+            let mut items: Vec<serde_json::Value> = current.settings["items"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default();
+            // Linking a bank again replaces its old item
+            items.retain(|item| item["itemId"] != linked["itemId"]);
+            items.push(linked);
+            settings["items"] = items.into();
+            if let Some(account_settings) = current.settings.get("accountSettings") {
+                settings["accountSettings"] = account_settings.clone();
+            }
+        }
+        self.repository.upsert_integration("plaid", &settings)
+    }
+
+    /// Start connecting a bank through GoCardless, returning the link where
+    /// the user gives consent
+    ///
+    /// Each bank is its own requisition. Connecting another bank adds it next
+    /// to those already connected; connecting one again (to renew access that
+    /// is expiring) replaces its old requisition, and sync carries its
+    /// accounts over by IBAN once the user has given consent.
+    ///
+    /// # Arguments
+    /// * `secret_id`, `secret_key` - User secrets from the GoCardless portal
+    /// * `institution_id` - The bank, from `list_gocardless_institutions`
+    /// * `redirect_url` - Where the bank sends the user afterwards (None = Treeline's site)
+    /// * `base_url` - Optional custom base URL for testing (None = production)
+    #[cfg(feature = "sync-gocardless")]
+    pub fn setup_gocardless(
+        &self,
+        secret_id: &str,
+        secret_key: &str,
+        institution_id: &str,
+        redirect_url: Option<&str>,
+        base_url: Option<&str>,
+    ) -> Result<String> {
+        let mut options = serde_json::json!({
+            "secretId": secret_id,
+            "secretKey": secret_key,
+            "institutionId": institution_id,
+        });
+        if let Some(url) = redirect_url {
+            options["redirect"] = serde_json::json!(url);
+        }
+        if let Some(url) = base_url {
+            options["baseUrl"] = serde_json::json!(url);
+        }
+
+        let provider = self
+            .integration_providers
+            .get("gocardless")
+            .ok_or_else(|| anyhow::anyhow!("Unknown provider: gocardless"))?;
+        let mut settings = provider.setup(&options)?;
+        let connected = settings["requisitions"][0].clone();
+
+        if let Some(current) = self
+            .repository
+            .get_integrations()?
+            .into_iter()
+            .find(|i| i.name == "gocardless")
+        {
+            let mut requisitions: Vec<serde_json::Value> = current.settings["requisitions"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default();
+            requisitions.retain(|r| r["institutionId"] != connected["institutionId"]);
+            requisitions.push(connected.clone());
+            settings["requisitions"] = requisitions.into();
+            if let Some(account_settings) = current.settings.get("accountSettings") {
+                settings["accountSettings"] = account_settings.clone();
+            }
+        }
+        self.repository
+            .upsert_integration("gocardless", &settings)?;
+
+        Ok(connected["link"].as_str().unwrap_or_default().to_string())
+    }
+
+    /// Banks GoCardless can connect to in a country (ISO 3166 code, e.g. "DE")
+    #[cfg(feature = "sync-gocardless")]
+    pub fn list_gocardless_institutions(
+        &self,
+        secret_id: &str,
+        secret_key: &str,
+        country: &str,
+        base_url: Option<&str>,
+    ) -> Result<Vec<GoCardlessInstitution>> {
+        let client = GoCardlessClient::connect(
+            secret_id,
+            secret_key,
+            base_url.unwrap_or(gocardless::GOCARDLESS_API_URL),
+        )?;
+        client.list_institutions(country)
+    }
+
+    /// Create a link token for opening Plaid Link, whose public token
+    /// `setup_plaid` then takes
+    #[cfg(feature = "sync-plaid")]
+    pub fn create_plaid_link_token(
+        &self,
+        client_id: &str,
+        secret: &str,
+        environment: &str,
+        base_url: Option<&str>,
+    ) -> Result<String> {
+        let base_url = match base_url {
+            Some(url) => url,
+            None => plaid::base_url_for(environment)?,
+        };
+        let client = PlaidClient::new(client_id, secret, base_url)?;
+        // Plaid wants an ID for the user that isn't personal information
+        client.create_link_token(&Uuid::new_v4().to_string())
+    }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct SyncResult {
     pub results: Vec<IntegrationSyncResult>,
     pub new_accounts_without_type: Vec<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct IntegrationSyncResult {
     pub integration: String,
     pub accounts_synced: i64,
+    /// Investment positions stored, for providers that report them
+    pub holdings_synced: i64,
     pub transactions_synced: i64,
     pub transaction_stats: TransactionStats,
     pub sync_type: String,
     pub start_date: String,
     pub end_date: String,
     pub provider_warnings: Vec<String>,
+    /// Accounts whose transactions couldn't be fetched; the rest synced
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failed_accounts: Vec<AccountSyncFailure>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
     /// Auto-tag rules that failed (if any)
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub auto_tag_failures: Vec<crate::services::tag::RuleFailure>,
+    /// New transactions per internal account ID (recorded in sync history)
+    #[serde(skip)]
+    pub new_by_account: HashMap<Uuid, i64>,
 }
 
-#[derive(Debug, Serialize)]
+/// An account left out of an otherwise successful sync
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct AccountSyncFailure {
+    /// Internal account ID, once the account has been synced before
+    pub account_id: Option<String>,
+    /// The provider's account ID
+    pub external_id: String,
+    pub account_name: String,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct TransactionStats {
     pub discovered: i64,
     pub new: i64,
+    /// Pending transactions brought up to date, including ones that posted
+    pub updated: i64,
     pub skipped: i64,
 }
 
+/// Sync freshness for one account, derived from sync history
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct AccountSyncActivity {
+    pub account_id: String,
+    pub account_name: String,
+    pub integration: String,
+    /// Start of the last successful sync of the account's integration
+    pub last_synced_at: Option<String>,
+    /// Start of the last sync that brought in new transactions for this account
+    pub last_new_data_at: Option<String>,
+    /// How many new transactions that sync brought in
+    pub last_new_transactions: i64,
+    /// Error from the integration's most recent sync, if it failed
+    pub last_error: Option<String>,
+}
+
+/// An account at an integration's provider
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct RemoteAccount {
+    /// The provider's account ID (Lunchflow, SimpleFIN, Plaid, GoCardless,
+    /// Coinbase wallet or Kraken asset ID)
+    pub external_id: String,
+    pub name: String,
+    pub institution_name: Option<String>,
+    pub currency: String,
+    /// Connection status reported by the provider, e.g. "ACTIVE" or "DISCONNECTED" (Lunchflow)
+    pub status: Option<String>,
+    /// Treeline account it syncs into, once synced
+    pub account_id: Option<String>,
+    /// Whether sync includes this account
+    pub enabled: bool,
+    /// Whether sync fetches only this account's balance
+    pub balances_only: bool,
+}
+
+/// Setup options for a crypto exchange's API key
+fn exchange_options(
+    api_key: &str,
+    api_secret: &str,
+    currency: Option<&str>,
+    base_url: Option<&str>,
+) -> serde_json::Value {
+    let mut options = serde_json::json!({
+        "apiKey": api_key,
+        "apiSecret": api_secret,
+    });
+    if let Some(currency) = currency {
+        options["currency"] = serde_json::json!(currency);
+    }
+    if let Some(url) = base_url {
+        options["baseUrl"] = serde_json::json!(url);
+    }
+    options
+}
+
+/// The provider account ID sync matches an account by
+fn external_id(provider: &str, account: &Account) -> Option<String> {
+    match provider {
+        // Demo mode: use the account name as the external ID (stable across syncs)
+        "demo" => Some(account.name.clone()),
+        _ => account.provider.external_id(provider).map(String::from),
+    }
+}
+
+/// What identifies an account across provider ID changes, for providers
+/// whose IDs change
+fn stable_key(provider: &str, account: &Account) -> Option<String> {
+    match provider {
+        "gocardless" => account
+            .provider
+            .metadata_str("gocardless", "iban")
+            .map(String::from),
+        _ => None,
+    }
+}
+
+/// A boolean from an account's entry in the integration's accountSettings
+fn account_flag(settings: &serde_json::Value, external_id: &str, key: &str) -> Option<bool> {
+    settings
+        .get("accountSettings")?
+        .get(external_id)?
+        .get(key)?
+        .as_bool()
+}
+
+/// Accounts sync unless turned off
+fn account_enabled(settings: &serde_json::Value, external_id: &str) -> bool {
+    account_flag(settings, external_id, "enabled").unwrap_or(true)
+}
+
+/// Index of the pending transaction that `posted` most likely settles
+///
+/// A candidate is in the same account, dated from a day before `posted` to
+/// PENDING_POST_DAYS after, with the same sign and an amount within a
+/// quarter of it (tips, fuel holds). The closest amount wins, then the
+/// closest date.
+fn pending_match(
+    posted: &crate::domain::Transaction,
+    candidates: &[crate::domain::Transaction],
+) -> Option<usize> {
+    candidates
+        .iter()
+        .enumerate()
+        .filter(|(_, pending)| {
+            let days = (posted.transaction_date - pending.transaction_date).num_days();
+            pending.account_id == posted.account_id
+                && (-1..=PENDING_POST_DAYS).contains(&days)
+                && pending.amount.is_sign_negative() == posted.amount.is_sign_negative()
+                && (posted.amount - pending.amount).abs() * Decimal::from(4) <= pending.amount.abs()
+        })
+        .min_by_key(|(_, pending)| {
+            (
+                (posted.amount - pending.amount).abs(),
+                (posted.transaction_date - pending.transaction_date)
+                    .num_days()
+                    .abs(),
+            )
+        })
+        .map(|(index, _)| index)
+}
+
+/// Date an account's transactions were last fetched through, from the
+/// integration's `syncedThrough` cursors
+fn synced_through(settings: &serde_json::Value, external_id: &str) -> Option<NaiveDate> {
+    let date = settings.get("syncedThrough")?.get(external_id)?.as_str()?;
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+/// Where a running sync is
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncProgress {
+    pub integration: String,
+    /// Integrations finished before this one, and how many the sync covers
+    pub integrations_done: usize,
+    pub integrations_total: usize,
+    pub stage: SyncStage,
+    /// Account whose transactions were just saved (Saving stage)
+    pub account_id: Option<String>,
+    pub account_name: Option<String>,
+    /// Accounts of this integration saved so far, of those with transactions
+    pub accounts_done: usize,
+    pub accounts_total: usize,
+    /// Transactions fetched for the account (for the integration when Finished)
+    pub fetched: i64,
+    /// Of those, new ones saved
+    pub inserted: i64,
+}
+
+impl SyncProgress {
+    fn new(integration: &str, integrations_done: usize, integrations_total: usize) -> Self {
+        Self {
+            integration: integration.to_string(),
+            integrations_done,
+            integrations_total,
+            stage: SyncStage::Accounts,
+            account_id: None,
+            account_name: None,
+            accounts_done: 0,
+            accounts_total: 0,
+            fetched: 0,
+            inserted: 0,
+        }
+    }
+
+    fn at(&self, stage: SyncStage) -> Self {
+        Self {
+            stage,
+            ..self.clone()
+        }
+    }
+
+    /// Share of the whole sync done, 0.0 to 1.0; each integration gets an
+    /// equal part, most of it for fetching and saving transactions
+    pub fn fraction(&self) -> f64 {
+        let within = match self.stage {
+            SyncStage::Accounts => 0.0,
+            SyncStage::Fetching => 0.2,
+            SyncStage::Saving => {
+                let saved = self.accounts_done as f64 / self.accounts_total.max(1) as f64;
+                0.5 + 0.5 * saved
+            }
+            SyncStage::Finished => 1.0,
+        };
+        let total = self.integrations_total.max(1) as f64;
+        ((self.integrations_done as f64 + within) / total).min(1.0)
+    }
+}
+
+/// Steps of one integration's sync, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncStage {
+    /// Fetching accounts and balances
+    Accounts,
+    /// Waiting for the provider's transactions
+    Fetching,
+    /// Saving one account's new transactions
+    Saving,
+    Finished,
+}
+
+impl SyncStage {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SyncStage::Accounts => "Fetching accounts",
+            SyncStage::Fetching => "Fetching transactions",
+            SyncStage::Saving => "Saving transactions",
+            SyncStage::Finished => "Done",
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct IntegrationInfo {
     pub name: String,
@@ -461,7 +1480,6 @@ pub struct IntegrationInfo {
 mod tests {
     use super::*;
     use crate::domain::Transaction;
-    use chrono::NaiveDate;
     use rust_decimal::Decimal;
 
     #[test]
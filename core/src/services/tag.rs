@@ -3,6 +3,7 @@
 use std::sync::Arc;
 
 use anyhow::Result;
+use schemars::JsonSchema;
 use serde::Serialize;
 use uuid::Uuid;
 
@@ -22,13 +23,15 @@ impl TagService {
     ///
     /// This fetches all enabled rules and applies matching tags to the given transactions.
     /// Rules are additive - they only add tags, never remove existing ones.
-    /// All matching rules apply (not first-match-wins).
+    /// All matching rules apply (not first-match-wins). A rule can also
+    /// exclude what it matches from reports, with or without tags.
     pub fn apply_auto_tag_rules(&self, tx_ids: &[Uuid]) -> Result<AutoTagResult> {
         if tx_ids.is_empty() {
             return Ok(AutoTagResult {
                 rules_evaluated: 0,
                 rules_matched: 0,
                 transactions_tagged: 0,
+                transactions_excluded: 0,
                 failed_rules: Vec::new(),
             });
         }
@@ -41,33 +44,44 @@ impl TagService {
                 rules_evaluated: 0,
                 rules_matched: 0,
                 transactions_tagged: 0,
+                transactions_excluded: 0,
                 failed_rules: Vec::new(),
             });
         }
 
         let mut rules_matched = 0;
         let mut transactions_tagged_set = std::collections::HashSet::new();
+        let mut transactions_excluded = 0;
         let mut failed_rules = Vec::new();
 
         // For each rule, find matching transactions and apply tags in bulk
         // Each rule uses a single DB connection for both matching and updating
         for rule in &rules {
-            // Skip rules with no tags to apply
-            if rule.tags.is_empty() {
+            // Skip rules with nothing to apply
+            if rule.tags.is_empty() && !rule.exclude_from_reports {
                 continue;
             }
 
             // Find matching transactions and apply tags in a single DB connection
-            match self.repository.bulk_apply_tags_to_matching(
-                tx_ids,
-                &rule.sql_condition,
-                &rule.tags,
-            ) {
-                Ok(modified_ids) => {
-                    if !modified_ids.is_empty() {
+            let applied = self
+                .repository
+                .bulk_apply_tags_to_matching(tx_ids, &rule.sql_condition, &rule.tags)
+                .and_then(|tagged| {
+                    let excluded = if rule.exclude_from_reports {
+                        self.repository
+                            .exclude_matching_from_reports(tx_ids, &rule.sql_condition)?
+                    } else {
+                        Vec::new()
+                    };
+                    Ok((tagged, excluded))
+                });
+            match applied {
+                Ok((tagged_ids, excluded_ids)) => {
+                    if !tagged_ids.is_empty() || !excluded_ids.is_empty() {
                         rules_matched += 1;
                     }
-                    for id in modified_ids {
+                    transactions_excluded += excluded_ids.len() as i64;
+                    for id in tagged_ids {
                         transactions_tagged_set.insert(id);
                     }
                 }
@@ -88,6 +102,7 @@ impl TagService {
             rules_evaluated: rules.len() as i64,
             rules_matched,
             transactions_tagged: transactions_tagged_set.len() as i64,
+            transactions_excluded,
             failed_rules,
         })
     }
@@ -168,7 +183,7 @@ impl TagService {
 }
 
 /// Result structure matching Python CLI output
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct TagResult {
     pub succeeded: i64,
     pub failed: i64,
@@ -176,7 +191,7 @@ pub struct TagResult {
 }
 
 /// Individual transaction result entry
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct TagResultEntry {
     pub transaction_id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -195,13 +210,15 @@ pub struct AutoTagResult {
     pub rules_matched: i64,
     /// Number of transactions that had tags applied
     pub transactions_tagged: i64,
+    /// Number of transactions newly excluded from reports
+    pub transactions_excluded: i64,
     /// Rules that failed to apply (with error messages)
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub failed_rules: Vec<RuleFailure>,
 }
 
 /// Information about a failed rule application
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, JsonSchema)]
 pub struct RuleFailure {
     /// Rule ID
     pub rule_id: String,
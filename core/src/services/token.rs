@@ -0,0 +1,337 @@
+//! Token service - scoped access tokens for external scripts
+//!
+//! Tokens are stored in `tokens.json` in the treeline directory. A token
+//! looks like `tlt_<id>_<secret>`; only a SHA-256 hash of the secret is
+//! written to disk. For encrypted databases the database key is stored
+//! wrapped with a key derived from the secret, so the token alone can open
+//! the database and revoking it (deleting the wrapped copy) cuts access.
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use rand::RngCore;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+use crate::adapters::credentials::CredentialCipher;
+use crate::domain::{AccessToken, EncryptionKey, TokenScope};
+use crate::json_file::{read_json, write_json};
+
+const TOKEN_PREFIX: &str = "tlt";
+
+/// Domain separation for the key that wraps the database key
+const WRAP_KEY_CONTEXT: &[u8] = b"treeline-token-wrap-v1";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredToken {
+    #[serde(flatten)]
+    token: AccessToken,
+    /// Hex SHA-256 of the secret part
+    secret_hash: String,
+    /// Database key sealed with the secret-derived key (encrypted databases only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    wrapped_key: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TokenFile {
+    #[serde(default)]
+    tokens: Vec<StoredToken>,
+}
+
+/// A freshly issued token; `token` is shown once and never stored
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct IssuedToken {
+    pub token: String,
+    #[serde(flatten)]
+    pub info: AccessToken,
+}
+
+/// What a valid token grants
+#[derive(Debug)]
+pub struct TokenGrant {
+    pub token_id: String,
+    pub scope: TokenScope,
+    /// Database key, when the database is encrypted
+    pub encryption_key: Option<EncryptionKey>,
+}
+
+/// Token service for issuing, checking and revoking access tokens
+pub struct TokenService {
+    treeline_dir: PathBuf,
+}
+
+impl TokenService {
+    pub fn new(treeline_dir: PathBuf) -> Self {
+        Self { treeline_dir }
+    }
+
+    fn tokens_file(&self) -> PathBuf {
+        self.treeline_dir.join("tokens.json")
+    }
+
+    /// A corrupted tokens.json reads as empty: every token stops working
+    /// until reissued, rather than failing open
+    fn load(&self) -> Result<TokenFile> {
+        Ok(read_json(&self.tokens_file())?.unwrap_or_default())
+    }
+
+    fn save(&self, file: &TokenFile) -> Result<()> {
+        fs::create_dir_all(&self.treeline_dir)?;
+        write_json(&self.tokens_file(), file)
+    }
+
+    /// Issue a new token
+    ///
+    /// `encryption_key` must be the database key when the database is
+    /// encrypted, otherwise the token can't open it.
+    pub fn issue(
+        &self,
+        name: Option<&str>,
+        scope: TokenScope,
+        ttl: Option<Duration>,
+        encryption_key: Option<&EncryptionKey>,
+    ) -> Result<IssuedToken> {
+        let mut id_bytes = [0u8; 6];
+        rand::thread_rng().fill_bytes(&mut id_bytes);
+        let id = hex::encode(id_bytes);
+
+        let mut secret_bytes = Zeroizing::new([0u8; 32]);
+        rand::thread_rng().fill_bytes(&mut *secret_bytes);
+        let secret = Zeroizing::new(hex::encode(&*secret_bytes));
+
+        let wrapped_key = encryption_key
+            .map(|key| wrap_cipher(&secret).seal(key.expose()))
+            .transpose()?;
+
+        let now = Utc::now();
+        let info = AccessToken {
+            id: id.clone(),
+            name: name.map(str::to_string),
+            scope,
+            created_at: now,
+            expires_at: ttl.map(|ttl| now + ttl),
+            revoked_at: None,
+            last_used_at: None,
+            use_count: 0,
+        };
+
+        let mut file = self.load()?;
+        file.tokens.push(StoredToken {
+            token: info.clone(),
+            secret_hash: hash_secret(&secret),
+            wrapped_key,
+        });
+        self.save(&file)?;
+
+        Ok(IssuedToken {
+            token: format!("{}_{}_{}", TOKEN_PREFIX, id, secret.as_str()),
+            info,
+        })
+    }
+
+    /// All tokens, newest first
+    pub fn list(&self) -> Result<Vec<AccessToken>> {
+        let mut tokens: Vec<AccessToken> =
+            self.load()?.tokens.into_iter().map(|t| t.token).collect();
+        tokens.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(tokens)
+    }
+
+    /// Revoke a token by id; the wrapped database key is deleted with it
+    pub fn revoke(&self, id: &str) -> Result<AccessToken> {
+        let mut file = self.load()?;
+        let stored = file
+            .tokens
+            .iter_mut()
+            .find(|t| t.token.id == id)
+            .ok_or_else(|| anyhow::anyhow!("Token not found: {}", id))?;
+
+        if stored.token.revoked_at.is_none() {
+            stored.token.revoked_at = Some(Utc::now());
+        }
+        stored.wrapped_key = None;
+        let revoked = stored.token.clone();
+        self.save(&file)?;
+        Ok(revoked)
+    }
+
+    /// Check a token and record its use
+    ///
+    /// Fails for unknown, expired or revoked tokens, and for a wrong secret.
+    pub fn authenticate(&self, token: &str) -> Result<TokenGrant> {
+        let (id, secret) = parse_token(token)?;
+        let now = Utc::now();
+
+        let mut file = self.load()?;
+        let stored = file
+            .tokens
+            .iter_mut()
+            .find(|t| t.token.id == id)
+            .ok_or_else(|| anyhow::anyhow!("Invalid token"))?;
+
+        // Constant time, so response timing doesn't leak how much of it matched
+        let matches = stored
+            .secret_hash
+            .as_bytes()
+            .ct_eq(hash_secret(secret).as_bytes());
+        if !bool::from(matches) {
+            anyhow::bail!("Invalid token");
+        }
+        if !stored.token.is_active(now) {
+            anyhow::bail!("Token {} is {}", id, stored.token.status(now));
+        }
+
+        let encryption_key = stored
+            .wrapped_key
+            .as_deref()
+            .map(|wrapped| wrap_cipher(secret).open(wrapped))
+            .transpose()?
+            .map(EncryptionKey::new);
+
+        stored.token.last_used_at = Some(now);
+        stored.token.use_count += 1;
+        let grant = TokenGrant {
+            token_id: stored.token.id.clone(),
+            scope: stored.token.scope,
+            encryption_key,
+        };
+        self.save(&file)?;
+
+        Ok(grant)
+    }
+}
+
+/// Parse a TTL like "90s", "30m", "1h", "7d" or "2w"
+pub fn parse_ttl(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid TTL '{}': expected e.g. 30m, 1h, 7d", value))?;
+    if amount <= 0 {
+        anyhow::bail!("TTL must be positive");
+    }
+
+    match unit {
+        "s" => Ok(Duration::seconds(amount)),
+        "m" => Ok(Duration::minutes(amount)),
+        "h" | "" => Ok(Duration::hours(amount)),
+        "d" => Ok(Duration::days(amount)),
+        "w" => Ok(Duration::weeks(amount)),
+        _ => anyhow::bail!("Invalid TTL unit '{}': use s, m, h, d or w", unit),
+    }
+}
+
+fn parse_token(token: &str) -> Result<(&str, &str)> {
+    let mut parts = token.trim().splitn(3, '_');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(TOKEN_PREFIX), Some(id), Some(secret)) if !id.is_empty() && !secret.is_empty() => {
+            Ok((id, secret))
+        }
+        _ => anyhow::bail!("Invalid token format"),
+    }
+}
+
+fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+fn wrap_cipher(secret: &str) -> CredentialCipher {
+    let mut hasher = Sha256::new();
+    hasher.update(WRAP_KEY_CONTEXT);
+    hasher.update(secret.as_bytes());
+    let mut key = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(&hasher.finalize());
+    CredentialCipher::new(&key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service() -> (tempfile::TempDir, TokenService) {
+        let dir = tempfile::tempdir().unwrap();
+        let service = TokenService::new(dir.path().to_path_buf());
+        (dir, service)
+    }
+
+    #[test]
+    fn test_parse_ttl() {
+        assert_eq!(parse_ttl("1h").unwrap(), Duration::hours(1));
+        assert_eq!(parse_ttl("30m").unwrap(), Duration::minutes(30));
+        assert_eq!(parse_ttl("7d").unwrap(), Duration::days(7));
+        assert_eq!(parse_ttl("2").unwrap(), Duration::hours(2));
+        assert!(parse_ttl("0h").is_err());
+        assert!(parse_ttl("1y").is_err());
+        assert!(parse_ttl("soon").is_err());
+    }
+
+    #[test]
+    fn test_issue_and_authenticate() {
+        let (_dir, service) = service();
+        let key = EncryptionKey::from_bytes(&[0x42; 32]);
+        let issued = service
+            .issue(
+                Some("notebook"),
+                TokenScope::ReadOnly,
+                Some(Duration::hours(1)),
+                Some(&key),
+            )
+            .unwrap();
+
+        let grant = service.authenticate(&issued.token).unwrap();
+        assert_eq!(grant.scope, TokenScope::ReadOnly);
+        assert_eq!(grant.encryption_key, Some(key));
+
+        let listed = service.list().unwrap();
+        assert_eq!(listed[0].use_count, 1);
+        assert!(listed[0].last_used_at.is_some());
+
+        // The secret never reaches disk
+        let on_disk = std::fs::read_to_string(service.tokens_file()).unwrap();
+        let secret = issued.token.rsplit('_').next().unwrap();
+        assert!(!on_disk.contains(secret));
+    }
+
+    #[test]
+    fn test_wrong_secret_is_rejected() {
+        let (_dir, service) = service();
+        let issued = service
+            .issue(None, TokenScope::ReadWrite, None, None)
+            .unwrap();
+        let forged = format!("tlt_{}_{}", issued.info.id, "00".repeat(32));
+        assert!(service.authenticate(&forged).is_err());
+        assert!(service.authenticate("not-a-token").is_err());
+    }
+
+    #[test]
+    fn test_revoked_and_expired_tokens_are_rejected() {
+        let (_dir, service) = service();
+        let issued = service
+            .issue(None, TokenScope::ReadOnly, None, None)
+            .unwrap();
+        service.revoke(&issued.info.id).unwrap();
+        let err = service.authenticate(&issued.token).unwrap_err();
+        assert!(err.to_string().contains("revoked"));
+
+        let expired = service
+            .issue(
+                None,
+                TokenScope::ReadOnly,
+                Some(Duration::seconds(-1)),
+                None,
+            )
+            .unwrap();
+        let err = service.authenticate(&expired.token).unwrap_err();
+        assert!(err.to_string().contains("expired"));
+    }
+}
@@ -0,0 +1,276 @@
+//! Tool service - curated read-only queries for AI assistants
+//!
+//! Backs `tl mcp`. Each tool is a fixed, parameterized SELECT run on a
+//! read-only connection, so an assistant can answer questions about the
+//! data without being handed arbitrary SQL. Results only include the
+//! columns listed here; provider IDs and raw sync payloads never leave.
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use schemars::{schema_for, JsonSchema};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::adapters::duckdb::{DuckDbRepository, QueryResult};
+
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+const MAX_SEARCH_LIMIT: usize = 200;
+
+/// A tool an assistant can call
+pub struct ToolDefinition {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// JSON Schema of the arguments object
+    pub input_schema: serde_json::Value,
+}
+
+impl ToolDefinition {
+    fn new<T: JsonSchema>(name: &'static str, description: &'static str) -> Self {
+        Self {
+            name,
+            description,
+            input_schema: serde_json::to_value(schema_for!(T)).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct ListAccountsArgs {}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct SpendingByCategoryArgs {
+    /// First day to include (YYYY-MM-DD)
+    start_date: Option<NaiveDate>,
+    /// Last day to include (YYYY-MM-DD)
+    end_date: Option<NaiveDate>,
+    /// Only this account (name or ID)
+    account: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct SearchTransactionsArgs {
+    /// Text the description must contain (case-insensitive)
+    text: Option<String>,
+    /// Only transactions with this tag
+    tag: Option<String>,
+    /// Only this account (name or ID)
+    account: Option<String>,
+    /// First day to include (YYYY-MM-DD)
+    start_date: Option<NaiveDate>,
+    /// Last day to include (YYYY-MM-DD)
+    end_date: Option<NaiveDate>,
+    /// Smallest amount; spending is negative
+    min_amount: Option<f64>,
+    /// Largest amount; spending is negative
+    max_amount: Option<f64>,
+    /// Maximum rows to return (default 50, at most 200)
+    limit: Option<usize>,
+}
+
+/// WHERE clause built from optional filters, with its bound parameters
+#[derive(Default)]
+pub(super) struct Filters {
+    conditions: Vec<&'static str>,
+    pub(super) params: Vec<serde_json::Value>,
+}
+
+impl Filters {
+    pub(super) fn push(&mut self, condition: &'static str, params: &[serde_json::Value]) {
+        self.conditions.push(condition);
+        self.params.extend_from_slice(params);
+    }
+
+    pub(super) fn date_range(&mut self, start: Option<NaiveDate>, end: Option<NaiveDate>) {
+        if let Some(start) = start {
+            self.push("transaction_date >= ?::DATE", &[start.to_string().into()]);
+        }
+        if let Some(end) = end {
+            self.push("transaction_date <= ?::DATE", &[end.to_string().into()]);
+        }
+    }
+
+    pub(super) fn account(&mut self, account: Option<String>) {
+        if let Some(account) = account {
+            self.push(
+                "(account_id = ? OR account_name ILIKE ?)",
+                &[account.clone().into(), account.into()],
+            );
+        }
+    }
+
+    pub(super) fn where_clause(&self) -> String {
+        if self.conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", self.conditions.join(" AND "))
+        }
+    }
+}
+
+/// Tool service for the curated read-only tools
+pub struct ToolService {
+    repository: Arc<DuckDbRepository>,
+}
+
+impl ToolService {
+    pub fn new(repository: Arc<DuckDbRepository>) -> Self {
+        Self { repository }
+    }
+
+    /// All available tools
+    pub fn definitions() -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::new::<ListAccountsArgs>(
+                "list_accounts",
+                "List accounts with their type, currency, current balance and institution.",
+            ),
+            ToolDefinition::new::<SpendingByCategoryArgs>(
+                "spending_by_category",
+                "Total spending per tag over a date range. A transaction with several tags \
+                 counts toward each of them; untagged spending is reported as '(untagged)'.",
+            ),
+            ToolDefinition::new::<SearchTransactionsArgs>(
+                "search_transactions",
+                "Find transactions by description text, tag, account, date range or amount, \
+                 newest first.",
+            ),
+        ]
+    }
+
+    /// Run a tool with a JSON arguments object
+    pub fn call(&self, name: &str, arguments: serde_json::Value) -> Result<QueryResult> {
+        match name {
+            "list_accounts" => {
+                let ListAccountsArgs {} = parse_args(name, arguments)?;
+                self.list_accounts()
+            }
+            "spending_by_category" => self.spending_by_category(parse_args(name, arguments)?),
+            "search_transactions" => self.search_transactions(parse_args(name, arguments)?),
+            _ => anyhow::bail!("Unknown tool: {}", name),
+        }
+    }
+
+    fn list_accounts(&self) -> Result<QueryResult> {
+        self.repository.execute_query_readonly_with_params(
+            "SELECT account_id, COALESCE(nickname, name) AS name, account_type, classification, \
+             currency, balance, institution_name \
+             FROM accounts ORDER BY name",
+            &[],
+        )
+    }
+
+    fn spending_by_category(&self, args: SpendingByCategoryArgs) -> Result<QueryResult> {
+        let mut filters = Filters::default();
+        filters.push("amount < 0", &[]);
+        filters.date_range(args.start_date, args.end_date);
+        filters.account(args.account);
+
+        let sql = format!(
+            "SELECT category, SUM(-amount) AS spent, COUNT(*) AS transactions \
+             FROM ( \
+                 SELECT amount, UNNEST(CASE WHEN tags IS NULL OR len(tags) = 0 \
+                     THEN ['(untagged)'] ELSE tags END) AS category \
+                 FROM transactions {} \
+             ) \
+             GROUP BY category ORDER BY spent DESC",
+            filters.where_clause()
+        );
+        self.repository
+            .execute_query_readonly_with_params(&sql, &filters.params)
+    }
+
+    fn search_transactions(&self, args: SearchTransactionsArgs) -> Result<QueryResult> {
+        let mut filters = Filters::default();
+        if let Some(text) = args.text {
+            filters.push("contains(lower(description), lower(?))", &[text.into()]);
+        }
+        if let Some(tag) = args.tag {
+            filters.push("list_contains(tags, ?)", &[tag.into()]);
+        }
+        filters.account(args.account);
+        filters.date_range(args.start_date, args.end_date);
+        if let Some(min) = args.min_amount {
+            filters.push("amount >= ?", &[min.into()]);
+        }
+        if let Some(max) = args.max_amount {
+            filters.push("amount <= ?", &[max.into()]);
+        }
+        let limit = args
+            .limit
+            .unwrap_or(DEFAULT_SEARCH_LIMIT)
+            .clamp(1, MAX_SEARCH_LIMIT);
+
+        let sql = format!(
+            "SELECT transaction_id, transaction_date, description, amount, currency, tags, \
+             account_name \
+             FROM transactions {} \
+             ORDER BY transaction_date DESC, transaction_id \
+             LIMIT {}",
+            filters.where_clause(),
+            limit
+        );
+        self.repository
+            .execute_query_readonly_with_params(&sql, &filters.params)
+    }
+}
+
+/// Parse a tool's arguments; a missing arguments object means no arguments
+fn parse_args<T: DeserializeOwned>(tool: &str, arguments: serde_json::Value) -> Result<T> {
+    let arguments = if arguments.is_null() {
+        serde_json::json!({})
+    } else {
+        arguments
+    };
+    serde_json::from_value(arguments).with_context(|| format!("Invalid arguments for {}", tool))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_definitions_have_object_schemas() {
+        let definitions = ToolService::definitions();
+        assert_eq!(definitions.len(), 3);
+        for definition in &definitions {
+            assert_eq!(definition.input_schema["type"], "object");
+        }
+        let search = &definitions[2].input_schema;
+        assert_eq!(search["properties"]["start_date"]["format"], "date");
+    }
+
+    #[test]
+    fn test_parse_args_rejects_unknown_fields_and_bad_dates() {
+        let args: SearchTransactionsArgs =
+            parse_args("search_transactions", serde_json::Value::Null).unwrap();
+        assert!(args.text.is_none());
+
+        assert!(parse_args::<SearchTransactionsArgs>(
+            "search_transactions",
+            serde_json::json!({"sql": "DROP TABLE sys_transactions"}),
+        )
+        .is_err());
+        assert!(parse_args::<SpendingByCategoryArgs>(
+            "spending_by_category",
+            serde_json::json!({"start_date": "2024-01-01'; --"}),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_filters_bind_values() {
+        let mut filters = Filters::default();
+        filters.date_range(NaiveDate::from_ymd_opt(2024, 1, 1), None);
+        filters.account(Some("Checking".to_string()));
+        assert_eq!(
+            filters.where_clause(),
+            "WHERE transaction_date >= ?::DATE AND (account_id = ? OR account_name ILIKE ?)"
+        );
+        assert_eq!(filters.params.len(), 3);
+    }
+}
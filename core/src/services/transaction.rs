@@ -0,0 +1,379 @@
+//! Transaction service - find, edit and delete transactions
+//!
+//! Deletes are soft: the row keeps its data with `deleted_at` set, the same
+//! as deleting in the app, so sync and CSV re-imports still recognise it and
+//! don't bring it back.
+//!
+//! Excluding a transaction from reports keeps it in the ledger and the
+//! account's balance; only reports, budgets and income detection skip it.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::adapters::duckdb::DuckDbRepository;
+use crate::domain::Transaction;
+
+/// Which transactions to list; every set field must match
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TransactionFilter {
+    pub account_id: Option<String>,
+    /// First transaction date (inclusive)
+    pub start: Option<NaiveDate>,
+    /// Last transaction date (inclusive)
+    pub end: Option<NaiveDate>,
+    /// Has this tag (case-insensitive)
+    pub tag: Option<String>,
+    /// Has no tags at all
+    #[serde(default)]
+    pub untagged: bool,
+    pub min_amount: Option<Decimal>,
+    pub max_amount: Option<Decimal>,
+    /// Description contains this text (case-insensitive)
+    pub text: Option<String>,
+    /// Excluded from reports (true) or counted in them (false)
+    pub excluded: Option<bool>,
+    /// Newest first, at most this many
+    pub limit: Option<usize>,
+}
+
+impl TransactionFilter {
+    fn matches(&self, tx: &Transaction) -> bool {
+        let description = tx.description.as_deref().unwrap_or_default().to_lowercase();
+        self.account_id
+            .as_ref()
+            .is_none_or(|id| tx.account_id.to_string() == *id)
+            && self.start.is_none_or(|start| tx.transaction_date >= start)
+            && self.end.is_none_or(|end| tx.transaction_date <= end)
+            && self
+                .tag
+                .as_ref()
+                .is_none_or(|tag| tx.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+            && (!self.untagged || tx.tags.is_empty())
+            && self.min_amount.is_none_or(|min| tx.amount >= min)
+            && self.max_amount.is_none_or(|max| tx.amount <= max)
+            && self
+                .text
+                .as_ref()
+                .is_none_or(|text| description.contains(&text.to_lowercase()))
+            && self
+                .excluded
+                .is_none_or(|excluded| tx.excluded_from_reports == excluded)
+    }
+}
+
+/// Changes to make to one transaction; unset fields are left alone
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TransactionEdit {
+    pub description: Option<String>,
+    /// Replaces all tags
+    pub tags: Option<Vec<String>>,
+    pub amount: Option<Decimal>,
+    pub date: Option<NaiveDate>,
+    pub excluded_from_reports: Option<bool>,
+}
+
+impl TransactionEdit {
+    pub fn is_empty(&self) -> bool {
+        self.description.is_none()
+            && self.tags.is_none()
+            && self.amount.is_none()
+            && self.date.is_none()
+            && self.excluded_from_reports.is_none()
+    }
+}
+
+/// A transaction as shown by `tl tx`
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TransactionInfo {
+    pub id: String,
+    pub account_id: String,
+    pub account_name: String,
+    pub date: NaiveDate,
+    pub posted_date: NaiveDate,
+    pub description: Option<String>,
+    #[schemars(with = "String")]
+    pub amount: Decimal,
+    pub tags: Vec<String>,
+    /// Set on the parts of a split transaction
+    pub parent_id: Option<String>,
+    /// "simplefin", "lunchflow", "plaid", "gocardless", "coinbase", "csv_import", "split" or "manual"
+    pub source: String,
+    pub check_number: Option<String>,
+    pub reference: Option<String>,
+    pub excluded_from_reports: bool,
+    /// Not posted by the bank yet
+    pub pending: bool,
+}
+
+/// Transaction service for listing and editing individual transactions
+pub struct TransactionService {
+    repository: Arc<DuckDbRepository>,
+}
+
+impl TransactionService {
+    pub fn new(repository: Arc<DuckDbRepository>) -> Self {
+        Self { repository }
+    }
+
+    /// Matching transactions, newest first
+    pub fn list(&self, filter: &TransactionFilter) -> Result<Vec<TransactionInfo>> {
+        let names = self.account_names()?;
+        let mut transactions: Vec<_> = match &filter.account_id {
+            Some(id) => self.repository.get_transactions_by_account(id)?,
+            None => self.repository.get_transactions()?,
+        }
+        .into_iter()
+        .filter(|tx| filter.matches(tx))
+        .collect();
+        transactions.sort_by(|a, b| {
+            b.transaction_date
+                .cmp(&a.transaction_date)
+                .then_with(|| b.created_at.cmp(&a.created_at))
+        });
+        if let Some(limit) = filter.limit {
+            transactions.truncate(limit);
+        }
+        Ok(transactions
+            .into_iter()
+            .map(|tx| to_info(tx, &names))
+            .collect())
+    }
+
+    pub fn get(&self, tx_id: &str) -> Result<TransactionInfo> {
+        let tx = self.find(tx_id)?;
+        Ok(to_info(tx, &self.account_names()?))
+    }
+
+    /// Apply an edit and return the updated transaction
+    pub fn edit(&self, tx_id: &str, edit: &TransactionEdit) -> Result<TransactionInfo> {
+        if edit.is_empty() {
+            anyhow::bail!("Nothing to change");
+        }
+        let mut tx = self.find(tx_id)?;
+        if let Some(description) = &edit.description {
+            tx.description = Some(description.trim().to_string()).filter(|d| !d.is_empty());
+        }
+        if let Some(tags) = &edit.tags {
+            let mut cleaned: Vec<String> = Vec::new();
+            for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+                if !cleaned.iter().any(|c| c == tag) {
+                    cleaned.push(tag.to_string());
+                }
+            }
+            tx.tags = cleaned;
+        }
+        if let Some(amount) = edit.amount {
+            tx.amount = amount;
+        }
+        if let Some(date) = edit.date {
+            tx.transaction_date = date;
+        }
+        if let Some(excluded) = edit.excluded_from_reports {
+            tx.excluded_from_reports = excluded;
+        }
+        tx.updated_at = Utc::now();
+        self.repository.upsert_transaction(&tx)?;
+        self.get(tx_id)
+    }
+
+    /// Soft delete transactions, checking first that they all exist
+    pub fn delete(&self, tx_ids: &[String]) -> Result<usize> {
+        for tx_id in tx_ids {
+            self.find(tx_id)?;
+        }
+        for tx_id in tx_ids {
+            self.repository.delete_transaction(tx_id)?;
+        }
+        Ok(tx_ids.len())
+    }
+
+    /// Exclude transactions from reports, or count them again, checking
+    /// first that they all exist; returns how many changed
+    pub fn set_excluded(&self, tx_ids: &[String], excluded: bool) -> Result<usize> {
+        for tx_id in tx_ids {
+            self.find(tx_id)?;
+        }
+        self.repository.set_transactions_excluded(tx_ids, excluded)
+    }
+
+    fn find(&self, tx_id: &str) -> Result<Transaction> {
+        self.repository
+            .get_transaction_by_id(tx_id)?
+            .filter(|tx| tx.deleted_at.is_none())
+            .ok_or_else(|| anyhow::anyhow!("Transaction not found: {}", tx_id))
+    }
+
+    fn account_names(&self) -> Result<HashMap<String, String>> {
+        Ok(self
+            .repository
+            .get_accounts()?
+            .into_iter()
+            .map(|a| (a.id.to_string(), a.nickname.unwrap_or(a.name)))
+            .collect())
+    }
+}
+
+pub(crate) fn to_info(tx: Transaction, account_names: &HashMap<String, String>) -> TransactionInfo {
+    // Same order as the source column of the transactions view
+    let source = if let Some(provider) = tx.provider.source() {
+        provider
+    } else if tx.csv_batch_id.is_some() {
+        "csv_import"
+    } else if tx.parent_transaction_id.is_some() {
+        "split"
+    } else {
+        "manual"
+    };
+    let account_id = tx.account_id.to_string();
+    TransactionInfo {
+        id: tx.id.to_string(),
+        account_name: account_names.get(&account_id).cloned().unwrap_or_default(),
+        account_id,
+        date: tx.transaction_date,
+        posted_date: tx.posted_date,
+        description: tx.description,
+        amount: tx.amount,
+        tags: tx.tags,
+        parent_id: tx.parent_transaction_id.map(|id| id.to_string()),
+        source: source.to_string(),
+        check_number: tx.check_number,
+        reference: tx.reference,
+        excluded_from_reports: tx.excluded_from_reports,
+        pending: tx.pending,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Account;
+    use uuid::Uuid;
+
+    fn setup() -> (tempfile::TempDir, TransactionService, Vec<String>) {
+        let dir = tempfile::tempdir().unwrap();
+        let repository =
+            Arc::new(DuckDbRepository::new(&dir.path().join("treeline.duckdb"), None).unwrap());
+        repository.ensure_schema().unwrap();
+
+        let account_id = Uuid::new_v4();
+        repository
+            .upsert_account(&Account::new(account_id, "Checking"))
+            .unwrap();
+        let mut ids = Vec::new();
+        for (amount, day, description, tags) in [
+            ("-12.50", 1, "Coffee Shop", vec!["coffee"]),
+            ("-80.00", 5, "Grocery Store", vec![]),
+            ("2500.00", 15, "Payroll", vec!["income"]),
+        ] {
+            let date = NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+            let mut tx =
+                Transaction::new(Uuid::new_v4(), account_id, amount.parse().unwrap(), date);
+            tx.description = Some(description.to_string());
+            tx.tags = tags.into_iter().map(String::from).collect();
+            repository.upsert_transaction(&tx).unwrap();
+            ids.push(tx.id.to_string());
+        }
+        (dir, TransactionService::new(repository), ids)
+    }
+
+    #[test]
+    fn test_list_filters() {
+        let (_dir, service, ids) = setup();
+
+        let all = service.list(&TransactionFilter::default()).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].id, ids[2], "newest first");
+        assert_eq!(all[0].account_name, "Checking");
+
+        let spending = TransactionFilter {
+            max_amount: Some(Decimal::ZERO),
+            text: Some("store".to_string()),
+            ..Default::default()
+        };
+        let found = service.list(&spending).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, ids[1]);
+
+        let tagged = TransactionFilter {
+            tag: Some("Coffee".to_string()),
+            start: NaiveDate::from_ymd_opt(2025, 3, 1),
+            end: NaiveDate::from_ymd_opt(2025, 3, 1),
+            ..Default::default()
+        };
+        assert_eq!(service.list(&tagged).unwrap().len(), 1);
+
+        let untagged = TransactionFilter {
+            untagged: true,
+            ..Default::default()
+        };
+        assert_eq!(service.list(&untagged).unwrap()[0].id, ids[1]);
+    }
+
+    #[test]
+    fn test_edit_and_delete() {
+        let (_dir, service, ids) = setup();
+
+        let edit = TransactionEdit {
+            description: Some("Farmers Market".to_string()),
+            tags: Some(vec!["groceries".to_string(), " groceries ".to_string()]),
+            amount: Some("-75.25".parse().unwrap()),
+            ..Default::default()
+        };
+        let tx = service.edit(&ids[1], &edit).unwrap();
+        assert_eq!(tx.description.as_deref(), Some("Farmers Market"));
+        assert_eq!(tx.tags, ["groceries"]);
+        assert_eq!(tx.amount.to_string(), "-75.25");
+        assert!(service.edit(&ids[1], &TransactionEdit::default()).is_err());
+
+        // Nothing is deleted when one of the IDs is unknown
+        let unknown = vec![ids[0].clone(), Uuid::new_v4().to_string()];
+        assert!(service.delete(&unknown).is_err());
+        assert_eq!(
+            service.list(&TransactionFilter::default()).unwrap().len(),
+            3
+        );
+
+        assert_eq!(service.delete(&ids[..1]).unwrap(), 1);
+        assert_eq!(
+            service.list(&TransactionFilter::default()).unwrap().len(),
+            2
+        );
+        assert!(service.get(&ids[0]).is_err());
+    }
+
+    #[test]
+    fn test_exclude_from_reports() {
+        let (_dir, service, ids) = setup();
+
+        assert_eq!(service.set_excluded(&ids[..2], true).unwrap(), 2);
+        // Already excluded ones don't count as changed
+        assert_eq!(service.set_excluded(&ids[..1], true).unwrap(), 0);
+        let excluded = TransactionFilter {
+            excluded: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(service.list(&excluded).unwrap().len(), 2);
+
+        // An edit keeps the flag unless it changes it
+        let edit = TransactionEdit {
+            description: Some("Reimbursed lunch".to_string()),
+            ..Default::default()
+        };
+        assert!(service.edit(&ids[0], &edit).unwrap().excluded_from_reports);
+        let include = TransactionEdit {
+            excluded_from_reports: Some(false),
+            ..Default::default()
+        };
+        let tx = service.edit(&ids[0], &include).unwrap();
+        assert!(!tx.excluded_from_reports);
+
+        let unknown = vec![Uuid::new_v4().to_string()];
+        assert!(service.set_excluded(&unknown, true).is_err());
+    }
+}
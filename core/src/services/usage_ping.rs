@@ -0,0 +1,284 @@
+//! Usage ping - opt-in, anonymous usage report
+//!
+//! Off unless `app.usagePing` is true in settings.json. When on, at most once
+//! a week it sends the app version, OS, which features are set up and the
+//! names of the commands, screens and integrations used since the last ping.
+//! Never account names, amounts, descriptions or anything else from the
+//! database, which isn't even opened. `build` returns the exact payload, so
+//! `tl logs ping --preview` shows what would be sent without sending it.
+//!
+//! The install ID is random and exists only for the ping; it lives in
+//! `usage_ping.json`, and deleting that file starts a new one.
+
+use std::path::PathBuf;
+#[cfg(feature = "http")]
+use std::time::Duration as StdDuration;
+
+#[cfg(feature = "http")]
+use anyhow::Context;
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::json_file::{read_json, write_json};
+use crate::services::{EncryptionService, EntryPoint, LoggingService};
+
+/// Where pings are sent
+pub const USAGE_PING_URL: &str = "https://treeline.money/api/usage-ping";
+
+/// Version of the payload layout
+const SCHEMA_VERSION: u32 = 1;
+
+/// Days between pings
+const PING_INTERVAL_DAYS: i64 = 7;
+
+/// Give up quickly; a ping is never worth holding up a command
+#[cfg(feature = "http")]
+const SEND_TIMEOUT: StdDuration = StdDuration::from_secs(5);
+
+const STATE_FILE: &str = "usage_ping.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PingState {
+    install_id: Option<String>,
+    last_sent_at: Option<DateTime<Utc>>,
+}
+
+/// Everything a usage ping sends
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct UsagePing {
+    pub schema_version: u32,
+    /// Random ID made for the ping, not derived from the machine or the data
+    pub install_id: String,
+    pub app_version: String,
+    /// "cli" or "desktop"
+    pub entry_point: String,
+    pub os: String,
+    pub arch: String,
+    /// Features that are set up, e.g. "encryption" or "plugins"
+    pub features: Vec<String>,
+    /// CLI commands run since the last ping
+    pub commands: Vec<String>,
+    /// Desktop screens opened since the last ping
+    pub pages: Vec<String>,
+    /// Integrations synced since the last ping
+    pub integrations: Vec<String>,
+}
+
+/// Whether the ping is on and when it last went out
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct UsagePingStatus {
+    pub enabled: bool,
+    pub url: String,
+    pub last_sent_at: Option<DateTime<Utc>>,
+    /// None when disabled
+    pub next_due_at: Option<DateTime<Utc>>,
+}
+
+/// Builds and sends the usage ping
+pub struct UsagePingService {
+    treeline_dir: PathBuf,
+    entry_point: EntryPoint,
+    app_version: String,
+}
+
+impl UsagePingService {
+    pub fn new(
+        treeline_dir: PathBuf,
+        entry_point: EntryPoint,
+        app_version: impl Into<String>,
+    ) -> Self {
+        Self {
+            treeline_dir,
+            entry_point,
+            app_version: app_version.into(),
+        }
+    }
+
+    /// Whether the user opted in
+    pub fn is_enabled(&self) -> bool {
+        Config::load(&self.treeline_dir)
+            .map(|config| config.usage_ping)
+            .unwrap_or(false)
+    }
+
+    /// Opt in or out
+    pub fn set_enabled(&self, enabled: bool) -> Result<()> {
+        let mut config = Config::load(&self.treeline_dir)?;
+        config.usage_ping = enabled;
+        config.save(&self.treeline_dir)
+    }
+
+    pub fn status(&self) -> Result<UsagePingStatus> {
+        let enabled = self.is_enabled();
+        let last_sent_at = self.load_state()?.last_sent_at;
+        Ok(UsagePingStatus {
+            enabled,
+            url: USAGE_PING_URL.to_string(),
+            last_sent_at,
+            next_due_at: enabled.then(|| {
+                last_sent_at
+                    .map(|at| at + Duration::days(PING_INTERVAL_DAYS))
+                    .unwrap_or_else(Utc::now)
+            }),
+        })
+    }
+
+    /// The payload the next ping would send
+    ///
+    /// Usage names come from the logs since the last ping (at most a week back).
+    pub fn build(&self, logger: Option<&LoggingService>) -> Result<UsagePing> {
+        let mut state = self.load_state()?;
+        let install_id = match &state.install_id {
+            Some(id) => id.clone(),
+            None => {
+                let id = Uuid::new_v4().to_string();
+                state.install_id = Some(id.clone());
+                self.save_state(&state)?;
+                id
+            }
+        };
+
+        let week_ago = Utc::now() - Duration::days(PING_INTERVAL_DAYS);
+        let since = state.last_sent_at.unwrap_or(week_ago).max(week_ago);
+        let used = match logger {
+            Some(logger) => logger.names_used_since(since.timestamp_millis())?,
+            None => Default::default(),
+        };
+
+        Ok(UsagePing {
+            schema_version: SCHEMA_VERSION,
+            install_id,
+            app_version: self.app_version.clone(),
+            entry_point: match self.entry_point {
+                EntryPoint::Cli => "cli",
+                EntryPoint::Desktop => "desktop",
+            }
+            .to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            features: self.features(),
+            commands: used.commands,
+            pages: used.pages,
+            integrations: used.integrations,
+        })
+    }
+
+    /// Send the ping if the user opted in and a week has passed
+    ///
+    /// Returns whether a ping was sent.
+    #[cfg(feature = "http")]
+    pub fn send_if_due(&self, logger: Option<&LoggingService>) -> Result<bool> {
+        if !self.is_enabled() {
+            return Ok(false);
+        }
+        let due = self
+            .load_state()?
+            .last_sent_at
+            .map(|at| Utc::now() - at >= Duration::days(PING_INTERVAL_DAYS))
+            .unwrap_or(true);
+        if !due {
+            return Ok(false);
+        }
+
+        let ping = self.build(logger)?;
+        reqwest::blocking::Client::builder()
+            .timeout(SEND_TIMEOUT)
+            .build()?
+            .post(USAGE_PING_URL)
+            .json(&ping)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .context("Failed to send usage ping")?;
+
+        let mut state = self.load_state()?;
+        state.last_sent_at = Some(Utc::now());
+        self.save_state(&state)?;
+        Ok(true)
+    }
+
+    /// Features that are set up, from settings and files only
+    fn features(&self) -> Vec<String> {
+        let config = Config::load(&self.treeline_dir).unwrap_or_default();
+        let db_filename = if config.demo_mode {
+            "demo.duckdb"
+        } else {
+            "treeline.duckdb"
+        };
+        let encrypted = EncryptionService::new(
+            self.treeline_dir.clone(),
+            self.treeline_dir.join(db_filename),
+        )
+        .is_encrypted()
+        .unwrap_or(false);
+        let has_entries = |dir: &str| {
+            std::fs::read_dir(self.treeline_dir.join(dir))
+                .map(|mut entries| entries.next().is_some())
+                .unwrap_or(false)
+        };
+
+        [
+            ("demo_mode", config.demo_mode),
+            ("encryption", encrypted),
+            ("home_assistant", config.home_assistant.is_some()),
+            ("hooks", has_entries("hooks")),
+            ("import_profiles", !config.import_profiles.is_empty()),
+            ("plugins", has_entries("plugins")),
+        ]
+        .into_iter()
+        .filter(|(_, used)| *used)
+        .map(|(name, _)| name.to_string())
+        .collect()
+    }
+
+    fn load_state(&self) -> Result<PingState> {
+        Ok(read_json(&self.treeline_dir.join(STATE_FILE))?.unwrap_or_default())
+    }
+
+    fn save_state(&self, state: &PingState) -> Result<()> {
+        write_json(&self.treeline_dir.join(STATE_FILE), state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_contains_names_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let logger = LoggingService::new(dir.path(), EntryPoint::Cli, "1.2.3").unwrap();
+        logger.log_command("sync").unwrap();
+        logger.log_command("sync").unwrap();
+        logger.log_command("backup create").unwrap();
+        logger
+            .log_error("sync_failed", "Bank said no: account ACME-SECRET", None)
+            .unwrap();
+
+        let service = UsagePingService::new(dir.path().to_path_buf(), EntryPoint::Cli, "1.2.3");
+        let ping = service.build(Some(&logger)).unwrap();
+        assert_eq!(ping.commands, ["backup create", "sync"]);
+        assert_eq!(ping.app_version, "1.2.3");
+        assert!(!serde_json::to_string(&ping).unwrap().contains("SECRET"));
+
+        // The install ID is kept between pings
+        assert_eq!(service.build(None).unwrap().install_id, ping.install_id);
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = UsagePingService::new(dir.path().to_path_buf(), EntryPoint::Cli, "1.0.0");
+        assert!(!service.is_enabled());
+        #[cfg(feature = "http")]
+        assert!(!service.send_if_due(None).unwrap());
+        assert_eq!(service.status().unwrap().next_due_at, None);
+
+        service.set_enabled(true).unwrap();
+        assert!(service.status().unwrap().enabled);
+    }
+}
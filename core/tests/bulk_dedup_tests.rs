@@ -22,9 +22,10 @@ use chrono::{NaiveDate, Utc};
 use rust_decimal::Decimal;
 use uuid::Uuid;
 
+use treeline_core::adapters::credentials::NoKeychain;
 use treeline_core::adapters::duckdb::DuckDbRepository;
 use treeline_core::config::ColumnMappings;
-use treeline_core::domain::{Account, Transaction};
+use treeline_core::domain::{Account, ProviderData, Transaction};
 use treeline_core::services::{ImportOptions, ImportService, SyncService};
 
 // Re-export for test convenience
@@ -37,7 +38,9 @@ fn setup_test_env() -> (TempDir, Arc<DuckDbRepository>, PathBuf) {
     let db_path = treeline_dir.join("data.duckdb");
 
     let repo = Arc::new(
-        DuckDbRepository::new(&db_path, None).expect("Failed to create repository"),
+        DuckDbRepository::new(&db_path, None)
+            .expect("Failed to create repository")
+            .with_key_source(Arc::new(NoKeychain)),
     );
     repo.ensure_schema().expect("Failed to run migrations");
 
@@ -60,23 +63,7 @@ fn create_test_account(repo: &DuckDbRepository, name: &str) -> Uuid {
         created_at: Utc::now(),
         updated_at: Utc::now(),
         is_manual: true,
-        sf_id: None,
-        sf_name: None,
-        sf_currency: None,
-        sf_balance: None,
-        sf_available_balance: None,
-        sf_balance_date: None,
-        sf_org_name: None,
-        sf_org_url: None,
-        sf_org_domain: None,
-        sf_extra: None,
-        lf_id: None,
-        lf_name: None,
-        lf_institution_name: None,
-        lf_institution_logo: None,
-        lf_provider: None,
-        lf_currency: None,
-        lf_status: None,
+        provider: ProviderData::default(),
     };
     repo.upsert_account(&account).expect("Failed to create account");
     account.id
@@ -118,6 +105,13 @@ fn test_csv_import_no_duplicates_on_reimport() {
         debit: None,
         credit: None,
         balance: None,
+        account: None,
+        category: None,
+        tags: None,
+        check_number: None,
+        reference: None,
+        direction: None,
+        currency: None,
     };
     let options = ImportOptions::default();
 
@@ -176,6 +170,13 @@ fn test_csv_import_partial_overlap() {
         debit: None,
         credit: None,
         balance: None,
+        account: None,
+        category: None,
+        tags: None,
+        check_number: None,
+        reference: None,
+        direction: None,
+        currency: None,
     };
     let options = ImportOptions::default();
 
@@ -217,7 +218,7 @@ fn test_bulk_insert_transactions() {
             NaiveDate::from_ymd_opt(2024, 1, 1).unwrap() + chrono::Duration::days(i),
         );
         tx.description = Some(format!("Transaction {}", i));
-        tx.sf_id = Some(format!("sf_test_{}", i));
+        tx.provider.set_external_id("simplefin", Some(format!("sf_test_{}", i)));
         transactions.push(tx);
     }
 
@@ -258,7 +259,7 @@ fn test_get_existing_sf_ids() {
             Decimal::new(100, 2),
             NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
         );
-        tx.sf_id = Some(format!("existing_sf_{}", i));
+        tx.provider.set_external_id("simplefin", Some(format!("existing_sf_{}", i)));
         transactions.push(tx);
     }
     repo.bulk_insert_transactions(&transactions).expect("Insert failed");
@@ -275,7 +276,7 @@ fn test_get_existing_sf_ids() {
         .collect();
 
     let existing = repo
-        .get_existing_sf_ids(&query_ids)
+        .get_existing_external_ids("simplefin", &query_ids)
         .expect("Query failed");
 
     assert_eq!(existing.len(), 10, "Should find 10 existing IDs");
@@ -311,7 +312,7 @@ fn test_get_existing_lf_ids() {
             Decimal::new(100, 2),
             NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
         );
-        tx.lf_id = Some(format!("existing_lf_{}", i));
+        tx.provider.set_external_id("lunchflow", Some(format!("existing_lf_{}", i)));
         transactions.push(tx);
     }
     repo.bulk_insert_transactions(&transactions).expect("Insert failed");
@@ -328,14 +329,14 @@ fn test_get_existing_lf_ids() {
         .collect();
 
     let existing = repo
-        .get_existing_lf_ids(&query_ids)
+        .get_existing_external_ids("lunchflow", &query_ids)
         .expect("Query failed");
 
     assert_eq!(existing.len(), 10, "Should find 10 existing IDs");
 }
 
 #[test]
-fn test_check_duplicate_sf_ids() {
+fn test_check_duplicate_external_ids() {
     let (_temp_dir, repo, _treeline_dir) = setup_test_env();
     let account_id = create_test_account(&repo, "Test Account");
 
@@ -348,7 +349,7 @@ fn test_check_duplicate_sf_ids() {
             Decimal::new(100, 2),
             NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
         );
-        tx.sf_id = Some(format!("unique_sf_{}", i));
+        tx.provider.set_external_id("simplefin", Some(format!("unique_sf_{}", i)));
         repo.upsert_transaction(&tx).expect("Insert failed");
     }
 
@@ -360,15 +361,18 @@ fn test_check_duplicate_sf_ids() {
             Decimal::new(100, 2),
             NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
         );
-        tx.sf_id = Some("duplicate_sf_id".to_string());
+        tx.provider.set_external_id("simplefin", Some("duplicate_sf_id".to_string()));
         repo.upsert_transaction(&tx).expect("Insert failed");
     }
 
     // Check for duplicates
-    let duplicates = repo.check_duplicate_sf_ids().expect("Check failed");
+    let duplicates = repo.check_duplicate_external_ids().expect("Check failed");
 
     assert_eq!(duplicates.len(), 1, "Should find 1 duplicate sf_id");
-    assert_eq!(duplicates[0], "duplicate_sf_id");
+    assert_eq!(
+        duplicates[0],
+        ("simplefin".to_string(), "duplicate_sf_id".to_string())
+    );
 }
 
 #[test]
@@ -381,7 +385,7 @@ fn test_bulk_dedup_workflow() {
     let incoming_sf_ids: Vec<String> = (0..50).map(|i| format!("sf_tx_{}", i)).collect();
 
     // First sync - all are new
-    let existing = repo.get_existing_sf_ids(&incoming_sf_ids).expect("Query failed");
+    let existing = repo.get_existing_external_ids("simplefin", &incoming_sf_ids).expect("Query failed");
     assert_eq!(existing.len(), 0, "No transactions should exist yet");
 
     // Create and insert the transactions
@@ -395,7 +399,7 @@ fn test_bulk_dedup_workflow() {
                 Decimal::new((i as i64 + 1) * 100, 2),
                 NaiveDate::from_ymd_opt(2024, 1, 1).unwrap() + chrono::Duration::days(i as i64),
             );
-            tx.sf_id = Some(sf_id.clone());
+            tx.provider.set_external_id("simplefin", Some(sf_id.clone()));
             tx.description = Some(format!("Transaction {}", i));
             tx
         })
@@ -405,15 +409,15 @@ fn test_bulk_dedup_workflow() {
     assert_eq!(inserted, 50);
 
     // Second sync - simulate same transactions coming in again
-    let existing_after = repo.get_existing_sf_ids(&incoming_sf_ids).expect("Query failed");
+    let existing_after = repo.get_existing_external_ids("simplefin", &incoming_sf_ids).expect("Query failed");
     assert_eq!(existing_after.len(), 50, "All 50 should now exist");
 
     // Filter to new only (should be none)
     let new_txs: Vec<&Transaction> = transactions
         .iter()
         .filter(|tx| {
-            tx.sf_id
-                .as_ref()
+            tx.provider
+                .external_id("simplefin")
                 .map(|id| !existing_after.contains(id))
                 .unwrap_or(true)
         })
@@ -422,7 +426,7 @@ fn test_bulk_dedup_workflow() {
     assert_eq!(new_txs.len(), 0, "No new transactions after filter");
 
     // Verify no duplicates
-    let duplicates = repo.check_duplicate_sf_ids().expect("Check failed");
+    let duplicates = repo.check_duplicate_external_ids().expect("Check failed");
     assert_eq!(duplicates.len(), 0, "Should have no duplicates");
 }
 
@@ -446,7 +450,7 @@ fn test_get_existing_sf_ids_large_batch() {
             NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
                 + chrono::Duration::days(i % 365),
         );
-        tx.sf_id = Some(format!("sf_{:04}", i));
+        tx.provider.set_external_id("simplefin", Some(format!("sf_{:04}", i)));
         transactions.push(tx);
     }
     repo.bulk_insert_transactions(&transactions).expect("Insert failed");
@@ -455,7 +459,7 @@ fn test_get_existing_sf_ids_large_batch() {
     let query_ids: Vec<String> = (0..700).map(|i| format!("sf_{:04}", i)).collect();
 
     let existing = repo
-        .get_existing_sf_ids(&query_ids)
+        .get_existing_external_ids("simplefin", &query_ids)
         .expect("Query failed");
 
     assert_eq!(
@@ -481,7 +485,7 @@ fn test_get_existing_lf_ids_large_batch() {
             NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
                 + chrono::Duration::days(i % 365),
         );
-        tx.lf_id = Some(format!("lf_{:04}", i));
+        tx.provider.set_external_id("lunchflow", Some(format!("lf_{:04}", i)));
         transactions.push(tx);
     }
     repo.bulk_insert_transactions(&transactions).expect("Insert failed");
@@ -490,7 +494,7 @@ fn test_get_existing_lf_ids_large_batch() {
     let query_ids: Vec<String> = (0..700).map(|i| format!("lf_{:04}", i)).collect();
 
     let existing = repo
-        .get_existing_lf_ids(&query_ids)
+        .get_existing_external_ids("lunchflow", &query_ids)
         .expect("Query failed");
 
     assert_eq!(
@@ -527,7 +531,7 @@ fn test_simplefin_sync_no_duplicates() {
 
     // First sync
     let result1 = sync_service
-        .sync(Some("simplefin"), false, false)
+        .sync(Some("simplefin"), false, false, false)
         .expect("First sync failed");
 
     let first_sync_new = result1.results[0].transaction_stats.new;
@@ -538,7 +542,7 @@ fn test_simplefin_sync_no_duplicates() {
 
     // Second sync - should not create any duplicates
     let result2 = sync_service
-        .sync(Some("simplefin"), false, false)
+        .sync(Some("simplefin"), false, false, false)
         .expect("Second sync failed");
 
     let second_sync_new = result2.results[0].transaction_stats.new;
@@ -553,7 +557,7 @@ fn test_simplefin_sync_no_duplicates() {
     let count_after_second = repo.get_transaction_count().expect("Failed to get count");
 
     // Check for duplicates
-    let duplicates = repo.check_duplicate_sf_ids().expect("Check failed");
+    let duplicates = repo.check_duplicate_external_ids().expect("Check failed");
     assert!(
         duplicates.is_empty(),
         "Should have no duplicate sf_ids after two syncs. Found: {:?}",
@@ -590,7 +594,7 @@ fn test_simplefin_rapid_sync_no_duplicates() {
     // Run 5 syncs rapidly
     for i in 0..5 {
         let result = sync_service
-            .sync(Some("simplefin"), false, false)
+            .sync(Some("simplefin"), false, false, false)
             .expect(&format!("Sync {} failed", i));
 
         println!(
@@ -602,7 +606,7 @@ fn test_simplefin_rapid_sync_no_duplicates() {
     }
 
     // Verify no duplicates
-    let duplicates = repo.check_duplicate_sf_ids().expect("Check failed");
+    let duplicates = repo.check_duplicate_external_ids().expect("Check failed");
     assert!(
         duplicates.is_empty(),
         "Should have no duplicate sf_ids after rapid syncs. Found: {:?}",
@@ -637,7 +641,7 @@ fn test_lunchflow_sync_no_duplicates() {
 
     // First sync
     let result1 = sync_service
-        .sync(Some("lunchflow"), false, false)
+        .sync(Some("lunchflow"), false, false, false)
         .expect("First sync failed");
 
     let first_sync_new = result1.results[0].transaction_stats.new;
@@ -645,7 +649,7 @@ fn test_lunchflow_sync_no_duplicates() {
 
     // Second sync
     let result2 = sync_service
-        .sync(Some("lunchflow"), false, false)
+        .sync(Some("lunchflow"), false, false, false)
         .expect("Second sync failed");
 
     let second_sync_new = result2.results[0].transaction_stats.new;
@@ -656,7 +660,7 @@ fn test_lunchflow_sync_no_duplicates() {
     );
 
     // Check for duplicates
-    let duplicates = repo.check_duplicate_lf_ids().expect("Check failed");
+    let duplicates = repo.check_duplicate_external_ids().expect("Check failed");
     assert!(
         duplicates.is_empty(),
         "Should have no duplicate lf_ids after two syncs. Found: {:?}",
@@ -684,7 +688,7 @@ fn test_lunchflow_rapid_sync_no_duplicates() {
     // Run 5 syncs rapidly
     for i in 0..5 {
         let result = sync_service
-            .sync(Some("lunchflow"), false, false)
+            .sync(Some("lunchflow"), false, false, false)
             .expect(&format!("Lunchflow sync {} failed", i));
 
         println!(
@@ -696,7 +700,7 @@ fn test_lunchflow_rapid_sync_no_duplicates() {
     }
 
     // Verify no duplicates
-    let duplicates = repo.check_duplicate_lf_ids().expect("Check failed");
+    let duplicates = repo.check_duplicate_external_ids().expect("Check failed");
     assert!(
         duplicates.is_empty(),
         "Should have no duplicate lf_ids after rapid syncs. Found: {:?}",
@@ -720,6 +724,13 @@ fn test_save_profile_preserves_skip_rows() {
         debit: None,
         credit: None,
         balance: None,
+        account: None,
+        category: None,
+        tags: None,
+        check_number: None,
+        reference: None,
+        direction: None,
+        currency: None,
     };
 
     let options = ImportOptions {
@@ -753,6 +764,13 @@ fn test_save_profile_preserves_number_format() {
         debit: None,
         credit: None,
         balance: None,
+        account: None,
+        category: None,
+        tags: None,
+        check_number: None,
+        reference: None,
+        direction: None,
+        currency: None,
     };
 
     let options = ImportOptions {
@@ -803,6 +821,13 @@ fn test_csv_import_identical_rows_all_imported() {
         debit: None,
         credit: None,
         balance: None,
+        account: None,
+        category: None,
+        tags: None,
+        check_number: None,
+        reference: None,
+        direction: None,
+        currency: None,
     };
     let options = ImportOptions::default();
 
@@ -838,6 +863,13 @@ fn test_csv_import_reimport_after_delete() {
         debit: None,
         credit: None,
         balance: None,
+        account: None,
+        category: None,
+        tags: None,
+        check_number: None,
+        reference: None,
+        direction: None,
+        currency: None,
     };
     let options = ImportOptions::default();
 
@@ -891,6 +923,13 @@ fn test_csv_import_reimport_no_delete() {
         debit: None,
         credit: None,
         balance: None,
+        account: None,
+        category: None,
+        tags: None,
+        check_number: None,
+        reference: None,
+        direction: None,
+        currency: None,
     };
     let options = ImportOptions::default();
 
@@ -1081,6 +1120,7 @@ fn test_bulk_insert_balance_snapshots() {
             balance: Decimal::new(100000 + i * 100, 2), // $1000 + $1/day
             snapshot_time,
             source: Some("csv_import".to_string()),
+            csv_batch_id: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         });
@@ -1129,6 +1169,13 @@ fn test_csv_import_with_balance_column() {
         debit: None,
         credit: None,
         balance: Some("Balance".to_string()),
+        account: None,
+        category: None,
+        tags: None,
+        check_number: None,
+        reference: None,
+        direction: None,
+        currency: None,
     };
     let options = ImportOptions::default();
 
@@ -1175,7 +1222,7 @@ fn test_multiple_providers_no_cross_contamination() {
                 Decimal::new(100, 2),
                 NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
             );
-            tx.sf_id = Some(format!("sf_{}", i));
+            tx.provider.set_external_id("simplefin", Some(format!("sf_{}", i)));
             tx.description = Some(format!("SimpleFin tx {}", i));
             tx
         })
@@ -1190,7 +1237,7 @@ fn test_multiple_providers_no_cross_contamination() {
                 Decimal::new(200, 2),
                 NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
             );
-            tx.lf_id = Some(format!("lf_{}", i));
+            tx.provider.set_external_id("lunchflow", Some(format!("lf_{}", i)));
             tx.description = Some(format!("Lunchflow tx {}", i));
             tx
         })
@@ -1224,13 +1271,18 @@ fn test_multiple_providers_no_cross_contamination() {
     let sf_ids: Vec<String> = (0..10).map(|i| format!("sf_{}", i)).collect();
     let lf_ids: Vec<String> = (0..10).map(|i| format!("lf_{}", i)).collect();
 
-    let existing_sf = repo.get_existing_sf_ids(&sf_ids).expect("Query failed");
-    let existing_lf = repo.get_existing_lf_ids(&lf_ids).expect("Query failed");
+    let existing_sf = repo.get_existing_external_ids("simplefin", &sf_ids).expect("Query failed");
+    let existing_lf = repo.get_existing_external_ids("lunchflow", &lf_ids).expect("Query failed");
 
     assert_eq!(existing_sf.len(), 10);
     assert_eq!(existing_lf.len(), 10);
 
+    // IDs are looked up per provider
+    let crossed = repo
+        .get_existing_external_ids("lunchflow", &sf_ids)
+        .expect("Query failed");
+    assert!(crossed.is_empty());
+
     // Verify no duplicates
-    assert!(repo.check_duplicate_sf_ids().expect("Check failed").is_empty());
-    assert!(repo.check_duplicate_lf_ids().expect("Check failed").is_empty());
+    assert!(repo.check_duplicate_external_ids().expect("Check failed").is_empty());
 }
@@ -0,0 +1,46 @@
+//! Fuzz regression tests
+//!
+//! Runs every input saved under `core/fuzz/regressions/<target>/` through the
+//! property its fuzz target checks. When `cargo fuzz` finds a crash, copy the
+//! input from `core/fuzz/artifacts/<target>/` into the matching regressions
+//! folder so it keeps being checked without nightly or cargo-fuzz.
+//!
+//! Run with: cargo test --test fuzz_regressions
+
+use std::path::PathBuf;
+
+#[path = "../fuzz/checks.rs"]
+mod checks;
+
+fn run_regressions(target: &str, check: fn(&[u8])) {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("fuzz/regressions")
+        .join(target);
+    let mut inputs: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}", dir.display(), e))
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    inputs.sort();
+    assert!(!inputs.is_empty(), "No inputs in {}", dir.display());
+
+    for input in inputs {
+        let data = std::fs::read(&input).unwrap();
+        let result = std::panic::catch_unwind(|| check(&data));
+        assert!(result.is_ok(), "{} failed on {}", target, input.display());
+    }
+}
+
+#[test]
+fn test_csv_sniff_regressions() {
+    run_regressions("csv_sniff", checks::csv_sniff);
+}
+
+#[test]
+fn test_amount_regressions() {
+    run_regressions("amount", checks::amount);
+}
+
+#[test]
+fn test_plugin_sql_regressions() {
+    run_regressions("plugin_sql", checks::plugin_sql);
+}
@@ -5,6 +5,7 @@
 //!
 //! Run with: cargo test --test integration_tests -- --nocapture
 
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 use tempfile::TempDir;
@@ -13,11 +14,13 @@ use uuid::Uuid;
 use chrono::{NaiveDate, Utc};
 use rust_decimal::Decimal;
 
-use treeline_core::adapters::duckdb::DuckDbRepository;
-use treeline_core::config::ColumnMappings;
+use treeline_core::adapters::credentials::NoKeychain;
+use treeline_core::adapters::duckdb::{DuckDbRepository, SyncHistoryEntry};
+use treeline_core::config::{BackupCompression, ColumnMappings, CompressionMethod};
 use treeline_core::domain::{Account, BalanceSnapshot, Transaction};
 use treeline_core::services::{
-    BackupService, BalanceService, ImportOptions, ImportService, NumberFormat, TagService,
+    BackupService, BalanceService, ImportOptions, ImportPreset, ImportService, LedgerFormat,
+    LedgerImportService, NumberFormat, SyncService, TagService,
 };
 
 // ============================================================================
@@ -27,7 +30,9 @@ use treeline_core::services::{
 /// Create a test repository with schema initialized
 fn create_test_repo(temp_dir: &TempDir) -> Arc<DuckDbRepository> {
     let db_path = temp_dir.path().join("test.duckdb");
-    let repo = DuckDbRepository::new(&db_path, None).expect("Failed to create repository");
+    let repo = DuckDbRepository::new(&db_path, None)
+        .expect("Failed to create repository")
+        .with_key_source(Arc::new(NoKeychain));
     repo.ensure_schema().expect("Failed to initialize schema");
     Arc::new(repo)
 }
@@ -314,6 +319,56 @@ fn test_backup_restore() {
     }
 }
 
+/// Test zstd backups restore transparently and report their compression
+#[test]
+fn test_zstd_backup_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.duckdb");
+    let repo = create_test_repo(&temp_dir);
+    repo.upsert_account(&create_test_account("Zstd Account"))
+        .unwrap();
+
+    let backup_service = BackupService::new_with_repository(
+        temp_dir.path().to_path_buf(),
+        "test.duckdb".to_string(),
+        repo.clone(),
+    );
+    let compression = BackupCompression {
+        method: CompressionMethod::Zstd,
+        level: Some(19),
+    };
+    let backup = backup_service
+        .create_with_compression(None, compression)
+        .unwrap();
+    assert_eq!(backup.compression.as_deref(), Some("zstd"));
+    assert_eq!(backup.level, Some(19));
+    assert!(backup.duration_ms.is_some());
+    assert!(backup.compression_ratio().unwrap() < 1.0);
+
+    let listed = &backup_service.list().unwrap()[0];
+    assert_eq!(listed.compression.as_deref(), Some("zstd"));
+    assert_eq!(listed.uncompressed_bytes, backup.uncompressed_bytes);
+
+    let out_of_range = BackupCompression {
+        method: CompressionMethod::Deflate,
+        level: Some(12),
+    };
+    assert!(backup_service
+        .create_with_compression(None, out_of_range)
+        .is_err());
+
+    repo.upsert_account(&create_test_account("After Backup"))
+        .unwrap();
+    drop(backup_service);
+    drop(repo);
+
+    BackupService::new(temp_dir.path().to_path_buf(), "test.duckdb".to_string())
+        .restore(&backup.name)
+        .unwrap();
+    let repo = DuckDbRepository::new(&db_path, None).unwrap();
+    assert_eq!(repo.get_accounts().unwrap().len(), 1);
+}
+
 // ============================================================================
 // Tag Service Tests
 // ============================================================================
@@ -474,6 +529,13 @@ fn test_csv_import_valid_data() {
         credit: None,
         debit: None,
         balance: None,
+        account: None,
+        category: None,
+        tags: None,
+        check_number: None,
+        reference: None,
+        direction: None,
+        currency: None,
     };
 
     let options = ImportOptions {
@@ -483,6 +545,11 @@ fn test_csv_import_valid_data() {
         number_format: NumberFormat::default(),
         anchor_balance: None,
         anchor_date: None,
+        column_number_formats: HashMap::new(),
+        account_map: HashMap::new(),
+        create_missing_accounts: false,
+        category_map: HashMap::new(),
+        date_format: None,
     };
 
     let result = import_service
@@ -543,6 +610,13 @@ fn test_csv_import_deduplication() {
         credit: None,
         debit: None,
         balance: None,
+        account: None,
+        category: None,
+        tags: None,
+        check_number: None,
+        reference: None,
+        direction: None,
+        currency: None,
     };
 
     let options = ImportOptions {
@@ -552,6 +626,11 @@ fn test_csv_import_deduplication() {
         number_format: NumberFormat::default(),
         anchor_balance: None,
         anchor_date: None,
+        column_number_formats: HashMap::new(),
+        account_map: HashMap::new(),
+        create_missing_accounts: false,
+        category_map: HashMap::new(),
+        date_format: None,
     };
 
     // First import
@@ -588,6 +667,641 @@ fn test_csv_import_deduplication() {
     assert_eq!(transactions.len(), 1, "Should have only 1 transaction");
 }
 
+/// Test that preview reports the rows it will skip and why
+#[test]
+fn test_csv_import_preview_reports_row_errors() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Row Error Test Account");
+    repo.upsert_account(&account).unwrap();
+
+    let csv_content = r#"date,amount,description
+2024-01-15,12.34,Coffee Shop
+15th of January,-45.67,Restaurant
+2024-01-17,ten dollars,Refund
+,5.00,Missing date"#;
+
+    let csv_path = temp_dir.path().join("test_row_errors.csv");
+    std::fs::write(&csv_path, csv_content).unwrap();
+
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+
+    let mappings = ColumnMappings {
+        date: "date".to_string(),
+        amount: "amount".to_string(),
+        description: Some("description".to_string()),
+        credit: None,
+        debit: None,
+        balance: None,
+        account: None,
+        category: None,
+        tags: None,
+        check_number: None,
+        reference: None,
+        direction: None,
+        currency: None,
+    };
+
+    let result = import_service
+        .import(
+            Path::new(&csv_path),
+            &account.id.to_string(),
+            &mappings,
+            &ImportOptions::default(),
+            true,
+        )
+        .unwrap();
+
+    assert_eq!(result.discovered, 1);
+    assert_eq!(result.skipped, 3);
+    assert_eq!(result.errors.len(), 3);
+
+    assert_eq!(result.errors[0].line, 3);
+    assert_eq!(result.errors[0].column, "date");
+    assert_eq!(result.errors[0].value, "15th of January");
+    assert_eq!(result.errors[0].reason, "invalid date format");
+
+    assert_eq!(result.errors[1].line, 4);
+    assert_eq!(result.errors[1].column, "amount");
+    assert_eq!(result.errors[1].value, "ten dollars");
+
+    assert_eq!(result.errors[2].line, 5);
+    assert_eq!(result.errors[2].reason, "empty date");
+}
+
+/// Test importing a file that covers several accounts via an account column
+#[test]
+fn test_csv_import_multi_account_column() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let checking = create_test_account("Checking");
+    let visa = create_test_account("Rewards Visa");
+    repo.upsert_account(&checking).unwrap();
+    repo.upsert_account(&visa).unwrap();
+
+    let csv_content = r#"date,amount,description,account
+2024-01-15,-12.34,Coffee Shop,VISA ...1234
+2024-01-16,2000.00,Payroll,Checking
+2024-01-17,-45.67,Restaurant,Travel Card
+2024-01-18,-5.00,Fee,"#;
+
+    let csv_path = temp_dir.path().join("test_multi_account.csv");
+    std::fs::write(&csv_path, csv_content).unwrap();
+
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+
+    let mappings = ColumnMappings {
+        date: "date".to_string(),
+        amount: "amount".to_string(),
+        description: Some("description".to_string()),
+        credit: None,
+        debit: None,
+        balance: None,
+        account: Some("account".to_string()),
+        category: None,
+        tags: None,
+        check_number: None,
+        reference: None,
+        direction: None,
+        currency: None,
+    };
+
+    let mut options = ImportOptions::default();
+    options
+        .account_map
+        .insert("VISA ...1234".to_string(), "Rewards Visa".to_string());
+
+    // Without account creation the unknown value is reported and skipped
+    let preview = import_service
+        .import(
+            Path::new(&csv_path),
+            &checking.id.to_string(),
+            &mappings,
+            &options,
+            true,
+        )
+        .unwrap();
+    assert_eq!(preview.discovered, 3);
+    assert_eq!(preview.errors.len(), 1);
+    assert_eq!(preview.errors[0].value, "Travel Card");
+    assert!(preview.accounts_created.is_empty());
+
+    options.create_missing_accounts = true;
+    let result = import_service
+        .import(
+            Path::new(&csv_path),
+            &checking.id.to_string(),
+            &mappings,
+            &options,
+            false,
+        )
+        .unwrap();
+    assert_eq!(result.imported, 4);
+    assert_eq!(result.accounts_created, vec!["Travel Card".to_string()]);
+
+    // Mapped value and blank cell
+    let visa_txs = repo
+        .get_transactions_by_account(&visa.id.to_string())
+        .unwrap();
+    assert_eq!(visa_txs.len(), 1);
+    let checking_txs = repo
+        .get_transactions_by_account(&checking.id.to_string())
+        .unwrap();
+    assert_eq!(checking_txs.len(), 2, "Payroll by name, fee by default");
+
+    let travel = repo
+        .get_accounts()
+        .unwrap()
+        .into_iter()
+        .find(|a| a.name == "Travel Card")
+        .expect("Travel Card account should be created");
+    let travel_txs = repo
+        .get_transactions_by_account(&travel.id.to_string())
+        .unwrap();
+    assert_eq!(travel_txs.len(), 1);
+}
+
+/// Test that category and tags columns become transaction tags
+#[test]
+fn test_csv_import_category_and_tags_columns() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Category Test Account");
+    repo.upsert_account(&account).unwrap();
+
+    let csv_content = r#"date,amount,description,category,labels
+2024-01-15,-12.34,Coffee Shop,Restaurants,work;reimbursable
+2024-01-16,-45.67,Grocer,Groceries,
+2024-01-17,500.00,Transfer In,Transfers,"#;
+
+    let csv_path = temp_dir.path().join("test_categories.csv");
+    std::fs::write(&csv_path, csv_content).unwrap();
+
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+
+    let mappings = ColumnMappings {
+        date: "date".to_string(),
+        amount: "amount".to_string(),
+        description: Some("description".to_string()),
+        credit: None,
+        debit: None,
+        balance: None,
+        account: None,
+        category: Some("category".to_string()),
+        tags: Some("labels".to_string()),
+        check_number: None,
+        reference: None,
+        direction: None,
+        currency: None,
+    };
+
+    let mut options = ImportOptions::default();
+    options
+        .category_map
+        .insert("Restaurants".to_string(), "dining".to_string());
+    options
+        .category_map
+        .insert("Transfers".to_string(), String::new());
+
+    let result = import_service
+        .import(
+            Path::new(&csv_path),
+            &account.id.to_string(),
+            &mappings,
+            &options,
+            false,
+        )
+        .unwrap();
+    assert_eq!(result.imported, 3);
+
+    let transactions = repo
+        .get_transactions_by_account(&account.id.to_string())
+        .unwrap();
+    let tags_for = |desc: &str| {
+        let mut tags = transactions
+            .iter()
+            .find(|t| t.description.as_deref() == Some(desc))
+            .unwrap()
+            .tags
+            .clone();
+        tags.sort();
+        tags
+    };
+
+    assert_eq!(
+        tags_for("Coffee Shop"),
+        vec!["dining", "reimbursable", "work"]
+    );
+    assert_eq!(tags_for("Grocer"), vec!["Groceries"]);
+    assert!(tags_for("Transfer In").is_empty());
+}
+
+/// Test that check numbers keep otherwise-identical rows distinct and are stored
+#[test]
+fn test_csv_import_check_numbers() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Check Number Test Account");
+    repo.upsert_account(&account).unwrap();
+
+    let csv_content = r#"date,amount,description,check
+2024-01-15,-100.00,CHECK,1001
+2024-01-15,-100.00,CHECK,1002"#;
+
+    let csv_path = temp_dir.path().join("test_checks.csv");
+    std::fs::write(&csv_path, csv_content).unwrap();
+
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+
+    let mappings = ColumnMappings {
+        date: "date".to_string(),
+        amount: "amount".to_string(),
+        description: Some("description".to_string()),
+        credit: None,
+        debit: None,
+        balance: None,
+        account: None,
+        category: None,
+        tags: None,
+        check_number: Some("check".to_string()),
+        reference: None,
+        direction: None,
+        currency: None,
+    };
+    let options = ImportOptions::default();
+
+    let result = import_service
+        .import(
+            Path::new(&csv_path),
+            &account.id.to_string(),
+            &mappings,
+            &options,
+            false,
+        )
+        .unwrap();
+    assert_eq!(result.imported, 2);
+
+    // Re-import is still deduplicated
+    let result = import_service
+        .import(
+            Path::new(&csv_path),
+            &account.id.to_string(),
+            &mappings,
+            &options,
+            false,
+        )
+        .unwrap();
+    assert_eq!(result.imported, 0);
+    assert_eq!(result.skipped, 2);
+
+    let mut check_numbers: Vec<String> = repo
+        .get_transactions_by_account(&account.id.to_string())
+        .unwrap()
+        .into_iter()
+        .filter_map(|t| t.check_number)
+        .collect();
+    check_numbers.sort();
+    assert_eq!(check_numbers, vec!["1001", "1002"]);
+}
+
+/// Test that a Mint export creates its accounts and takes signs from Transaction Type
+#[test]
+fn test_csv_import_mint_preset() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let csv_content = r#""Date","Description","Original Description","Amount","Transaction Type","Category","Account Name","Labels","Notes"
+"1/15/2024","Coffee Shop","COFFEE SHOP #12","4.00","debit","Coffee Shops","Sapphire","work",""
+"1/16/2024","Payroll","ACME PAYROLL","1500.00","credit","Paycheck","Checking","",""
+"1/17/2024","Misc","MISC","12.00","debit","Uncategorized","Checking","","""#;
+
+    let csv_path = temp_dir.path().join("mint.csv");
+    std::fs::write(&csv_path, csv_content).unwrap();
+
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+
+    let result = import_service
+        .import_preset(&csv_path, ImportPreset::Mint, None, NumberFormat::Us, false)
+        .unwrap();
+    assert_eq!(result.imported, 3);
+    assert_eq!(result.accounts_created, vec!["Sapphire", "Checking"]);
+
+    let accounts = repo.get_accounts().unwrap();
+    let checking = accounts.iter().find(|a| a.name == "Checking").unwrap();
+    let transactions = repo
+        .get_transactions_by_account(&checking.id.to_string())
+        .unwrap();
+    let total: Decimal = transactions.iter().map(|t| t.amount).sum();
+    assert_eq!(total, Decimal::from(1488));
+    let misc = transactions
+        .iter()
+        .find(|t| t.description.as_deref() == Some("Misc"))
+        .unwrap();
+    assert!(misc.tags.is_empty());
+
+    // Re-import is deduplicated without creating accounts again
+    let again = import_service
+        .import_preset(&csv_path, ImportPreset::Mint, None, NumberFormat::Us, false)
+        .unwrap();
+    assert_eq!(again.imported, 0);
+    assert!(again.accounts_created.is_empty());
+}
+
+/// Test that PayPal fees become separate transactions and conversions keep only the home-currency leg
+#[test]
+fn test_csv_import_paypal_preset() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("PayPal");
+    repo.upsert_account(&account).unwrap();
+
+    let csv_content = r#""Date","Time","TimeZone","Name","Type","Status","Currency","Gross","Fee","Net","Transaction ID","Reference Txn ID"
+"03/01/2024","10:00:00","PST","Acme Client","Website Payment","Completed","USD","100.00","-3.00","97.00","AAA1",""
+"03/02/2024","11:00:00","PST","Berlin Shop","Express Checkout Payment","Completed","EUR","-10.00","0.00","-10.00","BBB1",""
+"03/02/2024","11:00:00","PST","","General Currency Conversion","Completed","EUR","10.00","0.00","10.00","CCC1","BBB1"
+"03/02/2024","11:00:00","PST","","General Currency Conversion","Completed","USD","-11.00","0.00","-11.00","DDD1","BBB1"
+"03/03/2024","12:00:00","PST","Someone","Website Payment","Pending","USD","5.00","0.00","5.00","EEE1","""#;
+
+    let csv_path = temp_dir.path().join("paypal.csv");
+    std::fs::write(&csv_path, csv_content).unwrap();
+
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+    let account_id = account.id.to_string();
+
+    let result = import_service
+        .import_preset(
+            &csv_path,
+            ImportPreset::PayPal,
+            Some(&account_id),
+            NumberFormat::Us,
+            false,
+        )
+        .unwrap();
+    assert_eq!(result.imported, 3);
+    assert_eq!(result.skipped, 3);
+
+    let transactions = repo.get_transactions_by_account(&account_id).unwrap();
+    let fee = transactions
+        .iter()
+        .find(|t| t.description.as_deref() == Some("PayPal fee: Acme Client"))
+        .unwrap();
+    assert_eq!(fee.amount, Decimal::from(-3));
+    assert_eq!(fee.tags, vec!["fees"]);
+    let conversion = transactions
+        .iter()
+        .find(|t| t.reference.as_deref() == Some("DDD1"))
+        .unwrap();
+    assert_eq!(conversion.description.as_deref(), Some("Berlin Shop"));
+    assert_eq!(conversion.amount, Decimal::from(-11));
+
+    // Re-import is deduplicated by transaction ID
+    let again = import_service
+        .import_preset(
+            &csv_path,
+            ImportPreset::PayPal,
+            Some(&account_id),
+            NumberFormat::Us,
+            false,
+        )
+        .unwrap();
+    assert_eq!(again.imported, 0);
+}
+
+/// Test that Amazon orders rename the one matching card charge and leave ambiguous ones alone
+#[test]
+fn test_amazon_order_match() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Visa");
+    repo.upsert_account(&account).unwrap();
+
+    let charge = |amount: i64, date: &str, description: &str| {
+        let mut tx = Transaction::new(
+            Uuid::new_v4(),
+            account.id,
+            Decimal::new(amount, 2),
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+        );
+        tx.description = Some(description.to_string());
+        tx
+    };
+    repo.bulk_insert_transactions(&[
+        charge(-2345, "2024-03-01", "AMZN Mktp US*2K4"),
+        charge(-2345, "2024-03-01", "Grocery Store"),
+        charge(-999, "2024-03-05", "AMAZON.COM*AB12"),
+        charge(-999, "2024-03-06", "AMAZON.COM*CD34"),
+    ])
+    .unwrap();
+
+    let csv_content = "Order ID,Order Date,Product Name,Total Owed
+111-1,2024-02-28T10:00:00Z,USB-C Cable,12.45
+111-1,2024-02-28T10:00:00Z,Notebook,11.00
+222-2,2024-03-04T09:00:00Z,Batteries,9.99
+";
+    let csv_path = temp_dir.path().join("orders.csv");
+    std::fs::write(&csv_path, csv_content).unwrap();
+
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+    let result = import_service
+        .match_amazon_orders(&csv_path, None, false)
+        .unwrap();
+    assert_eq!(result.orders, 2);
+    assert_eq!(result.matched.len(), 1);
+    assert_eq!(result.ambiguous, 1);
+
+    let transactions = repo
+        .get_transactions_by_account(&account.id.to_string())
+        .unwrap();
+    let renamed: Vec<_> = transactions
+        .iter()
+        .filter_map(|t| t.description.as_deref())
+        .filter(|d| d.starts_with("Amazon: "))
+        .collect();
+    assert_eq!(renamed, vec!["Amazon: USB-C Cable, Notebook"]);
+}
+
+// ============================================================================
+// Ledger Import Tests
+// ============================================================================
+
+/// Test that a Money Manager EX database migrates accounts, categories and
+/// transfers, reports what it can't translate, and re-imports idempotently
+#[test]
+fn test_ledger_import_mmex() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let mmb_path = temp_dir.path().join("book.mmb");
+    let conn = rusqlite::Connection::open(&mmb_path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE CURRENCYFORMATS_V1 (CURRENCYID INTEGER, CURRENCY_SYMBOL TEXT);
+         INSERT INTO CURRENCYFORMATS_V1 VALUES (1, 'EUR');
+         CREATE TABLE ACCOUNTLIST_V1 (ACCOUNTID INTEGER, ACCOUNTNAME TEXT, ACCOUNTTYPE TEXT,
+             INITIALBAL NUMERIC, CURRENCYID INTEGER);
+         INSERT INTO ACCOUNTLIST_V1 VALUES (1, 'Giro', 'Checking', 100, 1),
+             (2, 'Visa', 'Credit Card', 0, 1);
+         CREATE TABLE CATEGORY_V1 (CATEGID INTEGER, CATEGNAME TEXT);
+         INSERT INTO CATEGORY_V1 VALUES (1, 'Groceries');
+         CREATE TABLE PAYEE_V1 (PAYEEID INTEGER, PAYEENAME TEXT);
+         INSERT INTO PAYEE_V1 VALUES (1, 'Aldi');
+         CREATE TABLE CHECKINGACCOUNT_V1 (TRANSID INTEGER, ACCOUNTID INTEGER, TOACCOUNTID INTEGER,
+             PAYEEID INTEGER, TRANSCODE TEXT, TRANSAMOUNT NUMERIC, STATUS TEXT,
+             TRANSACTIONNUMBER TEXT, NOTES TEXT, CATEGID INTEGER, TRANSDATE TEXT,
+             TOTRANSAMOUNT NUMERIC);
+         INSERT INTO CHECKINGACCOUNT_V1 VALUES
+             (1, 1, -1, 1, 'Withdrawal', 23, 'R', '', '', 1, '2024-02-01', 23),
+             (2, 1, 2, -1, 'Transfer', 50, 'R', '', '', -1, '2024-02-03', 50),
+             (3, 1, -1, 1, 'Withdrawal', 9.99, 'V', '', '', 1, '2024-02-04', 9.99);
+         CREATE TABLE BILLSDEPOSITS_V1 (BDID INTEGER);
+         INSERT INTO BILLSDEPOSITS_V1 VALUES (1);",
+    )
+    .unwrap();
+    drop(conn);
+
+    let service = LedgerImportService::new(repo.clone());
+
+    let preview = service.import(&mmb_path, LedgerFormat::Mmex, true).unwrap();
+    assert_eq!(preview.imported, 4);
+    assert!(repo.get_accounts().unwrap().is_empty());
+
+    let result = service
+        .import(&mmb_path, LedgerFormat::Mmex, false)
+        .unwrap();
+    assert_eq!(result.imported, 4);
+    let reasons: Vec<&str> = result
+        .untranslated
+        .iter()
+        .map(|u| u.kind.as_str())
+        .collect();
+    assert_eq!(reasons, vec!["transaction", "scheduled transaction"]);
+
+    let accounts = repo.get_accounts().unwrap();
+    let giro = accounts.iter().find(|a| a.name == "Giro").unwrap();
+    let visa = accounts.iter().find(|a| a.name == "Visa").unwrap();
+    assert_eq!(giro.currency, "EUR");
+    assert_eq!(visa.classification.as_deref(), Some("liability"));
+
+    let giro_txs = repo
+        .get_transactions_by_account(&giro.id.to_string())
+        .unwrap();
+    let total: Decimal = giro_txs.iter().map(|t| t.amount).sum();
+    assert_eq!(total, Decimal::from(27));
+    let groceries = giro_txs
+        .iter()
+        .find(|t| t.description.as_deref() == Some("Aldi"))
+        .unwrap();
+    assert_eq!(groceries.tags, vec!["Groceries"]);
+
+    let visa_txs = repo
+        .get_transactions_by_account(&visa.id.to_string())
+        .unwrap();
+    assert_eq!(visa_txs.len(), 1);
+    assert_eq!(visa_txs[0].amount, Decimal::from(50));
+
+    // Re-import matches existing accounts and skips every transaction
+    let again = service
+        .import(&mmb_path, LedgerFormat::Mmex, false)
+        .unwrap();
+    assert_eq!(again.imported, 0);
+    assert_eq!(again.skipped, 4);
+    assert!(again.accounts.iter().all(|a| !a.created));
+    assert_eq!(repo.get_accounts().unwrap().len(), 2);
+
+    // Renaming an account in MMEX doesn't re-import its history
+    let conn = rusqlite::Connection::open(&mmb_path).unwrap();
+    conn.execute(
+        "UPDATE ACCOUNTLIST_V1 SET ACCOUNTNAME = 'Girokonto' WHERE ACCOUNTID = 1",
+        [],
+    )
+    .unwrap();
+    drop(conn);
+    let renamed = service
+        .import(&mmb_path, LedgerFormat::Mmex, false)
+        .unwrap();
+    assert_eq!(renamed.imported, 0);
+    assert_eq!(renamed.skipped, 4);
+}
+
+// ============================================================================
+// Sync History Tests
+// ============================================================================
+
+/// Test that failed syncs are recorded and per-account activity is derived from history
+#[test]
+fn test_sync_history_records_failures_and_account_activity() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let sync_service = SyncService::new(repo.clone(), temp_dir.path().to_path_buf());
+
+    let mut checking = create_test_account("Checking");
+    checking
+        .provider
+        .set_external_id("simplefin", Some("ACT-1".to_string()));
+    let mut savings = create_test_account("Savings");
+    savings
+        .provider
+        .set_external_id("simplefin", Some("ACT-2".to_string()));
+    repo.upsert_account(&checking).unwrap();
+    repo.upsert_account(&savings).unwrap();
+
+    // An earlier successful run that only brought in data for checking
+    repo.insert_sync_history(&SyncHistoryEntry {
+        sync_id: Uuid::new_v4().to_string(),
+        integration: "simplefin".to_string(),
+        started_at: Utc::now() - chrono::Duration::hours(1),
+        duration_ms: 1200,
+        sync_type: Some("incremental".to_string()),
+        accounts_synced: 0,
+        transactions_discovered: 5,
+        transactions_new: 3,
+        transactions_skipped: 2,
+        account_stats: [(checking.id.to_string(), 3)].into_iter().collect(),
+        warnings: Vec::new(),
+        error: None,
+    })
+    .unwrap();
+
+    // A failing integration is recorded with its error; dry runs are not recorded
+    repo.upsert_integration("simplefin", &serde_json::json!({}))
+        .unwrap();
+    assert!(sync_service
+        .sync(Some("simplefin"), true, false, false)
+        .is_err());
+    assert!(sync_service
+        .sync(Some("simplefin"), false, false, false)
+        .is_err());
+
+    let history = sync_service.history(None, 10).unwrap();
+    assert_eq!(history.len(), 2);
+    assert!(history[0].error.is_some());
+    assert!(history[1].error.is_none());
+    assert!(sync_service
+        .history(Some("lunchflow"), 10)
+        .unwrap()
+        .is_empty());
+
+    let activity = sync_service.account_activity().unwrap();
+    let checking_activity = activity
+        .iter()
+        .find(|a| a.account_name == "Checking")
+        .unwrap();
+    assert!(checking_activity.last_new_data_at.is_some());
+    assert_eq!(checking_activity.last_new_transactions, 3);
+    assert!(checking_activity.last_error.is_some());
+    let savings_activity = activity
+        .iter()
+        .find(|a| a.account_name == "Savings")
+        .unwrap();
+    assert!(savings_activity.last_synced_at.is_some());
+    assert!(savings_activity.last_new_data_at.is_none());
+}
+
 // ============================================================================
 // Data Integrity Tests
 // ============================================================================
@@ -682,15 +1396,26 @@ fn test_sync_deduplication_by_provider_id() {
 
     let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
     let mut tx = create_test_transaction(account.id, 5000, date);
-    tx.sf_id = Some("unique_sf_id".to_string());
+    tx.provider
+        .set_external_id("simplefin", Some("unique_sf_id".to_string()));
     repo.upsert_transaction(&tx).unwrap();
 
     // Check if transaction exists by SF ID
-    let exists = repo.transaction_exists_by_sf_id("unique_sf_id").unwrap();
+    let exists = repo
+        .transaction_exists_by_external_id("simplefin", "unique_sf_id")
+        .unwrap();
     assert!(exists, "Transaction should exist by SF ID");
 
-    let not_exists = repo.transaction_exists_by_sf_id("nonexistent").unwrap();
+    let not_exists = repo
+        .transaction_exists_by_external_id("simplefin", "nonexistent")
+        .unwrap();
     assert!(!not_exists, "Non-existent SF ID should return false");
+
+    // IDs are per provider
+    let other_provider = repo
+        .transaction_exists_by_external_id("lunchflow", "unique_sf_id")
+        .unwrap();
+    assert!(!other_provider, "Another provider's ID should not match");
 }
 
 /// Test Lunchflow ID deduplication
@@ -704,14 +1429,19 @@ fn test_sync_deduplication_by_lunchflow_id() {
 
     let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
     let mut tx = create_test_transaction(account.id, 5000, date);
-    tx.lf_id = Some("unique_lf_id".to_string());
+    tx.provider
+        .set_external_id("lunchflow", Some("unique_lf_id".to_string()));
     repo.upsert_transaction(&tx).unwrap();
 
     // Check if transaction exists by LF ID
-    let exists = repo.transaction_exists_by_lf_id("unique_lf_id").unwrap();
+    let exists = repo
+        .transaction_exists_by_external_id("lunchflow", "unique_lf_id")
+        .unwrap();
     assert!(exists, "Transaction should exist by LF ID");
 
-    let not_exists = repo.transaction_exists_by_lf_id("nonexistent").unwrap();
+    let not_exists = repo
+        .transaction_exists_by_external_id("lunchflow", "nonexistent")
+        .unwrap();
     assert!(!not_exists, "Non-existent LF ID should return false");
 }
 
@@ -1131,3 +1861,27 @@ fn test_readonly_rejects_create_table() {
     let result = repo.execute_query_readonly("CREATE TABLE evil (id INTEGER)");
     assert!(result.is_err(), "CREATE TABLE should fail in readonly mode");
 }
+
+/// Test that a read-only repository reads but never writes
+#[test]
+fn test_open_read_only_repository() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let account = create_test_account("Checking");
+    repo.upsert_account(&account).unwrap();
+
+    let read_only = DuckDbRepository::open_read_only(repo.db_path(), None).unwrap();
+    assert!(read_only.is_read_only());
+    read_only.require_current_schema().unwrap();
+    assert_eq!(read_only.get_accounts().unwrap().len(), 1);
+
+    assert!(read_only.upsert_account(&create_test_account("Savings")).is_err());
+    assert!(read_only.execute_sql("DELETE FROM sys_accounts").is_err());
+    assert!(read_only.compact().is_err());
+    assert_eq!(repo.get_accounts().unwrap().len(), 1);
+
+    // Nothing is created for a missing database
+    let missing = temp_dir.path().join("missing.duckdb");
+    assert!(DuckDbRepository::open_read_only(&missing, None).is_err());
+    assert!(!missing.exists());
+}
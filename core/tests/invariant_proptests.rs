@@ -0,0 +1,169 @@
+//! Property-based tests for the invariants imports and balances rely on
+//!
+//! Each case builds a fresh database, so case counts are kept small; raise
+//! them with PROPTEST_CASES when touching dedup or backfill.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use chrono::{Duration, NaiveDate};
+use proptest::prelude::*;
+use rust_decimal::Decimal;
+use tempfile::TempDir;
+use uuid::Uuid;
+
+use treeline_core::adapters::duckdb::DuckDbRepository;
+use treeline_core::config::ColumnMappings;
+use treeline_core::domain::Account;
+use treeline_core::services::{BalanceService, ImportOptions, ImportService};
+
+/// One generated CSV row: days after 2024-01-01, amount in cents, description
+type Row = (i64, i64, String);
+
+fn rows() -> impl Strategy<Value = Vec<Row>> {
+    prop::collection::vec(
+        (0i64..90, -500_000i64..500_000, "[A-Za-z][A-Za-z ]{0,15}"),
+        1..40,
+    )
+}
+
+fn date(days: i64) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 1, 1).unwrap() + Duration::days(days)
+}
+
+fn to_csv(rows: &[Row]) -> String {
+    let mut csv = String::from("Date,Amount,Description\n");
+    for (days, cents, description) in rows {
+        csv.push_str(&format!(
+            "{},{},{}\n",
+            date(*days),
+            Decimal::new(*cents, 2),
+            description
+        ));
+    }
+    csv
+}
+
+/// A fresh database with one account
+fn setup() -> (TempDir, Arc<DuckDbRepository>, String) {
+    let dir = TempDir::new().unwrap();
+    let repo = Arc::new(DuckDbRepository::new(&dir.path().join("data.duckdb"), None).unwrap());
+    repo.ensure_schema().unwrap();
+    let account = Account::new(Uuid::new_v4(), "Checking");
+    repo.upsert_account(&account).unwrap();
+    (dir, repo, account.id.to_string())
+}
+
+fn import(dir: &TempDir, repo: &Arc<DuckDbRepository>, account_id: &str, csv: &str) -> i64 {
+    ImportService::new(repo.clone(), dir.path().to_path_buf())
+        .import_text(
+            csv,
+            account_id,
+            &ColumnMappings::default(),
+            &ImportOptions::default(),
+            false,
+        )
+        .unwrap()
+        .imported
+}
+
+/// End-of-day balance per date, failing if a day has more than one snapshot
+fn snapshots_by_date(repo: &DuckDbRepository, account_id: &str) -> BTreeMap<NaiveDate, Decimal> {
+    let mut by_date = BTreeMap::new();
+    for snapshot in repo.get_balance_snapshots(Some(account_id)).unwrap() {
+        let previous = by_date.insert(snapshot.snapshot_time.date(), snapshot.balance);
+        assert!(
+            previous.is_none(),
+            "two snapshots on {}",
+            snapshot.snapshot_time
+        );
+    }
+    by_date
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(24))]
+
+    /// Importing the same file again never adds transactions
+    #[test]
+    fn reimport_is_idempotent(rows in rows()) {
+        let (dir, repo, account_id) = setup();
+        let csv = to_csv(&rows);
+
+        let first = import(&dir, &repo, &account_id, &csv);
+        let count = repo.get_transaction_count().unwrap();
+        prop_assert_eq!(count, first);
+
+        let second = import(&dir, &repo, &account_id, &csv);
+        prop_assert_eq!(second, 0);
+        prop_assert_eq!(repo.get_transaction_count().unwrap(), count);
+    }
+
+    /// Importing a file in two overlapping pieces ends up the same as importing it whole
+    #[test]
+    fn overlapping_imports_match_whole_import(
+        rows in rows(),
+        split in any::<prop::sample::Index>(),
+    ) {
+        let cut = split.index(rows.len());
+
+        let (dir, repo, account_id) = setup();
+        import(&dir, &repo, &account_id, &to_csv(&rows));
+        let whole = repo.get_transaction_count().unwrap();
+
+        let (dir, repo, account_id) = setup();
+        import(&dir, &repo, &account_id, &to_csv(&rows[..cut]));
+        import(&dir, &repo, &account_id, &to_csv(&rows));
+        prop_assert_eq!(repo.get_transaction_count().unwrap(), whole);
+    }
+
+    /// Backfilled balances step by exactly each day's transactions, end at the
+    /// known balance, and backfilling again changes nothing
+    #[test]
+    fn backfill_matches_transaction_deltas(
+        rows in rows(),
+        known_cents in -10_000_000i64..10_000_000,
+    ) {
+        let (dir, repo, account_id) = setup();
+        import(&dir, &repo, &account_id, &to_csv(&rows));
+
+        let mut daily: BTreeMap<NaiveDate, Decimal> = BTreeMap::new();
+        for tx in repo.get_transactions_by_account(&account_id).unwrap() {
+            *daily.entry(tx.transaction_date).or_default() += tx.amount;
+        }
+        let Some(&known_date) = daily.keys().next_back() else {
+            return Ok(());
+        };
+        let known_balance = Decimal::new(known_cents, 2);
+
+        let balances = BalanceService::new(repo.clone());
+        balances
+            .backfill_execute(&account_id, known_balance, known_date, None, None)
+            .unwrap();
+        let snapshots = snapshots_by_date(&repo, &account_id);
+
+        prop_assert_eq!(
+            snapshots.keys().collect::<Vec<_>>(),
+            daily.keys().collect::<Vec<_>>()
+        );
+        prop_assert_eq!(snapshots[&known_date], known_balance);
+        let days: Vec<(&NaiveDate, &Decimal)> = snapshots.iter().collect();
+        for pair in days.windows(2) {
+            let ((_, before), (day, after)) = (pair[0], pair[1]);
+            prop_assert_eq!(*after - *before, daily[day], "balance step on {}", day);
+        }
+
+        // Recomputing from the same anchor finds every day already correct
+        let preview = balances
+            .backfill_preview(&account_id, known_balance, known_date, None, None)
+            .unwrap();
+        prop_assert!(preview
+            .iter()
+            .all(|p| !p.is_new && p.existing_balance == Some(p.balance)));
+
+        balances
+            .backfill_execute(&account_id, known_balance, known_date, None, None)
+            .unwrap();
+        prop_assert_eq!(snapshots_by_date(&repo, &account_id), snapshots);
+    }
+}
@@ -0,0 +1,55 @@
+/**
+ * Automatic Imports
+ *
+ * Imports CSVs as they land in ~/.treeline/imports/, using the import profile
+ * assigned to the matching account. The Rust file watcher does the importing;
+ * this listens for the results, toasts them and refreshes views.
+ */
+
+import { commands } from "../sdk/bindings";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import { registry } from "../sdk/registry";
+import { toast } from "../sdk/toast.svelte";
+import type { FolderImportFile } from "../sdk/settings";
+
+// Event listener cleanup
+let unlisteners: UnlistenFn[] = [];
+
+/**
+ * Start automatic imports: watch the imports folder and listen for results.
+ */
+export async function startAutoImport(): Promise<void> {
+  if (unlisteners.length > 0) return;
+
+  await commands.watchImportsDir();
+
+  unlisteners = await Promise.all([
+    listen<FolderImportFile>("import-completed", (event) => {
+      const file = event.payload;
+      toast.success(
+        `Imported ${file.file}`,
+        `${file.imported} transactions into ${file.account_name}` +
+          (file.skipped > 0 ? ` (${file.skipped} skipped)` : ""),
+      );
+      registry.emit("data:refresh");
+    }),
+    listen<FolderImportFile>("import-failed", (event) => {
+      const file = event.payload;
+      toast.error(`Couldn't import ${file.file}`, file.message ?? undefined);
+    }),
+  ]);
+}
+
+/**
+ * Stop automatic imports: stop the file watcher and remove event listeners.
+ */
+export async function stopAutoImport(): Promise<void> {
+  for (const unlisten of unlisteners) unlisten();
+  unlisteners = [];
+
+  try {
+    await commands.unwatchImportsDir();
+  } catch (error) {
+    console.error("[auto-import] Failed to stop file watcher:", error);
+  }
+}
@@ -30,7 +30,7 @@ export const plugin: Plugin = {
     description: "View and manage financial accounts",
     author: "Treeline",
     permissions: {
-      read: ["accounts", "sys_accounts", "sys_balance_snapshots", "transactions"],
+      read: ["accounts", "sys_accounts", "sys_balance_snapshots", "balance_snapshots_daily", "transactions"],
       write: ["sys_accounts", "sys_balance_snapshots"],  // For delete operations
       schemaName: "plugin_accounts",
     },
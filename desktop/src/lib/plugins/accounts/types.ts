@@ -52,7 +52,8 @@ export interface AccountsConfig {
 // Default asset/liability mapping based on account_type
 export function getDefaultClassification(accountType: string | null): BalanceClassification {
   if (!accountType) return "asset";
-  const liabilityTypes = ["credit", "loan"];
+  // "credit" is the pre-taxonomy name for credit_card
+  const liabilityTypes = ["credit_card", "loan", "mortgage", "credit"];
   return liabilityTypes.includes(accountType.toLowerCase()) ? "liability" : "asset";
 }
 
@@ -5,7 +5,7 @@
  * Uses the Rust file watcher backend to detect changes in ~/.treeline/plugins/.
  */
 
-import { invoke } from "@tauri-apps/api/core";
+import { commands } from "../sdk/bindings";
 import { convertFileSrc } from "@tauri-apps/api/core";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import { registry } from "../sdk/registry";
@@ -33,7 +33,7 @@ export function trackActivePlugin(pluginId: string, plugin: Plugin): void {
  */
 export async function startHotReload(): Promise<void> {
   // Start the file watcher on the Rust side
-  await invoke("watch_plugins_dir");
+  await commands.watchPluginsDir();
 
   // Listen for change events from the backend
   unlisten = await listen<string>("plugin-file-changed", async (event) => {
@@ -60,7 +60,7 @@ export async function stopHotReload(): Promise<void> {
   }
 
   try {
-    await invoke("unwatch_plugins_dir");
+    await commands.unwatchPluginsDir();
   } catch (error) {
     console.error("[hot-reload] Failed to stop file watcher:", error);
   }
@@ -95,7 +95,7 @@ async function reloadPlugin(pluginId: string): Promise<void> {
   activePlugins.delete(pluginId);
 
   // 4. Re-discover plugins to get fresh manifest from disk
-  const discovered = await invoke<ExternalPluginInfo[]>("discover_plugins");
+  const discovered = (await commands.discoverPlugins()) as ExternalPluginInfo[];
   const pluginInfo = discovered.find((p) => p.manifest.id === pluginId);
 
   if (!pluginInfo) {
@@ -109,7 +109,7 @@ async function reloadPlugin(pluginId: string): Promise<void> {
   }
 
   // 5. Re-import the JS module with cache-busting query parameter
-  const pluginsDir = await invoke<string>("get_plugins_dir");
+  const pluginsDir = await commands.getPluginsDir();
   const pluginPath = `${pluginsDir}/${pluginInfo.manifest.id}/${pluginInfo.manifest.main}`;
   const assetUrl = convertFileSrc(pluginPath);
   const cacheBustedUrl = `${assetUrl}?t=${Date.now()}`;
@@ -5,7 +5,7 @@
  * External plugins are loaded dynamically from ~/.treeline/plugins/
  */
 
-import { invoke } from "@tauri-apps/api/core";
+import { commands } from "../sdk/bindings";
 import { convertFileSrc } from "@tauri-apps/api/core";
 import { registry, themeManager, getDisabledPlugins, getAppSetting, executeQuery, executeQueryWithParams } from "../sdk";
 import type { Plugin, PluginContext, PluginMigration } from "../sdk/types";
@@ -123,10 +123,10 @@ async function runPluginMigrations(
 async function loadExternalPlugins(): Promise<LoadedExternalPlugin[]> {
   try {
     // Get the plugins directory path
-    const pluginsDir = await invoke<string>("get_plugins_dir");
+    const pluginsDir = await commands.getPluginsDir();
 
     // Discover all available plugins (reads manifest.json files)
-    const discovered = await invoke<ExternalPluginInfo[]>("discover_plugins");
+    const discovered = (await commands.discoverPlugins()) as ExternalPluginInfo[];
     const plugins: LoadedExternalPlugin[] = [];
 
     for (const pluginInfo of discovered) {
@@ -17,6 +17,10 @@ export interface Transaction {
   parent_transaction_id?: string | null;
   /** True if any tags on this transaction were applied by auto-tag rules */
   tags_auto_applied?: boolean;
+  /** Left out of reports, budgets and income detection */
+  excluded_from_reports?: boolean;
+  /** Not posted by the bank yet */
+  pending?: boolean;
 }
 
 export interface TagSuggestion {
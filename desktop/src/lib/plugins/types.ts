@@ -3,16 +3,15 @@
  */
 
 import type { Plugin } from "../sdk/types";
+import type { ExternalPlugin, PluginManifest } from "../sdk/bindings";
 
-export interface ExternalPluginInfo {
-  manifest: {
-    id: string;
-    name: string;
-    version: string;
-    description: string;
-    author: string;
-    main: string;
-    permissions?: {
+/**
+ * A plugin found by `discover_plugins`, with its free-form `permissions`
+ * JSON narrowed to the shapes manifests use.
+ */
+export interface ExternalPluginInfo extends Omit<ExternalPlugin, "manifest"> {
+  manifest: Omit<PluginManifest, "permissions"> & {
+    permissions: {
       tables?: {
         read?: string[];
         write?: string[];
@@ -23,9 +22,8 @@ export interface ExternalPluginInfo {
       write?: string[];
       create?: string[];
       schemaName?: string;
-    };
+    } | null;
   };
-  path: string;
 }
 
 export interface LoadedExternalPlugin {
@@ -2,7 +2,7 @@
  * API interface to Tauri backend
  */
 
-import { invoke } from "@tauri-apps/api/core";
+import { commands, type PluginContext } from "./bindings";
 import { logger } from "./logging";
 
 export interface QueryResult {
@@ -11,19 +11,17 @@ export interface QueryResult {
   row_count: number;
 }
 
-/**
- * Plugin context for permission validation in Rust
- */
-export interface PluginContext {
-  plugin_id: string;
-  plugin_schema: string;
-  allowed_reads: string[];
-  allowed_writes: string[];
-}
+export type { PluginContext };
 
 export interface ExecuteQueryOptions {
   readonly?: boolean;
   pluginContext?: PluginContext;
+  /**
+   * View or feature running the query (e.g. "accounts"), charged with its time
+   * in diagnostics. Plugin queries are always charged to the plugin; untagged
+   * ones to "user".
+   */
+  caller?: string;
 }
 
 /**
@@ -32,10 +30,10 @@ export interface ExecuteQueryOptions {
  * @param options.readonly If true (default), opens read-only connection. Set to false for writes.
  */
 export async function executeQuery(query: string, options: ExecuteQueryOptions = {}): Promise<QueryResult> {
-  const { readonly = true } = options;
+  const { readonly = true, caller } = options;
 
   try {
-    const jsonString = await invoke<string>("execute_query", { query, readonly });
+    const jsonString = await commands.executeQuery(query, readonly, caller ?? null);
 
     // Parse JSON string from Rust backend
     const response = JSON.parse(jsonString);
@@ -89,15 +87,16 @@ export async function executeQueryWithParams(
   params: QueryParam[] = [],
   options: ExecuteQueryOptions = {}
 ): Promise<QueryResult> {
-  const { readonly = true, pluginContext } = options;
+  const { readonly = true, pluginContext, caller } = options;
 
   try {
-    const jsonString = await invoke<string>("execute_query_with_params", {
+    const jsonString = await commands.executeQueryWithParams(
       query,
       params,
       readonly,
-      pluginContext: pluginContext ?? null
-    });
+      pluginContext ?? null,
+      caller ?? null
+    );
 
     // Parse JSON string from Rust backend
     const response = JSON.parse(jsonString);
@@ -118,6 +117,38 @@ export async function executeQueryWithParams(
   }
 }
 
+/**
+ * A natural-language question translated to SQL, with its results
+ */
+export interface NlQueryResult {
+  /** Template that matched, e.g. "spending_on_tag" */
+  template: string;
+  /** How the question was understood, to show the user for confirmation */
+  interpretation: string;
+  sql: string;
+  params: QueryParam[];
+  result: QueryResult;
+}
+
+/**
+ * Answer a question like "how much did I spend on groceries last month".
+ * Only a fixed set of question shapes is understood (translated locally by
+ * templates); unrecognized questions throw with example questions.
+ */
+export async function translateNlQuery(question: string): Promise<NlQueryResult> {
+  try {
+    const jsonString = await commands.translateNlQuery(question);
+    return JSON.parse(jsonString) as NlQueryResult;
+  } catch (e) {
+    // The question may contain user data, so only the error is logged
+    logger.error("nl_query_error", typeof e === "string" ? e : "Question could not be answered");
+    if (typeof e === "string") {
+      throw new Error(e);
+    }
+    throw e;
+  }
+}
+
 /**
  * Database helper object with convenience methods for parameterized queries.
  * Always use these methods instead of string interpolation to prevent SQL injection.
@@ -162,7 +193,7 @@ export const db = {
  */
 export async function deleteAccount(accountId: string): Promise<void> {
   try {
-    await invoke("delete_account", { accountId });
+    await commands.deleteAccount(accountId);
   } catch (e) {
     if (typeof e === 'string') {
       throw new Error(e);
@@ -0,0 +1,1111 @@
+// @ts-nocheck
+
+// This file was generated by [tauri-specta](https://github.com/oscartbeaumont/tauri-specta). Do not edit this file manually.
+
+/** user-defined commands **/
+
+
+export const commands = {
+async discoverPlugins() : Promise<ExternalPlugin[]> {
+    return await TAURI_INVOKE("discover_plugins");
+},
+async getPluginsDir() : Promise<string> {
+    return await TAURI_INVOKE("get_plugins_dir");
+},
+async getTreelineDirDisplay() : Promise<string> {
+    return await TAURI_INVOKE("get_treeline_dir_display");
+},
+/**
+ * Execute a SQL query using treeline-core
+ * All database access now goes through TreelineContext for unified connection management
+ * Uses spawn_blocking to avoid blocking the UI thread
+ */
+async executeQuery(query: string, readonly: boolean | null, caller: string | null) : Promise<string> {
+    return await TAURI_INVOKE("execute_query", { query, readonly, caller });
+},
+/**
+ * Execute a parameterized SQL query using treeline-core - SAFE from SQL injection
+ * Parameters are bound using ? placeholders
+ * If plugin_context is provided, validates query permissions before execution
+ * Uses spawn_blocking to avoid blocking the UI thread
+ */
+async executeQueryWithParams(query: string, params: JsonValue[], readonly: boolean | null, pluginContext: PluginContext | null, caller: string | null) : Promise<string> {
+    return await TAURI_INVOKE("execute_query_with_params", { query, params, readonly, pluginContext, caller });
+},
+/**
+ * Answer a natural-language question using treeline-core's local query templates
+ * Returns the interpretation, generated SQL and params alongside the results
+ * so the user can confirm what was run. Always read-only.
+ */
+async translateNlQuery(question: string) : Promise<string> {
+    return await TAURI_INVOKE("translate_nl_query", { question });
+},
+async readPluginConfig(pluginId: string, filename: string) : Promise<string> {
+    return await TAURI_INVOKE("read_plugin_config", { pluginId, filename });
+},
+async writePluginConfig(pluginId: string, filename: string, content: string) : Promise<null> {
+    return await TAURI_INVOKE("write_plugin_config", { pluginId, filename, content });
+},
+/**
+ * Read the unified settings.json file
+ */
+async readSettings() : Promise<string> {
+    return await TAURI_INVOKE("read_settings");
+},
+/**
+ * Write the unified settings.json file
+ */
+async writeSettings(content: string) : Promise<null> {
+    return await TAURI_INVOKE("write_settings", { content });
+},
+/**
+ * Get what the startup actions did; `finished` is false while they are still running
+ */
+async getStartupReport() : Promise<StartupReport> {
+    return await TAURI_INVOKE("get_startup_report");
+},
+/**
+ * Get the sync schedule and when the next scheduled sync is due
+ */
+async getSyncSchedule() : Promise<SyncScheduleStatus> {
+    return await TAURI_INVOKE("get_sync_schedule");
+},
+/**
+ * Read plugin-specific state file (for runtime state, not user settings)
+ */
+async readPluginState(pluginId: string) : Promise<string> {
+    return await TAURI_INVOKE("read_plugin_state", { pluginId });
+},
+/**
+ * Write plugin-specific state file (for runtime state, not user settings)
+ */
+async writePluginState(pluginId: string, content: string) : Promise<null> {
+    return await TAURI_INVOKE("write_plugin_state", { pluginId, content });
+},
+/**
+ * Read plugin state with its schema version, as `{"schemaVersion", "state"}` JSON
+ *
+ * State written by `write_plugin_state` is version 0.
+ */
+async readPluginStateVersioned(pluginId: string) : Promise<string> {
+    return await TAURI_INVOKE("read_plugin_state_versioned", { pluginId });
+},
+/**
+ * Write plugin state at a schema version
+ *
+ * With `expected_version`, writes only if the stored version still matches
+ * and returns false otherwise, so a migration is applied once.
+ */
+async writePluginStateVersioned(pluginId: string, content: string, version: number, expectedVersion: number | null) : Promise<boolean> {
+    return await TAURI_INVOKE("write_plugin_state_versioned", { pluginId, content, version, expectedVersion });
+},
+/**
+ * Run sync using treeline-core SyncService directly
+ * Uses spawn_blocking to avoid blocking the UI thread
+ * Creates a backup before syncing to protect against sync issues
+ * 
+ * Syncs every integration, or only `integration` (e.g. "simplefin") when given.
+ */
+async runSync(dryRun: boolean | null, balancesOnly: boolean | null, integration: string | null) : Promise<string> {
+    return await TAURI_INVOKE("run_sync", { dryRun, balancesOnly, integration });
+},
+/**
+ * Get recorded sync runs, newest first
+ * With `accounts`, returns per-account activity (last sync, last new data) instead
+ */
+async getSyncHistory(integration: string | null, limit: number | null, accounts: boolean | null) : Promise<string> {
+    return await TAURI_INVOKE("get_sync_history", { integration, limit, accounts });
+},
+/**
+ * Get the device sync relay and how far behind each other device this one is
+ * Returns JSON; `null` until `setup_device_sync` has run
+ */
+async getDeviceSyncStatus() : Promise<string> {
+    return await TAURI_INVOKE("get_device_sync_status");
+},
+/**
+ * Create or join a device sync relay folder
+ * Returns JSON: the relay and device ID saved in settings
+ */
+async setupDeviceSync(relay: string, passphrase: string, deviceName: string | null) : Promise<string> {
+    return await TAURI_INVOKE("setup_device_sync", { relay, passphrase, deviceName });
+},
+/**
+ * Send local changes to the relay, then apply what the other devices sent
+ * This is how the mobile companion gets its data; bank syncs run on the desktop.
+ * Returns JSON: counts of the changes pushed and applied
+ */
+async runDeviceSync(passphrase: string) : Promise<string> {
+    return await TAURI_INVOKE("run_device_sync", { passphrase });
+},
+/**
+ * Get current demo mode status from settings.json
+ */
+async getDemoMode() : Promise<boolean> {
+    return await TAURI_INVOKE("get_demo_mode");
+},
+/**
+ * Set demo mode in settings.json (shared with CLI)
+ */
+async setDemoMode(enabled: boolean) : Promise<null> {
+    return await TAURI_INVOKE("set_demo_mode", { enabled });
+},
+/**
+ * Enable demo mode (sets up demo integration and syncs demo data)
+ * Uses treeline-core DemoService directly instead of CLI subprocess
+ */
+async enableDemo() : Promise<null> {
+    return await TAURI_INVOKE("enable_demo");
+},
+/**
+ * Disable demo mode
+ * Uses treeline-core DemoService directly instead of CLI subprocess
+ */
+async disableDemo() : Promise<null> {
+    return await TAURI_INVOKE("disable_demo");
+},
+/**
+ * Install a plugin from GitHub URL using treeline-core
+ */
+async installPlugin(url: string, version: string | null) : Promise<string> {
+    return await TAURI_INVOKE("install_plugin", { url, version });
+},
+/**
+ * Uninstall a plugin using treeline-core
+ */
+async uninstallPlugin(pluginId: string) : Promise<string> {
+    return await TAURI_INVOKE("uninstall_plugin", { pluginId });
+},
+/**
+ * Upgrade a plugin to latest version using treeline-core
+ *
+ * The frontend creates a database backup via createBackup() before
+ * calling this command, protecting against breaking schema migrations.
+ */
+async upgradePlugin(pluginId: string) : Promise<string> {
+    return await TAURI_INVOKE("upgrade_plugin", { pluginId });
+},
+/**
+ * Check if a plugin has an update available using treeline-core
+ */
+async checkPluginUpdate(pluginId: string) : Promise<string> {
+    return await TAURI_INVOKE("check_plugin_update", { pluginId });
+},
+/**
+ * Fetch plugin manifest from GitHub release (for install preview) using treeline-core
+ */
+async fetchPluginManifest(url: string, version: string | null) : Promise<string> {
+    return await TAURI_INVOKE("fetch_plugin_manifest", { url, version });
+},
+/**
+ * Preview CSV import using treeline-core ImportService
+ * Returns JSON with detected columns and preview transactions
+ * Format matches frontend ImportPreviewResult interface
+ * Uses spawn_blocking to avoid blocking the UI thread
+ */
+async importCsvPreview(filePath: string, accountId: string, dateColumn: string | null, amountColumn: string | null, descriptionColumn: string | null, debitColumn: string | null, creditColumn: string | null, balanceColumn: string | null, flipSigns: boolean, debitNegative: boolean, skipRows: number | null, numberFormat: string | null, anchorBalance: string | null, anchorDate: string | null, columnNumberFormats: { [key in string]: string } | null, accountColumn: string | null, accountMap: { [key in string]: string } | null, createMissingAccounts: boolean | null, categoryColumn: string | null, tagsColumn: string | null, checkNumberColumn: string | null, referenceColumn: string | null, directionColumn: string | null, currencyColumn: string | null, categoryMap: { [key in string]: string } | null, dateFormat: string | null, noHeader: boolean | null) : Promise<string> {
+    return await TAURI_INVOKE("import_csv_preview", { filePath, accountId, dateColumn, amountColumn, descriptionColumn, debitColumn, creditColumn, balanceColumn, flipSigns, debitNegative, skipRows, numberFormat, anchorBalance, anchorDate, columnNumberFormats, accountColumn, accountMap, createMissingAccounts, categoryColumn, tagsColumn, checkNumberColumn, referenceColumn, directionColumn, currencyColumn, categoryMap, dateFormat, noHeader });
+},
+/**
+ * Execute CSV import using treeline-core ImportService
+ * Uses spawn_blocking to avoid blocking the UI thread
+ */
+async importCsvExecute(filePath: string, accountId: string, dateColumn: string | null, amountColumn: string | null, descriptionColumn: string | null, debitColumn: string | null, creditColumn: string | null, balanceColumn: string | null, flipSigns: boolean, debitNegative: boolean, skipRows: number | null, numberFormat: string | null, columnNumberFormats: { [key in string]: string } | null, accountColumn: string | null, accountMap: { [key in string]: string } | null, createMissingAccounts: boolean | null, categoryColumn: string | null, tagsColumn: string | null, checkNumberColumn: string | null, referenceColumn: string | null, directionColumn: string | null, currencyColumn: string | null, categoryMap: { [key in string]: string } | null, dateFormat: string | null, noHeader: boolean | null) : Promise<string> {
+    return await TAURI_INVOKE("import_csv_execute", { filePath, accountId, dateColumn, amountColumn, descriptionColumn, debitColumn, creditColumn, balanceColumn, flipSigns, debitNegative, skipRows, numberFormat, columnNumberFormats, accountColumn, accountMap, createMissingAccounts, categoryColumn, tagsColumn, checkNumberColumn, referenceColumn, directionColumn, currencyColumn, categoryMap, dateFormat, noHeader });
+},
+/**
+ * Import CSV text, such as a table pasted from a bank's website, without
+ * saving it to a file first
+ * 
+ * `mappings` is column mappings JSON and `options` import options JSON, both
+ * as in an import profile (`options` may also carry `skipRows` and
+ * `dateFormat`). Returns the ImportResult as JSON; with `preview_only` set it
+ * includes the transaction previews and nothing is written.
+ */
+async importCsvFromText(content: string, accountId: string, mappings: string, options: string, previewOnly: boolean) : Promise<string> {
+    return await TAURI_INVOKE("import_csv_from_text", { content, accountId, mappings, options, previewOnly });
+},
+/**
+ * Check a whole CSV file before importing it, so problems show up front
+ * instead of as skipped rows afterwards
+ * 
+ * `mappings` and `options` are JSON as for `import_csv_from_text`. Returns
+ * the ImportValidation as JSON; nothing is written.
+ */
+async validateCsvImport(filePath: string, mappings: string, options: string) : Promise<string> {
+    return await TAURI_INVOKE("validate_csv_import", { filePath, mappings, options });
+},
+/**
+ * Undo an import by its batch ID, deleting the transactions and balance
+ * snapshots it created
+ * 
+ * Returns JSON: the batch ID and how many transactions and snapshots were deleted.
+ */
+async undoImport(batchId: string) : Promise<string> {
+    return await TAURI_INVOKE("undo_import", { batchId });
+},
+/**
+ * Find likely duplicate transactions that exact dedup missed
+ * 
+ * Returns JSON array of DuplicateCandidate, newest first. Pairs already
+ * merged or dismissed are left out.
+ */
+async findDuplicates(maxDays: number | null, minSimilarity: number | null) : Promise<string> {
+    return await TAURI_INVOKE("find_duplicates", { maxDays, minSimilarity });
+},
+/**
+ * Resolve a likely duplicate by deleting one side; its tags move to the kept one
+ * 
+ * Returns JSON of the kept transaction.
+ */
+async mergeDuplicates(keepId: string, removeId: string) : Promise<string> {
+    return await TAURI_INVOKE("merge_duplicates", { keepId, removeId });
+},
+/**
+ * Mark two transactions as not duplicates, so they aren't suggested again
+ */
+async dismissDuplicates(firstId: string, secondId: string) : Promise<null> {
+    return await TAURI_INVOKE("dismiss_duplicates", { firstId, secondId });
+},
+/**
+ * Open file picker dialog for CSV files and PDF statements
+ */
+async pickCsvFile() : Promise<string | null> {
+    return await TAURI_INVOKE("pick_csv_file");
+},
+/**
+ * Read a PDF statement's transaction table into a CSV file, so the import
+ * dialog can map and preview it like any other
+ * 
+ * Returns the CSV's path. Its columns are Date, Description, Amount and,
+ * when the statement shows a running balance, Balance.
+ */
+async convertPdfStatement(filePath: string) : Promise<string> {
+    return await TAURI_INVOKE("convert_pdf_statement", { filePath });
+},
+/**
+ * Get CSV headers for column mapping, along with the sniffed dialect
+ * (delimiter, encoding, quoting style).
+ * Supports skip_rows to skip leading non-header rows (e.g., bank letterhead).
+ * With no_header the headers are synthetic "Column 1".."Column N" names.
+ */
+async getCsvHeaders(filePath: string, skipRows: number | null, noHeader: boolean | null) : Promise<CsvHeaders> {
+    return await TAURI_INVOKE("get_csv_headers", { filePath, skipRows, noHeader });
+},
+/**
+ * Recognize the bank a CSV export came from (Chase, Amex, Wise, N26, DKB, ...)
+ *
+ * Returns JSON: the preset id and name, the header row's position and an
+ * import profile with its columns, date format and sign convention, or null
+ * when no preset fits.
+ */
+async detectImportPreset(filePath: string) : Promise<string> {
+    return await TAURI_INVOKE("detect_import_preset", { filePath });
+},
+/**
+ * List saved import profiles, the same ones `tl import --profile` uses
+ *
+ * Returns JSON: an object of profile name to profile.
+ */
+async listImportProfiles() : Promise<string> {
+    return await TAURI_INVOKE("list_import_profiles");
+},
+/**
+ * Save an import profile (JSON, as returned by list_import_profiles),
+ * replacing any with the same name
+ */
+async saveImportProfile(name: string, profile: string) : Promise<null> {
+    return await TAURI_INVOKE("save_import_profile", { name, profile });
+},
+/**
+ * Delete a saved import profile, returning whether it existed
+ */
+async deleteImportProfile(name: string) : Promise<boolean> {
+    return await TAURI_INVOKE("delete_import_profile", { name });
+},
+/**
+ * List CSV files waiting in the imports folder
+ */
+async listPendingImports() : Promise<PendingImportFile[]> {
+    return await TAURI_INVOKE("list_pending_imports");
+},
+/**
+ * Move an imported file to the "imported" subfolder
+ */
+async moveImportedFile(filePath: string) : Promise<null> {
+    return await TAURI_INVOKE("move_imported_file", { filePath });
+},
+/**
+ * Import every file in the imports folder into the account whose assigned
+ * import profile matches it (by account name in the file name, or columns),
+ * moving imported files to the "imported" subfolder
+ *
+ * Returns JSON: one entry per file (imported, unmatched or failed, with the
+ * account, profile and counts) and totals.
+ */
+async importAllPending() : Promise<string> {
+    return await TAURI_INVOKE("import_all_pending");
+},
+/**
+ * Start watching the imports folder, importing each new CSV into the account
+ * whose assigned import profile matches it (as import_all_pending does).
+ * Emits "import-completed" with the file's result when it was imported, and
+ * "import-failed" when it matched no account or the import failed; those
+ * files stay in the folder.
+ */
+async watchImportsDir() : Promise<null> {
+    return await TAURI_INVOKE("watch_imports_dir");
+},
+/**
+ * Stop watching the imports folder.
+ */
+async unwatchImportsDir() : Promise<null> {
+    return await TAURI_INVOKE("unwatch_imports_dir");
+},
+/**
+ * Setup SimpleFIN integration using treeline-core SyncService
+ */
+async setupSimplefin(token: string) : Promise<string> {
+    return await TAURI_INVOKE("setup_simplefin", { token });
+},
+/**
+ * Setup Lunchflow integration using treeline-core SyncService
+ *
+ * Lunchflow is a multi-provider bank aggregator supporting global banks
+ * (20,000+ banks across 40+ countries).
+ */
+async setupLunchflow(apiKey: string, baseUrl: string | null) : Promise<string> {
+    return await TAURI_INVOKE("setup_lunchflow", { apiKey, baseUrl });
+},
+/**
+ * Create a link token for opening Plaid Link, whose public token
+ * `setup_plaid` then takes
+ */
+async createPlaidLinkToken(clientId: string, secret: string, environment: string, baseUrl: string | null) : Promise<string> {
+    return await TAURI_INVOKE("create_plaid_link_token", { clientId, secret, environment, baseUrl });
+},
+/**
+ * Link a bank through Plaid using treeline-core SyncService
+ * 
+ * Takes the public token Plaid Link hands back. Each bank is linked
+ * separately and joins the banks already linked.
+ */
+async setupPlaid(clientId: string, secret: string, environment: string, publicToken: string, baseUrl: string | null) : Promise<string> {
+    return await TAURI_INVOKE("setup_plaid", { clientId, secret, environment, publicToken, baseUrl });
+},
+/**
+ * List the banks GoCardless can connect to in a country (ISO code, e.g. "DE")
+ * 
+ * Returns JSON array of GoCardlessInstitution
+ */
+async listGocardlessInstitutions(secretId: string, secretKey: string, country: string, baseUrl: string | null) : Promise<string> {
+    return await TAURI_INVOKE("list_gocardless_institutions", { secretId, secretKey, country, baseUrl });
+},
+/**
+ * Start connecting a bank through GoCardless using treeline-core SyncService
+ * 
+ * Returns the link where the user gives consent at their bank; accounts
+ * sync once they have. Connecting a bank again renews its access.
+ */
+async setupGocardless(secretId: string, secretKey: string, institutionId: string, redirectUrl: string | null, baseUrl: string | null) : Promise<string> {
+    return await TAURI_INVOKE("setup_gocardless", { secretId, secretKey, institutionId, redirectUrl, baseUrl });
+},
+/**
+ * Set up Coinbase integration using treeline-core SyncService
+ * 
+ * Syncs each wallet's balance, valued in `currency` (None = the Coinbase
+ * account's currency), and its transactions.
+ */
+async setupCoinbase(apiKey: string, apiSecret: string, currency: string | null, baseUrl: string | null) : Promise<string> {
+    return await TAURI_INVOKE("setup_coinbase", { apiKey, apiSecret, currency, baseUrl });
+},
+/**
+ * Set up Kraken integration using treeline-core SyncService
+ * 
+ * Syncs each asset's balance, valued in `currency` (None = USD). Kraken has
+ * no fiat values for its ledger, so there are no transactions.
+ */
+async setupKraken(apiKey: string, apiSecret: string, currency: string | null, baseUrl: string | null) : Promise<string> {
+    return await TAURI_INVOKE("setup_kraken", { apiKey, apiSecret, currency, baseUrl });
+},
+/**
+ * Replace an integration's credentials (SimpleFIN setup token, Lunchflow
+ * API key, Plaid secret or GoCardless secret key), keeping its account settings
+ */
+async rotateIntegration(name: string, credential: string) : Promise<null> {
+    return await TAURI_INVOKE("rotate_integration", { name, credential });
+},
+/**
+ * List the accounts an integration's provider offers and whether each syncs
+ * 
+ * Includes accounts that were never synced or are turned off.
+ * Returns JSON array of RemoteAccount
+ */
+async listIntegrationAccounts(name: string) : Promise<string> {
+    return await TAURI_INVOKE("list_integration_accounts", { name });
+},
+/**
+ * Include or leave out one of an integration's accounts when syncing
+ * 
+ * `external_id` is the provider's account ID. A turned-off account is
+ * neither created nor updated by sync.
+ */
+async setIntegrationAccountEnabled(name: string, externalId: string, enabled: boolean) : Promise<null> {
+    return await TAURI_INVOKE("set_integration_account_enabled", { name, externalId, enabled });
+},
+/**
+ * Remove an integration and its stored credentials
+ * 
+ * With `unlink_accounts`, its accounts become standalone accounts.
+ * Returns the number of accounts unlinked.
+ */
+async removeIntegration(name: string, unlinkAccounts: boolean) : Promise<number> {
+    return await TAURI_INVOKE("remove_integration", { name, unlinkAccounts });
+},
+/**
+ * Preview balance backfill - shows what snapshots would be created/updated
+ * Returns a list of calculated end-of-day balances without persisting them
+ * Uses spawn_blocking to avoid blocking the UI thread
+ */
+async backfillPreview(accountId: string, knownBalance: string, knownDate: string, startDate: string | null, endDate: string | null) : Promise<BalanceSnapshotPreview[]> {
+    return await TAURI_INVOKE("backfill_preview", { accountId, knownBalance, knownDate, startDate, endDate });
+},
+/**
+ * Execute balance backfill - creates/updates balance snapshots
+ * Replaces all existing snapshots in range with calculated values
+ * Uses spawn_blocking to avoid blocking the UI thread
+ */
+async backfillExecute(accountId: string, knownBalance: string, knownDate: string, startDate: string | null, endDate: string | null) : Promise<BackfillExecuteResult> {
+    return await TAURI_INVOKE("backfill_execute", { accountId, knownBalance, knownDate, startDate, endDate });
+},
+/**
+ * List all backups
+ */
+async listBackups() : Promise<string> {
+    return await TAURI_INVOKE("list_backups");
+},
+/**
+ * Create a new backup
+ */
+async createBackup(maxBackups: number | null) : Promise<string> {
+    return await TAURI_INVOKE("create_backup", { maxBackups });
+},
+/**
+ * Restore from a backup
+ */
+async restoreBackup(backupName: string) : Promise<null> {
+    return await TAURI_INVOKE("restore_backup", { backupName });
+},
+/**
+ * Delete a backup
+ */
+async deleteBackup(backupName: string) : Promise<null> {
+    return await TAURI_INVOKE("delete_backup", { backupName });
+},
+/**
+ * Clear all backups
+ */
+async clearBackups() : Promise<string> {
+    return await TAURI_INVOKE("clear_backups");
+},
+/**
+ * Name of the cloud-sync service (iCloud Drive, Dropbox, ...) the data
+ * directory is inside, or null when it is safe
+ */
+async getCloudSyncProvider() : Promise<string | null> {
+    return await TAURI_INVOKE("get_cloud_sync_provider");
+},
+/**
+ * Move all data to a new (empty) folder, leaving a pointer in the old one
+ *
+ * Releases the database and stops the plugin watcher first. The app should
+ * restart afterwards so nothing keeps using the old location.
+ */
+async moveDataDirectory(destination: string, allowCloudSync: boolean) : Promise<string> {
+    return await TAURI_INVOKE("move_data_directory", { destination, allowCloudSync });
+},
+/**
+ * Compact the database (CHECKPOINT + VACUUM)
+ */
+async compactDatabase() : Promise<string> {
+    return await TAURI_INVOKE("compact_database");
+},
+/**
+ * Space used by tables, logs, backups and archives, with suggestions for
+ * freeing some (compact, archive a year, prune history, clear logs)
+ */
+async getStorageBreakdown() : Promise<string> {
+    return await TAURI_INVOKE("get_storage_breakdown");
+},
+/**
+ * Empty history tables (every prunable table when `tables` is empty)
+ */
+async pruneStorage(tables: string[]) : Promise<string> {
+    return await TAURI_INVOKE("prune_storage", { tables });
+},
+/**
+ * Get the banks accounts belong to, with cached logos as data URLs (JSON)
+ * 
+ * Reads the local cache only; call `refresh_institution_logos` to fetch
+ * logos that aren't cached yet.
+ */
+async getInstitutions() : Promise<string> {
+    return await TAURI_INVOKE("get_institutions");
+},
+/**
+ * Fetch logos for banks without one cached, from each bank's own site
+ * 
+ * With `force`, every logo is fetched again. Returns JSON: fetched and
+ * failed domains, and how many were skipped.
+ */
+async refreshInstitutionLogos(force: boolean) : Promise<string> {
+    return await TAURI_INVOKE("refresh_institution_logos", { force });
+},
+/**
+ * Get holdings in brokerage accounts, for one account or all (JSON)
+ * 
+ * Returns JSON: each holding with its account, quantity, price, market
+ * value, cost basis and gain, plus total value and gain by currency.
+ */
+async getHoldings(accountId: string | null) : Promise<string> {
+    return await TAURI_INVOKE("get_holdings", { accountId });
+},
+/**
+ * Get database, backup and log health for the diagnostics screen
+ *
+ * Returns JSON: file and WAL sizes, lock status, table row counts, last
+ * migration, backup freshness, log error counts, context age and query time
+ * by view and plugin.
+ */
+async getDiagnostics() : Promise<string> {
+    return await TAURI_INVOKE("get_diagnostics");
+},
+/**
+ * Save a printable HTML statement for one account and month ("YYYY-MM")
+ *
+ * Asks where to save it; returns the path, or None if the dialog was cancelled.
+ */
+async exportStatement(accountId: string, month: string) : Promise<string | null> {
+    return await TAURI_INVOKE("export_statement", { accountId, month });
+},
+/**
+ * Compare transaction totals between two periods, per category or account
+ *
+ * Periods are "2025", "2025-Q2", "2025-03", "2025-03-14" or "START..END";
+ * `previous` defaults to the period just before `current`. `group_by` is
+ * "category" or "account". Returns JSON with amount and percent changes.
+ */
+async comparePeriods(current: string, previous: string | null, groupBy: string) : Promise<string> {
+    return await TAURI_INVOKE("compare_periods", { current, previous, groupBy });
+},
+/**
+ * Income, spending by category and spending by merchant for a period
+ *
+ * `period` takes the same formats as `compare_periods`. Returns JSON nodes
+ * and weighted links (income sources -> income -> categories -> merchants)
+ * for a Sankey diagram.
+ */
+async getCashFlows(period: string) : Promise<string> {
+    return await TAURI_INVOKE("get_cash_flows", { period });
+},
+/**
+ * List the actions the command palette can offer right now
+ *
+ * Covers core commands that apply to the current setup (sync only with an
+ * integration, lock only when encrypted), views, per-account statements,
+ * saved queries and commands declared in plugin manifests. Database-backed
+ * entries are left out while the database is locked.
+ */
+async listCommands() : Promise<PaletteAction[]> {
+    return await TAURI_INVOKE("list_commands");
+},
+/**
+ * List running and recently finished operations (sync, import, backup, ...), newest first
+ */
+async listOperations() : Promise<string> {
+    return await TAURI_INVOKE("list_operations");
+},
+/**
+ * Get one operation's status, or null if it is unknown
+ */
+async getOperationStatus(operationId: string) : Promise<string | null> {
+    return await TAURI_INVOKE("get_operation_status", { operationId });
+},
+/**
+ * Ask a running operation to stop at its next checkpoint
+ *
+ * Returns false if the operation already finished.
+ */
+async cancelOperation(operationId: string) : Promise<boolean> {
+    return await TAURI_INVOKE("cancel_operation", { operationId });
+},
+/**
+ * Get encryption status - checks if database is encrypted and if we have a key
+ */
+async getEncryptionStatus() : Promise<EncryptionStatus> {
+    return await TAURI_INVOKE("get_encryption_status");
+},
+/**
+ * Try to auto-unlock using keychain key (called on app startup)
+ */
+async tryAutoUnlock() : Promise<boolean> {
+    return await TAURI_INVOKE("try_auto_unlock");
+},
+/**
+ * Unlock database with password
+ */
+async unlockDatabase(password: string) : Promise<null> {
+    return await TAURI_INVOKE("unlock_database", { password });
+},
+/**
+ * Lock an encrypted database: forget the key and close the shared connection
+ *
+ * The key is wiped from memory, the cached context is dropped, and a
+ * `database-locked` event tells the frontend to clear its state and show the
+ * unlock screen. Commands fail until `unlock_database` is called again.
+ */
+async lockDatabase() : Promise<null> {
+    return await TAURI_INVOKE("lock_database");
+},
+/**
+ * Enable encryption using treeline-core EncryptionService
+ */
+async enableEncryption(password: string) : Promise<null> {
+    return await TAURI_INVOKE("enable_encryption", { password });
+},
+/**
+ * Disable encryption using treeline-core EncryptionService
+ */
+async disableEncryption(password: string) : Promise<null> {
+    return await TAURI_INVOKE("disable_encryption", { password });
+},
+/**
+ * List all available themes from ~/.treeline/themes/
+ */
+async listThemes() : Promise<ThemeDefinition[]> {
+    return await TAURI_INVOKE("list_themes");
+},
+/**
+ * Set DevTools visibility (for plugin development)
+ * If `open` is None, toggles the current state
+ * Note: We track state ourselves because is_devtools_open() and close_devtools()
+ * are not supported on Windows
+ */
+async setDevtools(open: boolean | null) : Promise<boolean> {
+    return await TAURI_INVOKE("set_devtools", { open });
+},
+/**
+ * Start watching the plugins directory for file changes (hot-reload).
+ * Emits "plugin-file-changed" events with the plugin ID when index.js or manifest.json change.
+ */
+async watchPluginsDir() : Promise<null> {
+    return await TAURI_INVOKE("watch_plugins_dir");
+},
+/**
+ * Stop watching the plugins directory.
+ */
+async unwatchPluginsDir() : Promise<null> {
+    return await TAURI_INVOKE("unwatch_plugins_dir");
+},
+/**
+ * Run database migrations using treeline-core
+ * Called on app startup to ensure schema is up to date
+ */
+async runMigrations() : Promise<null> {
+    return await TAURI_INVOKE("run_migrations");
+},
+/**
+ * Delete an account and all associated data (transactions, balance snapshots)
+ * This is a cascading delete - all transactions and snapshots for the account are removed
+ */
+async deleteAccount(accountId: string) : Promise<null> {
+    return await TAURI_INVOKE("delete_account", { accountId });
+},
+/**
+ * List accounts with their latest balance, sorted by name
+ * Returns JSON; archived accounts are left out unless `include_archived`
+ */
+async listAccounts(includeArchived: boolean | null) : Promise<string> {
+    return await TAURI_INVOKE("list_accounts", { includeArchived });
+},
+/**
+ * Set whether an account's source reports charges as positive amounts
+ * `policy` is "as_reported" or "inverted"; with `normalize`, the account's existing
+ * synced and imported transactions are flipped once to match.
+ * Returns JSON: the account plus how many transactions were flipped
+ */
+async setAccountSignPolicy(accountId: string, policy: string, normalize: boolean) : Promise<string> {
+    return await TAURI_INVOKE("set_account_sign_policy", { accountId, policy, normalize });
+},
+/**
+ * List transactions newest first, optionally for one account or matching `search`
+ * Returns JSON
+ */
+async listTransactions(accountId: string | null, search: string | null, limit: number | null) : Promise<string> {
+    return await TAURI_INVOKE("list_transactions", { accountId, search, limit });
+},
+/**
+ * Check for app updates with staging endpoint support.
+ *
+ * This command uses UpdaterBuilder to dynamically configure the endpoint
+ * based on whether staging updates are enabled (`~/.treeline/use-staging-updates`).
+ * The Update object is stored in app state for later download/install.
+ */
+async checkForAppUpdate() : Promise<AppUpdateInfo | null> {
+    return await TAURI_INVOKE("check_for_app_update");
+},
+/**
+ * Download and install the available update.
+ * Must call check_for_app_update first to find an available update.
+ * Creates a backup before updating to protect against update failures.
+ */
+async downloadAndInstallAppUpdate() : Promise<null> {
+    return await TAURI_INVOKE("download_and_install_app_update");
+},
+/**
+ * Log a page/view navigation from the frontend
+ * Privacy: Only logs the view name, never any user data
+ */
+async logPage(page: string) : Promise<null> {
+    return await TAURI_INVOKE("log_page", { page });
+},
+/**
+ * Log a user action from the frontend
+ * Privacy: Only logs action/component names, never any user data
+ */
+async logAction(action: string, component: string) : Promise<null> {
+    return await TAURI_INVOKE("log_action", { action, component });
+},
+/**
+ * Log an error from the frontend
+ * Privacy: Error messages should be sanitized by the frontend before logging
+ */
+async logError(event: string, message: string, details: string | null) : Promise<null> {
+    return await TAURI_INVOKE("log_error", { event, message, details });
+},
+/**
+ * Get the path to the logs database for support purposes
+ */
+async getLogsPath() : Promise<string | null> {
+    return await TAURI_INVOKE("get_logs_path");
+},
+/**
+ * Get the log levels from settings.json (JSON: level plus per-category overrides)
+ */
+async getLoggingSettings() : Promise<string> {
+    return await TAURI_INVOKE("get_logging_settings");
+},
+/**
+ * Save log levels to settings.json and apply them right away
+ *
+ * `settings` is JSON: {"level": "actions", "categories": {"ui": "errors"}}
+ */
+async setLoggingSettings(settings: string) : Promise<null> {
+    return await TAURI_INVOKE("set_logging_settings", { settings });
+},
+/**
+ * Delete all log history; returns the number of entries deleted
+ */
+async purgeLogs() : Promise<number> {
+    return await TAURI_INVOKE("purge_logs");
+},
+/**
+ * Get whether the anonymous usage ping is on and when it was last sent (JSON)
+ */
+async getUsagePingStatus() : Promise<string> {
+    return await TAURI_INVOKE("get_usage_ping_status");
+},
+/**
+ * Opt in to or out of the weekly anonymous usage ping
+ */
+async setUsagePingEnabled(enabled: boolean) : Promise<null> {
+    return await TAURI_INVOKE("set_usage_ping_enabled", { enabled });
+},
+/**
+ * Get exactly what the next usage ping would send (JSON), without sending it
+ */
+async previewUsagePing() : Promise<string> {
+    return await TAURI_INVOKE("preview_usage_ping");
+},
+}
+
+
+/** user-defined events **/
+
+
+
+/** user-defined constants **/
+
+
+
+/** user-defined types **/
+
+/**
+ * Response from check_for_app_update command
+ */
+export type AppUpdateInfo = { version: string; body: string | null; date: string | null }
+/**
+ * Result of executing balance backfill
+ */
+export type BackfillExecuteResult = { snapshots_created: number; snapshots_updated: number; snapshots_skipped: number }
+/**
+ * Preview of a single balance snapshot for UI display
+ */
+export type BalanceSnapshotPreview = { date: string; 
+/**
+ * The calculated balance for this date (end of day)
+ */
+balance: number; 
+/**
+ * Net transaction amount for this day (positive = inflow, negative = outflow), 0 if no transactions
+ */
+daily_change: number; 
+/**
+ * Transactions that occurred on this day
+ */
+transactions: TransactionSummary[]; 
+/**
+ * True if no existing snapshot exists (will be created)
+ */
+is_new: boolean; 
+/**
+ * The existing balance if one exists (will be replaced)
+ */
+existing_balance: number | null; 
+/**
+ * Source of existing snapshot: "sync", "manual", "backfill", "import", or null
+ */
+existing_source: string | null; 
+/**
+ * Existing snapshots on this day, all replaced (more than one when synced several times a day)
+ */
+existing_count: number }
+/**
+ * Text encoding detected in a CSV file
+ */
+export type CsvEncoding = "utf8" | 
+/**
+ * UTF-8 with a leading byte order mark (common in Excel exports)
+ */
+"utf8_bom" | "utf16_le" | "utf16_be" | 
+/**
+ * Fallback for files that are not valid UTF-8 (older Windows bank exports)
+ */
+"windows1252"
+/**
+ * Headers and dialect detected by sniffing the start of a CSV file
+ */
+export type CsvHeaders = { headers: string[]; 
+/**
+ * Detected delimiter as a single-character string
+ */
+delimiter: string; encoding: CsvEncoding; quote_style: QuoteStyle }
+/**
+ * Encryption status for frontend
+ */
+export type EncryptionStatus = { encrypted: boolean; locked: boolean; algorithm: string | null; version: number | null }
+export type ExternalPlugin = { manifest: PluginManifest; path: string }
+export type JsonValue = null | boolean | number | string | JsonValue[] | { [key in string]: JsonValue }
+/**
+ * Pending import file info
+ */
+/**
+ * One action the command palette can offer
+ */
+export type PaletteAction = { 
+/**
+ * Stable ID, e.g. "data:sync" or "saved-query:<id>"
+ */
+id: string; name: string; category: string; description: string | null; shortcut: string | null; 
+/**
+ * "core", "saved_query" or the ID of the plugin that contributes it
+ */
+source: string; target: PaletteTarget }
+/**
+ * What choosing a palette action does
+ */
+export type PaletteTarget = 
+/**
+ * Run a command from the frontend registry
+ */
+{ type: "command"; id: string } | 
+/**
+ * Open a view, passing `props`
+ */
+{ type: "view"; view_id: string; props: JsonValue | null } | 
+/**
+ * Invoke a backend command with these arguments
+ */
+{ type: "invoke"; command: string; args: JsonValue }
+export type PendingImportFile = { path: string; filename: string; size_bytes: number }
+/**
+ * Context for plugin permission validation.
+ * Passed from TypeScript SDK when executing queries on behalf of a plugin.
+ */
+export type PluginContext = { 
+/**
+ * The plugin's unique identifier (e.g., "goals", "budget")
+ */
+plugin_id: string; 
+/**
+ * The plugin's schema name (e.g., "plugin_goals", "plugin_budget")
+ */
+plugin_schema: string; 
+/**
+ * Tables the plugin is allowed to read from (outside its own schema)
+ */
+allowed_reads: string[]; 
+/**
+ * Tables the plugin is allowed to write to (outside its own schema)
+ */
+allowed_writes: string[] }
+/**
+ * A command declared in a plugin manifest
+ */
+export type PluginCommand = { 
+/**
+ * ID the plugin registers the command under
+ */
+id: string; name: string; category: string | null; shortcut: string | null }
+export type PluginManifest = { id: string; name: string; version: string; description: string; author: string; main: string; permissions: JsonValue | null; source: string | null; 
+/**
+ * Commands the plugin registers, listed so the command palette knows them before it loads
+ */
+commands: PluginCommand[] }
+/**
+ * How fields are quoted in a CSV file
+ */
+export type QuoteStyle = 
+/**
+ * No field is quoted
+ */
+"none" | 
+/**
+ * Only some fields are quoted (typically those containing delimiters)
+ */
+"minimal" | 
+/**
+ * Every field is quoted
+ */
+"all"
+export type StartupActionResult = { 
+/**
+ * "backup", "sync", "update_check" or "restore_view"
+ */
+action: string; status: StartupActionStatus; message: string }
+/**
+ * Outcome of one startup action
+ */
+export type StartupActionStatus = "done" | 
+/**
+ * Turned off in settings, or not needed yet
+ */
+"skipped" | 
+/**
+ * Waiting for the database to be unlocked; the frontend runs it after unlock
+ */
+"deferred" | "failed"
+/**
+ * What the startup actions did
+ */
+export type StartupReport = { 
+/**
+ * False while the actions are still running
+ */
+finished: boolean; actions: StartupActionResult[]; 
+/**
+ * JSON sync result, when the startup sync ran
+ */
+sync_result: string | null; 
+/**
+ * Update found by the startup check
+ */
+update: AppUpdateInfo | null; 
+/**
+ * View to reopen, when restoring the last view is on
+ */
+last_view: string | null }
+/**
+ * How often to sync in the background, the `syncSchedule` app setting
+ */
+export type SyncSchedule = "off" | "hourly" | "daily"
+/**
+ * Where the sync scheduler stands; also the `sync-scheduled` payload
+ */
+export type SyncScheduleStatus = { schedule: SyncSchedule; 
+/**
+ * RFC 3339 time of the last successful sync of every integration
+ */
+last_sync_at: string | null; 
+/**
+ * RFC 3339 time the next scheduled sync is due (None when off)
+ */
+next_sync_at: string | null }
+/**
+ * Theme definition loaded from JSON files
+ */
+export type ThemeDefinition = { id: string; name: string; extends: string | null; variables: { [key in string]: string } }
+/**
+ * Summary of a transaction for preview display
+ */
+export type TransactionSummary = { description: string; amount: number }
+
+/** tauri-specta globals **/
+
+import {
+	invoke as TAURI_INVOKE,
+	Channel as TAURI_CHANNEL,
+} from "@tauri-apps/api/core";
+import * as TAURI_API_EVENT from "@tauri-apps/api/event";
+import { type WebviewWindow as __WebviewWindow__ } from "@tauri-apps/api/webviewWindow";
+
+type __EventObj__<T> = {
+	listen: (
+		cb: TAURI_API_EVENT.EventCallback<T>,
+	) => ReturnType<typeof TAURI_API_EVENT.listen<T>>;
+	once: (
+		cb: TAURI_API_EVENT.EventCallback<T>,
+	) => ReturnType<typeof TAURI_API_EVENT.once<T>>;
+	emit: null extends T
+		? (payload?: T) => ReturnType<typeof TAURI_API_EVENT.emit>
+		: (payload: T) => ReturnType<typeof TAURI_API_EVENT.emit>;
+};
+
+export type Result<T, E> =
+	| { status: "ok"; data: T }
+	| { status: "error"; error: E };
+
+function __makeEvents__<T extends Record<string, any>>(
+	mappings: Record<keyof T, string>,
+) {
+	return new Proxy(
+		{} as unknown as {
+			[K in keyof T]: __EventObj__<T[K]> & {
+				(handle: __WebviewWindow__): __EventObj__<T[K]>;
+			};
+		},
+		{
+			get: (_, event) => {
+				const name = mappings[event as keyof T];
+
+				return new Proxy((() => {}) as any, {
+					apply: (_, __, [window]: [__WebviewWindow__]) => ({
+						listen: (arg: any) => window.listen(name, arg),
+						once: (arg: any) => window.once(name, arg),
+						emit: (arg: any) => window.emit(name, arg),
+					}),
+					get: (_, command: keyof __EventObj__<any>) => {
+						switch (command) {
+							case "listen":
+								return (arg: any) => TAURI_API_EVENT.listen(name, arg);
+							case "once":
+								return (arg: any) => TAURI_API_EVENT.once(name, arg);
+							case "emit":
+								return (arg: any) => TAURI_API_EVENT.emit(name, arg);
+						}
+					},
+				});
+			},
+		},
+	);
+}
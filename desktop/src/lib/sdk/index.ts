@@ -28,8 +28,8 @@ export type { PluginSDK, PluginTablePermissions } from "./public";
 export { registry } from "./registry";
 
 // API
-export { executeQuery, executeQueryWithParams, db, deleteAccount } from "./api";
-export type { QueryResult, ExecuteQueryOptions, QueryParam } from "./api";
+export { executeQuery, executeQueryWithParams, translateNlQuery, db, deleteAccount } from "./api";
+export type { QueryResult, ExecuteQueryOptions, QueryParam, NlQueryResult } from "./api";
 
 // Theme
 export { themeManager } from "./theme";
@@ -49,8 +49,28 @@ export {
   clearSettingsCache,
   readPluginState,
   writePluginState,
+  readPluginStateVersioned,
+  writePluginStateVersioned,
   runSync,
+  getSyncHistory,
+  onSyncProgress,
+  getAccountSyncActivity,
+  getDeviceSyncStatus,
+  setupDeviceSync,
+  runDeviceSync,
   isSyncNeeded,
+  getStartupReport,
+  getSyncSchedule,
+  onSyncScheduled,
+  onSyncStarted,
+  onSyncFinished,
+  // Accounts & Transactions
+  listAccounts,
+  setAccountSignPolicy,
+  listTransactions,
+  findDuplicates,
+  mergeDuplicates,
+  dismissDuplicates,
   getDemoMode,
   setDemoMode,
   enableDemo,
@@ -65,9 +85,15 @@ export {
   backfillExecute,
   // CSV Import
   pickCsvFile,
+  convertPdfStatement,
   getCsvHeaders,
+  detectImportPreset,
   importCsvPreview,
   importCsvExecute,
+  onImportProgress,
+  importCsvFromText,
+  validateCsvImport,
+  undoImport,
   // Import Profiles
   getImportProfiles,
   getImportProfile,
@@ -82,6 +108,16 @@ export {
   // Integrations
   setupSimplefin,
   setupLunchflow,
+  createPlaidLinkToken,
+  setupPlaid,
+  listGoCardlessInstitutions,
+  setupGoCardless,
+  setupCoinbase,
+  setupKraken,
+  rotateIntegration,
+  listIntegrationAccounts,
+  setIntegrationAccountEnabled,
+  removeIntegration,
   // Integration Account Settings
   getIntegrationSettings,
   updateIntegrationAccountSetting,
@@ -92,28 +128,77 @@ export {
   getEncryptionStatus,
   tryAutoUnlock,
   unlockDatabase,
+  onUnlockProgress,
+  lockDatabase,
   enableEncryption,
   disableEncryption,
   // Watch Folder / Pending Imports
   listPendingImports,
   moveImportedFile,
+  importAllPending,
   // Backup & Restore
   listBackups,
   createBackup,
   restoreBackup,
   deleteBackup,
   clearBackups,
+  // Data Directory
+  getCloudSyncProvider,
+  moveDataDirectory,
   // Database Compact
   compactDatabase,
   formatBytes,
+  // Storage
+  getStorageBreakdown,
+  pruneStorage,
+  // Institutions
+  getInstitutions,
+  refreshInstitutionLogos,
+  // Investments
+  getHoldings,
+  // Diagnostics
+  getDiagnostics,
+  // Reports
+  exportStatement,
+  comparePeriods,
+  getCashFlows,
+  // Long-running Operations
+  listOperations,
+  getOperationStatus,
+  cancelOperation,
+  onDataRefreshed,
 } from "./settings";
 export type {
   Settings,
   AppSettings,
   SyncResult,
+  SyncHistoryEntry,
+  AccountSyncActivity,
+  DeviceSyncStatus,
+  DeviceSyncResult,
+  RemoteAccount,
+  PlaidKeys,
+  GoCardlessSecrets,
+  GoCardlessInstitution,
+  ExchangeKeys,
   ImportColumnMapping,
+  ImportPresetMatch,
   ImportPreviewResult,
   ImportExecuteResult,
+  UndoImportResult,
+  ImportValidation,
+  BalanceMismatch,
+  ValidationIssue,
+  ValidationIssueKind,
+  ImportRowError,
+  ImportRowStatus,
+  ImportRowStatusKind,
+  ImportAccountAssignment,
+  ImportBalanceImpact,
+  MonthlyBalanceDelta,
+  CsvHeadersResult,
+  CsvEncoding,
+  CsvQuoteStyle,
   ImportProfile,
   ImportProfileColumnMappings,
   ImportProfileOptions,
@@ -121,12 +206,52 @@ export type {
   PluginInstallResult,
   EncryptionStatus,
   PendingImportFile,
+  FolderImportFile,
+  FolderImportSummary,
+  PluginStateMigrations,
   BackupMetadata,
+  MoveDataResult,
   CompactResult,
+  TableStorage,
+  StorageSuggestion,
+  StorageBreakdown,
+  PruneResult,
+  InstitutionInfo,
+  InstitutionRefreshResult,
+  HoldingLot,
+  HoldingInfo,
+  HoldingsSummary,
+  Diagnostics,
+  ReportCacheStats,
+  ConsumerUsage,
+  QueryBudgetReport,
+  ReportPeriod,
+  ComparisonRow,
+  PeriodComparison,
+  FlowNode,
+  FlowLink,
+  CashFlows,
+  OperationStatus,
+  RefreshResult,
+  ImportProgress,
+  ImportStage,
+  PaymentDue,
   NumberFormat,
   TransactionSummary,
   BalanceSnapshotPreview,
   BackfillExecuteResult,
+  StartupReport,
+  SyncProgress,
+  SyncStage,
+  UnlockProgress,
+  UnlockStage,
+  SyncSchedule,
+  SyncScheduleStatus,
+  ScheduledSyncResult,
+  AccountInfo,
+  SignPolicy,
+  TransactionInfo,
+  DuplicateCandidate,
 } from "./settings";
 
 // Toast notifications
@@ -142,10 +267,29 @@ export { pluginUpdatesStore } from "./pluginUpdates.svelte";
 export type { PluginUpdateInfo } from "./pluginUpdates.svelte";
 
 // Platform utilities
-export { isMac, modKey, formatShortcut } from "./platform";
+export { isMac, isMobile, modKey, formatShortcut } from "./platform";
 
 // Logging (for troubleshooting)
-export { logger, logPage, logAction, logError, getLogsPath } from "./logging";
+export {
+  logger,
+  logPage,
+  logAction,
+  logError,
+  getLogsPath,
+  getLoggingSettings,
+  setLoggingSettings,
+  purgeLogs,
+  getUsagePingStatus,
+  setUsagePingEnabled,
+  previewUsagePing,
+} from "./logging";
+export type {
+  LogLevel,
+  LogCategory,
+  LoggingSettings,
+  UsagePing,
+  UsagePingStatus,
+} from "./logging";
 
 // Currency utilities
 export {
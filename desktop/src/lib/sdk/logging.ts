@@ -5,7 +5,7 @@
  * Only log: event names, view names, component names, sanitized error messages
  */
 
-import { invoke } from "@tauri-apps/api/core";
+import { commands } from "./bindings";
 
 /**
  * Log a page/view navigation
@@ -13,7 +13,7 @@ import { invoke } from "@tauri-apps/api/core";
  */
 export async function logPage(page: string): Promise<void> {
   try {
-    await invoke("log_page", { page });
+    await commands.logPage(page);
   } catch {
     // Silently ignore logging errors - should never break the app
   }
@@ -26,7 +26,7 @@ export async function logPage(page: string): Promise<void> {
  */
 export async function logAction(action: string, component: string): Promise<void> {
   try {
-    await invoke("log_action", { action, component });
+    await commands.logAction(action, component);
   } catch {
     // Silently ignore logging errors
   }
@@ -42,7 +42,7 @@ export async function logError(event: string, message: string, details?: string)
   try {
     // Sanitize message - remove any potential PII patterns
     const sanitizedMessage = sanitizeErrorMessage(message);
-    await invoke("log_error", { event, message: sanitizedMessage, details });
+    await commands.logError(event, sanitizedMessage, details ?? null);
   } catch {
     // Silently ignore logging errors
   }
@@ -53,12 +53,95 @@ export async function logError(event: string, message: string, details?: string)
  */
 export async function getLogsPath(): Promise<string | null> {
   try {
-    return await invoke<string | null>("get_logs_path");
+    return await commands.getLogsPath();
   } catch {
     return null;
   }
 }
 
+/**
+ * How much is logged: errors only, errors plus actions, or everything
+ * including page views
+ */
+export type LogLevel = "errors" | "actions" | "full";
+
+/** ui: screens and clicks, cli: commands, sync: integrations, app: the rest */
+export type LogCategory = "ui" | "cli" | "sync" | "app";
+
+export interface LoggingSettings {
+  /** Level for categories without their own */
+  level: LogLevel;
+  categories?: Partial<Record<LogCategory, LogLevel>>;
+}
+
+/**
+ * Get the log levels from settings
+ */
+export async function getLoggingSettings(): Promise<LoggingSettings> {
+  return JSON.parse(await commands.getLoggingSettings()) as LoggingSettings;
+}
+
+/**
+ * Save log levels; they apply immediately
+ */
+export async function setLoggingSettings(settings: LoggingSettings): Promise<void> {
+  await commands.setLoggingSettings(JSON.stringify(settings));
+}
+
+/**
+ * Delete all log history
+ * @returns Number of entries deleted
+ */
+export async function purgeLogs(): Promise<number> {
+  return commands.purgeLogs();
+}
+
+export interface UsagePingStatus {
+  enabled: boolean;
+  /** Where pings are sent */
+  url: string;
+  last_sent_at: string | null;
+  /** Null when disabled */
+  next_due_at: string | null;
+}
+
+/** Everything a usage ping sends: names and versions, never financial data */
+export interface UsagePing {
+  schema_version: number;
+  /** Random ID made for the ping alone */
+  install_id: string;
+  app_version: string;
+  entry_point: "cli" | "desktop";
+  os: string;
+  arch: string;
+  /** Features that are set up, e.g. "encryption" or "plugins" */
+  features: string[];
+  commands: string[];
+  pages: string[];
+  integrations: string[];
+}
+
+/**
+ * Get whether the opt-in usage ping is on and when it was last sent
+ */
+export async function getUsagePingStatus(): Promise<UsagePingStatus> {
+  return JSON.parse(await commands.getUsagePingStatus()) as UsagePingStatus;
+}
+
+/**
+ * Opt in to or out of the weekly anonymous usage ping (off by default)
+ */
+export async function setUsagePingEnabled(enabled: boolean): Promise<void> {
+  await commands.setUsagePingEnabled(enabled);
+}
+
+/**
+ * Exactly what the next usage ping would send, for showing before opting in
+ */
+export async function previewUsagePing(): Promise<UsagePing> {
+  return JSON.parse(await commands.previewUsagePing()) as UsagePing;
+}
+
 /**
  * Sanitize error messages to remove potential PII
  * Removes: account numbers, amounts, descriptions, paths with usernames
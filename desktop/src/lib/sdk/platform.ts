@@ -1,5 +1,5 @@
 /**
- * Platform detection utilities for keyboard shortcuts
+ * Platform detection utilities for keyboard shortcuts and the mobile app
  */
 
 // Type for the User-Agent Client Hints API (not yet in all TS libs)
@@ -29,6 +29,14 @@ export function isMac(): boolean {
   return /Mac|iPhone|iPad|iPod/i.test(navigator.userAgent);
 }
 
+/**
+ * Check if running as the mobile companion (iOS or Android)
+ */
+export function isMobile(): boolean {
+  if (typeof navigator === "undefined") return false;
+  return /Android|iPhone|iPad|iPod/i.test(navigator.userAgent);
+}
+
 /**
  * Get the modifier key symbol for the current platform
  * Returns ⌘ on Mac, Ctrl+ on Windows/Linux
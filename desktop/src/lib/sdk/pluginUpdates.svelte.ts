@@ -2,7 +2,7 @@
  * Plugin Updates Store - Track available plugin updates for badge display
  */
 
-import { invoke } from "@tauri-apps/api/core";
+import { commands } from "./bindings";
 
 export interface PluginUpdateInfo {
   pluginId: string;
@@ -11,13 +11,6 @@ export interface PluginUpdateInfo {
   source: string;
 }
 
-interface InstalledPluginManifest {
-  id: string;
-  name: string;
-  version: string;
-  source?: string;
-}
-
 class PluginUpdatesStore {
   private _updates = $state<Map<string, PluginUpdateInfo>>(new Map());
   private _isChecking = $state(false);
@@ -64,10 +57,10 @@ class PluginUpdatesStore {
 
     try {
       // Discover installed community plugins
-      const installed = await invoke<Array<{ manifest: InstalledPluginManifest; path: string }>>("discover_plugins");
+      const installed = await commands.discoverPlugins();
       const communityPlugins = installed
         .filter(p => p.manifest.source?.startsWith("https://github.com/"))
-        .map(p => ({ id: p.manifest.id, source: p.manifest.source }));
+        .map(p => ({ id: p.manifest.id, source: p.manifest.source ?? undefined }));
 
       if (communityPlugins.length === 0) return;
 
@@ -91,9 +84,7 @@ class PluginUpdatesStore {
         if (!plugin.source?.startsWith("https://github.com/")) continue;
 
         try {
-          const result = await invoke<string>("check_plugin_update", {
-            pluginId: plugin.id,
-          });
+          const result = await commands.checkPluginUpdate(plugin.id);
           const data = JSON.parse(result);
           if (data.has_update) {
             updates.set(plugin.id, {
@@ -16,6 +16,8 @@ import {
   setPluginSettings,
   readPluginState,
   writePluginState,
+  readPluginStateVersioned,
+  writePluginStateVersioned,
 } from "./settings";
 import {
   SUPPORTED_CURRENCIES,
@@ -24,6 +26,9 @@ import {
   formatCurrency,
   formatCurrencyCompact,
   formatAmount,
+  getMinorUnits,
+  toMinorUnits,
+  fromMinorUnits,
 } from "../shared/currency";
 import {
   getCurrency,
@@ -159,6 +164,9 @@ export function createPluginSDK(pluginId: string, permissions: PluginTablePermis
     state: {
       read: <T>() => readPluginState<T>(pluginId),
       write: <T>(state: T) => writePluginState(pluginId, state),
+      readVersioned: <T>(version: number, migrations?: Record<number, (state: any) => any>) =>
+        readPluginStateVersioned<T>(pluginId, version, migrations),
+      writeVersioned: <T>(version: number, state: T) => writePluginStateVersioned(pluginId, version, state),
     },
 
     // Currency formatting (uses user's currency preference by default)
@@ -168,6 +176,9 @@ export function createPluginSDK(pluginId: string, permissions: PluginTablePermis
       formatAmount: (amount: number) => formatAmount(amount),
       getSymbol: (currency?: string) => currency ? getCurrencySymbol(currency) : getUserCurrencySymbol(),
       getUserCurrency: () => getCurrency(),
+      getMinorUnits: (currency?: string) => getMinorUnits(currency ?? getCurrency()),
+      toMinorUnits: (amount: number | string, currency?: string) => toMinorUnits(amount, currency ?? getCurrency()),
+      fromMinorUnits: (minor: bigint | number, currency?: string) => fromMinorUnits(minor, currency ?? getCurrency()),
       supportedCurrencies: Object.keys(SUPPORTED_CURRENCIES),
     },
   };
@@ -9,8 +9,32 @@
  * - Plugin files (read/write_plugin_config): Domain data like budget months
  */
 
-import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { withActivity } from "./activity.svelte";
+import { toAmountString } from "../shared/currency";
+import {
+  commands,
+  type BackfillExecuteResult,
+  type BalanceSnapshotPreview,
+  type CsvHeaders,
+  type EncryptionStatus,
+  type PendingImportFile,
+  type StartupReport,
+  type SyncSchedule,
+  type SyncScheduleStatus,
+} from "./bindings";
+
+export type {
+  BackfillExecuteResult,
+  BalanceSnapshotPreview,
+  CsvEncoding,
+  EncryptionStatus,
+  PendingImportFile,
+  StartupReport,
+  SyncSchedule,
+  SyncScheduleStatus,
+  TransactionSummary,
+} from "./bindings";
 
 /**
  * App-level settings structure
@@ -18,8 +42,14 @@ import { withActivity } from "./activity.svelte";
 export interface AppSettings {
   theme: string; // Theme ID: "dark", "light", or "system"
   lastSyncDate: string | null;
+  lastSyncAt?: string | null; // When every integration last synced (RFC 3339)
   autoSyncOnStartup: boolean;
+  syncSchedule?: SyncSchedule; // Sync in the background while the app runs
   autoUpdate: boolean;
+  autoBackupOnStartup?: boolean; // Back up on startup when the last backup is over a day old
+  restoreLastView?: boolean;
+  autoImport?: boolean; // Import CSVs dropped into the imports folder automatically
+  lastView?: string | null; // View active when the app last closed
   lastUpdateCheck?: string | null;
   hasCompletedOnboarding?: boolean;
   sidebarCollapsed?: boolean;
@@ -48,7 +78,10 @@ const DEFAULT_SETTINGS: Settings = {
     theme: "system",
     lastSyncDate: null,
     autoSyncOnStartup: true,
+    syncSchedule: "off",
     autoUpdate: true,
+    autoBackupOnStartup: false,
+    restoreLastView: true,
     lastUpdateCheck: null,
   },
   plugins: {},
@@ -73,7 +106,7 @@ export function invalidateSettingsCache(): void {
  * Read all settings from disk
  */
 export async function readSettings(): Promise<Settings> {
-  const jsonString = await invoke<string>("read_settings");
+  const jsonString = await commands.readSettings();
   const parsed = JSON.parse(jsonString);
 
   // Merge with defaults to ensure all fields exist
@@ -92,7 +125,7 @@ export async function readSettings(): Promise<Settings> {
  * Write all settings to disk
  */
 export async function writeSettings(settings: Settings): Promise<void> {
-  await invoke("write_settings", { content: JSON.stringify(settings, null, 2) });
+  await commands.writeSettings(JSON.stringify(settings, null, 2));
   settingsCache = settings;
   notifySubscribers();
 }
@@ -243,7 +276,7 @@ export async function disablePlugin(pluginId: string): Promise<void> {
  * Read plugin state (runtime state, not user settings)
  */
 export async function readPluginState<T>(pluginId: string): Promise<T | null> {
-  const jsonString = await invoke<string>("read_plugin_state", { pluginId });
+  const jsonString = await commands.readPluginState(pluginId);
   if (jsonString === "null") {
     return null;
   }
@@ -254,10 +287,180 @@ export async function readPluginState<T>(pluginId: string): Promise<T | null> {
  * Write plugin state (runtime state, not user settings)
  */
 export async function writePluginState<T>(pluginId: string, state: T): Promise<void> {
-  await invoke("write_plugin_state", {
-    pluginId,
-    content: JSON.stringify(state, null, 2)
-  });
+  await commands.writePluginState(pluginId, JSON.stringify(state, null, 2));
+}
+
+/**
+ * Upgrade functions for versioned plugin state, keyed by the version each produces.
+ * `migrations[2]` turns version 1 state into version 2. State from the
+ * unversioned `writePluginState` is version 0.
+ */
+export type PluginStateMigrations = Record<number, (state: any) => any>;
+
+interface StoredPluginState {
+  schemaVersion: number;
+  state: unknown;
+}
+
+/**
+ * Read plugin state at `version`, running any migrations it needs first.
+ * Upgraded state is written back once; if another view migrated it in the
+ * meantime, its result is used instead.
+ */
+export async function readPluginStateVersioned<T>(
+  pluginId: string,
+  version: number,
+  migrations: PluginStateMigrations = {}
+): Promise<T | null> {
+  for (let attempt = 0; attempt < 3; attempt++) {
+    const stored = JSON.parse(await commands.readPluginStateVersioned(pluginId)) as StoredPluginState;
+    if (stored.state === null || stored.schemaVersion === version) {
+      return stored.state as T | null;
+    }
+    if (stored.schemaVersion > version) {
+      throw new Error(
+        `State for ${pluginId} is version ${stored.schemaVersion}, newer than this plugin's version ${version}`
+      );
+    }
+
+    let state = stored.state;
+    for (let next = stored.schemaVersion + 1; next <= version; next++) {
+      const migrate = migrations[next];
+      if (!migrate) {
+        throw new Error(`No migration to state version ${next} for ${pluginId}`);
+      }
+      state = migrate(state);
+    }
+
+    const written = await commands.writePluginStateVersioned(
+      pluginId,
+      JSON.stringify(state, null, 2),
+      version,
+      stored.schemaVersion
+    );
+    if (written) {
+      return state as T;
+    }
+  }
+  throw new Error(`State for ${pluginId} kept changing during migration`);
+}
+
+/**
+ * Write plugin state at `version` (use with readPluginStateVersioned)
+ */
+export async function writePluginStateVersioned<T>(pluginId: string, version: number, state: T): Promise<void> {
+  await commands.writePluginStateVersioned(pluginId, JSON.stringify(state, null, 2), version, null);
+}
+
+// ============================================================================
+// Accounts & Transactions
+// ============================================================================
+
+export interface AccountInfo {
+  id: string;
+  name: string;
+  nickname: string | null;
+  account_type: string | null;
+  classification: string | null; // "asset" or "liability"
+  currency: string;
+  balance: string | null; // Latest balance snapshot (decimal string)
+  institution_name: string | null;
+  integration: string | null; // "simplefin", "lunchflow", "plaid" or "gocardless"
+  archived_at: string | null;
+  statement_cycle: { closing_day: number; due_day: number } | null; // Credit cards only
+  sign_policy: SignPolicy;
+}
+
+/**
+ * "inverted" for accounts whose source reports charges as positive amounts
+ */
+export type SignPolicy = "as_reported" | "inverted";
+
+export interface TransactionInfo {
+  id: string;
+  account_id: string;
+  account_name: string;
+  date: string;
+  posted_date: string;
+  description: string | null;
+  amount: string; // Decimal string
+  tags: string[];
+  parent_id: string | null;
+  source: string;
+  check_number: string | null;
+  reference: string | null;
+}
+
+/**
+ * List accounts with their latest balance, sorted by name
+ */
+export async function listAccounts(includeArchived = false): Promise<AccountInfo[]> {
+  const jsonString = await commands.listAccounts(includeArchived);
+  return JSON.parse(jsonString) as AccountInfo[];
+}
+
+/**
+ * Set how an account's source signs amounts. With `normalize`, existing synced
+ * and imported transactions are flipped once to match.
+ */
+export async function setAccountSignPolicy(
+  accountId: string,
+  policy: SignPolicy,
+  normalize = false
+): Promise<AccountInfo & { transactions_flipped: number }> {
+  const jsonString = await commands.setAccountSignPolicy(accountId, policy, normalize);
+  return JSON.parse(jsonString) as AccountInfo & { transactions_flipped: number };
+}
+
+/**
+ * List transactions newest first (100 unless `limit` is given)
+ */
+export async function listTransactions(
+  options: { accountId?: string; search?: string; limit?: number } = {}
+): Promise<TransactionInfo[]> {
+  const jsonString = await commands.listTransactions(
+    options.accountId ?? null,
+    options.search ?? null,
+    options.limit ?? null
+  );
+  return JSON.parse(jsonString) as TransactionInfo[];
+}
+
+export interface DuplicateCandidate {
+  first: TransactionInfo;
+  second: TransactionInfo;
+  days_apart: number;
+  similarity: number; // 0 to 1
+  suggested_keep: string; // ID of the side to keep when merging
+}
+
+/**
+ * Find likely duplicates that exact dedup missed: same account and amount,
+ * dates close together and similar descriptions. Newest first.
+ */
+export async function findDuplicates(
+  options: { maxDays?: number; minSimilarity?: number } = {}
+): Promise<DuplicateCandidate[]> {
+  const jsonString = await commands.findDuplicates(
+    options.maxDays ?? null,
+    options.minSimilarity ?? null
+  );
+  return JSON.parse(jsonString) as DuplicateCandidate[];
+}
+
+/**
+ * Resolve a likely duplicate by deleting one side, returning the kept one
+ */
+export async function mergeDuplicates(keepId: string, removeId: string): Promise<TransactionInfo> {
+  const jsonString = await commands.mergeDuplicates(keepId, removeId);
+  return JSON.parse(jsonString) as TransactionInfo;
+}
+
+/**
+ * Mark two transactions as not duplicates, so they aren't suggested again
+ */
+export async function dismissDuplicates(firstId: string, secondId: string): Promise<void> {
+  await commands.dismissDuplicates(firstId, secondId);
 }
 
 // ============================================================================
@@ -272,9 +475,18 @@ export interface SyncResult {
     transaction_stats?: {
       discovered: number;
       new: number;
+      /** Pending transactions brought up to date, including ones that posted */
+      updated?: number;
       skipped: number;
     };
     provider_warnings?: string[];
+    /** Accounts whose transactions couldn't be fetched; the rest synced */
+    failed_accounts?: Array<{
+      account_id: string | null;
+      external_id: string;
+      account_name: string;
+      error: string;
+    }>;
     error?: string;
   }>;
 }
@@ -282,14 +494,16 @@ export interface SyncResult {
 export interface RunSyncOptions {
   dryRun?: boolean;
   balancesOnly?: boolean;
+  /** Only sync this integration (e.g. "simplefin"); all of them when omitted */
+  integration?: string;
 }
 
 /**
  * Run sync and update lastSyncDate (unless dry run)
  */
 export async function runSync(options: RunSyncOptions = {}): Promise<SyncResult> {
-  const { dryRun = false, balancesOnly = false } = options;
-  const jsonString = await invoke<string>("run_sync", { dryRun, balancesOnly });
+  const { dryRun = false, balancesOnly = false, integration } = options;
+  const jsonString = await commands.runSync(dryRun, balancesOnly, integration ?? null);
   const result = JSON.parse(jsonString) as SyncResult;
 
   // Update lastSyncDate on success (but not for dry runs)
@@ -301,6 +515,148 @@ export async function runSync(options: RunSyncOptions = {}): Promise<SyncResult>
   return result;
 }
 
+export type SyncStage = "accounts" | "fetching" | "saving" | "finished";
+
+export interface SyncProgress {
+  operation_id: string;
+  integration: string;
+  stage: SyncStage;
+  /** e.g. "Saving transactions" */
+  message: string;
+  /** Account whose transactions were just saved ("saving" stage) */
+  account_id: string | null;
+  account_name: string | null;
+  /** Accounts of this integration saved so far, of those with transactions */
+  accounts_done: number;
+  accounts_total: number;
+  /** Transactions fetched for the account (for the integration when "finished") */
+  fetched: number;
+  /** Of those, new ones saved */
+  inserted: number;
+  /** Share of the whole sync done, 0 to 1 */
+  fraction: number;
+}
+
+/**
+ * Call `handler` as a running `runSync` fetches and saves each account's
+ * transactions. Pass `operation_id` to `cancelOperation` to stop the sync
+ * between accounts.
+ * @returns a function that stops listening
+ */
+export async function onSyncProgress(
+  handler: (progress: SyncProgress) => void
+): Promise<() => void> {
+  return listen<SyncProgress>("sync-progress", (event) => handler(event.payload));
+}
+
+export interface SyncHistoryEntry {
+  sync_id: string;
+  integration: string;
+  started_at: string;
+  duration_ms: number;
+  sync_type: string | null;
+  accounts_synced: number;
+  transactions_discovered: number;
+  transactions_new: number;
+  transactions_skipped: number;
+  /** New transactions per account ID */
+  account_stats: Record<string, number>;
+  warnings: string[];
+  error: string | null;
+}
+
+export interface AccountSyncActivity {
+  account_id: string;
+  account_name: string;
+  integration: string;
+  last_synced_at: string | null;
+  last_new_data_at: string | null;
+  last_new_transactions: number;
+  last_error: string | null;
+}
+
+/**
+ * Get recorded sync runs, newest first
+ */
+export async function getSyncHistory(
+  options: { integration?: string; limit?: number } = {}
+): Promise<SyncHistoryEntry[]> {
+  const jsonString = await commands.getSyncHistory(
+    options.integration ?? null,
+    options.limit ?? null,
+    null
+  );
+  return JSON.parse(jsonString) as SyncHistoryEntry[];
+}
+
+/**
+ * Get when each synced account last synced and last produced new transactions
+ */
+export async function getAccountSyncActivity(): Promise<AccountSyncActivity[]> {
+  const jsonString = await commands.getSyncHistory(null, null, true);
+  return JSON.parse(jsonString) as AccountSyncActivity[];
+}
+
+export interface DevicePeerStatus {
+  device_id: string;
+  device_name: string | null;
+  /** Newest bundle it has written */
+  latest_seq: number;
+  /** Newest bundle applied here */
+  applied_seq: number;
+  last_synced_at: string | null;
+}
+
+export interface DeviceSyncStatus {
+  relay: string;
+  device_id: string;
+  device_name: string | null;
+  /** Fields exchanged with other devices so far */
+  tracked_fields: number;
+  peers: DevicePeerStatus[];
+}
+
+export interface DeviceSyncResult {
+  /** Fields changed here and written to the relay */
+  pushed: number;
+  /** Bundles read from other devices */
+  bundles_read: number;
+  /** Changes from other devices applied here */
+  applied: number;
+  /** Changes from other devices older than the value here */
+  outdated: number;
+  /** Changes to records this device couldn't create */
+  skipped: number;
+}
+
+/**
+ * Get the device sync relay and peers, or null when device sync isn't set up
+ */
+export async function getDeviceSyncStatus(): Promise<DeviceSyncStatus | null> {
+  const jsonString = await commands.getDeviceSyncStatus();
+  return JSON.parse(jsonString) as DeviceSyncStatus | null;
+}
+
+/**
+ * Create or join a relay folder shared with the other devices.
+ * Joining checks `passphrase` against the one the relay was created with.
+ */
+export async function setupDeviceSync(
+  relay: string,
+  passphrase: string,
+  deviceName?: string
+): Promise<void> {
+  await commands.setupDeviceSync(relay, passphrase, deviceName ?? null);
+}
+
+/**
+ * Exchange changes with the other devices through the relay
+ */
+export async function runDeviceSync(passphrase: string): Promise<DeviceSyncResult> {
+  const jsonString = await commands.runDeviceSync(passphrase);
+  return JSON.parse(jsonString) as DeviceSyncResult;
+}
+
 /**
  * Check if sync is needed (based on lastSyncDate)
  */
@@ -320,6 +676,85 @@ export async function isSyncNeeded(): Promise<boolean> {
   return lastSyncDate < today;
 }
 
+// ============================================================================
+// Sync Schedule
+// ============================================================================
+
+/**
+ * Outcome of a scheduled sync
+ */
+export interface ScheduledSyncResult {
+  /** JSON sync result, when the sync ran */
+  result: string | null;
+  error: string | null;
+}
+
+/**
+ * Get the sync schedule and when the next scheduled sync is due
+ */
+export async function getSyncSchedule(): Promise<SyncScheduleStatus> {
+  return commands.getSyncSchedule();
+}
+
+/**
+ * Call `handler` whenever the next scheduled sync time changes, e.g. after a
+ * sync or a new schedule
+ * @returns a function that stops listening
+ */
+export async function onSyncScheduled(
+  handler: (status: SyncScheduleStatus) => void
+): Promise<() => void> {
+  return listen<SyncScheduleStatus>("sync-scheduled", (event) => handler(event.payload));
+}
+
+/**
+ * Call `handler` when a scheduled sync starts
+ * @returns a function that stops listening
+ */
+export async function onSyncStarted(handler: () => void): Promise<() => void> {
+  return listen("sync-started", () => handler());
+}
+
+/**
+ * Call `handler` when a scheduled sync finishes, successfully or not
+ * @returns a function that stops listening
+ */
+export async function onSyncFinished(
+  handler: (result: ScheduledSyncResult) => void
+): Promise<() => void> {
+  return listen<ScheduledSyncResult>("sync-finished", (event) => handler(event.payload));
+}
+
+// ============================================================================
+// Startup Actions
+// ============================================================================
+
+let startupReport: Promise<StartupReport> | null = null;
+
+/**
+ * Get what the startup actions (backup, sync, update check, view restore) did,
+ * waiting for them to finish
+ */
+export function getStartupReport(): Promise<StartupReport> {
+  startupReport ??= (async () => {
+    let resolveFinished: (report: StartupReport) => void = () => {};
+    const finished = new Promise<StartupReport>((resolve) => (resolveFinished = resolve));
+    // Listen before asking, so the event can't slip in between
+    const unlisten = await listen<StartupReport>("startup-actions-finished", (event) =>
+      resolveFinished(event.payload)
+    );
+    try {
+      const report = await commands.getStartupReport();
+      return report.finished ? report : await finished;
+    } finally {
+      unlisten();
+      // The startup actions record lastSyncDate and lastUpdateCheck
+      invalidateSettingsCache();
+    }
+  })();
+  return startupReport;
+}
+
 // ============================================================================
 // Demo Mode
 // ============================================================================
@@ -328,14 +763,14 @@ export async function isSyncNeeded(): Promise<boolean> {
  * Get current demo mode status
  */
 export async function getDemoMode(): Promise<boolean> {
-  return invoke<boolean>("get_demo_mode");
+  return commands.getDemoMode();
 }
 
 /**
  * Set demo mode (requires window reload to take effect)
  */
 export async function setDemoMode(enabled: boolean): Promise<void> {
-  await invoke("set_demo_mode", { enabled });
+  await commands.setDemoMode(enabled);
 }
 
 /**
@@ -343,7 +778,7 @@ export async function setDemoMode(enabled: boolean): Promise<void> {
  */
 export async function enableDemo(): Promise<void> {
   await withActivity("Enabling demo mode", async () => {
-    await invoke("enable_demo");
+    await commands.enableDemo();
   });
   // rust-core modifies config directly, so invalidate our cache
   invalidateSettingsCache();
@@ -353,7 +788,7 @@ export async function enableDemo(): Promise<void> {
  * Disable demo mode
  */
 export async function disableDemo(): Promise<void> {
-  await invoke("disable_demo");
+  await commands.disableDemo();
   // rust-core modifies config directly, so invalidate our cache
   invalidateSettingsCache();
 }
@@ -362,66 +797,30 @@ export async function disableDemo(): Promise<void> {
 // Balance Backfill (Preview/Execute Pattern)
 // ============================================================================
 
-/**
- * Summary of a transaction for preview display
- */
-export interface TransactionSummary {
-  description: string;
-  amount: number;
-}
-
-/**
- * Preview of a single balance snapshot for UI display
- */
-export interface BalanceSnapshotPreview {
-  date: string;
-  /** The calculated balance for this date (end of day) */
-  balance: number;
-  /** Net transaction amount for this day (positive = inflow, negative = outflow), 0 if no transactions */
-  daily_change: number;
-  /** Transactions that occurred on this day */
-  transactions: TransactionSummary[];
-  /** True if no existing snapshot exists (will be created) */
-  is_new: boolean;
-  /** The existing balance if one exists (will be replaced) */
-  existing_balance: number | null;
-  /** Source of existing snapshot: "sync", "manual", "backfill", "import", or null */
-  existing_source: string | null;
-}
-
-/**
- * Result of executing balance backfill
- */
-export interface BackfillExecuteResult {
-  snapshots_created: number;
-  snapshots_updated: number;
-  snapshots_skipped: number;
-}
-
 /**
  * Preview balance backfill - shows what snapshots would be created/updated
  * Returns a list of calculated end-of-day balances without persisting them
  *
  * @param accountId - The account ID to preview backfill for
- * @param knownBalance - A known balance amount
+ * @param knownBalance - A known balance amount (a decimal string keeps it exact)
  * @param knownDate - The date of the known balance (YYYY-MM-DD format)
  * @param startDate - Optional start of date range (YYYY-MM-DD format)
  * @param endDate - Optional end of date range (YYYY-MM-DD format)
  */
 export async function backfillPreview(
   accountId: string,
-  knownBalance: number,
+  knownBalance: number | string,
   knownDate: string,
   startDate?: string,
   endDate?: string
 ): Promise<BalanceSnapshotPreview[]> {
-  return invoke<BalanceSnapshotPreview[]>("backfill_preview", {
+  return commands.backfillPreview(
     accountId,
-    knownBalance,
+    toAmountString(knownBalance),
     knownDate,
-    startDate: startDate || null,
-    endDate: endDate || null,
-  });
+    startDate || null,
+    endDate || null
+  );
 }
 
 /**
@@ -429,25 +828,25 @@ export async function backfillPreview(
  * Replaces all existing snapshots in range with calculated values
  *
  * @param accountId - The account ID to backfill
- * @param knownBalance - A known balance amount
+ * @param knownBalance - A known balance amount (a decimal string keeps it exact)
  * @param knownDate - The date of the known balance (YYYY-MM-DD format)
  * @param startDate - Optional start of date range (YYYY-MM-DD format)
  * @param endDate - Optional end of date range (YYYY-MM-DD format)
  */
 export async function backfillExecute(
   accountId: string,
-  knownBalance: number,
+  knownBalance: number | string,
   knownDate: string,
   startDate?: string,
   endDate?: string
 ): Promise<BackfillExecuteResult> {
-  return invoke<BackfillExecuteResult>("backfill_execute", {
+  return commands.backfillExecute(
     accountId,
-    knownBalance,
+    toAmountString(knownBalance),
     knownDate,
-    startDate: startDate || null,
-    endDate: endDate || null,
-  });
+    startDate || null,
+    endDate || null
+  );
 }
 
 // ============================================================================
@@ -462,10 +861,78 @@ export interface ImportColumnMapping {
   creditColumn?: string;
   /** Optional running balance column - creates balance snapshots when imported */
   balanceColumn?: string;
+  /** Optional account column for files that cover several accounts */
+  accountColumn?: string;
+  /** Optional category column, imported as a tag */
+  categoryColumn?: string;
+  /** Optional tags column (comma, semicolon or pipe separated) */
+  tagsColumn?: string;
+  /** Optional check number column */
+  checkNumberColumn?: string;
+  /** Optional reference/confirmation number column */
+  referenceColumn?: string;
+  /** Optional debit/credit indicator column for unsigned amounts */
+  directionColumn?: string;
+  /** Optional currency column for files mixing currencies (Wise, Revolut) */
+  currencyColumn?: string;
+  /** strftime format of the date column, e.g. "%d.%m.%Y" (common formats are tried when unset) */
+  dateFormat?: string;
+}
+
+/** How account-column values are routed to accounts (multi-account files) */
+export interface ImportAccountAssignment {
+  /** CSV value -> account ID or name, e.g. { "VISA ...1234": "<account id>" } */
+  accountMap?: Record<string, string>;
+  /** Create accounts for values that match no existing account */
+  createMissingAccounts?: boolean;
 }
 
 export type NumberFormat = "us" | "eu" | "eu_space";
 
+/** A CSV row that will be skipped, with the offending value and why */
+export interface ImportRowError {
+  /** 1-based line number in the CSV file */
+  line: number;
+  column: string;
+  value: string;
+  reason: string;
+}
+
+/** What an import does with a CSV row */
+export type ImportRowStatusKind = "new" | "duplicate" | "invalid";
+
+/** One CSV row's status in an import preview */
+export interface ImportRowStatus {
+  /** 1-based line number in the CSV file */
+  line: number;
+  status: ImportRowStatusKind;
+  /** Why an invalid row is rejected */
+  reason?: string;
+}
+
+/** Net balance movement for one month of an import */
+export interface MonthlyBalanceDelta {
+  /** YYYY-MM */
+  month: string;
+  count: number;
+  inflow: string;
+  outflow: string;
+  net: string;
+}
+
+/** Projected effect of an import on the account balance */
+export interface ImportBalanceImpact {
+  months: MonthlyBalanceDelta[];
+  net_change: string;
+  snapshot_balance?: string;
+  snapshot_date?: string;
+  /** Latest snapshot plus transactions dated after it */
+  projected_balance?: string;
+  csv_end_balance?: string;
+  /** Suspicious discrepancies (e.g. flipped signs) worth checking before importing */
+  warnings?: string[];
+}
+
 export interface ImportPreviewResult {
   file: string;
   flip_signs: boolean;
@@ -475,9 +942,47 @@ export interface ImportPreviewResult {
   preview: Array<{
     date: string;
     description: string | null;
-    amount: number;
-    balance?: number | null;
+    amount: string; // Decimal string
+    balance?: string | null;
+    /** Target account name (multi-account imports only) */
+    account?: string | null;
+    /** Tags from the category/tags columns */
+    tags?: string[];
+    check_number?: string | null;
+    reference?: string | null;
+    /** Row currency (multi-currency imports only) */
+    currency?: string | null;
+    /** New, or already imported and will be skipped */
+    status: "new" | "duplicate";
   }>;
+  /** Rows already imported, which the import will skip */
+  duplicates: number;
+  /** What will happen to each data row, in file order */
+  rows?: ImportRowStatus[];
+  errors?: ImportRowError[];
+  /** Accounts that will be created for unassigned account-column values */
+  accounts_created?: string[];
+  /** Date format detected from the whole date column (when none was set) */
+  date_format?: {
+    format: string;
+    /** Other formats that fit every date but read some differently */
+    alternatives?: string[];
+  } | null;
+  /** E.g. dates that read differently as DD/MM and MM/DD */
+  warnings?: string[];
+  /** Rows whose amount doesn't match the change in the balance column */
+  balance_mismatches?: BalanceMismatch[];
+  balance_impact?: ImportBalanceImpact;
+}
+
+export interface BalanceMismatch {
+  line: number;
+  /** Decimal string */
+  amount: string;
+  /** How much the balance column moved at this row, decimal string */
+  balance_change: string;
+  /** sign_reversed: moved by the amount negated; gap: a missing row or wrong value */
+  kind: "sign_reversed" | "gap";
 }
 
 export interface ImportExecuteResult {
@@ -485,29 +990,109 @@ export interface ImportExecuteResult {
   discovered: number;
   imported: number;
   skipped: number;
+  /** Rows skipped because they were already imported */
+  duplicates: number;
   fingerprints_checked: number;
   /** Number of balance snapshots created from running balance column */
   balance_snapshots_created: number;
+  /** Accounts created for unassigned account-column values */
+  accounts_created?: string[];
 }
 
 /**
- * Open file picker dialog for CSV files
+ * Open file picker dialog for CSV files and PDF statements
  */
 export async function pickCsvFile(): Promise<string | null> {
-  const result = await invoke<string | null>("pick_csv_file");
-  return result;
+  return commands.pickCsvFile();
 }
 
 /**
- * Get CSV column headers for mapping UI
+ * Read a PDF statement's transaction table (text layer only, no OCR) into a
+ * CSV file with Date, Description, Amount and Balance columns
+ * @returns Path of the CSV, to import like any other
+ */
+export async function convertPdfStatement(filePath: string): Promise<string> {
+  return commands.convertPdfStatement(filePath);
+}
+
+export type { QuoteStyle as CsvQuoteStyle } from "./bindings";
+
+export type CsvHeadersResult = CsvHeaders;
+
+/**
+ * Get CSV column headers for mapping UI, along with the sniffed dialect
  * @param filePath Path to the CSV file
  * @param skipRows Number of rows to skip before the header row (default: 0)
+ * @param noHeader The file has no header row; headers are "Column 1".."Column N"
  */
 export async function getCsvHeaders(
   filePath: string,
-  skipRows: number = 0
-): Promise<string[]> {
-  return invoke<string[]>("get_csv_headers", { filePath, skipRows });
+  skipRows: number = 0,
+  noHeader: boolean = false
+): Promise<CsvHeadersResult> {
+  return commands.getCsvHeaders(filePath, skipRows, noHeader);
+}
+
+/** A bank or app export layout recognized by detectImportPreset */
+export interface ImportPresetMatch {
+  /** Preset id, as used by `tl import --preset` (or the `tl import` subcommand for app exports) */
+  id: string;
+  name: string;
+  /** Rows above the header row */
+  skipRows: number;
+  headers: string[];
+  columnMapping: ImportColumnMapping;
+  flipSigns: boolean;
+  debitNegative: boolean;
+  numberFormat: NumberFormat;
+}
+
+/** detect_import_preset JSON (a core ImportProfile inside) */
+interface RawPresetMatch {
+  id: string;
+  name: string;
+  skip_rows: number;
+  headers: string[];
+  profile: {
+    columnMappings: Record<string, string | null>;
+    dateFormat: string | null;
+    options: { flipSigns: boolean; debitNegative: boolean; numberFormat: NumberFormat | null };
+  };
+}
+
+/**
+ * Recognize the bank or app (YNAB, Mint, Apple Card) a CSV export came from by its header row
+ * @param filePath Path to the CSV file
+ * @returns The preset's mappings and options, or null when no preset fits
+ */
+export async function detectImportPreset(filePath: string): Promise<ImportPresetMatch | null> {
+  const raw = JSON.parse(await commands.detectImportPreset(filePath)) as RawPresetMatch | null;
+  if (!raw) return null;
+  const columns = raw.profile.columnMappings;
+  const column = (name: string) => columns[name] ?? undefined;
+  return {
+    id: raw.id,
+    name: raw.name,
+    skipRows: raw.skip_rows,
+    headers: raw.headers,
+    columnMapping: {
+      dateColumn: column("date"),
+      amountColumn: column("amount"),
+      descriptionColumn: column("description"),
+      debitColumn: column("debit"),
+      creditColumn: column("credit"),
+      balanceColumn: column("balance"),
+      categoryColumn: column("category"),
+      tagsColumn: column("tags"),
+      checkNumberColumn: column("check_number"),
+      referenceColumn: column("reference"),
+      directionColumn: column("direction"),
+      dateFormat: raw.profile.dateFormat ?? undefined,
+    },
+    flipSigns: raw.profile.options.flipSigns,
+    debitNegative: raw.profile.options.debitNegative,
+    numberFormat: raw.profile.options.numberFormat ?? "us",
+  };
 }
 
 /**
@@ -522,25 +1107,42 @@ export async function importCsvPreview(
   debitNegative: boolean = false,
   skipRows: number = 0,
   numberFormat: NumberFormat = "us",
-  anchorBalance?: number,
-  anchorDate?: string
+  anchorBalance?: number | string,
+  anchorDate?: string,
+  columnNumberFormats: Record<string, NumberFormat> = {},
+  accountAssignment: ImportAccountAssignment = {},
+  categoryMap: Record<string, string> = {},
+  noHeader: boolean = false
 ): Promise<ImportPreviewResult> {
-  const jsonString = await invoke<string>("import_csv_preview", {
+  const jsonString = await commands.importCsvPreview(
     filePath,
     accountId,
-    dateColumn: columnMapping.dateColumn || null,
-    amountColumn: columnMapping.amountColumn || null,
-    descriptionColumn: columnMapping.descriptionColumn || null,
-    debitColumn: columnMapping.debitColumn || null,
-    creditColumn: columnMapping.creditColumn || null,
-    balanceColumn: columnMapping.balanceColumn || null,
+    columnMapping.dateColumn || null,
+    columnMapping.amountColumn || null,
+    columnMapping.descriptionColumn || null,
+    columnMapping.debitColumn || null,
+    columnMapping.creditColumn || null,
+    columnMapping.balanceColumn || null,
     flipSigns,
     debitNegative,
     skipRows,
     numberFormat,
-    anchorBalance: anchorBalance ?? null,
-    anchorDate: anchorDate ?? null,
-  });
+    anchorBalance != null ? toAmountString(anchorBalance) : null,
+    anchorDate ?? null,
+    columnNumberFormats,
+    columnMapping.accountColumn || null,
+    accountAssignment.accountMap ?? null,
+    accountAssignment.createMissingAccounts ?? false,
+    columnMapping.categoryColumn || null,
+    columnMapping.tagsColumn || null,
+    columnMapping.checkNumberColumn || null,
+    columnMapping.referenceColumn || null,
+    columnMapping.directionColumn || null,
+    columnMapping.currencyColumn || null,
+    categoryMap,
+    columnMapping.dateFormat || null,
+    noHeader
+  );
   return JSON.parse(jsonString) as ImportPreviewResult;
 }
 
@@ -554,25 +1156,153 @@ export async function importCsvExecute(
   flipSigns: boolean = false,
   debitNegative: boolean = false,
   skipRows: number = 0,
-  numberFormat: NumberFormat = "us"
+  numberFormat: NumberFormat = "us",
+  columnNumberFormats: Record<string, NumberFormat> = {},
+  accountAssignment: ImportAccountAssignment = {},
+  categoryMap: Record<string, string> = {},
+  noHeader: boolean = false
 ): Promise<ImportExecuteResult> {
-  const jsonString = await invoke<string>("import_csv_execute", {
+  const jsonString = await commands.importCsvExecute(
     filePath,
     accountId,
-    dateColumn: columnMapping.dateColumn || null,
-    amountColumn: columnMapping.amountColumn || null,
-    descriptionColumn: columnMapping.descriptionColumn || null,
-    debitColumn: columnMapping.debitColumn || null,
-    creditColumn: columnMapping.creditColumn || null,
-    balanceColumn: columnMapping.balanceColumn || null,
+    columnMapping.dateColumn || null,
+    columnMapping.amountColumn || null,
+    columnMapping.descriptionColumn || null,
+    columnMapping.debitColumn || null,
+    columnMapping.creditColumn || null,
+    columnMapping.balanceColumn || null,
     flipSigns,
     debitNegative,
     skipRows,
     numberFormat,
-  });
+    columnNumberFormats,
+    columnMapping.accountColumn || null,
+    accountAssignment.accountMap ?? null,
+    accountAssignment.createMissingAccounts ?? false,
+    columnMapping.categoryColumn || null,
+    columnMapping.tagsColumn || null,
+    columnMapping.checkNumberColumn || null,
+    columnMapping.referenceColumn || null,
+    columnMapping.directionColumn || null,
+    columnMapping.currencyColumn || null,
+    categoryMap,
+    columnMapping.dateFormat || null,
+    noHeader
+  );
+  return JSON.parse(jsonString) as ImportExecuteResult;
+}
+
+export type ImportStage = "parsing" | "deduplicating" | "inserting" | "finishing";
+
+export interface ImportProgress {
+  operation_id: string;
+  stage: ImportStage;
+  /** e.g. "Saving transactions" */
+  message: string;
+  /** Rows read or transactions saved so far in this stage */
+  done: number;
+  total: number;
+  /** Share of the whole import done, 0 to 1 */
+  fraction: number;
+}
+
+/**
+ * Call `handler` as a running `importCsvExecute` reads rows and saves
+ * transactions
+ * @returns a function that stops listening
+ */
+export async function onImportProgress(
+  handler: (progress: ImportProgress) => void
+): Promise<() => void> {
+  return listen<ImportProgress>("import-progress", (event) => handler(event.payload));
+}
+
+/**
+ * Import CSV text without saving it to a file first, e.g. a table pasted
+ * from a bank's website (tab-separated tables work too)
+ */
+export async function importCsvFromText(
+  content: string,
+  accountId: string,
+  mappings: ImportProfileColumnMappings = {},
+  options: ImportProfileOptions = {},
+  previewOnly: boolean = false
+): Promise<ImportExecuteResult> {
+  const jsonString = await commands.importCsvFromText(
+    content,
+    accountId,
+    JSON.stringify({ date: "Date", amount: "Amount", ...mappings }),
+    JSON.stringify(options),
+    previewOnly
+  );
   return JSON.parse(jsonString) as ImportExecuteResult;
 }
 
+export type ValidationIssueKind =
+  | "missing_column"
+  | "column_count"
+  | "invalid_date"
+  | "invalid_amount"
+  | "ambiguous_dates"
+  | "sign_distribution"
+  | "balance_mismatch";
+
+export interface ValidationIssue {
+  kind: ValidationIssueKind;
+  /** 1-based line in the file, for row issues */
+  line?: number;
+  column?: string;
+  /** Raw cell value as read from the file */
+  value?: string;
+  message: string;
+}
+
+export interface ImportValidation {
+  /** Data rows in the file */
+  rows: number;
+  /** Rows with a usable date and amount */
+  valid_rows: number;
+  /** False when a required column is missing or no row is valid */
+  importable: boolean;
+  date_format?: {
+    format: string;
+    alternatives?: string[];
+  };
+  /** Whole-file issues first, then rows in file order */
+  issues: ValidationIssue[];
+}
+
+/**
+ * Check a whole CSV file before importing it: bad dates and amounts, rows
+ * with the wrong number of fields, missing columns and reversed-looking signs
+ */
+export async function validateCsvImport(
+  filePath: string,
+  mappings: ImportProfileColumnMappings = {},
+  options: ImportProfileOptions = {}
+): Promise<ImportValidation> {
+  const jsonString = await commands.validateCsvImport(
+    filePath,
+    JSON.stringify({ ...mappings, date: mappings.date || "Date", amount: mappings.amount || "Amount" }),
+    JSON.stringify(options)
+  );
+  return JSON.parse(jsonString) as ImportValidation;
+}
+
+export interface UndoImportResult {
+  batch_id: string;
+  transactions_deleted: number;
+  balance_snapshots_deleted: number;
+}
+
+/**
+ * Undo an import, deleting the transactions and balance snapshots it created
+ */
+export async function undoImport(batchId: string): Promise<UndoImportResult> {
+  const jsonString = await commands.undoImport(batchId);
+  return JSON.parse(jsonString) as UndoImportResult;
+}
+
 // ============================================================================
 // Import Profiles (named, reusable across accounts)
 // ============================================================================
@@ -585,6 +1315,18 @@ export interface ImportProfileColumnMappings {
   credit?: string;
   /** Optional running balance column - creates balance snapshots when imported */
   balance?: string;
+  /** Optional account column for files that cover several accounts */
+  account?: string;
+  /** Optional category column, imported as a tag */
+  category?: string;
+  /** Optional tags column */
+  tags?: string;
+  checkNumber?: string;
+  reference?: string;
+  /** Optional debit/credit indicator column */
+  direction?: string;
+  /** Optional per-row currency column */
+  currency?: string;
 }
 
 export interface ImportProfileOptions {
@@ -592,6 +1334,14 @@ export interface ImportProfileOptions {
   debitNegative?: boolean;
   skipRows?: number;
   numberFormat?: NumberFormat;
+  /** strftime format for the date column, e.g. "%d.%m.%Y" */
+  dateFormat?: string;
+  accountMap?: Record<string, string>;
+  createMissingAccounts?: boolean;
+  /** Bank category -> tag translations (empty tag drops the category) */
+  categoryMap?: Record<string, string>;
+  /** The file has no header row; columns are "Column 1".."Column N" */
+  noHeader?: boolean;
 }
 
 export interface ImportProfile {
@@ -627,14 +1377,91 @@ function ensureImportProfilesContainer(settings: SettingsWithProfiles): ImportPr
   return settings.importProfiles as ImportProfilesContainer;
 }
 
+/**
+ * An import profile as treeline-core stores it (shared with `tl import --profile`)
+ */
+interface CoreImportProfile {
+  columnMappings: {
+    date: string;
+    amount: string;
+    description?: string | null;
+    debit?: string | null;
+    credit?: string | null;
+    balance?: string | null;
+    account?: string | null;
+    category?: string | null;
+    tags?: string | null;
+    check_number?: string | null;
+    reference?: string | null;
+    direction?: string | null;
+    currency?: string | null;
+  };
+  dateFormat?: string | null;
+  skipRows?: number;
+  options: {
+    flipSigns?: boolean;
+    debitNegative?: boolean;
+    numberFormat?: string | null;
+    columnNumberFormats?: Record<string, string>;
+    accountMap?: Record<string, string>;
+    createMissingAccounts?: boolean;
+    categoryMap?: Record<string, string>;
+    noHeader?: boolean;
+  };
+}
+
+function fromCoreImportProfile(profile: CoreImportProfile): ImportProfile {
+  const { check_number, ...columns } = profile.columnMappings;
+  const columnMappings: ImportProfileColumnMappings = {};
+  for (const [key, value] of Object.entries({ ...columns, checkNumber: check_number })) {
+    if (value) {
+      columnMappings[key as keyof ImportProfileColumnMappings] = value;
+    }
+  }
+  const { numberFormat, ...options } = profile.options;
+  return {
+    columnMappings,
+    options: {
+      ...options,
+      skipRows: profile.skipRows || undefined,
+      numberFormat: (numberFormat as NumberFormat) || undefined,
+      dateFormat: profile.dateFormat || undefined,
+    },
+  };
+}
+
+function toCoreImportProfile(
+  columnMappings: ImportProfileColumnMappings,
+  options: ImportProfileOptions
+): CoreImportProfile {
+  const { checkNumber, date, amount, ...columns } = columnMappings;
+  const { skipRows, dateFormat, numberFormat, ...rest } = options;
+  return {
+    columnMappings: {
+      ...columns,
+      date: date || "Date",
+      amount: amount || "Amount",
+      check_number: checkNumber,
+    },
+    dateFormat: dateFormat || null,
+    skipRows: skipRows ?? 0,
+    options: {
+      ...rest,
+      // "us" is the default and is left out, as `tl import --save-profile` does
+      numberFormat: numberFormat && numberFormat !== "us" ? numberFormat : null,
+    },
+  };
+}
+
 /**
  * Get all import profiles
  */
 export async function getImportProfiles(): Promise<Record<string, ImportProfile>> {
-  const jsonString = await invoke<string>("read_settings");
-  const settings = JSON.parse(jsonString) as SettingsWithProfiles;
-  const container = ensureImportProfilesContainer(settings);
-  return container.profiles;
+  const jsonString = await commands.listImportProfiles();
+  const profiles = JSON.parse(jsonString) as Record<string, CoreImportProfile>;
+  return Object.fromEntries(
+    Object.entries(profiles).map(([name, profile]) => [name, fromCoreImportProfile(profile)])
+  );
 }
 
 /**
@@ -661,16 +1488,10 @@ export async function saveImportProfile(
   columnMappings: ImportProfileColumnMappings,
   options: ImportProfileOptions = {}
 ): Promise<void> {
-  const jsonString = await invoke<string>("read_settings");
-  const settings = JSON.parse(jsonString) as SettingsWithProfiles;
-  const container = ensureImportProfilesContainer(settings);
-
-  container.profiles[name] = {
-    columnMappings,
-    options,
-  };
-
-  await invoke("write_settings", { content: JSON.stringify(settings, null, 2) });
+  await commands.saveImportProfile(
+    name,
+    JSON.stringify(toCoreImportProfile(columnMappings, options))
+  );
   invalidateSettingsCache();
 }
 
@@ -678,18 +1499,9 @@ export async function saveImportProfile(
  * Delete an import profile by name
  */
 export async function deleteImportProfile(name: string): Promise<boolean> {
-  const jsonString = await invoke<string>("read_settings");
-  const settings = JSON.parse(jsonString) as SettingsWithProfiles;
-  const container = ensureImportProfilesContainer(settings);
-
-  if (!container.profiles[name]) {
-    return false;
-  }
-
-  delete container.profiles[name];
-  await invoke("write_settings", { content: JSON.stringify(settings, null, 2) });
+  const deleted = await commands.deleteImportProfile(name);
   invalidateSettingsCache();
-  return true;
+  return deleted;
 }
 
 // ============================================================================
@@ -700,7 +1512,7 @@ export async function deleteImportProfile(name: string): Promise<boolean> {
  * Get the profile name mapped to an account
  */
 export async function getAccountProfileMapping(accountId: string): Promise<string | null> {
-  const jsonString = await invoke<string>("read_settings");
+  const jsonString = await commands.readSettings();
   const settings = JSON.parse(jsonString) as SettingsWithProfiles;
   const container = ensureImportProfilesContainer(settings);
   return container.accountMappings[accountId] || null;
@@ -710,13 +1522,13 @@ export async function getAccountProfileMapping(accountId: string): Promise<strin
  * Set the profile mapping for an account
  */
 export async function setAccountProfileMapping(accountId: string, profileName: string): Promise<void> {
-  const jsonString = await invoke<string>("read_settings");
+  const jsonString = await commands.readSettings();
   const settings = JSON.parse(jsonString) as SettingsWithProfiles;
   const container = ensureImportProfilesContainer(settings);
 
   container.accountMappings[accountId] = profileName;
 
-  await invoke("write_settings", { content: JSON.stringify(settings, null, 2) });
+  await commands.writeSettings(JSON.stringify(settings, null, 2));
   invalidateSettingsCache();
 }
 
@@ -724,7 +1536,7 @@ export async function setAccountProfileMapping(accountId: string, profileName: s
  * Remove the profile mapping for an account
  */
 export async function removeAccountProfileMapping(accountId: string): Promise<boolean> {
-  const jsonString = await invoke<string>("read_settings");
+  const jsonString = await commands.readSettings();
   const settings = JSON.parse(jsonString) as SettingsWithProfiles;
   const container = ensureImportProfilesContainer(settings);
 
@@ -733,7 +1545,7 @@ export async function removeAccountProfileMapping(accountId: string): Promise<bo
   }
 
   delete container.accountMappings[accountId];
-  await invoke("write_settings", { content: JSON.stringify(settings, null, 2) });
+  await commands.writeSettings(JSON.stringify(settings, null, 2));
   invalidateSettingsCache();
   return true;
 }
@@ -742,7 +1554,7 @@ export async function removeAccountProfileMapping(accountId: string): Promise<bo
  * Get all account to profile mappings
  */
 export async function getAccountProfileMappings(): Promise<Record<string, string>> {
-  const jsonString = await invoke<string>("read_settings");
+  const jsonString = await commands.readSettings();
   const settings = JSON.parse(jsonString) as SettingsWithProfiles;
   const container = ensureImportProfilesContainer(settings);
   return container.accountMappings;
@@ -756,7 +1568,7 @@ export async function getAccountProfileMappings(): Promise<Record<string, string
  * Setup SimpleFIN integration with a setup token
  */
 export async function setupSimplefin(token: string): Promise<string> {
-  return invoke<string>("setup_simplefin", { token });
+  return commands.setupSimplefin(token);
 }
 
 /**
@@ -772,7 +1584,227 @@ export async function setupLunchflow(
   apiKey: string,
   baseUrl?: string
 ): Promise<string> {
-  return invoke<string>("setup_lunchflow", { apiKey, baseUrl });
+  return commands.setupLunchflow(apiKey, baseUrl ?? null);
+}
+
+/** Plaid API keys from the dashboard, and which environment they're for */
+export interface PlaidKeys {
+  clientId: string;
+  secret: string;
+  environment: "sandbox" | "production";
+  /** Custom base URL for testing (omit for the environment's) */
+  baseUrl?: string;
+}
+
+/**
+ * Create a link token for opening Plaid Link
+ *
+ * Plaid Link hands back a public token once the user logs in to their bank;
+ * pass it to `setupPlaid`.
+ */
+export async function createPlaidLinkToken(keys: PlaidKeys): Promise<string> {
+  return commands.createPlaidLinkToken(
+    keys.clientId,
+    keys.secret,
+    keys.environment,
+    keys.baseUrl ?? null
+  );
+}
+
+/**
+ * Link a bank through Plaid
+ *
+ * Each bank is linked separately; linking another one adds it to the banks
+ * already linked.
+ *
+ * @param publicToken - The public token from Plaid Link's onSuccess
+ */
+export async function setupPlaid(keys: PlaidKeys, publicToken: string): Promise<string> {
+  return commands.setupPlaid(
+    keys.clientId,
+    keys.secret,
+    keys.environment,
+    publicToken,
+    keys.baseUrl ?? null
+  );
+}
+
+/** GoCardless user secrets from the Bank Account Data portal */
+export interface GoCardlessSecrets {
+  secretId: string;
+  secretKey: string;
+  /** Custom base URL for testing (omit for production) */
+  baseUrl?: string;
+}
+
+/** A bank GoCardless can connect to */
+export interface GoCardlessInstitution {
+  /** e.g. "REVOLUT_REVOGB21" */
+  id: string;
+  name: string;
+  bic: string | null;
+  /** How far back the bank offers transactions */
+  transaction_total_days: number | null;
+  /** Longest access the bank allows before consent must be renewed */
+  max_access_valid_for_days: number | null;
+  countries: string[];
+  logo: string | null;
+}
+
+/**
+ * List the banks GoCardless can connect to in a country
+ *
+ * @param country - ISO 3166 country code, e.g. "DE"
+ */
+export async function listGoCardlessInstitutions(
+  secrets: GoCardlessSecrets,
+  country: string
+): Promise<GoCardlessInstitution[]> {
+  const jsonString = await commands.listGocardlessInstitutions(
+    secrets.secretId,
+    secrets.secretKey,
+    country,
+    secrets.baseUrl ?? null
+  );
+  return JSON.parse(jsonString) as GoCardlessInstitution[];
+}
+
+/**
+ * Start connecting a bank through GoCardless
+ *
+ * Returns the link where the user gives consent at their bank; its accounts
+ * sync once they have. Connecting a bank again renews its access, which
+ * banks limit to 90 days or so.
+ *
+ * @param institutionId - The bank, from `listGoCardlessInstitutions`
+ * @param redirectUrl - Where the bank sends the user afterwards (omit for Treeline's site)
+ */
+export async function setupGoCardless(
+  secrets: GoCardlessSecrets,
+  institutionId: string,
+  redirectUrl?: string
+): Promise<string> {
+  return commands.setupGocardless(
+    secrets.secretId,
+    secrets.secretKey,
+    institutionId,
+    redirectUrl ?? null,
+    secrets.baseUrl ?? null
+  );
+}
+
+/** A crypto exchange API key, and the currency to value balances in */
+export interface ExchangeKeys {
+  apiKey: string;
+  apiSecret: string;
+  /** e.g. "USD" (omit for the exchange's default) */
+  currency?: string;
+  /** Custom base URL for testing (omit for production) */
+  baseUrl?: string;
+}
+
+/**
+ * Set up Coinbase with a read-only API key
+ *
+ * Each wallet holding something becomes a crypto account valued at
+ * Coinbase's exchange rate, with its transactions. Without a currency,
+ * balances are valued in the Coinbase account's currency.
+ */
+export async function setupCoinbase(keys: ExchangeKeys): Promise<string> {
+  return commands.setupCoinbase(
+    keys.apiKey,
+    keys.apiSecret,
+    keys.currency ?? null,
+    keys.baseUrl ?? null
+  );
+}
+
+/**
+ * Set up Kraken with an API key that has the Query Funds permission
+ *
+ * Each asset held becomes a crypto account valued at Kraken's last trade
+ * price (USD unless a currency is given). Only balances are synced.
+ */
+export async function setupKraken(keys: ExchangeKeys): Promise<string> {
+  return commands.setupKraken(
+    keys.apiKey,
+    keys.apiSecret,
+    keys.currency ?? null,
+    keys.baseUrl ?? null
+  );
+}
+
+/**
+ * Replace an integration's credentials, keeping its account settings
+ *
+ * @param name - The integration name ("simplefin", "lunchflow", "plaid" or "gocardless")
+ * @param credential - A new SimpleFIN setup token, Lunchflow API key, Plaid secret
+ *   or GoCardless secret key
+ */
+export async function rotateIntegration(name: string, credential: string): Promise<void> {
+  await commands.rotateIntegration(name, credential);
+}
+
+/**
+ * An account at an integration's provider, and how sync treats it
+ */
+export interface RemoteAccount {
+  /** The provider's account ID */
+  external_id: string;
+  name: string;
+  institution_name: string | null;
+  currency: string;
+  /** Connection status reported by the provider (Lunchflow) */
+  status: string | null;
+  /** Treeline account it syncs into, once synced */
+  account_id: string | null;
+  /** Whether sync includes this account */
+  enabled: boolean;
+  /** Whether sync fetches only this account's balance */
+  balances_only: boolean;
+}
+
+/**
+ * List the accounts an integration's provider offers, including ones that
+ * have never been synced or are turned off
+ *
+ * @param name - The integration name (e.g., "lunchflow", "simplefin")
+ */
+export async function listIntegrationAccounts(name: string): Promise<RemoteAccount[]> {
+  const jsonString = await commands.listIntegrationAccounts(name);
+  return JSON.parse(jsonString) as RemoteAccount[];
+}
+
+/**
+ * Include or leave out one of an integration's accounts when syncing
+ *
+ * A turned-off account is neither created nor updated by sync, so closed or
+ * noisy accounts stay out of Treeline.
+ *
+ * @param name - The integration name (e.g., "simplefin")
+ * @param externalId - The provider's account ID
+ * @param enabled - Whether sync should include the account
+ */
+export async function setIntegrationAccountEnabled(
+  name: string,
+  externalId: string,
+  enabled: boolean
+): Promise<void> {
+  await commands.setIntegrationAccountEnabled(name, externalId, enabled);
+}
+
+/**
+ * Remove an integration and its stored credentials
+ *
+ * @param name - The integration name (e.g., "lunchflow")
+ * @param unlinkAccounts - Also unlink its accounts, keeping them as standalone accounts
+ * @returns The number of accounts unlinked
+ */
+export async function removeIntegration(
+  name: string,
+  unlinkAccounts = false
+): Promise<number> {
+  return commands.removeIntegration(name, unlinkAccounts);
 }
 
 // ============================================================================
@@ -783,11 +1815,13 @@ export async function setupLunchflow(
  * Get integration settings from the database
  */
 export async function getIntegrationSettings(integrationName: string): Promise<Record<string, unknown>> {
-  const result = await invoke<string>("execute_query_with_params", {
-    query: `SELECT integration_settings FROM sys_integrations WHERE integration_name = ?`,
-    params: [integrationName],
-    readonly: true,
-  });
+  const result = await commands.executeQueryWithParams(
+    `SELECT integration_settings FROM sys_integrations WHERE integration_name = ?`,
+    [integrationName],
+    true,
+    null,
+    "settings"
+  );
   const parsed = JSON.parse(result);
   if (parsed.rows && parsed.rows.length > 0 && parsed.rows[0][0]) {
     return JSON.parse(parsed.rows[0][0]);
@@ -825,11 +1859,13 @@ export async function updateIntegrationAccountSetting(
 
   // Write back to database
   const settingsJson = JSON.stringify(settings);
-  await invoke<string>("execute_query_with_params", {
-    query: `UPDATE sys_integrations SET integration_settings = ? WHERE integration_name = ?`,
-    params: [settingsJson, integrationName],
-    readonly: false,
-  });
+  await commands.executeQueryWithParams(
+    `UPDATE sys_integrations SET integration_settings = ? WHERE integration_name = ?`,
+    [settingsJson, integrationName],
+    false,
+    null,
+    "settings"
+  );
 }
 
 // ============================================================================
@@ -854,7 +1890,7 @@ export interface PluginInstallResult {
  * @param version - Optional version tag (e.g., "v1.0.0"). Defaults to latest release.
  */
 export async function installPlugin(url: string, version?: string): Promise<PluginInstallResult> {
-  const jsonString = await invoke<string>("install_plugin", { url, version: version || null });
+  const jsonString = await commands.installPlugin(url, version || null);
   return JSON.parse(jsonString) as PluginInstallResult;
 }
 
@@ -864,7 +1900,7 @@ export async function installPlugin(url: string, version?: string): Promise<Plug
  * @param pluginId - The plugin ID to uninstall
  */
 export async function uninstallPlugin(pluginId: string): Promise<{ success: boolean; plugin_id: string; plugin_name: string }> {
-  const jsonString = await invoke<string>("uninstall_plugin", { pluginId });
+  const jsonString = await commands.uninstallPlugin(pluginId);
   return JSON.parse(jsonString);
 }
 
@@ -872,18 +1908,11 @@ export async function uninstallPlugin(pluginId: string): Promise<{ success: bool
 // Encryption
 // ============================================================================
 
-export interface EncryptionStatus {
-  encrypted: boolean;
-  locked: boolean;
-  algorithm: string | null;
-  version: number | null;
-}
-
 /**
  * Get current encryption status
  */
 export async function getEncryptionStatus(): Promise<EncryptionStatus> {
-  return invoke<EncryptionStatus>("get_encryption_status");
+  return commands.getEncryptionStatus();
 }
 
 /**
@@ -891,7 +1920,7 @@ export async function getEncryptionStatus(): Promise<EncryptionStatus> {
  * Returns true if unlocked (or not encrypted), false if needs manual unlock
  */
 export async function tryAutoUnlock(): Promise<boolean> {
-  return invoke<boolean>("try_auto_unlock");
+  return commands.tryAutoUnlock();
 }
 
 /**
@@ -899,7 +1928,35 @@ export async function tryAutoUnlock(): Promise<boolean> {
  * @param password - The encryption password
  */
 export async function unlockDatabase(password: string): Promise<void> {
-  return invoke<void>("unlock_database", { password });
+  await commands.unlockDatabase(password);
+}
+
+export type UnlockStage = "backing_up" | "exporting" | "importing" | "replacing";
+
+export interface UnlockProgress {
+  stage: UnlockStage;
+  /** e.g. "Re-encrypting the database" */
+  message: string;
+}
+
+/**
+ * Call `handler` as `unlockDatabase` re-encrypts a database whose key uses
+ * weaker settings than configured; a plain unlock sends nothing
+ * @returns a function that stops listening
+ */
+export async function onUnlockProgress(
+  handler: (progress: UnlockProgress) => void
+): Promise<() => void> {
+  return listen<UnlockProgress>("unlock-progress", (event) => handler(event.payload));
+}
+
+/**
+ * Lock the encrypted database: the key is wiped from memory and the
+ * "database-locked" event fires so the app returns to the unlock screen
+ */
+export async function lockDatabase(): Promise<void> {
+  await commands.lockDatabase();
+  invalidateSettingsCache();
 }
 
 /**
@@ -907,7 +1964,7 @@ export async function unlockDatabase(password: string): Promise<void> {
  * @param password - The new encryption password
  */
 export async function enableEncryption(password: string): Promise<void> {
-  return invoke<void>("enable_encryption", { password });
+  await commands.enableEncryption(password);
 }
 
 /**
@@ -915,27 +1972,18 @@ export async function enableEncryption(password: string): Promise<void> {
  * @param password - The current encryption password
  */
 export async function disableEncryption(password: string): Promise<void> {
-  return invoke<void>("disable_encryption", { password });
+  await commands.disableEncryption(password);
 }
 
 // ============================================================================
 // Watch Folder / Pending Imports
 // ============================================================================
 
-/**
- * Pending import file information
- */
-export interface PendingImportFile {
-  path: string;
-  filename: string;
-  size_bytes: number;
-}
-
 /**
  * List CSV files waiting in the imports folder (~/.treeline/imports/)
  */
 export async function listPendingImports(): Promise<PendingImportFile[]> {
-  return invoke<PendingImportFile[]>("list_pending_imports");
+  return commands.listPendingImports();
 }
 
 /**
@@ -943,7 +1991,40 @@ export async function listPendingImports(): Promise<PendingImportFile[]> {
  * Call this after a successful import to clean up the pending file
  */
 export async function moveImportedFile(filePath: string): Promise<void> {
-  return invoke<void>("move_imported_file", { filePath });
+  await commands.moveImportedFile(filePath);
+}
+
+export interface FolderImportFile {
+  file: string;
+  /** "unmatched" and "failed" files stay in the imports folder */
+  status: "imported" | "unmatched" | "failed";
+  account_id: string | null;
+  account_name: string | null;
+  profile: string | null;
+  imported: number;
+  skipped: number;
+  moved_to: string | null;
+  /** Why the file was left in place */
+  message: string | null;
+}
+
+export interface FolderImportSummary {
+  files: FolderImportFile[];
+  imported_files: number;
+  unmatched_files: number;
+  failed_files: number;
+  transactions_imported: number;
+  preview: boolean;
+}
+
+/**
+ * Import every pending file into the account whose assigned import profile
+ * matches it (account name in the file name, or the profile's columns), and
+ * move imported files to the "imported" subfolder
+ */
+export async function importAllPending(): Promise<FolderImportSummary> {
+  const jsonString = await commands.importAllPending();
+  return JSON.parse(jsonString) as FolderImportSummary;
 }
 
 // ============================================================================
@@ -954,13 +2035,20 @@ export interface BackupMetadata {
   name: string;
   created_at: string;
   size_bytes: number;
+  /** "deflate", "zstd" or "none"; absent for legacy .duckdb backups */
+  compression?: string;
+  level?: number;
+  /** Size of the archived files before compression */
+  uncompressed_bytes?: number;
+  /** How long creating the backup took; absent for older backups */
+  duration_ms?: number;
 }
 
 /**
  * List all available backups
  */
 export async function listBackups(): Promise<BackupMetadata[]> {
-  const jsonString = await invoke<string>("list_backups");
+  const jsonString = await commands.listBackups();
   return JSON.parse(jsonString) as BackupMetadata[];
 }
 
@@ -969,7 +2057,7 @@ export async function listBackups(): Promise<BackupMetadata[]> {
  * @param maxBackups - Optional max number of backups to retain (auto-deletes oldest)
  */
 export async function createBackup(maxBackups?: number): Promise<BackupMetadata> {
-  const jsonString = await invoke<string>("create_backup", { maxBackups: maxBackups || null });
+  const jsonString = await commands.createBackup(maxBackups || null);
   return JSON.parse(jsonString) as BackupMetadata;
 }
 
@@ -978,7 +2066,7 @@ export async function createBackup(maxBackups?: number): Promise<BackupMetadata>
  * @param backupName - The backup filename to restore from
  */
 export async function restoreBackup(backupName: string): Promise<void> {
-  return invoke<void>("restore_backup", { backupName });
+  await commands.restoreBackup(backupName);
 }
 
 /**
@@ -986,7 +2074,7 @@ export async function restoreBackup(backupName: string): Promise<void> {
  * @param backupName - The backup filename to delete
  */
 export async function deleteBackup(backupName: string): Promise<void> {
-  return invoke<void>("delete_backup", { backupName });
+  await commands.deleteBackup(backupName);
 }
 
 export interface ClearBackupsResult {
@@ -997,10 +2085,43 @@ export interface ClearBackupsResult {
  * Clear all backups
  */
 export async function clearBackups(): Promise<ClearBackupsResult> {
-  const jsonString = await invoke<string>("clear_backups");
+  const jsonString = await commands.clearBackups();
   return JSON.parse(jsonString) as ClearBackupsResult;
 }
 
+// ============================================================================
+// Data Directory
+// ============================================================================
+
+export interface MoveDataResult {
+  from: string;
+  to: string;
+  files_moved: number;
+  bytes_moved: number;
+}
+
+/**
+ * Cloud-sync service the data directory is inside ("iCloud Drive", "Dropbox", ...),
+ * or null when it is safe. Synced folders can corrupt the database.
+ */
+export async function getCloudSyncProvider(): Promise<string | null> {
+  return commands.getCloudSyncProvider();
+}
+
+/**
+ * Move all Treeline data to a new or empty folder.
+ * The old folder keeps a pointer to the new one. Restart the app afterwards.
+ * @param destination - Absolute path of the new folder
+ * @param allowCloudSync - Allow a destination inside a cloud-synced folder
+ */
+export async function moveDataDirectory(
+  destination: string,
+  allowCloudSync = false
+): Promise<MoveDataResult> {
+  const jsonString = await commands.moveDataDirectory(destination, allowCloudSync);
+  return JSON.parse(jsonString) as MoveDataResult;
+}
+
 // ============================================================================
 // Database Compact
 // ============================================================================
@@ -1015,10 +2136,159 @@ export interface CompactResult {
  * Reduces file size by reclaiming unused space
  */
 export async function compactDatabase(): Promise<CompactResult> {
-  const jsonString = await invoke<string>("compact_database");
+  const jsonString = await commands.compactDatabase();
   return JSON.parse(jsonString) as CompactResult;
 }
 
+// ============================================================================
+// Storage
+// ============================================================================
+
+export interface TableStorage {
+  table: string;
+  group: "data" | "plugin" | "history" | "system";
+  rows: number;
+  /** Estimated from the storage blocks the table uses */
+  estimated_bytes: number;
+  /** Whether pruneStorage can empty it */
+  prunable: boolean;
+}
+
+export interface StorageSuggestion {
+  action: "compact" | "archive" | "prune" | "clear_logs";
+  message: string;
+  /** CLI command that does it */
+  command: string;
+  reclaimable_bytes: number | null;
+}
+
+export interface StorageBreakdown {
+  database_path: string;
+  database_bytes: number;
+  wal_bytes: number;
+  /** Free blocks inside the database file, returned by compacting */
+  free_bytes: number;
+  /** Largest first */
+  tables: TableStorage[];
+  logs_bytes: number;
+  backups_bytes: number;
+  archives_bytes: number;
+  suggestions: StorageSuggestion[];
+  generated_at: string;
+}
+
+export interface PruneResult {
+  table: string;
+  rows_deleted: number;
+}
+
+/**
+ * Get where the data directory's space goes, with suggestions for freeing some
+ */
+export async function getStorageBreakdown(): Promise<StorageBreakdown> {
+  const jsonString = await commands.getStorageBreakdown();
+  return JSON.parse(jsonString) as StorageBreakdown;
+}
+
+/**
+ * Empty history tables (query history, sync history)
+ * @param tables - Tables to prune; all prunable tables when empty
+ */
+export async function pruneStorage(tables: string[] = []): Promise<PruneResult[]> {
+  const jsonString = await commands.pruneStorage(tables);
+  return JSON.parse(jsonString) as PruneResult[];
+}
+
+// ============================================================================
+// Institutions
+// ============================================================================
+
+export interface InstitutionInfo {
+  /** e.g. "chase.com" */
+  domain: string;
+  /** The name most of its accounts use */
+  name: string | null;
+  url: string | null;
+  /** Accounts at this bank */
+  account_ids: string[];
+  /** Cached logo as a data URL; null until fetched, or when the bank has none */
+  logo: string | null;
+  checked_at: string | null;
+}
+
+export interface InstitutionRefreshResult {
+  fetched: string[];
+  failed: string[];
+  skipped: number;
+}
+
+/**
+ * Get the banks accounts belong to, with their cached logos
+ *
+ * Never goes to the network, so it's safe to call on every render.
+ */
+export async function getInstitutions(): Promise<InstitutionInfo[]> {
+  const jsonString = await commands.getInstitutions();
+  return JSON.parse(jsonString) as InstitutionInfo[];
+}
+
+/**
+ * Fetch logos that aren't cached yet, from each bank's own site
+ * @param force - Fetch every logo again
+ */
+export async function refreshInstitutionLogos(force = false): Promise<InstitutionRefreshResult> {
+  const jsonString = await commands.refreshInstitutionLogos(force);
+  return JSON.parse(jsonString) as InstitutionRefreshResult;
+}
+
+// ============================================================================
+// Investments
+// ============================================================================
+
+export interface HoldingLot {
+  acquired_date: string | null;
+  quantity: string;
+  cost_basis: string | null;
+}
+
+/** A position in a brokerage account (amounts are decimal strings) */
+export interface HoldingInfo {
+  holding_id: string;
+  account_id: string;
+  account_name: string;
+  symbol: string | null;
+  description: string | null;
+  quantity: string;
+  /** Market value per share or unit */
+  price: string | null;
+  market_value: string | null;
+  cost_basis: string | null;
+  /** Market value minus cost basis */
+  unrealized_gain: string | null;
+  currency: string;
+  /** When the provider valued the holding */
+  as_of: string;
+  lots: HoldingLot[];
+}
+
+export interface HoldingsSummary {
+  /** Largest market value first */
+  holdings: HoldingInfo[];
+  /** Currency -> total market value */
+  market_value_totals: Record<string, string>;
+  /** Currency -> unrealized gain of holdings with a known cost basis */
+  unrealized_gain_totals: Record<string, string>;
+}
+
+/**
+ * Get holdings in brokerage accounts (from providers that report them, e.g. SimpleFIN)
+ * @param accountId - Only this account's holdings; all accounts when omitted
+ */
+export async function getHoldings(accountId?: string): Promise<HoldingsSummary> {
+  const jsonString = await commands.getHoldings(accountId ?? null);
+  return JSON.parse(jsonString) as HoldingsSummary;
+}
+
 /**
  * Format bytes to human-readable size
  */
@@ -1028,3 +2298,261 @@ export function formatBytes(bytes: number): string {
   if (bytes < 1024 * 1024 * 1024) return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
   return `${(bytes / (1024 * 1024 * 1024)).toFixed(1)} GB`;
 }
+
+// ============================================================================
+// Diagnostics
+// ============================================================================
+
+export interface WriteQueuePriorityStats {
+  acquired: number;
+  waiting: number;
+  total_wait_ms: number;
+  max_wait_ms: number;
+}
+
+export interface ReportCacheStats {
+  /** Results held right now */
+  entries: number;
+  hits: number;
+  /** Requests that computed the report, including stale ones */
+  misses: number;
+  /** Misses where the data had changed since the result was cached */
+  stale: number;
+  /** Requests that skipped the cache */
+  bypassed: number;
+}
+
+export interface ConsumerUsage {
+  /** Plugin id, view caller tag, or "user" for the SQL console */
+  consumer: string;
+  queries: number;
+  total_ms: number;
+  max_ms: number;
+  /** Fraction of all query time, 0 to 1 */
+  share: number;
+  over_budget: boolean;
+}
+
+export interface QueryBudgetReport {
+  total_ms: number;
+  /** Share one consumer may use before it's flagged (settings.json `app.queryBudgetShare`) */
+  max_share: number;
+  /** Heaviest first */
+  consumers: ConsumerUsage[];
+  /** One message per consumer over budget */
+  warnings: string[];
+}
+
+export interface Diagnostics {
+  database_path: string;
+  database_size_bytes: number;
+  /** Write-ahead log size; 0 when everything is checkpointed */
+  wal_size_bytes: number;
+  /** Whether another operation (a sync, the CLI) held the database lock */
+  locked: boolean;
+  tables: { table: string; rows: number }[];
+  /** How long the app's operations waited for the database, by priority */
+  write_queue: Record<"interactive" | "background", WriteQueuePriorityStats>;
+  /** How often the app's reports were served from the cache */
+  report_cache: ReportCacheStats;
+  last_migration: { name: string; applied_at: string } | null;
+  backup_count: number;
+  last_backup_at: string | null;
+  last_backup_age_seconds: number | null;
+  /** Null when the logs database isn't available */
+  log_errors: { last_24h: number; last_7d: number; total: number } | null;
+  generated_at: string;
+  /** Seconds since the app's database context was created */
+  context_age_seconds: number | null;
+  /** Query time by view and plugin since the app started */
+  query_budget: QueryBudgetReport;
+}
+
+/**
+ * Get database, backup and log health for the diagnostics screen
+ */
+export async function getDiagnostics(): Promise<Diagnostics> {
+  const jsonString = await commands.getDiagnostics();
+  return JSON.parse(jsonString) as Diagnostics;
+}
+
+// ============================================================================
+// Reports
+// ============================================================================
+
+/**
+ * Save a monthly statement (opening balance, transactions, closing balance,
+ * category summary) as printable HTML. Asks the user where to save it.
+ *
+ * @param month - Month as "YYYY-MM"
+ * @returns The saved path, or null if the user cancelled
+ */
+export async function exportStatement(accountId: string, month: string): Promise<string | null> {
+  return commands.exportStatement(accountId, month);
+}
+
+export interface ReportPeriod {
+  /** First day, YYYY-MM-DD */
+  start: string;
+  /** Last day (inclusive), YYYY-MM-DD */
+  end: string;
+}
+
+export interface ComparisonRow {
+  /** Category, or account name when grouping by account */
+  label: string;
+  /** Decimal amounts as strings; spending is negative */
+  current: string;
+  previous: string;
+  change: string;
+  /** Null when the previous amount was zero */
+  change_percent: number | null;
+  current_count: number;
+  previous_count: number;
+}
+
+export interface PeriodComparison {
+  group_by: "category" | "account";
+  current: ReportPeriod;
+  previous: ReportPeriod;
+  /** Largest change first */
+  rows: ComparisonRow[];
+  total: ComparisonRow;
+}
+
+/**
+ * Compare transaction totals between two periods, e.g. for a bar chart of
+ * this quarter vs last.
+ *
+ * @param current - "2025", "2025-Q2", "2025-03", "2025-03-14" or "START..END"
+ * @param previous - Same formats; defaults to the period just before `current`
+ * @param groupBy - Group by category (first tag) or account
+ */
+export async function comparePeriods(
+  current: string,
+  previous: string | null = null,
+  groupBy: "category" | "account" = "category"
+): Promise<PeriodComparison> {
+  const jsonString = await commands.comparePeriods(current, previous, groupBy);
+  return JSON.parse(jsonString) as PeriodComparison;
+}
+
+export interface FlowNode {
+  /** Unique within the diagram, e.g. "category:groceries" */
+  id: string;
+  label: string;
+  kind: "source" | "income" | "category" | "merchant" | "saved" | "shortfall";
+  /** Decimal amount as a string, everything flowing through the node */
+  value: string;
+}
+
+export interface FlowLink {
+  /** Node IDs */
+  source: string;
+  target: string;
+  /** Decimal amount as a string, always positive */
+  value: string;
+}
+
+export interface CashFlows {
+  period: ReportPeriod;
+  total_income: string;
+  /** Positive */
+  total_spending: string;
+  /** Sources, the income node, categories (largest first) and their merchants */
+  nodes: FlowNode[];
+  links: FlowLink[];
+}
+
+/**
+ * Where a period's money came from and went, as nodes and links for a
+ * Sankey diagram. Transfers and credit card payments are left out; leftover
+ * income flows to a "saved" node, overspending comes from a "shortfall" one.
+ *
+ * @param period - "2025", "2025-Q2", "2025-03", "2025-03-14" or "START..END"
+ */
+export async function getCashFlows(period: string): Promise<CashFlows> {
+  const jsonString = await commands.getCashFlows(period);
+  return JSON.parse(jsonString) as CashFlows;
+}
+
+// ============================================================================
+// Long-running Operations
+// ============================================================================
+
+export interface OperationStatus {
+  id: string;
+  kind: "sync" | "import" | "backfill" | "backup" | "restore" | "compact" | "refresh";
+  state: "running" | "completed" | "failed" | "cancelled";
+  /** Fraction done (0 to 1), null when the operation can't tell */
+  progress: number | null;
+  /** Current step, e.g. "Creating backup" */
+  message: string | null;
+  started_at: string;
+  finished_at: string | null;
+  error: string | null;
+  cancel_requested: boolean;
+  /** Set for work that never runs twice at once, e.g. "sync" or "backup" */
+  key: string | null;
+  /** Duplicate requests (a double-click) that waited for this one instead */
+  joined: number;
+}
+
+/**
+ * List running and recently finished operations, newest first.
+ * Poll this to show background work (sync, import, backup, ...).
+ */
+export async function listOperations(): Promise<OperationStatus[]> {
+  const jsonString = await commands.listOperations();
+  return JSON.parse(jsonString) as OperationStatus[];
+}
+
+/**
+ * Get one operation's status, or null if it is unknown
+ */
+export async function getOperationStatus(operationId: string): Promise<OperationStatus | null> {
+  const jsonString = await commands.getOperationStatus(operationId);
+  return jsonString === null ? null : (JSON.parse(jsonString) as OperationStatus);
+}
+
+/**
+ * Ask a running operation to stop. Cancellation is cooperative: the operation
+ * stops at its next checkpoint, and single-step operations finish normally.
+ * @returns false if the operation already finished
+ */
+export async function cancelOperation(operationId: string): Promise<boolean> {
+  return commands.cancelOperation(operationId);
+}
+
+/** A card with a payment due soon, from its statement cycle */
+export interface PaymentDue {
+  account_id: string;
+  account_name: string;
+  currency: string;
+  /** Left to pay on the last statement, as a decimal string */
+  payment_due: string | null;
+  due_date: string;
+  days_until_due: number;
+}
+
+export interface RefreshResult {
+  /** Reports computed into the cache */
+  reports: number;
+  /** Cards with something left to pay, due within 3 days or overdue */
+  payments_due: PaymentDue[];
+  /** Times it started over because data changed while it ran */
+  restarts: number;
+  duration_ms: number;
+}
+
+/**
+ * Call `handler` each time derived state (cached reports, payments due) has
+ * been recomputed in the background after a sync or import. Reports opened
+ * after this are served from the cache.
+ * @returns a function that stops listening
+ */
+export async function onDataRefreshed(
+  handler: (result: RefreshResult) => void
+): Promise<() => void> {
+  return listen<RefreshResult>("data-refreshed", (event) => handler(event.payload));
+}
@@ -5,7 +5,7 @@
  * Users can add custom themes by dropping JSON files in that directory.
  */
 
-import { invoke } from "@tauri-apps/api/core";
+import { commands, type ThemeDefinition } from "./bindings";
 import type { ThemeInterface } from "./types";
 import { getAppSetting, setAppSetting } from "./settings";
 
@@ -13,12 +13,7 @@ import { getAppSetting, setAppSetting } from "./settings";
 // Theme Definitions
 // ============================================================================
 
-export interface ThemeDefinition {
-  id: string;
-  name: string;
-  extends?: string;
-  variables: Record<string, string>;
-}
+export type { ThemeDefinition };
 
 // Fallback theme variables in case themes can't be loaded
 const FALLBACK_VARIABLES: Record<string, string> = {
@@ -160,7 +155,7 @@ class ThemeManager implements ThemeInterface {
 
     try {
       // Load themes from Tauri backend
-      const themeList = await invoke<ThemeDefinition[]>("list_themes");
+      const themeList = await commands.listThemes();
       this.themes = new Map(themeList.map((t) => [t.id, t]));
     } catch (err) {
       console.warn("Failed to load themes from backend, using fallback:", err);
@@ -168,6 +163,7 @@ class ThemeManager implements ThemeInterface {
       this.themes.set("dark", {
         id: "dark",
         name: "Dark",
+        extends: null,
         variables: FALLBACK_VARIABLES,
       });
     }
@@ -220,7 +216,7 @@ class ThemeManager implements ThemeInterface {
    */
   async reloadThemes(): Promise<void> {
     try {
-      const themeList = await invoke<ThemeDefinition[]>("list_themes");
+      const themeList = await commands.listThemes();
       this.themes = new Map(themeList.map((t) => [t.id, t]));
 
       // Re-apply current theme in case it was updated
@@ -8,16 +8,9 @@
  * `~/.treeline/use-staging-updates` file for testing release candidates.
  */
 
-import { invoke } from "@tauri-apps/api/core";
+import { commands } from "./bindings";
 import { relaunch, exit } from "@tauri-apps/plugin-process";
-import { getAppSetting, setAppSetting } from "./settings";
-
-/** Update info returned from Rust check_for_app_update command */
-interface AppUpdateInfo {
-  version: string;
-  body: string | null;
-  date: string | null;
-}
+import { getAppSetting, getStartupReport, setAppSetting } from "./settings";
 
 /** Simplified Update type matching what we need from the original */
 interface Update {
@@ -102,7 +95,7 @@ export async function checkForUpdate(force = false): Promise<Update | null> {
 
   try {
     console.log("Checking for updates...");
-    const update = await invoke<AppUpdateInfo | null>("check_for_app_update");
+    const update = await commands.checkForAppUpdate();
     console.log("Update check result:", update ? `v${update.version} available` : "no update");
     availableUpdate = update;
 
@@ -158,7 +151,7 @@ export async function downloadAndInstall(): Promise<void> {
   try {
     // Call Rust command to download and install
     // The Update is stored in Rust state from the check call
-    await invoke("download_and_install_app_update");
+    await commands.downloadAndInstallAppUpdate();
 
     downloadProgress = 100;
     isDownloading = false;
@@ -243,17 +236,16 @@ export function stopPeriodicChecks(): void {
  * Should be called once on app startup
  */
 export async function initUpdater(): Promise<void> {
-  // Check for updates on startup
-  const autoUpdate = await getAppSetting("autoUpdate");
-
-  // Always check on startup (if auto-update is enabled)
-  if (autoUpdate) {
-    try {
-      await checkForUpdate();
-    } catch (error) {
-      // Don't fail app startup on update check error
-      console.error("Update check on startup failed:", error);
+  // The startup check runs in the backend (if auto-update is enabled)
+  try {
+    const report = await getStartupReport();
+    if (report.update) {
+      availableUpdate = report.update;
+      notifySubscribers();
     }
+  } catch (error) {
+    // Don't fail app startup on update check error
+    console.error("Update check on startup failed:", error);
   }
 
   // Start periodic checks
@@ -34,6 +34,85 @@ export const SUPPORTED_CURRENCIES: Record<
 
 export const DEFAULT_CURRENCY = "USD";
 
+// ISO 4217 currencies whose minor unit isn't hundredths. Mirrors
+// `minor_units` in treeline-core's money module.
+const MINOR_UNITS: Record<string, number> = {
+  BIF: 0, CLP: 0, DJF: 0, GNF: 0, ISK: 0, JPY: 0, KMF: 0, KRW: 0,
+  PYG: 0, RWF: 0, UGX: 0, VND: 0, VUV: 0, XAF: 0, XOF: 0, XPF: 0,
+  BHD: 3, IQD: 3, JOD: 3, KWD: 3, LYD: 3, OMR: 3, TND: 3,
+};
+
+/**
+ * Decimal places of a currency's minor unit: 0 for JPY, 3 for BHD, otherwise 2.
+ */
+export function getMinorUnits(currency: string): number {
+  return MINOR_UNITS[currency.trim().toUpperCase()] ?? 2;
+}
+
+const DECIMAL_PATTERN = /^(-?)(\d+)(?:\.(\d+))?$/;
+
+/**
+ * Amount as a plain decimal string, the form the backend takes amounts in.
+ * Strings are checked and passed through so they never go through a float.
+ */
+export function toAmountString(amount: number | string): string {
+  if (typeof amount === "string") {
+    const trimmed = amount.trim();
+    if (!DECIMAL_PATTERN.test(trimmed)) {
+      throw new Error(`Invalid amount '${trimmed}'`);
+    }
+    return trimmed;
+  }
+  if (!Number.isFinite(amount)) {
+    throw new Error(`Invalid amount '${amount}'`);
+  }
+  const plain = String(amount);
+  // Very large and very small numbers stringify with an exponent
+  return plain.includes("e") ? amount.toFixed(20).replace(/\.?0+$/, "") : plain;
+}
+
+/**
+ * Parse a decimal amount string from the backend into a number for display
+ * and charts. Keep the string for anything sent back.
+ */
+export function parseAmount(amount: string | number): number {
+  return typeof amount === "number" ? amount : Number(amount);
+}
+
+/**
+ * Convert a decimal amount to whole minor units (cents, yen, fils), rounding
+ * halves away from zero, without going through a float.
+ */
+export function toMinorUnits(amount: number | string, currency: string): bigint {
+  const match = DECIMAL_PATTERN.exec(toAmountString(amount));
+  if (!match) {
+    throw new Error(`Invalid amount '${amount}'`);
+  }
+  const [, sign, whole, fraction = ""] = match;
+  const digits = getMinorUnits(currency);
+  const padded = fraction.padEnd(digits + 1, "0");
+  let minor = BigInt(whole + padded.slice(0, digits));
+  if (Number(padded[digits]) >= 5) {
+    minor += 1n;
+  }
+  return sign === "-" ? -minor : minor;
+}
+
+/**
+ * Decimal amount string for whole minor units, e.g. 1234n JPY is "1234",
+ * 1234n USD "12.34" and 1234n BHD "1.234".
+ */
+export function fromMinorUnits(minor: bigint | number, currency: string): string {
+  const value = BigInt(minor);
+  const digits = getMinorUnits(currency);
+  const sign = value < 0n ? "-" : "";
+  const abs = (value < 0n ? -value : value).toString().padStart(digits + 1, "0");
+  if (digits === 0) {
+    return sign + abs;
+  }
+  return `${sign}${abs.slice(0, -digits)}.${abs.slice(-digits)}`;
+}
+
 /**
  * Get the symbol for a currency code.
  */
@@ -51,7 +130,8 @@ export function getCurrencyLocale(currency: string): string {
 }
 
 /**
- * Format an amount in the specified currency.
+ * Format an amount in the specified currency, with the currency's decimal
+ * places unless the options say otherwise.
  */
 export function formatCurrency(
   amount: number,
@@ -61,7 +141,8 @@ export function formatCurrency(
     maximumFractionDigits?: number;
   } = {}
 ): string {
-  const { minimumFractionDigits = 2, maximumFractionDigits = 2 } = options;
+  const digits = getMinorUnits(currency);
+  const { minimumFractionDigits = digits, maximumFractionDigits = digits } = options;
   const locale = getCurrencyLocale(currency);
 
   return new Intl.NumberFormat(locale, {
@@ -114,12 +195,12 @@ export function formatAmount(
 }
 
 /**
- * Format cents (integer) to currency display.
+ * Format whole minor units (cents, or yen for JPY) to currency display.
  * Useful when storing amounts as integers.
  */
 export function formatCurrencyCents(
   cents: number,
   currency: string = DEFAULT_CURRENCY
 ): string {
-  return formatCurrency(cents / 100, currency);
+  return formatCurrency(parseAmount(fromMinorUnits(cents, currency)), currency);
 }
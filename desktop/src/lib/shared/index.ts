@@ -24,6 +24,11 @@ export {
   formatCurrencyCompact,
   formatAmount,
   formatCurrencyCents,
+  getMinorUnits,
+  toAmountString,
+  parseAmount,
+  toMinorUnits,
+  fromMinorUnits,
 } from "./currency";
 
 // Currency store (reactive)
@@ -0,0 +1,27 @@
+"""Read-only access to your Treeline finance data.
+
+    import treeline
+
+    tl = treeline.connect()                      # same data directory as `tl`
+    tl = treeline.connect(token="tlt_...")       # encrypted database, see `tl token issue`
+    df = treeline.to_dataframe(tl.query("SELECT * FROM transactions"))
+
+Reads go through treeline-core and take the same database lock as the
+desktop app and CLI, so notebooks can run while the app is open.
+"""
+
+from ._treeline import Treeline
+
+__all__ = ["Treeline", "connect", "to_dataframe"]
+
+
+def connect(treeline_dir=None, *, token=None, password=None):
+    """Open a read-only handle to a Treeline database."""
+    return Treeline(treeline_dir, token=token, password=password)
+
+
+def to_dataframe(result):
+    """Turn a ``Treeline.query`` result into a pandas DataFrame."""
+    import pandas as pd
+
+    return pd.DataFrame(result["rows"], columns=result["columns"])
@@ -0,0 +1,20 @@
+from os import PathLike
+from typing import Any, Optional
+
+class Treeline:
+    def __init__(
+        self,
+        treeline_dir: Optional[str | PathLike[str]] = None,
+        *,
+        token: Optional[str] = None,
+        password: Optional[str] = None,
+    ) -> None: ...
+    def query(self, sql: str) -> dict[str, Any]: ...
+    def query_records(self, sql: str) -> list[dict[str, Any]]: ...
+    def status(self) -> dict[str, Any]: ...
+    def accounts(self) -> list[dict[str, Any]]: ...
+    def transactions(
+        self, start: Optional[str] = None, end: Optional[str] = None
+    ) -> list[dict[str, Any]]: ...
+    def export_csv(self, sql: str, path: str | PathLike[str]) -> int: ...
+    def export_json(self, sql: str, path: str | PathLike[str]) -> int: ...
@@ -0,0 +1,257 @@
+//! Python bindings for treeline-core
+//!
+//! Gives notebooks read-only access through the same repository the app and
+//! CLI use, so reads take the shared database lock instead of racing the app
+//! for the DuckDB file. The database is opened read-only and never migrated,
+//! so it must have been opened by the app or CLI since their last update.
+//! The only write is the use count an access token records in tokens.json.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use chrono::NaiveDate;
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use pythonize::pythonize;
+use treeline_core::config::Config;
+use treeline_core::services::{
+    portable_data_dir, resolve_data_dir, EncryptionService, TokenService,
+};
+use treeline_core::{EncryptionKey, QueryResult, TreelineContext};
+
+fn to_py_err(e: anyhow::Error) -> PyErr {
+    PyRuntimeError::new_err(format!("{:#}", e))
+}
+
+/// The data directory, resolved like the CLI's
+///
+/// `TREELINE_DIR` wins, then portable mode's `data` folder, then
+/// `~/.treeline`. Follows the pointer left by `tl move-data`, if any.
+fn default_treeline_dir() -> PyResult<PathBuf> {
+    let dir = if let Ok(dir) = std::env::var("TREELINE_DIR") {
+        PathBuf::from(dir)
+    } else if let Some(dir) = portable_data_dir() {
+        dir
+    } else {
+        dirs::home_dir()
+            .map(|home| home.join(".treeline"))
+            .ok_or_else(|| PyRuntimeError::new_err("Could not find home directory"))?
+    };
+    Ok(resolve_data_dir(dir))
+}
+
+/// Key for an encrypted database, from an access token or the password
+///
+/// Same sources and order as the CLI: TL_DB_PASSWORD / `password`, then
+/// TL_TOKEN / `token`.
+fn resolve_key(
+    treeline_dir: &Path,
+    token: Option<String>,
+    password: Option<String>,
+) -> anyhow::Result<Option<EncryptionKey>> {
+    let config = Config::load(treeline_dir)?;
+    if config.demo_mode {
+        return Ok(None);
+    }
+
+    let encryption_service = EncryptionService::new(
+        treeline_dir.to_path_buf(),
+        treeline_dir.join("treeline.duckdb"),
+    );
+
+    let password = password.or_else(|| std::env::var("TL_DB_PASSWORD").ok());
+    let token = token.or_else(|| std::env::var("TL_TOKEN").ok());
+
+    if let Some(password) = password {
+        if encryption_service.is_encrypted()? {
+            return encryption_service
+                .derive_key_for_connection(&password)
+                .map(Some)
+                .context("Failed to derive encryption key from password");
+        }
+        return Ok(None);
+    }
+
+    if let Some(token) = token {
+        let grant = TokenService::new(treeline_dir.to_path_buf())
+            .authenticate(&token)
+            .context("Token was rejected")?;
+        return Ok(grant.encryption_key);
+    }
+
+    if encryption_service.is_encrypted()? {
+        anyhow::bail!("Database is encrypted: pass token= (see `tl token issue`) or password=");
+    }
+    Ok(None)
+}
+
+/// Read-only handle to a Treeline database
+#[pyclass(module = "treeline")]
+struct Treeline {
+    ctx: TreelineContext,
+}
+
+#[pymethods]
+impl Treeline {
+    #[new]
+    #[pyo3(signature = (treeline_dir=None, *, token=None, password=None))]
+    fn new(
+        treeline_dir: Option<PathBuf>,
+        token: Option<String>,
+        password: Option<String>,
+    ) -> PyResult<Self> {
+        let treeline_dir = match treeline_dir {
+            Some(dir) => resolve_data_dir(dir),
+            None => default_treeline_dir()?,
+        };
+        let key = resolve_key(&treeline_dir, token, password).map_err(to_py_err)?;
+        let password = key.as_ref().map(EncryptionKey::expose);
+        let ctx = TreelineContext::open_read_only(&treeline_dir, password)
+            .context("Failed to open Treeline database")
+            .map_err(to_py_err)?;
+        Ok(Self { ctx })
+    }
+
+    /// Run a read-only SQL query; returns {"columns", "rows", "row_count"}
+    fn query(&self, py: Python<'_>, sql: &str) -> PyResult<PyObject> {
+        let result = self.run_query(sql)?;
+        Ok(pythonize(py, &result)?.unbind())
+    }
+
+    /// Run a read-only SQL query; returns one dict per row
+    fn query_records(&self, py: Python<'_>, sql: &str) -> PyResult<PyObject> {
+        let result = self.run_query(sql)?;
+        Ok(pythonize(py, &to_records(&result))?.unbind())
+    }
+
+    /// Account, transaction and integration counts, like `tl status --json`
+    fn status(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let status = self.ctx.status_service.get_status().map_err(to_py_err)?;
+        Ok(pythonize(py, &status)?.unbind())
+    }
+
+    /// All accounts as dicts
+    fn accounts(&self, py: Python<'_>) -> PyResult<PyObject> {
+        self.query_records(py, "SELECT * FROM accounts ORDER BY name")
+    }
+
+    /// Transactions as dicts, optionally limited to a date range (YYYY-MM-DD, inclusive)
+    #[pyo3(signature = (start=None, end=None))]
+    fn transactions(
+        &self,
+        py: Python<'_>,
+        start: Option<&str>,
+        end: Option<&str>,
+    ) -> PyResult<PyObject> {
+        let mut conditions = Vec::new();
+        if let Some(start) = start {
+            conditions.push(format!("transaction_date >= '{}'", parse_date(start)?));
+        }
+        if let Some(end) = end {
+            conditions.push(format!("transaction_date <= '{}'", parse_date(end)?));
+        }
+        let filter = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", conditions.join(" AND "))
+        };
+        self.query_records(
+            py,
+            &format!("SELECT * FROM transactions{filter} ORDER BY transaction_date DESC"),
+        )
+    }
+
+    /// Write the results of a read-only query to a CSV file; returns the row count
+    fn export_csv(&self, sql: &str, path: PathBuf) -> PyResult<usize> {
+        let result = self.run_query(sql)?;
+        write_csv(&result, &path).map_err(to_py_err)?;
+        Ok(result.row_count)
+    }
+
+    /// Write the results of a read-only query to a JSON file of records; returns the row count
+    fn export_json(&self, sql: &str, path: PathBuf) -> PyResult<usize> {
+        let result = self.run_query(sql)?;
+        let content = serde_json::to_string_pretty(&to_records(&result))
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write {}", path.display()))
+            .map_err(to_py_err)?;
+        Ok(result.row_count)
+    }
+}
+
+impl Treeline {
+    fn run_query(&self, sql: &str) -> PyResult<QueryResult> {
+        self.ctx
+            .query_service
+            .execute_readonly(sql)
+            .map_err(to_py_err)
+    }
+}
+
+/// One JSON object per row, keyed by column name
+fn to_records(result: &QueryResult) -> Vec<serde_json::Map<String, serde_json::Value>> {
+    result
+        .rows
+        .iter()
+        .map(|row| result.columns.iter().cloned().zip(row.clone()).collect())
+        .collect()
+}
+
+/// Validate a date argument before it is put into SQL
+fn parse_date(value: &str) -> PyResult<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| PyRuntimeError::new_err(format!("Invalid date '{}': use YYYY-MM-DD", value)))
+}
+
+fn write_csv(result: &QueryResult, path: &Path) -> anyhow::Result<()> {
+    let mut writer = csv::Writer::from_path(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    writer.write_record(&result.columns)?;
+    for row in &result.rows {
+        writer.write_record(row.iter().map(|v| match v {
+            serde_json::Value::Null => String::new(),
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        }))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[pymodule]
+fn _treeline(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Treeline>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use treeline_core::services::DataDirService;
+    use treeline_core::Account;
+
+    #[test]
+    fn test_opens_moved_data() {
+        let home = tempfile::tempdir().unwrap();
+        let old_dir = home.path().join(".treeline");
+        let ctx = TreelineContext::new(&old_dir, None).unwrap();
+        ctx.repository
+            .upsert_account(&Account::new(uuid::Uuid::new_v4(), "Checking"))
+            .unwrap();
+        drop(ctx);
+        DataDirService::new(old_dir.clone())
+            .move_to(&home.path().join("moved"), false)
+            .unwrap();
+
+        pyo3::prepare_freethreaded_python();
+        std::env::set_var("TREELINE_DIR", &old_dir);
+        Python::with_gil(|py| {
+            let treeline = Bound::new(py, Treeline::new(None, None, None).unwrap()).unwrap();
+            let accounts = treeline.call_method0("accounts").unwrap();
+            assert_eq!(accounts.len().unwrap(), 1);
+        });
+        // Nothing was created in the emptied directory
+        assert!(!old_dir.join("treeline.duckdb").exists());
+    }
+}
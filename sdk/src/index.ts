@@ -257,6 +257,17 @@ export interface PluginSDK {
     read: <T>() => Promise<T | null>;
     /** Write plugin state */
     write: <T>(state: T) => Promise<void>;
+    /**
+     * Read state saved with `writeVersioned`, upgrading it to `version` first.
+     * `migrations[n]` turns version n-1 state into version n; state from
+     * `write` is version 0. Upgraded state is saved once.
+     */
+    readVersioned: <T>(
+      version: number,
+      migrations?: Record<number, (state: any) => any>
+    ) => Promise<T | null>;
+    /** Write plugin state at a schema version */
+    writeVersioned: <T>(version: number, state: T) => Promise<void>;
   };
 
   /**